@@ -0,0 +1,48 @@
+//! Sender recovery and checks of the transactions of a block, before they
+//! are executed. Compare runs with and without `--features parallel`.
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use execution_specs_rs::ethereum::{
+    base_types::U256,
+    frontier::{
+        fork::{prevalidate_transactions, signing_hash},
+        fork_types::Transaction,
+    },
+};
+use k256::ecdsa::SigningKey;
+
+fn transactions(count: usize) -> Vec<Transaction> {
+    let key = SigningKey::from_slice(&[0x45; 32]).unwrap();
+    (0..count)
+        .map(|i| {
+            let mut tx = Transaction {
+                nonce: U256::from(i),
+                gas_price: U256::from(50_000_000_000u64),
+                gas: U256::from(90_000u32),
+                to: Some([0xcc; 20]),
+                value: U256::from(10u8).pow(18),
+                data: vec![i as u8; 100].into(),
+                v: U256::from(0u8),
+                r: U256::from(0u8),
+                s: U256::from(0u8),
+            };
+            let (signature, recovery_id) =
+                key.sign_prehash_recoverable(&signing_hash(&tx)).unwrap();
+            let (r, s) = signature.split_bytes();
+            tx.v = U256::from(27 + recovery_id.to_byte());
+            tx.r = U256::from_bytes_be(&r);
+            tx.s = U256::from_bytes_be(&s);
+            tx
+        })
+        .collect()
+}
+
+fn prevalidation(c: &mut Criterion) {
+    let transactions = transactions(300);
+    c.bench_function("prevalidate block of 300 transactions", |b| {
+        b.iter(|| prevalidate_transactions(&transactions).unwrap())
+    });
+}
+
+criterion_group!(benches, prevalidation);
+criterion_main!(benches);
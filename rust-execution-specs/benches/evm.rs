@@ -0,0 +1,64 @@
+//! Interpreter throughput on small bytecode kernels, run through
+//! `evm_tools::run` the way `evm run` runs them.
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use execution_specs_rs::{
+    ethereum::base_types::Bytes,
+    ethereum_spec_tools::evm_tools::run::{run, RunConfig},
+};
+
+/// Iterations of each kernel loop.
+const ITERATIONS: u16 = 1000;
+
+/// Wrap `body` in a loop running it `ITERATIONS` times. The body must leave
+/// the stack as it found it.
+fn kernel(body: &[u8]) -> Bytes {
+    let [high, low] = ITERATIONS.to_be_bytes();
+    // PUSH2 ITERATIONS JUMPDEST
+    let mut code = vec![0x61, high, low, 0x5b];
+    code.extend(body);
+    // PUSH1 1 SWAP1 SUB DUP1 PUSH1 3 JUMPI STOP
+    code.extend([0x60, 0x01, 0x90, 0x03, 0x80, 0x60, 0x03, 0x57, 0x00]);
+    code.into()
+}
+
+fn bench_kernel(c: &mut Criterion, name: &str, body: &[u8], input: &[u8]) {
+    let code = kernel(body);
+    c.bench_function(name, |b| {
+        b.iter(|| {
+            let output = run(
+                RunConfig {
+                    code: code.clone(),
+                    input: input.into(),
+                    ..Default::default()
+                },
+                None,
+            );
+            assert_eq!(output.error, None);
+            output
+        })
+    });
+}
+
+fn interpreter(c: &mut Criterion) {
+    // MSTORE(0, SHA3(0, 32))
+    bench_kernel(c, "keccak loop", &[0x60, 0x20, 0x60, 0x00, 0x20, 0x60, 0x00, 0x52], &[]);
+    // CALLDATACOPY(0, 0, CALLDATASIZE) of 1 KiB
+    bench_kernel(c, "memory copy", &[0x36, 0x60, 0x00, 0x60, 0x00, 0x37], &[0xab; 1024]);
+    // SSTORE(counter % 16, counter): sets, resets and overwrites of a few slots
+    bench_kernel(c, "sstore churn", &[0x80, 0x60, 0x10, 0x81, 0x06, 0x55], &[]);
+    // POP(CALL(0xffff, IDENTITY, 0, 0, 1024, 1024, 1024)): 1 KiB of memory
+    // copied through the identity precompile
+    bench_kernel(
+        c,
+        "identity copy",
+        &[
+            0x61, 0x04, 0x00, 0x61, 0x04, 0x00, 0x61, 0x04, 0x00, 0x60, 0x00, 0x60, 0x00, 0x60, 0x04, 0x61, 0xff, 0xff,
+            0xf1, 0x50,
+        ],
+        &[],
+    );
+}
+
+criterion_group!(benches, interpreter);
+criterion_main!(benches);
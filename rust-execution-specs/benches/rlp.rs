@@ -0,0 +1,56 @@
+//! RLP encoding of a full block.
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use execution_specs_rs::ethereum::{
+    base_types::{Uint, U256},
+    frontier::fork_types::{Block, Header, Transaction},
+    rlp,
+};
+
+fn block(transactions: usize) -> Block {
+    let header = Header {
+        parent_hash: [1; 32],
+        ommers_hash: [2; 32],
+        coinbase: [3; 20],
+        state_root: [4; 32],
+        transactions_root: [5; 32],
+        receipt_root: [6; 32],
+        bloom: [7; 256],
+        difficulty: Uint::from(0x400000000u64),
+        number: Uint::from(1_000_000u32),
+        gas_limit: Uint::from(3_141_592u32),
+        gas_used: Uint::from(3_000_000u32),
+        timestamp: U256::from(1_438_269_988u32),
+        extra_data: vec![8; 32].into(),
+        mix_digest: [9; 32],
+        nonce: [10; 8],
+    };
+    let transactions = (0..transactions)
+        .map(|i| Transaction {
+            nonce: U256::from(i),
+            gas_price: U256::from(50_000_000_000u64),
+            gas: U256::from(90_000u32),
+            to: Some([0xcc; 20]),
+            value: U256::from(10u8).pow(18),
+            data: vec![i as u8; 100].into(),
+            v: U256::from(27u8),
+            r: U256::from_bytes_be(&[0x11; 32]),
+            s: U256::from_bytes_be(&[0x22; 32]),
+        })
+        .collect();
+    Block {
+        header: header.clone(),
+        transactions,
+        ommers: vec![header],
+    }
+}
+
+fn block_encoding(c: &mut Criterion) {
+    let block = block(300);
+    c.bench_function("encode block of 300 transactions", |b| {
+        b.iter(|| rlp::encode(&block))
+    });
+}
+
+criterion_group!(benches, block_encoding);
+criterion_main!(benches);
@@ -0,0 +1,44 @@
+//! Root computation of secured storage tries, and the hashing of their keys.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use execution_specs_rs::ethereum::{
+    base_types::{u256_to_be_bytes32, U256},
+    crypto::hash::{keccak256, keccak256_batch},
+    frontier::trie::{dummy_root, root, trie_set, Trie},
+};
+
+fn storage_trie(size: u32) -> Trie<[u8; 32], U256> {
+    let mut trie = Trie::new(true, U256::default());
+    for i in 1..=size {
+        let key = u256_to_be_bytes32(&U256::from(i));
+        trie_set(&mut trie, key, U256::from(i) * 0x1234_5678u32);
+    }
+    trie
+}
+
+fn trie_root(c: &mut Criterion) {
+    let mut group = c.benchmark_group("trie root");
+    group.sample_size(10);
+    for size in [10_000, 1_000_000] {
+        let trie = storage_trie(size);
+        group.bench_with_input(BenchmarkId::from_parameter(size), &trie, |b, trie| {
+            b.iter(|| root(trie, dummy_root))
+        });
+    }
+    group.finish();
+}
+
+fn key_hashing(c: &mut Criterion) {
+    let keys: Vec<[u8; 32]> = (0..100_000u32)
+        .map(|i| u256_to_be_bytes32(&U256::from(i)))
+        .collect();
+    let mut group = c.benchmark_group("hash 100000 trie keys");
+    group.bench_function("one by one", |b| {
+        b.iter(|| keys.iter().map(|key| keccak256(key)).collect::<Vec<_>>())
+    });
+    group.bench_function("batch", |b| b.iter(|| keccak256_batch(&keys)));
+    group.finish();
+}
+
+criterion_group!(benches, trie_root, key_hashing);
+criterion_main!(benches);
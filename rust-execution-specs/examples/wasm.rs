@@ -0,0 +1,129 @@
+//!
+//! # WebAssembly Bindings
+//!
+//! A small JavaScript API over the spec EVM, for running bytecode in a
+//! browser, such as in an educational visualizer. Build it without the file
+//! system and command line features:
+//!
+//! ```text
+//! cargo build --example wasm --target wasm32-unknown-unknown \
+//!     --no-default-features --features wasm,frontier --release
+//! wasm-bindgen --target web --out-dir pkg \
+//!     target/wasm32-unknown-unknown/release/examples/wasm.wasm
+//! ```
+//!
+//! and use it from JavaScript:
+//!
+//! ```text
+//! import init, { run, disassemble } from "./pkg/wasm.js";
+//!
+//! await init();
+//! const result = JSON.parse(run("0x6001600201", "0x", 100000n, true));
+//! console.log(result.gasUsed, result.trace);
+//! console.log(disassemble("0x6001600201"));
+//! ```
+//!
+
+use std::{cell::RefCell, io, rc::Rc};
+
+use execution_specs_rs::{
+    ethereum::{
+        base_types::Uint,
+        trace::Tracer,
+        utils::hexadecimal::{hex, hex_to_bytes},
+    },
+    ethereum_spec_tools::{
+        evm_tools::{
+            disasm::disassemble_to_string,
+            eip3155::Eip3155Tracer,
+            run::{run as run_code, RunConfig},
+        },
+        forks::HardFork,
+    },
+};
+use serde_json::{json, Value};
+use wasm_bindgen::prelude::wasm_bindgen;
+
+/// Collects the lines written by the tracer, as there is no stderr.
+#[derive(Clone, Default)]
+struct SharedBuffer(Rc<RefCell<Vec<u8>>>);
+
+impl io::Write for SharedBuffer {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.borrow_mut().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+///
+///     Run bytecode as a message call against an empty state.
+///
+///     Parameters
+///     ----------
+///     code :
+///         The bytecode, in hex.
+///     input :
+///         The call data, in hex.
+///     gas :
+///         The gas available to the execution.
+///     trace :
+///         Whether to include the EIP-3155 trace of every step.
+///
+///     Returns
+///     -------
+///     result : `string`
+///         JSON with the `output`, `gasUsed` and `error` of the execution,
+///         and its `trace` if asked for.
+///
+#[wasm_bindgen]
+pub fn run(code: &str, input: &str, gas: u64, trace: bool) -> Result<String, String> {
+    let code = hex_to_bytes(code).map_err(|_| format!("invalid code `{code}`"))?;
+    let input = hex_to_bytes(input).map_err(|_| format!("invalid input `{input}`"))?;
+
+    let buffer = SharedBuffer::default();
+    let tracer: Option<Box<dyn Tracer<_>>> = if trace {
+        Some(Box::new(Eip3155Tracer::new(Box::new(buffer.clone()))))
+    } else {
+        None
+    };
+    let config = RunConfig {
+        code,
+        input,
+        gas: Uint::from(gas),
+        ..Default::default()
+    };
+    let output = run_code(config, tracer);
+
+    let mut result = json!({
+        "output": hex(&output.output),
+        "gasUsed": output.gas_used.to_string(),
+        "error": output.error.map(|error| error.to_string()),
+    });
+    if trace {
+        let lines = String::from_utf8_lossy(&buffer.0.borrow()).into_owned();
+        let steps: Vec<Value> = lines
+            .lines()
+            .filter_map(|line| serde_json::from_str(line).ok())
+            .collect();
+        result["trace"] = Value::Array(steps);
+    }
+    Ok(result.to_string())
+}
+
+///
+///     Print the instructions of some bytecode, one per line.
+///
+///     Parameters
+///     ----------
+///     code :
+///         The bytecode, in hex.
+///
+#[wasm_bindgen]
+pub fn disassemble(code: &str) -> Result<String, String> {
+    let code = hex_to_bytes(code).map_err(|_| format!("invalid code `{code}`"))?;
+    Ok(disassemble_to_string(&HardFork::Frontier, &code))
+}
@@ -0,0 +1,141 @@
+"""
+Bridge between the Rust fuzz targets and the Python execution-specs.
+
+Reads one JSON request per line from stdin and answers each with one JSON
+line on stdout, either `{"result": ...}` or `{"error": ...}`.
+"""
+
+import dataclasses
+import json
+import os
+import sys
+
+SPEC_SRC = os.environ.get(
+    "EXECUTION_SPECS_SRC",
+    os.path.join(os.path.dirname(__file__), "..", "..", "..", "execution-specs", "src"),
+)
+sys.path.insert(0, os.path.abspath(SPEC_SRC))
+
+from ethereum import rlp  # noqa: E402
+from ethereum.base_types import U256, Bytes0, Bytes20, Bytes32, Uint  # noqa: E402
+from ethereum.exceptions import InvalidBlock  # noqa: E402
+from ethereum.frontier import fork, state, vm  # noqa: E402
+from ethereum.frontier.fork_types import Account, Transaction  # noqa: E402
+from ethereum.frontier.trie import Trie, root, trie_set  # noqa: E402
+
+
+def from_hex(value):
+    return bytes.fromhex(value[2:])
+
+
+def to_hex(value):
+    return "0x" + value.hex()
+
+
+def rlp_item(value):
+    if isinstance(value, list):
+        return [rlp_item(item) for item in value]
+    return from_hex(value)
+
+
+def handle_rlp(request):
+    return to_hex(rlp.encode(rlp_item(request["item"])))
+
+
+def handle_trie(request):
+    trie = Trie(secured=request["secured"], default=b"")
+    for key, value in request["items"]:
+        trie_set(trie, from_hex(key), from_hex(value))
+    return to_hex(root(trie))
+
+
+def build_state(accounts):
+    pre_state = state.State()
+    for account in accounts:
+        address = Bytes20(from_hex(account["address"]))
+        state.set_account(
+            pre_state,
+            address,
+            Account(
+                nonce=Uint(account["nonce"]),
+                balance=U256(int(account["balance"], 16)),
+                code=from_hex(account["code"]),
+            ),
+        )
+        for key, value in account["storage"]:
+            state.set_storage(
+                pre_state, address, Bytes32(from_hex(key)), U256(int(value, 16))
+            )
+    return pre_state
+
+
+def handle_state(request):
+    return to_hex(state.state_root(build_state(request["accounts"])))
+
+
+def handle_transaction(request):
+    pre_state = build_state(request["accounts"])
+    sender = Bytes20(from_hex(request["sender"]))
+    # The sender is `caller` in older releases of the spec and `origin` in
+    # newer ones, which also record `traces`.
+    values = {
+        "caller": sender,
+        "origin": sender,
+        "block_hashes": [],
+        "coinbase": Bytes20(from_hex(request["coinbase"])),
+        "number": Uint(request["number"]),
+        "gas_limit": Uint(request["gas_limit"]),
+        "gas_price": U256(request["gas_price"]),
+        "time": U256(request["time"]),
+        "difficulty": Uint(request["difficulty"]),
+        "state": pre_state,
+        "traces": [],
+    }
+    env = vm.Environment(
+        **{
+            field.name: values[field.name]
+            for field in dataclasses.fields(vm.Environment)
+        }
+    )
+    to = request["to"]
+    tx = Transaction(
+        nonce=U256(request["nonce"]),
+        gas_price=U256(request["gas_price"]),
+        gas=U256(request["gas"]),
+        to=Bytes0(b"") if to is None else Bytes20(from_hex(to)),
+        value=U256(int(request["value"], 16)),
+        data=from_hex(request["data"]),
+        v=U256(0),
+        r=U256(0),
+        s=U256(0),
+    )
+    try:
+        gas_used, _ = fork.process_transaction(env, tx)
+    except InvalidBlock:
+        return None
+    return {
+        "gas_used": hex(gas_used),
+        "state_root": to_hex(state.state_root(pre_state)),
+    }
+
+
+HANDLERS = {
+    "rlp": handle_rlp,
+    "trie": handle_trie,
+    "state": handle_state,
+    "transaction": handle_transaction,
+}
+
+
+def main():
+    for line in sys.stdin:
+        request = json.loads(line)
+        try:
+            response = {"result": HANDLERS[request["kind"]](request)}
+        except Exception as error:  # noqa: BLE001
+            response = {"error": repr(error)}
+        print(json.dumps(response), flush=True)
+
+
+if __name__ == "__main__":
+    main()
@@ -0,0 +1,23 @@
+#![no_main]
+
+use execution_specs_rs::ethereum::{
+    frontier::{fork, state},
+    utils::hexadecimal::hex,
+};
+use execution_specs_rs_fuzz::{bridge, inputs::TransactionInput};
+use libfuzzer_sys::fuzz_target;
+use serde_json::{json, Value};
+
+fuzz_target!(|input: TransactionInput| {
+    let mut state = input.pre_state.state();
+    let result = fork::process_transaction(&input.block_env(), &input.tx_env(), &mut state, &input.transaction());
+    let outcome = match result {
+        Ok((gas_used, _)) => json!({
+            "gas_used": format!("{gas_used:#x}"),
+            "state_root": hex(&state::state_root(&state)),
+        }),
+        Err(_) => Value::Null,
+    };
+    let expected = bridge::query(input.to_json());
+    assert_eq!(outcome, expected, "{input:?}");
+});
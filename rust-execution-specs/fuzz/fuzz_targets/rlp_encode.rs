@@ -0,0 +1,12 @@
+#![no_main]
+
+use execution_specs_rs::ethereum::{rlp, utils::hexadecimal::hex};
+use execution_specs_rs_fuzz::{bridge, inputs::RlpItem};
+use libfuzzer_sys::fuzz_target;
+use serde_json::json;
+
+fuzz_target!(|item: RlpItem| {
+    let encoded = rlp::encode(&item);
+    let expected = bridge::query(json!({ "kind": "rlp", "item": item.to_json() }));
+    assert_eq!(hex(&encoded), expected.as_str().unwrap(), "{item:?}");
+});
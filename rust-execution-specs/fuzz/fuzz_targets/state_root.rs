@@ -0,0 +1,11 @@
+#![no_main]
+
+use execution_specs_rs::ethereum::{frontier::state, utils::hexadecimal::hex};
+use execution_specs_rs_fuzz::{bridge, inputs::StateInput};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|input: StateInput| {
+    let root = state::state_root(&input.state());
+    let expected = bridge::query(input.to_json());
+    assert_eq!(hex(&root), expected.as_str().unwrap(), "{input:?}");
+});
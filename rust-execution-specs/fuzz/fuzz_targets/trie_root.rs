@@ -0,0 +1,11 @@
+#![no_main]
+
+use execution_specs_rs::ethereum::utils::hexadecimal::hex;
+use execution_specs_rs_fuzz::{bridge, inputs::TrieInput};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|input: TrieInput| {
+    let root = input.root();
+    let expected = bridge::query(input.to_json());
+    assert_eq!(hex(&root), expected.as_str().unwrap(), "{input:?}");
+});
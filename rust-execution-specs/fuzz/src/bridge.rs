@@ -0,0 +1,70 @@
+//!
+//! # Python Bridge
+//!
+//! ## Introduction
+//!
+//! Drives a long running `spec_bridge.py` process which answers one JSON
+//! request per line using the canonical Python specification.
+//!
+
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Child, ChildStdin, ChildStdout, Command, Stdio};
+use std::sync::{Mutex, OnceLock};
+
+use serde_json::Value;
+
+/// A running `spec_bridge.py` process.
+pub struct Bridge {
+    _child: Child,
+    stdin: ChildStdin,
+    stdout: BufReader<ChildStdout>,
+}
+
+impl Bridge {
+    /// Start the bridge.
+    ///
+    /// The interpreter can be overridden with `PYTHON` and the location of
+    /// the execution-specs sources with `EXECUTION_SPECS_SRC`.
+    pub fn spawn() -> Self {
+        let python = std::env::var("PYTHON").unwrap_or_else(|_| "python3".to_owned());
+        let script = concat!(env!("CARGO_MANIFEST_DIR"), "/bridge/spec_bridge.py");
+        let mut child = Command::new(python)
+            .arg(script)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .expect("failed to start the python bridge");
+        let stdin = child.stdin.take().unwrap();
+        let stdout = BufReader::new(child.stdout.take().unwrap());
+        Self { _child: child, stdin, stdout }
+    }
+
+    /// Send `request` and wait for the result.
+    ///
+    /// Panics if the Python specification raised, since the Rust side has
+    /// already accepted the same input.
+    pub fn request(&mut self, request: &Value) -> Value {
+        writeln!(self.stdin, "{request}").expect("python bridge closed stdin");
+        self.stdin.flush().unwrap();
+
+        let mut line = String::new();
+        self.stdout
+            .read_line(&mut line)
+            .expect("python bridge closed stdout");
+        let response: Value = serde_json::from_str(&line).expect("malformed bridge response");
+        if let Some(error) = response.get("error") {
+            panic!("python spec raised {error} for {request}");
+        }
+        response["result"].clone()
+    }
+}
+
+/// Send `request` to the bridge shared by the whole fuzzing process.
+pub fn query(request: Value) -> Value {
+    static BRIDGE: OnceLock<Mutex<Bridge>> = OnceLock::new();
+    BRIDGE
+        .get_or_init(|| Mutex::new(Bridge::spawn()))
+        .lock()
+        .unwrap()
+        .request(&request)
+}
@@ -0,0 +1,222 @@
+//!
+//! # Fuzz Inputs
+//!
+//! ## Introduction
+//!
+//! Structured inputs generated by the fuzzer, along with their evaluation in
+//! this crate and their JSON form understood by `spec_bridge.py`.
+//!
+
+use arbitrary::Arbitrary;
+use serde_json::{json, Value};
+
+use execution_specs_rs::ethereum::{
+    base_types::{Bytes, Uint, U256},
+    frontier::{
+        fork_types::{Account, Address, Root, Transaction},
+        state::{self, State},
+        trie::{self, Trie},
+        vm::{BlockEnvironment, TxEnvironment},
+    },
+    rlp::{self, RLP},
+    utils::hexadecimal::hex,
+};
+
+/// An arbitrarily nested RLP structure.
+#[derive(Debug, Arbitrary)]
+pub enum RlpItem {
+    Bytes(Vec<u8>),
+    List(Vec<RlpItem>),
+}
+
+impl RLP for RlpItem {
+    fn encode(&self) -> Bytes {
+        match self {
+            RlpItem::Bytes(bytes) => rlp::encode_bytes(bytes),
+            RlpItem::List(items) => items.encode(),
+        }
+    }
+}
+
+impl RlpItem {
+    pub fn to_json(&self) -> Value {
+        match self {
+            RlpItem::Bytes(bytes) => json!(hex(bytes)),
+            RlpItem::List(items) => Value::Array(items.iter().map(Self::to_json).collect()),
+        }
+    }
+}
+
+/// Key-value pairs inserted, in order, into a `Bytes` trie.
+#[derive(Debug, Arbitrary)]
+pub struct TrieInput {
+    pub secured: bool,
+    pub items: Vec<(Vec<u8>, Vec<u8>)>,
+}
+
+impl TrieInput {
+    pub fn root(&self) -> Root {
+        let mut trie = Trie::<Bytes, Bytes>::new(self.secured, Bytes::default());
+        for (key, value) in &self.items {
+            trie::trie_set(&mut trie, key.clone().into(), value.clone().into());
+        }
+        trie::root(&trie, trie::dummy_root)
+    }
+
+    pub fn to_json(&self) -> Value {
+        let items: Vec<_> = self
+            .items
+            .iter()
+            .map(|(key, value)| json!([hex(key), hex(value)]))
+            .collect();
+        json!({ "kind": "trie", "secured": self.secured, "items": items })
+    }
+}
+
+/// An account of a generated pre-state, along with its storage.
+#[derive(Debug, Arbitrary)]
+pub struct AccountInput {
+    pub address: Address,
+    pub nonce: u64,
+    pub balance: [u8; 32],
+    pub code: Vec<u8>,
+    pub storage: Vec<([u8; 32], [u8; 32])>,
+}
+
+/// A pre-state, built by applying the accounts in order.
+#[derive(Debug, Arbitrary)]
+pub struct StateInput {
+    pub accounts: Vec<AccountInput>,
+}
+
+impl StateInput {
+    pub fn state(&self) -> State {
+        let mut state = State::default();
+        for input in &self.accounts {
+            let account = Account {
+                nonce: Uint::from(input.nonce),
+                balance: U256::from_bytes_be(&input.balance),
+                code: input.code.clone().into(),
+            };
+            state::set_account(&mut state, input.address, Some(account));
+            for (key, value) in &input.storage {
+                state::set_storage(&mut state, input.address, key, U256::from_bytes_be(value));
+            }
+        }
+        state
+    }
+
+    pub fn to_json(&self) -> Value {
+        let accounts: Vec<_> = self
+            .accounts
+            .iter()
+            .map(|account| {
+                let storage: Vec<_> = account
+                    .storage
+                    .iter()
+                    .map(|(key, value)| json!([hex(key), hex(value)]))
+                    .collect();
+                json!({
+                    "address": hex(&account.address),
+                    "nonce": account.nonce,
+                    "balance": hex(&account.balance),
+                    "code": hex(&account.code),
+                    "storage": storage,
+                })
+            })
+            .collect();
+        json!({ "kind": "state", "accounts": accounts })
+    }
+}
+
+/// The most gas a generated transaction gets, and the gas limit of its
+/// block, so that the Python spec keeps up with the fuzzer.
+pub const GAS_LIMIT: u32 = 1_000_000;
+
+/// A transaction run against a generated pre-state.
+///
+/// The sender and the recipient are picked among the pre-state accounts by
+/// index, so that most transactions get past the balance and nonce checks
+/// and the calls reach code. The signature isn't checked by
+/// `process_transaction`, so it is left out.
+#[derive(Debug, Arbitrary)]
+pub struct TransactionInput {
+    pub pre_state: StateInput,
+    pub sender: u8,
+    pub to: Option<u8>,
+    pub nonce: u64,
+    pub gas_price: u64,
+    pub gas: u32,
+    pub value: [u8; 32],
+    pub data: Vec<u8>,
+    pub coinbase: Address,
+    pub number: u32,
+    pub time: u64,
+    pub difficulty: u64,
+}
+
+impl TransactionInput {
+    fn account_address(&self, index: u8) -> Address {
+        let accounts = &self.pre_state.accounts;
+        match accounts.len() {
+            0 => Address::default(),
+            len => accounts[usize::from(index) % len].address,
+        }
+    }
+
+    fn gas(&self) -> u32 {
+        self.gas % (GAS_LIMIT + 1)
+    }
+
+    pub fn block_env(&self) -> BlockEnvironment {
+        BlockEnvironment {
+            block_hashes: vec![],
+            coinbase: self.coinbase,
+            number: Uint::from(self.number),
+            gas_limit: Uint::from(GAS_LIMIT),
+            time: U256::from(self.time),
+            difficulty: Uint::from(self.difficulty),
+        }
+    }
+
+    pub fn tx_env(&self) -> TxEnvironment {
+        TxEnvironment {
+            origin: self.account_address(self.sender),
+            gas_price: U256::from(self.gas_price),
+        }
+    }
+
+    pub fn transaction(&self) -> Transaction {
+        Transaction {
+            nonce: U256::from(self.nonce),
+            gas_price: U256::from(self.gas_price),
+            gas: U256::from(self.gas()),
+            to: self.to.map(|index| self.account_address(index)),
+            value: U256::from_bytes_be(&self.value),
+            data: self.data.clone().into(),
+            v: U256::default(),
+            r: U256::default(),
+            s: U256::default(),
+        }
+    }
+
+    pub fn to_json(&self) -> Value {
+        let to = self.to.map(|index| hex(&self.account_address(index)));
+        json!({
+            "kind": "transaction",
+            "accounts": self.pre_state.to_json()["accounts"],
+            "sender": hex(&self.account_address(self.sender)),
+            "to": to,
+            "nonce": self.nonce,
+            "gas_price": self.gas_price,
+            "gas": self.gas(),
+            "value": hex(&self.value),
+            "data": hex(&self.data),
+            "coinbase": hex(&self.coinbase),
+            "number": self.number,
+            "gas_limit": GAS_LIMIT,
+            "time": self.time,
+            "difficulty": self.difficulty,
+        })
+    }
+}
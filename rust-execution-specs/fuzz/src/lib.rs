@@ -0,0 +1,12 @@
+//!
+//! # Differential Fuzzing
+//!
+//! ## Introduction
+//!
+//! Helpers shared by the fuzz targets. Every target computes a value with
+//! this crate, asks the Python execution-specs for the same value through
+//! `bridge/spec_bridge.py` and asserts that both implementations agree.
+//!
+
+pub mod bridge;
+pub mod inputs;
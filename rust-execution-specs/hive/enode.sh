@@ -0,0 +1,5 @@
+#!/bin/sh
+
+# The spec has no devp2p networking, so there is no enode URL.
+echo "execution-specs-rs does not support devp2p" >&2
+exit 1
@@ -0,0 +1,22 @@
+#!/bin/sh
+
+# Starts the spec from the files and variables hive provides:
+#
+#   /genesis.json                 the genesis, in the format geth reads
+#   HIVE_CHAIN_ID                 the chain id, overriding the genesis
+#   HIVE_FORK_LONDON              the London block
+#   HIVE_TERMINAL_TOTAL_DIFFICULTY
+#   HIVE_SHANGHAI_TIMESTAMP       the Shanghai and Cancun timestamps
+#   HIVE_CANCUN_TIMESTAMP
+#
+# The HTTP endpoint listens on 8545 and the Engine API on 8551.
+
+set -e
+
+jq -f /mapper.jq /genesis.json > /tmp/genesis.json
+
+if [ -f /chain.rlp ] || [ -d /blocks ]; then
+    echo "warning: importing /chain.rlp and /blocks is not supported, starting from the genesis" >&2
+fi
+
+exec spec-hive --genesis /tmp/genesis.json --http-port 8545 --engine-port 8551
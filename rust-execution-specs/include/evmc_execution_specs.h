@@ -0,0 +1,26 @@
+/*
+ * EVMC VM of execution-specs-rs, built with `make evmc`.
+ *
+ * Only EVMC_FRONTIER is supported. See
+ * src/ethereum_spec_tools/evm_tools/evmc.rs for how the work is split with
+ * the host.
+ */
+
+#ifndef EVMC_EXECUTION_SPECS_H
+#define EVMC_EXECUTION_SPECS_H
+
+#include <evmc/evmc.h>
+#include <evmc/utils.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* Create an instance of the VM. Release it with its destroy function. */
+EVMC_EXPORT struct evmc_vm *evmc_create_execution_specs_rs(void);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* EVMC_EXECUTION_SPECS_H */
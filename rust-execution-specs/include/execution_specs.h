@@ -0,0 +1,44 @@
+/*
+ * C interface of execution-specs-rs, built with `make ffi`.
+ *
+ * A VM holds a state and runs Frontier transactions against it. Every call
+ * leaves a JSON result in the VM, read with es_vm_result. See
+ * src/ethereum_spec_tools/evm_tools/ffi.rs for the JSON formats.
+ */
+
+#ifndef EXECUTION_SPECS_H
+#define EXECUTION_SPECS_H
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* The call succeeded. */
+#define ES_OK 0
+/* An argument is not valid JSON of the expected shape. */
+#define ES_INVALID_INPUT 1
+/* The transaction can't be included in a block. */
+#define ES_INVALID_TRANSACTION 2
+
+typedef struct EsVm EsVm;
+
+/* Create a VM with an empty state. Release it with es_vm_free. */
+EsVm *es_vm_new(void);
+
+/* Release a VM. Null is ignored. */
+void es_vm_free(EsVm *vm);
+
+/* Replace the state with a geth style allocation. */
+int es_vm_load_prestate(EsVm *vm, const char *alloc_json);
+
+/* Run a transaction against the state. The state only changes on ES_OK. */
+int es_vm_execute(EsVm *vm, const char *tx_json);
+
+/* The JSON result of the last call, valid until the next call on the VM. */
+const char *es_vm_result(const EsVm *vm);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* EXECUTION_SPECS_H */
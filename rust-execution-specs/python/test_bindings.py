@@ -0,0 +1,95 @@
+"""
+Checks of the `execution_specs_rs` Python module, built with `make python`.
+
+Run with `python3 python/test_bindings.py` or pytest, with the module on the
+path. The same calls work against the Python execution-specs, by importing
+`ethereum.rlp`, `ethereum.frontier.trie` and `ethereum.frontier.fork`
+instead.
+"""
+
+import os
+from types import SimpleNamespace
+
+import execution_specs_rs
+from execution_specs_rs.fork import BlockChain, state_transition
+from execution_specs_rs.rlp import decode, encode
+from execution_specs_rs.trie import Trie, root, trie_get, trie_set
+
+ASSETS = os.path.join(os.path.dirname(__file__), "..", "assets")
+EMPTY_TRIE_ROOT = bytes.fromhex(
+    "56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421"
+)
+
+
+def test_rlp():
+    assert encode(b"dog") == b"\x83dog"
+    assert encode([b"cat", b"dog"]) == b"\xc8\x83cat\x83dog"
+    assert encode(0) == b"\x80"
+    assert encode(1024) == b"\x82\x04\x00"
+    assert decode(b"\xc8\x83cat\x83dog") == [b"cat", b"dog"]
+    try:
+        decode(b"\xc5")
+    except execution_specs_rs.RLPDecodingError:
+        pass
+    else:
+        raise AssertionError("decoded a truncated list")
+
+
+def test_trie_root():
+    trie = Trie(False, b"")
+    assert root(trie) == EMPTY_TRIE_ROOT
+    for key, value in [
+        (b"do", b"verb"),
+        (b"dog", b"puppy"),
+        (b"doge", b"coin"),
+        (b"horse", b"stallion"),
+    ]:
+        trie_set(trie, key, value)
+    assert trie_get(trie, b"dog") == b"puppy"
+    assert root(trie).hex() == (
+        "5991bb8c6514148a29db676a14ac506cd2cd5775ace63c30a4fe457715e9ac84"
+    )
+    trie_set(trie, b"horse", b"")
+    assert b"horse" not in trie._data
+
+
+def test_mainnet_block_1():
+    with open(os.path.join(ASSETS, "mainnet.json")) as genesis:
+        chain = BlockChain.from_genesis(genesis.read())
+    h = bytes.fromhex
+    header = SimpleNamespace(
+        parent_hash=h("d4e56740f876aef8c010b86a40d5f56745a118d0906a34e69aec8c0db1cb8fa3"),
+        ommers_hash=h("1dcc4de8dec75d7aab85b567b6ccd41ad312451b948a7413f0a142fd40d49347"),
+        coinbase=h("05a56e2d52c817161883f50c441c3228cfe54d9f"),
+        state_root=h("d67e4d450343046425ae4271474353857ab860dbc0a1dde64b41b5cd3a532bf3"),
+        transactions_root=EMPTY_TRIE_ROOT,
+        receipt_root=EMPTY_TRIE_ROOT,
+        bloom=bytes(256),
+        difficulty=0x3FF800000,
+        number=1,
+        gas_limit=5000,
+        gas_used=0,
+        timestamp=1438269988,
+        extra_data=h("476574682f76312e302e302f6c696e75782f676f312e342e32"),
+        mix_digest=h("969b900de27b6ac6a67742365dd65f55a0526c41fd18e1b16f1a1215c2e66f59"),
+        nonce=h("539bd4979fef1ec4"),
+    )
+    block = SimpleNamespace(header=header, transactions=[], ommers=[])
+
+    state_transition(chain, block)
+    assert chain.head_number == 1
+    assert chain.state_root == header.state_root
+
+    try:
+        state_transition(chain, block)
+    except execution_specs_rs.InvalidBlock:
+        pass
+    else:
+        raise AssertionError("applied block 1 twice")
+
+
+if __name__ == "__main__":
+    test_rlp()
+    test_trie_root()
+    test_mainnet_block_1()
+    print("ok")
@@ -0,0 +1,190 @@
+//!
+//! # Access Lists
+//!
+//! ## Introduction
+//!
+//! The access lists of EIP-2930. Frontier transactions don't carry one, but
+//! `eth_createAccessList` answers with an [AccessList] of what a call
+//! accessed, see `ethereum_spec_tools::access_list`, and [access_list_counts]
+//! gives what the intrinsic gas of one is charged for.
+//!
+//! A list is RLP encoded as a sequence of `[address, [storage_key, ...]]`,
+//! and written as JSON, with serde or [AccessListEntry::to_json], as
+//! `eth_createAccessList` and the transactions of the JSON-RPC API have it:
+//!
+//! ```json
+//! [{"address": "0x…", "storageKeys": ["0x…"]}]
+//! ```
+//!
+
+use crate::prelude::*;
+
+use serde::{de::Error as _, Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::{json, Value};
+
+use super::{
+    base_types::{Bytes, Bytes32},
+    exceptions::EthereumException,
+    frontier::fork_types::Address,
+    rlp::{self, Simple, RLP},
+    utils::hexadecimal::{hex, hex_to_bytes20, hex_to_bytes32},
+};
+
+/// An address of an access list, with the storage keys listed for it.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct AccessListEntry {
+    pub address: Address,
+    pub storage_keys: Vec<Bytes32>,
+}
+
+/// The addresses and storage keys a transaction warms before it runs.
+pub type AccessList = Vec<AccessListEntry>;
+
+impl RLP for AccessListEntry {
+    fn encode(&self) -> Bytes {
+        rlp::encode(&(&self.address, &self.storage_keys))
+    }
+}
+
+fn rlp_array<const N: usize>(item: &Simple) -> Result<[u8; N], EthereumException> {
+    match item {
+        Simple::Bytes(bytes) => <[u8; N]>::try_from(&bytes[..]).map_err(|_| EthereumException::RLPDecodingError),
+        Simple::Sequence(_) => Err(EthereumException::RLPDecodingError),
+    }
+}
+
+fn json_error(message: &str) -> EthereumException {
+    EthereumException::JsonDecodeError(message.to_owned())
+}
+
+impl AccessListEntry {
+    ///
+    ///     Read an entry from its decoded RLP, `[address, [storage_key, ...]]`.
+    ///
+    ///     Raises
+    ///     ------
+    ///     RLPDecodingError :
+    ///         If the item isn't of that shape, or the address or a key is
+    ///         of the wrong length.
+    ///
+    pub fn from_rlp(item: &Simple) -> Result<Self, EthereumException> {
+        let Simple::Sequence(fields) = item else {
+            return Err(EthereumException::RLPDecodingError);
+        };
+        let [address, Simple::Sequence(storage_keys)] = &fields[..] else {
+            return Err(EthereumException::RLPDecodingError);
+        };
+        Ok(Self {
+            address: rlp_array(address)?,
+            storage_keys: storage_keys.iter().map(rlp_array).collect::<Result<_, _>>()?,
+        })
+    }
+
+    /// The entry as the JSON-RPC API writes it.
+    pub fn to_json(&self) -> Value {
+        json!({
+            "address": hex(&self.address),
+            "storageKeys": self.storage_keys.iter().map(|key| hex(key)).collect::<Vec<_>>(),
+        })
+    }
+
+    ///
+    ///     Read an entry as the JSON-RPC API writes it. Missing
+    ///     `storageKeys` are none.
+    ///
+    ///     Raises
+    ///     ------
+    ///     JsonDecodeError :
+    ///         If the address is missing, or the keys aren't an array of
+    ///         strings.
+    ///     BadHexString :
+    ///         If the address or a key isn't hex of the right length.
+    ///
+    pub fn from_json(value: &Value) -> Result<Self, EthereumException> {
+        let address = value["address"]
+            .as_str()
+            .ok_or_else(|| json_error("missing field `address`"))?;
+        let storage_keys = match &value["storageKeys"] {
+            Value::Null => vec![],
+            Value::Array(keys) => keys
+                .iter()
+                .map(|key| {
+                    hex_to_bytes32(
+                        key.as_str()
+                            .ok_or_else(|| json_error("a storage key must be a string"))?,
+                    )
+                })
+                .collect::<Result<_, _>>()?,
+            _ => return Err(json_error("`storageKeys` must be an array")),
+        };
+        Ok(Self {
+            address: hex_to_bytes20(address)?,
+            storage_keys,
+        })
+    }
+}
+
+impl Serialize for AccessListEntry {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.to_json().serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for AccessListEntry {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Self::from_json(&Value::deserialize(deserializer)?).map_err(|e| D::Error::custom(format!("{e:?}")))
+    }
+}
+
+///
+///     Read an access list from its decoded RLP, as a field of a
+///     transaction.
+///
+///     Raises
+///     ------
+///     RLPDecodingError :
+///         If the item isn't a sequence of entries.
+///
+pub fn access_list_from_rlp(item: &Simple) -> Result<AccessList, EthereumException> {
+    match item {
+        Simple::Sequence(entries) => entries.iter().map(AccessListEntry::from_rlp).collect(),
+        Simple::Bytes(_) => Err(EthereumException::RLPDecodingError),
+    }
+}
+
+///
+///     Decode an RLP encoded access list.
+///
+///     Parameters
+///     ----------
+///     encoded :
+///         The list, as `rlp::encode` writes it.
+///
+///     Returns
+///     -------
+///     access_list : `AccessList`
+///         The entries, in order.
+///
+///     Raises
+///     ------
+///     RLPDecodingError :
+///         If `encoded` isn't an access list.
+///
+pub fn decode_access_list(encoded: &[u8]) -> Result<AccessList, EthereumException> {
+    access_list_from_rlp(&rlp::decode(encoded)?)
+}
+
+///
+///     Count what an access list charges intrinsic gas for.
+///
+///     Returns
+///     -------
+///     addresses : `u64`
+///         The number of entries.
+///     storage_keys : `u64`
+///         The number of storage keys, over all entries.
+///
+pub fn access_list_counts(access_list: &[AccessListEntry]) -> (u64, u64) {
+    let storage_keys = access_list.iter().map(|entry| entry.storage_keys.len() as u64).sum();
+    (access_list.len() as u64, storage_keys)
+}
@@ -0,0 +1,98 @@
+//!
+//! # Arbitrary Values
+//!
+//! ## Introduction
+//!
+//! `proptest` strategies for the core types, used by the property tests.
+//!
+//! `Uint`, `U256` and `Bytes` are aliases of foreign types, so they get
+//! strategy functions rather than `Arbitrary` impls.
+//!
+
+use std::collections::HashMap;
+
+use num_bigint::BigUint;
+use proptest::{collection, prelude::*};
+
+use super::{
+    base_types::{Bytes, Bytes32, Uint, U256},
+    frontier::fork_types::{Account, Address, Transaction},
+    rlp::Simple,
+};
+
+/// An arbitrary `Uint` of up to 512 bits.
+pub fn uint() -> impl Strategy<Value = Uint> {
+    collection::vec(any::<u8>(), 0..=64).prop_map(|bytes| BigUint::from_bytes_be(&bytes))
+}
+
+/// An arbitrary `U256`, biased towards the edges of the range.
+pub fn u256() -> impl Strategy<Value = U256> {
+    prop_oneof![
+        Just(U256::from(0u8)),
+        Just((U256::from(1u8) << 256usize) - 1u8),
+        any::<u64>().prop_map(U256::from),
+        any::<Bytes32>().prop_map(|bytes| U256::from_bytes_be(&bytes)),
+    ]
+}
+
+/// Arbitrary `Bytes`, long enough to cover the long form RLP encodings.
+pub fn bytes() -> impl Strategy<Value = Bytes> {
+    collection::vec(any::<u8>(), 0..128).prop_map(Bytes::from)
+}
+
+/// An arbitrary tree of RLP items, nested up to four levels deep.
+pub fn rlp_item() -> impl Strategy<Value = Simple> {
+    bytes().prop_map(Simple::Bytes).prop_recursive(4, 64, 8, |inner| {
+        collection::vec(inner, 0..8).prop_map(Simple::Sequence)
+    })
+}
+
+/// An arbitrary set of storage slots, as stored in a storage trie. Zero
+/// values are never stored, so none are generated.
+pub fn storage() -> impl Strategy<Value = HashMap<Bytes32, U256>> {
+    collection::hash_map(any::<Bytes32>(), u256().prop_filter("zero", |v| *v != U256::from(0u8)), 0..16)
+}
+
+/// Arbitrary key/value pairs for an unsecured `Trie<Bytes, Bytes>`. Keys may
+/// share prefixes, so branch and extension nodes are exercised.
+pub fn trie_items() -> impl Strategy<Value = HashMap<Bytes, Bytes>> {
+    let key = collection::vec(0u8..4, 0..6).prop_map(Bytes::from);
+    let value = collection::vec(any::<u8>(), 1..48).prop_map(Bytes::from);
+    collection::hash_map(key, value, 0..32)
+}
+
+impl Arbitrary for Account {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        (any::<u64>(), u256(), bytes())
+            .prop_map(|(nonce, balance, code)| Account { nonce: Uint::from(nonce), balance, code: code.into() })
+            .boxed()
+    }
+}
+
+impl Arbitrary for Transaction {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        (
+            (u256(), u256(), u256(), any::<Option<Address>>()),
+            (u256(), bytes()),
+            (u256(), u256(), u256()),
+        )
+            .prop_map(|((nonce, gas_price, gas, to), (value, data), (v, r, s))| Transaction {
+                nonce,
+                gas_price,
+                gas,
+                to,
+                value,
+                data,
+                v,
+                r,
+                s,
+            })
+            .boxed()
+    }
+}
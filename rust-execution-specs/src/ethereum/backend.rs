@@ -0,0 +1,234 @@
+//!
+//! # State Backends
+//!
+//! ## Introduction
+//!
+//! The key-value stores a [Trie](crate::ethereum::frontier::trie::Trie)
+//! keeps its entries in.
+//!
+//! The specification only ever needs an in-memory map, but a client built on
+//! it may want to hold the state somewhere else, such as on disk. A store only
+//! has to implement [StateBackend]. It hands out owned values, so it can
+//! decode them from storage on each read. A slow store can be put behind a
+//! [CachedBackend], which keeps the entries used most recently in memory.
+//!
+
+use alloc::collections::BTreeMap;
+use core::{
+    cell::{Cell, RefCell},
+    hash::Hash,
+};
+
+use crate::prelude::*;
+
+/// A key-value store holding the entries of a trie.
+pub trait StateBackend<K, V>: Clone + Default {
+    /// Read the value at `key`.
+    fn get(&self, key: &K) -> Option<V>;
+
+    /// Store `value` at `key`, replacing any previous value.
+    fn set(&mut self, key: K, value: V);
+
+    /// Delete the value at `key`, if there is one.
+    fn delete(&mut self, key: &K);
+
+    /// Every entry of the store, in no particular order.
+    fn entries(&self) -> Box<dyn Iterator<Item = (K, V)> + '_>;
+
+    /// Whether the store has no entries.
+    fn is_empty(&self) -> bool;
+}
+
+impl<K, V> StateBackend<K, V> for HashMap<K, V>
+where
+    K: Eq + Hash + Clone,
+    V: Clone,
+{
+    fn get(&self, key: &K) -> Option<V> {
+        HashMap::get(self, key).cloned()
+    }
+
+    fn set(&mut self, key: K, value: V) {
+        self.insert(key, value);
+    }
+
+    fn delete(&mut self, key: &K) {
+        self.remove(key);
+    }
+
+    fn entries(&self) -> Box<dyn Iterator<Item = (K, V)> + '_> {
+        Box::new(self.iter().map(|(key, value)| (key.clone(), value.clone())))
+    }
+
+    fn is_empty(&self) -> bool {
+        HashMap::is_empty(self)
+    }
+}
+
+/// An ordered store, whose entries are listed by key.
+impl<K, V> StateBackend<K, V> for BTreeMap<K, V>
+where
+    K: Ord + Clone,
+    V: Clone,
+{
+    fn get(&self, key: &K) -> Option<V> {
+        BTreeMap::get(self, key).cloned()
+    }
+
+    fn set(&mut self, key: K, value: V) {
+        self.insert(key, value);
+    }
+
+    fn delete(&mut self, key: &K) {
+        self.remove(key);
+    }
+
+    fn entries(&self) -> Box<dyn Iterator<Item = (K, V)> + '_> {
+        Box::new(self.iter().map(|(key, value)| (key.clone(), value.clone())))
+    }
+
+    fn is_empty(&self) -> bool {
+        BTreeMap::is_empty(self)
+    }
+}
+
+/// Number of entries a [CachedBackend] keeps unless told otherwise.
+pub const DEFAULT_CACHE_CAPACITY: usize = 4096;
+
+/// How often reads through a [CachedBackend] found their entry cached.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+}
+
+/// The least recently used entries read from or written to a store.
+#[derive(Clone, Debug)]
+struct Lru<K, V> {
+    /// Each entry with the tick it was last used at.
+    entries: HashMap<K, (Option<V>, u64)>,
+    /// The entries by the tick they were last used at.
+    order: BTreeMap<u64, K>,
+    tick: u64,
+}
+
+impl<K, V> Default for Lru<K, V> {
+    fn default() -> Self {
+        Self {
+            entries: HashMap::new(),
+            order: BTreeMap::new(),
+            tick: 0,
+        }
+    }
+}
+
+impl<K: Eq + Hash + Clone, V: Clone> Lru<K, V> {
+    fn get(&mut self, key: &K) -> Option<Option<V>> {
+        let (value, tick) = self.entries.get_mut(key)?;
+        self.order.remove(tick);
+        self.tick += 1;
+        *tick = self.tick;
+        self.order.insert(self.tick, key.clone());
+        Some(value.clone())
+    }
+
+    fn put(&mut self, key: K, value: Option<V>, capacity: usize) {
+        self.tick += 1;
+        if let Some((_, tick)) = self.entries.insert(key.clone(), (value, self.tick)) {
+            self.order.remove(&tick);
+        }
+        self.order.insert(self.tick, key);
+        while self.entries.len() > capacity {
+            let (_, oldest) = self.order.pop_first().unwrap();
+            self.entries.remove(&oldest);
+        }
+    }
+}
+
+/// A store that keeps recently used entries of another store in memory.
+///
+/// Reads that miss are cached, including the absence of a value. Writes go
+/// through to the inner store.
+#[derive(Clone, Debug)]
+pub struct CachedBackend<K, V, B> {
+    backend: B,
+    capacity: usize,
+    cache: RefCell<Lru<K, V>>,
+    stats: Cell<CacheStats>,
+}
+
+impl<K, V, B: Default> Default for CachedBackend<K, V, B> {
+    fn default() -> Self {
+        Self::new(B::default(), DEFAULT_CACHE_CAPACITY)
+    }
+}
+
+impl<K, V, B> CachedBackend<K, V, B> {
+    /// Cache up to `capacity` entries of `backend`.
+    pub fn new(backend: B, capacity: usize) -> Self {
+        Self {
+            backend,
+            capacity,
+            cache: RefCell::new(Lru::default()),
+            stats: Cell::new(CacheStats::default()),
+        }
+    }
+
+    /// The number of reads served from the cache and from the inner store.
+    pub fn stats(&self) -> CacheStats {
+        self.stats.get()
+    }
+
+    /// The inner store.
+    pub fn into_inner(self) -> B {
+        self.backend
+    }
+}
+
+impl<K, V, B> StateBackend<K, V> for CachedBackend<K, V, B>
+where
+    K: Eq + Hash + Clone,
+    V: Clone,
+    B: StateBackend<K, V>,
+{
+    fn get(&self, key: &K) -> Option<V> {
+        let mut stats = self.stats.get();
+        let cached = self.cache.borrow_mut().get(key);
+        let value = match cached {
+            Some(value) => {
+                stats.hits += 1;
+                #[cfg(feature = "metrics")]
+                crate::ethereum::metrics::BACKEND_CACHE_HITS.increment();
+                value
+            }
+            None => {
+                stats.misses += 1;
+                #[cfg(feature = "metrics")]
+                crate::ethereum::metrics::BACKEND_CACHE_MISSES.increment();
+                let value = self.backend.get(key);
+                self.cache.borrow_mut().put(key.clone(), value.clone(), self.capacity);
+                value
+            }
+        };
+        self.stats.set(stats);
+        value
+    }
+
+    fn set(&mut self, key: K, value: V) {
+        self.cache.get_mut().put(key.clone(), Some(value.clone()), self.capacity);
+        self.backend.set(key, value);
+    }
+
+    fn delete(&mut self, key: &K) {
+        self.cache.get_mut().put(key.clone(), None, self.capacity);
+        self.backend.delete(key);
+    }
+
+    fn entries(&self) -> Box<dyn Iterator<Item = (K, V)> + '_> {
+        self.backend.entries()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.backend.is_empty()
+    }
+}
@@ -0,0 +1,181 @@
+//!
+//! # Base Fee
+//!
+//! ## Introduction
+//!
+//! The base fees of the EIP-1559 fee market and the blob fee market of
+//! EIP-4844. Frontier has neither; these serve the tools that work with the
+//! fee parameters of a chain, see `ethereum_spec_tools::chain_spec`.
+//!
+//! The base fee per gas of a block rises when the parent used more than its
+//! gas target, the gas limit over the elasticity multiplier, and falls when
+//! it used less, by at most a fraction of it a block. The blob base fee
+//! follows the blob gas the chain used over its target as an exponential of
+//! it. A [BaseFeeSchedule] and a [BlobFeeSchedule] hold the parameters of
+//! each; [CANCUN] is the blob fee market of EIP-4844 as Cancun launched it.
+//!
+
+use crate::ethereum::base_types::Uint;
+
+/// The blob gas of a blob, EIP-4844.
+pub const GAS_PER_BLOB: u64 = 1 << 17;
+
+/// The rules of one EIP-1559 fee market.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BaseFeeSchedule {
+    /// The gas limit over the gas target.
+    pub elasticity_multiplier: u64,
+    /// The inverse of the largest change of the base fee in a block.
+    pub base_fee_max_change_denominator: u64,
+}
+
+/// The rules of one blob fee market.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BlobFeeSchedule {
+    /// The blob gas a block is meant to use.
+    pub target_blob_gas_per_block: u64,
+    /// How slowly the blob base fee follows the excess blob gas: it grows
+    /// `e` times for each this much excess.
+    pub base_fee_update_fraction: u64,
+    /// The blob base fee with no excess blob gas.
+    pub min_base_fee_per_blob_gas: u64,
+}
+
+/// Cancun, EIP-4844.
+pub const CANCUN: BlobFeeSchedule = BlobFeeSchedule {
+    target_blob_gas_per_block: 3 * GAS_PER_BLOB,
+    base_fee_update_fraction: 3_338_477,
+    min_base_fee_per_blob_gas: 1,
+};
+
+///
+///     Calculate the base fee per gas of a block from its parent.
+///
+///     The gas limit of the block is checked apart, by `validate_header`.
+///
+///     Parameters
+///     ----------
+///     schedule :
+///         The rules of the fork of the block.
+///     parent_gas_limit :
+///         Gas limit of the parent block. It must be at least the
+///         elasticity multiplier, which the minimum gas limit ensures.
+///     parent_gas_used :
+///         Gas used by the parent block.
+///     parent_base_fee_per_gas :
+///         Base fee per gas of the parent block.
+///
+///     Returns
+///     -------
+///     base_fee_per_gas : `ethereum.base_types.Uint`
+///         The base fee per gas of the block.
+///
+pub fn calculate_base_fee_per_gas(
+    schedule: &BaseFeeSchedule,
+    parent_gas_limit: &Uint,
+    parent_gas_used: &Uint,
+    parent_base_fee_per_gas: &Uint,
+) -> Uint {
+    let parent_gas_target = parent_gas_limit / schedule.elasticity_multiplier;
+    if *parent_gas_used == parent_gas_target {
+        parent_base_fee_per_gas.clone()
+    } else if *parent_gas_used > parent_gas_target {
+        let gas_used_delta = parent_gas_used - &parent_gas_target;
+        let parent_fee_gas_delta = parent_base_fee_per_gas * gas_used_delta;
+        let target_fee_gas_delta = parent_fee_gas_delta / parent_gas_target;
+        // A block over its target always raises the base fee.
+        let base_fee_per_gas_delta =
+            (target_fee_gas_delta / schedule.base_fee_max_change_denominator).max(Uint::from(1u8));
+        parent_base_fee_per_gas + base_fee_per_gas_delta
+    } else {
+        let gas_used_delta = &parent_gas_target - parent_gas_used;
+        let parent_fee_gas_delta = parent_base_fee_per_gas * gas_used_delta;
+        let target_fee_gas_delta = parent_fee_gas_delta / parent_gas_target;
+        let base_fee_per_gas_delta = target_fee_gas_delta / schedule.base_fee_max_change_denominator;
+        parent_base_fee_per_gas - base_fee_per_gas_delta
+    }
+}
+
+///
+///     Calculate the excess blob gas of a block, the blob gas the chain
+///     used over its target up to and including the parent.
+///
+///     Parameters
+///     ----------
+///     schedule :
+///         The blob rules of the fork of the block.
+///     parent_excess_blob_gas :
+///         Excess blob gas of the parent block, zero if it predates Cancun.
+///     parent_blob_gas_used :
+///         Blob gas used by the parent block, zero if it predates Cancun.
+///
+///     Returns
+///     -------
+///     excess_blob_gas : `ethereum.base_types.Uint`
+///         The excess blob gas of the block.
+///
+pub fn calculate_excess_blob_gas(
+    schedule: &BlobFeeSchedule,
+    parent_excess_blob_gas: &Uint,
+    parent_blob_gas_used: &Uint,
+) -> Uint {
+    let parent_blob_gas = parent_excess_blob_gas + parent_blob_gas_used;
+    let target = Uint::from(schedule.target_blob_gas_per_block);
+    if parent_blob_gas < target {
+        return Uint::default();
+    }
+    parent_blob_gas - target
+}
+
+///
+///     Approximate `factor * e ** (numerator / denominator)` with integers,
+///     by its Taylor expansion.
+///
+///     Parameters
+///     ----------
+///     factor :
+///         The value at zero.
+///     numerator :
+///         Numerator of the exponent.
+///     denominator :
+///         Denominator of the exponent.
+///
+///     Returns
+///     -------
+///     output : `ethereum.base_types.Uint`
+///         The approximation, rounded down.
+///
+pub fn fake_exponential(factor: &Uint, numerator: &Uint, denominator: &Uint) -> Uint {
+    let mut i = 1u64;
+    let mut output = Uint::default();
+    let mut numerator_accumulated = factor * denominator;
+    while numerator_accumulated > Uint::default() {
+        output += &numerator_accumulated;
+        numerator_accumulated = (numerator_accumulated * numerator) / (denominator * i);
+        i += 1;
+    }
+    output / denominator
+}
+
+///
+///     Calculate the blob base fee of a block, the price of its blob gas.
+///
+///     Parameters
+///     ----------
+///     schedule :
+///         The blob rules of the fork of the block.
+///     excess_blob_gas :
+///         The excess blob gas of the block, see `calculate_excess_blob_gas`.
+///
+///     Returns
+///     -------
+///     base_fee_per_blob_gas : `ethereum.base_types.Uint`
+///         The price of a unit of blob gas, in wei.
+///
+pub fn calculate_blob_gas_price(schedule: &BlobFeeSchedule, excess_blob_gas: &Uint) -> Uint {
+    fake_exponential(
+        &Uint::from(schedule.min_base_fee_per_blob_gas),
+        excess_blob_gas,
+        &Uint::from(schedule.base_fee_update_fraction),
+    )
+}
@@ -14,14 +14,31 @@
 // U32_MAX_VALUE = (2).pow(32) - 1;
 // U32_CEIL_VALUE = (2).pow(32);
 // U64_MAX_VALUE = (2).pow(64) - 1;
-// U255_MAX_VALUE = (2).pow(255) - 1;
-// U255_CEIL_VALUE = (2).pow(255);
-// U256_MAX_VALUE = (2).pow(256) - 1;
-// U256_CEIL_VALUE = (2).pow(256);
 
-use num_bigint::BigUint;
+use crate::prelude::*;
+use num_bigint::{BigInt, BigUint, Sign};
 
-// use super::exceptions::EthereumException;
+use super::exceptions::EthereumException;
+
+#[allow(non_snake_case)]
+pub fn U255_MAX_VALUE() -> U256 {
+    U255_CEIL_VALUE() - 1u8
+}
+
+#[allow(non_snake_case)]
+pub fn U255_CEIL_VALUE() -> U256 {
+    U256::from(1u8) << 255usize
+}
+
+#[allow(non_snake_case)]
+pub fn U256_MAX_VALUE() -> U256 {
+    U256_CEIL_VALUE() - 1u8
+}
+
+#[allow(non_snake_case)]
+pub fn U256_CEIL_VALUE() -> U256 {
+    U256::from(1u8) << 256usize
+}
 
 /// Array of bytes.
 pub type Bytes = Box<[u8]>;
@@ -81,18 +98,197 @@ pub type Bytes20 = [u8; 20];
 ///
 pub type Bytes32 = [u8; 32];
 
+///
+///     Byte array of exactly 48 elements, such as a KZG commitment.
+///
+pub type Bytes48 = [u8; 48];
+
 ///
 ///     Byte array of exactly 64 elements.
 ///
 pub type Bytes64 = [u8; 64];
 
+///
+///     Byte array of exactly 96 elements, such as a BLS signature.
+///
+pub type Bytes96 = [u8; 96];
+
 ///
 ///     Byte array of exactly 256 elements.
 ///
 pub type Bytes256 = [u8; 256];
 
+///
+///     Copies a byte sequence into a byte array of its exact length, as the
+///     constructors of the fixed length byte types of the Python spec do.
+///
+///     Raises
+///     ------
+///     ValueError :
+///         If `value` isn't `N` bytes long.
+///
+pub fn to_fixed_bytes<const N: usize>(value: &[u8]) -> Result<[u8; N], EthereumException> {
+    value.try_into().map_err(|_| EthereumException::ValueError)
+}
+
 /// Does exactly what it says on the tin.
 pub fn strip_leading_zeros(value: &[u8]) -> &[u8] {
     let leading_zeros = value.iter().position(|b| *b != 0).unwrap_or(value.len());
     &value[leading_zeros..]
 }
+
+///
+///     Converts this 256-bit unsigned integer into a 32-byte sequence.
+///
+///     Returns
+///     -------
+///     bytes : `Bytes32`
+///         Big endian (most significant bits first) representation.
+///
+pub fn u256_to_be_bytes32(value: &U256) -> Bytes32 {
+    let bytes = value.to_bytes_be();
+    let bytes = strip_leading_zeros(&bytes);
+    assert!(bytes.len() <= 32, "value does not fit in a U256");
+    let mut result = [0; 32];
+    result[32 - bytes.len()..].copy_from_slice(bytes);
+    result
+}
+
+///
+///     Decodes a signed integer from its two's complement representation.
+///
+///     Returns
+///     -------
+///     signed : `int`
+///         Signed integer.
+///
+pub fn u256_to_signed(value: &U256) -> BigInt {
+    if value <= &U255_MAX_VALUE() {
+        // This means that the sign bit is 0
+        BigInt::from_biguint(Sign::Plus, value.clone())
+    } else {
+        // -1 * (2's complement of value)
+        BigInt::from_biguint(Sign::Plus, value.clone()) - BigInt::from_biguint(Sign::Plus, U256_CEIL_VALUE())
+    }
+}
+
+///
+///     Creates an unsigned integer representing `value` using two's
+///     complement.
+///
+///     Parameters
+///     ----------
+///     value :
+///         Signed integer in the range `-2 ** 255` to `2 ** 255 - 1`.
+///
+pub fn u256_from_signed(value: BigInt) -> U256 {
+    match value.to_biguint() {
+        Some(value) => value,
+        None => U256_CEIL_VALUE() - value.magnitude(),
+    }
+}
+
+///
+///     The methods of the Python spec's `U256` and the conversions the EVM
+///     needs, for `U256` and `Uint`, which are both `BigUint`.
+///
+///     The arithmetic wraps around `2 ** 256`, as the EVM's does, and the
+///     conversions to machine integers either keep the low bits
+///     (`low_u64`) or saturate at the largest value (`saturating_to_u64`),
+///     so that each use says which one it means.
+///
+pub trait U256Ext: Sized {
+    ///
+    ///     Decodes an unsigned integer from its big endian representation.
+    ///
+    ///     Raises
+    ///     ------
+    ///     ValueError :
+    ///         If `buffer` is longer than 32 bytes.
+    ///
+    fn from_be_bytes(buffer: &[u8]) -> Result<Self, EthereumException>;
+
+    ///
+    ///     Converts this 256-bit unsigned integer into a 32-byte sequence,
+    ///     like `u256_to_be_bytes32`.
+    ///
+    fn to_be_bytes32(&self) -> Bytes32;
+
+    /// `self + right`, modulo `2 ** 256`.
+    fn wrapping_add(&self, right: &U256) -> U256;
+
+    /// `self - right`, modulo `2 ** 256`.
+    fn wrapping_sub(&self, right: &U256) -> U256;
+
+    /// `self * right`, modulo `2 ** 256`.
+    fn wrapping_mul(&self, right: &U256) -> U256;
+
+    /// `self ** exponent`, or `None` if it doesn't fit in 256 bits.
+    fn checked_pow(&self, exponent: &U256) -> Option<U256>;
+
+    /// The low 256 bits of an unsigned integer, which is how a `Uint`
+    /// becomes a `U256`.
+    fn low_u256(&self) -> U256;
+
+    /// The low 64 bits.
+    fn low_u64(&self) -> U64;
+
+    /// The value, or `u64::MAX` if it is larger.
+    fn saturating_to_u64(&self) -> U64;
+
+    /// The value, or `usize::MAX` if it is larger.
+    fn saturating_to_usize(&self) -> usize;
+}
+
+impl U256Ext for BigUint {
+    fn from_be_bytes(buffer: &[u8]) -> Result<Self, EthereumException> {
+        if buffer.len() > 32 {
+            return Err(EthereumException::ValueError);
+        }
+        Ok(BigUint::from_bytes_be(buffer))
+    }
+
+    fn to_be_bytes32(&self) -> Bytes32 {
+        u256_to_be_bytes32(self)
+    }
+
+    fn wrapping_add(&self, right: &U256) -> U256 {
+        (self + right).low_u256()
+    }
+
+    fn wrapping_sub(&self, right: &U256) -> U256 {
+        (self + U256_CEIL_VALUE() - right).low_u256()
+    }
+
+    fn wrapping_mul(&self, right: &U256) -> U256 {
+        (self * right).low_u256()
+    }
+
+    fn checked_pow(&self, exponent: &U256) -> Option<U256> {
+        if self <= &U256::from(1u8) {
+            return Some(if exponent.bits() == 0 { U256::from(1u8) } else { self.clone() });
+        }
+        // Any base of two or more overflows past an exponent of 255.
+        let exponent = u32::try_from(exponent).ok().filter(|exponent| *exponent < 256)?;
+        Some(self.pow(exponent)).filter(|result| result.bits() <= 256)
+    }
+
+    fn low_u256(&self) -> U256 {
+        if self.bits() <= 256 {
+            return self.clone();
+        }
+        self % U256_CEIL_VALUE()
+    }
+
+    fn low_u64(&self) -> U64 {
+        self.iter_u64_digits().next().unwrap_or(0)
+    }
+
+    fn saturating_to_u64(&self) -> U64 {
+        U64::try_from(self).unwrap_or(U64::MAX)
+    }
+
+    fn saturating_to_usize(&self) -> usize {
+        usize::try_from(self).unwrap_or(usize::MAX)
+    }
+}
@@ -0,0 +1,442 @@
+//!
+//! # Clique
+//!
+//! ## Introduction
+//!
+//! Clique (EIP-225) seals the blocks of proof of authority networks such as
+//! Görli and Rinkeby, and of many devnets, by the signature of one of a set
+//! of authorized signers rather than by proof of work.
+//!
+//! The signature fills the last 65 bytes of the extra data of a header, after
+//! 32 bytes of vanity. A signer signs in turn when the block number modulo
+//! the number of signers is its position among the sorted signers, and gives
+//! the block a difficulty of 2 then, and 1 otherwise. A signer may sign only
+//! one of any `signers / 2 + 1` consecutive blocks.
+//!
+//! Signers vote on adding or removing an account by naming it as the
+//! coinbase of their block, with a nonce of all ones to add it and of zeros
+//! to remove it. A vote passes once more than half of the signers cast it.
+//! Every `epoch` blocks a checkpoint drops the pending votes and lists the
+//! signers in its extra data, between the vanity and the seal.
+//!
+//! A [Snapshot] holds the signers and votes after a block and checks the
+//! header of the next one. The rules of the forks are checked apart: the
+//! Frontier rules expect a proof of work difficulty, so a Clique chain can't
+//! be run by them.
+//!
+
+use crate::prelude::*;
+use alloc::collections::{BTreeMap, BTreeSet};
+
+use crate::ethereum::{
+    base_types::{u256_to_be_bytes32, Bytes, Bytes32, Bytes8, U256},
+    crypto::elliptic_curve::{secp256k1_recover, secp256k1_sign},
+    exceptions::EthereumException,
+    frontier::fork_types::{keccak256, Address, Hash32, Header},
+    rlp::{self, RLP},
+    utils::ensure::ensure,
+};
+
+/// Bytes of the extra data before the signers and the seal.
+pub const EXTRA_VANITY: usize = 32;
+/// Bytes of the extra data taken by the seal.
+pub const EXTRA_SEAL: usize = 65;
+/// The nonce of a vote to add the coinbase to the signers.
+pub const NONCE_AUTH: Bytes8 = [0xff; 8];
+/// The nonce of a vote to remove the coinbase from the signers.
+pub const NONCE_DROP: Bytes8 = [0; 8];
+/// The difficulty of a block signed in turn.
+pub const DIFF_IN_TURN: u8 = 2;
+/// The difficulty of a block signed out of turn.
+pub const DIFF_NO_TURN: u8 = 1;
+
+///
+///     Whether a block is a checkpoint, which resets the votes and lists the
+///     signers.
+///
+pub fn is_checkpoint(number: u64, epoch: u64) -> bool {
+    number.is_multiple_of(epoch)
+}
+
+fn header_number(header: &Header) -> Result<u64, EthereumException> {
+    u64::try_from(&header.number).map_err(|_| EthereumException::InvalidBlock)
+}
+
+///
+///     Compute the hash a signer signs: the hash of the header without its
+///     seal.
+///
+///     Parameters
+///     ----------
+///     header :
+///         Header to hash.
+///
+///     Returns
+///     -------
+///     hash : `Hash32`
+///         The hash of the header with the last 65 bytes of its extra data
+///         left out.
+///
+///     Raises
+///     ------
+///     InvalidBlock :
+///         If the extra data has no room for the vanity and the seal.
+///
+pub fn seal_hash(header: &Header) -> Result<Hash32, EthereumException> {
+    let length = header.extra_data.len();
+    ensure(length >= EXTRA_VANITY + EXTRA_SEAL, EthereumException::InvalidBlock)?;
+    let extra_data: Bytes = header.extra_data[..length - EXTRA_SEAL].into();
+    // Tuples only implement `RLP` up to 12 fields.
+    let fields: [&dyn RLP; 15] = [
+        &header.parent_hash,
+        &header.ommers_hash,
+        &header.coinbase,
+        &header.state_root,
+        &header.transactions_root,
+        &header.receipt_root,
+        &header.bloom,
+        &header.difficulty,
+        &header.number,
+        &header.gas_limit,
+        &header.gas_used,
+        &header.timestamp,
+        &extra_data,
+        &header.mix_digest,
+        &header.nonce,
+    ];
+    Ok(keccak256(&rlp::encode_iter(fields)))
+}
+
+///
+///     Recover the signer of a header from its seal.
+///
+///     Parameters
+///     ----------
+///     header :
+///         Header sealed by a signer.
+///
+///     Returns
+///     -------
+///     signer : `ethereum.fork_types.Address`
+///         The address whose key signed the header.
+///
+///     Raises
+///     ------
+///     InvalidBlock :
+///         If the header has no seal, or its seal isn't a valid signature.
+///
+pub fn signer(header: &Header) -> Result<Address, EthereumException> {
+    let hash = seal_hash(header)?;
+    let seal = &header.extra_data[header.extra_data.len() - EXTRA_SEAL..];
+    let r = U256::from_bytes_be(&seal[..32]);
+    let s = U256::from_bytes_be(&seal[32..64]);
+    let v = U256::from(seal[64]);
+    let public_key = secp256k1_recover(&r, &s, &v, &hash).map_err(|_| EthereumException::InvalidBlock)?;
+    Ok(keccak256(&public_key)[12..32].try_into().unwrap())
+}
+
+///
+///     Sign a header, writing the seal over the last 65 bytes of its extra
+///     data.
+///
+///     Parameters
+///     ----------
+///     header :
+///         Header to seal, whose extra data has room for the vanity and the
+///         seal.
+///     private_key :
+///         The key of the signer.
+///
+pub fn seal_header(header: &mut Header, private_key: &Bytes32) -> Result<(), EthereumException> {
+    let hash = seal_hash(header)?;
+    let (r, s, v) = secp256k1_sign(&hash, private_key)?;
+    let length = header.extra_data.len();
+    let seal = &mut header.extra_data[length - EXTRA_SEAL..];
+    seal[..32].copy_from_slice(&u256_to_be_bytes32(&r));
+    seal[32..64].copy_from_slice(&u256_to_be_bytes32(&s));
+    seal[64] = u8::try_from(&v).expect("the recovery id is 0 or 1");
+    Ok(())
+}
+
+///
+///     Read the signers a checkpoint lists in its extra data.
+///
+///     Parameters
+///     ----------
+///     header :
+///         A checkpoint, or the genesis block.
+///
+///     Returns
+///     -------
+///     signers : `Vec<ethereum.fork_types.Address>`
+///         The signers, in the order listed.
+///
+///     Raises
+///     ------
+///     InvalidBlock :
+///         If the extra data between the vanity and the seal isn't a list of
+///         addresses.
+///
+pub fn checkpoint_signers(header: &Header) -> Result<Vec<Address>, EthereumException> {
+    let length = header.extra_data.len();
+    ensure(length >= EXTRA_VANITY + EXTRA_SEAL, EthereumException::InvalidBlock)?;
+    let signers = &header.extra_data[EXTRA_VANITY..length - EXTRA_SEAL];
+    ensure(signers.len().is_multiple_of(20), EthereumException::InvalidBlock)?;
+    Ok(signers.chunks(20).map(|signer| signer.try_into().unwrap()).collect())
+}
+
+/// A vote cast by a signer, pending until it passes or a checkpoint.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Vote {
+    pub signer: Address,
+    pub block: u64,
+    pub address: Address,
+    pub authorize: bool,
+}
+
+/// The votes pending on one account.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Tally {
+    authorize: bool,
+    votes: usize,
+}
+
+///
+///     The signers of a Clique chain and their votes after a block.
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Snapshot {
+    /// The seconds between blocks.
+    pub period: u64,
+    /// The blocks between checkpoints.
+    pub epoch: u64,
+    /// The block of the snapshot.
+    pub number: u64,
+    /// The hash of the block of the snapshot.
+    pub hash: Hash32,
+    pub signers: BTreeSet<Address>,
+    /// The signer of each of the last blocks, by number.
+    pub recents: BTreeMap<u64, Address>,
+    /// The votes pending, in the order cast.
+    pub votes: Vec<Vote>,
+    tally: BTreeMap<Address, Tally>,
+}
+
+impl Snapshot {
+    ///
+    ///     Start from the genesis block, whose extra data lists the first
+    ///     signers.
+    ///
+    ///     Parameters
+    ///     ----------
+    ///     genesis :
+    ///         Header of the genesis block.
+    ///     period :
+    ///         The seconds between blocks.
+    ///     epoch :
+    ///         The blocks between checkpoints.
+    ///
+    pub fn from_genesis(genesis: &Header, period: u64, epoch: u64) -> Result<Self, EthereumException> {
+        ensure(epoch > 0, EthereumException::ValueError)?;
+        Ok(Self {
+            period,
+            epoch,
+            number: header_number(genesis)?,
+            hash: genesis.hash(),
+            signers: checkpoint_signers(genesis)?.into_iter().collect(),
+            recents: BTreeMap::new(),
+            votes: vec![],
+            tally: BTreeMap::new(),
+        })
+    }
+
+    ///
+    ///     Whether `signer` signs block `number` in turn.
+    ///
+    pub fn in_turn(&self, number: u64, signer: &Address) -> bool {
+        match self.signers.iter().position(|s| s == signer) {
+            Some(position) => number % self.signers.len() as u64 == position as u64,
+            None => false,
+        }
+    }
+
+    /// The number of consecutive blocks a signer may sign only one of.
+    fn signing_limit(&self) -> u64 {
+        self.signers.len() as u64 / 2 + 1
+    }
+
+    ///
+    ///     Check the header of the block after the snapshot.
+    ///
+    ///     Parameters
+    ///     ----------
+    ///     header :
+    ///         Header of the next block.
+    ///     parent :
+    ///         Header of the block of the snapshot.
+    ///
+    ///     Returns
+    ///     -------
+    ///     signer : `ethereum.fork_types.Address`
+    ///         The signer of the header.
+    ///
+    ///     Raises
+    ///     ------
+    ///     InvalidBlock :
+    ///         If the header breaks a rule of Clique, or isn't signed by a
+    ///         signer allowed to sign it.
+    ///
+    pub fn validate(&self, header: &Header, parent: &Header) -> Result<Address, EthereumException> {
+        let number = header_number(header)?;
+        ensure(number == self.number + 1, EthereumException::InvalidBlock)?;
+        ensure(header.parent_hash == self.hash, EthereumException::InvalidBlock)?;
+        ensure(parent.hash() == self.hash, EthereumException::InvalidBlock)?;
+        ensure(
+            header.timestamp >= &parent.timestamp + self.period,
+            EthereumException::InvalidBlock,
+        )?;
+
+        let checkpoint = is_checkpoint(number, self.epoch);
+        ensure(
+            header.nonce == NONCE_AUTH || header.nonce == NONCE_DROP,
+            EthereumException::InvalidBlock,
+        )?;
+        if checkpoint {
+            ensure(header.coinbase == [0; 20], EthereumException::InvalidBlock)?;
+            ensure(header.nonce == NONCE_DROP, EthereumException::InvalidBlock)?;
+            let listed = checkpoint_signers(header)?;
+            ensure(listed.iter().eq(self.signers.iter()), EthereumException::InvalidBlock)?;
+        } else {
+            ensure(
+                header.extra_data.len() == EXTRA_VANITY + EXTRA_SEAL,
+                EthereumException::InvalidBlock,
+            )?;
+        }
+        ensure(header.mix_digest == [0; 32], EthereumException::InvalidBlock)?;
+        ensure(
+            header.ommers_hash == rlp::rlp_hash::<[Header]>(&[]),
+            EthereumException::InvalidBlock,
+        )?;
+
+        let signer = signer(header)?;
+        ensure(self.signers.contains(&signer), EthereumException::InvalidBlock)?;
+        let limit = self.signing_limit();
+        for (&seen, recent) in &self.recents {
+            ensure(
+                *recent != signer || seen + limit <= number,
+                EthereumException::InvalidBlock,
+            )?;
+        }
+        let difficulty = match self.in_turn(number, &signer) {
+            true => DIFF_IN_TURN,
+            false => DIFF_NO_TURN,
+        };
+        ensure(header.difficulty == difficulty.into(), EthereumException::InvalidBlock)?;
+        Ok(signer)
+    }
+
+    ///
+    ///     Check the header of the next block and move the snapshot to it,
+    ///     counting its vote.
+    ///
+    ///     Parameters
+    ///     ----------
+    ///     header :
+    ///         Header of the next block.
+    ///     parent :
+    ///         Header of the block of the snapshot.
+    ///
+    ///     Raises
+    ///     ------
+    ///     InvalidBlock :
+    ///         If the header isn't valid, see [Snapshot::validate]. The
+    ///         snapshot is left as it was.
+    ///
+    pub fn apply(&mut self, header: &Header, parent: &Header) -> Result<(), EthereumException> {
+        let signer = self.validate(header, parent)?;
+        let number = self.number + 1;
+        if is_checkpoint(number, self.epoch) {
+            self.votes.clear();
+            self.tally.clear();
+        }
+        if let Some(expired) = number.checked_sub(self.signing_limit()) {
+            self.recents.remove(&expired);
+        }
+        self.recents.insert(number, signer);
+
+        // A signer has one vote on each account, its last.
+        let coinbase = header.coinbase;
+        if let Some(index) = self
+            .votes
+            .iter()
+            .position(|vote| vote.signer == signer && vote.address == coinbase)
+        {
+            let vote = self.votes.remove(index);
+            self.uncast(&vote.address, vote.authorize);
+        }
+        let authorize = header.nonce == NONCE_AUTH;
+        if self.cast(coinbase, authorize) {
+            self.votes.push(Vote {
+                signer,
+                block: number,
+                address: coinbase,
+                authorize,
+            });
+        }
+
+        let passed = self
+            .tally
+            .get(&coinbase)
+            .filter(|tally| tally.votes > self.signers.len() / 2)
+            .copied();
+        if let Some(tally) = passed {
+            if tally.authorize {
+                self.signers.insert(coinbase);
+            } else {
+                self.signers.remove(&coinbase);
+                // One signer fewer may let another sign sooner.
+                if let Some(expired) = number.checked_sub(self.signing_limit()) {
+                    self.recents.remove(&expired);
+                }
+                let (dropped, kept) = self.votes.drain(..).partition(|vote| vote.signer == coinbase);
+                self.votes = kept;
+                for vote in dropped {
+                    self.uncast(&vote.address, vote.authorize);
+                }
+            }
+            self.votes.retain(|vote| vote.address != coinbase);
+            self.tally.remove(&coinbase);
+        }
+
+        self.number = number;
+        self.hash = header.hash();
+        Ok(())
+    }
+
+    /// Count a vote, if it would change the signers.
+    fn cast(&mut self, address: Address, authorize: bool) -> bool {
+        if self.signers.contains(&address) == authorize {
+            return false;
+        }
+        self.tally
+            .entry(address)
+            .and_modify(|tally| tally.votes += 1)
+            .or_insert(Tally { authorize, votes: 1 });
+        true
+    }
+
+    /// Take back a vote counted by `cast`.
+    fn uncast(&mut self, address: &Address, authorize: bool) {
+        let Some(tally) = self.tally.get_mut(address) else {
+            return;
+        };
+        if tally.authorize != authorize {
+            return;
+        }
+        if tally.votes > 1 {
+            tally.votes -= 1;
+        } else {
+            self.tally.remove(address);
+        }
+    }
+}
@@ -0,0 +1,193 @@
+//!
+//! # Create Policy
+//!
+//! ## Introduction
+//!
+//! The rules a contract creation follows, whichever way it is made: by
+//! `CREATE` or by a transaction without a recipient.
+//!
+//! A creation pays [FRONTIER]'s `code_deposit` for each byte of code it
+//! deposits, and one left without the gas for that still succeeds, with no
+//! code. A [CreatePolicy] can also describe the rules later forks added:
+//! failing such a creation (EIP-2), capping the size of the code deposited
+//! (EIP-170) and of the initcode run (EIP-3860), rejecting code starting
+//! with `0xEF` (EIP-3541), and deploying the valid containers of the
+//! experimental `ethereum::eof`.
+//!
+
+use crate::ethereum::base_types::Uint;
+use crate::ethereum::hard_fork::HardFork;
+
+/// The rules of contract creation of a fork.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CreatePolicy {
+    /// Cost per byte of deployed code.
+    pub code_deposit: u64,
+    /// Whether a creation without the gas for its code deposit fails,
+    /// rather than creating the contract without code.
+    pub deposit_out_of_gas_fails: bool,
+    /// The largest code a creation can deposit.
+    pub max_code_size: Option<u64>,
+    /// The largest initcode a creation can run.
+    pub max_initcode_size: Option<u64>,
+    /// Whether a creation fails if its code starts with `0xEF`.
+    pub reject_ef_prefix: bool,
+    /// Whether a valid EOF container is deployed all the same. Needs the
+    /// `eof` feature.
+    pub accept_eof: bool,
+}
+
+/// Frontier.
+pub const FRONTIER: CreatePolicy = CreatePolicy {
+    code_deposit: 200,
+    deposit_out_of_gas_fails: false,
+    max_code_size: None,
+    max_initcode_size: None,
+    reject_ef_prefix: false,
+    accept_eof: false,
+};
+
+///
+///     Get the contract creation rules of a fork.
+///
+///     Parameters
+///     ----------
+///     fork :
+///         The fork.
+///
+///     Returns
+///     -------
+///     policy : `CreatePolicy`
+///         The contract creation rules of the fork.
+///
+pub fn create_policy(fork: HardFork) -> &'static CreatePolicy {
+    match fork {
+        HardFork::Frontier => &FRONTIER,
+    }
+}
+
+/// Why a creation fails after its initcode ran.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CreateFailure {
+    /// There isn't the gas for the code deposit, or the code is over the
+    /// maximum size.
+    OutOfGas,
+    /// The code starts with `0xEF`, EIP-3541.
+    InvalidContractPrefix,
+}
+
+/// What becomes of the code a creation returns.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CodeDeposit {
+    /// Charge the gas and store the code.
+    Store(Uint),
+    /// Store no code and charge nothing, but let the creation succeed.
+    Empty,
+    /// Fail the creation.
+    Fail(CreateFailure),
+}
+
+///
+///     Decide whether the code returned by a creation is deposited.
+///
+///     The checks are made in the order of the specification, which decides
+///     the failure reported: the prefix, then the gas, then the size.
+///
+///     Parameters
+///     ----------
+///     policy :
+///         The rules of the fork.
+///     code :
+///         The code returned by the initcode.
+///     gas_left :
+///         Gas left to the creation after running the initcode.
+///
+///     Returns
+///     -------
+///     deposit : `CodeDeposit`
+///         The code deposit, or what happens instead.
+///
+pub fn code_deposit(policy: &CreatePolicy, code: &[u8], gas_left: &Uint) -> CodeDeposit {
+    if !check_code_prefix(policy, code) {
+        return CodeDeposit::Fail(CreateFailure::InvalidContractPrefix);
+    }
+    let cost = Uint::from(code.len()) * policy.code_deposit;
+    if cost > *gas_left {
+        return if policy.deposit_out_of_gas_fails {
+            CodeDeposit::Fail(CreateFailure::OutOfGas)
+        } else {
+            CodeDeposit::Empty
+        };
+    }
+    if !check_code_size(policy, code.len()) {
+        // EIP-170 reports an oversized code as running out of gas.
+        return CodeDeposit::Fail(CreateFailure::OutOfGas);
+    }
+    CodeDeposit::Store(cost)
+}
+
+///
+///     Check the first byte of the code a creation deposits.
+///
+///     Parameters
+///     ----------
+///     policy :
+///         The rules of the fork.
+///     code :
+///         The code.
+///
+///     Returns
+///     -------
+///     valid : `bool`
+///         False if the fork rejects the code for starting with `0xEF`.
+///
+pub fn check_code_prefix(policy: &CreatePolicy, code: &[u8]) -> bool {
+    if !policy.reject_ef_prefix || code.first() != Some(&0xef) {
+        return true;
+    }
+    #[cfg(feature = "eof")]
+    if policy.accept_eof {
+        return crate::ethereum::eof::validation::validate(code).is_ok();
+    }
+    false
+}
+
+///
+///     Check the size of the code a creation deposits.
+///
+///     Parameters
+///     ----------
+///     policy :
+///         The rules of the fork.
+///     code_size :
+///         Length of the code.
+///
+///     Returns
+///     -------
+///     valid : `bool`
+///         False if the code is over the fork's maximum.
+///
+pub fn check_code_size(policy: &CreatePolicy, code_size: usize) -> bool {
+    policy.max_code_size.is_none_or(|max| code_size as u64 <= max)
+}
+
+///
+///     Check the size of the initcode of a creating transaction or of
+///     `CREATE`. A transaction over the limit is invalid, a `CREATE` fails
+///     as if it ran out of gas.
+///
+///     Parameters
+///     ----------
+///     policy :
+///         The rules of the fork.
+///     initcode_size :
+///         Length of the initcode.
+///
+///     Returns
+///     -------
+///     valid : `bool`
+///         False if the initcode is over the fork's maximum.
+///
+pub fn check_initcode_size(policy: &CreatePolicy, initcode_size: usize) -> bool {
+    policy.max_initcode_size.is_none_or(|max| initcode_size as u64 <= max)
+}
@@ -0,0 +1,121 @@
+//!
+//! # Elliptic Curves
+//!
+//! Signing messages, and recovery of the public key that signed one.
+//!
+
+use k256::ecdsa::{RecoveryId, Signature, SigningKey, VerifyingKey};
+
+use crate::ethereum::{
+    base_types::{u256_to_be_bytes32, Bytes32, Bytes64, U256},
+    exceptions::EthereumException,
+};
+
+/// The order of the secp256k1 curve.
+#[allow(non_snake_case)]
+pub fn SECP256K1N() -> U256 {
+    U256::from_bytes_be(&hex_literal::hex!(
+        "fffffffffffffffffffffffffffffffebaaedce6af48a03bbfd25e8cd0364141"
+    ))
+}
+
+///
+///     Recovers the public key from a given signature.
+///
+///     Parameters
+///     ----------
+///     r :
+///         The `r` value of the signature.
+///     s :
+///         The `s` value of the signature.
+///     v :
+///         The recovery id, the parity of the `y` coordinate of the curve point
+///         for `r` (`0` or `1`).
+///     msg_hash :
+///         Hash of the message being recovered.
+///
+///     Returns
+///     -------
+///     public_key : `ethereum.base_types.Bytes64`
+///         Recovered public key, without the `0x04` prefix.
+///
+pub fn secp256k1_recover(
+    r: &U256,
+    s: &U256,
+    v: &U256,
+    msg_hash: &Bytes32,
+) -> Result<Bytes64, EthereumException> {
+    let invalid = |_| EthereumException::InvalidSignature;
+    let mut signature =
+        Signature::from_scalars(u256_to_be_bytes32(r), u256_to_be_bytes32(s)).map_err(invalid)?;
+    let v = u8::try_from(v).map_err(|_| EthereumException::InvalidSignature)?;
+    let mut recovery_id = RecoveryId::from_byte(v).ok_or(EthereumException::InvalidSignature)?;
+
+    // `k256` only accepts signatures with a low `s`. Negating `s` mirrors
+    // the point `R`, so the same key is recovered with the other parity.
+    if let Some(normalized) = signature.normalize_s() {
+        signature = normalized;
+        recovery_id = RecoveryId::new(!recovery_id.is_y_odd(), recovery_id.is_x_reduced());
+    }
+
+    let key = VerifyingKey::recover_from_prehash(msg_hash, &signature, recovery_id).map_err(invalid)?;
+    let point = key.to_encoded_point(false);
+    Ok(point.as_bytes()[1..].try_into().unwrap())
+}
+
+///
+///     Signs a message hash with a private key.
+///
+///     The signature has a low `s`, as EIP-2 requires from Homestead on.
+///
+///     Parameters
+///     ----------
+///     msg_hash :
+///         Hash of the message to sign.
+///     private_key :
+///         The private key, a big endian scalar.
+///
+///     Returns
+///     -------
+///     r : `ethereum.base_types.U256`
+///         The `r` value of the signature.
+///     s : `ethereum.base_types.U256`
+///         The `s` value of the signature.
+///     v : `ethereum.base_types.U256`
+///         The recovery id, `0` or `1`.
+///
+pub fn secp256k1_sign(msg_hash: &Bytes32, private_key: &Bytes32) -> Result<(U256, U256, U256), EthereumException> {
+    let key = SigningKey::from_slice(private_key).map_err(|_| EthereumException::ValueError)?;
+    let (signature, recovery_id) =
+        key.sign_prehash_recoverable(msg_hash).map_err(|_| EthereumException::ValueError)?;
+    let (r, s) = signature.split_bytes();
+    Ok((
+        U256::from_bytes_be(&r),
+        U256::from_bytes_be(&s),
+        U256::from(recovery_id.to_byte()),
+    ))
+}
+
+///
+///     Derives the public key of a private key.
+///
+///     Parameters
+///     ----------
+///     private_key :
+///         The private key, a big endian scalar.
+///
+///     Returns
+///     -------
+///     public_key : `ethereum.base_types.Bytes64`
+///         The public key, without the `0x04` prefix.
+///
+///     Raises
+///     ------
+///     ValueError :
+///         If the key is zero or not below the order of the curve.
+///
+pub fn secp256k1_public_key(private_key: &Bytes32) -> Result<Bytes64, EthereumException> {
+    let key = SigningKey::from_slice(private_key).map_err(|_| EthereumException::ValueError)?;
+    let point = key.verifying_key().to_encoded_point(false);
+    Ok(point.as_bytes()[1..].try_into().unwrap())
+}
@@ -0,0 +1,194 @@
+//!
+//! # Cryptographic Hash Functions
+//!
+//! Keccak, the hash used for addresses, code hashes, trie nodes and the keys
+//! of secured tries. By default it is computed with `tiny-keccak`; the
+//! `asm-keccak` feature swaps in the assembly implementations of XKCP, which
+//! use the SIMD and SHA-3 instructions of the CPU when it has them.
+//!
+//! SHA-256, for the precompiled contract at address 2 and for the code of
+//! forks that hashes with it, is computed with `sha2`, and RIPEMD-160, for
+//! the precompiled contract at address 3, with `ripemd`.
+//!
+
+use crate::prelude::*;
+
+pub type Hash20 = [u8; 20];
+pub type Hash32 = [u8; 32];
+pub type Hash64 = [u8; 64];
+
+/// Below this many inputs a batch is hashed on the calling thread, as
+/// handing the work out costs more than it saves.
+#[cfg(feature = "parallel")]
+const PARALLEL_BATCH_MINIMUM: usize = 1024;
+
+///
+///     Computes the keccak256 hash of the input `buffer`.
+///
+///     Parameters
+///     ----------
+///     buffer :
+///         Input for the hashing function.
+///
+///     Returns
+///     -------
+///     hash : `ethereum.crypto.hash.Hash32`
+///         Output of the hash function.
+///
+#[cfg(not(feature = "asm-keccak"))]
+pub fn keccak256(buffer: &[u8]) -> Hash32 {
+    use tiny_keccak::{Hasher, Keccak};
+
+    let mut buf = [0u8; 32];
+    let mut hasher = Keccak::v256();
+    hasher.update(buffer);
+    hasher.finalize(&mut buf);
+    buf
+}
+
+#[cfg(feature = "asm-keccak")]
+pub fn keccak256(buffer: &[u8]) -> Hash32 {
+    use keccak_asm::Keccak256;
+
+    Keccak256::digest(buffer).into()
+}
+
+///
+///     Computes the keccak512 hash of the input `buffer`.
+///
+///     Parameters
+///     ----------
+///     buffer :
+///         Input for the hashing function.
+///
+///     Returns
+///     -------
+///     hash : `ethereum.crypto.hash.Hash64`
+///         Output of the hash function.
+///
+#[cfg(not(feature = "asm-keccak"))]
+pub fn keccak512(buffer: &[u8]) -> Hash64 {
+    use tiny_keccak::{Hasher, Keccak};
+
+    let mut buf = [0u8; 64];
+    let mut hasher = Keccak::v512();
+    hasher.update(buffer);
+    hasher.finalize(&mut buf);
+    buf
+}
+
+#[cfg(feature = "asm-keccak")]
+pub fn keccak512(buffer: &[u8]) -> Hash64 {
+    use keccak_asm::Keccak512;
+
+    Keccak512::digest(buffer).into()
+}
+
+///
+///     Computes the keccak256 hash of each of `buffers`, such as all the keys
+///     of a secured trie.
+///
+///     With the `parallel` feature, large batches are split across all cores.
+///
+///     Parameters
+///     ----------
+///     buffers :
+///         Inputs for the hashing function.
+///
+///     Returns
+///     -------
+///     hashes : `Vec<ethereum.crypto.hash.Hash32>`
+///         The hash of each input, in the same order.
+///
+pub fn keccak256_batch<T: AsRef<[u8]> + Sync>(buffers: &[T]) -> Vec<Hash32> {
+    #[cfg(feature = "parallel")]
+    if buffers.len() >= PARALLEL_BATCH_MINIMUM {
+        use rayon::prelude::*;
+        return buffers.par_iter().map(|buffer| keccak256(buffer.as_ref())).collect();
+    }
+    buffers.iter().map(|buffer| keccak256(buffer.as_ref())).collect()
+}
+
+///
+///     Computes the SHA-256 hash of the input `buffer`.
+///
+///     Parameters
+///     ----------
+///     buffer :
+///         Input for the hashing function.
+///
+///     Returns
+///     -------
+///     hash : `ethereum.crypto.hash.Hash32`
+///         Output of the hash function.
+///
+pub fn sha256(buffer: &[u8]) -> Hash32 {
+    use sha2::Digest;
+
+    sha2::Sha256::digest(buffer).into()
+}
+
+/// A SHA-256 hash computed over input given in parts, such as the fields of
+/// a structure, without joining them first.
+#[derive(Clone, Default)]
+pub struct Sha256(sha2::Sha256);
+
+impl Sha256 {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Hash `buffer` after the input given so far.
+    pub fn update(&mut self, buffer: &[u8]) -> &mut Self {
+        sha2::Digest::update(&mut self.0, buffer);
+        self
+    }
+
+    /// The hash of all the input.
+    pub fn finalize(self) -> Hash32 {
+        sha2::Digest::finalize(self.0).into()
+    }
+}
+
+///
+///     Computes the SHA-256 hash of each of `buffers`.
+///
+///     With the `parallel` feature, large batches are split across all cores.
+///
+///     Parameters
+///     ----------
+///     buffers :
+///         Inputs for the hashing function.
+///
+///     Returns
+///     -------
+///     hashes : `Vec<ethereum.crypto.hash.Hash32>`
+///         The hash of each input, in the same order.
+///
+pub fn sha256_batch<T: AsRef<[u8]> + Sync>(buffers: &[T]) -> Vec<Hash32> {
+    #[cfg(feature = "parallel")]
+    if buffers.len() >= PARALLEL_BATCH_MINIMUM {
+        use rayon::prelude::*;
+        return buffers.par_iter().map(|buffer| sha256(buffer.as_ref())).collect();
+    }
+    buffers.iter().map(|buffer| sha256(buffer.as_ref())).collect()
+}
+
+///
+///     Computes the RIPEMD-160 hash of the input `buffer`.
+///
+///     Parameters
+///     ----------
+///     buffer :
+///         Input for the hashing function.
+///
+///     Returns
+///     -------
+///     hash : `ethereum.crypto.hash.Hash20`
+///         Output of the hash function.
+///
+pub fn ripemd160(buffer: &[u8]) -> Hash20 {
+    use ripemd::Digest;
+
+    ripemd::Ripemd160::digest(buffer).into()
+}
@@ -0,0 +1,8 @@
+//!
+//! # Cryptographic Functions
+//!
+//! Cryptographic primitives used in Ethereum.
+//!
+
+pub mod elliptic_curve;
+pub mod hash;
@@ -0,0 +1,220 @@
+//!
+//! # EOF Container
+//!
+//! ## Introduction
+//!
+//! The layout of a container, EIP-3540 with the subcontainers of EIP-7620:
+//!
+//! ```text
+//! magic    0xEF00
+//! version  0x01
+//! 0x01 types_size:u16
+//! 0x02 code_count:u16 code_size:u16...
+//! 0x03 container_count:u16 container_size:u32...    (optional)
+//! 0xFF data_size:u16
+//! 0x00
+//! types    (inputs:u8 outputs:u8 max_stack_height:u16)...
+//! code sections, subcontainers, data
+//! ```
+//!
+//! Parsing checks the layout and the bounds of the types, not the code.
+//!
+
+use crate::prelude::*;
+use super::{exceptions::EofError, MAGIC, VERSION};
+use crate::ethereum::{base_types::Bytes, utils::ensure::ensure};
+
+const KIND_TYPES: u8 = 0x01;
+const KIND_CODE: u8 = 0x02;
+const KIND_CONTAINER: u8 = 0x03;
+const KIND_DATA: u8 = 0xff;
+const TERMINATOR: u8 = 0x00;
+
+/// The most code sections a container has.
+pub const MAX_CODE_SECTIONS: usize = 1024;
+/// The most subcontainers a container has.
+pub const MAX_CONTAINER_SECTIONS: usize = 256;
+/// The most items a function may take or return.
+pub const MAX_INPUTS_OUTPUTS: u8 = 0x7f;
+/// The `outputs` of a function that never returns, EIP-6206.
+pub const NON_RETURNING: u8 = 0x80;
+/// The highest stack a function may use.
+pub const MAX_STACK_HEIGHT: u16 = 1023;
+
+/// The type of a code section.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TypeSection {
+    /// Items the function takes from the stack.
+    pub inputs: u8,
+    /// Items it leaves in their place, or [NON_RETURNING].
+    pub outputs: u8,
+    /// The most items on the stack while it runs, its inputs included.
+    pub max_stack_height: u16,
+}
+
+impl TypeSection {
+    /// Whether the function returns to its caller.
+    pub fn returns(&self) -> bool {
+        self.outputs != NON_RETURNING
+    }
+}
+
+/// A parsed container.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Container {
+    /// The type of each code section.
+    pub types: Vec<TypeSection>,
+    pub code: Vec<Bytes>,
+    pub containers: Vec<Bytes>,
+    pub data: Bytes,
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize, error: EofError) -> Result<&'a [u8], EofError> {
+        let bytes = self.bytes.get(self.pos..self.pos + len).ok_or(error)?;
+        self.pos += len;
+        Ok(bytes)
+    }
+
+    fn u8(&mut self) -> Result<u8, EofError> {
+        Ok(self.take(1, EofError::InvalidHeader)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, EofError> {
+        let bytes = self.take(2, EofError::InvalidHeader)?;
+        Ok(u16::from_be_bytes([bytes[0], bytes[1]]))
+    }
+
+    fn u32(&mut self) -> Result<u32, EofError> {
+        let bytes = self.take(4, EofError::InvalidHeader)?;
+        Ok(u32::from_be_bytes(bytes.try_into().unwrap()))
+    }
+
+    fn expect(&mut self, kind: u8) -> Result<(), EofError> {
+        ensure(self.u8()? == kind, EofError::InvalidHeader)
+    }
+
+    /// Read a section count and that many non-zero sizes.
+    fn sizes(&mut self, max_count: usize, wide: bool) -> Result<Vec<usize>, EofError> {
+        let count = usize::from(self.u16()?);
+        ensure((1..=max_count).contains(&count), EofError::InvalidHeader)?;
+        (0..count)
+            .map(|_| {
+                let size = if wide { self.u32()? as usize } else { usize::from(self.u16()?) };
+                ensure(size > 0, EofError::InvalidHeader)?;
+                Ok(size)
+            })
+            .collect()
+    }
+}
+
+///
+///     Parse a container.
+///
+///     Parameters
+///     ----------
+///     code :
+///         The bytes of the container.
+///
+///     Returns
+///     -------
+///     container : `Result<Container, EofError>`
+///         The container, or how its layout or types are invalid.
+///
+pub fn parse(code: &[u8]) -> Result<Container, EofError> {
+    let mut reader = Reader { bytes: code, pos: 0 };
+    ensure(reader.take(2, EofError::InvalidMagic)? == MAGIC, EofError::InvalidMagic)?;
+    let version = reader.u8()?;
+    ensure(version == VERSION, EofError::UnsupportedVersion(version))?;
+
+    reader.expect(KIND_TYPES)?;
+    let types_size = usize::from(reader.u16()?);
+    reader.expect(KIND_CODE)?;
+    let code_sizes = reader.sizes(MAX_CODE_SECTIONS, false)?;
+    let container_sizes = match reader.u8()? {
+        KIND_CONTAINER => {
+            let sizes = reader.sizes(MAX_CONTAINER_SECTIONS, true)?;
+            reader.expect(KIND_DATA)?;
+            sizes
+        }
+        KIND_DATA => vec![],
+        _ => return Err(EofError::InvalidHeader),
+    };
+    let data_size = usize::from(reader.u16()?);
+    reader.expect(TERMINATOR)?;
+
+    ensure(types_size == 4 * code_sizes.len(), EofError::InvalidTypes)?;
+    let types: Vec<TypeSection> = reader
+        .take(types_size, EofError::InvalidSectionBodies)?
+        .chunks(4)
+        .map(|ty| TypeSection {
+            inputs: ty[0],
+            outputs: ty[1],
+            max_stack_height: u16::from_be_bytes([ty[2], ty[3]]),
+        })
+        .collect();
+    ensure(types[0].inputs == 0 && !types[0].returns(), EofError::InvalidTypes)?;
+    for ty in &types {
+        ensure(
+            ty.inputs <= MAX_INPUTS_OUTPUTS
+                && (ty.outputs <= MAX_INPUTS_OUTPUTS || !ty.returns())
+                && ty.max_stack_height <= MAX_STACK_HEIGHT,
+            EofError::InvalidTypes,
+        )?;
+    }
+
+    let mut section = |size| reader.take(size, EofError::InvalidSectionBodies).map(Bytes::from);
+    let code = code_sizes.into_iter().map(&mut section).collect::<Result<_, _>>()?;
+    let containers = container_sizes.into_iter().map(&mut section).collect::<Result<_, _>>()?;
+    let data = section(data_size)?;
+    ensure(reader.pos == reader.bytes.len(), EofError::InvalidSectionBodies)?;
+
+    Ok(Container {
+        types,
+        code,
+        containers,
+        data,
+    })
+}
+
+impl Container {
+    ///
+    ///     Serialize the container, the inverse of [parse].
+    ///
+    pub fn encode(&self) -> Vec<u8> {
+        let mut bytes = MAGIC.to_vec();
+        bytes.push(VERSION);
+        bytes.push(KIND_TYPES);
+        bytes.extend((4 * self.types.len() as u16).to_be_bytes());
+        bytes.push(KIND_CODE);
+        bytes.extend((self.code.len() as u16).to_be_bytes());
+        for code in &self.code {
+            bytes.extend((code.len() as u16).to_be_bytes());
+        }
+        if !self.containers.is_empty() {
+            bytes.push(KIND_CONTAINER);
+            bytes.extend((self.containers.len() as u16).to_be_bytes());
+            for container in &self.containers {
+                bytes.extend((container.len() as u32).to_be_bytes());
+            }
+        }
+        bytes.push(KIND_DATA);
+        bytes.extend((self.data.len() as u16).to_be_bytes());
+        bytes.push(TERMINATOR);
+
+        for ty in &self.types {
+            bytes.extend([ty.inputs, ty.outputs]);
+            bytes.extend(ty.max_stack_height.to_be_bytes());
+        }
+        for section in self.code.iter().chain(&self.containers) {
+            bytes.extend_from_slice(section);
+        }
+        bytes.extend_from_slice(&self.data);
+        bytes
+    }
+}
@@ -0,0 +1,100 @@
+//!
+//! # EOF Exceptions
+//!
+//! ## Introduction
+//!
+//! Why a container is malformed, or its code invalid. Code errors locate the
+//! instruction by its code section and offset in it.
+//!
+
+use core::fmt;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum EofError {
+    /// The code doesn't start with `0xEF00`.
+    InvalidMagic,
+    /// The version isn't 1.
+    UnsupportedVersion(u8),
+    /// A section header is missing, out of order, or declares no or too
+    /// many sections, or an empty one.
+    InvalidHeader,
+    /// The type section doesn't describe the code sections, or a type is out
+    /// of bounds.
+    InvalidTypes,
+    /// The sections are shorter or longer than the header says.
+    InvalidSectionBodies,
+    /// The opcode isn't defined in EOF code.
+    UndefinedInstruction { section: usize, pos: usize, opcode: u8 },
+    /// The immediate arguments run past the end of the code.
+    TruncatedInstruction { section: usize, pos: usize },
+    /// A relative jump lands outside the code or inside an instruction.
+    InvalidJumpDestination { section: usize, pos: usize },
+    /// `CALLF` or `JUMPF` name a section that doesn't exist.
+    InvalidCodeSectionIndex { section: usize, pos: usize },
+    /// `CALLF` names a section that doesn't return.
+    CallfToNonReturning { section: usize, pos: usize },
+    /// `DATALOADN` reads past the end of the data section.
+    InvalidDataloadnIndex { section: usize, pos: usize },
+    /// No path from the start of the section reaches the instruction.
+    UnreachableCode { section: usize, pos: usize },
+    /// The instruction may run with fewer items on the stack than it takes.
+    StackUnderflow { section: usize, pos: usize },
+    /// The instruction may overflow the stack in the function it calls.
+    StackOverflow { section: usize, pos: usize },
+    /// The stack height differs between the paths to the instruction in a
+    /// way that isn't allowed, or doesn't match what the section returns.
+    StackHeightMismatch { section: usize, pos: usize },
+    /// The type of the section doesn't give its maximum stack height.
+    InvalidMaxStackHeight { section: usize },
+    /// Execution can run past the end of the section.
+    NoTerminatingInstruction { section: usize },
+    /// The section returns without being declared returning, or the other
+    /// way round.
+    InvalidNonReturning { section: usize },
+    /// No `CALLF` or `JUMPF` chain from the first section reaches the
+    /// section.
+    UnreachableCodeSection { section: usize },
+    /// No instruction references the subcontainer.
+    UnreferencedSubcontainer { index: usize },
+}
+
+impl fmt::Display for EofError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EofError::InvalidMagic => write!(f, "invalid magic"),
+            EofError::UnsupportedVersion(version) => write!(f, "unsupported version {version}"),
+            EofError::InvalidHeader => write!(f, "invalid section headers"),
+            EofError::InvalidTypes => write!(f, "invalid type section"),
+            EofError::InvalidSectionBodies => write!(f, "section bodies don't match the headers"),
+            EofError::UndefinedInstruction { section, pos, opcode } => {
+                write!(f, "undefined instruction 0x{opcode:02x} at {section}:{pos}")
+            }
+            EofError::TruncatedInstruction { section, pos } => write!(f, "truncated instruction at {section}:{pos}"),
+            EofError::InvalidJumpDestination { section, pos } => {
+                write!(f, "invalid jump destination at {section}:{pos}")
+            }
+            EofError::InvalidCodeSectionIndex { section, pos } => {
+                write!(f, "invalid code section index at {section}:{pos}")
+            }
+            EofError::CallfToNonReturning { section, pos } => {
+                write!(f, "CALLF to a non-returning section at {section}:{pos}")
+            }
+            EofError::InvalidDataloadnIndex { section, pos } => write!(f, "invalid DATALOADN index at {section}:{pos}"),
+            EofError::UnreachableCode { section, pos } => write!(f, "unreachable code at {section}:{pos}"),
+            EofError::StackUnderflow { section, pos } => write!(f, "stack underflow at {section}:{pos}"),
+            EofError::StackOverflow { section, pos } => write!(f, "stack overflow at {section}:{pos}"),
+            EofError::StackHeightMismatch { section, pos } => write!(f, "stack height mismatch at {section}:{pos}"),
+            EofError::InvalidMaxStackHeight { section } => write!(f, "invalid max stack height of section {section}"),
+            EofError::NoTerminatingInstruction { section } => {
+                write!(f, "section {section} doesn't end in a terminating instruction")
+            }
+            EofError::InvalidNonReturning { section } => {
+                write!(f, "section {section} doesn't return as its type says")
+            }
+            EofError::UnreachableCodeSection { section } => write!(f, "unreachable code section {section}"),
+            EofError::UnreferencedSubcontainer { index } => write!(f, "unreferenced subcontainer {index}"),
+        }
+    }
+}
+
+impl core::error::Error for EofError {}
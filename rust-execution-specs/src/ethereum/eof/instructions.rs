@@ -0,0 +1,582 @@
+//!
+//! # EOF Instructions
+//!
+//! ## Introduction
+//!
+//! The instructions EOF adds, the stack effect of every instruction EOF
+//! code may use, and the implementations of the new ones. The Frontier
+//! instructions run as in legacy code.
+//!
+
+use super::interpreter::EofFrame;
+use crate::ethereum::{
+    base_types::{Uint, U256},
+    frontier::{
+        constants::STACK_SIZE_LIMIT,
+        vm::{
+            exceptions::{EvmError, Result},
+            gas::{self, charge_gas},
+            instructions::Ops,
+            memory::{buffer_read, memory_write},
+            stack, Evm,
+        },
+    },
+    utils::numeric::ceil32,
+};
+
+pub const DATALOAD: u8 = 0xd0;
+pub const DATALOADN: u8 = 0xd1;
+pub const DATASIZE: u8 = 0xd2;
+pub const DATACOPY: u8 = 0xd3;
+pub const RJUMP: u8 = 0xe0;
+pub const RJUMPI: u8 = 0xe1;
+pub const RJUMPV: u8 = 0xe2;
+pub const CALLF: u8 = 0xe3;
+pub const RETF: u8 = 0xe4;
+pub const JUMPF: u8 = 0xe5;
+pub const DUPN: u8 = 0xe6;
+pub const SWAPN: u8 = 0xe7;
+pub const EXCHANGE: u8 = 0xe8;
+/// The designated invalid instruction, which ends a section without running
+/// off its end.
+pub const INVALID: u8 = 0xfe;
+
+/// Paid by `RJUMPI` and `RJUMPV`.
+#[allow(non_snake_case)]
+pub fn GAS_CONDITIONAL_JUMP() -> Uint {
+    Uint::from(4u8)
+}
+
+/// Paid by `DATALOAD`.
+#[allow(non_snake_case)]
+pub fn GAS_DATALOAD() -> Uint {
+    Uint::from(4u8)
+}
+
+/// How an instruction is encoded and what it does to the stack.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Instruction {
+    pub inputs: u8,
+    pub outputs: u8,
+    /// Bytes of immediate arguments. Those of `RJUMPV` depend on its first
+    /// one, and aren't counted.
+    pub immediates: usize,
+    /// Whether execution ends or leaves the section at the instruction.
+    pub terminating: bool,
+}
+
+impl Instruction {
+    const fn new(inputs: u8, outputs: u8) -> Self {
+        Self {
+            inputs,
+            outputs,
+            immediates: 0,
+            terminating: false,
+        }
+    }
+
+    const fn immediates(self, immediates: usize) -> Self {
+        Self { immediates, ..self }
+    }
+
+    const fn terminating(self) -> Self {
+        Self {
+            terminating: true,
+            ..self
+        }
+    }
+}
+
+///
+///     Describe an instruction of EOF code.
+///
+///     The stack effects of `CALLF`, `RETF`, `JUMPF`, `DUPN`, `SWAPN` and
+///     `EXCHANGE` depend on their immediates, and are given as nothing.
+///
+///     Parameters
+///     ----------
+///     opcode :
+///         The opcode.
+///
+///     Returns
+///     -------
+///     instruction : `Option<Instruction>`
+///         The instruction, or `None` if it isn't defined in EOF code.
+///
+pub fn instruction(opcode: u8) -> Option<Instruction> {
+    let instruction = match opcode {
+        DATALOAD => Instruction::new(1, 1),
+        DATALOADN => Instruction::new(0, 1).immediates(2),
+        DATASIZE => Instruction::new(0, 1),
+        DATACOPY => Instruction::new(3, 0),
+        RJUMP => Instruction::new(0, 0).immediates(2),
+        RJUMPI => Instruction::new(1, 0).immediates(2),
+        RJUMPV => Instruction::new(1, 0),
+        CALLF => Instruction::new(0, 0).immediates(2),
+        RETF => Instruction::new(0, 0).terminating(),
+        JUMPF => Instruction::new(0, 0).immediates(2).terminating(),
+        DUPN | SWAPN | EXCHANGE => Instruction::new(0, 0).immediates(1),
+        INVALID => Instruction::new(0, 0).terminating(),
+        _ => frontier_instruction(Ops::try_from(opcode).ok()?)?,
+    };
+    Some(instruction)
+}
+
+fn frontier_instruction(op: Ops) -> Option<Instruction> {
+    let value = op as u8;
+    let instruction = match op {
+        Ops::STOP => Instruction::new(0, 0).terminating(),
+        Ops::RETURN => Instruction::new(2, 0).terminating(),
+        // `NOP` in EOF code.
+        Ops::JUMPDEST => Instruction::new(0, 0),
+        Ops::ADD
+        | Ops::MUL
+        | Ops::SUB
+        | Ops::DIV
+        | Ops::SDIV
+        | Ops::MOD
+        | Ops::SMOD
+        | Ops::EXP
+        | Ops::SIGNEXTEND
+        | Ops::LT
+        | Ops::GT
+        | Ops::SLT
+        | Ops::SGT
+        | Ops::EQ
+        | Ops::AND
+        | Ops::OR
+        | Ops::XOR
+        | Ops::BYTE
+        | Ops::KECCAK => Instruction::new(2, 1),
+        Ops::ADDMOD | Ops::MULMOD => Instruction::new(3, 1),
+        Ops::ISZERO | Ops::NOT | Ops::BALANCE | Ops::CALLDATALOAD | Ops::BLOCKHASH | Ops::MLOAD | Ops::SLOAD => {
+            Instruction::new(1, 1)
+        }
+        Ops::ADDRESS
+        | Ops::ORIGIN
+        | Ops::CALLER
+        | Ops::CALLVALUE
+        | Ops::CALLDATASIZE
+        | Ops::GASPRICE
+        | Ops::COINBASE
+        | Ops::TIMESTAMP
+        | Ops::NUMBER
+        | Ops::DIFFICULTY
+        | Ops::GASLIMIT
+        | Ops::MSIZE => Instruction::new(0, 1),
+        Ops::CALLDATACOPY => Instruction::new(3, 0),
+        Ops::POP => Instruction::new(1, 0),
+        Ops::MSTORE | Ops::MSTORE8 | Ops::SSTORE => Instruction::new(2, 0),
+        Ops::LOG0 | Ops::LOG1 | Ops::LOG2 | Ops::LOG3 | Ops::LOG4 => {
+            Instruction::new(value - Ops::LOG0 as u8 + 2, 0)
+        }
+        Ops::DUP1
+        | Ops::DUP2
+        | Ops::DUP3
+        | Ops::DUP4
+        | Ops::DUP5
+        | Ops::DUP6
+        | Ops::DUP7
+        | Ops::DUP8
+        | Ops::DUP9
+        | Ops::DUP10
+        | Ops::DUP11
+        | Ops::DUP12
+        | Ops::DUP13
+        | Ops::DUP14
+        | Ops::DUP15
+        | Ops::DUP16 => {
+            let n = value - Ops::DUP1 as u8 + 1;
+            Instruction::new(n, n + 1)
+        }
+        Ops::SWAP1
+        | Ops::SWAP2
+        | Ops::SWAP3
+        | Ops::SWAP4
+        | Ops::SWAP5
+        | Ops::SWAP6
+        | Ops::SWAP7
+        | Ops::SWAP8
+        | Ops::SWAP9
+        | Ops::SWAP10
+        | Ops::SWAP11
+        | Ops::SWAP12
+        | Ops::SWAP13
+        | Ops::SWAP14
+        | Ops::SWAP15
+        | Ops::SWAP16 => {
+            let n = value - Ops::SWAP1 as u8 + 2;
+            Instruction::new(n, n)
+        }
+        _ if op.push_size() > 0 => Instruction::new(0, 1).immediates(op.push_size()),
+        // Replaced by the instructions above, or removed since code can't
+        // observe gas or itself.
+        Ops::JUMP
+        | Ops::JUMPI
+        | Ops::PC
+        | Ops::GAS
+        | Ops::CODESIZE
+        | Ops::CODECOPY
+        | Ops::EXTCODESIZE
+        | Ops::EXTCODECOPY
+        | Ops::CREATE
+        | Ops::CALL
+        | Ops::CALLCODE
+        | Ops::SELFDESTRUCT => return None,
+        _ => unreachable!("{} has no stack effect", op.name()),
+    };
+    Some(instruction)
+}
+
+fn immediate_u16(evm: &Evm, offset: usize) -> u16 {
+    u16::from_be_bytes([evm.code[evm.pc + offset], evm.code[evm.pc + offset + 1]])
+}
+
+/// Move the program counter from `end`, the end of the jump instruction, by
+/// the offset in the immediate `immediate` bytes after the opcode.
+fn relative_jump(evm: &mut Evm, end: usize, immediate: usize) {
+    let offset = immediate_u16(evm, immediate) as i16;
+    evm.pc = end.checked_add_signed(offset.into()).unwrap();
+}
+
+/// Check the stack has room for `section` to run, once the caller's items
+/// that aren't its inputs are below it.
+fn check_stack_room(evm: &Evm, frame: &EofFrame, section: usize) -> Result<()> {
+    let ty = frame.container.types[section];
+    let height = evm.stack.len() + usize::from(ty.max_stack_height) - usize::from(ty.inputs);
+    if height > STACK_SIZE_LIMIT {
+        return Err(EvmError::StackOverflow);
+    }
+    Ok(())
+}
+
+/// Jump by a relative offset.
+///
+/// Parameters
+/// ----------
+/// evm :
+///     The current EVM frame.
+pub fn rjump(evm: &mut Evm, _: &mut EofFrame) -> Result<()> {
+    // GAS
+    charge_gas(evm, gas::GAS_BASE())?;
+
+    // PROGRAM COUNTER
+    relative_jump(evm, evm.pc + 3, 1);
+    Ok(())
+}
+
+/// Jump by a relative offset if the top of the stack isn't zero.
+///
+/// Parameters
+/// ----------
+/// evm :
+///     The current EVM frame.
+pub fn rjumpi(evm: &mut Evm, _: &mut EofFrame) -> Result<()> {
+    // STACK
+    let condition = stack::pop(&mut evm.stack)?;
+
+    // GAS
+    charge_gas(evm, GAS_CONDITIONAL_JUMP())?;
+
+    // PROGRAM COUNTER
+    if condition == U256::from(0u8) {
+        evm.pc += 3;
+    } else {
+        relative_jump(evm, evm.pc + 3, 1);
+    }
+    Ok(())
+}
+
+/// Jump by the relative offset the top of the stack indexes, or to the next
+/// instruction if the index is out of bounds.
+///
+/// Parameters
+/// ----------
+/// evm :
+///     The current EVM frame.
+pub fn rjumpv(evm: &mut Evm, _: &mut EofFrame) -> Result<()> {
+    // STACK
+    let case = stack::pop(&mut evm.stack)?;
+
+    // GAS
+    charge_gas(evm, GAS_CONDITIONAL_JUMP())?;
+
+    // PROGRAM COUNTER
+    let max_index = evm.code[evm.pc + 1];
+    let end = evm.pc + 2 + 2 * (usize::from(max_index) + 1);
+    match u8::try_from(case) {
+        Ok(case) if case <= max_index => relative_jump(evm, end, 2 + 2 * usize::from(case)),
+        _ => evm.pc = end,
+    }
+    Ok(())
+}
+
+/// Call a function, a code section that returns.
+///
+/// Parameters
+/// ----------
+/// evm :
+///     The current EVM frame.
+/// frame :
+///     The code sections and return stack of the frame.
+pub fn callf(evm: &mut Evm, frame: &mut EofFrame) -> Result<()> {
+    let section = usize::from(immediate_u16(evm, 1));
+
+    // GAS
+    charge_gas(evm, gas::GAS_LOW())?;
+
+    // OPERATION
+    check_stack_room(evm, frame, section)?;
+    if frame.return_stack.len() == STACK_SIZE_LIMIT {
+        return Err(EvmError::StackOverflow);
+    }
+    frame.return_stack.push((frame.section, evm.pc + 3));
+    frame.enter(evm, section);
+    Ok(())
+}
+
+/// Return from a function to the instruction after its `CALLF`.
+///
+/// Parameters
+/// ----------
+/// evm :
+///     The current EVM frame.
+/// frame :
+///     The code sections and return stack of the frame.
+pub fn retf(evm: &mut Evm, frame: &mut EofFrame) -> Result<()> {
+    // GAS
+    charge_gas(evm, gas::GAS_VERY_LOW())?;
+
+    // OPERATION
+    let (section, pc) = frame.return_stack.pop().ok_or(EvmError::Halt)?;
+    frame.enter(evm, section);
+
+    // PROGRAM COUNTER
+    evm.pc = pc;
+    Ok(())
+}
+
+/// Continue in another code section, which returns to the caller of this
+/// one if it returns.
+///
+/// Parameters
+/// ----------
+/// evm :
+///     The current EVM frame.
+/// frame :
+///     The code sections and return stack of the frame.
+pub fn jumpf(evm: &mut Evm, frame: &mut EofFrame) -> Result<()> {
+    let section = usize::from(immediate_u16(evm, 1));
+
+    // GAS
+    charge_gas(evm, gas::GAS_LOW())?;
+
+    // OPERATION
+    check_stack_room(evm, frame, section)?;
+    frame.enter(evm, section);
+    Ok(())
+}
+
+/// Duplicate the stack item the immediate indexes, counting from one.
+///
+/// Parameters
+/// ----------
+/// evm :
+///     The current EVM frame.
+pub fn dupn(evm: &mut Evm, _: &mut EofFrame) -> Result<()> {
+    let n = usize::from(evm.code[evm.pc + 1]) + 1;
+
+    // GAS
+    charge_gas(evm, gas::GAS_VERY_LOW())?;
+
+    // OPERATION
+    let index = evm.stack.len().checked_sub(n).ok_or(EvmError::StackUnderflow)?;
+    let value = evm.stack[index].clone();
+    stack::push(&mut evm.stack, value)?;
+
+    // PROGRAM COUNTER
+    evm.pc += 2;
+    Ok(())
+}
+
+/// Swap the top of the stack with the item the immediate indexes, counting
+/// from one below the top.
+///
+/// Parameters
+/// ----------
+/// evm :
+///     The current EVM frame.
+pub fn swapn(evm: &mut Evm, _: &mut EofFrame) -> Result<()> {
+    let n = usize::from(evm.code[evm.pc + 1]) + 1;
+
+    // GAS
+    charge_gas(evm, gas::GAS_VERY_LOW())?;
+
+    // OPERATION
+    let top = evm.stack.len().checked_sub(1).ok_or(EvmError::StackUnderflow)?;
+    let index = top.checked_sub(n).ok_or(EvmError::StackUnderflow)?;
+    evm.stack.swap(top, index);
+
+    // PROGRAM COUNTER
+    evm.pc += 2;
+    Ok(())
+}
+
+/// Swap two items below the top of the stack: the `n + 1`th and the
+/// `n + m + 1`th, where the high nibble of the immediate is `n - 1` and the
+/// low one `m - 1`.
+///
+/// Parameters
+/// ----------
+/// evm :
+///     The current EVM frame.
+pub fn exchange(evm: &mut Evm, _: &mut EofFrame) -> Result<()> {
+    let immediate = evm.code[evm.pc + 1];
+    let (n, m) = (usize::from(immediate >> 4) + 1, usize::from(immediate & 0x0f) + 1);
+
+    // GAS
+    charge_gas(evm, gas::GAS_VERY_LOW())?;
+
+    // OPERATION
+    let top = evm.stack.len().checked_sub(1).ok_or(EvmError::StackUnderflow)?;
+    let second = top.checked_sub(n + m).ok_or(EvmError::StackUnderflow)?;
+    evm.stack.swap(top - n, second);
+
+    // PROGRAM COUNTER
+    evm.pc += 2;
+    Ok(())
+}
+
+/// Push the 32 bytes of the data section at the offset on the stack,
+/// padded with zeros.
+///
+/// Parameters
+/// ----------
+/// evm :
+///     The current EVM frame.
+/// frame :
+///     The container of the frame.
+pub fn dataload(evm: &mut Evm, frame: &mut EofFrame) -> Result<()> {
+    // STACK
+    let offset = stack::pop(&mut evm.stack)?;
+
+    // GAS
+    charge_gas(evm, GAS_DATALOAD())?;
+
+    // OPERATION
+    let value = buffer_read(frame.container.data.clone(), offset, U256::from(32u8));
+    stack::push(&mut evm.stack, U256::from_bytes_be(&value))?;
+
+    // PROGRAM COUNTER
+    evm.pc += 1;
+    Ok(())
+}
+
+/// Push the 32 bytes of the data section at the offset given by the
+/// immediate, which validation keeps in bounds.
+///
+/// Parameters
+/// ----------
+/// evm :
+///     The current EVM frame.
+/// frame :
+///     The container of the frame.
+pub fn dataloadn(evm: &mut Evm, frame: &mut EofFrame) -> Result<()> {
+    let offset = usize::from(immediate_u16(evm, 1));
+
+    // GAS
+    charge_gas(evm, gas::GAS_VERY_LOW())?;
+
+    // OPERATION
+    let value = &frame.container.data[offset..offset + 32];
+    stack::push(&mut evm.stack, U256::from_bytes_be(value))?;
+
+    // PROGRAM COUNTER
+    evm.pc += 3;
+    Ok(())
+}
+
+/// Push the size of the data section on the stack.
+///
+/// Parameters
+/// ----------
+/// evm :
+///     The current EVM frame.
+/// frame :
+///     The container of the frame.
+pub fn datasize(evm: &mut Evm, frame: &mut EofFrame) -> Result<()> {
+    // GAS
+    charge_gas(evm, gas::GAS_BASE())?;
+
+    // OPERATION
+    stack::push(&mut evm.stack, U256::from(frame.container.data.len()))?;
+
+    // PROGRAM COUNTER
+    evm.pc += 1;
+    Ok(())
+}
+
+/// Copy a part of the data section to memory, padded with zeros.
+///
+/// Parameters
+/// ----------
+/// evm :
+///     The current EVM frame.
+/// frame :
+///     The container of the frame.
+pub fn datacopy(evm: &mut Evm, frame: &mut EofFrame) -> Result<()> {
+    // STACK
+    let memory_start_index = stack::pop(&mut evm.stack)?;
+    let data_start_index = stack::pop(&mut evm.stack)?;
+    let size = stack::pop(&mut evm.stack)?;
+
+    // GAS
+    let words = ceil32(size.clone()) / U256::from(32u8);
+    let copy_gas_cost = gas::GAS_COPY() * words;
+    let extend_memory =
+        gas::calculate_gas_extend_memory(&evm.memory, [(memory_start_index.clone(), size.clone())].to_vec());
+    charge_gas(evm, gas::GAS_VERY_LOW() + copy_gas_cost + extend_memory.cost)?;
+
+    // OPERATION
+    evm.memory
+        .extend([0].repeat(usize::try_from(extend_memory.expand_by).unwrap()));
+    let value = buffer_read(frame.container.data.clone(), data_start_index, size);
+    memory_write(&mut evm.memory, memory_start_index, &value);
+
+    // PROGRAM COUNTER
+    evm.pc += 1;
+    Ok(())
+}
+
+/// The implementation of an instruction EOF adds.
+///
+/// Parameters
+/// ----------
+/// opcode :
+///     The opcode to look up.
+///
+/// Returns
+/// -------
+/// implementation : `Option<fn(&mut Evm, &mut EofFrame) -> Result<()>>`
+///     The function executing the instruction, or `None` if it is a
+///     Frontier one.
+#[allow(clippy::type_complexity)]
+pub fn eof_implementation(opcode: u8) -> Option<fn(&mut Evm, &mut EofFrame) -> Result<()>> {
+    let implementation: fn(&mut Evm, &mut EofFrame) -> Result<()> = match opcode {
+        DATALOAD => dataload,
+        DATALOADN => dataloadn,
+        DATASIZE => datasize,
+        DATACOPY => datacopy,
+        RJUMP => rjump,
+        RJUMPI => rjumpi,
+        RJUMPV => rjumpv,
+        CALLF => callf,
+        RETF => retf,
+        JUMPF => jumpf,
+        DUPN => dupn,
+        SWAPN => swapn,
+        EXCHANGE => exchange,
+        _ => return None,
+    };
+    Some(implementation)
+}
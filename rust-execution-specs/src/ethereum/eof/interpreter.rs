@@ -0,0 +1,91 @@
+//!
+//! # EOF Interpreter
+//!
+//! ## Introduction
+//!
+//! Runs the code of a valid container, from its first code section. The
+//! frame's `code` is the section running, and `pc` is relative to it.
+//!
+
+use crate::prelude::*;
+use super::{container::Container, instructions::eof_implementation};
+use crate::ethereum::{
+    base_types::U256,
+    frontier::vm::{
+        evm_trace,
+        exceptions::EvmError,
+        instructions::{op_implementation, Ops},
+        interpreter::new_evm,
+        Environment, Evm, Message,
+    },
+    trace::TraceEvent,
+};
+
+/// What EOF adds to a frame: the code sections, the one running, and where
+/// each `CALLF` returns to.
+pub struct EofFrame<'a> {
+    pub container: &'a Container,
+    pub section: usize,
+    /// The section and offset after each `CALLF` not returned from.
+    pub return_stack: Vec<(usize, usize)>,
+}
+
+impl EofFrame<'_> {
+    /// Continue at the start of another code section.
+    pub fn enter(&mut self, evm: &mut Evm, section: usize) {
+        self.section = section;
+        evm.code = self.container.code[section].clone();
+        evm.pc = 0;
+    }
+}
+
+///
+///     Execute the code of a container.
+///
+///     Parameters
+///     ----------
+///     container :
+///         The container, which must be valid, see `validate_container`.
+///     message :
+///         Transaction specific items. Its `code` isn't used.
+///     env :
+///         External items required for EVM execution.
+///
+///     Returns
+///     -------
+///     evm : `Evm`
+///         The frame once it has stopped. Like in legacy code, an
+///         exceptional halt consumes all the gas.
+///
+pub fn execute_code(container: &Container, message: Message, env: Environment) -> Evm {
+    let mut evm = new_evm(message, env);
+    evm.valid_jump_destinations.clear();
+    let mut frame = EofFrame {
+        container,
+        section: 0,
+        return_stack: vec![],
+    };
+    frame.enter(&mut evm, 0);
+    if let Err(error) = run(&mut evm, &mut frame) {
+        evm_trace(&mut evm, TraceEvent::OpException { error: error.to_string() });
+        evm.gas_left = U256::from(0u8);
+        evm.error = Some(error);
+    }
+    evm
+}
+
+fn run(evm: &mut Evm, frame: &mut EofFrame) -> Result<(), EvmError> {
+    // Valid code ends every path in a terminating instruction.
+    while evm.running {
+        let opcode = evm.code[evm.pc];
+        evm_trace(evm, TraceEvent::OpStart { op: opcode });
+        match eof_implementation(opcode) {
+            Some(implementation) => implementation(evm, frame)?,
+            None => op_implementation(Ops::try_from(opcode)?)(evm)?,
+        }
+        evm_trace(evm, TraceEvent::OpEnd);
+    }
+
+    evm_trace(evm, TraceEvent::EvmStop { op: Ops::STOP as u8 });
+    Ok(())
+}
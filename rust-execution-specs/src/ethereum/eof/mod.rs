@@ -0,0 +1,47 @@
+//!
+//! # EVM Object Format
+//!
+//! ## Introduction
+//!
+//! An experimental implementation of the EVM Object Format (EOF), for
+//! prototyping the behavior of the Osaka era:
+//!
+//! - the container format, EIP-3540,
+//! - code validation, EIP-3670,
+//! - static relative jumps, EIP-4200,
+//! - functions, EIP-4750, and non-returning functions, EIP-6206,
+//! - stack validation, EIP-5450,
+//! - `DUPN`, `SWAPN` and `EXCHANGE`, EIP-663,
+//! - the data section instructions, EIP-7480.
+//!
+//! Only Frontier is implemented, so EOF code runs the Frontier instructions
+//! besides its own. The instructions EOF removes (`JUMP`, `JUMPI`, `PC`,
+//! `GAS`, `CODESIZE`, `CODECOPY`, `EXTCODESIZE`, `EXTCODECOPY`, `CREATE`,
+//! `CALL`, `CALLCODE` and `SELFDESTRUCT`) are undefined, and so are the ones
+//! replacing them, such as `EXTCALL` and `EOFCREATE`. Without `EOFCREATE`
+//! nothing can reference a subcontainer, so containers holding some are
+//! invalid.
+//!
+//! A container is parsed with [container::parse], checked with
+//! [validation::validate_container] and its code run by
+//! [interpreter::execute_code]. Nothing in the Frontier fork deploys or runs
+//! EOF code.
+//!
+
+pub mod container;
+pub mod exceptions;
+pub mod instructions;
+pub mod interpreter;
+pub mod validation;
+
+/// The first bytes of every container. `0xEF` can't start deployed code
+/// since EIP-3541.
+pub const MAGIC: [u8; 2] = [0xef, 0x00];
+
+/// The only version of the format.
+pub const VERSION: u8 = 1;
+
+/// Whether `code` claims to be a container, that is starts with [MAGIC].
+pub fn is_eof(code: &[u8]) -> bool {
+    code.starts_with(&MAGIC)
+}
@@ -0,0 +1,244 @@
+//!
+//! # EOF Validation
+//!
+//! ## Introduction
+//!
+//! Checks the code of a container once, so that it can run without checking
+//! its jumps, and without checking its stack for underflow or, outside of
+//! `CALLF` and `JUMPF`, overflow:
+//!
+//! - every instruction is defined and complete (EIP-3670),
+//! - relative jumps land on instructions (EIP-4200),
+//! - `CALLF` and `JUMPF` name sections of the right kind, and every section
+//!   can be reached from the first (EIP-4750, EIP-6206),
+//! - the stack height at each instruction is bounded the same way on every
+//!   path, and the types give the maximum (EIP-5450),
+//! - `DATALOADN` reads inside the data section (EIP-7480).
+//!
+//! Instructions are visited in order. Only backward jumps reach an
+//! instruction already visited, and they must bring the stack height range
+//! it was visited with.
+//!
+
+use crate::prelude::*;
+use alloc::collections::BTreeSet;
+
+use super::{
+    container::{parse, Container},
+    exceptions::EofError,
+    instructions::{
+        instruction, CALLF, DATALOADN, DUPN, EXCHANGE, JUMPF, RETF, RJUMP, RJUMPI, RJUMPV, SWAPN,
+    },
+};
+use crate::ethereum::{frontier::constants::STACK_SIZE_LIMIT, utils::ensure::ensure};
+
+///
+///     Parse and validate a container.
+///
+///     Parameters
+///     ----------
+///     code :
+///         The bytes of the container.
+///
+///     Returns
+///     -------
+///     container : `Result<Container, EofError>`
+///         The container if it is valid, or the first error found.
+///
+pub fn validate(code: &[u8]) -> Result<Container, EofError> {
+    let container = parse(code)?;
+    validate_container(&container)?;
+    Ok(container)
+}
+
+///
+///     Validate the code of a parsed container.
+///
+///     Parameters
+///     ----------
+///     container :
+///         The container.
+///
+///     Returns
+///     -------
+///     result : `Result<(), EofError>`
+///         The first error found, in the order of the sections.
+///
+pub fn validate_container(container: &Container) -> Result<(), EofError> {
+    let mut calls = vec![];
+    for section in 0..container.code.len() {
+        calls.push(validate_code_section(container, section)?);
+    }
+
+    let mut reached = BTreeSet::from([0]);
+    let mut pending = vec![0];
+    while let Some(section) = pending.pop() {
+        for &callee in &calls[section] {
+            if reached.insert(callee) {
+                pending.push(callee);
+            }
+        }
+    }
+    if let Some(section) = (0..container.code.len()).find(|section| !reached.contains(section)) {
+        return Err(EofError::UnreachableCodeSection { section });
+    }
+
+    // Only `EOFCREATE` and `RETURNCONTRACT` reference subcontainers.
+    ensure(container.containers.is_empty(), EofError::UnreferencedSubcontainer { index: 0 })
+}
+
+/// The lowest and highest stack heights an instruction may run with.
+type Heights = (usize, usize);
+
+///
+///     Validate one code section.
+///
+///     Parameters
+///     ----------
+///     container :
+///         The container.
+///     section :
+///         The index of the section.
+///
+///     Returns
+///     -------
+///     callees : `Result<BTreeSet<usize>, EofError>`
+///         The sections the section calls or jumps to.
+///
+pub fn validate_code_section(container: &Container, section: usize) -> Result<BTreeSet<usize>, EofError> {
+    let code = &container.code[section];
+    let ty = container.types[section];
+
+    // Split the code into instructions.
+    let mut starts = vec![false; code.len()];
+    let mut instructions = vec![];
+    let mut pos = 0;
+    while pos < code.len() {
+        let opcode = code[pos];
+        let info = instruction(opcode).ok_or(EofError::UndefinedInstruction { section, pos, opcode })?;
+        let immediates = match opcode {
+            RJUMPV => {
+                let max_index = *code.get(pos + 1).ok_or(EofError::TruncatedInstruction { section, pos })?;
+                1 + 2 * (usize::from(max_index) + 1)
+            }
+            _ => info.immediates,
+        };
+        ensure(pos + immediates < code.len(), EofError::TruncatedInstruction { section, pos })?;
+        starts[pos] = true;
+        instructions.push((pos, info, immediates));
+        pos += 1 + immediates;
+    }
+
+    let u16_at = |pos: usize| usize::from(u16::from_be_bytes([code[pos], code[pos + 1]]));
+    let mut heights: Vec<Option<Heights>> = vec![None; code.len()];
+    heights[0] = Some((usize::from(ty.inputs), usize::from(ty.inputs)));
+    let mut max_height = usize::from(ty.inputs);
+    let mut callees = BTreeSet::new();
+    let mut returns = false;
+
+    for (pos, info, immediates) in instructions {
+        let (min, max) = heights[pos].ok_or(EofError::UnreachableCode { section, pos })?;
+        let opcode = code[pos];
+        let next = pos + 1 + immediates;
+
+        let (inputs, outputs) = match opcode {
+            CALLF | JUMPF => {
+                let target = u16_at(pos + 1);
+                let target_ty = *container
+                    .types
+                    .get(target)
+                    .ok_or(EofError::InvalidCodeSectionIndex { section, pos })?;
+                callees.insert(target);
+                let (inputs, max_stack_height) =
+                    (usize::from(target_ty.inputs), usize::from(target_ty.max_stack_height));
+                ensure(
+                    max + max_stack_height <= STACK_SIZE_LIMIT + inputs,
+                    EofError::StackOverflow { section, pos },
+                )?;
+                if opcode == CALLF {
+                    ensure(target_ty.returns(), EofError::CallfToNonReturning { section, pos })?;
+                    (inputs, usize::from(target_ty.outputs))
+                } else if target_ty.returns() {
+                    // The target returns to this section's caller in its
+                    // place, leaving what this section returns.
+                    ensure(ty.returns(), EofError::InvalidNonReturning { section })?;
+                    ensure(ty.outputs >= target_ty.outputs, EofError::StackHeightMismatch { section, pos })?;
+                    let height = usize::from(ty.outputs) + inputs - usize::from(target_ty.outputs);
+                    ensure(min == height && max == height, EofError::StackHeightMismatch { section, pos })?;
+                    returns = true;
+                    (inputs, 0)
+                } else {
+                    (inputs, 0)
+                }
+            }
+            RETF => {
+                ensure(ty.returns(), EofError::InvalidNonReturning { section })?;
+                let height = usize::from(ty.outputs);
+                ensure(min == height && max == height, EofError::StackHeightMismatch { section, pos })?;
+                returns = true;
+                (height, 0)
+            }
+            DATALOADN => {
+                ensure(
+                    u16_at(pos + 1) + 32 <= container.data.len(),
+                    EofError::InvalidDataloadnIndex { section, pos },
+                )?;
+                (0, 1)
+            }
+            DUPN => {
+                let n = usize::from(code[pos + 1]) + 1;
+                (n, n + 1)
+            }
+            SWAPN => {
+                let n = usize::from(code[pos + 1]) + 2;
+                (n, n)
+            }
+            EXCHANGE => {
+                let immediate = code[pos + 1];
+                let n = usize::from(immediate >> 4) + usize::from(immediate & 0x0f) + 3;
+                (n, n)
+            }
+            _ => (usize::from(info.inputs), usize::from(info.outputs)),
+        };
+        ensure(min >= inputs, EofError::StackUnderflow { section, pos })?;
+        let after = (min - inputs + outputs, max - inputs + outputs);
+        max_height = max_height.max(after.1);
+
+        let mut successors = vec![];
+        if !info.terminating && opcode != RJUMP {
+            ensure(next < code.len(), EofError::NoTerminatingInstruction { section })?;
+            successors.push(next);
+        }
+        let offsets = match opcode {
+            RJUMP | RJUMPI => vec![pos + 1],
+            RJUMPV => (0..=usize::from(code[pos + 1])).map(|case| pos + 2 + 2 * case).collect(),
+            _ => vec![],
+        };
+        for offset in offsets {
+            let relative = i16::from_be_bytes([code[offset], code[offset + 1]]);
+            let target = next
+                .checked_add_signed(relative.into())
+                .filter(|&target| target < code.len() && starts[target])
+                .ok_or(EofError::InvalidJumpDestination { section, pos })?;
+            successors.push(target);
+        }
+
+        for successor in successors {
+            heights[successor] = match heights[successor] {
+                _ if successor <= pos => {
+                    ensure(heights[successor] == Some(after), EofError::StackHeightMismatch { section, pos })?;
+                    Some(after)
+                }
+                None => Some(after),
+                Some((min, max)) => Some((min.min(after.0), max.max(after.1))),
+            };
+        }
+    }
+
+    ensure(
+        max_height == usize::from(ty.max_stack_height),
+        EofError::InvalidMaxStackHeight { section },
+    )?;
+    ensure(returns == ty.returns(), EofError::InvalidNonReturning { section })?;
+    Ok(callees)
+}
@@ -1,8 +1,9 @@
+use crate::prelude::*;
 use num_traits::ToPrimitive;
 
 use super::{
-    base_types::Uint,
-    frontier::fork_types::{keccak256, keccak512, Hash32}, utils::numeric::{le_uint32_sequence_to_uint, le_bytes_to_uint32_sequence, le_uint32_sequence_to_bytes},
+    base_types::Bytes8,
+    frontier::fork_types::{keccak256, keccak512, Hash32}, utils::numeric::{le_bytes_to_uint32_sequence, le_uint32_sequence_to_bytes},
 };
 
 pub const EPOCH_SIZE: u64 = 30_000;
@@ -24,10 +25,13 @@ pub fn is_prime(number: u64) -> bool {
     if number < 2 {
         return false;
     }
-    for i in 2..=((number as f64).sqrt() as u64) {
+    // Without `std` there is no square root of a float to stop at.
+    let mut i = 2;
+    while i * i <= number {
         if number % i == 0 {
             return false;
         }
+        i += 1;
     }
     true
 }
@@ -79,7 +83,7 @@ pub fn generate_cache(block_number: u64) -> Vec<Vec<u32>> {
     for _ in 0..CACHE_ROUNDS {
         for index in 0..cache_size_words {
             let first_cache_item = cache
-                [((index.wrapping_sub(1) + cache_size_words) % cache_size_words) as usize]
+                [((index + cache_size_words - 1) % cache_size_words) as usize]
                 .clone();
             let second_cache_item = cache[u32::from_le_bytes([
                 cache[index as usize][0],
@@ -111,7 +115,7 @@ pub fn generate_cache(block_number: u64) -> Vec<Vec<u32>> {
 
 pub fn fnv(a: u32, b: u32) -> u32 {
     const FNV_PRIME: u32 = 0x0100_0193;
-    const U32_MAX_VALUE: u32 = std::u32::MAX;
+    const U32_MAX_VALUE: u32 = core::u32::MAX;
 
     let a = u64::from(a);
     let b = u64::from(b);
@@ -129,10 +133,13 @@ pub fn fnv_hash(mix_integers: &[u32], data: &[u32]) -> Vec<u32> {
 }
 
 pub fn generate_dataset_item(cache: &Vec<Vec<u32>>, index: usize) -> Vec<u8> {
-    let mix = keccak512(
-        (le_uint32_sequence_to_uint(&cache[index % cache.len()]).unwrap() ^ Uint::from(index))
-            .to_bytes_le().as_slice(),
-    );
+    // The index is xored into the cache item read as a little endian
+    // number, that is into its first bytes.
+    let mut seed = le_uint32_sequence_to_bytes(&cache[index % cache.len()]).unwrap().to_vec();
+    for (byte, index_byte) in seed.iter_mut().zip((index as u64).to_le_bytes()) {
+        *byte ^= index_byte;
+    }
+    let mix = keccak512(&seed);
 
     let mut mix_integers = le_bytes_to_uint32_sequence(&mix);
 
@@ -145,7 +152,7 @@ pub fn generate_dataset_item(cache: &Vec<Vec<u32>>, index: usize) -> Vec<u8> {
 
     let mix = le_uint32_sequence_to_bytes(&mix_integers).unwrap();
 
-    keccak512(Box::leak(mix)).to_vec()
+    keccak512(&mix).to_vec()
 }
 
 pub fn generate_dataset(block_number: u64) -> Vec<Vec<u8>> {
@@ -155,4 +162,100 @@ pub fn generate_dataset(block_number: u64) -> Vec<Vec<u8>> {
     (0..(dataset_size_bytes / HASH_BYTES as u64))
         .map(|index| generate_dataset_item(&cache, index as usize))
         .collect()
-}
\ No newline at end of file
+}
+
+///
+///     Obtain the mix digest and the final value for a header, by
+///     aggregating data from the full dataset.
+///
+///     Parameters
+///     ----------
+///     header_hash :
+///         The PoW valid rlp hash of a header.
+///     nonce :
+///         The propogated nonce for the given block.
+///     dataset_size :
+///         Dataset size for the epoch containing the current block.
+///     fetch_dataset_item :
+///         The function which will be used to obtain a specific item
+///         from the dataset, as little endian words.
+///
+///     Returns
+///     -------
+///     mix_digest : `Hash32`
+///         Mix digest generated from the header hash and propogated nonce.
+///     result : `Hash32`
+///         The final result obtained which will be checked for leading zeros
+///         (in byte representation) in correspondence with the block
+///         difficulty.
+///
+pub fn hashimoto(
+    header_hash: &Hash32,
+    nonce: &Bytes8,
+    dataset_size: u64,
+    fetch_dataset_item: impl Fn(usize) -> Vec<u32>,
+) -> (Hash32, Hash32) {
+    let mut seed = header_hash.to_vec();
+    seed.extend(nonce.iter().rev());
+    let seed_hash = keccak512(&seed);
+    let seed_head = u32::from_le_bytes([seed_hash[0], seed_hash[1], seed_hash[2], seed_hash[3]]);
+
+    let rows = (dataset_size / MIX_BYTES as u64) as u32;
+    let mut mix = le_bytes_to_uint32_sequence(&seed_hash).repeat(MIX_BYTES / HASH_BYTES);
+
+    for i in 0..HASHIMOTO_ACCESSES {
+        let parent = fnv(i as u32 ^ seed_head, mix[i % mix.len()]) % rows;
+        let new_data: Vec<u32> = (0..MIX_BYTES / HASH_BYTES)
+            .flat_map(|j| fetch_dataset_item(2 * parent as usize + j))
+            .collect();
+        mix = fnv_hash(&mix, &new_data);
+    }
+
+    let compressed_mix: Vec<u32> = mix
+        .chunks_exact(4)
+        .map(|words| fnv(fnv(fnv(words[0], words[1]), words[2]), words[3]))
+        .collect();
+    let mix_digest: Hash32 = le_uint32_sequence_to_bytes(&compressed_mix).unwrap()[..]
+        .try_into()
+        .unwrap();
+    let mut result = seed_hash.to_vec();
+    result.extend(mix_digest);
+
+    (mix_digest, keccak256(&result))
+}
+
+///
+///     Run the hashimoto algorithm with the cache only, generating the
+///     dataset items as they are needed. This is how light clients and the
+///     spec verify proofs of work.
+///
+///     Parameters
+///     ----------
+///     header_hash :
+///         The PoW valid rlp hash of a header.
+///     nonce :
+///         The propogated nonce for the given block.
+///     cache :
+///         The generated cache for the epoch of the block.
+///     dataset_size :
+///         Dataset size for the epoch containing the current block.
+///
+///     Returns
+///     -------
+///     mix_digest : `Hash32`
+///         Mix digest generated from the header hash and propogated nonce.
+///     result : `Hash32`
+///         The final result obtained which will be checked for leading zeros
+///         (in byte representation) in correspondence with the block
+///         difficulty.
+///
+pub fn hashimoto_light(
+    header_hash: &Hash32,
+    nonce: &Bytes8,
+    cache: &Vec<Vec<u32>>,
+    dataset_size: u64,
+) -> (Hash32, Hash32) {
+    hashimoto(header_hash, nonce, dataset_size, |index| {
+        le_bytes_to_uint32_sequence(&generate_dataset_item(cache, index))
+    })
+}
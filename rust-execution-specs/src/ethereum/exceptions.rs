@@ -12,7 +12,21 @@
 //!
 //! The base class from which all exceptions thrown by the specification during
 //! normal operation derive.
-//!     
+//!
+//! An exception can be wrapped in the block, transaction or account it was
+//! raised for, as it travels up, see [ErrorContext]: the wrapped exception
+//! is its `source`, and [EthereumException::root_cause] is the one that was
+//! raised. It is displayed with its context, such as `block 46147:
+//! transaction 0: invalid block`.
+//!
+
+use crate::prelude::*;
+use core::fmt;
+
+use crate::ethereum::{
+    base_types::{Uint, U256},
+    utils::hexadecimal::hex,
+};
 
 #[derive(Debug)]
 pub enum EthereumException {
@@ -21,6 +35,28 @@ pub enum EthereumException {
     ///
     InvalidBlock,
 
+    ///
+    ///     Thrown when a transaction being processed is found to be invalid.
+    ///
+    InvalidTransaction,
+
+    ///
+    ///     Thrown when a signature can't be recovered.
+    ///
+    InvalidSignature,
+
+    ///
+    ///     Thrown when a block is governed by a fork the specification
+    ///     doesn't implement.
+    ///
+    UnsupportedFork(String),
+
+    ///
+    ///     Thrown when a block executed without the state needs an account,
+    ///     storage slot or code its witness doesn't have.
+    ///
+    MissingWitness(String),
+
     ///
     ///     Indicates that RLP decoding failed.
     ///
@@ -39,4 +75,174 @@ pub enum EthereumException {
     JsonDecodeError(String),
 
     BadHexString(String),
+
+    ///
+    ///     An exception raised while processing a block.
+    ///
+    InBlock { number: Uint, source: Box<EthereumException> },
+
+    ///
+    ///     An exception raised while processing a transaction of a block.
+    ///
+    InTransaction { index: usize, source: Box<EthereumException> },
+
+    ///
+    ///     An exception raised about an account.
+    ///
+    InAccount { address: [u8; 20], source: Box<EthereumException> },
+}
+
+impl EthereumException {
+    ///
+    ///     The exception that was raised, without its context.
+    ///
+    pub fn root_cause(&self) -> &EthereumException {
+        match self {
+            Self::InBlock { source, .. } | Self::InTransaction { source, .. } | Self::InAccount { source, .. } => {
+                source.root_cause()
+            }
+            other => other,
+        }
+    }
+
+    /// The exceptions from this one to its root cause.
+    fn chain(&self) -> impl Iterator<Item = &EthereumException> {
+        core::iter::successors(Some(self), |exception| match exception {
+            Self::InBlock { source, .. } | Self::InTransaction { source, .. } | Self::InAccount { source, .. } => {
+                Some(source)
+            }
+            _ => None,
+        })
+    }
+
+    /// The number of the block the exception was raised for, if known.
+    pub fn block_number(&self) -> Option<&Uint> {
+        self.chain().find_map(|exception| match exception {
+            Self::InBlock { number, .. } => Some(number),
+            _ => None,
+        })
+    }
+
+    /// The index of the transaction the exception was raised for, if known.
+    pub fn transaction_index(&self) -> Option<usize> {
+        self.chain().find_map(|exception| match exception {
+            Self::InTransaction { index, .. } => Some(*index),
+            _ => None,
+        })
+    }
+
+    /// The account the exception was raised about, if known.
+    pub fn address(&self) -> Option<&[u8; 20]> {
+        self.chain().find_map(|exception| match exception {
+            Self::InAccount { address, .. } => Some(address),
+            _ => None,
+        })
+    }
+}
+
+impl fmt::Display for EthereumException {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidBlock => write!(f, "invalid block"),
+            Self::InvalidTransaction => write!(f, "invalid transaction"),
+            Self::InvalidSignature => write!(f, "invalid signature"),
+            Self::UnsupportedFork(fork) => write!(f, "unsupported fork {fork}"),
+            Self::MissingWitness(what) => write!(f, "the witness is missing the {what}"),
+            Self::RLPDecodingError => write!(f, "RLP decoding failed"),
+            Self::RLPEncodingError => write!(f, "RLP encoding failed"),
+            Self::ValueError => write!(f, "invalid value"),
+            Self::FileNotFound(file) => write!(f, "file not found: {file}"),
+            Self::JsonDecodeError(message) => write!(f, "invalid JSON: {message}"),
+            Self::BadHexString(string) => write!(f, "bad hex string: {string}"),
+            Self::InBlock { number, source } => write!(f, "block {number}: {source}"),
+            Self::InTransaction { index, source } => write!(f, "transaction {index}: {source}"),
+            Self::InAccount { address, source } => write!(f, "account {}: {source}", hex(address)),
+        }
+    }
+}
+
+impl core::error::Error for EthereumException {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            Self::InBlock { source, .. } | Self::InTransaction { source, .. } | Self::InAccount { source, .. } => {
+                Some(source.as_ref())
+            }
+            _ => None,
+        }
+    }
+}
+
+///
+///     Wrap the exception of a result in what it was raised for.
+///
+pub trait ErrorContext<T> {
+    /// The exception was raised while processing block `number`.
+    fn in_block(self, number: &Uint) -> Result<T, EthereumException>;
+
+    /// The exception was raised while processing the transaction at `index`
+    /// of a block.
+    fn in_transaction(self, index: usize) -> Result<T, EthereumException>;
+
+    /// The exception was raised about the account at `address`.
+    fn in_account(self, address: &[u8; 20]) -> Result<T, EthereumException>;
+}
+
+impl<T> ErrorContext<T> for Result<T, EthereumException> {
+    fn in_block(self, number: &Uint) -> Result<T, EthereumException> {
+        self.map_err(|source| EthereumException::InBlock {
+            number: number.clone(),
+            source: Box::new(source),
+        })
+    }
+
+    fn in_transaction(self, index: usize) -> Result<T, EthereumException> {
+        self.map_err(|source| EthereumException::InTransaction {
+            index,
+            source: Box::new(source),
+        })
+    }
+
+    fn in_account(self, address: &[u8; 20]) -> Result<T, EthereumException> {
+        self.map_err(|source| EthereumException::InAccount {
+            address: *address,
+            source: Box::new(source),
+        })
+    }
+}
+
+///
+///     Why a transaction can't be included in a block, as found by a fork's
+///     `check_transaction`. Forks only report the reasons their rules have.
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum InvalidTransactionReason {
+    ///
+    ///     The transaction's gas doesn't cover its intrinsic cost.
+    ///
+    IntrinsicGasTooLow { intrinsic_cost: Uint, gas: U256 },
+
+    ///
+    ///     The transaction wants more gas than the block has.
+    ///
+    GasAboveBlockLimit { gas: U256, gas_limit: Uint },
+
+    ///
+    ///     The nonce is at the maximum and can't be incremented.
+    ///
+    NonceTooLarge,
+
+    ///
+    ///     The nonce isn't the sender's next one.
+    ///
+    NonceMismatch { expected: Uint, nonce: U256 },
+
+    ///
+    ///     The sender can't pay for the gas and value up front.
+    ///
+    InsufficientBalance { required: U256, balance: U256 },
+
+    ///
+    ///     The sender has code, so it can't have signed the transaction.
+    ///
+    SenderNotEoa,
 }
@@ -0,0 +1,323 @@
+//!
+//! # Binary Trie
+//!
+//! ## Introduction
+//!
+//! A sparse binary Merkle trie in the style of EIP-3102, as an alternative
+//! to the hexary Merkle Patricia tries of the state, to compare the size of
+//! proofs and the cost of computing roots.
+//!
+//! Keys are 32 bytes, read as 256 bits from the most significant one. A
+//! subtree holding no key hashes to zero, and one holding a single key is a
+//! leaf, so paths are only as long as needed to tell the keys apart:
+//!
+//! - a leaf hashes to `keccak256(0x00 || key || keccak256(value))`,
+//! - an internal node to `keccak256(0x01 || left || right)`.
+//!
+//! Unlike the Merkle Patricia tries, the whole state is in one trie. An
+//! account is at `keccak256(address)`, with the value `RLP([nonce, balance,
+//! keccak256(code)])`, and each of its storage slots at `keccak256(address ||
+//! slot)`, with the slot's value as RLP.
+//!
+
+use crate::prelude::*;
+
+use crate::ethereum::{
+    backend::StateBackend,
+    base_types::{Bytes, Bytes32},
+    crypto::hash::keccak256,
+    frontier::state::{get_accounts, get_storage_slots, State},
+    rlp,
+};
+
+const LEAF_PREFIX: u8 = 0x00;
+const INTERNAL_PREFIX: u8 = 0x01;
+
+/// The root of an empty trie.
+pub const EMPTY_ROOT: Bytes32 = [0; 32];
+
+///
+/// The binary trie.
+///
+/// Like a [Trie](crate::ethereum::frontier::trie::Trie), its entries are
+/// kept in a [StateBackend], a `HashMap` unless another is chosen with
+/// [BinaryTrie::with_backend].
+///
+#[derive(Clone, Debug, Default)]
+pub struct BinaryTrie<B = HashMap<Bytes32, Bytes>>
+where
+    B: StateBackend<Bytes32, Bytes>,
+{
+    pub data: B,
+}
+
+impl BinaryTrie {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<B: StateBackend<Bytes32, Bytes>> BinaryTrie<B> {
+    /// Create a trie keeping its entries in `backend`, which may already
+    /// hold some.
+    pub fn with_backend(backend: B) -> Self {
+        Self { data: backend }
+    }
+}
+
+/// A proof of the value of a key, or of its absence.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BinaryProof {
+    /// The hashes of the siblings of the nodes on the path to the key, from
+    /// the root down.
+    pub siblings: Vec<Bytes32>,
+    /// The key and value hash of the leaf the path ends at, if it doesn't
+    /// end at an empty subtree.
+    pub leaf: Option<(Bytes32, Bytes32)>,
+}
+
+impl BinaryProof {
+    /// The number of bytes the proof takes, to compare with the nodes of a
+    /// Merkle Patricia proof.
+    pub fn size(&self) -> usize {
+        32 * self.siblings.len() + self.leaf.map_or(0, |_| 64)
+    }
+}
+
+fn bit(key: &Bytes32, depth: usize) -> bool {
+    key[depth / 8] & (0x80 >> (depth % 8)) != 0
+}
+
+fn leaf_hash(key: &Bytes32, value_hash: &Bytes32) -> Bytes32 {
+    let mut buffer = Vec::with_capacity(65);
+    buffer.push(LEAF_PREFIX);
+    buffer.extend(key);
+    buffer.extend(value_hash);
+    keccak256(&buffer)
+}
+
+fn internal_hash(left: &Bytes32, right: &Bytes32) -> Bytes32 {
+    let mut buffer = Vec::with_capacity(65);
+    buffer.push(INTERNAL_PREFIX);
+    buffer.extend(left);
+    buffer.extend(right);
+    keccak256(&buffer)
+}
+
+/// A key of the trie and the hash of its value.
+type Leaf = (Bytes32, Bytes32);
+
+/// The entries of the trie sorted by key, with their values hashed.
+fn leaves<B: StateBackend<Bytes32, Bytes>>(trie: &BinaryTrie<B>) -> Vec<Leaf> {
+    let mut leaves: Vec<_> = trie.data.entries().map(|(key, value)| (key, keccak256(&value))).collect();
+    leaves.sort_unstable();
+    leaves
+}
+
+/// Split sorted `leaves` sharing their first `depth` bits on the next one.
+fn split(leaves: &[Leaf], depth: usize) -> (&[Leaf], &[Leaf]) {
+    leaves.split_at(leaves.partition_point(|(key, _)| !bit(key, depth)))
+}
+
+fn subtree_hash(leaves: &[Leaf], depth: usize) -> Bytes32 {
+    match leaves {
+        [] => EMPTY_ROOT,
+        [(key, value_hash)] => leaf_hash(key, value_hash),
+        _ => {
+            let (left, right) = split(leaves, depth);
+            internal_hash(&subtree_hash(left, depth + 1), &subtree_hash(right, depth + 1))
+        }
+    }
+}
+
+///
+/// Stores an item in a binary trie, deleting the key if `value` is empty.
+///
+/// Parameters
+/// ----------
+/// trie:
+///     Trie to store in.
+/// key :
+///     Key to store at.
+/// value :
+///     Value to store.
+///
+pub fn trie_set<B: StateBackend<Bytes32, Bytes>>(trie: &mut BinaryTrie<B>, key: Bytes32, value: Bytes) {
+    if value.is_empty() {
+        trie.data.delete(&key);
+    } else {
+        trie.data.set(key, value);
+    }
+}
+
+///
+/// Gets an item from a binary trie, empty if the key is missing.
+///
+/// Parameters
+/// ----------
+/// trie:
+///     Trie to lookup in.
+/// key :
+///     Key to lookup.
+///
+/// Returns
+/// -------
+/// value : `Bytes`
+///     Value at `key` in the trie.
+///
+pub fn trie_get<B: StateBackend<Bytes32, Bytes>>(trie: &BinaryTrie<B>, key: &Bytes32) -> Bytes {
+    trie.data.get(key).unwrap_or_default()
+}
+
+///
+/// Computes the root of a binary trie.
+///
+/// Parameters
+/// ----------
+/// trie :
+///     Trie to get the root of.
+///
+/// Returns
+/// -------
+/// root : `Bytes32`
+///     The hash of the root node, [EMPTY_ROOT] for an empty trie.
+///
+pub fn root<B: StateBackend<Bytes32, Bytes>>(trie: &BinaryTrie<B>) -> Bytes32 {
+    subtree_hash(&leaves(trie), 0)
+}
+
+///
+/// Build a proof of the value of a key in a binary trie, or of its absence.
+///
+/// Parameters
+/// ----------
+/// trie :
+///     Trie to prove in.
+/// key :
+///     Key to prove.
+///
+/// Returns
+/// -------
+/// proof : `BinaryProof`
+///     The siblings on the path to `key` and the leaf it ends at.
+///
+pub fn trie_proof<B: StateBackend<Bytes32, Bytes>>(trie: &BinaryTrie<B>, key: &Bytes32) -> BinaryProof {
+    let leaves = leaves(trie);
+    let mut subtree = &leaves[..];
+    let mut siblings = vec![];
+    while subtree.len() > 1 {
+        let depth = siblings.len();
+        let (left, right) = split(subtree, depth);
+        let (path, sibling) = if bit(key, depth) { (right, left) } else { (left, right) };
+        siblings.push(subtree_hash(sibling, depth + 1));
+        subtree = path;
+    }
+    BinaryProof {
+        siblings,
+        leaf: subtree.first().copied(),
+    }
+}
+
+///
+/// Check a proof built by [trie_proof].
+///
+/// Parameters
+/// ----------
+/// root :
+///     Root of the trie.
+/// key :
+///     Key the proof is of.
+/// value :
+///     The value claimed at `key`, empty for its absence.
+/// proof :
+///     The proof.
+///
+/// Returns
+/// -------
+/// valid : `bool`
+///     Whether the proof shows that `key` has `value` in the trie.
+///
+pub fn verify_proof(root: &Bytes32, key: &Bytes32, value: &[u8], proof: &BinaryProof) -> bool {
+    let depth = proof.siblings.len();
+    if depth > 256 {
+        return false;
+    }
+    let mut hash = match &proof.leaf {
+        Some((leaf_key, value_hash)) if leaf_key == key => {
+            if value.is_empty() || *value_hash != keccak256(value) {
+                return false;
+            }
+            leaf_hash(leaf_key, value_hash)
+        }
+        // Another key in the only leaf of the subtree `key` would be in.
+        Some((leaf_key, value_hash)) => {
+            if !value.is_empty() || (0..depth).any(|i| bit(leaf_key, i) != bit(key, i)) {
+                return false;
+            }
+            leaf_hash(leaf_key, value_hash)
+        }
+        None => {
+            if !value.is_empty() {
+                return false;
+            }
+            EMPTY_ROOT
+        }
+    };
+    for (i, sibling) in proof.siblings.iter().enumerate().rev() {
+        hash = if bit(key, i) { internal_hash(sibling, &hash) } else { internal_hash(&hash, sibling) };
+    }
+    hash == *root
+}
+
+/// The key of an account.
+pub fn account_key(address: &[u8; 20]) -> Bytes32 {
+    keccak256(address)
+}
+
+/// The key of a storage slot of an account.
+pub fn storage_key(address: &[u8; 20], slot: &Bytes32) -> Bytes32 {
+    keccak256(&[&address[..], &slot[..]].concat())
+}
+
+///
+/// Build the binary trie of a state.
+///
+/// Parameters
+/// ----------
+/// state :
+///     The state.
+///
+/// Returns
+/// -------
+/// trie : `BinaryTrie`
+///     Every account and storage slot of `state`.
+///
+pub fn state_trie(state: &State) -> BinaryTrie {
+    let mut trie = BinaryTrie::new();
+    for (address, account) in get_accounts(state) {
+        let value = rlp::encode(&(account.nonce, account.balance, keccak256(&account.code)));
+        trie_set(&mut trie, account_key(&address), value);
+        for (slot, value) in get_storage_slots(state, &address) {
+            trie_set(&mut trie, storage_key(&address, &slot), rlp::encode(&value));
+        }
+    }
+    trie
+}
+
+///
+/// Computes the binary trie root of a state, the counterpart of
+/// [state_root](crate::ethereum::frontier::state::state_root).
+///
+/// Parameters
+/// ----------
+/// state :
+///     The state.
+///
+/// Returns
+/// -------
+/// root : `Bytes32`
+///     The root of [state_trie].
+///
+pub fn state_root(state: &State) -> Bytes32 {
+    root(&state_trie(state))
+}
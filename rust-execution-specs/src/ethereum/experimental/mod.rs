@@ -0,0 +1,13 @@
+//!
+//! # Experimental
+//!
+//! ## Introduction
+//!
+//! Explorations of changes no fork has adopted yet, each behind its own
+//! feature. Nothing in the forks uses them.
+//!
+
+#[cfg(feature = "binary-trie")]
+pub mod binary_trie;
+#[cfg(feature = "verkle")]
+pub mod verkle;
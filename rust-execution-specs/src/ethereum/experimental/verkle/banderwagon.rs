@@ -0,0 +1,279 @@
+//!
+//! # Banderwagon
+//!
+//! ## Introduction
+//!
+//! Banderwagon is the quotient of the Bandersnatch curve, the twisted
+//! Edwards curve `a * x^2 + y^2 = 1 + d * x^2 * y^2` with `a = -5` over the
+//! scalar field of BLS12-381, by its points of order two. Its elements form a
+//! group of prime order, which commitments need, and serialize to 32 bytes.
+//!
+//! `(x, y)` and `(-x, -y)` are the same element. An element is serialized as
+//! the `x` of the representative whose `y` is the larger of `y` and `-y`,
+//! big endian.
+//!
+
+use std::sync::OnceLock;
+
+use hex_literal::hex;
+
+use crate::ethereum::base_types::{u256_to_be_bytes32, Bytes32, Uint};
+
+/// The modulus of the field coordinates are in, the order of the scalar
+/// field of BLS12-381.
+#[allow(non_snake_case)]
+pub fn BASE_FIELD_MODULUS() -> Uint {
+    p().clone()
+}
+
+/// The order of the group, the modulus of the scalars points are multiplied
+/// by.
+#[allow(non_snake_case)]
+pub fn SCALAR_FIELD_MODULUS() -> Uint {
+    Uint::from_bytes_be(&hex!("1cfb69d4ca675f520cce760202687600ff8f87007419047174fd06b52876e7e1"))
+}
+
+fn p() -> &'static Uint {
+    static P: OnceLock<Uint> = OnceLock::new();
+    P.get_or_init(|| {
+        Uint::from_bytes_be(&hex!("73eda753299d7d483339d80809a1d80553bda402fffe5bfeffffffff00000001"))
+    })
+}
+
+/// `d`, `138827208126141220649022263972958607803 /
+/// 171449701953573178309673572579671231137`.
+fn d() -> &'static Uint {
+    static D: OnceLock<Uint> = OnceLock::new();
+    D.get_or_init(|| {
+        Uint::from_bytes_be(&hex!("6389c12633c267cbc66e3bf86be3b6d8cb66677177e54f92b369f2f5188d58e7"))
+    })
+}
+
+/// `a`, the field element `-5`.
+fn a() -> Uint {
+    p() - 5u8
+}
+
+fn add(x: &Uint, y: &Uint) -> Uint {
+    (x + y) % p()
+}
+
+fn sub(x: &Uint, y: &Uint) -> Uint {
+    (x + p() - y) % p()
+}
+
+fn mul(x: &Uint, y: &Uint) -> Uint {
+    (x * y) % p()
+}
+
+fn neg(x: &Uint) -> Uint {
+    (p() - x) % p()
+}
+
+fn inv(x: &Uint) -> Uint {
+    x.modpow(&(p() - 2u8), p())
+}
+
+/// Whether `y` is the larger of `y` and `-y`.
+fn is_largest(y: &Uint) -> bool {
+    *y > (p() - 1u8) / 2u8
+}
+
+/// Whether `x` is a non-zero square.
+fn is_square(x: &Uint) -> bool {
+    x.modpow(&((p() - 1u8) / 2u8), p()) == Uint::from(1u8)
+}
+
+/// A square root of `x`, with Tonelli-Shanks since `p - 1` is divisible by
+/// `2^32`.
+fn sqrt(x: &Uint) -> Option<Uint> {
+    let zero = Uint::from(0u8);
+    let one = Uint::from(1u8);
+    if *x == zero {
+        return Some(zero);
+    }
+    if !is_square(x) {
+        return None;
+    }
+    let mut q = p() - 1u8;
+    let mut s = 0;
+    while !q.bit(0) {
+        q >>= 1;
+        s += 1;
+    }
+    let non_residue = (2u8..).map(Uint::from).find(|z| !is_square(z)).unwrap();
+
+    let mut m = s;
+    let mut c = non_residue.modpow(&q, p());
+    let mut t = x.modpow(&q, p());
+    let mut root = x.modpow(&((&q + 1u8) / 2u8), p());
+    while t != one {
+        let mut i = 0;
+        let mut t_squared = t.clone();
+        while t_squared != one {
+            t_squared = mul(&t_squared, &t_squared);
+            i += 1;
+        }
+        let b = c.modpow(&(Uint::from(1u8) << (m - i - 1)), p());
+        m = i;
+        c = mul(&b, &b);
+        t = mul(&t, &c);
+        root = mul(&root, &b);
+    }
+    Some(root)
+}
+
+/// An element of the group, in extended twisted Edwards coordinates: the
+/// affine point is `(x / z, y / z)`, and `t = x * y / z`.
+#[derive(Clone, Debug)]
+pub struct Point {
+    x: Uint,
+    y: Uint,
+    z: Uint,
+    t: Uint,
+}
+
+impl PartialEq for Point {
+    /// Whether the points are the same element, up to the sign of both
+    /// coordinates.
+    fn eq(&self, other: &Self) -> bool {
+        mul(&self.x, &other.y) == mul(&other.x, &self.y)
+    }
+}
+
+impl Eq for Point {}
+
+impl Point {
+    /// The neutral element.
+    pub fn identity() -> Self {
+        Self::from_affine(Uint::from(0u8), Uint::from(1u8))
+    }
+
+    /// The generator of the group, that of Bandersnatch.
+    pub fn generator() -> Self {
+        Self::from_affine(
+            Uint::from_bytes_be(&hex!("29c132cc2c0b34c5743711777bbe42f32b79c022ad998465e1e71866a252ae18")),
+            Uint::from_bytes_be(&hex!("2a6c669eda123e0f157d8b50badcd586358cad81eee464605e3167b6cc974166")),
+        )
+    }
+
+    fn from_affine(x: Uint, y: Uint) -> Self {
+        let t = mul(&x, &y);
+        Self {
+            x,
+            y,
+            z: Uint::from(1u8),
+            t,
+        }
+    }
+
+    /// The affine coordinates of the representative.
+    pub fn to_affine(&self) -> (Uint, Uint) {
+        let z_inverse = inv(&self.z);
+        (mul(&self.x, &z_inverse), mul(&self.y, &z_inverse))
+    }
+
+    /// The sum of two elements.
+    pub fn add(&self, other: &Self) -> Self {
+        // add-2008-hwcd, which doubles too.
+        let a_ = mul(&self.x, &other.x);
+        let b = mul(&self.y, &other.y);
+        let c = mul(&mul(d(), &self.t), &other.t);
+        let d_ = mul(&self.z, &other.z);
+        let e = sub(&sub(&mul(&add(&self.x, &self.y), &add(&other.x, &other.y)), &a_), &b);
+        let f = sub(&d_, &c);
+        let g = add(&d_, &c);
+        let h = sub(&b, &mul(&a(), &a_));
+        Self {
+            x: mul(&e, &f),
+            y: mul(&g, &h),
+            z: mul(&f, &g),
+            t: mul(&e, &h),
+        }
+    }
+
+    /// The inverse of the element.
+    pub fn neg(&self) -> Self {
+        Self {
+            x: neg(&self.x),
+            y: self.y.clone(),
+            z: self.z.clone(),
+            t: neg(&self.t),
+        }
+    }
+
+    /// The element added to itself `scalar` times.
+    pub fn mul(&self, scalar: &Uint) -> Self {
+        let mut result = Self::identity();
+        for i in (0..scalar.bits()).rev() {
+            result = result.add(&result);
+            if scalar.bit(i) {
+                result = result.add(self);
+            }
+        }
+        result
+    }
+
+    ///
+    ///     Serialize the element.
+    ///
+    ///     Returns
+    ///     -------
+    ///     bytes : `Bytes32`
+    ///         `x`, negated unless `y` is the larger of `y` and `-y`, big
+    ///         endian.
+    ///
+    pub fn serialize(&self) -> Bytes32 {
+        let (mut x, y) = self.to_affine();
+        if !is_largest(&y) {
+            x = neg(&x);
+        }
+        u256_to_be_bytes32(&x)
+    }
+
+    ///
+    ///     Deserialize an element.
+    ///
+    ///     Parameters
+    ///     ----------
+    ///     bytes :
+    ///         What [Point::serialize] returns.
+    ///
+    ///     Returns
+    ///     -------
+    ///     point : `Option<Point>`
+    ///         The element, or `None` if `bytes` isn't the `x` of a point of
+    ///         the group.
+    ///
+    pub fn deserialize(bytes: &Bytes32) -> Option<Self> {
+        let x = Uint::from_bytes_be(bytes);
+        if x >= *p() {
+            return None;
+        }
+        let x_squared = mul(&x, &x);
+        let numerator = sub(&Uint::from(1u8), &mul(&a(), &x_squared));
+        let denominator = sub(&Uint::from(1u8), &mul(d(), &x_squared));
+        // Points of the prime order subgroup have a square `1 - a * x^2`.
+        if !is_square(&numerator) {
+            return None;
+        }
+        let mut y = sqrt(&mul(&numerator, &inv(&denominator)))?;
+        if !is_largest(&y) {
+            y = neg(&y);
+        }
+        Some(Self::from_affine(x, y))
+    }
+
+    ///
+    ///     Map the element to a scalar, to commit to commitments.
+    ///
+    ///     Returns
+    ///     -------
+    ///     scalar : `Uint`
+    ///         `x / y`, which is the same for both representatives, reduced
+    ///         modulo the order of the group.
+    ///
+    pub fn map_to_scalar_field(&self) -> Uint {
+        mul(&self.x, &inv(&self.y)) % SCALAR_FIELD_MODULUS()
+    }
+}
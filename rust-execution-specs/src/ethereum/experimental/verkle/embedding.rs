@@ -0,0 +1,191 @@
+//!
+//! # Tree Embedding
+//!
+//! ## Introduction
+//!
+//! Where EIP-6800 places the state in the tree. A key is a 31-byte stem and
+//! a suffix byte: the 256 keys of a stem share a leaf. The stem is a
+//! Pedersen hash of the address and a tree index, so an account's header
+//! fields, its first 64 storage slots and its first 128 code chunks share
+//! the leaf of tree index 0.
+//!
+//! | Suffix of tree index 0 | Value                                      |
+//! |------------------------|--------------------------------------------|
+//! | 0                      | version, code size, nonce and balance      |
+//! | 1                      | the hash of the code                       |
+//! | 64 to 127              | storage slots 0 to 63                      |
+//! | 128 to 255             | code chunks 0 to 127                       |
+//!
+//! The other storage slots start at `256^31`, far from the code chunks
+//! after the first 128.
+//!
+
+use super::pedersen::commit;
+use crate::ethereum::{
+    base_types::{u256_to_be_bytes32, Bytes32, Uint, U256},
+    frontier::{
+        fork_types::{Account, Address},
+        vm::instructions::Ops,
+    },
+};
+
+pub const BASIC_DATA_LEAF_KEY: u8 = 0;
+pub const CODE_HASH_LEAF_KEY: u8 = 1;
+pub const HEADER_STORAGE_OFFSET: u64 = 64;
+pub const CODE_OFFSET: u64 = 128;
+pub const VERKLE_NODE_WIDTH: u64 = 256;
+
+/// Bytes of code in a chunk, after the byte counting the leading push data.
+pub const CHUNK_SIZE: usize = 31;
+
+/// Where the storage slots outside of the account's leaf start, `256^31`.
+#[allow(non_snake_case)]
+pub fn MAIN_STORAGE_OFFSET() -> Uint {
+    Uint::from(1u8) << (8 * 31)
+}
+
+/// The first 31 bytes of a key, which select its leaf.
+pub type Stem = [u8; 31];
+
+/// `value` as little endian bytes, padded or truncated to `N`.
+fn le_bytes<const N: usize>(value: &Uint) -> [u8; N] {
+    let mut bytes = [0; N];
+    for (byte, value) in bytes.iter_mut().zip(value.to_bytes_le()) {
+        *byte = value;
+    }
+    bytes
+}
+
+///
+///     Compute the key of a value of an account.
+///
+///     Parameters
+///     ----------
+///     address :
+///         The account.
+///     tree_index :
+///         Which of the account's stems.
+///     sub_index :
+///         The suffix.
+///
+///     Returns
+///     -------
+///     key : `Bytes32`
+///         The stem, the little endian commitment to the 32 byte address and
+///         `tree_index` as 16 byte integers mapped to a scalar, and the
+///         suffix.
+///
+pub fn get_tree_key(address: &Address, tree_index: &Uint, sub_index: u8) -> Bytes32 {
+    let mut input = [0; 64];
+    input[12..32].copy_from_slice(address);
+    input[32..].copy_from_slice(&le_bytes::<32>(tree_index));
+
+    // A marker for the length of the input, then the input.
+    let mut values = vec![Uint::from(2u16 + 256 * 64)];
+    values.extend(input.chunks(16).map(Uint::from_bytes_le));
+    let hash = commit(&values).map_to_scalar_field();
+
+    let mut key = le_bytes::<32>(&hash);
+    key[31] = sub_index;
+    key
+}
+
+/// The key of the version, code size, nonce and balance of an account.
+pub fn get_tree_key_for_basic_data(address: &Address) -> Bytes32 {
+    get_tree_key(address, &Uint::from(0u8), BASIC_DATA_LEAF_KEY)
+}
+
+/// The key of the hash of the code of an account.
+pub fn get_tree_key_for_code_hash(address: &Address) -> Bytes32 {
+    get_tree_key(address, &Uint::from(0u8), CODE_HASH_LEAF_KEY)
+}
+
+/// The key of a chunk of the code of an account, see [chunkify_code].
+pub fn get_tree_key_for_code_chunk(address: &Address, chunk_id: u64) -> Bytes32 {
+    let position = Uint::from(CODE_OFFSET) + chunk_id;
+    get_position_key(address, position)
+}
+
+/// The key of a storage slot of an account.
+pub fn get_tree_key_for_storage_slot(address: &Address, storage_key: &U256) -> Bytes32 {
+    let position = if *storage_key < Uint::from(CODE_OFFSET - HEADER_STORAGE_OFFSET) {
+        Uint::from(HEADER_STORAGE_OFFSET) + storage_key
+    } else {
+        MAIN_STORAGE_OFFSET() + storage_key
+    };
+    get_position_key(address, position)
+}
+
+fn get_position_key(address: &Address, position: Uint) -> Bytes32 {
+    let width = Uint::from(VERKLE_NODE_WIDTH);
+    let sub_index = u8::try_from(&position % &width).unwrap();
+    get_tree_key(address, &(position / width), sub_index)
+}
+
+///
+///     Pack the header fields of an account into the basic data leaf.
+///
+///     Parameters
+///     ----------
+///     account :
+///         The account.
+///
+///     Returns
+///     -------
+///     basic_data : `Bytes32`
+///         Big endian fields: the version (zero) in byte 0, the code size in
+///         bytes 5 to 7, the nonce in bytes 8 to 15, and the balance in bytes
+///         16 to 31.
+///
+pub fn basic_data(account: &Account) -> Bytes32 {
+    let mut data = [0; 32];
+    data[5..8].copy_from_slice(&(account.code.len() as u32).to_be_bytes()[1..]);
+    data[8..16].copy_from_slice(&u256_to_be_bytes32(&account.nonce)[24..]);
+    data[16..].copy_from_slice(&u256_to_be_bytes32(&account.balance)[16..]);
+    data
+}
+
+///
+///     Split code into the chunks the tree holds.
+///
+///     Parameters
+///     ----------
+///     code :
+///         The code.
+///
+///     Returns
+///     -------
+///     chunks : `Vec<Bytes32>`
+///         [CHUNK_SIZE] bytes of code each, the last one padded with zeros,
+///         after a byte counting how many of them at the start are `PUSH`
+///         data, so that jump destinations can be checked from a chunk
+///         alone.
+///
+pub fn chunkify_code(code: &[u8]) -> Vec<Bytes32> {
+    let padded_len = code.len().div_ceil(CHUNK_SIZE) * CHUNK_SIZE;
+    let mut code = code.to_vec();
+    // The push data of a truncated `PUSH` at the end still counts.
+    let mut push_data_end = vec![0; padded_len];
+    let mut pos = 0;
+    while pos < code.len() {
+        let push_size = Ops::try_from(code[pos]).map(|op| op.push_size()).unwrap_or(0);
+        pos += 1;
+        for end in push_data_end.iter_mut().skip(pos).take(push_size) {
+            *end = 1;
+        }
+        pos += push_size;
+    }
+    code.resize(padded_len, 0);
+
+    code.chunks(CHUNK_SIZE)
+        .enumerate()
+        .map(|(index, chunk)| {
+            let start = index * CHUNK_SIZE;
+            let leading = push_data_end[start..start + CHUNK_SIZE].iter().take_while(|&&data| data == 1).count();
+            let mut bytes = [0; 32];
+            bytes[0] = leading as u8;
+            bytes[1..].copy_from_slice(chunk);
+            bytes
+        })
+        .collect()
+}
@@ -0,0 +1,250 @@
+//!
+//! # Inner Product Arguments
+//!
+//! ## Introduction
+//!
+//! A committed vector is read as a polynomial in evaluation form: its
+//! values at `0, 1, ..., n - 1`. Its value at any point `z` is an inner
+//! product `<a, b>` of the vector with weights `b` depending on `z` only, so
+//! an inner product argument proves it without revealing the vector.
+//!
+//! Each round halves the vectors, committing to the two cross terms `L` and
+//! `R`, until a single scalar is left. Challenges are derived with a SHA-256
+//! transcript, like go-ipa's.
+//!
+
+use sha2::{Digest, Sha256};
+
+use super::{
+    banderwagon::{Point, SCALAR_FIELD_MODULUS},
+    pedersen::{crs, multi_scalar_mul},
+};
+use crate::ethereum::base_types::Uint;
+
+fn scalar_bytes(scalar: &Uint) -> [u8; 32] {
+    let mut bytes = [0; 32];
+    let le = scalar.to_bytes_le();
+    bytes[..le.len()].copy_from_slice(&le);
+    bytes
+}
+
+fn inverse(scalar: &Uint) -> Uint {
+    let r = SCALAR_FIELD_MODULUS();
+    scalar.modpow(&(&r - 2u8), &r)
+}
+
+fn inner_product(a: &[Uint], b: &[Uint]) -> Uint {
+    a.iter().zip(b).fold(Uint::from(0u8), |sum, (a, b)| (sum + a * b) % SCALAR_FIELD_MODULUS())
+}
+
+/// `left + x * right`, element by element.
+fn fold_scalars(left: &[Uint], right: &[Uint], x: &Uint) -> Vec<Uint> {
+    left.iter().zip(right).map(|(l, r)| (l + x * r) % SCALAR_FIELD_MODULUS()).collect()
+}
+
+fn fold_points(left: &[Point], right: &[Point], x: &Uint) -> Vec<Point> {
+    left.iter().zip(right).map(|(l, r)| l.add(&r.mul(x))).collect()
+}
+
+/// The Fiat-Shamir transcript, hashing everything the prover sent to
+/// derive the challenges.
+pub struct Transcript {
+    state: Vec<u8>,
+}
+
+impl Transcript {
+    pub fn new(label: &str) -> Self {
+        Self {
+            state: label.as_bytes().to_vec(),
+        }
+    }
+
+    pub fn domain_separator(&mut self, label: &str) {
+        self.state.extend(label.as_bytes());
+    }
+
+    pub fn append_scalar(&mut self, scalar: &Uint, label: &str) {
+        self.state.extend(label.as_bytes());
+        self.state.extend(scalar_bytes(scalar));
+    }
+
+    pub fn append_point(&mut self, point: &Point, label: &str) {
+        self.state.extend(label.as_bytes());
+        self.state.extend(point.serialize());
+    }
+
+    /// Derive a challenge from everything appended, which it replaces.
+    pub fn challenge_scalar(&mut self, label: &str) -> Uint {
+        self.state.extend(label.as_bytes());
+        let hash = Sha256::digest(&self.state);
+        self.state = hash.to_vec();
+        Uint::from_bytes_le(&hash) % SCALAR_FIELD_MODULUS()
+    }
+}
+
+/// A proof that a commitment opens to a value at a point.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IpaProof {
+    /// The left cross terms, one per round.
+    pub l: Vec<Point>,
+    /// The right cross terms, one per round.
+    pub r: Vec<Point>,
+    /// The committed vector, folded down to one scalar.
+    pub a: Uint,
+}
+
+///
+///     Compute the weights evaluating a polynomial in evaluation form.
+///
+///     Parameters
+///     ----------
+///     z :
+///         The point.
+///     n :
+///         The size of the domain, `0, 1, ..., n - 1`.
+///
+///     Returns
+///     -------
+///     weights : `Vec<Uint>`
+///         The `b` such that the value at `z` of the polynomial taking
+///         values `a` on the domain is `<a, b>`: the barycentric weights
+///         `A(z) / (A'(i) * (z - i))`, or a unit vector if `z` is in the
+///         domain.
+///
+pub fn evaluation_weights(z: &Uint, n: usize) -> Vec<Uint> {
+    let r = SCALAR_FIELD_MODULUS();
+    if *z < Uint::from(n) {
+        return (0..n).map(|i| Uint::from(u8::from(Uint::from(i) == *z))).collect();
+    }
+    // A(z) = (z - 0) * (z - 1) * ... * (z - n + 1)
+    let z_minus = |i: usize| (z + &r - Uint::from(i) % &r) % &r;
+    let vanishing = (0..n).fold(Uint::from(1u8), |product, i| product * z_minus(i) % &r);
+    (0..n)
+        .map(|i| {
+            // A'(i) = (i - 0) * ... * (i - n + 1), without (i - i).
+            let derivative = (0..n).filter(|&j| j != i).fold(Uint::from(1u8), |product, j| {
+                let difference = (Uint::from(i) + &r - Uint::from(j)) % &r;
+                product * difference % &r
+            });
+            &vanishing * inverse(&(derivative * z_minus(i) % &r)) % &r
+        })
+        .collect()
+}
+
+fn begin(transcript: &mut Transcript, commitment: &Point, z: &Uint, y: &Uint) -> Point {
+    transcript.append_point(commitment, "C");
+    transcript.append_scalar(z, "input point");
+    transcript.append_scalar(y, "output point");
+    let w = transcript.challenge_scalar("w");
+    Point::generator().mul(&w)
+}
+
+///
+///     Prove the value of a committed polynomial at a point.
+///
+///     Parameters
+///     ----------
+///     transcript :
+///         The transcript, which the verifier must start in the same state.
+///     commitment :
+///         The commitment to `a`, with the first generators of the
+///         commitment key.
+///     a :
+///         The polynomial in evaluation form, its length a power of two no
+///         larger than the commitment key.
+///     z :
+///         The point.
+///
+///     Returns
+///     -------
+///     y : `Uint`
+///         The value at `z`.
+///     proof : `IpaProof`
+///         The proof.
+///
+pub fn prove(transcript: &mut Transcript, commitment: &Point, a: &[Uint], z: &Uint) -> (Uint, IpaProof) {
+    assert!(a.len().is_power_of_two() && a.len() <= crs().len());
+    transcript.domain_separator("ipa");
+    let mut b = evaluation_weights(z, a.len());
+    let y = inner_product(a, &b);
+    let q = begin(transcript, commitment, z, &y);
+
+    let mut a = a.to_vec();
+    let mut generators = crs()[..a.len()].to_vec();
+    let (mut l, mut r) = (vec![], vec![]);
+    while a.len() > 1 {
+        let half = a.len() / 2;
+        let (a_left, a_right) = a.split_at(half);
+        let (b_left, b_right) = b.split_at(half);
+        let (g_left, g_right) = generators.split_at(half);
+
+        let left = multi_scalar_mul(g_left, a_right).add(&q.mul(&inner_product(a_right, b_left)));
+        let right = multi_scalar_mul(g_right, a_left).add(&q.mul(&inner_product(a_left, b_right)));
+        transcript.append_point(&left, "L");
+        transcript.append_point(&right, "R");
+        let x = transcript.challenge_scalar("x");
+        let x_inverse = inverse(&x);
+
+        a = fold_scalars(a_left, a_right, &x);
+        b = fold_scalars(b_left, b_right, &x_inverse);
+        generators = fold_points(g_left, g_right, &x_inverse);
+        l.push(left);
+        r.push(right);
+    }
+
+    let proof = IpaProof {
+        l,
+        r,
+        a: a.pop().unwrap(),
+    };
+    (y, proof)
+}
+
+///
+///     Check a proof of the value of a committed polynomial at a point.
+///
+///     Parameters
+///     ----------
+///     transcript :
+///         The transcript, in the state the prover started it in.
+///     commitment :
+///         The commitment.
+///     n :
+///         The length of the committed vector.
+///     z :
+///         The point.
+///     y :
+///         The claimed value at `z`.
+///     proof :
+///         The proof.
+///
+///     Returns
+///     -------
+///     valid : `bool`
+///         Whether the proof shows the polynomial takes `y` at `z`.
+///
+pub fn verify(transcript: &mut Transcript, commitment: &Point, n: usize, z: &Uint, y: &Uint, proof: &IpaProof) -> bool {
+    if !n.is_power_of_two() || n > crs().len() || 1 << proof.l.len() != n || proof.r.len() != proof.l.len() {
+        return false;
+    }
+    transcript.domain_separator("ipa");
+    let q = begin(transcript, commitment, z, y);
+
+    let mut folded = commitment.add(&q.mul(y));
+    let mut b = evaluation_weights(z, n);
+    let mut generators = crs()[..n].to_vec();
+    for (left, right) in proof.l.iter().zip(&proof.r) {
+        transcript.append_point(left, "L");
+        transcript.append_point(right, "R");
+        let x = transcript.challenge_scalar("x");
+        let x_inverse = inverse(&x);
+        folded = folded.add(&left.mul(&x)).add(&right.mul(&x_inverse));
+
+        let half = b.len() / 2;
+        b = fold_scalars(&b[..half], &b[half..], &x_inverse);
+        generators = fold_points(&generators[..half], &generators[half..], &x_inverse);
+    }
+
+    let expected = generators[0].mul(&proof.a).add(&q.mul(&(&proof.a * &b[0] % SCALAR_FIELD_MODULUS())));
+    folded == expected
+}
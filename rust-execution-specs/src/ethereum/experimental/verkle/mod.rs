@@ -0,0 +1,27 @@
+//!
+//! # Verkle Trees
+//!
+//! ## Introduction
+//!
+//! The verkle tree of EIP-6800, which would replace the Merkle Patricia
+//! tries of the state with a single tree of width 256 committed to with
+//! Pedersen vector commitments, so that witnesses stay small.
+//!
+//! - [banderwagon]: the prime order group the commitments live in,
+//! - [pedersen]: the commitment key and vector commitments,
+//! - [ipa]: inner product argument proofs of the value of a committed
+//!   vector's polynomial at a point,
+//! - [embedding]: where accounts, storage slots and code chunks are placed
+//!   in the tree,
+//! - [tree]: the tree itself and its root.
+//!
+//! Points are serialized and keys derived like go-ipa and go-verkle, whose
+//! commitment key this reproduces. The arithmetic favors clarity over
+//! speed, and multiproofs for witnesses aren't implemented.
+//!
+
+pub mod banderwagon;
+pub mod embedding;
+pub mod ipa;
+pub mod pedersen;
+pub mod tree;
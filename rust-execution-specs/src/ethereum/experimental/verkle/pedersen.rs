@@ -0,0 +1,88 @@
+//!
+//! # Pedersen Commitments
+//!
+//! ## Introduction
+//!
+//! A vector of up to 256 scalars is committed to as `v_0 * G_0 + ... +
+//! v_255 * G_255`. The generators `G_i` have no known discrete logarithm
+//! relative to each other: each is the first point whose `x` is the SHA-256
+//! of the seed `"eth_verkle_oct_2021"` and a big endian 64-bit counter.
+//!
+
+use std::sync::OnceLock;
+
+use sha2::{Digest, Sha256};
+
+use super::banderwagon::{Point, BASE_FIELD_MODULUS};
+use crate::ethereum::base_types::{u256_to_be_bytes32, Uint};
+
+/// The seed the commitment key is derived from.
+pub const CRS_SEED: &[u8] = b"eth_verkle_oct_2021";
+
+/// Scalars a commitment commits to, the width of the tree.
+pub const VERKLE_NODE_WIDTH: usize = 256;
+
+///
+///     Get the commitment key, derived on first use.
+///
+///     Returns
+///     -------
+///     crs : `&[Point]`
+///         The [VERKLE_NODE_WIDTH] generators.
+///
+pub fn crs() -> &'static [Point] {
+    static CRS: OnceLock<Vec<Point>> = OnceLock::new();
+    CRS.get_or_init(|| {
+        let mut points = vec![];
+        let mut counter = 0u64;
+        while points.len() < VERKLE_NODE_WIDTH {
+            let hash = Sha256::new().chain_update(CRS_SEED).chain_update(counter.to_be_bytes()).finalize();
+            counter += 1;
+            let x = Uint::from_bytes_be(&hash) % BASE_FIELD_MODULUS();
+            points.extend(Point::deserialize(&u256_to_be_bytes32(&x)));
+        }
+        points
+    })
+}
+
+///
+///     Compute the sum of points multiplied by scalars.
+///
+///     Parameters
+///     ----------
+///     points :
+///         The points.
+///     scalars :
+///         A scalar for each point, as many or fewer.
+///
+///     Returns
+///     -------
+///     sum : `Point`
+///         `scalars[0] * points[0] + scalars[1] * points[1] + ...`
+///
+pub fn multi_scalar_mul(points: &[Point], scalars: &[Uint]) -> Point {
+    let zero = Uint::from(0u8);
+    points
+        .iter()
+        .zip(scalars)
+        .filter(|(_, scalar)| **scalar != zero)
+        .fold(Point::identity(), |sum, (point, scalar)| sum.add(&point.mul(scalar)))
+}
+
+///
+///     Commit to a vector.
+///
+///     Parameters
+///     ----------
+///     values :
+///         At most [VERKLE_NODE_WIDTH] scalars, the missing ones zero.
+///
+///     Returns
+///     -------
+///     commitment : `Point`
+///         The commitment.
+///
+pub fn commit(values: &[Uint]) -> Point {
+    assert!(values.len() <= VERKLE_NODE_WIDTH);
+    multi_scalar_mul(crs(), values)
+}
@@ -0,0 +1,174 @@
+//!
+//! # Verkle Tree
+//!
+//! ## Introduction
+//!
+//! The tree maps 32-byte keys to 32-byte values. Keys sharing their 31-byte
+//! stem share a leaf, which commits to the stem and to the 256 values under
+//! it in two halves, `C1` and `C2`. Internal nodes commit to their 256
+//! children, and are only as deep as needed to tell the stems apart: a
+//! subtree with one stem is a leaf. The root is always an internal node.
+//!
+//! Each value is committed to as two scalars, its low and high 16 bytes as
+//! little endian integers, with `2^128` added to the low one so that a zero
+//! value differs from a missing one.
+//!
+
+use std::collections::BTreeMap;
+
+use super::{
+    embedding::{
+        basic_data, chunkify_code, get_tree_key_for_basic_data, get_tree_key_for_code_chunk,
+        get_tree_key_for_code_hash, get_tree_key_for_storage_slot, Stem,
+    },
+    pedersen::{commit, VERKLE_NODE_WIDTH},
+    banderwagon::Point,
+};
+use crate::ethereum::{
+    base_types::{u256_to_be_bytes32, Bytes32, Uint, U256},
+    crypto::hash::keccak256,
+    frontier::state::{get_accounts, get_storage_slots, State},
+};
+
+/// A verkle tree, the values it holds; commitments are computed from them
+/// when asked for.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct VerkleTree {
+    leaves: BTreeMap<Stem, BTreeMap<u8, Bytes32>>,
+}
+
+fn split_key(key: &Bytes32) -> (Stem, u8) {
+    let mut stem = [0; 31];
+    stem.copy_from_slice(&key[..31]);
+    (stem, key[31])
+}
+
+impl VerkleTree {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the value of a key.
+    pub fn insert(&mut self, key: Bytes32, value: Bytes32) {
+        let (stem, suffix) = split_key(&key);
+        self.leaves.entry(stem).or_default().insert(suffix, value);
+    }
+
+    /// The value of a key, if it was set.
+    pub fn get(&self, key: &Bytes32) -> Option<&Bytes32> {
+        let (stem, suffix) = split_key(key);
+        self.leaves.get(&stem)?.get(&suffix)
+    }
+
+    /// The number of values.
+    pub fn len(&self) -> usize {
+        self.leaves.values().map(BTreeMap::len).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.leaves.is_empty()
+    }
+
+    ///
+    ///     Compute the commitment to the root.
+    ///
+    ///     Returns
+    ///     -------
+    ///     commitment : `Point`
+    ///         The commitment, the identity for an empty tree.
+    ///
+    pub fn root_commitment(&self) -> Point {
+        let stems: Vec<&Stem> = self.leaves.keys().collect();
+        self.internal_commitment(&stems, 0)
+    }
+
+    ///
+    ///     Compute the root of the tree.
+    ///
+    ///     Returns
+    ///     -------
+    ///     root : `Bytes32`
+    ///         The serialized commitment to the root, zero for an empty tree.
+    ///
+    pub fn root(&self) -> Bytes32 {
+        self.root_commitment().serialize()
+    }
+
+    /// The commitment to the internal node at `depth` above `stems`, which
+    /// are sorted and share their first `depth` bytes.
+    fn internal_commitment(&self, stems: &[&Stem], depth: usize) -> Point {
+        let mut children = vec![Uint::from(0u8); VERKLE_NODE_WIDTH];
+        for group in stems.chunk_by(|a, b| a[depth] == b[depth]) {
+            let child = match group {
+                [stem] => self.leaf_commitment(stem),
+                _ => self.internal_commitment(group, depth + 1),
+            };
+            children[usize::from(group[0][depth])] = child.map_to_scalar_field();
+        }
+        commit(&children)
+    }
+
+    /// The commitment to the leaf of `stem`.
+    fn leaf_commitment(&self, stem: &Stem) -> Point {
+        let mut halves = vec![Uint::from(0u8); 2 * VERKLE_NODE_WIDTH];
+        for (&suffix, value) in &self.leaves[stem] {
+            let index = 2 * usize::from(suffix);
+            halves[index] = Uint::from_bytes_le(&value[..16]) + (Uint::from(1u8) << 128);
+            halves[index + 1] = Uint::from_bytes_le(&value[16..]);
+        }
+        let (c1, c2) = halves.split_at(VERKLE_NODE_WIDTH);
+        commit(&[
+            Uint::from(1u8),
+            Uint::from_bytes_le(stem),
+            commit(c1).map_to_scalar_field(),
+            commit(c2).map_to_scalar_field(),
+        ])
+    }
+}
+
+///
+///     Build the verkle tree holding a state.
+///
+///     Parameters
+///     ----------
+///     state :
+///         The state.
+///
+///     Returns
+///     -------
+///     tree : `VerkleTree`
+///         The basic data, code hash and code chunks of every account, and
+///         its non-zero storage slots as big endian values.
+///
+pub fn state_tree(state: &State) -> VerkleTree {
+    let mut tree = VerkleTree::new();
+    for (address, account) in get_accounts(state) {
+        tree.insert(get_tree_key_for_basic_data(&address), basic_data(&account));
+        tree.insert(get_tree_key_for_code_hash(&address), keccak256(&account.code));
+        for (chunk_id, chunk) in chunkify_code(&account.code).into_iter().enumerate() {
+            tree.insert(get_tree_key_for_code_chunk(&address, chunk_id as u64), chunk);
+        }
+        for (key, value) in get_storage_slots(state, &address) {
+            let key = U256::from_bytes_be(&key);
+            tree.insert(get_tree_key_for_storage_slot(&address, &key), u256_to_be_bytes32(&value));
+        }
+    }
+    tree
+}
+
+///
+///     Compute the verkle root of a state.
+///
+///     Parameters
+///     ----------
+///     state :
+///         The state.
+///
+///     Returns
+///     -------
+///     root : `Bytes32`
+///         The root of [state_tree].
+///
+pub fn state_root(state: &State) -> Bytes32 {
+    state_tree(state).root()
+}
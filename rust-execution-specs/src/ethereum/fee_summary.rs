@@ -0,0 +1,75 @@
+//!
+//! # Fee Summary
+//!
+//! ## Introduction
+//!
+//! Where the ether of a block comes from and goes to, as `apply_body`
+//! returns it in a [BlockFeeSummary] to check issuance against.
+//!
+//! In Frontier the transactions pay for their gas at their gas price, all of
+//! which goes to the coinbase, and ether is created for the miner of the
+//! block and the miners of its ommers. The summary also keeps the base fee
+//! and blob fees later forks burn, which are zero in Frontier.
+//!
+
+use crate::ethereum::base_types::{Uint, U256};
+
+/// The value flow of one block, in wei.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct BlockFeeSummary {
+    /// Everything the transactions paid for execution gas.
+    pub transaction_fees: U256,
+    /// The part of `transaction_fees` paid to the coinbase.
+    pub priority_fees: U256,
+    /// The part of `transaction_fees` burned as base fee.
+    pub burned_base_fee: U256,
+    /// What the transactions paid for blob gas, which is burned.
+    pub burned_blob_fee: U256,
+    /// Ether created for the miner, including the reward for ommers.
+    pub miner_reward: U256,
+    /// Ether created for the miners of the ommers.
+    pub ommer_rewards: U256,
+}
+
+impl BlockFeeSummary {
+    ///
+    ///     Account for the execution gas of a transaction.
+    ///
+    ///     Parameters
+    ///     ----------
+    ///     gas_used :
+    ///         Gas used by the transaction, after refunds.
+    ///     gas_price :
+    ///         The price the sender paid per gas.
+    ///     base_fee_per_gas :
+    ///         The part of the price that is burned, zero before London.
+    ///
+    pub fn add_transaction(&mut self, gas_used: &Uint, gas_price: &U256, base_fee_per_gas: &U256) {
+        let burned = gas_used * base_fee_per_gas;
+        let fee = gas_used * gas_price;
+        self.priority_fees += &fee - &burned;
+        self.burned_base_fee += burned;
+        self.transaction_fees += fee;
+    }
+
+    ///
+    ///     The ether the block creates.
+    ///
+    pub fn issuance(&self) -> U256 {
+        &self.miner_reward + &self.ommer_rewards
+    }
+
+    ///
+    ///     The ether the block destroys.
+    ///
+    pub fn burned(&self) -> U256 {
+        &self.burned_base_fee + &self.burned_blob_fee
+    }
+
+    ///
+    ///     What the coinbase receives: the priority fees and the miner reward.
+    ///
+    pub fn coinbase_income(&self) -> U256 {
+        &self.priority_fees + &self.miner_reward
+    }
+}
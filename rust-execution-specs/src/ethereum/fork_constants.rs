@@ -0,0 +1,107 @@
+//!
+//! # Fork Constants
+//!
+//! ## Introduction
+//!
+//! The consensus critical constants of each fork, by name, for tools that
+//! compare the parameters of forks. Each fork defines its own in its
+//! `constants` module, with where they come from.
+//!
+//! A fork lists the constants it uses: Spurious Dragon adds
+//! `MAX_CODE_SIZE` (EIP-170) and Cancun the blob parameters (EIP-4844), so
+//! a constant may exist in only one of two forks.
+//!
+
+use crate::prelude::*;
+use crate::ethereum::frontier;
+use crate::ethereum::hard_fork::HardFork;
+
+/// A constant of a fork.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ForkConstant {
+    /// The name the fork gives it.
+    pub name: &'static str,
+    pub value: u64,
+    /// Where it is defined, e.g. an EIP or a section of the Yellow Paper.
+    pub source: &'static str,
+}
+
+/// A constant that differs between two forks.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ConstantChange {
+    pub name: &'static str,
+    /// The value in the first fork, `None` if it doesn't have it.
+    pub before: Option<u64>,
+    /// The value in the second fork, `None` if it doesn't have it.
+    pub after: Option<u64>,
+}
+
+///
+///     Get the constants of a fork.
+///
+///     Parameters
+///     ----------
+///     fork :
+///         The fork.
+///
+///     Returns
+///     -------
+///     constants : `&[ForkConstant]`
+///         Every constant of the fork.
+///
+pub fn fork_constants(fork: HardFork) -> &'static [ForkConstant] {
+    match fork {
+        HardFork::Frontier => frontier::constants::CONSTANTS,
+    }
+}
+
+///
+///     Get a constant of a fork by name.
+///
+///     Parameters
+///     ----------
+///     fork :
+///         The fork.
+///     name :
+///         The name of the constant, e.g. `GAS_SLOAD`.
+///
+///     Returns
+///     -------
+///     constant : `Option<&ForkConstant>`
+///         The constant, or `None` if the fork doesn't have it.
+///
+pub fn fork_constant(fork: HardFork, name: &str) -> Option<&'static ForkConstant> {
+    fork_constants(fork).iter().find(|constant| constant.name == name)
+}
+
+///
+///     Compare the constants of two forks.
+///
+///     Parameters
+///     ----------
+///     before :
+///         The first fork.
+///     after :
+///         The second fork.
+///
+///     Returns
+///     -------
+///     changes : `Vec<ConstantChange>`
+///         The constants whose value differs or that only one fork has, in
+///         the order of `before` then the ones `after` adds.
+///
+pub fn compare_constants(before: HardFork, after: HardFork) -> Vec<ConstantChange> {
+    let value = |fork, name| fork_constant(fork, name).map(|constant| constant.value);
+    let names = fork_constants(before)
+        .iter()
+        .chain(fork_constants(after).iter().filter(|constant| fork_constant(before, constant.name).is_none()))
+        .map(|constant| constant.name);
+    names
+        .map(|name| ConstantChange {
+            name,
+            before: value(before, name),
+            after: value(after, name),
+        })
+        .filter(|change| change.before != change.after)
+        .collect()
+}
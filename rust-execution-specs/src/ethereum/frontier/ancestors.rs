@@ -0,0 +1,157 @@
+//!
+//! # Ancestors
+//!
+//! ## Introduction
+//!
+//! What a block needs to know of the blocks before it: the hashes of the
+//! last 256 blocks, for `BLOCKHASH`, and the headers of the last few blocks
+//! and the hashes of their ommers, for the ommer rules.
+//!
+//! Finding those in `BlockChain.blocks` hashes the recent blocks again for
+//! every block. A chain runner keeps an [Ancestors] instead, pushing each
+//! block it applies, so that every block is hashed once.
+//!
+
+use crate::prelude::*;
+use alloc::collections::VecDeque;
+
+use super::{
+    constants::MAX_OMMER_DEPTH,
+    fork_types::{Block, Hash32, Header},
+};
+
+/// The headers kept, enough for the ommer rules, which look back
+/// `MAX_OMMER_DEPTH + 1` blocks.
+pub const ANCESTOR_DEPTH: usize = 8;
+/// The block hashes kept, those `BLOCKHASH` can return.
+pub const BLOCK_HASH_DEPTH: usize = 256;
+
+const _: () = assert!(ANCESTOR_DEPTH > MAX_OMMER_DEPTH as usize);
+
+#[derive(Clone, Debug)]
+struct Ancestor {
+    header: Header,
+    hash: Hash32,
+    ommers: Vec<Hash32>,
+}
+
+impl Ancestor {
+    fn new(block: &Block, hash: Hash32) -> Self {
+        Self {
+            header: block.header.clone(),
+            hash,
+            ommers: block.ommers.iter().map(Header::hash).collect(),
+        }
+    }
+}
+
+///
+///     The last blocks of a chain, as the next block sees them.
+///
+#[derive(Clone, Debug, Default)]
+pub struct Ancestors {
+    /// The last `ANCESTOR_DEPTH` blocks, oldest first.
+    headers: VecDeque<Ancestor>,
+    /// The hashes of the last `BLOCK_HASH_DEPTH` blocks, oldest first.
+    hashes: VecDeque<Hash32>,
+}
+
+impl Ancestors {
+    ///
+    ///     Collect the ancestors of the block after the last of `blocks`.
+    ///
+    ///     The hashes are read from the parent hashes of the last 255
+    ///     blocks, so that only the last block is hashed, and so include the
+    ///     hash of the parent of the first of them, zero for the genesis
+    ///     block.
+    ///
+    ///     Parameters
+    ///     ----------
+    ///     blocks :
+    ///         The last blocks of a chain, oldest first.
+    ///
+    pub fn new(blocks: &[Block]) -> Self {
+        let Some(head) = blocks.last() else {
+            return Self::default();
+        };
+        let head_hash = head.hash();
+        let mut hashes: VecDeque<Hash32> = blocks[blocks.len().saturating_sub(BLOCK_HASH_DEPTH - 1)..]
+            .iter()
+            .map(|block| block.header.parent_hash)
+            .collect();
+        hashes.push_back(head_hash);
+
+        let recent = &blocks[blocks.len().saturating_sub(ANCESTOR_DEPTH)..];
+        let headers = recent
+            .iter()
+            .zip(hashes.iter().skip(hashes.len() - recent.len()))
+            .map(|(block, hash)| Ancestor::new(block, *hash))
+            .collect();
+        Self { headers, hashes }
+    }
+
+    ///
+    ///     Add the block after the head.
+    ///
+    ///     Parameters
+    ///     ----------
+    ///     block :
+    ///         The new head.
+    ///     hash :
+    ///         The hash of `block`.
+    ///
+    pub fn push(&mut self, block: &Block, hash: Hash32) {
+        if self.headers.len() == ANCESTOR_DEPTH {
+            self.headers.pop_front();
+        }
+        self.headers.push_back(Ancestor::new(block, hash));
+        if self.hashes.len() == BLOCK_HASH_DEPTH {
+            self.hashes.pop_front();
+        }
+        self.hashes.push_back(hash);
+    }
+
+    ///
+    ///     The hash of the head, or `None` if there are no blocks.
+    ///
+    pub fn head_hash(&self) -> Option<&Hash32> {
+        self.hashes.back()
+    }
+
+    ///
+    ///     The hashes of the last 256 blocks, in order of increasing block
+    ///     number, as `BlockEnvironment.block_hashes` takes them.
+    ///
+    pub fn block_hashes(&self) -> Vec<Hash32> {
+        self.hashes.iter().copied().collect()
+    }
+
+    ///
+    ///     The header of the block `depth` blocks before the head, the head
+    ///     being at depth 0, if it is kept.
+    ///
+    pub fn header(&self, depth: usize) -> Option<&Header> {
+        self.ancestor(depth).map(|ancestor| &ancestor.header)
+    }
+
+    ///
+    ///     The hash of the block `depth` blocks before the head, if its
+    ///     header is kept.
+    ///
+    pub fn hash(&self, depth: usize) -> Option<&Hash32> {
+        self.ancestor(depth).map(|ancestor| &ancestor.hash)
+    }
+
+    ///
+    ///     The hashes of the ommers of the block `depth` blocks before the
+    ///     head, if its header is kept.
+    ///
+    pub fn ommers(&self, depth: usize) -> Option<&[Hash32]> {
+        self.ancestor(depth).map(|ancestor| &ancestor.ommers[..])
+    }
+
+    fn ancestor(&self, depth: usize) -> Option<&Ancestor> {
+        let index = self.headers.len().checked_sub(depth + 1)?;
+        self.headers.get(index)
+    }
+}
@@ -0,0 +1,138 @@
+//!
+//! # Frontier Constants
+//!
+//! ## Introduction
+//!
+//! Every consensus critical constant of Frontier, each with where it is
+//! defined. Gas costs are named as in the Yellow Paper's fee schedule.
+//!
+//! The modules that use the constants re-export them, so they are also
+//! found where they are used. [CONSTANTS] lists them all for tooling, see
+//! `fork_constants`.
+//!
+
+use crate::ethereum::{
+    base_types::{Uint, U256},
+    create_policy,
+    fork_constants::ForkConstant,
+    mining_reward,
+};
+
+macro_rules! constant {
+    (fn $name:ident: $ty:ident = $value:expr; $source:literal; $($doc:literal)*) => {
+        $(#[doc = $doc])*
+        #[doc = ""]
+        #[doc = concat!("Source: ", $source, ".")]
+        #[allow(non_snake_case)]
+        pub fn $name() -> $ty {
+            $ty::from($value as u64)
+        }
+    };
+    (const $name:ident: $ty:ident = $value:expr; $source:literal; $($doc:literal)*) => {
+        $(#[doc = $doc])*
+        #[doc = ""]
+        #[doc = concat!("Source: ", $source, ".")]
+        pub const $name: $ty = $value;
+    };
+}
+
+/// Define the constants, as functions for the big integer ones, and list
+/// them in `CONSTANTS`.
+macro_rules! constants {
+    ($($(#[doc = $doc:literal])* $kind:ident $name:ident: $ty:ident = $value:expr, $source:literal;)*) => {
+        $(constant!($kind $name: $ty = $value; $source; $($doc)*);)*
+
+        /// Every constant, in the order they are defined.
+        pub const CONSTANTS: &[ForkConstant] = &[$(ForkConstant {
+            name: stringify!($name),
+            value: $value as u64,
+            source: $source,
+        }),*];
+    };
+}
+
+constants! {
+    // Blocks
+
+    /// Ether created for the miner of a block, in wei.
+    fn BLOCK_REWARD: U256 = mining_reward::FRONTIER.block_reward, "Yellow Paper, section 11.3 (R_block)";
+    /// A block's gas limit may differ from its parent's by less than the
+    /// parent's over this.
+    const GAS_LIMIT_ADJUSTMENT_FACTOR: u64 = 1024, "Yellow Paper, section 4.3.4";
+    /// The lowest gas limit of a block.
+    const GAS_LIMIT_MINIMUM: u64 = 5000, "Yellow Paper, section 4.3.4";
+    /// The lowest difficulty of a block.
+    const MINIMUM_DIFFICULTY: u64 = 131072, "Yellow Paper, section 4.3.4 (D_0)";
+    /// The difficulty changes by the parent's over this.
+    const DIFFICULTY_BOUND_DIVISOR: u64 = 2048, "Yellow Paper, section 4.3.4";
+    /// Blocks mined sooner than this many seconds after their parent raise
+    /// the difficulty.
+    const DIFFICULTY_DURATION_LIMIT: u64 = 13, "Yellow Paper, section 4.3.4";
+    /// The difficulty bomb doubles every this many blocks.
+    const BOMB_PERIOD: u64 = 100_000, "Yellow Paper, section 4.3.4 (epsilon)";
+    /// The longest `extra_data` of a header, in bytes.
+    const MAX_EXTRA_DATA_SIZE: usize = 32, "Yellow Paper, section 4.3.4";
+    /// How many generations back an ommer may be from the block.
+    const MAX_OMMER_DEPTH: u8 = 6, "Yellow Paper, section 11.1";
+
+    // Transactions
+
+    /// Paid by every transaction.
+    const TX_BASE_COST: u64 = 21000, "Yellow Paper, Appendix G (G_transaction)";
+    /// Paid for every non-zero byte of data of a transaction.
+    const TX_DATA_COST_PER_NON_ZERO: u64 = 68, "Yellow Paper, Appendix G (G_txdatanonzero)";
+    /// Paid for every zero byte of data of a transaction.
+    const TX_DATA_COST_PER_ZERO: u64 = 4, "Yellow Paper, Appendix G (G_txdatazero)";
+    /// The refund of a transaction is at most the gas it used over this.
+    const MAX_REFUND_QUOTIENT: u64 = 2, "Yellow Paper, section 6.2";
+
+    // The virtual machine
+
+    /// The deepest message call.
+    const STACK_DEPTH_LIMIT: u32 = 1024, "Yellow Paper, section 8";
+    /// The most items the stack holds.
+    const STACK_SIZE_LIMIT: usize = 1024, "Yellow Paper, section 9.4.2";
+    fn GAS_JUMPDEST: Uint = 1, "Yellow Paper, Appendix G (G_jumpdest)";
+    fn GAS_BASE: Uint = 2, "Yellow Paper, Appendix G (G_base)";
+    fn GAS_VERY_LOW: Uint = 3, "Yellow Paper, Appendix G (G_verylow)";
+    fn GAS_SLOAD: Uint = 50, "Yellow Paper, Appendix G (G_sload)";
+    fn GAS_STORAGE_SET: Uint = 20000, "Yellow Paper, Appendix G (G_sset)";
+    fn GAS_STORAGE_UPDATE: Uint = 5000, "Yellow Paper, Appendix G (G_sreset)";
+    fn GAS_STORAGE_CLEAR_REFUND: Uint = 15000, "Yellow Paper, Appendix G (R_sclear)";
+    fn GAS_LOW: Uint = 5, "Yellow Paper, Appendix G (G_low)";
+    fn GAS_MID: Uint = 8, "Yellow Paper, Appendix G (G_mid)";
+    fn GAS_HIGH: Uint = 10, "Yellow Paper, Appendix G (G_high)";
+    fn GAS_EXPONENTIATION: Uint = 10, "Yellow Paper, Appendix G (G_exp)";
+    fn GAS_EXPONENTIATION_PER_BYTE: Uint = 10, "Yellow Paper, Appendix G (G_expbyte)";
+    fn GAS_MEMORY: Uint = 3, "Yellow Paper, Appendix G (G_memory)";
+    /// The quadratic part of the cost of memory is the square of its words
+    /// over this.
+    fn GAS_MEMORY_QUADRATIC_DENOMINATOR: Uint = 512, "Yellow Paper, Appendix H (G_quaddivisor)";
+    fn GAS_KECCAK256: Uint = 30, "Yellow Paper, Appendix G (G_sha3)";
+    fn GAS_KECCAK256_WORD: Uint = 6, "Yellow Paper, Appendix G (G_sha3word)";
+    fn GAS_COPY: Uint = 3, "Yellow Paper, Appendix G (G_copy)";
+    fn GAS_BLOCK_HASH: Uint = 20, "Yellow Paper, Appendix G (G_blockhash)";
+    fn GAS_EXTERNAL: Uint = 20, "Yellow Paper, Appendix G (G_extcode)";
+    fn GAS_BALANCE: Uint = 20, "Yellow Paper, Appendix G (G_balance)";
+    fn GAS_LOG: Uint = 375, "Yellow Paper, Appendix G (G_log)";
+    fn GAS_LOG_DATA: Uint = 8, "Yellow Paper, Appendix G (G_logdata)";
+    fn GAS_LOG_TOPIC: Uint = 375, "Yellow Paper, Appendix G (G_logtopic)";
+    fn GAS_CREATE: Uint = 32000, "Yellow Paper, Appendix G (G_create)";
+    fn GAS_CODE_DEPOSIT: Uint = create_policy::FRONTIER.code_deposit, "Yellow Paper, Appendix G (G_codedeposit)";
+    fn GAS_ZERO: Uint = 0, "Yellow Paper, Appendix G (G_zero)";
+    fn GAS_CALL: Uint = 40, "Yellow Paper, Appendix G (G_call)";
+    fn GAS_NEW_ACCOUNT: Uint = 25000, "Yellow Paper, Appendix G (G_newaccount)";
+    fn GAS_CALL_VALUE: Uint = 9000, "Yellow Paper, Appendix G (G_callvalue)";
+    fn GAS_CALL_STIPEND: Uint = 2300, "Yellow Paper, Appendix G (G_callstipend)";
+    fn REFUND_SELF_DESTRUCT: Uint = 24000, "Yellow Paper, Appendix G (R_selfdestruct)";
+
+    // Precompiled contracts
+
+    fn GAS_ECRECOVER: Uint = 3000, "Yellow Paper, Appendix E";
+    fn GAS_SHA256: Uint = 60, "Yellow Paper, Appendix E";
+    fn GAS_SHA256_WORD: Uint = 12, "Yellow Paper, Appendix E";
+    fn GAS_RIPEMD160: Uint = 600, "Yellow Paper, Appendix E";
+    fn GAS_RIPEMD160_WORD: Uint = 120, "Yellow Paper, Appendix E";
+    fn GAS_IDENTITY: Uint = 15, "Yellow Paper, Appendix E";
+    fn GAS_IDENTITY_WORD: Uint = 3, "Yellow Paper, Appendix E";
+}
@@ -28,20 +28,29 @@
 // use super::utils::message::{prepare_message};
 // use super::vm::interpreter::{process_message_call};
 
-use std::{todo, ops::Add};
+use core::ops::Add;
 
-use crate::ethereum::{base_types::{U64, Uint, U256}, exceptions::EthereumException, rlp, frontier::state::state_root};
-use super::{fork_types::{Block, Hash32, keccak256, Header, Bloom, Root, Transaction, Address}, state::State};
+use crate::prelude::*;
+use crate::ethereum::{base_types::{Bytes, U64, Uint, U256, U256Ext, U256_CEIL_VALUE}, create_policy::{self, check_initcode_size}, ethash::{dataset_size, hashimoto_light}, rlp::RLP, crypto::elliptic_curve::{secp256k1_recover, SECP256K1N}, exceptions::{ErrorContext, EthereumException, InvalidTransactionReason}, fee_summary::BlockFeeSummary, intrinsic_gas::{self, IntrinsicGasInputs}, mining_reward, rlp, trace::Tracer, transaction_signature, frontier::state::state_root, utils::ensure::ensure};
+use super::{
+    ancestors::Ancestors,
+    bloom::logs_bloom,
+    fork_types::{Account, Address, Block, Hash32, keccak256, Header, Bloom, Receipt, Root, Transaction, Log},
+    state::{State, begin_transaction, commit_transaction, create_ether, destroy_account, get_account, increment_nonce, rollback_transaction, set_account_balance},
+    trie::{Trie, dummy_root, root, trie_set},
+    utils::message::prepare_message,
+    vm::{BlockEnvironment, Environment, Evm, TxEnvironment, interpreter::process_message_call},
+};
 
-// static  BLOCK_REWARD: U256 = U256::from(10u64).pow(18u64).mul(U256::from(5u64));
-pub const GAS_LIMIT_ADJUSTMENT_FACTOR: u64 = 1024;
-pub const GAS_LIMIT_MINIMUM:u64 = 5000;
-pub const MINIMUM_DIFFICULTY:u64 = 131072;
-pub const MAX_OMMER_DEPTH: u8 = 6;
+pub use super::constants::{
+    BLOCK_REWARD, BOMB_PERIOD, DIFFICULTY_BOUND_DIVISOR, DIFFICULTY_DURATION_LIMIT, GAS_LIMIT_ADJUSTMENT_FACTOR,
+    GAS_LIMIT_MINIMUM, MAX_EXTRA_DATA_SIZE, MAX_OMMER_DEPTH, MAX_REFUND_QUOTIENT, MINIMUM_DIFFICULTY,
+};
 
 ///
 ///     History and current state of the block chain.
 ///     
+#[derive(Clone)]
 pub struct BlockChain {
     pub blocks: Vec<Block>,
     pub state: State,
@@ -93,22 +102,7 @@ pub fn apply_fork(old: BlockChain) -> Result<BlockChain, EthereumException> {
 ///         Hashes of the recent 256 blocks in order of increasing block number.
 ///
 pub fn get_last_256_block_hashes(chain: &BlockChain) -> Result<Vec<Hash32>, EthereumException> {
-    // get last 255 blocks
-    let recent_blocks = chain.blocks.iter().rev().take(255).collect::<Vec<&Block>>();
-    if recent_blocks.len() == 0 {
-        return Ok(Vec::default());
-    }
-    
-    let mut recent_block_hashes = Vec::new();
-    for block in &recent_blocks {
-        let prev_block_hash = block.header.parent_hash;
-        recent_block_hashes.push(prev_block_hash);
-    }
-    
-    let last_header = &recent_blocks.last().unwrap().header;
-    let most_recent_block_hash = keccak256(Box::leak(rlp::encode(&last_header)));
-    recent_block_hashes.push(most_recent_block_hash);
-    return Ok(recent_block_hashes);
+    Ok(Ancestors::new(&chain.blocks).block_hashes())
 }
 
 ///
@@ -125,6 +119,8 @@ pub fn get_last_256_block_hashes(chain: &BlockChain) -> Result<Vec<Hash32>, Ethe
 ///     concerned, only those blocks are accessed. Practically, however, clients
 ///     should store more blocks to handle reorgs.
 ///
+///     An invalid block leaves `chain` as it was.
+///
 ///     Parameters
 ///     ----------
 ///     chain :
@@ -133,26 +129,98 @@ pub fn get_last_256_block_hashes(chain: &BlockChain) -> Result<Vec<Hash32>, Ethe
 ///         Block to apply to `chain`.
 ///
 pub fn state_transition(chain: &mut BlockChain, block: Block) -> Result<(), EthereumException> {
+    state_transition_traced(chain, block, &mut None)
+}
+
+///
+///     Applies a block, like `state_transition`, reporting the execution of
+///     every transaction to a tracer.
+///
+///     Parameters
+///     ----------
+///     tracer :
+///         Receives the trace events of every transaction, if set.
+///
+pub fn state_transition_traced(
+    chain: &mut BlockChain,
+    block: Block,
+    tracer: &mut Option<Box<dyn Tracer<Evm>>>,
+) -> Result<(), EthereumException> {
+    let mut ancestors = Ancestors::new(&chain.blocks);
+    state_transition_with_ancestors(chain, &mut ancestors, block, tracer)?;
+    Ok(())
+}
+
+///
+///     Applies a block, like `state_transition_traced`, looking up the
+///     recent blocks in `ancestors` rather than in `chain.blocks`.
+///
+///     Parameters
+///     ----------
+///     ancestors :
+///         The last blocks of `chain`, kept by the caller from one block to
+///         the next. The block is added to it once applied. If its head
+///         isn't the head of `chain` it is collected again.
+///
+///     Returns
+///     -------
+///     receipts : `Vec<Receipt>`
+///         The receipts of the transactions of the block, in order.
+///
+pub fn state_transition_with_ancestors(
+    chain: &mut BlockChain,
+    ancestors: &mut Ancestors,
+    block: Block,
+    tracer: &mut Option<Box<dyn Tracer<Evm>>>,
+) -> Result<Vec<Receipt>, EthereumException> {
     // should be there one block
     let parent_header = chain.blocks.last().unwrap().header.clone();
 
     validate_header(&block.header, parent_header)?;
-    validate_ommers(&block.ommers, block.header.clone(), chain)?;
-    let (gas_used, transactions_root, receipt_root, block_logs_bloom, state) = apply_body(&chain.state, get_last_256_block_hashes(chain)?, &block.header.coinbase, &block.header.number, &block.header.gas_limit, &block.header.timestamp, &block.header.difficulty, &block.transactions, &block.ommers)?;
-
-    assert!(gas_used == block.header.gas_used, "InvalidBlock");
-    assert!(transactions_root == block.header.transactions_root, "InvalidBlock");
-    assert!(state_root(&state) == block.header.state_root, "InvalidBlock");
-    assert!(receipt_root == block.header.receipt_root, "InvalidBlock");
-    assert!(block_logs_bloom == block.header.bloom, "InvalidBlock");
-    
+    if ancestors.head_hash() != Some(&block.header.parent_hash) {
+        *ancestors = Ancestors::new(&chain.blocks);
+    }
+    validate_ommers_with_ancestors(&block.ommers, &block.header, ancestors)?;
+    let block_env = BlockEnvironment {
+        block_hashes: ancestors.block_hashes(),
+        coinbase: block.header.coinbase,
+        number: block.header.number.clone(),
+        gas_limit: block.header.gas_limit.clone(),
+        time: block.header.timestamp.clone(),
+        difficulty: block.header.difficulty.clone(),
+    };
+    // The block runs in a state transaction, which is only committed once
+    // the block is known to be valid.
+    begin_transaction(&mut chain.state);
+    let checked = apply_body_traced(&mut chain.state, &block_env, &block.transactions, &block.ommers, tracer).and_then(
+        |(gas_used, transactions_root, receipt_root, block_logs_bloom, _, _, receipts)| {
+            ensure(gas_used == block.header.gas_used, EthereumException::InvalidBlock)?;
+            ensure(transactions_root == block.header.transactions_root, EthereumException::InvalidBlock)?;
+            ensure(state_root(&chain.state) == block.header.state_root, EthereumException::InvalidBlock)?;
+            ensure(receipt_root == block.header.receipt_root, EthereumException::InvalidBlock)?;
+            ensure(block_logs_bloom == block.header.bloom, EthereumException::InvalidBlock)?;
+            Ok(receipts)
+        },
+    );
+    let receipts = match checked {
+        Ok(receipts) => {
+            commit_transaction(&mut chain.state);
+            receipts
+        }
+        Err(error) => {
+            rollback_transaction(&mut chain.state);
+            return Err(error);
+        }
+    };
+
+    ancestors.push(&block, block.hash());
     chain.blocks.push(block);
     if chain.blocks.len() > 255 {
-        // remove first 255 blocks
-        chain.blocks.drain(0..255);
+        // keep the most recent 255 blocks
+        chain.blocks.drain(..chain.blocks.len() - 255);
     }
 
-    Ok(())
+    Ok(receipts)
 }
 
 ///
@@ -173,16 +241,15 @@ pub fn state_transition(chain: &mut BlockChain, block: Block) -> Result<(), Ethe
 ///         Parent Header of the header to check for correctness
 ///
 pub fn validate_header(header: &Header, parent_header: Header) -> Result<(), EthereumException> {
-    assert!(header.timestamp > parent_header.timestamp, "InvalidBlock");
+    ensure(header.timestamp > parent_header.timestamp, EthereumException::InvalidBlock)?;
     let parent_header_number = parent_header.number.clone();
-    assert!(header.number == parent_header_number.add(1u64), "InvalidBlock");
-    assert!(check_gas_limit(&header.gas_limit, &parent_header.gas_limit)?, "InvalidBlock");
-    assert!(header.extra_data.len() <= 32, "InvalidBlock");
+    ensure(header.number == parent_header_number.add(1u64), EthereumException::InvalidBlock)?;
+    ensure(check_gas_limit(&header.gas_limit, &parent_header.gas_limit)?, EthereumException::InvalidBlock)?;
+    ensure(header.extra_data.len() <= MAX_EXTRA_DATA_SIZE, EthereumException::InvalidBlock)?;
     let block_difficulty = calculate_block_difficulty(&header.number, &header.timestamp, &parent_header.timestamp, &parent_header.difficulty)?;
-    assert!(header.difficulty == block_difficulty, "InvalidBlock");
-    let block_parent_hash = keccak256(Box::leak(rlp::encode(&parent_header)));
-    assert!(header.parent_hash == block_parent_hash, "InvalidBlock");
-    // validate_proof_of_work(header)?;
+    ensure(header.difficulty == block_difficulty, EthereumException::InvalidBlock)?;
+    ensure(header.parent_hash == parent_header.hash(), EthereumException::InvalidBlock)?;
+    // The proof of work is checked apart, by `validate_proof_of_work`.
 
     Ok(())
 }
@@ -209,85 +276,185 @@ pub fn validate_header(header: &Header, parent_header: Header) -> Result<(), Eth
 ///     hash : `Hash32`
 ///         The PoW valid rlp hash of the passed in header.
 ///
-pub fn generate_header_hash_for_pow(_header: &Header) -> Result<Hash32, EthereumException> {
-    // header_data_without_pow_artefacts = [header.parent_hash, header.ommers_hash, header.coinbase, header.state_root, header.transactions_root, header.receipt_root, header.bloom, header.difficulty, header.number, header.gas_limit, header.gas_used, header.timestamp, header.extra_data];
-    // return Ok(rlp.rlp_hash(header_data_without_pow_artefacts)?);
-    todo!()
+pub fn generate_header_hash_for_pow(header: &Header) -> Result<Hash32, EthereumException> {
+    // Tuples only implement `RLP` up to 12 fields.
+    let header_data_without_pow_artefacts: [&dyn RLP; 13] = [
+        &header.parent_hash,
+        &header.ommers_hash,
+        &header.coinbase,
+        &header.state_root,
+        &header.transactions_root,
+        &header.receipt_root,
+        &header.bloom,
+        &header.difficulty,
+        &header.number,
+        &header.gas_limit,
+        &header.gas_used,
+        &header.timestamp,
+        &header.extra_data,
+    ];
+    Ok(keccak256(&rlp::encode_iter(header_data_without_pow_artefacts)))
 }
 
-// ///
-// ///     Validates the Proof of Work constraints.
-// ///
-// ///     In order to verify that a miner's proof-of-work is valid for a block, a
-// ///     ``mix-digest`` and ``result`` are calculated using the ``hashimoto_light``
-// ///     hash function. The mix digest is a hash of the header and the nonce that
-// ///     is passed through and it confirms whether or not proof-of-work was done
-// ///     on the correct block. The result is the actual hash value of the block.
-// ///
-// ///     Parameters
-// ///     ----------
-// ///     header :
-// ///         Header of interest.
-// ///
-// pub fn validate_proof_of_work(header: &Header) -> Result<(), EthereumException> {
-//     let header_hash = generate_header_hash_for_pow(header)?;
-//     let cache = generate_cache(header.number)?;
-//     (mix_digest, result) = hashimoto_light(header_hash, header.nonce, cache, dataset_size(header.number)?)?;
-//     ensure(mix_digest == header.mix_digest, InvalidBlock)?;
-//     ensure(Uint.from_be_bytes(result)? <= (U256_CEIL_VALUE).floordiv(header.difficulty), InvalidBlock)?;
-//     todo!()
-// }
+///
+///     Validates the Proof of Work constraints.
+///
+///     In order to verify that a miner's proof-of-work is valid for a block, a
+///     ``mix-digest`` and ``result`` are calculated using the ``hashimoto_light``
+///     hash function. The mix digest is a hash of the header and the nonce that
+///     is passed through and it confirms whether or not proof-of-work was done
+///     on the correct block. The result is the actual hash value of the block.
+///
+///     Generating the cache of an epoch takes far longer than checking a
+///     header, so unlike the python spec this takes it from the caller, who
+///     can keep it for the 30000 blocks of the epoch. For the same reason
+///     `validate_header` doesn't call this.
+///
+///     Parameters
+///     ----------
+///     header :
+///         Header of interest.
+///     cache :
+///         The ethash cache of the header's epoch, from `generate_cache`.
+///
+pub fn validate_proof_of_work(header: &Header, cache: &Vec<Vec<u32>>) -> Result<(), EthereumException> {
+    let header_hash = generate_header_hash_for_pow(header)?;
+    let number = u64::try_from(&header.number).map_err(|_| EthereumException::InvalidBlock)?;
+    let (mix_digest, result) = hashimoto_light(&header_hash, &header.nonce, cache, dataset_size(number));
+    ensure(mix_digest == header.mix_digest, EthereumException::InvalidBlock)?;
+    ensure(header.difficulty != Uint::from(0u8), EthereumException::InvalidBlock)?;
+    ensure(
+        U256::from_bytes_be(&result) <= U256_CEIL_VALUE() / &header.difficulty,
+        EthereumException::InvalidBlock,
+    )
+}
 
-// ///
-// ///     Check if the transaction is includable in the block.
-// ///
-// ///     Parameters
-// ///     ----------
-// ///     tx :
-// ///         The transaction.
-// ///     gas_available :
-// ///         The gas remaining in the block.
-// ///
-// ///     Returns
-// ///     -------
-// ///     sender_address :
-// ///         The sender of the transaction.
-// ///
-// ///     Raises
-// ///     ------
-// ///     InvalidBlock :
-// ///         If the transaction is not includable.
-// ///
-// pub fn check_transaction(tx: Transaction, gas_available: Uint) -> Result<Address, Error> {
-//     ensure(tx.gas <= gas_available, InvalidBlock)?;
-//     sender_address = recover_sender(tx)?;
-//     return Ok(sender_address);
-// }
+///
+///     Check each transaction of a block on its own, before any of them is
+///     executed.
+///
+///     Recovering the sender and checking the intrinsic cost and nonce of a
+///     transaction don't depend on the state, so with the `parallel` feature
+///     the transactions are checked on all cores.
+///
+///     Parameters
+///     ----------
+///     transactions :
+///         Transactions included in the block.
+///
+///     Returns
+///     -------
+///     senders : `Vec<ethereum.fork_types.Address>`
+///         The sender of each transaction.
+///
+///     Raises
+///     ------
+///     InvalidBlock :
+///         If a transaction is invalid on its own.
+///
+pub fn prevalidate_transactions(transactions: &[Transaction]) -> Result<Vec<Address>, EthereumException> {
+    let prevalidate = |(index, tx): (usize, &Transaction)| {
+        ensure(validate_transaction(tx), EthereumException::InvalidBlock)
+            .and_then(|()| recover_sender(tx))
+            .in_transaction(index)
+    };
 
-// ///
-// ///     Make the receipt for a transaction that was executed.
-// ///
-// ///     Parameters
-// ///     ----------
-// ///     tx :
-// ///         The executed transaction.
-// ///     post_state :
-// ///         The state root immediately after this transaction.
-// ///     cumulative_gas_used :
-// ///         The total gas used so far in the block after the transaction was
-// ///         executed.
-// ///     logs :
-// ///         The logs produced by the transaction.
-// ///
-// ///     Returns
-// ///     -------
-// ///     receipt :
-// ///         The receipt for the transaction.
-// ///
-// pub fn make_receipt(tx: Transaction, post_state: Bytes32, cumulative_gas_used: Uint, logs: Tuple[Log][...]) -> Result<Receipt, Error> {
-//     receipt = Receipt(post_state = post_state, cumulative_gas_used = cumulative_gas_used, bloom = logs_bloom(logs)?, logs = logs)?;
-//     return Ok(receipt);
-// }
+    #[cfg(feature = "parallel")]
+    {
+        use rayon::prelude::*;
+        // Collected in order first, so that the error is that of the first
+        // invalid transaction whichever thread found it.
+        let checked: Vec<_> = transactions.par_iter().enumerate().map(prevalidate).collect();
+        checked.into_iter().collect()
+    }
+    #[cfg(not(feature = "parallel"))]
+    {
+        transactions.iter().enumerate().map(prevalidate).collect()
+    }
+}
+
+///
+///     Make the receipt for a transaction that was executed.
+///
+///     Parameters
+///     ----------
+///     post_state :
+///         The state root immediately after this transaction.
+///     cumulative_gas_used :
+///         The total gas used so far in the block after the transaction was
+///         executed.
+///     logs :
+///         The logs produced by the transaction.
+///
+///     Returns
+///     -------
+///     receipt :
+///         The receipt for the transaction.
+///
+pub fn make_receipt(post_state: Root, cumulative_gas_used: Uint, logs: &[Log]) -> Receipt {
+    Receipt {
+        post_state,
+        cumulative_gas_used,
+        bloom: logs_bloom(logs),
+        logs: logs.to_vec(),
+    }
+}
+
+///
+///     Compute the root of the trie of a block's transactions, keyed by the
+///     RLP encoding of their index.
+///
+///     Frontier only has legacy transactions, stored as their RLP encoding.
+///     From Berlin a typed transaction is stored as its envelope, the type
+///     byte followed by the encoded payload, which each fork's version of
+///     this function takes care of.
+///
+///     Parameters
+///     ----------
+///     transactions :
+///         The transactions of the block, in order.
+///
+///     Returns
+///     -------
+///     root : `ethereum.fork_types.Root`
+///         The `transactions_root` of the block's header.
+///
+pub fn compute_transactions_root(transactions: &[Transaction]) -> Root {
+    let mut trie = Trie::<Bytes, Option<Transaction>>::new(false, None);
+    for (i, tx) in transactions.iter().enumerate() {
+        trie_set(&mut trie, rlp::encode(&Uint::from(i)), Some(tx.clone()));
+    }
+    root(&trie, dummy_root)
+}
+
+///
+///     Compute the root of the trie of a block's receipts, keyed by the RLP
+///     encoding of the index of their transaction.
+///
+///     As with `compute_transactions_root`, the receipts of typed
+///     transactions are stored as envelopes from Berlin on.
+///
+///     Parameters
+///     ----------
+///     receipts :
+///         The receipts of the block's transactions, in order.
+///
+///     Returns
+///     -------
+///     root : `ethereum.fork_types.Root`
+///         The `receipt_root` of the block's header.
+///
+pub fn compute_receipts_root(receipts: &[Receipt]) -> Root {
+    let mut trie = Trie::<Bytes, Option<Receipt>>::new(false, None);
+    for (i, receipt) in receipts.iter().enumerate() {
+        trie_set(&mut trie, rlp::encode(&Uint::from(i)), Some(receipt.clone()));
+    }
+    root(&trie, dummy_root)
+}
+
+/// The gas used, transactions root, receipt root, logs bloom, fee summary,
+/// logs of each transaction and receipts of a block, see `apply_body`.
+pub type ApplyBodyOutput = (Uint, Root, Root, Bloom, BlockFeeSummary, Vec<Vec<Log>>, Vec<Receipt>);
 
 ///
 ///     Executes a block.
@@ -303,19 +470,9 @@ pub fn generate_header_hash_for_pow(_header: &Header) -> Result<Hash32, Ethereum
 ///     ----------
 ///     state :
 ///         Current account state.
-///     block_hashes :
-///         List of hashes of the previous 256 blocks in the order of
-///         increasing block number.
-///     coinbase :
-///         Address of account which receives block reward and transaction fees.
-///     block_number :
-///         Position of the block within the chain.
-///     block_gas_limit :
-///         Initial amount of gas available for execution in this block.
-///     block_time :
-///         Time the block was produced, measured in seconds since the epoch.
-///     block_difficulty :
-///         Difficulty of the block.
+///     block_env :
+///         The block the transactions run in: the hashes of the previous 256
+///         blocks, the coinbase, number, gas limit, time and difficulty.
 ///     transactions :
 ///         Transactions included in the block.
 ///     ommers :
@@ -333,30 +490,171 @@ pub fn generate_header_hash_for_pow(_header: &Header) -> Result<Hash32, Ethereum
 ///     block_logs_bloom : `Bloom`
 ///         Logs bloom of all the logs included in all the transactions of the
 ///         block.
-///     state : `ethereum.fork_types.State`
-///         State after all transactions have been executed.
-///
-#[allow(unused_variables)]
-pub fn apply_body(state: &State, block_hashes: Vec<Hash32>, coinbase: &Address, block_number: &Uint, block_gas_limit: &Uint, block_time: &U256, block_difficulty: &Uint, transactions: &Vec<Transaction>, ommers: &Vec<Header>) -> Result<(Uint, Root, Root, Bloom, State), EthereumException> {
-    // gas_available = block_gas_limit;
-    // // TypedAssignment unsupported
-    // // TypedAssignment unsupported
-    // // TypedAssignment unsupported
-    // for (i, tx) in enumerate(transactions)? {
-    //     trie_set(transactions_trie, rlp.encode(Uint(i)?)?, tx)?;
-    //     sender_address = check_transaction(tx, gas_available)?;
-    //     env = vm.Environment(caller = sender_address, origin = sender_address, block_hashes = block_hashes, coinbase = coinbase, number = block_number, gas_limit = block_gas_limit, gas_price = tx.gas_price, time = block_time, difficulty = block_difficulty, state = state)?;
-    //     (gas_used, logs) = process_transaction(env, tx)?;
-    //     gas_available -= gas_used;
-    //     receipt = make_receipt(tx, state_root(state)?, block_gas_limit - gas_available, logs)?;
-    //     trie_set(receipts_trie, rlp.encode(Uint(i)?)?, receipt)?;
-    //     block_logs += logs;
-    // }
-    // pay_rewards(state, block_number, coinbase, ommers)?;
-    // block_gas_used = block_gas_limit - gas_available;
-    // block_logs_bloom = logs_bloom(block_logs)?;
-    // return Ok((block_gas_used, root(transactions_trie)?, root(receipts_trie)?, block_logs_bloom, state));
-    todo!()
+///     fee_summary : `BlockFeeSummary`
+///         The fees paid by the transactions and the rewards paid for the
+///         block. Frontier burns nothing.
+///     transaction_logs : `Vec<Vec<Log>>`
+///         The logs of each transaction, in order. See `LogFilter` to select
+///         some of them.
+///     receipts : `Vec<Receipt>`
+///         The receipt of each transaction, in order.
+///
+pub fn apply_body(state: &mut State, block_env: &BlockEnvironment, transactions: &[Transaction], ommers: &[Header]) -> Result<ApplyBodyOutput, EthereumException> {
+    apply_body_traced(state, block_env, transactions, ommers, &mut None)
+}
+
+///
+///     Executes a block, like `apply_body`, reporting the execution of
+///     every transaction to a tracer.
+///
+///     Parameters
+///     ----------
+///     tracer :
+///         Receives the trace events of every transaction, if set. It is
+///         left in place afterwards.
+///
+pub fn apply_body_traced(
+    state: &mut State,
+    block_env: &BlockEnvironment,
+    transactions: &[Transaction],
+    ommers: &[Header],
+    tracer: &mut Option<Box<dyn Tracer<Evm>>>,
+) -> Result<ApplyBodyOutput, EthereumException> {
+    let (block_gas_used, mut fee_summary, transaction_logs, receipts) =
+        apply_transactions(state, block_env, transactions, &ExecutionLimit::default(), tracer)?;
+
+    (fee_summary.miner_reward, fee_summary.ommer_rewards) =
+        pay_rewards(state, &block_env.number, block_env.coinbase, ommers);
+
+    let block_logs_bloom = logs_bloom(&transaction_logs.concat());
+    Ok((
+        block_gas_used,
+        compute_transactions_root(transactions),
+        compute_receipts_root(&receipts),
+        block_logs_bloom,
+        fee_summary,
+        transaction_logs,
+        receipts,
+    ))
+}
+
+/// Where `apply_body_partial` stops a block. A limit that is not set does
+/// not stop it.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ExecutionLimit {
+    /// The number of transactions to execute at most.
+    pub transactions: Option<usize>,
+    /// The gas the block uses after which no further transaction is
+    /// executed. The transaction that reaches it is.
+    pub gas: Option<Uint>,
+}
+
+/// A block executed up to an `ExecutionLimit`, see `apply_body_partial`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PartialBody {
+    /// The number of transactions executed, from the first.
+    pub executed: usize,
+    pub gas_used: Uint,
+    /// The state root after the last transaction executed.
+    pub state_root: Root,
+    /// The fees paid by the transactions executed. No rewards are paid.
+    pub fee_summary: BlockFeeSummary,
+    pub transaction_logs: Vec<Vec<Log>>,
+    pub receipts: Vec<Receipt>,
+}
+
+///
+///     Executes the transactions of a block up to a limit, for studying how
+///     blocks are built and how much gas they should hold.
+///
+///     The transactions run as in `apply_body`, in the same environment,
+///     until the limit is reached. The block is not finished: the rewards
+///     are not paid and the state is left as it is after the last
+///     transaction executed.
+///
+///     Parameters
+///     ----------
+///     state :
+///         Current account state.
+///     block_env :
+///         The block the transactions run in.
+///     transactions :
+///         Transactions included in the block.
+///     limit :
+///         Where to stop.
+///     tracer :
+///         Receives the trace events of every transaction, if set.
+///
+///     Returns
+///     -------
+///     partial_body : `PartialBody`
+///         What the transactions executed did.
+///
+///     Raises
+///     ------
+///     InvalidBlock :
+///         If a transaction to execute is invalid, or doesn't fit in the gas
+///         left in the block.
+///
+pub fn apply_body_partial(
+    state: &mut State,
+    block_env: &BlockEnvironment,
+    transactions: &[Transaction],
+    limit: &ExecutionLimit,
+    tracer: &mut Option<Box<dyn Tracer<Evm>>>,
+) -> Result<PartialBody, EthereumException> {
+    let (gas_used, fee_summary, transaction_logs, receipts) =
+        apply_transactions(state, block_env, transactions, limit, tracer)?;
+    Ok(PartialBody {
+        executed: receipts.len(),
+        gas_used,
+        state_root: state_root(state),
+        fee_summary,
+        transaction_logs,
+        receipts,
+    })
+}
+
+/// The gas used, fees, logs and receipts of the transactions executed.
+type TransactionsOutput = (Uint, BlockFeeSummary, Vec<Vec<Log>>, Vec<Receipt>);
+
+///
+///     Executes transactions of a block until a limit.
+///
+fn apply_transactions(
+    state: &mut State,
+    block_env: &BlockEnvironment,
+    transactions: &[Transaction],
+    limit: &ExecutionLimit,
+    tracer: &mut Option<Box<dyn Tracer<Evm>>>,
+) -> Result<TransactionsOutput, EthereumException> {
+    let mut gas_available = block_env.gas_limit.clone();
+    let mut receipts = vec![];
+    let mut transaction_logs = vec![];
+    let mut fee_summary = BlockFeeSummary::default();
+
+    let count = limit.transactions.map_or(transactions.len(), |count| count.min(transactions.len()));
+    let transactions = &transactions[..count];
+    let senders = prevalidate_transactions(transactions)?;
+    for (index, (tx, sender)) in transactions.iter().zip(senders).enumerate() {
+        if limit.gas.as_ref().is_some_and(|gas| &block_env.gas_limit - &gas_available >= *gas) {
+            break;
+        }
+        ensure(tx.gas <= gas_available, EthereumException::InvalidBlock).in_transaction(index)?;
+        let tx_env = TxEnvironment {
+            origin: sender,
+            gas_price: tx.gas_price.clone(),
+        };
+        let (gas_used, logs) =
+            process_transaction_traced(block_env, &tx_env, state, tx, tracer).in_transaction(index)?;
+        fee_summary.add_transaction(&gas_used, &tx.gas_price, &U256::default());
+        gas_available -= gas_used;
+
+        receipts.push(make_receipt(state_root(state), &block_env.gas_limit - &gas_available, &logs));
+        transaction_logs.push(logs);
+    }
+
+    Ok((&block_env.gas_limit - gas_available, fee_summary, transaction_logs, receipts))
 }
 
 ///
@@ -380,240 +678,400 @@ pub fn apply_body(state: &State, block_hashes: Vec<Hash32>, coinbase: &Address,
 ///     chain :
 ///         History and current state.
 ///
-#[allow(unused_variables)]
 pub fn validate_ommers(ommers: &Vec<Header>, block_header: Header, chain: &BlockChain) -> Result<(), EthereumException> {
-    // block_hash = rlp.rlp_hash(block_header)?;
-    // ensure(rlp.rlp_hash(ommers)? == block_header.ommers_hash, InvalidBlock)?;
-    // if len(ommers)? == 0 {
-    //     return;
-    // }
-    // for ommer in ommers {
-    //     ensure(1 <= ommer.number < block_header.number, InvalidBlock)?;
-    //     ommer_parent_header = chain.blocks[-(block_header.number - ommer.number) - 1].header;
-    //     validate_header(ommer, ommer_parent_header)?;
-    // }
-    // ensure(len(ommers)? <= 2, InvalidBlock)?;
-    // ommers_hashes = /* ListComp unsupported */;
-    // ensure(len(ommers_hashes)? == len(set(ommers_hashes)?)?, InvalidBlock)?;
-    // recent_canonical_blocks = chain.blocks[-(MAX_OMMER_DEPTH + 1)..];
-    // recent_canonical_block_hashes = /* SetComp unsupported */;
-    // // TypedAssignment unsupported
-    // for block in recent_canonical_blocks {
-    //     recent_ommers_hashes = recent_ommers_hashes.union(/* SetComp unsupported */)?;
-    // }
-    // for (ommer_index, ommer) in enumerate(ommers)? {
-    //     ommer_hash = ommers_hashes[ommer_index];
-    //     ensure(ommer_hash != block_hash, InvalidBlock)?;
-    //     ensure(!(ommer_hash).contains(recent_canonical_block_hashes), InvalidBlock)?;
-    //     ensure(!(ommer_hash).contains(recent_ommers_hashes), InvalidBlock)?;
-    //     ommer_age = block_header.number - ommer.number;
-    //     ensure(1 <= ommer_age <= MAX_OMMER_DEPTH, InvalidBlock)?;
-    //     ensure((ommer.parent_hash).contains(recent_canonical_block_hashes), InvalidBlock)?;
-    //     ensure(ommer.parent_hash != block_header.parent_hash, InvalidBlock)?;
-    // }
-    todo!()
+    validate_ommers_with_ancestors(ommers, &block_header, &Ancestors::new(&chain.blocks))
 }
 
-// ///
-// ///     Pay rewards to the block miner as well as the ommers miners.
-// ///
-// ///     The miner of the canonical block is rewarded with the predetermined
-// ///     block reward, ``BLOCK_REWARD``, plus a variable award based off of the
-// ///     number of ommer blocks that were mined around the same time, and included
-// ///     in the canonical block's header. An ommer block is a block that wasn't
-// ///     added to the canonical blockchain because it wasn't validated as fast as
-// ///     the accepted block but was mined at the same time. Although not all blocks
-// ///     that are mined are added to the canonical chain, miners are still paid a
-// ///     reward for their efforts. This reward is called an ommer reward and is
-// ///     calculated based on the number associated with the ommer block that they
-// ///     mined.
-// ///
-// ///     Parameters
-// ///     ----------
-// ///     state :
-// ///         Current account state.
-// ///     block_number :
-// ///         Position of the block within the chain.
-// ///     coinbase :
-// ///         Address of account which receives block reward and transaction fees.
-// ///     ommers :
-// ///         List of ommers mentioned in the current block.
-// ///
-// pub fn pay_rewards(state: State, block_number: Uint, coinbase: Address, ommers: Tuple[Header][...]) -> Result<(), Error> {
-//     miner_reward = BLOCK_REWARD + len(ommers)? * (BLOCK_REWARD).floordiv(32);
-//     create_ether(state, coinbase, miner_reward)?;
-//     for ommer in ommers {
-//         ommer_age = U256(block_number - ommer.number)?;
-//         ommer_miner_reward = (8 - ommer_age * BLOCK_REWARD).floordiv(8);
-//         create_ether(state, ommer.coinbase, ommer_miner_reward)?;
-//     }
-// }
+///
+///     Validates the ommers mentioned in the block, like `validate_ommers`,
+///     looking up the recent blocks in `ancestors`.
+///
+///     Parameters
+///     ----------
+///     ommers :
+///         List of ommers mentioned in the current block.
+///     block_header:
+///         The header of current block.
+///     ancestors :
+///         The last blocks of the chain, up to the parent of the block.
+///
+pub fn validate_ommers_with_ancestors(ommers: &[Header], block_header: &Header, ancestors: &Ancestors) -> Result<(), EthereumException> {
+    let block_hash = block_header.hash();
+    ensure(rlp::rlp_hash(ommers) == block_header.ommers_hash, EthereumException::InvalidBlock)?;
+    if ommers.is_empty() {
+        // Nothing to validate
+        return Ok(());
+    }
 
-// ///
-// ///     Execute a transaction against the provided environment.
-// ///
-// ///     This function processes the actions needed to execute a transaction.
-// ///     It decrements the sender's account after calculating the gas fee and
-// ///     refunds them the proper amount after execution. Calling contracts,
-// ///     deploying code, and incrementing nonces are all examples of actions that
-// ///     happen within this function or from a call made within this function.
-// ///
-// ///     Accounts that are marked for deletion are processed and destroyed after
-// ///     execution.
-// ///
-// ///     Parameters
-// ///     ----------
-// ///     env :
-// ///         Environment for the Ethereum Virtual Machine.
-// ///     tx :
-// ///         Transaction to execute.
-// ///
-// ///     Returns
-// ///     -------
-// ///     gas_left : `ethereum.base_types.U256`
-// ///         Remaining gas after execution.
-// ///     logs : `Tuple[ethereum.fork_types.Log, ...]`
-// ///         Logs generated during execution.
-// ///
-// pub fn process_transaction(env: vm.Environment, tx: Transaction) -> Result<Tuple[U256][Tuple[Log][...]], Error> {
-//     ensure(validate_transaction(tx)?, InvalidBlock)?;
-//     sender = env.origin;
-//     sender_account = get_account(env.state, sender)?;
-//     gas_fee = tx.gas * tx.gas_price;
-//     ensure(sender_account.nonce == tx.nonce, InvalidBlock)?;
-//     ensure(sender_account.balance >= gas_fee + tx.value, InvalidBlock)?;
-//     ensure(sender_account.code == bytearray()?, InvalidBlock)?;
-//     gas = tx.gas - calculate_intrinsic_cost(tx)?;
-//     increment_nonce(env.state, sender)?;
-//     sender_balance_after_gas_fee = sender_account.balance - gas_fee;
-//     set_account_balance(env.state, sender, sender_balance_after_gas_fee)?;
-//     message = prepare_message(sender, tx.to, tx.value, tx.data, gas, env)?;
-//     output = process_message_call(message, env)?;
-//     gas_used = tx.gas - output.gas_left;
-//     gas_refund = min((gas_used).floordiv(2), output.refund_counter)?;
-//     gas_refund_amount = output.gas_left + gas_refund * tx.gas_price;
-//     transaction_fee = tx.gas - output.gas_left - gas_refund * tx.gas_price;
-//     total_gas_used = gas_used - gas_refund;
-//     sender_balance_after_refund = get_account(env.state, sender)?.balance + gas_refund_amount;
-//     set_account_balance(env.state, sender, sender_balance_after_refund)?;
-//     coinbase_balance_after_mining_fee = get_account(env.state, env.coinbase)?.balance + transaction_fee;
-//     set_account_balance(env.state, env.coinbase, coinbase_balance_after_mining_fee)?;
-//     for address in output.accounts_to_delete {
-//         destroy_account(env.state, address)?;
-//     }
-//     return Ok((total_gas_used, output.logs));
-// }
+    // Check that each ommer satisfies the constraints of a header
+    for ommer in ommers {
+        ensure(Uint::from(1u8) <= ommer.number && ommer.number < block_header.number, EthereumException::InvalidBlock)?;
+        // Only the most recent blocks are kept.
+        let depth = (&block_header.number - &ommer.number).saturating_to_usize();
+        let ommer_parent_header = ancestors.header(depth).ok_or(EthereumException::InvalidBlock)?;
+        validate_header(ommer, ommer_parent_header.clone())?;
+    }
 
-// ///
-// ///     Verifies a transaction.
-// ///
-// ///     The gas in a transaction gets used to pay for the intrinsic cost of
-// ///     operations, therefore if there is insufficient gas then it would not
-// ///     be possible to execute a transaction and it will be declared invalid.
-// ///
-// ///     Additionally, the nonce of a transaction must not equal or exceed the
-// ///     limit defined in `EIP-2681 <https://eips.ethereum.org/EIPS/eip-2681>`_.
-// ///     In practice, defining the limit as ``2**64-1`` has no impact because
-// ///     sending ``2**64-1`` transactions is improbable. It's not strictly
-// ///     impossible though, ``2**64-1`` transactions is the entire capacity of the
-// ///     Ethereum blockchain at 2022 gas limits for a little over 22 years.
-// ///
-// ///     Parameters
-// ///     ----------
-// ///     tx :
-// ///         Transaction to validate.
-// ///
-// ///     Returns
-// ///     -------
-// ///     verified : `bool`
-// ///         True if the transaction can be executed, or False otherwise.
-// ///
-// pub fn validate_transaction(tx: Transaction) -> Result<bool, Error> {
-//     return Ok(calculate_intrinsic_cost(tx)? <= tx.gas && tx.nonce < (2).pow(64) - 1);
-// }
+    // Check that there can be only at most 2 ommers for a block.
+    ensure(ommers.len() <= 2, EthereumException::InvalidBlock)?;
 
-// ///
-// ///     Calculates the gas that is charged before execution is started.
-// ///
-// ///     The intrinsic cost of the transaction is charged before execution has
-// ///     begun. Functions/operations in the EVM cost money to execute so this
-// ///     intrinsic cost is for the operations that need to be paid for as part of
-// ///     the transaction. Data transfer, for example, is part of this intrinsic
-// ///     cost. It costs ether to send data over the wire and that ether is
-// ///     accounted for in the intrinsic cost calculated in this function. This
-// ///     intrinsic cost must be calculated and paid for before execution in order
-// ///     for all operations to be implemented.
-// ///
-// ///     Parameters
-// ///     ----------
-// ///     tx :
-// ///         Transaction to compute the intrinsic cost of.
-// ///
-// ///     Returns
-// ///     -------
-// ///     verified : `ethereum.base_types.Uint`
-// ///         The intrinsic cost of the transaction.
-// ///
-// pub fn calculate_intrinsic_cost(tx: Transaction) -> Result<Uint, Error> {
-//     data_cost = 0;
-//     for byte in tx.data {
-//         if byte == 0 {
-//             data_cost += TX_DATA_COST_PER_ZERO;
-//         } else {
-//             data_cost += TX_DATA_COST_PER_NON_ZERO;
-//         }
-//     }
-//     return Ok(Uint(TX_BASE_COST + data_cost)?);
-// }
+    let ommers_hashes: Vec<Hash32> = ommers.iter().map(Header::hash).collect();
+    // Check that there are no duplicates in the ommers of current block
+    ensure(ommers_hashes.len() < 2 || ommers_hashes[0] != ommers_hashes[1], EthereumException::InvalidBlock)?;
 
-// ///
-// ///     Extracts the sender address from a transaction.
-// ///
-// ///     The v, r, and s values are the three parts that make up the signature
-// ///     of a transaction. In order to recover the sender of a transaction the two
-// ///     components needed are the signature (``v``, ``r``, and ``s``) and the
-// ///     signing hash of the transaction. The sender's public key can be obtained
-// ///     with these two values and therefore the sender address can be retrieved.
-// ///
-// ///     Parameters
-// ///     ----------
-// ///     tx :
-// ///         Transaction of interest.
-// ///
-// ///     Returns
-// ///     -------
-// ///     sender : `ethereum.fork_types.Address`
-// ///         The address of the account that signed the transaction.
-// ///
-// pub fn recover_sender(tx: Transaction) -> Result<Address, Error> {
-//     (v, r, s) = tx.v = tx.r = tx.s;
-//     ensure(v == 27 || v == 28, InvalidBlock)?;
-//     ensure(0 < r && r < SECP256K1N, InvalidBlock)?;
-//     ensure(0 < s && s < SECP256K1N, InvalidBlock)?;
-//     public_key = secp256k1_recover(r, s, v - 27, signing_hash(tx)?)?;
-//     return Ok(Address(keccak256(public_key)?[12..32])?);
-// }
+    let recent_canonical_depths = 0..=MAX_OMMER_DEPTH as usize;
+    let recent_canonical_block_hashes: HashSet<Hash32> = recent_canonical_depths
+        .clone()
+        .filter_map(|depth| ancestors.hash(depth).copied())
+        .collect();
+    let recent_ommers_hashes: HashSet<Hash32> = recent_canonical_depths
+        .filter_map(|depth| ancestors.ommers(depth))
+        .flatten()
+        .copied()
+        .collect();
 
-// ///
-// ///     Compute the hash of a transaction used in the signature.
-// ///
-// ///     The values that are used to compute the signing hash set the rules for a
-// ///     transaction. For example, signing over the gas sets a limit for the
-// ///     amount of money that is allowed to be pulled out of the sender's account.
-// ///
-// ///     Parameters
-// ///     ----------
-// ///     tx :
-// ///         Transaction of interest.
-// ///
-// ///     Returns
-// ///     -------
-// ///     hash : `ethereum.crypto.hash.Hash32`
-// ///         Hash of the transaction.
-// ///
-// pub fn signing_hash(tx: Transaction) -> Result<Hash32, Error> {
-//     return Ok(keccak256(rlp.encode((tx.nonce, tx.gas_price, tx.gas, tx.to, tx.value, tx.data))?)?);
-// }
+    for (ommer, ommer_hash) in ommers.iter().zip(&ommers_hashes) {
+        // The current block shouldn't count as an ommer
+        ensure(*ommer_hash != block_hash, EthereumException::InvalidBlock)?;
+        // An ommer must not be one of the recent canonical blocks
+        ensure(!recent_canonical_block_hashes.contains(ommer_hash), EthereumException::InvalidBlock)?;
+        // An ommer must not be included in the recent blocks already
+        ensure(!recent_ommers_hashes.contains(ommer_hash), EthereumException::InvalidBlock)?;
+
+        // Ommer age with respect to the current block. For example, an age of
+        // 1 indicates that the ommer is a sibling of previous block.
+        let ommer_age = &block_header.number - &ommer.number;
+        ensure(
+            Uint::from(1u8) <= ommer_age && ommer_age <= Uint::from(MAX_OMMER_DEPTH),
+            EthereumException::InvalidBlock,
+        )?;
+        // The ommer must descend from a recent canonical block
+        ensure(recent_canonical_block_hashes.contains(&ommer.parent_hash), EthereumException::InvalidBlock)?;
+        // An ommer can't be a sibling of the current block
+        ensure(ommer.parent_hash != block_header.parent_hash, EthereumException::InvalidBlock)?;
+    }
+    Ok(())
+}
+
+///
+///     Pay rewards to the block miner as well as the ommers miners.
+///
+///     The miner of the canonical block is rewarded with the predetermined
+///     block reward, ``BLOCK_REWARD``, plus a variable award based off of the
+///     number of ommer blocks that were mined around the same time, and included
+///     in the canonical block's header. An ommer block is a block that wasn't
+///     added to the canonical blockchain because it wasn't validated as fast as
+///     the accepted block but was mined at the same time. Although not all blocks
+///     that are mined are added to the canonical chain, miners are still paid a
+///     reward for their efforts. This reward is called an ommer reward and is
+///     calculated based on the number associated with the ommer block that they
+///     mined.
+///
+///     Parameters
+///     ----------
+///     state :
+///         Current account state.
+///     block_number :
+///         Position of the block within the chain.
+///     coinbase :
+///         Address of account which receives block reward and transaction fees.
+///     ommers :
+///         List of ommers mentioned in the current block.
+///
+///     Returns
+///     -------
+///     miner_reward : `ethereum.base_types.U256`
+///         Ether created for the miner of the block.
+///     ommer_rewards : `ethereum.base_types.U256`
+///         Ether created for the miners of the ommers, together.
+///
+pub fn pay_rewards(state: &mut State, block_number: &Uint, coinbase: Address, ommers: &[Header]) -> (U256, U256) {
+    let schedule = &mining_reward::FRONTIER;
+    let miner_reward = mining_reward::miner_reward(schedule, ommers.len());
+    create_ether(state, coinbase, miner_reward.clone());
+    let mut ommer_rewards = U256::default();
+    for ommer in ommers {
+        let ommer_miner_reward = mining_reward::ommer_reward(schedule, block_number, &ommer.number);
+        ommer_rewards += &ommer_miner_reward;
+        create_ether(state, ommer.coinbase, ommer_miner_reward);
+    }
+    (miner_reward, ommer_rewards)
+}
+
+///
+///     Execute a transaction against the provided environment.
+///
+///     This function processes the actions needed to execute a transaction.
+///     It decrements the sender's account after calculating the gas fee and
+///     refunds them the proper amount after execution. Calling contracts,
+///     deploying code, and incrementing nonces are all examples of actions that
+///     happen within this function or from a call made within this function.
+///
+///     Accounts that are marked for deletion are processed and destroyed after
+///     execution.
+///
+///     Parameters
+///     ----------
+///     block_env :
+///         The block the transaction is included in.
+///     tx_env :
+///         The sender of the transaction and its gas price.
+///     state :
+///         Current account state.
+///     tx :
+///         Transaction to execute.
+///
+///     Returns
+///     -------
+///     gas_used : `ethereum.base_types.Uint`
+///         Gas used by the transaction, after refunds.
+///     logs : `Vec<ethereum.fork_types.Log>`
+///         Logs generated during execution.
+///
+pub fn process_transaction(
+    block_env: &BlockEnvironment,
+    tx_env: &TxEnvironment,
+    state: &mut State,
+    tx: &Transaction,
+) -> Result<(Uint, Vec<Log>), EthereumException> {
+    process_transaction_traced(block_env, tx_env, state, tx, &mut None)
+}
+
+///
+///     Execute a transaction, like `process_transaction`, reporting its
+///     execution to a tracer.
+///
+///     Parameters
+///     ----------
+///     tracer :
+///         Receives the trace events of the transaction, if set. It is left
+///         in place afterwards.
+///
+pub fn process_transaction_traced(
+    block_env: &BlockEnvironment,
+    tx_env: &TxEnvironment,
+    state: &mut State,
+    tx: &Transaction,
+    tracer: &mut Option<Box<dyn Tracer<Evm>>>,
+) -> Result<(Uint, Vec<Log>), EthereumException> {
+    let sender = tx_env.origin;
+    let sender_account = get_account(state, &sender);
+    check_transaction(block_env, &sender_account, tx)
+        .map_err(|_| EthereumException::InvalidBlock)?;
+
+    let gas_fee = &tx.gas * &tx.gas_price;
+
+    let gas = &tx.gas - calculate_intrinsic_cost(tx);
+    increment_nonce(state, sender);
+    let sender_balance_after_gas_fee = sender_account.balance - gas_fee;
+    set_account_balance(state, sender, sender_balance_after_gas_fee);
+
+    let mut env = Environment {
+        block: block_env.clone(),
+        tx: tx_env.clone(),
+        state: core::mem::take(state),
+        tracer: tracer.take(),
+    };
+    let message = prepare_message(sender, tx.to, tx.value.clone(), tx.data.clone(), gas, &env, None);
+    let output = process_message_call(message, &mut env);
+    *state = env.state;
+    *tracer = env.tracer;
+
+    let gas_used = &tx.gas - &output.gas_left;
+    let gas_refund = (&gas_used / MAX_REFUND_QUOTIENT).min(output.refund_counter);
+    let gas_refund_amount = (&output.gas_left + &gas_refund) * &tx.gas_price;
+    let transaction_fee = (&tx.gas - &output.gas_left - &gas_refund) * &tx.gas_price;
+    let total_gas_used = gas_used - gas_refund;
+
+    // refund gas
+    let sender_balance_after_refund = get_account(state, &sender).balance + gas_refund_amount;
+    set_account_balance(state, sender, sender_balance_after_refund);
+
+    // transfer miner fees, before the self-destructed accounts are removed:
+    // a coinbase that destroyed itself loses them too. Frontier keeps empty
+    // accounts, so a coinbase paid nothing is still created.
+    let coinbase_balance_after_mining_fee =
+        get_account(state, &block_env.coinbase).balance + transaction_fee;
+    set_account_balance(state, block_env.coinbase, coinbase_balance_after_mining_fee);
+
+    for address in &output.accounts_to_delete {
+        destroy_account(state, address);
+    }
+
+    Ok((total_gas_used, output.logs))
+}
+
+///
+///     Check that a transaction can be included in a block, given the
+///     account of its sender, without executing it.
+///
+///     This makes the checks `process_transaction` makes before it changes
+///     any state, and also that the transaction fits in the block, so it
+///     can screen transactions the way a transaction pool would.
+///
+///     Parameters
+///     ----------
+///     block_env :
+///         The block the transaction would be included in.
+///     sender_account :
+///         Account of the sender of the transaction.
+///     tx :
+///         Transaction to check.
+///
+///     Returns
+///     -------
+///     result : `Result<(), InvalidTransactionReason>`
+///         The first rule the transaction breaks, if any.
+///
+pub fn check_transaction(
+    block_env: &BlockEnvironment,
+    sender_account: &Account,
+    tx: &Transaction,
+) -> Result<(), InvalidTransactionReason> {
+    let intrinsic_cost = calculate_intrinsic_cost(tx);
+    ensure(
+        intrinsic_cost <= tx.gas,
+        InvalidTransactionReason::IntrinsicGasTooLow {
+            intrinsic_cost,
+            gas: tx.gas.clone(),
+        },
+    )?;
+    ensure(tx.nonce < U256::from(u64::MAX), InvalidTransactionReason::NonceTooLarge)?;
+    ensure(
+        tx.gas <= block_env.gas_limit,
+        InvalidTransactionReason::GasAboveBlockLimit {
+            gas: tx.gas.clone(),
+            gas_limit: block_env.gas_limit.clone(),
+        },
+    )?;
+    ensure(
+        sender_account.nonce == tx.nonce,
+        InvalidTransactionReason::NonceMismatch {
+            expected: sender_account.nonce.clone(),
+            nonce: tx.nonce.clone(),
+        },
+    )?;
+    let required = &tx.gas * &tx.gas_price + &tx.value;
+    ensure(
+        sender_account.balance >= required,
+        InvalidTransactionReason::InsufficientBalance {
+            required,
+            balance: sender_account.balance.clone(),
+        },
+    )?;
+    ensure(sender_account.code.is_empty(), InvalidTransactionReason::SenderNotEoa)
+}
+
+///
+///     Verifies a transaction.
+///
+///     The gas in a transaction gets used to pay for the intrinsic cost of
+///     operations, therefore if there is insufficient gas then it would not
+///     be possible to execute a transaction and it will be declared invalid.
+///
+///     Additionally, the nonce of a transaction must not equal or exceed the
+///     limit defined in `EIP-2681 <https://eips.ethereum.org/EIPS/eip-2681>`_.
+///     In practice, defining the limit as ``2**64-1`` has no impact because
+///     sending ``2**64-1`` transactions is improbable. It's not strictly
+///     impossible though, ``2**64-1`` transactions is the entire capacity of the
+///     Ethereum blockchain at 2022 gas limits for a little over 22 years.
+///
+///     A contract creation's initcode is limited from Shanghai on (EIP-3860),
+///     which `check_initcode_size` leaves to the forks that have it.
+///
+///     Parameters
+///     ----------
+///     tx :
+///         Transaction to validate.
+///
+///     Returns
+///     -------
+///     verified : `bool`
+///         True if the transaction can be executed, or False otherwise.
+///
+pub fn validate_transaction(tx: &Transaction) -> bool {
+    calculate_intrinsic_cost(tx) <= tx.gas
+        && tx.nonce < U256::from(u64::MAX)
+        && (tx.to.is_some() || check_initcode_size(&create_policy::FRONTIER, tx.data.len()))
+}
+
+///
+///     Calculates the gas that is charged before execution is started.
+///
+///     The intrinsic cost of the transaction is charged before execution has
+///     begun. Functions/operations in the EVM cost money to execute so this
+///     intrinsic cost is for the operations that need to be paid for as part of
+///     the transaction. Data transfer, for example, is part of this intrinsic
+///     cost. It costs ether to send data over the wire and that ether is
+///     accounted for in the intrinsic cost calculated in this function. This
+///     intrinsic cost must be calculated and paid for before execution in order
+///     for all operations to be implemented.
+///
+///     Parameters
+///     ----------
+///     tx :
+///         Transaction to compute the intrinsic cost of.
+///
+///     Returns
+///     -------
+///     verified : `ethereum.base_types.Uint`
+///         The intrinsic cost of the transaction.
+///
+pub fn calculate_intrinsic_cost(tx: &Transaction) -> Uint {
+    intrinsic_gas::intrinsic_cost(&intrinsic_gas::FRONTIER, &IntrinsicGasInputs::from(tx))
+}
+
+///
+///     Extracts the sender address from a transaction.
+///
+///     The v, r, and s values are the three parts that make up the signature
+///     of a transaction. In order to recover the sender of a transaction the two
+///     components needed are the signature (``v``, ``r``, and ``s``) and the
+///     signing hash of the transaction. The sender's public key can be obtained
+///     with these two values and therefore the sender address can be retrieved.
+///
+///     Parameters
+///     ----------
+///     tx :
+///         Transaction of interest.
+///
+///     Returns
+///     -------
+///     sender : `ethereum.fork_types.Address`
+///         The address of the account that signed the transaction.
+///
+pub fn recover_sender(tx: &Transaction) -> Result<Address, EthereumException> {
+    let (v, r, s) = (&tx.v, &tx.r, &tx.s);
+    let zero = U256::from(0u8);
+    ensure(*v == U256::from(27u8) || *v == U256::from(28u8), EthereumException::InvalidBlock)?;
+    ensure(zero < *r && *r < SECP256K1N(), EthereumException::InvalidBlock)?;
+    ensure(zero < *s && *s < SECP256K1N(), EthereumException::InvalidBlock)?;
+
+    let public_key = secp256k1_recover(r, s, &(v - 27u8), &signing_hash(tx))
+        .map_err(|_| EthereumException::InvalidBlock)?;
+    Ok(keccak256(&public_key)[12..32].try_into().unwrap())
+}
+
+///
+///     Compute the hash of a transaction used in the signature.
+///
+///     The values that are used to compute the signing hash set the rules for a
+///     transaction. For example, signing over the gas sets a limit for the
+///     amount of money that is allowed to be pulled out of the sender's account.
+///
+///     Parameters
+///     ----------
+///     tx :
+///         Transaction of interest.
+///
+///     Returns
+///     -------
+///     hash : `ethereum.crypto.hash.Hash32`
+///         Hash of the transaction.
+///
+pub fn signing_hash(tx: &Transaction) -> Hash32 {
+    transaction_signature::signing_hash_pre155(tx)
+}
 
 // ///
 // ///     Computes the hash of a block header.
@@ -677,20 +1135,18 @@ pub fn validate_ommers(ommers: &Vec<Header>, block_header: Header, chain: &Block
 ///     check : `bool`
 ///         True if gas limit constraints are satisfied, False otherwise.
 ///
-#[allow(unused_variables)]
 pub fn check_gas_limit(gas_limit: &Uint, parent_gas_limit: &Uint) -> Result<bool, EthereumException> {
-    // max_adjustment_delta = (parent_gas_limit).floordiv(GAS_LIMIT_ADJUSTMENT_FACTOR);
-    // if gas_limit >= parent_gas_limit + max_adjustment_delta {
-    //     return Ok(false);
-    // }
-    // if gas_limit <= parent_gas_limit - max_adjustment_delta {
-    //     return Ok(false);
-    // }
-    // if gas_limit < GAS_LIMIT_MINIMUM {
-    //     return Ok(false);
-    // }
-    // return Ok(true);
-    todo!()
+    let max_adjustment_delta = parent_gas_limit / GAS_LIMIT_ADJUSTMENT_FACTOR;
+    if *gas_limit >= parent_gas_limit + &max_adjustment_delta {
+        return Ok(false);
+    }
+    if gas_limit + &max_adjustment_delta <= *parent_gas_limit {
+        return Ok(false);
+    }
+    if *gas_limit < Uint::from(GAS_LIMIT_MINIMUM) {
+        return Ok(false);
+    }
+    Ok(true)
 }
 
 ///
@@ -722,18 +1178,19 @@ pub fn check_gas_limit(gas_limit: &Uint, parent_gas_limit: &Uint) -> Result<bool
 ///     difficulty : `ethereum.base_types.Uint`
 ///         Computed difficulty for a block.
 ///
-#[allow(unused_variables)]
 pub fn calculate_block_difficulty(block_number: &Uint, block_timestamp: &U256, parent_timestamp: &U256, parent_difficulty: &Uint) -> Result<Uint, EthereumException> {
-    // max_adjustment_delta = (parent_difficulty).floordiv(Uint(2048)?);
-    // if block_timestamp < parent_timestamp + 13 {
-    //     difficulty = parent_difficulty + max_adjustment_delta;
-    // } else {
-    //     difficulty = parent_difficulty - max_adjustment_delta;
-    // }
-    // num_bomb_periods = (int(block_number)?).floordiv(100000) - 2;
-    // if num_bomb_periods >= 0 {
-    //     difficulty += (2).pow(num_bomb_periods);
-    // }
-    // return Ok(max(difficulty, MINIMUM_DIFFICULTY)?);
-    todo!()
+    let max_adjustment_delta = parent_difficulty / DIFFICULTY_BOUND_DIVISOR;
+    let mut difficulty = if *block_timestamp < parent_timestamp + DIFFICULTY_DURATION_LIMIT {
+        parent_difficulty + max_adjustment_delta
+    } else {
+        // The difficulty never drops below the minimum, so this can't
+        // underflow.
+        parent_difficulty - max_adjustment_delta
+    };
+    let periods = block_number / BOMB_PERIOD;
+    if periods >= Uint::from(2u8) {
+        let num_bomb_periods = u32::try_from(periods - 2u8).map_err(|_| EthereumException::ValueError)?;
+        difficulty += Uint::from(2u8).pow(num_bomb_periods);
+    }
+    Ok(difficulty.max(Uint::from(MINIMUM_DIFFICULTY)))
 }
@@ -6,42 +6,25 @@
 /// Types re-used throughout the specification, which are specific to Ethereum.
 /// 
 
+use crate::prelude::*;
 use crate::ethereum::{base_types::{Uint, U256, Bytes, Bytes20, Bytes256, Bytes32, Bytes8}, rlp::{self, RLP}};
 
-pub type Hash32 = [u8; 32];
-pub type Hash64 = [u8; 64];
+pub use crate::ethereum::crypto::hash::{keccak256, keccak512, Hash32, Hash64};
 
 pub type Address = Bytes20;
 pub type Root = Hash32;
 pub type Bloom = Bytes256;
 
-pub const TX_BASE_COST : u64 = 21000;
-pub const TX_DATA_COST_PER_NON_ZERO : u64 = 68;
-pub const TX_DATA_COST_PER_ZERO : u64 = 4;
+/// The code of a contract. Accounts with the same code share one copy of
+/// it, which the state looks up by its hash.
+pub type Code = alloc::sync::Arc<[u8]>;
 
-pub fn keccak256(data: &[u8]) -> Hash32 {
-    use tiny_keccak::{Hasher, Keccak};
-
-    let mut buf = [0u8; 32];
-    let mut hasher = Keccak::v256();
-    hasher.update(data.as_ref());
-    hasher.finalize(&mut buf);
-    buf
-}
-
-pub fn keccak512(data: &[u8]) -> Hash64 {
-    use tiny_keccak::{Hasher, Keccak};
-
-    let mut buf = [0u8; 64];
-    let mut hasher = Keccak::v512();
-    hasher.update(data.as_ref());
-    hasher.finalize(&mut buf);
-    buf
-}
+pub use super::constants::{TX_BASE_COST, TX_DATA_COST_PER_NON_ZERO, TX_DATA_COST_PER_ZERO};
 
 /// 
 ///     Atomic operation performed on the block chain.
 ///     
+#[derive(Clone, Debug, PartialEq)]
 pub struct Transaction {
     pub nonce: U256,
     pub gas_price: U256,
@@ -54,6 +37,27 @@ pub struct Transaction {
     pub s: U256,
 }
 
+impl RLP for Transaction {
+    fn encode(&self) -> Bytes {
+        // A contract creation has no recipient, which is encoded as `b""`.
+        let to: &[u8] = match &self.to {
+            Some(to) => to,
+            None => &[],
+        };
+        rlp::encode(&(
+            &self.nonce,
+            &self.gas_price,
+            &self.gas,
+            to,
+            &self.value,
+            &self.data,
+            &self.v,
+            &self.r,
+            &self.s,
+        ))
+    }
+}
+
 
 /// 
 ///     State associated with an address.
@@ -62,10 +66,11 @@ pub struct Transaction {
 pub struct Account {
     pub nonce: Uint,
     pub balance: U256,
-    pub code: Bytes,
+    pub code: Code,
 }
 
-pub fn empty_account() -> Account {
+#[allow(non_snake_case)]
+pub fn EMPTY_ACCOUNT() -> Account {
     Account::default()
 }
 
@@ -109,28 +114,36 @@ pub struct Header {
 
 
 impl Header {
+    ///
+    ///     The hash identifying the block, `keccak256` of the RLP encoded
+    ///     header, which the next block names as its `parent_hash`.
+    ///
+    pub fn hash(&self) -> Hash32 {
+        rlp::rlp_hash(self)
+    }
 }
 
 impl RLP for Header {
     fn encode(&self) -> Bytes {
-        // rlp::encode(&(
-        //     &self.parent_hash,
-        //     &self.ommers_hash,
-        //     &self.coinbase,
-        //     &self.state_root,
-        //     &self.transactions_root,
-        //     &self.receipt_root,
-        //     &self.bloom,
-        //     &self.difficulty.clone(),
-        //     &self.number.clone(),
-        //     &self.gas_limit.clone(),
-        //     &self.gas_used.clone(),
-        //     &self.timestamp.clone(),
-        //     &self.extra_data.clone(),
-        //     &self.mix_digest,
-        //     &self.nonce,
-        // ))
-        todo!()
+        // Tuples only implement `RLP` up to 12 fields.
+        let fields: [&dyn RLP; 15] = [
+            &self.parent_hash,
+            &self.ommers_hash,
+            &self.coinbase,
+            &self.state_root,
+            &self.transactions_root,
+            &self.receipt_root,
+            &self.bloom,
+            &self.difficulty,
+            &self.number,
+            &self.gas_limit,
+            &self.gas_used,
+            &self.timestamp,
+            &self.extra_data,
+            &self.mix_digest,
+            &self.nonce,
+        ];
+        rlp::encode_iter(fields)
     }
 }
 
@@ -138,6 +151,7 @@ impl RLP for Header {
 /// 
 ///     A complete block.
 ///     
+#[derive(Clone, Debug)]
 pub struct Block {
     pub header: Header,
     pub transactions: Vec<Transaction>,
@@ -146,13 +160,25 @@ pub struct Block {
 
 
 impl Block {
+    ///
+    ///     The hash of the block's header.
+    ///
+    pub fn hash(&self) -> Hash32 {
+        self.header.hash()
+    }
+}
+
+impl RLP for Block {
+    fn encode(&self) -> Bytes {
+        rlp::encode(&(&self.header, &self.transactions, &self.ommers))
+    }
 }
 
 
 /// 
 ///     Data record produced during the execution of a transaction.
 ///     
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct Log {
     pub address: Address,
     pub topics: Vec<Hash32>,
@@ -163,10 +189,17 @@ pub struct Log {
 impl Log {
 }
 
+impl RLP for Log {
+    fn encode(&self) -> Bytes {
+        rlp::encode(&(&self.address, &self.topics, &self.data))
+    }
+}
+
 
 /// 
 ///     Result of a transaction.
 ///     
+#[derive(Clone, Debug, PartialEq)]
 pub struct Receipt {
     pub post_state: Root,
     pub cumulative_gas_used: Uint,
@@ -177,3 +210,14 @@ pub struct Receipt {
 
 impl Receipt {
 }
+
+impl RLP for Receipt {
+    fn encode(&self) -> Bytes {
+        rlp::encode(&(
+            &self.post_state,
+            &self.cumulative_gas_used,
+            &self.bloom,
+            &self.logs,
+        ))
+    }
+}
@@ -0,0 +1,360 @@
+//!
+//! # Historical State
+//!
+//! ## Introduction
+//!
+//! The states of past blocks, kept in a `NodeStore` by their root: each
+//! block commits what it changed, see [commit_state], and the tries of the
+//! states share the nodes that didn't change. [StateAtRoot] reads the
+//! accounts and storage of any root the store still has.
+//!
+//! A call runs on the part of a state it reads, which [StateAtRoot::run]
+//! loads from the store as it goes: the call runs again with what it read
+//! and the store didn't give yet, until it reads nothing new. A run is
+//! deterministic, so the last one goes as it would on the whole state.
+//!
+//! A root is kept until it is pruned, with `NodeStore::dereference`, which
+//! drops the nodes, and the storage tries, only it held.
+//!
+
+use alloc::collections::BTreeSet;
+
+use crate::prelude::*;
+
+use super::{
+    fork_types::{encode_account, keccak256, Account, Address, Code, Hash32, Root},
+    state::{
+        get_account_optional, get_accounts, get_storage, iter_storage, mark_storage, record_accesses, set_account,
+        set_storage, take_accesses, State, StateAccesses,
+    },
+    trie::{persistent::NodeStore, EMPTY_TRIE_ROOT},
+};
+use crate::ethereum::{
+    base_types::{Bytes, Bytes32, Uint, U256},
+    exceptions::EthereumException,
+    rlp::{self, Simple},
+    utils::ensure::ensure,
+};
+
+/// An account as the account trie holds it.
+struct StoredAccount {
+    nonce: Uint,
+    balance: U256,
+    storage_root: Root,
+    code_hash: Hash32,
+}
+
+fn decode_account(encoded: &[u8]) -> Result<StoredAccount, EthereumException> {
+    let fields = rlp::decode_to_sequence(encoded)?;
+    let [Simple::Bytes(nonce), Simple::Bytes(balance), Simple::Bytes(storage_root), Simple::Bytes(code_hash)] =
+        &fields[..]
+    else {
+        return Err(EthereumException::RLPDecodingError);
+    };
+    Ok(StoredAccount {
+        nonce: Uint::from_bytes_be(nonce),
+        balance: U256::from_bytes_be(balance),
+        storage_root: Root::try_from(&storage_root[..]).map_err(|_| EthereumException::RLPDecodingError)?,
+        code_hash: Hash32::try_from(&code_hash[..]).map_err(|_| EthereumException::RLPDecodingError)?,
+    })
+}
+
+///
+///     Commit the changes a block made to the state after its parent, and
+///     keep the root of the state after it.
+///
+///     Parameters
+///     ----------
+///     store :
+///         The store, made with `NodeStore::for_state`, which has the state
+///         after the parent.
+///     parent_root :
+///         Root of the state after the parent.
+///     state :
+///         The state after the block.
+///     accesses :
+///         What the block accessed of the state, which includes everything it
+///         changed.
+///
+///     Returns
+///     -------
+///     root : `Root`
+///         The root of the state after the block, referenced once more.
+///
+///     Raises
+///     ------
+///     ValueError :
+///         If the store doesn't have the state after the parent.
+///
+pub fn commit_state(
+    store: &mut NodeStore,
+    parent_root: &Root,
+    state: &State,
+    accesses: &StateAccesses,
+) -> Result<Root, EthereumException> {
+    let mut addresses = accesses.accounts.clone();
+    addresses.extend(accesses.storage.keys());
+    addresses.extend(&accesses.destroyed_storage);
+
+    // The new storage roots are kept until the accounts reference them.
+    let mut storage_roots = Vec::new();
+    let mut changes = Vec::with_capacity(addresses.len());
+    for address in &addresses {
+        let key = keccak256(address);
+        let Some(account) = get_account_optional(state, address) else {
+            changes.push((Bytes::from(&key[..]), Bytes::default()));
+            continue;
+        };
+        // Destroyed storage holds only what was written since.
+        let (pre_root, slots): (Root, BTreeSet<Bytes32>) = if accesses.destroyed_storage.contains(address) {
+            (
+                EMPTY_TRIE_ROOT(),
+                iter_storage(state, address).map(|(_, key, _)| key).collect(),
+            )
+        } else {
+            let pre_root = match store.get_value(parent_root, &key)? {
+                Some(encoded) => decode_account(&encoded)?.storage_root,
+                None => EMPTY_TRIE_ROOT(),
+            };
+            (pre_root, accesses.storage.get(address).cloned().unwrap_or_default())
+        };
+        let storage_root = match slots.is_empty() {
+            true => pre_root,
+            false => {
+                let slots = slots.iter().map(|slot| {
+                    let value = get_storage(state, address, slot);
+                    let value = if value == U256::default() {
+                        Bytes::default()
+                    } else {
+                        rlp::encode(&value)
+                    };
+                    (Bytes::from(&keccak256(slot)[..]), value)
+                });
+                let root = store.update(&pre_root, slots.collect::<Vec<_>>())?;
+                storage_roots.push(root);
+                root
+            }
+        };
+        if !account.code.is_empty() {
+            store.insert_code(&account.code);
+        }
+        changes.push((Bytes::from(&key[..]), encode_account(account, storage_root)));
+    }
+
+    let root = store.update(parent_root, changes)?;
+    for storage_root in &storage_roots {
+        store.dereference(storage_root);
+    }
+    Ok(root)
+}
+
+///
+///     Commit a whole state to a store made with `NodeStore::for_state`,
+///     as that of the first block kept, and keep its root.
+///
+pub fn commit_whole_state(store: &mut NodeStore, state: &State) -> Result<Root, EthereumException> {
+    let accounts: BTreeSet<Address> = get_accounts(state).into_keys().collect();
+    let accesses = StateAccesses {
+        destroyed_storage: accounts.clone(),
+        accounts,
+        ..StateAccesses::default()
+    };
+    commit_state(store, &EMPTY_TRIE_ROOT(), state, &accesses)
+}
+
+///
+///     Read-only access to the state of a root a store keeps.
+///
+#[derive(Clone, Copy, Debug)]
+pub struct StateAtRoot<'a> {
+    store: &'a NodeStore,
+    root: Root,
+}
+
+impl<'a> StateAtRoot<'a> {
+    ///
+    ///     Open the state of a root.
+    ///
+    ///     Raises
+    ///     ------
+    ///     ValueError :
+    ///         If the store doesn't have the root, which it never kept or
+    ///         has pruned.
+    ///
+    pub fn open(store: &'a NodeStore, root: Root) -> Result<Self, EthereumException> {
+        ensure(
+            root == EMPTY_TRIE_ROOT() || store.get(&root).is_some(),
+            EthereumException::ValueError,
+        )?;
+        Ok(Self { store, root })
+    }
+
+    /// The root of the state.
+    pub fn root(&self) -> Root {
+        self.root
+    }
+
+    fn stored_account(&self, address: &Address) -> Result<Option<StoredAccount>, EthereumException> {
+        self.store
+            .get_value(&self.root, &keccak256(address))?
+            .map(|encoded| decode_account(&encoded))
+            .transpose()
+    }
+
+    ///
+    ///     Get the account at an address, or `None` if there is none.
+    ///
+    ///     Raises
+    ///     ------
+    ///     ValueError :
+    ///         If the store doesn't have the nodes or the code of the
+    ///         account.
+    ///
+    pub fn get_account_optional(&self, address: &Address) -> Result<Option<Account>, EthereumException> {
+        let Some(stored) = self.stored_account(address)? else {
+            return Ok(None);
+        };
+        let code = match self.store.code(&stored.code_hash) {
+            Some(code) => Code::from(code),
+            None if stored.code_hash == keccak256(&[]) => Code::default(),
+            None => return Err(EthereumException::ValueError),
+        };
+        Ok(Some(Account {
+            nonce: stored.nonce,
+            balance: stored.balance,
+            code,
+        }))
+    }
+
+    ///
+    ///     Get the storage root of an account, that of the empty trie if
+    ///     there is no account.
+    ///
+    pub fn storage_root(&self, address: &Address) -> Result<Root, EthereumException> {
+        Ok(self
+            .stored_account(address)?
+            .map_or_else(EMPTY_TRIE_ROOT, |stored| stored.storage_root))
+    }
+
+    ///
+    ///     Get the value of a storage slot of an account, zero if it isn't
+    ///     set.
+    ///
+    pub fn get_storage(&self, address: &Address, key: &Bytes32) -> Result<U256, EthereumException> {
+        let storage_root = self.storage_root(address)?;
+        if storage_root == EMPTY_TRIE_ROOT() {
+            return Ok(U256::default());
+        }
+        match self.store.get_value(&storage_root, &keccak256(key))? {
+            Some(encoded) => Ok(U256::from_bytes_be(rlp::decode_to_bytes(&encoded)?)),
+            None => Ok(U256::default()),
+        }
+    }
+
+    ///
+    ///     Build the part of the state with the accounts and storage slots
+    ///     of `accesses`. The accounts with storage are noted to have it,
+    ///     whichever slots are loaded.
+    ///
+    pub fn load(&self, accesses: &StateAccesses) -> Result<State, EthereumException> {
+        let mut addresses = accesses.accounts.clone();
+        addresses.extend(accesses.storage.keys());
+        addresses.extend(&accesses.storage_presence);
+
+        let mut state = State::default();
+        for address in &addresses {
+            let Some(account) = self.get_account_optional(address)? else {
+                continue;
+            };
+            set_account(&mut state, *address, Some(account));
+            for key in accesses.storage.get(address).into_iter().flatten() {
+                let value = self.get_storage(address, key)?;
+                if value != U256::default() {
+                    set_storage(&mut state, *address, key, value);
+                }
+            }
+            if self.storage_root(address)? != EMPTY_TRIE_ROOT() {
+                mark_storage(&mut state, *address);
+            }
+        }
+        Ok(state)
+    }
+
+    ///
+    ///     Run something on the state, such as a call, loading what it reads
+    ///     as it goes. Its changes are dropped.
+    ///
+    ///     Parameters
+    ///     ----------
+    ///     f :
+    ///         What to run, which may run several times, each on a state
+    ///         with more of what it read.
+    ///
+    ///     Returns
+    ///     -------
+    ///     result : `T`
+    ///         What `f` returns once the state has everything it read.
+    ///
+    ///     Raises
+    ///     ------
+    ///     ValueError :
+    ///         If the store doesn't have part of what `f` reads.
+    ///
+    pub fn run<T>(&self, f: impl FnMut(&mut State) -> T) -> Result<T, EthereumException> {
+        run_on_demand(|accesses| self.load(accesses), f)
+    }
+}
+
+///
+///     Run something on a state loaded as it goes, from wherever `load`
+///     reads it: it runs on what `load` gives for what it has read so far,
+///     then again with what it read that it wasn't given, until it reads
+///     nothing new.
+///
+///     Parameters
+///     ----------
+///     load :
+///         Builds the part of the state with the accounts and storage slots
+///         it is asked for.
+///     f :
+///         What to run, as in `StateAtRoot::run`.
+///
+///     Returns
+///     -------
+///     result : `T`
+///         What `f` returns once the state has everything it read.
+///
+///     Raises
+///     ------
+///     Whatever `load` raises.
+///
+pub fn run_on_demand<T, E>(
+    mut load: impl FnMut(&StateAccesses) -> Result<State, E>,
+    mut f: impl FnMut(&mut State) -> T,
+) -> Result<T, E> {
+    let mut loaded = StateAccesses::default();
+    loop {
+        let mut state = load(&loaded)?;
+        record_accesses(&mut state);
+        let result = f(&mut state);
+        let accesses = take_accesses(&mut state).unwrap_or_default();
+
+        let known = (
+            loaded.accounts.len(),
+            loaded.storage.values().map(BTreeSet::len).sum::<usize>(),
+        );
+        loaded.accounts.extend(accesses.accounts);
+        loaded.accounts.extend(accesses.storage_presence);
+        for (address, keys) in accesses.storage {
+            loaded.accounts.insert(address);
+            loaded.storage.entry(address).or_default().extend(keys);
+        }
+        let now = (
+            loaded.accounts.len(),
+            loaded.storage.values().map(BTreeSet::len).sum::<usize>(),
+        );
+        if now == known {
+            return Ok(result);
+        }
+    }
+}
@@ -0,0 +1,100 @@
+//!
+//! # Log Filter
+//!
+//! ## Introduction
+//!
+//! Select the logs of a transaction or block by the contract that emitted
+//! them and by their topics, as `eth_getLogs` does:
+//!
+//! ```
+//! # use execution_specs_rs::ethereum::frontier::{fork_types::Log, log_filter::LogFilter};
+//! let log = Log { address: [0xcc; 20], topics: vec![[1; 32], [2; 32]], data: Box::default() };
+//! // Logs of 0xcc..cc whose second topic is 0x02..02 or 0x03..03.
+//! let filter = LogFilter::new().address([0xcc; 20]).topic(1, [[2; 32], [3; 32]]);
+//! assert!(filter.matches(&log));
+//! assert!(!filter.clone().topic(0, [[2; 32]]).matches(&log));
+//! ```
+//!
+
+use crate::prelude::*;
+use super::{
+    bloom::add_to_bloom,
+    fork_types::{Address, Bloom, Hash32, Log},
+};
+
+/// Which logs to select, with the semantics of the `eth_getLogs` filter.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct LogFilter {
+    /// The contracts the logs may come from. Any contract if empty.
+    pub addresses: Vec<Address>,
+    /// The topics, by position. `None`, or an empty list, matches any topic;
+    /// a list matches any of its topics. A log with fewer topics than there
+    /// are positions doesn't match.
+    pub topics: Vec<Option<Vec<Hash32>>>,
+}
+
+impl LogFilter {
+    /// A filter matching every log.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Also match the logs of `address`.
+    pub fn address(mut self, address: Address) -> Self {
+        self.addresses.push(address);
+        self
+    }
+
+    /// Match the logs whose topic at `position` is any of `topics`. The
+    /// positions before it that aren't set match any topic.
+    pub fn topic(mut self, position: usize, topics: impl IntoIterator<Item = Hash32>) -> Self {
+        if self.topics.len() <= position {
+            self.topics.resize(position + 1, None);
+        }
+        self.topics[position] = Some(topics.into_iter().collect());
+        self
+    }
+
+    /// Whether `log` is selected by the filter.
+    pub fn matches(&self, log: &Log) -> bool {
+        if !self.addresses.is_empty() && !self.addresses.contains(&log.address) {
+            return false;
+        }
+        if log.topics.len() < self.topics.len() {
+            return false;
+        }
+        self.topics.iter().zip(&log.topics).all(|(wanted, topic)| match wanted {
+            Some(wanted) if !wanted.is_empty() => wanted.contains(topic),
+            _ => true,
+        })
+    }
+
+    /// The logs selected by the filter, in order.
+    pub fn filter<'a>(&'a self, logs: impl IntoIterator<Item = &'a Log>) -> impl Iterator<Item = &'a Log> {
+        logs.into_iter().filter(|log| self.matches(log))
+    }
+
+    ///
+    ///     Whether a block or receipt with the logs bloom `bloom` may hold
+    ///     logs selected by the filter. When it can't, its logs need not be
+    ///     looked at.
+    ///
+    ///     Parameters
+    ///     ----------
+    ///     bloom :
+    ///         The logs bloom of the block or receipt.
+    ///
+    pub fn may_match(&self, bloom: &Bloom) -> bool {
+        let contains = |entry: &[u8]| {
+            let mut entry_bloom = [0; 256];
+            add_to_bloom(&mut entry_bloom, entry);
+            bloom.iter().zip(entry_bloom).all(|(bits, entry_bits)| bits & entry_bits == entry_bits)
+        };
+        let address_matches =
+            self.addresses.is_empty() || self.addresses.iter().any(|address| contains(address));
+        address_matches
+            && self.topics.iter().flatten().all(|wanted| {
+                wanted.is_empty() || wanted.iter().any(|topic| contains(topic))
+            })
+    }
+}
@@ -8,9 +8,16 @@
 /// Where it all started.
 pub const MAINNET_FORK_BLOCK : u32 = 0;
 
+pub mod ancestors;
+pub mod constants;
 pub mod fork_types;
 pub mod trie;
 pub mod bloom;
 pub mod fork;
+pub mod historical_state;
+pub mod log_filter;
+pub mod proof;
 pub mod state;
+pub mod stateless;
+pub mod utils;
 pub mod vm;
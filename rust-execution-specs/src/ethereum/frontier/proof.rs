@@ -0,0 +1,213 @@
+//!
+//! # Proofs
+//!
+//! ## Introduction
+//!
+//! Merkle proofs of accounts and storage slots, in the shape of an
+//! `eth_getProof` response.
+//!
+//! A proof lists the serialized trie nodes from a root towards a key. Anyone
+//! holding only the state root can check it, and learn the account (or that
+//! it doesn't exist) and the proven storage values.
+//!
+
+use crate::prelude::*;
+use serde_json::{json, Value};
+
+use super::{
+    fork_types::{keccak256, Address, Hash32, Root},
+    state::{account_proof, get_account_optional, get_storage, storage_proof, storage_root, State},
+    trie::{verify_trie_proof, EMPTY_TRIE_ROOT},
+};
+use crate::ethereum::{
+    base_types::{u256_to_be_bytes32, Bytes, Bytes32, Uint, U256},
+    exceptions::EthereumException,
+    rlp,
+    utils::{
+        ensure::ensure,
+        hexadecimal::{hex, hex_to_bytes, hex_to_bytes20, hex_to_bytes32, hex_to_hash, hex_to_u256, hex_to_uint},
+    },
+};
+
+///
+///     Proof of an account, and of some of its storage slots.
+///
+#[derive(Clone, Debug, PartialEq)]
+pub struct AccountProof {
+    pub address: Address,
+    /// Trie nodes from the state root towards the account.
+    pub account_proof: Vec<Bytes>,
+    pub balance: U256,
+    pub code_hash: Hash32,
+    pub nonce: Uint,
+    pub storage_hash: Root,
+    pub storage_proof: Vec<StorageProof>,
+}
+
+///
+///     Proof of a storage slot.
+///
+#[derive(Clone, Debug, PartialEq)]
+pub struct StorageProof {
+    pub key: Bytes32,
+    pub value: U256,
+    /// Trie nodes from the storage root towards the slot.
+    pub proof: Vec<Bytes>,
+}
+
+///
+///     Prove an account and some of its storage slots, as `eth_getProof`
+///     does.
+///
+///     An account that doesn't exist is proven absent, and reported with
+///     the fields of an empty account, like geth does.
+///
+///     Parameters
+///     ----------
+///     state :
+///         The current state, with no open transactions.
+///     address :
+///         Address of the account.
+///     keys :
+///         Keys of the storage slots to prove.
+///
+///     Returns
+///     -------
+///     proof : `AccountProof`
+///         The proof.
+///
+pub fn get_proof(state: &State, address: &Address, keys: &[Bytes32]) -> AccountProof {
+    let account = get_account_optional(state, address).unwrap_or_default();
+    AccountProof {
+        address: *address,
+        account_proof: account_proof(state, address),
+        balance: account.balance,
+        code_hash: keccak256(&account.code),
+        nonce: account.nonce,
+        storage_hash: storage_root(state, address),
+        storage_proof: keys
+            .iter()
+            .map(|key| StorageProof {
+                key: *key,
+                value: get_storage(state, address, key),
+                proof: storage_proof(state, address, key),
+            })
+            .collect(),
+    }
+}
+
+///
+///     Check a proof against a state root.
+///
+///     Parameters
+///     ----------
+///     state_root :
+///         Root of the state the proof claims to be from.
+///     proof :
+///         The proof.
+///
+///     Returns
+///     -------
+///     result : `Result<(), EthereumException>`
+///         `ValueError` if the proof is malformed, doesn't lead to its keys
+///         or doesn't match the values it claims.
+///
+pub fn verify_proof(state_root: &Root, proof: &AccountProof) -> Result<(), EthereumException> {
+    let encoded = verify_trie_proof(state_root, &keccak256(&proof.address), &proof.account_proof)?;
+    let is_empty = proof.nonce == Uint::default()
+        && proof.balance == U256::default()
+        && proof.code_hash == keccak256(&[])
+        && proof.storage_hash == EMPTY_TRIE_ROOT();
+    match encoded {
+        // The code itself isn't part of the proof, only its hash.
+        Some(encoded) => {
+            let claimed = rlp::encode(&(
+                proof.nonce.clone(),
+                proof.balance.clone(),
+                proof.storage_hash,
+                proof.code_hash,
+            ));
+            ensure(encoded == claimed, EthereumException::ValueError)?;
+        }
+        None => ensure(is_empty, EthereumException::ValueError)?,
+    }
+
+    for slot in &proof.storage_proof {
+        let encoded = verify_trie_proof(&proof.storage_hash, &keccak256(&slot.key), &slot.proof)?;
+        let value = match encoded {
+            Some(encoded) => U256::from_bytes_be(rlp::decode_to_bytes(&encoded)?),
+            None => U256::default(),
+        };
+        ensure(value == slot.value, EthereumException::ValueError)?;
+    }
+    Ok(())
+}
+
+impl AccountProof {
+    ///
+    ///     Serialize the proof as an `eth_getProof` result.
+    ///
+    pub fn to_json(&self) -> Value {
+        json!({
+            "address": hex(&self.address),
+            "accountProof": self.account_proof.iter().map(|node| hex(node)).collect::<Vec<_>>(),
+            "balance": format!("{:#x}", self.balance),
+            "codeHash": hex(&self.code_hash),
+            "nonce": format!("{:#x}", self.nonce),
+            "storageHash": hex(&self.storage_hash),
+            "storageProof": self.storage_proof.iter().map(|slot| json!({
+                "key": hex(&slot.key),
+                "value": format!("{:#x}", slot.value),
+                "proof": slot.proof.iter().map(|node| hex(node)).collect::<Vec<_>>(),
+            })).collect::<Vec<_>>(),
+        })
+    }
+
+    ///
+    ///     Parse an `eth_getProof` result.
+    ///
+    pub fn from_json(value: &Value) -> Result<Self, EthereumException> {
+        let field = |value: &Value, name: &str| -> Result<String, EthereumException> {
+            value[name]
+                .as_str()
+                .map(str::to_owned)
+                .ok_or_else(|| EthereumException::JsonDecodeError(format!("missing {name}")))
+        };
+        let nodes = |value: &Value, name: &str| -> Result<Vec<Bytes>, EthereumException> {
+            value[name]
+                .as_array()
+                .ok_or_else(|| EthereumException::JsonDecodeError(format!("missing {name}")))?
+                .iter()
+                .map(|node| {
+                    hex_to_bytes(node.as_str().ok_or_else(|| {
+                        EthereumException::JsonDecodeError(format!("bad node in {name}"))
+                    })?)
+                })
+                .collect()
+        };
+
+        let storage_proof = value["storageProof"]
+            .as_array()
+            .ok_or_else(|| EthereumException::JsonDecodeError("missing storageProof".into()))?
+            .iter()
+            .map(|slot| {
+                Ok(StorageProof {
+                    // Keys may be given as quantities, without leading zeros.
+                    key: u256_to_be_bytes32(&hex_to_u256(&field(slot, "key")?)?),
+                    value: hex_to_u256(&field(slot, "value")?)?,
+                    proof: nodes(slot, "proof")?,
+                })
+            })
+            .collect::<Result<_, EthereumException>>()?;
+
+        Ok(Self {
+            address: hex_to_bytes20(&field(value, "address")?)?,
+            account_proof: nodes(value, "accountProof")?,
+            balance: hex_to_u256(&field(value, "balance")?)?,
+            code_hash: hex_to_hash(&field(value, "codeHash")?)?,
+            nonce: hex_to_uint(&field(value, "nonce")?)?,
+            storage_hash: hex_to_bytes32(&field(value, "storageHash")?)?,
+            storage_proof,
+        })
+    }
+}
@@ -7,17 +7,30 @@
 //!
 //! It consists of a main account trie and storage tries for each contract.
 //!
+//! The accounts share their code: the state keeps one copy of each code,
+//! under its hash, for as long as an account holds it.
+//!
 //! There is a distinction between an account that does not exist and
 //! `EMPTY_ACCOUNT`.
 //!
+//! A state can record the accounts and storage slots it is asked about,
+//! see `record_accesses`, which is what a witness for stateless execution
+//! has to prove.
+//!
 
 use super::{
-    fork_types::{Account, Address, Root, empty_account},
+    fork_types::{keccak256, Account, Address, Code, Hash32, Root, EMPTY_ACCOUNT},
     trie::{self, Trie},
 };
 use crate::ethereum::{base_types::{Bytes, Uint, U256, Bytes32}, frontier::trie::dummy_root};
 use num_traits::CheckedSub;
-use std::collections::HashMap;
+use crate::prelude::*;
+use alloc::collections::{BTreeMap, BTreeSet};
+use core::sync::atomic::{AtomicBool, Ordering};
+#[cfg(not(feature = "std"))]
+use spin::{Mutex, MutexGuard};
+#[cfg(feature = "std")]
+use std::sync::{Mutex, MutexGuard};
 
 /// Contains all information that is preserved between transactions.
 pub struct State {
@@ -27,6 +40,38 @@ pub struct State {
         Trie<Address, Option<Account>>,
         HashMap<Address, Trie<Bytes32, U256>>,
     )>,
+    /// The storage roots computed since each account's storage last
+    /// changed, so that computing the state root only hashes the storage
+    /// tries written to since. Writing to a storage trie drops its root,
+    /// and roots are only computed outside of nested transactions, so only
+    /// rolling back the outermost one can restore a trie whose root is
+    /// still here.
+    storage_roots: Mutex<HashMap<Address, Root>>,
+    /// Whether roots were computed inside the outermost transaction, which
+    /// rolling it back has to forget.
+    roots_in_transaction: AtomicBool,
+    /// The code of the accounts by its hash. A code is dropped once neither
+    /// the accounts nor the snapshots hold it, nor a copy handed out by
+    /// `get_account`.
+    codes: HashMap<Hash32, Code>,
+    /// Whether an account has let go of its code since the codes were last
+    /// pruned.
+    codes_released: bool,
+    /// What was accessed since `record_accesses`, if recording.
+    accesses: Option<Mutex<StateAccesses>>,
+}
+
+/// The accounts and storage slots a state was asked about, read or written.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct StateAccesses {
+    pub accounts: BTreeSet<Address>,
+    pub storage: BTreeMap<Address, BTreeSet<Bytes32>>,
+    /// The accounts asked whether they have storage at all, as
+    /// `is_create_collision` does, or whose storage was destroyed.
+    pub storage_presence: BTreeSet<Address>,
+    /// The accounts whose storage was destroyed. Accounts are destroyed
+    /// once their transaction is over, so this is never rolled back.
+    pub destroyed_storage: BTreeSet<Address>,
 }
 
 impl Default for State {
@@ -35,10 +80,103 @@ impl Default for State {
             main_trie: Trie::new(true, None),
             storage_tries: HashMap::new(),
             snapshots: Vec::new(),
+            storage_roots: Mutex::new(HashMap::new()),
+            roots_in_transaction: AtomicBool::new(false),
+            codes: HashMap::new(),
+            codes_released: false,
+            accesses: None,
+        }
+    }
+}
+
+impl Clone for State {
+    fn clone(&self) -> Self {
+        Self {
+            main_trie: self.main_trie.clone(),
+            storage_tries: self.storage_tries.clone(),
+            snapshots: self.snapshots.clone(),
+            storage_roots: Mutex::new(storage_roots(self).clone()),
+            roots_in_transaction: AtomicBool::new(self.roots_in_transaction.load(Ordering::Relaxed)),
+            codes: self.codes.clone(),
+            codes_released: self.codes_released,
+            accesses: self.accesses.as_ref().map(|accesses| Mutex::new(lock(accesses).clone())),
         }
     }
 }
 
+fn storage_roots(state: &State) -> MutexGuard<'_, HashMap<Address, Root>> {
+    // The cache is consistent whenever the lock is released.
+    lock(&state.storage_roots)
+}
+
+#[cfg(feature = "std")]
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// Without `std` the lock spins, and isn't poisoned.
+#[cfg(not(feature = "std"))]
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock()
+}
+
+/// Note an access to the state, if recording.
+fn record(state: &State, f: impl FnOnce(&mut StateAccesses)) {
+    if let Some(accesses) = &state.accesses {
+        f(&mut lock(accesses));
+    }
+}
+
+/// Forget the storage root of an account whose storage changed.
+fn storage_changed(state: &mut State, address: &Address) {
+    lock(&state.storage_roots).remove(address);
+}
+
+/// The shared copy of `code`, which becomes it if there is none. The code
+/// `previous` holds is the shared one already.
+fn share_code(state: &mut State, previous: Option<&Account>, code: Code) -> Code {
+    if code.is_empty() || previous.is_some_and(|previous| Code::ptr_eq(&previous.code, &code)) {
+        return code;
+    }
+    state.codes.entry(keccak256(&code)).or_insert(code).clone()
+}
+
+/// Drop the codes no account holds any more. The snapshots hold the codes
+/// of their accounts, so this waits for the transactions to end.
+fn prune_codes(state: &mut State) {
+    if state.codes_released && state.snapshots.is_empty() {
+        state.codes.retain(|_, code| Code::strong_count(code) > 1);
+        state.codes_released = false;
+    }
+}
+
+/// Start recording the accounts and storage slots accessed, forgetting
+/// those recorded so far.
+///
+/// Parameters
+/// ----------
+/// state: `State`
+///     The state
+pub fn record_accesses(state: &mut State) {
+    state.accesses = Some(Mutex::default());
+}
+
+/// Stop recording accesses.
+///
+/// Parameters
+/// ----------
+/// state: `State`
+///     The state
+///
+/// Returns
+/// -------
+/// accesses : `Option<StateAccesses>`
+///     What was accessed since `record_accesses`, or `None` if the state
+///     wasn't recording.
+pub fn take_accesses(state: &mut State) -> Option<StateAccesses> {
+    state.accesses.take().map(|accesses| core::mem::take(&mut *lock(&accesses)))
+}
+
 /// Free resources held by the state. Used by optimized implementations to
 /// release file descriptors.
 pub fn close_state(_: State) {}
@@ -46,7 +184,7 @@ pub fn close_state(_: State) {}
 /// Start a state transaction.
 ///
 /// Transactions are entirely implicit and can be nested. It is not possible to
-/// calculate the state root during a transaction.
+/// calculate the state root during a nested transaction.
 ///
 /// Parameters
 /// ----------
@@ -67,6 +205,10 @@ pub fn begin_transaction(state: &mut State) {
 ///
 pub fn commit_transaction(state: &mut State) {
     state.snapshots.pop();
+    if state.snapshots.is_empty() {
+        *state.roots_in_transaction.get_mut() = false;
+    }
+    prune_codes(state);
 }
 
 /// Rollback a state transaction, resetting the state to the point when the
@@ -81,6 +223,10 @@ pub fn rollback_transaction(state: &mut State) {
     let (main_trie, storage_tries) = state.snapshots.pop().expect("No ongoing transaction");
     state.main_trie = main_trie;
     state.storage_tries = storage_tries;
+    if state.snapshots.is_empty() && core::mem::take(state.roots_in_transaction.get_mut()) {
+        storage_roots(state).clear();
+    }
+    prune_codes(state);
 }
 
 /// Get the `Account` object at an address. Returns `EMPTY_ACCOUNT` if there
@@ -101,7 +247,7 @@ pub fn rollback_transaction(state: &mut State) {
 /// account : `Account`
 ///     Account at address.
 pub fn get_account(state: &State, address: &Address) -> Account {
-    get_account_optional(state, address).unwrap_or(empty_account())
+    get_account_optional(state, address).unwrap_or(EMPTY_ACCOUNT())
 }
 
 /// Get the `Account` object at an address. Returns `None` (rather than
@@ -119,9 +265,102 @@ pub fn get_account(state: &State, address: &Address) -> Account {
 /// account : `Account`
 ///     Account at address.
 pub fn get_account_optional(state: &State, address: &Address) -> Option<Account> {
+    record(state, |accesses| {
+        accesses.accounts.insert(*address);
+    });
     trie::trie_get(&state.main_trie, address)
 }
 
+/// Get the code with a given hash, if an account holds it, or held it when
+/// a transaction that is still running began.
+///
+/// Parameters
+/// ----------
+/// state: `State`
+///     The state
+/// code_hash : `Hash32`
+///     The hash of the code, as in the account trie.
+///
+/// Returns
+/// -------
+/// code : `Option<Code>`
+///     The code, empty for the hash of the empty code, or `None` if no
+///     account has the code.
+pub fn get_code_by_hash(state: &State, code_hash: &Hash32) -> Option<Code> {
+    if *code_hash == keccak256(&[]) {
+        return Some(Code::default());
+    }
+    state.codes.get(code_hash).cloned()
+}
+
+/// List every account in the state, ordered by address.
+///
+/// Parameters
+/// ----------
+/// state: `State`
+///     The state
+///
+/// Returns
+/// -------
+/// accounts : `BTreeMap<Address, Account>`
+///     The accounts that exist.
+pub fn get_accounts(state: &State) -> BTreeMap<Address, Account> {
+    state
+        .main_trie
+        .data
+        .iter()
+        .filter_map(|(address, account)| Some((*address, account.clone()?)))
+        .collect()
+}
+
+/// List the non-zero storage slots of an account, ordered by key.
+///
+/// Parameters
+/// ----------
+/// state: `State`
+///     The state
+/// address : `Address`
+///     Address of the account.
+///
+/// Returns
+/// -------
+/// storage : `BTreeMap<Bytes32, U256>`
+///     The slots holding a value.
+pub fn get_storage_slots(state: &State, address: &Address) -> BTreeMap<Bytes32, U256> {
+    state
+        .storage_tries
+        .get(address)
+        .map(|trie| trie.data.iter().map(|(key, value)| (*key, value.clone())).collect())
+        .unwrap_or_default()
+}
+
+/// Iterate over the non-zero storage slots of an account in the order of its
+/// storage trie, which is that of the hashes of their keys.
+///
+/// The trie only holds the hashes, so each slot comes with the key it was
+/// written under as well, the preimage of its hash.
+///
+/// Parameters
+/// ----------
+/// state: `State`
+///     The state
+/// address : `Address`
+///     Address of the account.
+///
+/// Returns
+/// -------
+/// slots : `Iterator[Tuple[Hash32, Bytes32, U256]]`
+///     The hash of each key, the key, and the value.
+pub fn iter_storage(state: &State, address: &Address) -> impl Iterator<Item = (Hash32, Bytes32, U256)> {
+    let mut slots: Vec<_> = state
+        .storage_tries
+        .get(address)
+        .map(|trie| trie.data.iter().map(|(key, value)| (keccak256(key), *key, value.clone())).collect())
+        .unwrap_or_default();
+    slots.sort_unstable_by_key(|(hash, _, _)| *hash);
+    slots.into_iter()
+}
+
 /// Set the `Account` object at an address. Setting to `None` deletes
 /// the account (but not its storage, see `destroy_account()`).
 ///
@@ -134,7 +373,20 @@ pub fn get_account_optional(state: &State, address: &Address) -> Option<Account>
 /// account : `Account`
 /// Account to set at address.
 pub fn set_account(state: &mut State, address: Address, account: Option<Account>) {
+    record(state, |accesses| {
+        accesses.accounts.insert(address);
+    });
+    let previous = trie::trie_get(&state.main_trie, &address);
+    let account = account.map(|account| Account {
+        code: share_code(state, previous.as_ref(), account.code.clone()),
+        ..account
+    });
+    if let Some(previous) = previous.filter(|previous| !previous.code.is_empty()) {
+        let kept = account.as_ref().is_some_and(|account| Code::ptr_eq(&account.code, &previous.code));
+        state.codes_released |= !kept;
+    }
     trie::trie_set(&mut state.main_trie, address, account);
+    prune_codes(state);
 }
 
 /// Completely remove the account at `address` and all of its storage.
@@ -165,7 +417,29 @@ pub fn destroy_account(state: &mut State, address: &Address) {
 ///     Address of account whose storage is to be deleted.
 ///
 pub fn destroy_storage(state: &mut State, address: &Address) {
+    record(state, |accesses| {
+        accesses.storage_presence.insert(*address);
+        accesses.destroyed_storage.insert(*address);
+    });
     state.storage_tries.remove(address);
+    storage_changed(state, address);
+}
+
+/// Note that an account has storage the state doesn't hold, for a state
+/// built from part of another, so that `account_has_storage` tells.
+///
+/// Parameters
+/// ----------
+/// state: `State`
+///     The state
+/// address : `Address`
+///     Address of the account.
+///
+pub fn mark_storage(state: &mut State, address: Address) {
+    state
+        .storage_tries
+        .entry(address)
+        .or_insert_with(|| Trie::new(true, Uint::default()));
 }
 
 /// Get a value at a storage key on an account. Returns `U256(0)` if the
@@ -186,12 +460,41 @@ pub fn destroy_storage(state: &mut State, address: &Address) {
 ///     Value at the key.
 ///
 pub fn get_storage(state: &State, address: &Address, key: &Bytes32) -> U256 {
+    record(state, |accesses| {
+        accesses.storage.entry(*address).or_default().insert(*key);
+    });
     let Some(trie) = state.storage_tries.get(address) else {
         return U256::from(0u8);
     };
     trie::trie_get(&trie, key)
 }
 
+/// Get the original value in a storage slot i.e. the value before the current
+/// transaction began. This function reads the value from the snapshots taken
+/// before executing the transaction.
+///
+/// Parameters
+/// ----------
+/// state:
+///     The current state.
+/// address:
+///     Address of the account to read the value from.
+/// key:
+///     Key of the storage slot.
+///
+pub fn get_storage_original(state: &State, address: &Address, key: &Bytes32) -> U256 {
+    record(state, |accesses| {
+        accesses.storage.entry(*address).or_default().insert(*key);
+    });
+    let Some((_, original_tries)) = state.snapshots.first() else {
+        return get_storage(state, address, key);
+    };
+    original_tries
+        .get(address)
+        .map(|trie| trie::trie_get(trie, key))
+        .unwrap_or_default()
+}
+
 /// Set a value at a storage key on an account. Setting to `U256(0)` deletes
 /// the key.
 ///
@@ -208,19 +511,26 @@ pub fn get_storage(state: &State, address: &Address, key: &Bytes32) -> U256 {
 ///
 pub fn set_storage(state: &mut State, address: Address, key: &Bytes32, value: U256) {
     // assert!(trie::trie_get(&state.main_trie).is_some());
+    record(state, |accesses| {
+        accesses.storage.entry(address).or_default().insert(*key);
+    });
 
     let trie = state
         .storage_tries
         .entry(address)
         .or_insert_with(|| Trie::new(true, Uint::default()));
-    trie::trie_set(trie, key.clone(), value);
-    // todo
-    // if trie._data == {}:
-    //         del state._storage_tries[address]
+    trie::trie_set(trie, *key, value);
+    if trie.data.is_empty() {
+        state.storage_tries.remove(&address);
+    }
+    storage_changed(state, &address);
 }
 
 /// Calculate the storage root of an account.
 ///
+/// The state can be inside a transaction, as a block runs in one, but not
+/// a nested one.
+///
 /// Parameters
 /// ----------
 /// state:
@@ -234,19 +544,31 @@ pub fn set_storage(state: &mut State, address: Address, key: &Bytes32, value: U2
 ///     Storage root of the account.
 ///
 pub fn storage_root(state: &State, address: &Address) -> Root {
-    assert!(state.snapshots.is_empty());
-    let z = state
+    assert!(state.snapshots.len() <= 1);
+    if let Some(root) = storage_roots(state).get(address) {
+        #[cfg(feature = "metrics")]
+        crate::ethereum::metrics::STORAGE_ROOT_CACHE_HITS.increment();
+        return *root;
+    }
+    #[cfg(feature = "metrics")]
+    crate::ethereum::metrics::STORAGE_ROOT_CACHE_MISSES.increment();
+    let root = state
         .storage_tries
         .get(address)
         .map(|trie| trie::root(trie, dummy_root))
-        .unwrap()
-        // .unwrap_or(trie::EMPTY_TRIE_ROOT.clone());
-    ;
-    z
+        .unwrap_or_else(trie::EMPTY_TRIE_ROOT);
+    storage_roots(state).insert(*address, root);
+    if !state.snapshots.is_empty() {
+        state.roots_in_transaction.store(true, Ordering::Relaxed);
+    }
+    root
 }
 
 /// Calculate the state root.
 ///
+/// The state can be inside a transaction, as a block runs in one, but not
+/// a nested one.
+///
 /// Parameters
 /// ----------
 /// state:
@@ -258,12 +580,58 @@ pub fn storage_root(state: &State, address: &Address) -> Root {
 ///     The state root.
 ///
 pub fn state_root(state: &State) -> Root {
-    assert!(state.snapshots.is_empty());
+    assert!(state.snapshots.len() <= 1);
 
     let get_state_root = |address: &Address| -> Root { storage_root(state, address) };
     trie::root(&state.main_trie, get_state_root)
 }
 
+/// Build a Merkle proof of an account in the state trie.
+///
+/// Parameters
+/// ----------
+/// state:
+///     The current state.
+/// address:
+///     Address of the account to prove.
+///
+/// Returns
+/// -------
+/// proof : `Vec<Bytes>`
+///     The serialized trie nodes from the state root towards the account.
+///
+pub fn account_proof(state: &State, address: &Address) -> Vec<Bytes> {
+    assert!(state.snapshots.is_empty());
+
+    let get_storage_root = |address: &Address| -> Root { storage_root(state, address) };
+    trie::trie_proof(&state.main_trie, address, get_storage_root)
+}
+
+/// Build a Merkle proof of a storage slot in the storage trie of an account.
+///
+/// Parameters
+/// ----------
+/// state:
+///     The current state.
+/// address:
+///     Address of the account.
+/// key:
+///     Key of the storage slot to prove.
+///
+/// Returns
+/// -------
+/// proof : `Vec<Bytes>`
+///     The serialized trie nodes from the storage root towards the slot.
+///
+pub fn storage_proof(state: &State, address: &Address, key: &Bytes32) -> Vec<Bytes> {
+    assert!(state.snapshots.is_empty());
+
+    match state.storage_tries.get(address) {
+        Some(trie) => trie::trie_proof(trie, key, dummy_root),
+        None => trie::trie_proof(&Trie::<Bytes32, U256>::new(true, U256::default()), key, dummy_root),
+    }
+}
+
 /// Checks if an account exists in the state trie
 ///
 /// Parameters
@@ -302,6 +670,106 @@ pub fn account_has_code_or_nonce(state: &State, address: &Address) -> bool {
     account.nonce != Uint::from(0u8) || *account.code != *b""
 }
 
+/// Checks if an account has storage.
+///
+/// Parameters
+/// ----------
+/// state:
+///     The state
+/// address:
+///     Address of the account that needs to be checked.
+///
+/// Returns
+/// -------
+/// has_storage : `bool`
+///     True if the account has storage, False otherwise.
+///
+pub fn account_has_storage(state: &State, address: &Address) -> bool {
+    record(state, |accesses| {
+        accesses.storage_presence.insert(*address);
+    });
+    state.storage_tries.contains_key(address)
+}
+
+/// Checks if a contract can't be created at an address, because it has
+/// code, a nonce or storage (EIP-7610, which applies to every fork).
+///
+/// Parameters
+/// ----------
+/// state:
+///     The state
+/// address:
+///     Address the contract would be created at.
+///
+/// Returns
+/// -------
+/// is_collision : `bool`
+///     True if creating a contract at the address fails, False otherwise.
+///
+pub fn is_create_collision(state: &State, address: &Address) -> bool {
+    account_has_code_or_nonce(state, address) || account_has_storage(state, address)
+}
+
+/// Checks if an account has zero nonce, empty code and zero balance, the
+/// emptiness of EIP-161. A non-existent account is empty too.
+///
+/// Frontier doesn't distinguish empty accounts, this is for the forks from
+/// Spurious Dragon on.
+///
+/// Parameters
+/// ----------
+/// state:
+///     The state
+/// address:
+///     Address of the account that needs to be checked.
+///
+/// Returns
+/// -------
+/// is_empty : `bool`
+///     True if the account is empty, False otherwise.
+///
+pub fn is_account_empty(state: &State, address: &Address) -> bool {
+    get_account(state, address) == EMPTY_ACCOUNT()
+}
+
+/// Checks if an account exists and is empty.
+///
+/// Parameters
+/// ----------
+/// state:
+///     The state
+/// address:
+///     Address of the account that needs to be checked.
+///
+/// Returns
+/// -------
+/// exists_and_is_empty : `bool`
+///     True if the account exists and has zero nonce, empty code and zero
+///     balance, False otherwise.
+///
+pub fn account_exists_and_is_empty(state: &State, address: &Address) -> bool {
+    get_account_optional(state, address).is_some_and(|account| account == EMPTY_ACCOUNT())
+}
+
+/// Checks if an account exists and is not empty, that is "alive" in the
+/// sense of EIP-161.
+///
+/// Parameters
+/// ----------
+/// state:
+///     The state
+/// address:
+///     Address of the account that needs to be checked.
+///
+/// Returns
+/// -------
+/// is_alive : `bool`
+///     True if the account is alive, False otherwise.
+///
+pub fn is_account_alive(state: &State, address: &Address) -> bool {
+    get_account_optional(state, address).is_some_and(|account| account != EMPTY_ACCOUNT())
+}
+
 /// Modify an `Account` in the `State`.
 pub fn modify_state<F>(state: &mut State, address: Address, f: F)
 where
@@ -366,7 +834,7 @@ pub fn set_account_balance(state: &mut State, address: Address, amount: U256) {
 ///
 pub fn touch_account(state: &mut State, address: Address) {
     if !account_exists(state, &address) {
-        set_account(state, address, Some(empty_account()));
+        set_account(state, address, Some(EMPTY_ACCOUNT()));
     }
 }
 
@@ -402,7 +870,7 @@ pub fn increment_nonce(state: &mut State, address: Address) {
 ///
 pub fn set_code(state: &mut State, address: Address, code: Bytes) {
     let write_code = |sender: &mut Account| {
-        sender.code = code;
+        sender.code = code.into();
     };
     modify_state(state, address, write_code);
 }
@@ -424,3 +892,99 @@ pub fn create_ether(state: &mut State, address: Address, amount: U256) {
     };
     modify_state(state, address, increase_balance);
 }
+
+/// How an account changed, see [transaction_diff] and [state_diff].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct AccountDiff {
+    /// The account before the change, `None` if it did not exist.
+    pub pre: Option<Account>,
+    /// The account after the change, `None` if it does not exist anymore.
+    pub post: Option<Account>,
+    /// The storage slots that changed, with their values before and after.
+    pub storage: BTreeMap<Bytes32, (U256, U256)>,
+}
+
+/// The accounts that changed, see [transaction_diff] and [state_diff].
+pub type StateDiff = BTreeMap<Address, AccountDiff>;
+
+/// Compute the changes made to the state since the innermost ongoing
+/// transaction began.
+///
+/// Parameters
+/// ----------
+/// state:
+///     The state, with an ongoing transaction.
+///
+/// Returns
+/// -------
+/// diff : `StateDiff`
+///     Every account whose fields or storage differ from the start of the
+///     transaction.
+///
+pub fn transaction_diff(state: &State) -> StateDiff {
+    let (pre_main_trie, pre_storage_tries) = state.snapshots.last().expect("No ongoing transaction");
+    diff_tries(pre_main_trie, pre_storage_tries, &state.main_trie, &state.storage_tries)
+}
+
+/// Compute the changes that turn one state into another, such as the
+/// states before and after a block.
+///
+/// Parameters
+/// ----------
+/// pre:
+///     The state before.
+/// post:
+///     The state after.
+///
+/// Returns
+/// -------
+/// diff : `StateDiff`
+///     Every account whose fields or storage differ between the states.
+///
+pub fn state_diff(pre: &State, post: &State) -> StateDiff {
+    diff_tries(&pre.main_trie, &pre.storage_tries, &post.main_trie, &post.storage_tries)
+}
+
+fn diff_tries(
+    pre_main_trie: &Trie<Address, Option<Account>>,
+    pre_storage_tries: &HashMap<Address, Trie<Bytes32, U256>>,
+    main_trie: &Trie<Address, Option<Account>>,
+    storage_tries: &HashMap<Address, Trie<Bytes32, U256>>,
+) -> StateDiff {
+    let addresses: BTreeSet<&Address> = pre_main_trie
+        .data
+        .keys()
+        .chain(main_trie.data.keys())
+        .chain(pre_storage_tries.keys())
+        .chain(storage_tries.keys())
+        .collect();
+
+    let mut diff = StateDiff::new();
+    for address in addresses {
+        let pre = trie::trie_get(pre_main_trie, address);
+        let post = trie::trie_get(main_trie, address);
+
+        let pre_storage = pre_storage_tries.get(address);
+        let post_storage = storage_tries.get(address);
+        let keys: BTreeSet<&Bytes32> = pre_storage
+            .into_iter()
+            .chain(post_storage)
+            .flat_map(|trie| trie.data.keys())
+            .collect();
+        let storage: BTreeMap<Bytes32, (U256, U256)> = keys
+            .into_iter()
+            .filter_map(|key| {
+                let value = |trie: Option<&Trie<Bytes32, U256>>| {
+                    trie.map(|trie| trie::trie_get(trie, key)).unwrap_or_default()
+                };
+                let (pre_value, post_value) = (value(pre_storage), value(post_storage));
+                (pre_value != post_value).then_some((*key, (pre_value, post_value)))
+            })
+            .collect();
+
+        if pre != post || !storage.is_empty() {
+            diff.insert(*address, AccountDiff { pre, post, storage });
+        }
+    }
+    diff
+}
@@ -0,0 +1,602 @@
+//!
+//! # Stateless Execution
+//!
+//! ## Introduction
+//!
+//! Executing a block without the state, from a witness: proofs of the
+//! accounts and storage slots the block accesses, against the state root of
+//! its parent, and the code of those accounts.
+//!
+//! The proofs are checked before anything runs. The block then runs on the
+//! part of the state they prove, which records what the execution accesses;
+//! an account, storage slot or code the witness doesn't have fails the
+//! execution, whatever it would have done with the value it was missing. An
+//! account that doesn't exist is proven absent, and a slot of an account
+//! without storage needs no proof of its own.
+//!
+//! Only the proven part of the state is known, which is enough for the
+//! state root after the block: the changes are made to the tries the proofs
+//! open, see `PartialTrie`. A deletion that merges a branch with a sibling
+//! the proofs don't reach needs the node of the sibling, which the witness
+//! has too. The state roots after each transaction would need the proofs of
+//! what the later transactions access, so the receipts root, which holds
+//! them before Byzantium, isn't computed. The execution gives the gas used,
+//! logs and fees of the block, its state root, and how it changed the
+//! accounts and slots of the witness.
+//!
+//! A witness is generated by executing the block on the state, see
+//! `apply_body_with_witness`, to make test vectors for stateless clients.
+//!
+
+use alloc::collections::{BTreeMap, BTreeSet};
+
+use crate::prelude::*;
+
+use serde_json::{json, Value};
+
+use super::{
+    fork::{apply_body, ApplyBodyOutput},
+    fork_types::{encode_account, keccak256, Account, Address, Bloom, Code, Hash32, Header, Log, Root, Transaction},
+    proof::{get_proof, verify_proof, AccountProof},
+    state::{
+        account_proof, get_account, get_account_optional, get_accounts, get_storage, iter_storage, record_accesses,
+        set_account, set_storage, state_diff, state_root, storage_proof, take_accesses, State, StateAccesses, StateDiff,
+    },
+    trie::{
+        bytes_to_nibble_list,
+        partial::{MissingNode, PartialTrie},
+        verify_trie_proof, EMPTY_TRIE_ROOT,
+    },
+    vm::BlockEnvironment,
+};
+use crate::ethereum::{
+    base_types::{Bytes, Bytes32, Uint, U256},
+    rlp,
+    exceptions::{ErrorContext, EthereumException},
+    fee_summary::BlockFeeSummary,
+    utils::{
+        ensure::ensure,
+        hexadecimal::{hex, hex_to_bytes},
+    },
+};
+
+///
+///     The part of a state a block needs, proven against a state root.
+///
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Witness {
+    /// Each account accessed, with the storage slots accessed.
+    pub accounts: Vec<AccountProof>,
+    /// The code of the accounts with code.
+    pub codes: Vec<Bytes>,
+    /// Trie nodes the state root after the block needs that the proofs
+    /// don't have: the siblings that deletions merge branches with.
+    pub nodes: Vec<Bytes>,
+}
+
+impl Witness {
+    ///
+    ///     Serialize the witness, the accounts as `eth_getProof` results.
+    ///
+    pub fn to_json(&self) -> Value {
+        json!({
+            "accounts": self.accounts.iter().map(AccountProof::to_json).collect::<Vec<_>>(),
+            "codes": self.codes.iter().map(|code| hex(code)).collect::<Vec<_>>(),
+            "nodes": self.nodes.iter().map(|node| hex(node)).collect::<Vec<_>>(),
+        })
+    }
+
+    ///
+    ///     Parse a witness serialized by `to_json`. The nodes may be left
+    ///     out.
+    ///
+    pub fn from_json(value: &Value) -> Result<Self, EthereumException> {
+        let array = |name: &str| {
+            value[name]
+                .as_array()
+                .ok_or_else(|| EthereumException::JsonDecodeError(format!("missing {name}")))
+        };
+        let hex_array = |values: &[Value], what: &str| {
+            values
+                .iter()
+                .map(|value| {
+                    hex_to_bytes(
+                        value
+                            .as_str()
+                            .ok_or_else(|| EthereumException::JsonDecodeError(format!("bad {what}")))?,
+                    )
+                })
+                .collect::<Result<_, _>>()
+        };
+        Ok(Self {
+            accounts: array("accounts")?
+                .iter()
+                .map(AccountProof::from_json)
+                .collect::<Result<_, _>>()?,
+            codes: hex_array(array("codes")?, "code")?,
+            nodes: match value.get("nodes") {
+                Some(_) => hex_array(array("nodes")?, "node")?,
+                None => Vec::new(),
+            },
+        })
+    }
+}
+
+/// What a block executed without the state did, see `apply_body_stateless`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct StatelessOutput {
+    pub gas_used: Uint,
+    pub transactions_root: Root,
+    pub block_logs_bloom: Bloom,
+    pub fee_summary: BlockFeeSummary,
+    pub transaction_logs: Vec<Vec<Log>>,
+    /// The state root after the block.
+    pub state_root: Root,
+    /// How the block changed the accounts and storage slots of the
+    /// witness.
+    pub diff: StateDiff,
+}
+
+///
+///     Check a witness against a state root, and build the part of the
+///     state it proves.
+///
+///     Parameters
+///     ----------
+///     state_root :
+///         Root of the state the witness is from.
+///     witness :
+///         The witness.
+///
+///     Returns
+///     -------
+///     state : `State`
+///         The proven accounts that exist, with their code and the proven
+///         storage slots that hold a value.
+///
+///     Raises
+///     ------
+///     ValueError :
+///         If a proof doesn't hold against the root, in the account of the
+///         proof.
+///     MissingWitness :
+///         If the code of a proven account is missing.
+///
+pub fn witness_state(state_root: &Root, witness: &Witness) -> Result<State, EthereumException> {
+    let codes: HashMap<_, _> = witness.codes.iter().map(|code| (keccak256(code), code)).collect();
+    let mut state = State::default();
+    for proof in &witness.accounts {
+        verify_proof(state_root, proof).in_account(&proof.address)?;
+        if verify_trie_proof(state_root, &keccak256(&proof.address), &proof.account_proof)?.is_none() {
+            continue;
+        }
+        let code = match codes.get(&proof.code_hash) {
+            Some(code) => Code::from(&code[..]),
+            None if proof.code_hash == keccak256(&[]) => Code::default(),
+            None => {
+                return Err(EthereumException::MissingWitness(format!(
+                    "code of {}",
+                    hex(&proof.address)
+                )))
+            }
+        };
+        let account = Account {
+            nonce: proof.nonce.clone(),
+            balance: proof.balance.clone(),
+            code,
+        };
+        set_account(&mut state, proof.address, Some(account));
+        for slot in &proof.storage_proof {
+            if slot.value != U256::default() {
+                set_storage(&mut state, proof.address, &slot.key, slot.value.clone());
+            }
+        }
+    }
+    Ok(state)
+}
+
+///
+///     Check that a witness has everything an execution accessed.
+///
+fn check_accesses(witness: &Witness, accesses: &StateAccesses) -> Result<(), EthereumException> {
+    let proofs: HashMap<&Address, &AccountProof> =
+        witness.accounts.iter().map(|proof| (&proof.address, proof)).collect();
+    let proof = |address: &Address| {
+        proofs
+            .get(address)
+            .ok_or_else(|| EthereumException::MissingWitness(format!("account {}", hex(address))))
+    };
+
+    for address in &accesses.accounts {
+        proof(address)?;
+    }
+    for (address, keys) in &accesses.storage {
+        let proof = proof(address)?;
+        if proof.storage_hash == EMPTY_TRIE_ROOT() {
+            continue;
+        }
+        for key in keys {
+            ensure(
+                proof.storage_proof.iter().any(|slot| slot.key == *key),
+                EthereumException::MissingWitness(format!("storage slot {} of {}", hex(key), hex(address))),
+            )?;
+        }
+    }
+    // The state only knows an account has storage from a slot that holds
+    // a value.
+    for address in &accesses.storage_presence {
+        let proof = proof(address)?;
+        ensure(
+            proof.storage_hash == EMPTY_TRIE_ROOT()
+                || proof.storage_proof.iter().any(|slot| slot.value != U256::default()),
+            EthereumException::MissingWitness(format!("a storage slot of {}", hex(address))),
+        )?;
+    }
+    Ok(())
+}
+
+///
+///     Executes a block from a witness of the state of its parent, rather
+///     than from the state.
+///
+///     Parameters
+///     ----------
+///     parent_state_root :
+///         The state root of the parent block, which the witness is checked
+///         against.
+///     block_env :
+///         The block the transactions run in.
+///     transactions :
+///         Transactions included in the block.
+///     ommers :
+///         Headers of ancestor blocks which are not direct parents.
+///     witness :
+///         Proofs of the accounts and storage slots the block accesses, and
+///         their code.
+///
+///     Returns
+///     -------
+///     output : `StatelessOutput`
+///         The gas used, transactions root, logs bloom, fees and logs of the
+///         block, its state root, and the changes it made.
+///
+///     Raises
+///     ------
+///     ValueError :
+///         If a proof doesn't hold against the parent state root.
+///     MissingWitness :
+///         If the block accesses an account, storage slot or code the
+///         witness doesn't have, or the state root needs a trie node it
+///         doesn't have.
+///     InvalidBlock :
+///         If the block is invalid.
+///
+pub fn apply_body_stateless(
+    parent_state_root: &Root,
+    block_env: &BlockEnvironment,
+    transactions: &[Transaction],
+    ommers: &[Header],
+    witness: &Witness,
+) -> Result<StatelessOutput, EthereumException> {
+    let pre = witness_state(parent_state_root, witness)?;
+    let mut state = pre.clone();
+    let (output, accesses) = run_on_witness(&mut state, block_env, transactions, ommers, witness)?;
+    let state_root = witness_state_root(parent_state_root, witness, &state, &accesses)?;
+
+    let (gas_used, transactions_root, _, block_logs_bloom, fee_summary, transaction_logs, _) = output;
+    Ok(StatelessOutput {
+        gas_used,
+        transactions_root,
+        block_logs_bloom,
+        fee_summary,
+        transaction_logs,
+        state_root,
+        diff: state_diff(&pre, &state),
+    })
+}
+
+///
+///     Executes a block on the state a witness proves, checking that the
+///     witness has everything the block accessed.
+///
+///     Returns
+///     -------
+///     output : `ApplyBodyOutput`
+///         What `apply_body` returns.
+///     accesses : `StateAccesses`
+///         What the block accessed.
+///
+pub(crate) fn run_on_witness(
+    state: &mut State,
+    block_env: &BlockEnvironment,
+    transactions: &[Transaction],
+    ommers: &[Header],
+    witness: &Witness,
+) -> Result<(ApplyBodyOutput, StateAccesses), EthereumException> {
+    record_accesses(state);
+    let result = apply_body(state, block_env, transactions, ommers);
+    let accesses = take_accesses(state).unwrap_or_default();
+    // A block that looks invalid for want of a witness is reported as such.
+    check_accesses(witness, &accesses)?;
+    Ok((result?, accesses))
+}
+
+/// Why the state root after a block couldn't be computed from a witness.
+enum RootError {
+    /// A trie node is missing, of the storage of `address` or of the state
+    /// if there is none.
+    MissingNode { address: Option<Address>, path: Bytes },
+    Exception(EthereumException),
+}
+
+impl From<EthereumException> for RootError {
+    fn from(exception: EthereumException) -> Self {
+        Self::Exception(exception)
+    }
+}
+
+///
+///     Computes the state root after a block executed from a witness.
+///
+///     Parameters
+///     ----------
+///     parent_state_root :
+///         The state root of the parent block.
+///     witness :
+///         The witness the block executed from.
+///     state :
+///         The state the witness proves, after the block.
+///     accesses :
+///         What the block accessed.
+///
+///     Returns
+///     -------
+///     state_root : `Root`
+///         The state root after the block.
+///
+///     Raises
+///     ------
+///     MissingWitness :
+///         If a trie node the changes need is missing.
+///
+pub fn witness_state_root(
+    parent_state_root: &Root,
+    witness: &Witness,
+    state: &State,
+    accesses: &StateAccesses,
+) -> Result<Root, EthereumException> {
+    compute_state_root(parent_state_root, witness, state, accesses).map_err(|error| match error {
+        RootError::MissingNode { address, path } => {
+            EthereumException::MissingWitness(match address {
+                Some(address) => format!("trie node {} of the storage of {}", nibbles(&path), hex(&address)),
+                None => format!("trie node {} of the state", nibbles(&path)),
+            })
+        }
+        RootError::Exception(exception) => exception,
+    })
+}
+
+/// The trie nodes of a witness, those of the proofs and the others.
+fn trie_nodes(witness: &Witness) -> impl Iterator<Item = &Bytes> {
+    witness
+        .accounts
+        .iter()
+        .flat_map(|proof| {
+            proof
+                .account_proof
+                .iter()
+                .chain(proof.storage_proof.iter().flat_map(|slot| &slot.proof))
+        })
+        .chain(&witness.nodes)
+}
+
+fn nibbles(path: &[u8]) -> String {
+    path.iter().map(|nibble| format!("{nibble:x}")).collect()
+}
+
+fn compute_state_root(
+    parent_state_root: &Root,
+    witness: &Witness,
+    state: &State,
+    accesses: &StateAccesses,
+) -> Result<Root, RootError> {
+    let nodes: HashMap<Hash32, &[u8]> = trie_nodes(witness).map(|node| (keccak256(node), &node[..])).collect();
+    let proofs: HashMap<&Address, &AccountProof> =
+        witness.accounts.iter().map(|proof| (&proof.address, proof)).collect();
+    let mut addresses = accesses.accounts.clone();
+    addresses.extend(accesses.storage.keys());
+    addresses.extend(&accesses.destroyed_storage);
+
+    let mut updates = Vec::with_capacity(addresses.len());
+    for address in &addresses {
+        let value = match get_account_optional(state, address) {
+            Some(account) => {
+                let storage_root = account_storage_root(&nodes, proofs.get(address), state, accesses, address)?;
+                encode_account(account, storage_root)
+            }
+            None => Bytes::default(),
+        };
+        updates.push((keccak256(address), value));
+    }
+    // Deletions go last, so that a branch they leave with one child may
+    // have gained others.
+    updates.sort_by_key(|(_, value)| value.is_empty());
+
+    let mut trie = PartialTrie::new(parent_state_root, &nodes)?;
+    for (key, value) in updates {
+        trie.set(&key, value)
+            .map_err(|MissingNode { path }| RootError::MissingNode { address: None, path })?;
+    }
+    Ok(trie.root())
+}
+
+/// The storage root of an account after a block executed from a witness.
+fn account_storage_root(
+    nodes: &HashMap<Hash32, &[u8]>,
+    proof: Option<&&AccountProof>,
+    state: &State,
+    accesses: &StateAccesses,
+    address: &Address,
+) -> Result<Root, RootError> {
+    let mut updates: Vec<(Hash32, Bytes)> = Vec::new();
+    // Destroyed storage holds only what was written since, all of which
+    // the state knows.
+    let pre_root = if accesses.destroyed_storage.contains(address) {
+        updates.extend(iter_storage(state, address).map(|(hash, _, value)| (hash, rlp::encode(&value))));
+        EMPTY_TRIE_ROOT()
+    } else {
+        let proof = proof.ok_or_else(|| EthereumException::MissingWitness(format!("account {}", hex(address))))?;
+        for key in accesses.storage.get(address).into_iter().flatten() {
+            let value = get_storage(state, address, key);
+            let value = if value == U256::default() { Bytes::default() } else { rlp::encode(&value) };
+            updates.push((keccak256(key), value));
+        }
+        proof.storage_hash
+    };
+    if updates.is_empty() {
+        return Ok(pre_root);
+    }
+    updates.sort_by_key(|(_, value)| value.is_empty());
+
+    let mut trie = PartialTrie::new(&pre_root, nodes)?;
+    for (key, value) in updates {
+        trie.set(&key, value).map_err(|MissingNode { path }| RootError::MissingNode {
+            address: Some(*address),
+            path,
+        })?;
+    }
+    Ok(trie.root())
+}
+
+///
+///     Prove what was accessed in a state.
+///
+///     Parameters
+///     ----------
+///     state :
+///         The state before the accesses, with no open transactions.
+///     accesses :
+///         The accounts and storage slots accessed.
+///
+///     Returns
+///     -------
+///     witness : `Witness`
+///         Proofs of the accounts and slots, ordered by address, and the
+///         code of the accounts with code.
+///
+pub fn build_witness(state: &State, accesses: &StateAccesses) -> Witness {
+    let mut keys: BTreeMap<Address, BTreeSet<Bytes32>> = accesses
+        .accounts
+        .iter()
+        .map(|address| (*address, BTreeSet::new()))
+        .collect();
+    for (address, slots) in &accesses.storage {
+        keys.entry(*address).or_default().extend(slots);
+    }
+    // A slot with a value shows the account has storage.
+    for address in &accesses.storage_presence {
+        let slots = keys.entry(*address).or_default();
+        let has_value = slots
+            .iter()
+            .any(|key| get_storage(state, address, key) != U256::default());
+        if let Some((_, key, _)) = iter_storage(state, address).next().filter(|_| !has_value) {
+            slots.insert(key);
+        }
+    }
+
+    let mut codes = BTreeMap::new();
+    let accounts = keys
+        .iter()
+        .map(|(address, slots)| {
+            let code = get_account(state, address).code;
+            if !code.is_empty() {
+                codes.insert(keccak256(&code), Bytes::from(&code[..]));
+            }
+            get_proof(state, address, &slots.iter().copied().collect::<Vec<_>>())
+        })
+        .collect();
+    Witness {
+        accounts,
+        codes: codes.into_values().collect(),
+        nodes: Vec::new(),
+    }
+}
+
+///
+///     Executes a block, like `apply_body`, and generates the witness to
+///     execute it without the state with `apply_body_stateless`.
+///
+///     Parameters
+///     ----------
+///     state :
+///         Current account state.
+///     block_env :
+///         The block the transactions run in.
+///     transactions :
+///         Transactions included in the block.
+///     ommers :
+///         Headers of ancestor blocks which are not direct parents.
+///
+///     Returns
+///     -------
+///     output : `ApplyBodyOutput`
+///         What `apply_body` returns.
+///     witness : `Witness`
+///         Proofs against the state before the block of every account and
+///         storage slot it accessed, their code, and the trie nodes the
+///         state root after the block needs.
+///
+///     Raises
+///     ------
+///     InvalidBlock :
+///         If the block is invalid.
+///
+pub fn apply_body_with_witness(
+    state: &mut State,
+    block_env: &BlockEnvironment,
+    transactions: &[Transaction],
+    ommers: &[Header],
+) -> Result<(ApplyBodyOutput, Witness), EthereumException> {
+    let pre = state.clone();
+    record_accesses(state);
+    let result = apply_body(state, block_env, transactions, ommers);
+    let accesses = take_accesses(state).unwrap_or_default();
+    let output = result?;
+
+    let mut witness = build_witness(&pre, &accesses);
+    let parent_state_root = state_root(&pre);
+    loop {
+        match compute_state_root(&parent_state_root, &witness, state, &accesses) {
+            Ok(_) => return Ok((output, witness)),
+            Err(RootError::MissingNode { address, path }) => {
+                let known: HashSet<Hash32> = trie_nodes(&witness).map(|node| keccak256(node)).collect();
+                let missing: Vec<Bytes> = prove_path(&pre, address.as_ref(), &path)
+                    .into_iter()
+                    .filter(|node| !known.contains(&keccak256(node)))
+                    .collect();
+                ensure(
+                    !missing.is_empty(),
+                    EthereumException::MissingWitness(format!("trie node {}", nibbles(&path))),
+                )?;
+                witness.nodes.extend(missing);
+            }
+            Err(RootError::Exception(exception)) => return Err(exception),
+        }
+    }
+}
+
+/// The nodes of the proof of a key of the state, or of the storage of
+/// `address`, that is under `path`, which lead down to the node there.
+fn prove_path(state: &State, address: Option<&Address>, path: &[u8]) -> Vec<Bytes> {
+    let under = |hash: &Hash32| bytes_to_nibble_list(hash).starts_with(path);
+    let proof = match address {
+        Some(address) => iter_storage(state, address)
+            .find(|(hash, _, _)| under(hash))
+            .map(|(_, key, _)| storage_proof(state, address, &key)),
+        None => get_accounts(state)
+            .keys()
+            .find(|address| under(&keccak256(*address)))
+            .map(|address| account_proof(state, address)),
+    };
+    proof.unwrap_or_default()
+}
@@ -0,0 +1,323 @@
+//!
+//! # Trie Debugging
+//!
+//! Renders the nodes [patricialize](super::patricialize) builds from a
+//! [Trie], to find where two tries whose roots don't match part ways. Each
+//! node shows its hash and the compact-encoded key segment it stores, as
+//! Graphviz DOT:
+//!
+//! ```text
+//! digraph trie {
+//!     node [shape=box, fontname="monospace"];
+//!     n0 [label="extension\nkey 0x16\nhash 0x5991bb8c..."];
+//!     n0 -> n1;
+//!     ...
+//! }
+//! ```
+//!
+//! or as a JSON tree, with the full hashes:
+//!
+//! ```json
+//! {
+//!     "type": "extension",
+//!     "path": "",
+//!     "key": "0x16",
+//!     "hash": "0x5991bb8c6514148a29db676a14ac506cd2cd5775ace63c30a4fe457715e9ac84",
+//!     "embedded": false,
+//!     "child": { "type": "branch", "path": "6", "children": { "4": ..., "8": ... }, ... }
+//! }
+//! ```
+//!
+//! A node whose encoding is shorter than 32 bytes is embedded in its parent
+//! rather than referenced by its hash, so its hash is not part of the root;
+//! the JSON gives the encoding of such a node as `rlp`.
+//!
+//! Every node is encoded on its own, which makes this quadratic in the depth
+//! of the trie. It is meant for the small tries of tests.
+//!
+
+use crate::prelude::*;
+use core::fmt::Write;
+
+use serde_json::{json, Map, Value};
+
+use super::{
+    _prepare_trie, common_prefix_length, internal_node_rlp, nibble_list_to_compact, patricialize_sorted, Key,
+    MaybeSync, Node, Trie, EMPTY_TRIE_ROOT,
+};
+use crate::ethereum::{
+    backend::StateBackend,
+    base_types::Bytes,
+    frontier::fork_types::{keccak256, Address, Root},
+    utils::hexadecimal::hex,
+};
+
+/// The kind of a [TrieNode].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TrieNodeKind {
+    Leaf,
+    Extension,
+    Branch,
+}
+
+impl TrieNodeKind {
+    pub fn name(self) -> &'static str {
+        match self {
+            TrieNodeKind::Leaf => "leaf",
+            TrieNodeKind::Extension => "extension",
+            TrieNodeKind::Branch => "branch",
+        }
+    }
+}
+
+/// A node of a trie and the nodes below it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TrieNode {
+    pub kind: TrieNodeKind,
+    /// The nibbles leading to the node from the root.
+    pub path: Bytes,
+    /// The compact-encoded key segment of a leaf or an extension. Empty for
+    /// a branch.
+    pub key: Bytes,
+    /// The value of a leaf, or of a branch if a key ends there.
+    pub value: Bytes,
+    /// The RLP of the node.
+    pub encoded: Bytes,
+    /// The hash of the RLP of the node.
+    pub hash: Root,
+    /// Whether the parent holds the node itself rather than its hash.
+    pub embedded: bool,
+    /// The child of an extension, or the non-empty children of a branch in
+    /// order of nibble.
+    pub children: Vec<TrieNode>,
+}
+
+impl TrieNode {
+    fn build(obj: &[(Bytes, Bytes)], level: usize, path: Vec<u8>) -> Self {
+        let encoded = internal_node_rlp(patricialize_sorted(obj, level)).encode();
+        let mut node = Self {
+            kind: TrieNodeKind::Branch,
+            hash: keccak256(&encoded),
+            // The root is hashed whatever its length.
+            embedded: !path.is_empty() && encoded.len() < 32,
+            encoded,
+            path: path.clone().into(),
+            key: Bytes::default(),
+            value: Bytes::default(),
+            children: vec![],
+        };
+
+        let (first, last) = match obj {
+            [(key, value)] => {
+                node.kind = TrieNodeKind::Leaf;
+                node.key = nibble_list_to_compact(&key[level..], true);
+                node.value = value.clone();
+                return node;
+            }
+            [first, .., last] => (&first.0, &last.0),
+            [] => unreachable!("empty subtries are left out"),
+        };
+
+        let prefix_length = common_prefix_length(&first[level..], &last[level..]);
+        if prefix_length > 0 {
+            let segment = &first[level..level + prefix_length];
+            node.kind = TrieNodeKind::Extension;
+            node.key = nibble_list_to_compact(segment, false);
+            node.children
+                .push(Self::build(obj, level + prefix_length, [&path[..], segment].concat()));
+            return node;
+        }
+
+        // As in `patricialize_sorted`, a key ending here comes first.
+        let mut rest = match obj {
+            [(key, value), rest @ ..] if key.len() == level => {
+                node.value = value.clone();
+                rest
+            }
+            _ => obj,
+        };
+        for nibble in 0..16 {
+            let length = rest.iter().take_while(|(key, _)| key[level] == nibble).count();
+            let (branch, remaining) = rest.split_at(length);
+            if !branch.is_empty() {
+                node.children
+                    .push(Self::build(branch, level + 1, [&path[..], &[nibble]].concat()));
+            }
+            rest = remaining;
+        }
+        node
+    }
+
+    ///
+    ///     Serialize the node and the nodes below it as a JSON tree.
+    ///
+    pub fn to_json(&self) -> Value {
+        let mut node = Map::new();
+        node.insert("type".into(), json!(self.kind.name()));
+        node.insert("path".into(), json!(nibbles(&self.path)));
+        if self.kind != TrieNodeKind::Branch {
+            node.insert("key".into(), json!(hex(&self.key)));
+        }
+        if self.kind == TrieNodeKind::Leaf || !self.value.is_empty() {
+            node.insert("value".into(), json!(hex(&self.value)));
+        }
+        node.insert("hash".into(), json!(hex(&self.hash)));
+        node.insert("embedded".into(), json!(self.embedded));
+        if self.embedded {
+            node.insert("rlp".into(), json!(hex(&self.encoded)));
+        }
+        match self.kind {
+            TrieNodeKind::Leaf => {}
+            TrieNodeKind::Extension => {
+                node.insert("child".into(), self.children[0].to_json());
+            }
+            TrieNodeKind::Branch => {
+                let children = self
+                    .children
+                    .iter()
+                    .map(|child| (nibbles(&child.path[self.path.len()..]), child.to_json()))
+                    .collect();
+                node.insert("children".into(), Value::Object(children));
+            }
+        }
+        Value::Object(node)
+    }
+
+    fn write_dot(&self, out: &mut String, id: &mut usize) -> usize {
+        let this = *id;
+        *id += 1;
+        let mut label = self.kind.name().to_owned();
+        if self.kind != TrieNodeKind::Branch {
+            write!(label, "\\nkey {}", hex(&self.key)).unwrap();
+        }
+        if self.kind == TrieNodeKind::Leaf || !self.value.is_empty() {
+            write!(label, "\\nvalue {}", hex(&self.value)).unwrap();
+        }
+        if self.embedded {
+            label.push_str("\\nembedded");
+        } else {
+            write!(label, "\\nhash {}", short_hash(&self.hash)).unwrap();
+        }
+        writeln!(out, "    n{this} [label=\"{label}\"];").unwrap();
+        for child in &self.children {
+            let child_id = child.write_dot(out, id);
+            match self.kind {
+                TrieNodeKind::Branch => {
+                    let nibble = nibbles(&child.path[self.path.len()..]);
+                    writeln!(out, "    n{this} -> n{child_id} [label=\"{nibble}\"];").unwrap();
+                }
+                _ => writeln!(out, "    n{this} -> n{child_id};").unwrap(),
+            }
+        }
+        this
+    }
+}
+
+/// Nibbles as hex digits, without a prefix.
+fn nibbles(nibbles: &[u8]) -> String {
+    nibbles.iter().map(|nibble| format!("{nibble:x}")).collect()
+}
+
+/// The first four bytes of a hash, enough to tell nodes apart in a drawing.
+fn short_hash(hash: &Root) -> String {
+    format!("{}...", hex(&hash[..4]))
+}
+
+///
+///     Build the nodes of a trie.
+///
+///     Parameters
+///     ----------
+///     trie :
+///         `Trie` to build the nodes of.
+///     get_storage_root :
+///         Function to get the storage root of an account. Needed to encode
+///         `Account` objects.
+///
+///     Returns
+///     -------
+///     node : `Option<TrieNode>`
+///         The root node, whose hash is the root of the trie, or `None` if
+///         the trie is empty.
+///
+pub fn trie_nodes<K, V, B, F>(trie: &Trie<K, V, B>, f: F) -> Option<TrieNode>
+where
+    K: Key,
+    V: Node,
+    B: StateBackend<K, V>,
+    F: Fn(&Address) -> Root + Clone + MaybeSync,
+{
+    let mut obj = _prepare_trie(trie, f).unwrap();
+    if obj.is_empty() {
+        return None;
+    }
+    obj.sort_unstable_by(|(a, _), (b, _)| a.cmp(b));
+    Some(TrieNode::build(&obj, 0, vec![]))
+}
+
+///
+///     Render a trie as a Graphviz DOT graph.
+///
+///     Parameters
+///     ----------
+///     trie :
+///         `Trie` to render.
+///     get_storage_root :
+///         Function to get the storage root of an account. Needed to encode
+///         `Account` objects.
+///
+///     Returns
+///     -------
+///     dot : `String`
+///         A `digraph` of the nodes, edges from a branch labelled with their
+///         nibble.
+///
+pub fn to_dot<K, V, B, F>(trie: &Trie<K, V, B>, f: F) -> String
+where
+    K: Key,
+    V: Node,
+    B: StateBackend<K, V>,
+    F: Fn(&Address) -> Root + Clone + MaybeSync,
+{
+    let mut out = String::from("digraph trie {\n    node [shape=box, fontname=\"monospace\"];\n");
+    match trie_nodes(trie, f) {
+        Some(node) => {
+            node.write_dot(&mut out, &mut 0);
+        }
+        None => writeln!(
+            out,
+            "    n0 [label=\"empty\\nhash {}\"];",
+            short_hash(&EMPTY_TRIE_ROOT())
+        )
+        .unwrap(),
+    }
+    out.push_str("}\n");
+    out
+}
+
+///
+///     Render a trie as a JSON tree.
+///
+///     Parameters
+///     ----------
+///     trie :
+///         `Trie` to render.
+///     get_storage_root :
+///         Function to get the storage root of an account. Needed to encode
+///         `Account` objects.
+///
+///     Returns
+///     -------
+///     tree : `serde_json::Value`
+///         The root node, see [TrieNode::to_json], or `null` if the trie is
+///         empty.
+///
+pub fn to_json<K, V, B, F>(trie: &Trie<K, V, B>, f: F) -> Value
+where
+    K: Key,
+    V: Node,
+    B: StateBackend<K, V>,
+    F: Fn(&Address) -> Root + Clone + MaybeSync,
+{
+    trie_nodes(trie, f).map_or(Value::Null, |node| node.to_json())
+}
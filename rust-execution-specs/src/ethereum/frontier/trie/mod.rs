@@ -0,0 +1,856 @@
+//! 
+//! # State Trie
+//! 
+//! ## Introduction
+//! 
+//! The state trie is the structure responsible for storing
+//! `.fork_types.Account` objects.
+//! 
+#![allow(dead_code)]
+
+use core::marker::PhantomData;
+
+use crate::prelude::*;
+
+use crate::ethereum::{backend::StateBackend, rlp::{self, RLP}, base_types::{Bytes, U256, Bytes32}, exceptions::EthereumException, utils::ensure::ensure};
+
+use super::fork_types::{encode_account, keccak256, Account, Address, Receipt, Root, Transaction};
+
+pub mod debug;
+pub mod partial;
+pub mod persistent;
+
+/// Below this many entries a trie is prepared on the calling thread, as
+/// handing the work out costs more than it saves.
+#[cfg(feature = "parallel")]
+const PARALLEL_PREPARE_MINIMUM: usize = 256;
+
+/// `Send + Sync` with the `parallel` feature, which prepares the entries of
+/// a trie on all cores, and nothing without it.
+#[cfg(feature = "parallel")]
+pub trait MaybeSync: Send + Sync {}
+#[cfg(feature = "parallel")]
+impl<T: Send + Sync> MaybeSync for T {}
+#[cfg(not(feature = "parallel"))]
+pub trait MaybeSync {}
+#[cfg(not(feature = "parallel"))]
+impl<T> MaybeSync for T {}
+
+pub trait Key : Eq + core::hash::Hash + AsRef<[u8]> + Clone + MaybeSync {}
+
+#[allow(non_snake_case)]
+pub fn EMPTY_TRIE_ROOT() -> Root {
+    let value = hex_literal::hex!("56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421");
+    Root::from(value)
+}
+
+/// A value that can be stored in a [Trie].
+///
+/// `preimage` is the (unhashed) key the value is stored under. Accounts use it
+/// to look up their storage root through `f`, every other node ignores both.
+pub trait Node: PartialEq + core::fmt::Debug + Clone + MaybeSync {
+    fn encode<F : Fn(&Address) -> Root>(&self, preimage: &[u8], f: F) -> Bytes;
+}
+
+impl Node for String {
+    fn encode<F : Fn(&Address) -> Root>(&self, _preimage: &[u8], _f: F) -> Bytes {
+        self.as_bytes().into()
+    }
+}
+
+impl Node for Bytes {
+    fn encode<F : Fn(&Address) -> Root>(&self, _preimage: &[u8], _f: F) -> Bytes {
+        self.clone()
+    }
+}
+
+impl Node for () {
+    fn encode<F : Fn(&Address) -> Root>(&self, _preimage: &[u8], _f: F) -> Bytes {
+        Bytes::default()
+    }
+}
+
+impl Node for Option<Account> {
+    fn encode<F : Fn(&Address) -> Root>(&self, preimage: &[u8], f: F) -> Bytes {
+        // `None` is the default value, so it is never stored in the trie.
+        let Some(account) = self else {
+            return Bytes::default();
+        };
+        let address = Address::try_from(preimage).expect("account keys are addresses");
+        encode_account(account.clone(), f(&address))
+    }
+}
+
+impl Node for Option<Transaction> {
+    fn encode<F : Fn(&Address) -> Root>(&self, _preimage: &[u8], _f: F) -> Bytes {
+        self.as_ref().map(rlp::encode).unwrap_or_default()
+    }
+}
+
+impl Node for Option<Receipt> {
+    fn encode<F : Fn(&Address) -> Root>(&self, _preimage: &[u8], _f: F) -> Bytes {
+        self.as_ref().map(rlp::encode).unwrap_or_default()
+    }
+}
+
+impl Node for U256 {
+    fn encode<F : Fn(&Address) -> Root>(&self, _preimage: &[u8], _f: F) -> Bytes {
+        rlp::encode(self)
+    }
+}
+
+impl Key for Bytes {}
+impl Key for String {}
+impl Key for Address {}
+impl Key for Bytes32 {}
+
+/// The possible return values of the function [patricialize].
+#[derive(Debug)]
+pub enum InternalNode {
+    /// The leaf of the Trie containing the value.
+    LeafNode{
+        rest_of_key: Bytes,
+        value: Bytes,
+    },
+    /// A node representing a group of keys with a common prefix.
+    ExtensionNode{
+        key_segment: Bytes,
+        subnode: Encodable,
+    },
+    /// A node representing a group of keys without a common prefix.
+    BranchNode{
+        subnodes: Vec<Encodable>,
+        value: Bytes,
+    },
+    None,
+}
+
+#[derive(Debug)]
+pub enum Encodable {
+    Bytes(Bytes),
+    RLP(Box<dyn RLP>),
+    Root(Root),
+}
+
+impl RLP for Encodable {
+    fn encode(&self) -> Bytes {
+        match self {
+            Encodable::Bytes(bytes) => bytes.as_ref().encode(),
+            Encodable::RLP(rlp) => rlp.encode(),
+            Encodable::Root(root) => root.encode(),
+        }
+    }
+}
+
+/// 
+/// Encodes a Merkle Trie node into its RLP form. The RLP will then be
+/// serialized into a `Bytes` and hashed unless it is less that 32 bytes
+/// when serialized.
+/// 
+/// This function also accepts `None`, representing the absence of a node,
+/// which is encoded to `b""`.
+/// 
+/// ## Parameters
+/// node : Optional[InternalNode]
+///     The node to encode.
+/// 
+/// ## Returns
+/// encoded : `rlp.RLP`
+///     The node encoded as RLP.
+/// 
+/// ## Rust note:
+/// 
+/// This code follows the Python model as close as is possbile.
+/// The use of Dyn is significantly suboptimal, but the code is illustrative only.
+/// 
+pub fn encode_internal_node(node: InternalNode) -> Encodable {
+    let unencoded = internal_node_rlp(node);
+    let encoded = unencoded.encode();
+    if encoded.len() < 32 {
+        Encodable::RLP(unencoded)
+    } else {
+        Encodable::Root(keccak256(&encoded))
+    }
+}
+
+/// 
+/// The RLP structure of a node, before it is serialized.
+/// 
+fn internal_node_rlp(node: InternalNode) -> Box<dyn RLP> {
+    match node {
+        InternalNode::LeafNode{rest_of_key, value} => {
+            Box::new((
+                nibble_list_to_compact(&rest_of_key, true),
+                value
+            ))
+        },
+        InternalNode::ExtensionNode{key_segment, subnode} => {
+            Box::new((
+                nibble_list_to_compact(&key_segment, false),
+                subnode
+            ))
+        },
+        InternalNode::BranchNode{mut subnodes, value} => {
+            subnodes.push(Encodable::Bytes(value));
+            Box::new(
+                subnodes
+            )
+        },
+        InternalNode::None => {
+            Box::new("")
+        },
+    }
+}
+
+
+/// 
+/// Encode a Node for storage in the Merkle Trie.
+/// 
+/// `preimage` is the key the node is stored under, which `Account` nodes need
+/// in order to obtain their storage root from `f`.
+/// 
+pub fn encode_node<N : Node, F : Fn(&Address) -> Root>(node: &N, preimage: &[u8], f: F) -> Bytes {
+    node.encode(preimage, f)
+}
+
+/// 
+/// The Merkle Trie.
+/// 
+/// Its entries are kept in a [StateBackend], a `HashMap` unless another is
+/// chosen with [Trie::with_backend].
+/// 
+#[derive(Clone, Debug)]
+pub struct Trie<K, V, B = HashMap<K, V>>
+where
+    K: Key, V: Node, B: StateBackend<K, V>,
+{
+    pub secured: bool,
+    pub default: V,
+    pub data: B,
+    _entries: PhantomData<(K, V)>,
+}
+
+
+impl<K, V> Trie<K, V>
+where
+    K: Key, V: Node,
+{
+    /// Create a new Trie.
+    /// A secured trie hashes its keys.
+    /// The default value is not included in the root.
+    pub fn new(secured: bool, default: V) -> Self {
+        Self::with_backend(secured, default, HashMap::new())
+    }
+}
+
+impl<K, V, B> Trie<K, V, B>
+where
+    K: Key, V: Node, B: StateBackend<K, V>,
+{
+    /// Create a new Trie keeping its entries in `backend`, which may
+    /// already hold some.
+    pub fn with_backend(secured: bool, default: V, backend: B) -> Self {
+        Self {
+            secured,
+            default,
+            data: backend,
+            _entries: PhantomData,
+        }
+    }
+
+    /// 
+    ///     Bulk-load a Trie from entries sorted by key, such as the pre-state
+    ///     of a fixture. Entries holding the default value are left out, as
+    ///     [trie_set] would.
+    /// 
+    ///     Parameters
+    ///     ----------
+    ///     secured :
+    ///         Whether the trie hashes its keys.
+    ///     default :
+    ///         The value that is not included in the root.
+    ///     entries :
+    ///         The entries, in increasing order of key and without repeats.
+    /// 
+    pub fn from_sorted_iter<I>(secured: bool, default: V, entries: I) -> Self
+    where
+        I: IntoIterator<Item = (K, V)>,
+    {
+        let mut backend = B::default();
+        let mut previous: Option<K> = None;
+        for (key, value) in entries {
+            if let Some(previous) = &previous {
+                debug_assert!(previous.as_ref() < key.as_ref(), "trie entries must be sorted and unique");
+            }
+            if value != default {
+                backend.set(key.clone(), value);
+            }
+            previous = Some(key);
+        }
+        Self::with_backend(secured, default, backend)
+    }
+}
+
+/// An unsecured Trie whose default value is `V::default()`.
+impl<K, V> FromIterator<(K, V)> for Trie<K, V>
+where
+    K: Key, V: Node + Default,
+{
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(entries: I) -> Self {
+        let mut trie = Self::new(false, V::default());
+        for (key, value) in entries {
+            trie_set(&mut trie, key, value);
+        }
+        trie
+    }
+}
+
+
+/// 
+/// Create a copy of `trie`. Since only frozen objects may be stored in tries,
+/// the contents are reused.
+/// 
+/// Parameters
+/// ----------
+/// trie: `Trie`
+///     Trie to copy.
+/// 
+/// Returns
+/// -------
+/// new_trie : `Trie[K, V]`
+///     A copy of the trie.
+/// 
+pub fn copy_trie<K, V, B>(trie: Trie<K, V, B>) -> Trie<K, V, B>
+where
+    K: Key, V: Node, B: StateBackend<K, V>,
+{
+    trie.clone()
+}
+
+
+/// 
+/// Stores an item in a Merkle Trie.
+/// 
+/// This method deletes the key if `value == trie.default`, because the Merkle
+/// Trie represents the default value by omitting it from the trie.
+/// 
+/// Parameters
+/// ----------
+/// trie: `Trie`
+///     Trie to store in.
+/// key : `Bytes`
+///     Key to lookup.
+/// value : `V`
+///     Node to insert at `key`.
+/// 
+pub fn trie_set<K, V, B>(trie: &mut Trie<K, V, B>, key: K, value: V)
+where
+    K: Key, V: Node, B: StateBackend<K, V>,
+{
+    if value == trie.default {
+        trie.data.delete(&key);
+    } else {
+        trie.data.set(key, value);
+    }
+}
+
+
+/// 
+/// Gets an item from the Merkle Trie.
+/// 
+/// This method returns `trie.default` if the key is missing.
+/// 
+/// Parameters
+/// ----------
+/// trie:
+///     Trie to lookup in.
+/// key :
+///     Key to lookup.
+/// 
+/// Returns
+/// -------
+/// node : `V`
+///     Node at `key` in the trie.
+/// 
+pub fn trie_get<K, V, B>(trie: &Trie<K, V, B>, key: &K) -> V
+where
+    K: Key, V: Node, B: StateBackend<K, V>,
+{
+    trie.data.get(key).unwrap_or_else(|| trie.default.clone())
+}
+
+
+/// 
+/// Find the longest common prefix of two sequences.
+/// 
+pub fn common_prefix_length(a: &[u8], b: &[u8]) -> usize {
+    for i in 0..a.len() {
+        if i >= b.len() || a[i] != b[i] {
+            return i;
+        }
+    }
+    a.len()
+}
+
+
+/// 
+/// Compresses nibble-list into a standard byte array with a flag.
+/// 
+/// A nibble-list is a list of byte values no greater than `15`. The flag is
+/// encoded in high nibble of the highest byte. The flag nibble can be broken
+/// down into two two-bit flags.
+/// 
+/// Highest nibble:
+///     +---+---+----------+--------+
+///     | _ | _ | is_leaf | parity |
+///     +---+---+----------+--------+
+///       3   2      1         0
+/// 
+/// The lowest bit of the nibble encodes the parity of the length of the
+/// remaining nibbles -- `0` when even and `1` when odd. The second lowest bit
+/// is used to distinguish leaf and extension nodes. The other two bits are not
+/// used.
+/// 
+/// ## Parameters
+/// x :
+///     Array of nibbles.
+/// is_leaf :
+///     True if this is part of a leaf node, or false if it is an extension
+///     node.
+/// 
+/// ## Returns
+/// compressed : `bytearray`
+///     Compact byte array.
+/// 
+pub fn nibble_list_to_compact(x: &[u8], is_leaf: bool) -> Bytes {
+    let mut compact = vec![];
+    if x.len() % 2 == 0 {
+        compact.push(16 * 2 * is_leaf as u8);
+        for i in (0..x.len()).step_by(2) {
+            compact.push(16 * x[i] + x[i + 1]);
+        }
+    } else {
+        compact.push(16 * (2 * is_leaf as u8 + 1) + x[0]);
+        for i in (1..x.len()).step_by(2) {
+            compact.push(16 * x[i] + x[i + 1]);
+        }
+    }
+    Box::from(compact)
+}
+
+
+/// 
+/// Converts a `Bytes` into to a sequence of nibbles (bytes with value < 16).
+/// 
+/// Parameters
+/// ----------
+/// bytes_:
+///     The `Bytes` to convert.
+/// 
+/// Returns
+/// -------
+/// nibble_list : `Bytes`
+///     The `Bytes` in nibble-list format.
+/// 
+pub fn bytes_to_nibble_list(bytes: &[u8]) -> Bytes {
+    let mut res = vec![0; bytes.len()*2];
+    for i in 0..bytes.len() {
+        res[i*2 + 0] = bytes[i] >> 4;
+        res[i*2 + 1] = bytes[i] & 15;
+    }
+    Bytes::from(res)
+}
+
+
+/// 
+/// Prepares the trie for root calculation. Removes values that are empty,
+/// hashes the keys (if `secured == True`) and encodes all the nodes.
+/// 
+/// Each entry is prepared on its own, so with the `parallel` feature the
+/// entries of a large trie are prepared on all cores. The pairs come out in
+/// the order of the entries of the backend either way.
+/// 
+/// Parameters
+/// ----------
+/// trie :
+///     The `Trie` to prepare.
+/// get_storage_root :
+///     Function to get the storage root of an account. Needed to encode
+///     `Account` objects.
+/// 
+/// Returns
+/// -------
+/// out : `Mapping[ethereum.base_types.Bytes, Node]`
+///     Object with keys mapped to nibble-byte form.
+/// 
+fn _prepare_trie<K, V, B, F>(trie: &Trie<K, V, B>, f: F) -> Result<Vec<(Bytes, Bytes)>, EthereumException>
+where
+    K: Key, V: Node, B: StateBackend<K, V>,
+    F : Fn(&Address) -> Root + Clone + MaybeSync,
+{
+    let secured = trie.secured;
+    let prepare = |(preimage, value): &(K, V)| {
+        let encoded_value = encode_node(value, preimage.as_ref(), f.clone());
+        assert!(!encoded_value.is_empty());
+        let path = if secured {
+            bytes_to_nibble_list(&keccak256(preimage.as_ref()))
+        } else {
+            bytes_to_nibble_list(preimage.as_ref())
+        };
+        (path, encoded_value)
+    };
+
+    let entries: Vec<(K, V)> = trie.data.entries().collect();
+    #[cfg(feature = "parallel")]
+    if entries.len() >= PARALLEL_PREPARE_MINIMUM {
+        use rayon::prelude::*;
+        return Ok(entries.par_iter().map(prepare).collect());
+    }
+    Ok(entries.iter().map(prepare).collect())
+}
+
+/// 
+/// Computes the root of a modified merkle patricia trie (MPT).
+/// 
+/// Parameters
+/// ----------
+/// trie :
+///     `Trie` to get the root of.
+/// get_storage_root :
+///     Function to get the storage root of an account. Needed to encode
+///     `Account` objects.
+/// 
+/// 
+/// Returns
+/// -------
+/// root : `.fork_types.Root`
+///     MPT root of the underlying key-value pairs.
+/// 
+pub fn root<K, V, B, F : Fn(&Address) -> Root + Clone + MaybeSync>(trie: &Trie<K, V, B>, f: F) -> Root
+where
+    K: Key, V: Node, B: StateBackend<K, V>,
+{
+    let mut obj = _prepare_trie(&trie, f).unwrap();
+    obj.sort_unstable_by(|(a, _), (b, _)| a.cmp(b));
+    sorted_root(&obj)
+}
+
+/// 
+/// Computes the root of key-value pairs sorted by their nibble-list keys,
+/// in a single pass over them.
+/// 
+/// Parameters
+/// ----------
+/// obj :
+///     Trie key-value pairs, with keys in nibble-list format, in increasing
+///     order of key and without repeats.
+/// 
+/// Returns
+/// -------
+/// root : `.fork_types.Root`
+///     MPT root of the key-value pairs.
+/// 
+pub fn sorted_root(obj: &[(Bytes, Bytes)]) -> Root {
+    match encode_internal_node(patricialize_sorted(obj, 0)) {
+        Encodable::RLP(rlp) => {
+            let encoded = rlp.encode();
+            Root::from(keccak256(&encoded))
+        }
+        Encodable::Root(root) => root,
+        Encodable::Bytes(_) => unreachable!(),
+    }
+}
+
+
+/// 
+/// Structural composition function.
+/// 
+/// Used to recursively patricialize and merkleize a dictionary. Includes
+/// memoization of the tree structure and hashes.
+/// 
+/// Parameters
+/// ----------
+/// obj :
+/// Underlying trie key-value pairs, with keys in nibble-list format.
+/// level :
+/// Current trie level.
+/// 
+/// Returns
+/// -------
+/// node : `ethereum.base_types.Bytes`
+/// Root node of `obj`.
+/// 
+pub fn patricialize(mut obj: Vec<(Bytes, Bytes)>, level: usize) -> InternalNode {
+    if obj.is_empty() {
+        return InternalNode::None;
+    }
+
+    if obj.len() == 1 {
+        let (arbitrary_key, value) = obj.pop().unwrap();
+        return InternalNode::LeafNode{
+            rest_of_key: Box::from(&arbitrary_key[level..]),
+            value
+        };
+    }
+
+    let arbitrary_key = &obj[0].0;
+    let substring = &arbitrary_key[level..];
+    let mut prefix_length = substring.len();
+    for (key, _value) in obj.iter().skip(1) {
+        prefix_length = <usize>::min(
+            prefix_length, 
+            common_prefix_length(substring, &key[level..])
+        );
+        if prefix_length == 0 {
+            break;
+        }
+    }
+
+    if prefix_length > 0 {
+        let prefix : Bytes = arbitrary_key[level..level + prefix_length].into();
+        let subnode = patricialize(obj, level + prefix_length);
+        return InternalNode::ExtensionNode {
+            key_segment: prefix,
+            subnode: encode_internal_node(subnode),
+        };
+    }
+
+    const EMPTY : Vec<(Bytes, Bytes)> = Vec::new();
+    let mut branches = [EMPTY; 16];
+    let mut value = Bytes::default();
+    for (key, v) in obj {
+        if key.len() == level {
+            value = v;
+        } else {
+            branches[key[level] as usize].push((key, v));
+        }
+    }
+
+    let subnodes = branches
+        .into_iter()
+        .map(|obj| encode_internal_node(patricialize(obj, level+1)))
+        .collect::<Vec<_>>();
+
+    InternalNode::BranchNode { subnodes, value }
+}
+
+/// 
+/// [patricialize] for key-value pairs sorted by key.
+/// 
+/// Sorted keys sharing a prefix are next to each other, so the common prefix
+/// of them all is that of the first and the last, and each branch is a run of
+/// consecutive pairs. Nothing is copied but the leaves.
+/// 
+/// Parameters
+/// ----------
+/// obj :
+/// Trie key-value pairs, with keys in nibble-list format, in increasing
+/// order of key and without repeats.
+/// level :
+/// Current trie level.
+/// 
+/// Returns
+/// -------
+/// node : `ethereum.base_types.Bytes`
+/// Root node of `obj`.
+/// 
+pub fn patricialize_sorted(obj: &[(Bytes, Bytes)], level: usize) -> InternalNode {
+    let (first, last) = match obj {
+        [] => return InternalNode::None,
+        [(key, value)] => {
+            return InternalNode::LeafNode{
+                rest_of_key: Box::from(&key[level..]),
+                value: value.clone(),
+            };
+        }
+        [first, .., last] => (&first.0, &last.0),
+    };
+
+    let prefix_length = common_prefix_length(&first[level..], &last[level..]);
+    if prefix_length > 0 {
+        let prefix : Bytes = first[level..level + prefix_length].into();
+        let subnode = patricialize_sorted(obj, level + prefix_length);
+        return InternalNode::ExtensionNode {
+            key_segment: prefix,
+            subnode: encode_internal_node(subnode),
+        };
+    }
+
+    // A key ending here is a prefix of the others, so it comes first.
+    let (value, mut rest) = match obj {
+        [(key, value), rest @ ..] if key.len() == level => (value.clone(), rest),
+        _ => (Bytes::default(), obj),
+    };
+    let mut subnodes = Vec::with_capacity(16);
+    for nibble in 0..16 {
+        let length = rest.iter().take_while(|(key, _)| key[level] == nibble).count();
+        let (branch, remaining) = rest.split_at(length);
+        subnodes.push(encode_internal_node(patricialize_sorted(branch, level + 1)));
+        rest = remaining;
+    }
+
+    InternalNode::BranchNode { subnodes, value }
+}
+
+/// A dummy root provider for when the value is not an address.
+pub fn dummy_root(_: &Address) -> Root {
+    Root::default()
+}
+
+/// 
+/// Builds a Merkle proof for `key`: the serialized nodes on the path from the
+/// root towards the key, as returned by `eth_getProof`.
+/// 
+/// The root node is always included. Below it, only nodes referenced by hash
+/// are listed, since smaller nodes are embedded in their parent. The proof
+/// shows that the key is absent if the path ends before reaching it.
+/// 
+/// Parameters
+/// ----------
+/// trie :
+///     `Trie` to prove the key in.
+/// key :
+///     Key to prove.
+/// get_storage_root :
+///     Function to get the storage root of an account. Needed to encode
+///     `Account` objects.
+/// 
+/// Returns
+/// -------
+/// proof : `Vec<Bytes>`
+///     The serialized nodes, starting at the root.
+/// 
+pub fn trie_proof<K, V, B, F>(trie: &Trie<K, V, B>, key: &K, f: F) -> Vec<Bytes>
+where
+    K: Key, V: Node, B: StateBackend<K, V>,
+    F : Fn(&Address) -> Root + Clone + MaybeSync,
+{
+    let path = if trie.secured {
+        bytes_to_nibble_list(&keccak256(key.as_ref()))
+    } else {
+        bytes_to_nibble_list(key.as_ref())
+    };
+
+    let mut obj = _prepare_trie(trie, f).unwrap();
+    let mut level = 0;
+    let mut proof = vec![];
+    loop {
+        let encoded = internal_node_rlp(patricialize(obj.clone(), level)).encode();
+        if level == 0 || encoded.len() >= 32 {
+            proof.push(encoded);
+        }
+
+        // Follow the path the way `patricialize` splits the keys.
+        if obj.len() <= 1 {
+            return proof;
+        }
+        let substring = &obj[0].0[level..];
+        let prefix_length = obj
+            .iter()
+            .skip(1)
+            .map(|(key, _)| common_prefix_length(substring, &key[level..]))
+            .min()
+            .unwrap();
+        if prefix_length > 0 {
+            if path.get(level..level + prefix_length) != Some(&substring[..prefix_length]) {
+                return proof;
+            }
+            level += prefix_length;
+        } else {
+            let Some(nibble) = path.get(level) else {
+                return proof;
+            };
+            obj.retain(|(key, _)| key.get(level) == Some(nibble));
+            if obj.is_empty() {
+                return proof;
+            }
+            level += 1;
+        }
+    }
+}
+
+/// 
+/// Checks a Merkle proof made by [trie_proof] against a root.
+/// 
+/// Parameters
+/// ----------
+/// root :
+///     Root of the trie the proof claims to be from.
+/// key :
+///     The proven key, hashed already if the trie is secured.
+/// proof :
+///     The serialized nodes, starting at the root.
+/// 
+/// Returns
+/// -------
+/// value : `Option<Bytes>`
+///     The encoded value at `key`, or `None` if the proof shows the key is
+///     absent. Fails if the proof doesn't lead from the root to the key.
+/// 
+pub fn verify_trie_proof(root: &Root, key: &[u8], proof: &[Bytes]) -> Result<Option<Bytes>, EthereumException> {
+    let path = bytes_to_nibble_list(key);
+    let mut level = 0;
+    let mut nodes = proof.iter();
+    let mut reference = rlp::Simple::Bytes(Box::from(&root[..]));
+    loop {
+        let node = match reference {
+            rlp::Simple::Bytes(hash) if hash.is_empty() => return Ok(None),
+            rlp::Simple::Bytes(hash) => {
+                let encoded = nodes.next().ok_or(EthereumException::ValueError)?;
+                ensure(keccak256(encoded)[..] == hash[..], EthereumException::ValueError)?;
+                match rlp::decode(encoded)? {
+                    // The root of an empty trie.
+                    rlp::Simple::Bytes(empty) if empty.is_empty() => return Ok(None),
+                    node => node,
+                }
+            }
+            embedded => embedded,
+        };
+        let rlp::Simple::Sequence(mut items) = node else {
+            return Err(EthereumException::ValueError);
+        };
+
+        match items.len() {
+            17 => {
+                let Some(nibble) = path.get(level) else {
+                    return match items.pop() {
+                        Some(rlp::Simple::Bytes(value)) if !value.is_empty() => Ok(Some(value)),
+                        _ => Ok(None),
+                    };
+                };
+                reference = items.swap_remove(*nibble as usize);
+                level += 1;
+            }
+            2 => {
+                let child = items.pop();
+                let (Some(rlp::Simple::Bytes(compact)), Some(child)) = (items.pop(), child) else {
+                    return Err(EthereumException::ValueError);
+                };
+                let (segment, is_leaf) = compact_to_nibble_list(&compact)?;
+                if path.get(level..level + segment.len()) != Some(&segment[..]) {
+                    return Ok(None);
+                }
+                level += segment.len();
+                if is_leaf {
+                    let rlp::Simple::Bytes(value) = child else {
+                        return Err(EthereumException::ValueError);
+                    };
+                    return Ok((level == path.len()).then_some(value));
+                }
+                reference = child;
+            }
+            _ => return Err(EthereumException::ValueError),
+        }
+    }
+}
+
+/// 
+/// Inverse of [nibble_list_to_compact].
+/// 
+fn compact_to_nibble_list(compact: &[u8]) -> Result<(Bytes, bool), EthereumException> {
+    let nibbles = bytes_to_nibble_list(compact);
+    let flag = *nibbles.first().ok_or(EthereumException::ValueError)?;
+    ensure(flag < 4, EthereumException::ValueError)?;
+    let start = if flag & 1 == 1 { 1 } else { 2 };
+    Ok((Box::from(&nibbles[start..]), flag & 2 == 2))
+}
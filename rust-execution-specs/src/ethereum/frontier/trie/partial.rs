@@ -0,0 +1,422 @@
+//!
+//! # Partial Tries
+//!
+//! ## Introduction
+//!
+//! A trie of which only some paths are known, those the nodes of proofs
+//! lead down: enough to set and delete the proven keys and compute the root
+//! after, which is how a block executed from a witness gets its state root.
+//! The subtries off those paths are known only by their hashes.
+//!
+//! Deleting a key can leave a branch with a single child, which then merges
+//! with it. If that child is one of the subtries known only by its hash,
+//! its node is needed as well, and [MissingNode] says where it is.
+//!
+
+use crate::prelude::*;
+
+use super::{
+    bytes_to_nibble_list, common_prefix_length, compact_to_nibble_list, encode_internal_node, internal_node_rlp,
+    Encodable, InternalNode, EMPTY_TRIE_ROOT,
+};
+use crate::ethereum::{
+    base_types::Bytes,
+    crypto::hash::Hash32,
+    exceptions::EthereumException,
+    frontier::fork_types::{keccak256, Root},
+    rlp::{self, RLP},
+};
+
+/// A node of a [PartialTrie].
+#[derive(Clone, Debug, Default)]
+enum PartialNode {
+    #[default]
+    Empty,
+    Leaf {
+        rest_of_key: Bytes,
+        value: Bytes,
+    },
+    Extension {
+        key_segment: Bytes,
+        subnode: Box<PartialNode>,
+    },
+    Branch {
+        subnodes: Box<[PartialNode; 16]>,
+        value: Bytes,
+    },
+    /// A subtrie of which only the hash is known.
+    Hash(Hash32),
+}
+
+///
+///     The path, in nibbles, to a node a change needs that the trie
+///     doesn't know.
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MissingNode {
+    pub path: Bytes,
+}
+
+///
+///     A trie of which only the paths down some nodes are known.
+///
+#[derive(Clone, Debug)]
+pub struct PartialTrie {
+    root: PartialNode,
+}
+
+impl PartialTrie {
+    ///
+    ///     Open the trie of a root, as far as the nodes known go.
+    ///
+    ///     Parameters
+    ///     ----------
+    ///     root :
+    ///         Root of the trie.
+    ///     nodes :
+    ///         Serialized nodes, by their hash. Nodes of other tries are
+    ///         ignored.
+    ///
+    ///     Raises
+    ///     ------
+    ///     ValueError :
+    ///         If a node on the known paths is malformed.
+    ///
+    pub fn new(root: &Root, nodes: &HashMap<Hash32, &[u8]>) -> Result<Self, EthereumException> {
+        let root = if *root == EMPTY_TRIE_ROOT() {
+            PartialNode::Empty
+        } else {
+            decode_reference(rlp::Simple::Bytes(Box::from(&root[..])), nodes)?
+        };
+        Ok(Self { root })
+    }
+
+    ///
+    ///     Set the value of a key, deleting it if the value is empty.
+    ///
+    ///     Parameters
+    ///     ----------
+    ///     key :
+    ///         The key, hashed already if the trie is secured.
+    ///     value :
+    ///         The encoded value.
+    ///
+    ///     Returns
+    ///     -------
+    ///     result : `Result<(), MissingNode>`
+    ///         The node the change needs that isn't known, if any, in which
+    ///         case the trie is left as it was.
+    ///
+    pub fn set(&mut self, key: &[u8], value: Bytes) -> Result<(), MissingNode> {
+        let path = bytes_to_nibble_list(key);
+        if value.is_empty() {
+            delete(&mut self.root, &path, 0)
+        } else {
+            insert(&mut self.root, &path, 0, value)
+        }
+    }
+
+    ///
+    ///     Computes the root of the trie.
+    ///
+    pub fn root(&self) -> Root {
+        match &self.root {
+            PartialNode::Hash(hash) => *hash,
+            node => match encode_internal_node(internal_node(node)) {
+                Encodable::RLP(rlp) => keccak256(&rlp.encode()),
+                Encodable::Root(root) => root,
+                Encodable::Bytes(_) => unreachable!(),
+            },
+        }
+    }
+
+    ///
+    ///     The serialized nodes of the known part of the trie that are
+    ///     referenced by their hash, children before their parents. The
+    ///     root, which is always referenced by its hash, comes last.
+    ///
+    pub fn nodes(&self) -> Vec<Bytes> {
+        let mut nodes = Vec::new();
+        if let Encodable::RLP(root) = collect_nodes(&self.root, &mut nodes) {
+            if !matches!(self.root, PartialNode::Empty) {
+                nodes.push(root.encode());
+            }
+        }
+        nodes
+    }
+}
+
+/// Decode the reference to a node in its parent: its hash, or the node
+/// itself if it is embedded.
+fn decode_reference(reference: rlp::Simple, nodes: &HashMap<Hash32, &[u8]>) -> Result<PartialNode, EthereumException> {
+    match reference {
+        rlp::Simple::Bytes(hash) if hash.is_empty() => Ok(PartialNode::Empty),
+        rlp::Simple::Bytes(hash) => {
+            let hash = Hash32::try_from(&hash[..]).map_err(|_| EthereumException::ValueError)?;
+            match nodes.get(&hash) {
+                Some(encoded) => decode_node(rlp::decode(encoded)?, nodes),
+                None => Ok(PartialNode::Hash(hash)),
+            }
+        }
+        embedded => decode_node(embedded, nodes),
+    }
+}
+
+fn decode_node(node: rlp::Simple, nodes: &HashMap<Hash32, &[u8]>) -> Result<PartialNode, EthereumException> {
+    let rlp::Simple::Sequence(mut items) = node else {
+        return Err(EthereumException::ValueError);
+    };
+    match items.len() {
+        17 => {
+            let Some(rlp::Simple::Bytes(value)) = items.pop() else {
+                return Err(EthereumException::ValueError);
+            };
+            let subnodes: Vec<PartialNode> = items
+                .into_iter()
+                .map(|item| decode_reference(item, nodes))
+                .collect::<Result<_, _>>()?;
+            let subnodes: [PartialNode; 16] = subnodes.try_into().expect("16 subnodes");
+            Ok(PartialNode::Branch {
+                subnodes: Box::new(subnodes),
+                value,
+            })
+        }
+        2 => {
+            let child = items.pop();
+            let (Some(rlp::Simple::Bytes(compact)), Some(child)) = (items.pop(), child) else {
+                return Err(EthereumException::ValueError);
+            };
+            let (key_segment, is_leaf) = compact_to_nibble_list(&compact)?;
+            if is_leaf {
+                let rlp::Simple::Bytes(value) = child else {
+                    return Err(EthereumException::ValueError);
+                };
+                return Ok(PartialNode::Leaf {
+                    rest_of_key: key_segment,
+                    value,
+                });
+            }
+            Ok(PartialNode::Extension {
+                key_segment,
+                subnode: Box::new(decode_reference(child, nodes)?),
+            })
+        }
+        _ => Err(EthereumException::ValueError),
+    }
+}
+
+fn missing(path: &[u8]) -> MissingNode {
+    MissingNode { path: Box::from(path) }
+}
+
+/// A branch holding a leaf for each of two keys that part at `level`, one
+/// of which may end there.
+fn branch(path: &[u8], level: usize, value: Bytes, other: PartialNode, other_path: &[u8]) -> PartialNode {
+    let mut subnodes: [PartialNode; 16] = Default::default();
+    let mut branch_value = Bytes::default();
+    match path.get(level) {
+        Some(nibble) => {
+            subnodes[*nibble as usize] = PartialNode::Leaf {
+                rest_of_key: Box::from(&path[level + 1..]),
+                value,
+            }
+        }
+        None => branch_value = value,
+    }
+    match (other_path.first(), other) {
+        (Some(nibble), other) => subnodes[*nibble as usize] = other,
+        (None, PartialNode::Leaf { value, .. }) => branch_value = value,
+        (None, _) => unreachable!("only a leaf ends where it parts"),
+    }
+    PartialNode::Branch {
+        subnodes: Box::new(subnodes),
+        value: branch_value,
+    }
+}
+
+/// Prefix a node with the nibbles of `segment`, if any.
+fn extend(segment: &[u8], node: PartialNode) -> PartialNode {
+    if segment.is_empty() {
+        return node;
+    }
+    match node {
+        PartialNode::Leaf { rest_of_key, value } => PartialNode::Leaf {
+            rest_of_key: [segment, &rest_of_key].concat().into(),
+            value,
+        },
+        PartialNode::Extension { key_segment, subnode } => PartialNode::Extension {
+            key_segment: [segment, &key_segment].concat().into(),
+            subnode,
+        },
+        node => PartialNode::Extension {
+            key_segment: Box::from(segment),
+            subnode: Box::new(node),
+        },
+    }
+}
+
+// `insert` and `delete` only change the trie once they know they can, so
+// that a missing node leaves it as it was.
+
+fn insert(node: &mut PartialNode, path: &[u8], level: usize, value: Bytes) -> Result<(), MissingNode> {
+    let replacement = match node {
+        PartialNode::Empty => PartialNode::Leaf {
+            rest_of_key: Box::from(&path[level..]),
+            value,
+        },
+        PartialNode::Hash(_) => return Err(missing(&path[..level])),
+        PartialNode::Leaf {
+            rest_of_key,
+            value: old,
+        } => {
+            if rest_of_key[..] == path[level..] {
+                *old = value;
+                return Ok(());
+            }
+            let common = common_prefix_length(rest_of_key, &path[level..]);
+            let other = PartialNode::Leaf {
+                rest_of_key: rest_of_key.get(common + 1..).unwrap_or_default().into(),
+                value: core::mem::take(old),
+            };
+            let branch = branch(path, level + common, value, other, &rest_of_key[common..]);
+            extend(&path[level..level + common], branch)
+        }
+        PartialNode::Extension { key_segment, subnode } => {
+            let common = common_prefix_length(key_segment, &path[level..]);
+            if common == key_segment.len() {
+                return insert(subnode, path, level + common, value);
+            }
+            let other = extend(&key_segment[common + 1..], core::mem::take(subnode));
+            let branch = branch(path, level + common, value, other, &key_segment[common..]);
+            extend(&path[level..level + common], branch)
+        }
+        PartialNode::Branch { subnodes, value: old } => {
+            return match path.get(level) {
+                Some(nibble) => insert(&mut subnodes[*nibble as usize], path, level + 1, value),
+                None => {
+                    *old = value;
+                    Ok(())
+                }
+            };
+        }
+    };
+    *node = replacement;
+    Ok(())
+}
+
+fn delete(node: &mut PartialNode, path: &[u8], level: usize) -> Result<(), MissingNode> {
+    match node {
+        PartialNode::Empty => Ok(()),
+        PartialNode::Hash(_) => Err(missing(&path[..level])),
+        PartialNode::Leaf { rest_of_key, .. } => {
+            if rest_of_key[..] == path[level..] {
+                *node = PartialNode::Empty;
+            }
+            Ok(())
+        }
+        PartialNode::Extension { key_segment, subnode } => {
+            if !path[level..].starts_with(key_segment) {
+                return Ok(());
+            }
+            delete(subnode, path, level + key_segment.len())?;
+            // The branch below may have merged with its last child.
+            let replacement = extend(key_segment, core::mem::take(subnode));
+            *node = replacement;
+            Ok(())
+        }
+        PartialNode::Branch { subnodes, value } => {
+            let nibble = path.get(level).map(|nibble| *nibble as usize);
+            let emptied = match nibble {
+                Some(nibble) => matches!(
+                    &subnodes[nibble],
+                    PartialNode::Leaf { rest_of_key, .. } if rest_of_key[..] == path[level + 1..]
+                ),
+                None => !value.is_empty(),
+            };
+            let value_left = nibble.is_some() && !value.is_empty();
+            let mut others = subnodes
+                .iter()
+                .enumerate()
+                .filter(|(other, subnode)| Some(*other) != nibble && !matches!(subnode, PartialNode::Empty));
+            // The last child takes the place of the branch, which needs to
+            // know what it is.
+            if let (true, false, Some((other, PartialNode::Hash(_))), None) =
+                (emptied, value_left, others.next(), others.next())
+            {
+                return Err(missing(&[&path[..level], &[other as u8]].concat()));
+            }
+
+            match nibble {
+                Some(nibble) => delete(&mut subnodes[nibble], path, level + 1)?,
+                None => *value = Bytes::default(),
+            }
+            let mut remaining = subnodes
+                .iter()
+                .enumerate()
+                .filter(|(_, subnode)| !matches!(subnode, PartialNode::Empty))
+                .map(|(nibble, _)| nibble);
+            let replacement = match (remaining.next(), remaining.next(), value.is_empty()) {
+                (None, _, true) => PartialNode::Empty,
+                (None, _, false) => PartialNode::Leaf {
+                    rest_of_key: Bytes::default(),
+                    value: core::mem::take(value),
+                },
+                (Some(last), None, true) => extend(&[last as u8], core::mem::take(&mut subnodes[last])),
+                _ => return Ok(()),
+            };
+            *node = replacement;
+            Ok(())
+        }
+    }
+}
+
+/// The reference to a node in its parent, like [encodable], adding the
+/// nodes it references by their hash to `nodes`.
+fn collect_nodes(node: &PartialNode, nodes: &mut Vec<Bytes>) -> Encodable {
+    let node = match node {
+        PartialNode::Hash(hash) => return Encodable::Root(*hash),
+        PartialNode::Extension { key_segment, subnode } => InternalNode::ExtensionNode {
+            key_segment: key_segment.clone(),
+            subnode: collect_nodes(subnode, nodes),
+        },
+        PartialNode::Branch { subnodes, value } => InternalNode::BranchNode {
+            subnodes: subnodes.iter().map(|subnode| collect_nodes(subnode, nodes)).collect(),
+            value: value.clone(),
+        },
+        node => internal_node(node),
+    };
+    let unencoded = internal_node_rlp(node);
+    let encoded = unencoded.encode();
+    if encoded.len() < 32 {
+        return Encodable::RLP(unencoded);
+    }
+    let hash = keccak256(&encoded);
+    nodes.push(encoded);
+    Encodable::Root(hash)
+}
+
+fn encodable(node: &PartialNode) -> Encodable {
+    match node {
+        PartialNode::Hash(hash) => Encodable::Root(*hash),
+        node => encode_internal_node(internal_node(node)),
+    }
+}
+
+fn internal_node(node: &PartialNode) -> InternalNode {
+    match node {
+        PartialNode::Empty => InternalNode::None,
+        PartialNode::Leaf { rest_of_key, value } => InternalNode::LeafNode {
+            rest_of_key: rest_of_key.clone(),
+            value: value.clone(),
+        },
+        PartialNode::Extension { key_segment, subnode } => InternalNode::ExtensionNode {
+            key_segment: key_segment.clone(),
+            subnode: encodable(subnode),
+        },
+        PartialNode::Branch { subnodes, value } => InternalNode::BranchNode {
+            subnodes: subnodes.iter().map(encodable).collect(),
+            value: value.clone(),
+        },
+        PartialNode::Hash(_) => unreachable!("encoded by its hash"),
+    }
+}
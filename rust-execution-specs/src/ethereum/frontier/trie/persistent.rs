@@ -0,0 +1,571 @@
+//!
+//! # Persistent Tries
+//!
+//! ## Introduction
+//!
+//! A [NodeStore] keeps the serialized nodes of tries by their hash, the way
+//! clients keep the state on disk, so that every version of a trie that is
+//! kept can be read again from its root. A [PersistentTrie] is a trie whose
+//! versions are kept there: each update adds the nodes it changes and leaves
+//! those of the previous versions in place.
+//!
+//! Versions share the nodes they have in common, so a node counts the
+//! references to it: one from each parent, and one each time its root is
+//! kept. Letting go of an old root, see [PersistentTrie::prune], drops the
+//! nodes no other version references.
+//!
+//! A store made [NodeStore::for_state] holds the tries of states: the
+//! leaves of the account trie reference the storage roots of their
+//! accounts, so that the storage tries go with the accounts that have them,
+//! and it keeps the code of the accounts too, see `historical_state`.
+//!
+//! The store can be saved to a file and loaded again, with `std`, so a trie
+//! can be reopened at any root it kept in another run.
+//!
+
+use crate::prelude::*;
+
+#[cfg(feature = "std")]
+use std::{fs, path::Path};
+
+use super::{bytes_to_nibble_list, compact_to_nibble_list, partial::PartialTrie, verify_trie_proof, EMPTY_TRIE_ROOT};
+use crate::ethereum::{
+    base_types::Bytes,
+    crypto::hash::Hash32,
+    exceptions::EthereumException,
+    frontier::fork_types::{keccak256, Root},
+    rlp::{self, Simple},
+    utils::ensure::ensure,
+};
+
+/// A node in a [NodeStore], and how many references to it there are.
+#[derive(Clone, Debug)]
+struct StoredNode {
+    node: Bytes,
+    references: u32,
+}
+
+///
+///     Serialized trie nodes by their hash, each kept for as long as it is
+///     referenced.
+///
+#[derive(Clone, Debug, Default)]
+pub struct NodeStore {
+    nodes: HashMap<Hash32, StoredNode>,
+    /// Whether the store holds states, whose account leaves reference
+    /// their storage roots.
+    state: bool,
+    /// The code of the accounts of the states, by its hash. Code is never
+    /// dropped.
+    codes: HashMap<Hash32, Bytes>,
+}
+
+/// The storage root an account leaf holds, unless it is that of the empty
+/// trie. Storage values are byte strings, so only accounts are sequences.
+fn storage_root(value: &[u8]) -> Option<Hash32> {
+    let Ok(Simple::Sequence(fields)) = rlp::decode(value) else {
+        return None;
+    };
+    match &fields[..] {
+        [_, _, Simple::Bytes(root), _] => Hash32::try_from(&root[..])
+            .ok()
+            .filter(|root| *root != EMPTY_TRIE_ROOT()),
+        _ => None,
+    }
+}
+
+/// The next `length` bytes of a saved store.
+#[cfg(feature = "std")]
+fn take<'a>(rest: &mut &'a [u8], length: usize) -> Result<&'a [u8], EthereumException> {
+    if rest.len() < length {
+        return Err(EthereumException::ValueError);
+    }
+    let (taken, after) = rest.split_at(length);
+    *rest = after;
+    Ok(taken)
+}
+
+#[cfg(feature = "std")]
+fn take_u32(rest: &mut &[u8]) -> Result<u32, EthereumException> {
+    Ok(u32::from_be_bytes(take(rest, 4)?.try_into().unwrap()))
+}
+
+impl NodeStore {
+    ///
+    ///     A store for the tries of states, whose account leaves keep the
+    ///     storage tries of their accounts.
+    ///
+    pub fn for_state() -> Self {
+        Self {
+            state: true,
+            ..Self::default()
+        }
+    }
+
+    /// The hashes of the nodes a node references by their hash. Embedded
+    /// nodes are too short to hold a hash, so only those of the node itself
+    /// count, and the storage root of an account leaf in a state store.
+    fn children(&self, node: &[u8]) -> Result<Vec<Hash32>, EthereumException> {
+        let Simple::Sequence(mut items) = rlp::decode(node)? else {
+            return Err(EthereumException::ValueError);
+        };
+        if items.len() == 2 {
+            let Simple::Bytes(compact) = &items[0] else {
+                return Err(EthereumException::ValueError);
+            };
+            if compact_to_nibble_list(compact)?.1 {
+                return Ok(match &items[1] {
+                    Simple::Bytes(value) if self.state => storage_root(value).into_iter().collect(),
+                    _ => Vec::new(),
+                });
+            }
+            items.remove(0);
+        } else {
+            items.truncate(16);
+        }
+        Ok(items
+            .iter()
+            .filter_map(|item| match item {
+                Simple::Bytes(hash) => Hash32::try_from(&hash[..]).ok(),
+                Simple::Sequence(_) => None,
+            })
+            .collect())
+    }
+
+    ///
+    ///     The serialized node with a hash, if the store has it.
+    ///
+    pub fn get(&self, hash: &Hash32) -> Option<&[u8]> {
+        self.nodes.get(hash).map(|stored| &stored.node[..])
+    }
+
+    ///
+    ///     How many references there are to a node, zero if the store
+    ///     doesn't have it.
+    ///
+    pub fn references(&self, hash: &Hash32) -> u32 {
+        self.nodes.get(hash).map_or(0, |stored| stored.references)
+    }
+
+    /// The number of nodes in the store.
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Whether the store has no nodes.
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// The code with a hash, if the store has it.
+    pub fn code(&self, code_hash: &Hash32) -> Option<&[u8]> {
+        self.codes.get(code_hash).map(|code| &code[..])
+    }
+
+    /// Add the code of an account, returning its hash.
+    pub fn insert_code(&mut self, code: &[u8]) -> Hash32 {
+        let code_hash = keccak256(code);
+        self.codes.entry(code_hash).or_insert_with(|| code.into());
+        code_hash
+    }
+
+    ///
+    ///     Add a node, which references the nodes it has the hash of. A
+    ///     node the store has already is left as it is.
+    ///
+    ///     Parameters
+    ///     ----------
+    ///     node :
+    ///         The serialized node.
+    ///
+    ///     Returns
+    ///     -------
+    ///     hash : `Hash32`
+    ///         The hash of the node.
+    ///
+    ///     Raises
+    ///     ------
+    ///     ValueError :
+    ///         If the node is malformed, or references a node the store
+    ///         doesn't have.
+    ///
+    pub fn insert(&mut self, node: &[u8]) -> Result<Hash32, EthereumException> {
+        let hash = keccak256(node);
+        if self.nodes.contains_key(&hash) {
+            return Ok(hash);
+        }
+        let children = self.children(node)?;
+        ensure(
+            children.iter().all(|child| self.nodes.contains_key(child)),
+            EthereumException::ValueError,
+        )?;
+        for child in &children {
+            self.reference(child);
+        }
+        self.nodes.insert(
+            hash,
+            StoredNode {
+                node: node.into(),
+                references: 0,
+            },
+        );
+        Ok(hash)
+    }
+
+    ///
+    ///     Add a reference to a node the store has, such as the root of a
+    ///     trie to keep.
+    ///
+    pub fn reference(&mut self, hash: &Hash32) {
+        if let Some(stored) = self.nodes.get_mut(hash) {
+            stored.references += 1;
+        }
+    }
+
+    ///
+    ///     Drop a reference to a node. A node left without references is
+    ///     removed, dropping its references to its children in turn.
+    ///
+    pub fn dereference(&mut self, hash: &Hash32) {
+        let mut released = vec![*hash];
+        while let Some(hash) = released.pop() {
+            let Some(stored) = self.nodes.get_mut(&hash) else {
+                continue;
+            };
+            stored.references = stored.references.saturating_sub(1);
+            if stored.references == 0 {
+                let stored = self.nodes.remove(&hash).expect("the node is in the store");
+                released.extend(self.children(&stored.node).unwrap_or_default());
+            }
+        }
+    }
+
+    ///
+    ///     The nodes, each referenced by its hash, that `path` goes through
+    ///     from `root`, as far as the trie goes along it.
+    ///
+    ///     Parameters
+    ///     ----------
+    ///     root :
+    ///         Root of the trie.
+    ///     path :
+    ///         The path, in nibbles.
+    ///
+    ///     Raises
+    ///     ------
+    ///     ValueError :
+    ///         If the store doesn't have one of the nodes, as when its root
+    ///         was pruned, or one is malformed.
+    ///
+    pub fn walk(&self, root: &Root, path: &[u8]) -> Result<Vec<&[u8]>, EthereumException> {
+        let mut nodes = Vec::new();
+        if *root == EMPTY_TRIE_ROOT() {
+            return Ok(nodes);
+        }
+        let mut level = 0;
+        let mut reference = Simple::Bytes(Box::from(&root[..]));
+        loop {
+            let node = match reference {
+                Simple::Bytes(hash) if hash.is_empty() => return Ok(nodes),
+                Simple::Bytes(hash) => {
+                    let hash = Hash32::try_from(&hash[..]).map_err(|_| EthereumException::ValueError)?;
+                    let node = self.get(&hash).ok_or(EthereumException::ValueError)?;
+                    nodes.push(node);
+                    rlp::decode(node)?
+                }
+                embedded => embedded,
+            };
+            let Simple::Sequence(mut items) = node else {
+                return Err(EthereumException::ValueError);
+            };
+            match items.len() {
+                17 => {
+                    let Some(nibble) = path.get(level) else {
+                        return Ok(nodes);
+                    };
+                    reference = items.swap_remove(*nibble as usize);
+                    level += 1;
+                }
+                2 => {
+                    let Simple::Bytes(compact) = &items[0] else {
+                        return Err(EthereumException::ValueError);
+                    };
+                    let (segment, is_leaf) = compact_to_nibble_list(compact)?;
+                    if is_leaf || path.get(level..level + segment.len()) != Some(&segment[..]) {
+                        return Ok(nodes);
+                    }
+                    level += segment.len();
+                    reference = items.swap_remove(1);
+                }
+                _ => return Err(EthereumException::ValueError),
+            }
+        }
+    }
+
+    ///
+    ///     Read the value of a key in the trie of a root.
+    ///
+    ///     Parameters
+    ///     ----------
+    ///     root :
+    ///         Root of the trie.
+    ///     key :
+    ///         The key, hashed already if the trie is secured.
+    ///
+    ///     Returns
+    ///     -------
+    ///     value : `Option<Bytes>`
+    ///         The encoded value, or `None` if the key isn't in the trie.
+    ///
+    ///     Raises
+    ///     ------
+    ///     ValueError :
+    ///         If the store doesn't have the nodes down to the key.
+    ///
+    pub fn get_value(&self, root: &Root, key: &[u8]) -> Result<Option<Bytes>, EthereumException> {
+        let proof: Vec<Bytes> = self
+            .walk(root, &bytes_to_nibble_list(key))?
+            .into_iter()
+            .map(Bytes::from)
+            .collect();
+        verify_trie_proof(root, key, &proof)
+    }
+
+    ///
+    ///     Make a new version of a trie, and keep its root. The version of
+    ///     `root` is left as it is.
+    ///
+    ///     Parameters
+    ///     ----------
+    ///     root :
+    ///         Root of the version to change.
+    ///     changes :
+    ///         The keys to set, hashed already if the trie is secured, and
+    ///         their encoded values, empty to delete them.
+    ///
+    ///     Returns
+    ///     -------
+    ///     root : `Root`
+    ///         The root of the new version, referenced once more.
+    ///
+    ///     Raises
+    ///     ------
+    ///     ValueError :
+    ///         If the store doesn't have the nodes of the version of `root`,
+    ///         or those a value references.
+    ///
+    pub fn update(
+        &mut self,
+        root: &Root,
+        changes: impl IntoIterator<Item = (Bytes, Bytes)>,
+    ) -> Result<Root, EthereumException> {
+        let mut changes: Vec<(Bytes, Bytes)> = changes.into_iter().collect();
+        // Deletions go last, so that a branch they leave with one child may
+        // have gained others.
+        changes.sort_by_key(|(_, value)| value.is_empty());
+
+        let trie = {
+            let mut nodes = HashMap::new();
+            for (key, _) in &changes {
+                for node in self.walk(root, &bytes_to_nibble_list(key))? {
+                    nodes.insert(keccak256(node), node);
+                }
+            }
+            // Opening only the paths of the keys, a deletion may need the node
+            // of a sibling to merge with.
+            loop {
+                let mut trie = PartialTrie::new(root, &nodes)?;
+                let Err(missing) = changes.iter().try_for_each(|(key, value)| trie.set(key, value.clone())) else {
+                    break trie;
+                };
+                let known = nodes.len();
+                for node in self.walk(root, &missing.path)? {
+                    nodes.insert(keccak256(node), node);
+                }
+                ensure(nodes.len() > known, EthereumException::ValueError)?;
+            }
+        };
+
+        for node in trie.nodes() {
+            self.insert(&node)?;
+        }
+        let root = trie.root();
+        self.reference(&root);
+        Ok(root)
+    }
+
+    ///
+    ///     Write the store to a file, replacing it at once: whether it
+    ///     holds states, as a byte, the number of nodes, then each node with
+    ///     its references and its length before it, and each code with its
+    ///     length before it, all as big endian `u32`.
+    ///
+    ///     Raises
+    ///     ------
+    ///     FileNotFound :
+    ///         If the file can't be written.
+    ///
+    #[cfg(feature = "std")]
+    pub fn save(&self, path: &Path) -> Result<(), EthereumException> {
+        let io_error = |error: std::io::Error| EthereumException::FileNotFound(format!("{}: {error}", path.display()));
+        let mut hashes: Vec<&Hash32> = self.nodes.keys().collect();
+        hashes.sort_unstable();
+        let mut encoded = vec![u8::from(self.state)];
+        encoded.extend((hashes.len() as u32).to_be_bytes());
+        for hash in hashes {
+            let stored = &self.nodes[hash];
+            encoded.extend(stored.references.to_be_bytes());
+            encoded.extend((stored.node.len() as u32).to_be_bytes());
+            encoded.extend(&stored.node[..]);
+        }
+        let mut code_hashes: Vec<&Hash32> = self.codes.keys().collect();
+        code_hashes.sort_unstable();
+        for code_hash in code_hashes {
+            let code = &self.codes[code_hash];
+            encoded.extend((code.len() as u32).to_be_bytes());
+            encoded.extend(&code[..]);
+        }
+        let temporary = path.with_extension("tmp");
+        fs::write(&temporary, encoded).map_err(io_error)?;
+        fs::rename(&temporary, path).map_err(io_error)
+    }
+
+    ///
+    ///     Read a store written by [NodeStore::save].
+    ///
+    ///     Raises
+    ///     ------
+    ///     FileNotFound :
+    ///         If the file can't be read.
+    ///     ValueError :
+    ///         If it is cut short.
+    ///
+    #[cfg(feature = "std")]
+    pub fn load(path: &Path) -> Result<Self, EthereumException> {
+        let encoded =
+            fs::read(path).map_err(|error| EthereumException::FileNotFound(format!("{}: {error}", path.display())))?;
+        let mut rest = &encoded[..];
+        let mut store = Self {
+            state: take(&mut rest, 1)?[0] != 0,
+            ..Self::default()
+        };
+        for _ in 0..take_u32(&mut rest)? {
+            let references = take_u32(&mut rest)?;
+            let length = take_u32(&mut rest)?;
+            let node = take(&mut rest, length as usize)?;
+            store.nodes.insert(
+                keccak256(node),
+                StoredNode {
+                    node: node.into(),
+                    references,
+                },
+            );
+        }
+        while !rest.is_empty() {
+            let length = take_u32(&mut rest)?;
+            store.insert_code(take(&mut rest, length as usize)?);
+        }
+        Ok(store)
+    }
+}
+
+///
+///     A trie whose versions are kept in a [NodeStore].
+///
+#[derive(Clone, Debug)]
+pub struct PersistentTrie {
+    store: NodeStore,
+    root: Root,
+}
+
+impl Default for PersistentTrie {
+    fn default() -> Self {
+        Self {
+            store: NodeStore::default(),
+            root: EMPTY_TRIE_ROOT(),
+        }
+    }
+}
+
+impl PersistentTrie {
+    ///
+    ///     Open the trie of a root kept in a store.
+    ///
+    ///     Raises
+    ///     ------
+    ///     ValueError :
+    ///         If the store doesn't have the root.
+    ///
+    pub fn open(store: NodeStore, root: Root) -> Result<Self, EthereumException> {
+        ensure(
+            root == EMPTY_TRIE_ROOT() || store.get(&root).is_some(),
+            EthereumException::ValueError,
+        )?;
+        Ok(Self { store, root })
+    }
+
+    /// The root of the current version.
+    pub fn root(&self) -> Root {
+        self.root
+    }
+
+    /// The store the versions are kept in.
+    pub fn store(&self) -> &NodeStore {
+        &self.store
+    }
+
+    /// Close the trie, keeping its store, to save it or reopen it at
+    /// another root.
+    pub fn into_store(self) -> NodeStore {
+        self.store
+    }
+
+    ///
+    ///     Read the value of a key in the current version.
+    ///
+    pub fn get(&self, key: &[u8]) -> Result<Option<Bytes>, EthereumException> {
+        self.store.get_value(&self.root, key)
+    }
+
+    ///
+    ///     Read the value of a key in the version of a root, which must not
+    ///     have been pruned.
+    ///
+    pub fn get_at(&self, root: &Root, key: &[u8]) -> Result<Option<Bytes>, EthereumException> {
+        self.store.get_value(root, key)
+    }
+
+    ///
+    ///     Make a new version of the trie, which becomes the current one.
+    ///     The previous version is kept until it is pruned.
+    ///
+    ///     Parameters
+    ///     ----------
+    ///     changes :
+    ///         The keys to set, hashed already if the trie is secured, and
+    ///         their encoded values, empty to delete them.
+    ///
+    ///     Returns
+    ///     -------
+    ///     root : `Root`
+    ///         The root of the new version.
+    ///
+    ///     Raises
+    ///     ------
+    ///     ValueError :
+    ///         If the store doesn't have the nodes of the current version.
+    ///
+    pub fn update(&mut self, changes: impl IntoIterator<Item = (Bytes, Bytes)>) -> Result<Root, EthereumException> {
+        self.root = self.store.update(&self.root, changes)?;
+        Ok(self.root)
+    }
+
+    ///
+    ///     Let go of a version, dropping the nodes no version kept
+    ///     references any more. A root kept by several updates is pruned
+    ///     once for each.
+    ///
+    pub fn prune(&mut self, root: &Root) {
+        self.store.dereference(root);
+    }
+}
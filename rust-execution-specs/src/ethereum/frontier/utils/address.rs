@@ -0,0 +1,53 @@
+//!
+//! # Hardfork Utility Functions For Addresses
+//!
+//! ## Introduction
+//!
+//! Address specific functions used in this frontier version of
+//! specification.
+//!
+
+use crate::ethereum::{
+    base_types::{u256_to_be_bytes32, Uint, U256},
+    frontier::fork_types::{keccak256, Address},
+    rlp,
+};
+
+///
+///     Convert a Uint or U256 value to a valid address (20 bytes).
+///
+///     Parameters
+///     ----------
+///     data :
+///         The string to be converted to bytes.
+///
+///     Returns
+///     -------
+///     address : `Address`
+///         The obtained address.
+///
+pub fn to_address(data: &U256) -> Address {
+    let bytes = u256_to_be_bytes32(data);
+    Address::try_from(&bytes[12..]).unwrap()
+}
+
+///
+///     Computes address of the new account that needs to be created.
+///
+///     Parameters
+///     ----------
+///     address :
+///         The address of the account that wants to create the new account.
+///     nonce :
+///         The transaction count of the account that wants to create the new
+///         account.
+///
+///     Returns
+///     -------
+///     address: `ethereum.frontier.fork_types.Address`
+///         The computed address of the new account.
+///
+pub fn compute_contract_address(address: &Address, nonce: &Uint) -> Address {
+    let computed_address = keccak256(&rlp::encode(&(address, nonce)));
+    Address::try_from(&computed_address[12..]).unwrap()
+}
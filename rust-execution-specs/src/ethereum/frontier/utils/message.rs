@@ -0,0 +1,82 @@
+//!
+//! # Hardfork Utility Functions For The Message Data-structure
+//!
+//! ## Introduction
+//!
+//! Message specific functions used in this frontier version of
+//! specification.
+//!
+
+use crate::ethereum::{
+    base_types::{Bytes, Uint, U256},
+    frontier::{
+        fork_types::Address,
+        state::get_account,
+        vm::{Environment, Message},
+    },
+};
+
+use super::address::compute_contract_address;
+
+///
+///     Execute a transaction against the provided environment.
+///
+///     Parameters
+///     ----------
+///     caller :
+///         Address which initiated the transaction
+///     target :
+///         Address whose code will be executed, or `None` to create a
+///         contract.
+///     value :
+///         Value to be transferred.
+///     data :
+///         Array of bytes provided to the code in `target`.
+///     gas :
+///         Gas provided for the code in `target`.
+///     env :
+///         Environment for the Ethereum Virtual Machine.
+///     code_address :
+///         This is usually same as the `target` address except when an alternative
+///         accounts code needs to be executed.
+///         eg. `CALLCODE` calling a precompile.
+///
+///     Returns
+///     -------
+///     message: `ethereum.frontier.vm.Message`
+///         Items containing contract creation or message call specific data.
+///
+pub fn prepare_message(
+    caller: Address,
+    target: Option<Address>,
+    value: U256,
+    data: Bytes,
+    gas: U256,
+    env: &Environment,
+    code_address: Option<Address>,
+) -> Message {
+    let (current_target, msg_data, code, code_address) = match target {
+        None => {
+            // The sender's nonce has already been incremented for this
+            // transaction.
+            let nonce = get_account(&env.state, &caller).nonce - Uint::from(1u8);
+            (compute_contract_address(&caller, &nonce), Bytes::default(), data, code_address)
+        }
+        Some(target) => {
+            let code = Bytes::from(&get_account(&env.state, &target).code[..]);
+            (target, data, code, code_address.or(Some(target)))
+        }
+    };
+
+    Message {
+        caller,
+        target,
+        current_target,
+        gas,
+        value,
+        data: msg_data,
+        code_address,
+        code,
+        depth: Uint::from(0u8),
+    }
+}
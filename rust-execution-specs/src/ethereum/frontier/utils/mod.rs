@@ -0,0 +1,10 @@
+//!
+//! # Frontier Utility Functions
+//!
+//! ## Introduction
+//!
+//! Utility functions unique to this particular fork.
+//!
+
+pub mod address;
+pub mod message;
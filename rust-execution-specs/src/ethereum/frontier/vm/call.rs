@@ -0,0 +1,235 @@
+//! Message Call Builder
+//! ^^^^^^^^^^^^^^^^^^^^
+//!
+//! .. contents:: Table of Contents
+//!     :backlinks: none
+//!     :local:
+//!
+//! Introduction
+//! ------------
+//!
+//! Runs a single message against a `State`, without a surrounding block or
+//! transaction, for embedding the specification as a reference EVM:
+//!
+//! ```
+//! # use execution_specs_rs::ethereum::frontier::{state::State, vm::Evm};
+//! let mut state = State::default();
+//! let result = Evm::call()
+//!     .to([0x10; 20])
+//!     .gas(100_000u32.into())
+//!     .run(&mut state)
+//!     .unwrap();
+//! assert!(result.error.is_none());
+//! ```
+//!
+//! No intrinsic gas is charged and no fees are paid or refunded. Frontier has
+//! no access lists, so there is nothing to warm up before the call.
+
+use crate::prelude::*;
+
+use super::{
+    exceptions::EvmError, interpreter::process_message_call, BlockEnvironment, Environment, Evm,
+    TxEnvironment,
+};
+use crate::ethereum::{
+    base_types::{Bytes, Uint, U256},
+    exceptions::EthereumException,
+    frontier::{
+        fork_types::{Address, Log},
+        state::{
+            begin_transaction, commit_transaction, get_account, increment_nonce,
+            transaction_diff, State, StateDiff,
+        },
+        utils::message::prepare_message,
+    },
+    trace::Tracer,
+    utils::ensure::ensure,
+};
+
+/// Builds a message call, see [Evm::call].
+pub struct CallBuilder {
+    block: BlockEnvironment,
+    tx: TxEnvironment,
+    caller: Option<Address>,
+    target: Option<Address>,
+    value: U256,
+    data: Bytes,
+    gas: Uint,
+    depth: Uint,
+    tracer: Option<Box<dyn Tracer<Evm>>>,
+}
+
+/// The outcome of a message call built with [Evm::call].
+#[derive(Debug)]
+pub struct CallResult {
+    /// The return data, or the deployed code of a created contract.
+    pub output: Bytes,
+    pub logs: Vec<Log>,
+    pub gas_used: Uint,
+    pub gas_left: Uint,
+    pub refund_counter: U256,
+    /// Contracts which have self-destructed. They are not removed from the
+    /// state; that happens at the end of a transaction.
+    pub accounts_to_delete: HashSet<Address>,
+    pub error: Option<EvmError>,
+    /// The address of the contract, when creating one.
+    pub created_address: Option<Address>,
+    /// Every account changed by the call.
+    pub state_diff: StateDiff,
+}
+
+impl Evm {
+    /// Start building a message call. By default it is a call from and to
+    /// the zero address, with no value or data, ten billion gas and an
+    /// all-zero block with a gas limit of ten billion.
+    pub fn call() -> CallBuilder {
+        CallBuilder {
+            block: BlockEnvironment {
+                gas_limit: Uint::from(10_000_000_000u64),
+                ..Default::default()
+            },
+            tx: TxEnvironment::default(),
+            caller: None,
+            target: Some(Address::default()),
+            value: U256::from(0u8),
+            data: Bytes::default(),
+            gas: Uint::from(10_000_000_000u64),
+            depth: Uint::from(0u8),
+            tracer: None,
+        }
+    }
+}
+
+impl CallBuilder {
+    /// The account that signed the transaction, as seen by `ORIGIN`. It is
+    /// also the caller unless [CallBuilder::caller] is given.
+    pub fn origin(mut self, origin: Address) -> Self {
+        self.tx.origin = origin;
+        self
+    }
+
+    /// The account the message is sent from, as seen by `CALLER`.
+    pub fn caller(mut self, caller: Address) -> Self {
+        self.caller = Some(caller);
+        self
+    }
+
+    /// Call the code at `target`.
+    pub fn to(mut self, target: Address) -> Self {
+        self.target = Some(target);
+        self
+    }
+
+    /// Create a contract, running the data as init code. The nonce of the
+    /// caller is incremented first, as a transaction would.
+    pub fn create(mut self) -> Self {
+        self.target = None;
+        self
+    }
+
+    pub fn value(mut self, value: U256) -> Self {
+        self.value = value;
+        self
+    }
+
+    /// The call data, or the init code when creating.
+    pub fn data(mut self, data: Bytes) -> Self {
+        self.data = data;
+        self
+    }
+
+    pub fn gas(mut self, gas: Uint) -> Self {
+        self.gas = gas;
+        self
+    }
+
+    pub fn gas_price(mut self, gas_price: U256) -> Self {
+        self.tx.gas_price = gas_price;
+        self
+    }
+
+    /// The depth the message runs at, as if it had been sent by that many
+    /// nested frames. Calls and creates fail once it would go over
+    /// `STACK_DEPTH_LIMIT`.
+    pub fn depth(mut self, depth: Uint) -> Self {
+        self.depth = depth;
+        self
+    }
+
+    /// The block the call runs in.
+    pub fn block(mut self, block: BlockEnvironment) -> Self {
+        self.block = block;
+        self
+    }
+
+    pub fn tracer(mut self, tracer: Box<dyn Tracer<Evm>>) -> Self {
+        self.tracer = Some(tracer);
+        self
+    }
+
+    ///
+    ///     Run the message against `state`.
+    ///
+    ///     Parameters
+    ///     ----------
+    ///     state :
+    ///         The state to run against. It is updated in place.
+    ///
+    ///     Returns
+    ///     -------
+    ///     result : `CallResult`
+    ///         The output, logs, gas and state changes of the call. Raises
+    ///         `InvalidTransaction` if the caller can't afford the value.
+    ///
+    pub fn run(self, state: &mut State) -> Result<CallResult, EthereumException> {
+        let caller = self.caller.unwrap_or(self.tx.origin);
+        ensure(
+            get_account(state, &caller).balance >= self.value,
+            EthereumException::InvalidTransaction,
+        )?;
+
+        let mut env = Environment {
+            block: self.block,
+            tx: self.tx,
+            state: core::mem::take(state),
+            tracer: self.tracer,
+        };
+
+        // The outer transaction records the state before the call, for the
+        // diff.
+        begin_transaction(&mut env.state);
+        if self.target.is_none() {
+            increment_nonce(&mut env.state, caller);
+        }
+
+        let mut message = prepare_message(
+            caller,
+            self.target,
+            self.value,
+            self.data,
+            self.gas.clone(),
+            &env,
+            None,
+        );
+        message.depth = self.depth;
+        let created_address = self.target.is_none().then_some(message.current_target);
+        let output = process_message_call(message, &mut env);
+        let created_address = created_address.filter(|_| output.error.is_none());
+
+        let state_diff = transaction_diff(&env.state);
+        commit_transaction(&mut env.state);
+        *state = env.state;
+
+        Ok(CallResult {
+            output: output.return_data,
+            logs: output.logs,
+            gas_used: self.gas - &output.gas_left,
+            gas_left: output.gas_left,
+            refund_counter: output.refund_counter,
+            accounts_to_delete: output.accounts_to_delete,
+            error: output.error,
+            created_address,
+            state_diff,
+        })
+    }
+}
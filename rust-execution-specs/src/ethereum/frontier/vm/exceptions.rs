@@ -10,9 +10,9 @@
 //!
 //! Exceptions which cause the EVM to halt exceptionally.
 
-pub type Result<T, E = EvmError> = std::result::Result<T, E>;
+pub type Result<T, E = EvmError> = core::result::Result<T, E>;
 
-#[derive(Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum EvmError {
     /// Indicates that the EVM has experienced an exceptional halt. This causes
     /// execution to immediately end with all gas being consumed.
@@ -36,4 +36,14 @@ pub enum EvmError {
     InvalidJumpDest,
     /// Raised when the message depth is greater than `1024`
     StackDepthLimit,
+    /// Raised when the new contract address has a collision.
+    AddressCollision,
 }
+
+impl core::fmt::Display for EvmError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        core::fmt::Debug::fmt(self, f)
+    }
+}
+
+impl core::error::Error for EvmError {}
@@ -10,65 +10,58 @@
 //!
 //! EVM gas constants and calculators.
 
+use crate::prelude::*;
 use super::{
     super::state::{self, State},
+    evm_trace,
     exceptions::{EvmError, Result},
     Evm,
 };
-use crate::ethereum::base_types::U256;
+use crate::ethereum::{base_types::U256, trace::TraceEvent};
 use crate::ethereum::frontier::fork_types::Address;
 use crate::ethereum::{base_types::Uint, utils::numeric::ceil32};
 use num_traits::CheckedSub;
 
-macro_rules! gas {
-    ($($name:ident = $num:literal;)*) => {$(
-        #[allow(non_snake_case)]
-        pub fn $name() -> Uint {
-            let num: u64 = $num;
-            Uint::from(num)
-        }
-    )*};
-}
-
-gas! {
-    GAS_JUMPDEST = 1;
-    GAS_BASE = 2;
-    GAS_VERY_LOW = 3;
-    GAS_SLOAD = 50;
-    GAS_STORAGE_SET = 20000;
-    GAS_STORAGE_UPDATE = 5000;
-    GAS_STORAGE_CLEAR_REFUND = 15000;
-    GAS_LOW = 5;
-    GAS_MID = 8;
-    GAS_HIGH = 10;
-    GAS_EXPONENTIATION = 10;
-    GAS_EXPONENTIATION_PER_BYTE = 10;
-    GAS_MEMORY = 3;
-    GAS_KECCAK256 = 30;
-    GAS_KECCAK256_WORD = 6;
-    GAS_COPY = 3;
-    GAS_BLOCK_HASH = 20;
-    GAS_EXTERNAL = 20;
-    GAS_BALANCE = 20;
-    GAS_LOG = 375;
-    GAS_LOG_DATA = 8;
-    GAS_LOG_TOPIC = 375;
-    GAS_CREATE = 32000;
-    GAS_CODE_DEPOSIT = 200;
-    GAS_ZERO = 0;
-    GAS_CALL = 40;
-    GAS_NEW_ACCOUNT = 25000;
-    GAS_CALL_VALUE = 9000;
-    GAS_CALL_STIPEND = 2300;
-    REFUND_SELF_DESTRUCT = 24000;
-    GAS_ECRECOVER = 3000;
-    GAS_SHA256 = 60;
-    GAS_SHA256_WORD = 12;
-    GAS_RIPEMD160 = 600;
-    GAS_RIPEMD160_WORD = 120;
-    GAS_IDENTITY = 15;
-    GAS_IDENTITY_WORD = 3;
-}
+pub use crate::ethereum::frontier::constants::{
+    GAS_JUMPDEST,
+    GAS_BASE,
+    GAS_VERY_LOW,
+    GAS_SLOAD,
+    GAS_STORAGE_SET,
+    GAS_STORAGE_UPDATE,
+    GAS_STORAGE_CLEAR_REFUND,
+    GAS_LOW,
+    GAS_MID,
+    GAS_HIGH,
+    GAS_EXPONENTIATION,
+    GAS_EXPONENTIATION_PER_BYTE,
+    GAS_MEMORY,
+    GAS_MEMORY_QUADRATIC_DENOMINATOR,
+    GAS_KECCAK256,
+    GAS_KECCAK256_WORD,
+    GAS_COPY,
+    GAS_BLOCK_HASH,
+    GAS_EXTERNAL,
+    GAS_BALANCE,
+    GAS_LOG,
+    GAS_LOG_DATA,
+    GAS_LOG_TOPIC,
+    GAS_CREATE,
+    GAS_CODE_DEPOSIT,
+    GAS_ZERO,
+    GAS_CALL,
+    GAS_NEW_ACCOUNT,
+    GAS_CALL_VALUE,
+    GAS_CALL_STIPEND,
+    REFUND_SELF_DESTRUCT,
+    GAS_ECRECOVER,
+    GAS_SHA256,
+    GAS_SHA256_WORD,
+    GAS_RIPEMD160,
+    GAS_RIPEMD160_WORD,
+    GAS_IDENTITY,
+    GAS_IDENTITY_WORD,
+};
 
 /// Define the parameters for memory extension in opcodes
 ///
@@ -104,6 +97,7 @@ pub struct MessageCallGas {
 ///     The amount of gas the current operation requires.
 ///
 pub fn charge_gas(evm: &mut Evm, amount: Uint) -> Result<()> {
+    evm_trace(evm, TraceEvent::GasAndRefund { gas_cost: amount.clone() });
     evm.gas_left = evm
         .gas_left
         .checked_sub(&amount)
@@ -127,7 +121,7 @@ pub fn charge_gas(evm: &mut Evm, amount: Uint) -> Result<()> {
 pub fn calculate_memory_gas_cost(size_in_bytes: Uint) -> Uint {
     let size_in_words = ceil32(size_in_bytes) / Uint::from(32u8);
     let linear_cost = &size_in_words * GAS_MEMORY();
-    let quadratic_cost = size_in_words.pow(2) / Uint::from(512u16);
+    let quadratic_cost = size_in_words.pow(2) / GAS_MEMORY_QUADRATIC_DENOMINATOR();
     let total_gas_cost = linear_cost + quadratic_cost;
     total_gas_cost
 }
@@ -2,7 +2,9 @@ use super::super::{
     exceptions::{Result},
     gas, stack, Evm,
 };
-use crate::ethereum::base_types::U256;
+use crate::ethereum::base_types::{
+    u256_from_signed, u256_to_be_bytes32, u256_to_signed, U255_CEIL_VALUE, U256, U256Ext, U256_CEIL_VALUE,
+};
 use crate::ethereum::utils::numeric::get_sign;
 use num_bigint::BigInt;
 use num_traits::Signed;
@@ -23,8 +25,7 @@ pub fn add(evm: &mut Evm) -> Result<()> {
     gas::charge_gas(evm, gas::GAS_VERY_LOW())?;
 
     // OPERATION
-    // fixme: wrapping_add
-    let result = x + y;
+    let result = x.wrapping_add(&y);
 
     stack::push(&mut evm.stack, result)?;
 
@@ -49,8 +50,7 @@ pub fn sub(evm: &mut Evm) -> Result<()> {
     gas::charge_gas(evm, gas::GAS_VERY_LOW())?;
 
     // OPERATION
-    // fixme: wrapping_sub
-    let result = x - y;
+    let result = x.wrapping_sub(&y);
 
     stack::push(&mut evm.stack, result)?;
 
@@ -75,8 +75,7 @@ pub fn mul(evm: &mut Evm) -> Result<()> {
     gas::charge_gas(evm, gas::GAS_LOW())?;
 
     // OPERATION
-    // fixme: wrapping_mul
-    let result = x * y;
+    let result = x.wrapping_mul(&y);
 
     stack::push(&mut evm.stack, result)?;
 
@@ -123,9 +122,8 @@ pub fn div(evm: &mut Evm) -> Result<()> {
 ///     The current EVM frame.
 pub fn sdiv(evm: &mut Evm) -> Result<()> {
     // STACK
-    // fixme: will not do sign conversion
-    let dividend = BigInt::from(stack::pop(&mut evm.stack)?);
-    let divisor = BigInt::from(stack::pop(&mut evm.stack)?);
+    let dividend = u256_to_signed(&stack::pop(&mut evm.stack)?);
+    let divisor = u256_to_signed(&stack::pop(&mut evm.stack)?);
 
     // GAS
     gas::charge_gas(evm, gas::GAS_LOW())?;
@@ -133,14 +131,14 @@ pub fn sdiv(evm: &mut Evm) -> Result<()> {
     // OPERATION
     let quotient = if divisor == BigInt::from(0u8) {
         BigInt::from(0u8)
-    } else if dividend == -BigInt::from(2u8).pow(255) && divisor == BigInt::from(-1) {
-        -BigInt::from(2u8).pow(255)
+    } else if dividend == -BigInt::from(U255_CEIL_VALUE()) && divisor == BigInt::from(-1) {
+        -BigInt::from(U255_CEIL_VALUE())
     } else {
         let sign = get_sign(&dividend * &divisor);
         sign * (dividend.abs() / divisor.abs())
     };
 
-    stack::push(&mut evm.stack, U256::new(quotient.to_u32_digits().1))?;
+    stack::push(&mut evm.stack, u256_from_signed(quotient))?;
 
     // PROGRAM COUNTER
     evm.pc += 1;
@@ -185,9 +183,8 @@ pub fn r#mod(evm: &mut Evm) -> Result<()> {
 ///     The current EVM frame.
 pub fn smod(evm: &mut Evm) -> Result<()> {
     // STACK
-    // fixme: does not do sign conversion
-    let x = BigInt::from(stack::pop(&mut evm.stack)?);
-    let y = BigInt::from(stack::pop(&mut evm.stack)?);
+    let x = u256_to_signed(&stack::pop(&mut evm.stack)?);
+    let y = u256_to_signed(&stack::pop(&mut evm.stack)?);
 
     // GAS
     gas::charge_gas(evm, gas::GAS_LOW())?;
@@ -199,7 +196,7 @@ pub fn smod(evm: &mut Evm) -> Result<()> {
         get_sign(x.clone()) * (x.abs() % y.abs())
     };
 
-    stack::push(&mut evm.stack, U256::new(remainder.to_u32_digits().1))?;
+    stack::push(&mut evm.stack, u256_from_signed(remainder))?;
 
     // PROGRAM COUNTER
     evm.pc += 1;
@@ -289,8 +286,7 @@ pub fn exp(evm: &mut Evm) -> Result<()> {
     )?;
 
     // OPERATION
-    // fixme: ceil
-    let result = base.pow(u32::try_from(exponent).unwrap());
+    let result = base.modpow(&exponent, &U256_CEIL_VALUE());
 
     stack::push(&mut evm.stack, result)?;
 
@@ -319,8 +315,7 @@ pub fn signextend(evm: &mut Evm) -> Result<()> {
         // Can't extend any further
         value
     } else {
-        // U256(0).to_be_bytes() gives b'' instead b'\x00'. (fixme: how does to_bytes_be handle 0)
-        let value_bytes = value.to_bytes_be();
+        let value_bytes = u256_to_be_bytes32(&value);
         // Now among the obtained value bytes, consider only
         // N `least significant bytes`, where N is `byte_num + 1`.
         let value_bytes = &value_bytes[(31 - usize::try_from(byte_num.clone()).unwrap())..];
@@ -5,7 +5,7 @@
 //! Implementations of the EVM bitwise instructions.
 
 use super::super::{exceptions::Result, gas, stack, Evm};
-use crate::ethereum::base_types::U256;
+use crate::ethereum::base_types::{U256, U256_MAX_VALUE};
 
 /// Bitwise AND operation of the top 2 elements of the stack. Pushes the
 /// result back on the stack.
@@ -91,8 +91,7 @@ pub fn bitwise_not(evm: &mut Evm) -> Result<()> {
     gas::charge_gas(evm, gas::GAS_VERY_LOW())?;
 
     // OPERATION
-    let all_set = U256::from_bytes_be(&[0xff].repeat(x.bits() as usize * 8 + 1));
-    let inverse = x ^ all_set;
+    let inverse = x ^ U256_MAX_VALUE();
     stack::push(&mut evm.stack, inverse)?;
 
     // PROGRAM COUNTER
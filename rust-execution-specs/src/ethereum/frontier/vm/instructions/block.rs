@@ -29,14 +29,16 @@ pub fn block_hash(evm: &mut Evm) -> Result<()> {
 
     // OPERATION
     let hash =
-        if evm.env.number <= block_number || evm.env.number > &block_number + U256::from(256u16) {
+        if evm.env.block.number <= block_number || evm.env.block.number > &block_number + U256::from(256u16) {
             U256::from(0u8)
         } else {
-            // fixme: not sure about this
-            // hash = evm.env.block_hashes[-(evm.env.number - block_number)]
-            let hash = evm.env.block_hashes
-                [!usize::try_from(evm.env.number.clone() - block_number).unwrap()];
-            U256::from_bytes_be(&hash)
+            // hash = evm.env.block.block_hashes[-(evm.env.block.number - block_number)]
+            let depth = usize::try_from(evm.env.block.number.clone() - block_number).unwrap();
+            // Hashes older than the start of the chain are not provided.
+            match evm.env.block.block_hashes.len().checked_sub(depth) {
+                Some(index) => U256::from_bytes_be(&evm.env.block.block_hashes[index]),
+                None => U256::from(0u8),
+            }
         };
     stack::push(&mut evm.stack, hash)?;
 
@@ -63,7 +65,7 @@ pub fn coinbase(evm: &mut Evm) -> Result<()> {
     gas::charge_gas(evm, gas::GAS_BASE())?;
 
     // OPERATION
-    stack::push(&mut evm.stack, U256::from_bytes_be(&evm.env.coinbase))?;
+    stack::push(&mut evm.stack, U256::from_bytes_be(&evm.env.block.coinbase))?;
 
     // PROGRAM COUNTER
     evm.pc += 1;
@@ -88,7 +90,7 @@ pub fn timestamp(evm: &mut Evm) -> Result<()> {
     gas::charge_gas(evm, gas::GAS_BASE())?;
 
     // OPERATION
-    stack::push(&mut evm.stack, evm.env.time.clone())?;
+    stack::push(&mut evm.stack, evm.env.block.time.clone())?;
 
     // PROGRAM COUNTER
     evm.pc += 1;
@@ -112,7 +114,7 @@ pub fn number(evm: &mut Evm) -> Result<()> {
     gas::charge_gas(evm, gas::GAS_BASE())?;
 
     // OPERATION
-    stack::push(&mut evm.stack, evm.env.number.clone())?;
+    stack::push(&mut evm.stack, evm.env.block.number.clone())?;
 
     // PROGRAM COUNTER
     evm.pc += 1;
@@ -136,7 +138,7 @@ pub fn difficulty(evm: &mut Evm) -> Result<()> {
     gas::charge_gas(evm, gas::GAS_BASE())?;
 
     // OPERATION
-    stack::push(&mut evm.stack, evm.env.difficulty.clone())?;
+    stack::push(&mut evm.stack, evm.env.block.difficulty.clone())?;
 
     // PROGRAM COUNTER
     evm.pc += 1;
@@ -160,7 +162,7 @@ pub fn gas_limit(evm: &mut Evm) -> Result<()> {
     gas::charge_gas(evm, gas::GAS_BASE())?;
 
     // OPERATION
-    stack::push(&mut evm.stack, evm.env.gas_limit.clone())?;
+    stack::push(&mut evm.stack, evm.env.block.gas_limit.clone())?;
 
     // PROGRAM COUNTER
     evm.pc += 1;
@@ -11,8 +11,7 @@
 //! Implementations of the EVM Comparison instructions.
 
 use super::super::{exceptions::Result, gas, stack, Evm};
-use crate::ethereum::base_types::U256;
-use num_bigint::BigInt;
+use crate::ethereum::base_types::{u256_to_signed, U256};
 
 /// Checks if the top element is less than the next top element. Pushes the
 /// result back on the stack.
@@ -46,9 +45,8 @@ pub fn less_than(evm: &mut Evm) -> Result<()> {
 ///     The current EVM frame.
 pub fn signed_less_than(evm: &mut Evm) -> Result<()> {
     // STACK
-    // fixme: sign conversion is incorrect
-    let left = U256::from(stack::pop(&mut evm.stack)?);
-    let right = U256::from(stack::pop(&mut evm.stack)?);
+    let left = u256_to_signed(&stack::pop(&mut evm.stack)?);
+    let right = u256_to_signed(&stack::pop(&mut evm.stack)?);
 
     // GAS
     gas::charge_gas(evm, gas::GAS_VERY_LOW())?;
@@ -94,9 +92,8 @@ pub fn greater_than(evm: &mut Evm) -> Result<()> {
 ///     The current EVM frame.
 pub fn signed_greater_than(evm: &mut Evm) -> Result<()> {
     // STACK
-    // fixme: sign conversion is incorrect
-    let left = BigInt::from(stack::pop(&mut evm.stack)?);
-    let right = BigInt::from(stack::pop(&mut evm.stack)?);
+    let left = u256_to_signed(&stack::pop(&mut evm.stack)?);
+    let right = u256_to_signed(&stack::pop(&mut evm.stack)?);
 
     // GAS
     gas::charge_gas(evm, gas::GAS_VERY_LOW())?;
@@ -13,6 +13,7 @@
 use super::super::{exceptions::Result, gas, stack, Evm};
 use crate::ethereum::base_types::U256;
 use crate::ethereum::frontier::state::get_account;
+use crate::ethereum::frontier::utils::address::to_address;
 use crate::ethereum::frontier::vm::memory::{buffer_read, memory_write};
 use crate::ethereum::utils::numeric::ceil32;
 
@@ -48,10 +49,7 @@ pub fn address(evm: &mut Evm) -> Result<()> {
 ///     The current EVM frame.
 pub fn balance(evm: &mut Evm) -> Result<()> {
     // STACK
-    let address = stack::pop(&mut evm.stack)?
-        .to_bytes_be()
-        .try_into()
-        .unwrap();
+    let address = to_address(&stack::pop(&mut evm.stack)?);
 
     // GAS
     gas::charge_gas(evm, gas::GAS_BALANCE())?;
@@ -80,7 +78,7 @@ pub fn origin(evm: &mut Evm) -> Result<()> {
     gas::charge_gas(evm, gas::GAS_BASE())?;
 
     // OPERATION
-    stack::push(&mut evm.stack, U256::from_bytes_be(&evm.env.origin))?;
+    stack::push(&mut evm.stack, U256::from_bytes_be(&evm.env.tx.origin))?;
 
     // PROGRAM COUNTER
     evm.pc += 1;
@@ -204,7 +202,7 @@ pub fn calldatacopy(evm: &mut Evm) -> Result<()> {
     evm.memory
         .extend([0].repeat(usize::try_from(extend_memory.expand_by).unwrap()));
     let value = buffer_read(evm.message.data.clone(), data_start_index, size);
-    memory_write(&mut evm.memory, memory_start_index, value);
+    memory_write(&mut evm.memory, memory_start_index, &value);
 
     // PROGRAM COUNTER
     evm.pc += 1;
@@ -263,7 +261,7 @@ pub fn codecopy(evm: &mut Evm) -> Result<()> {
     evm.memory
         .extend([0].repeat(usize::try_from(extend_memory.expand_by).unwrap()));
     let value = buffer_read(evm.code.clone(), code_start_index, size);
-    memory_write(&mut evm.memory, memory_start_index, value);
+    memory_write(&mut evm.memory, memory_start_index, &value);
 
     // PROGRAM COUNTER
     evm.pc += 1;
@@ -284,7 +282,7 @@ pub fn gasprice(evm: &mut Evm) -> Result<()> {
     gas::charge_gas(evm, gas::GAS_BASE())?;
 
     // OPERATION
-    stack::push(&mut evm.stack, evm.env.gas_price.clone())?;
+    stack::push(&mut evm.stack, evm.env.tx.gas_price.clone())?;
 
     // PROGRAM COUNTER
     evm.pc += 1;
@@ -299,16 +297,13 @@ pub fn gasprice(evm: &mut Evm) -> Result<()> {
 ///     The current EVM frame.
 pub fn extcodesize(evm: &mut Evm) -> Result<()> {
     // STACK
-    let address = stack::pop(&mut evm.stack)?
-        .to_bytes_be()
-        .try_into()
-        .unwrap();
+    let address = to_address(&stack::pop(&mut evm.stack)?);
 
     // GAS
     gas::charge_gas(evm, gas::GAS_EXTERNAL())?;
 
     // OPERATION
-    let codesize = U256::from_bytes_be(&get_account(&evm.env.state, &address).code);
+    let codesize = U256::from(get_account(&evm.env.state, &address).code.len());
     stack::push(&mut evm.stack, codesize)?;
 
     // PROGRAM COUNTER
@@ -324,10 +319,7 @@ pub fn extcodesize(evm: &mut Evm) -> Result<()> {
 ///     The current EVM frame.
 pub fn extcodecopy(evm: &mut Evm) -> Result<()> {
     // STACK
-    let address = stack::pop(&mut evm.stack)?
-        .to_bytes_be()
-        .try_into()
-        .unwrap();
+    let address = to_address(&stack::pop(&mut evm.stack)?);
     let memory_start_index = stack::pop(&mut evm.stack)?;
     let code_start_index = stack::pop(&mut evm.stack)?;
     let size = stack::pop(&mut evm.stack)?;
@@ -348,8 +340,8 @@ pub fn extcodecopy(evm: &mut Evm) -> Result<()> {
     evm.memory
         .extend([0].repeat(usize::try_from(extend_memory.expand_by).unwrap()));
     let code = get_account(&evm.env.state, &address).code;
-    let value = buffer_read(code, code_start_index, size);
-    memory_write(&mut evm.memory, memory_start_index, value);
+    let value = buffer_read(code[..].into(), code_start_index, size);
+    memory_write(&mut evm.memory, memory_start_index, &value);
 
     // PROGRAM COUNTER
     evm.pc += 1;
@@ -10,7 +10,9 @@
 //!
 //! Implementations of the EVM logging instructions.
 
+use crate::prelude::*;
 use super::super::{exceptions::Result, gas, stack, Evm};
+use crate::ethereum::base_types::u256_to_be_bytes32;
 use crate::ethereum::frontier::fork_types::Log;
 use crate::ethereum::frontier::vm::memory::memory_read_bytes;
 
@@ -32,8 +34,8 @@ pub fn log_n(evm: &mut Evm, num_topics: usize) -> Result<()> {
 
     let mut topics = Vec::new();
     for _ in 0..num_topics {
-        let topic = stack::pop(&mut evm.stack)?.to_bytes_be();
-        topics.push(topic.try_into().unwrap());
+        let topic = u256_to_be_bytes32(&stack::pop(&mut evm.stack)?);
+        topics.push(topic);
     }
 
     // GAS
@@ -74,6 +76,14 @@ pub fn log1(evm: &mut Evm) -> Result<()> {
     log_n(evm, 1)
 }
 
+pub fn log2(evm: &mut Evm) -> Result<()> {
+    log_n(evm, 2)
+}
+
+pub fn log3(evm: &mut Evm) -> Result<()> {
+    log_n(evm, 3)
+}
+
 pub fn log4(evm: &mut Evm) -> Result<()> {
     log_n(evm, 4)
 }
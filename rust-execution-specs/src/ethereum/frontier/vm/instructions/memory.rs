@@ -11,7 +11,7 @@
 //! Implementations of the EVM Memory instructions.
 
 use super::super::{exceptions::Result, gas, stack, Evm};
-use crate::ethereum::base_types::U256;
+use crate::ethereum::base_types::{u256_to_be_bytes32, U256};
 use crate::ethereum::frontier::vm::memory::{memory_read_bytes, memory_write};
 
 /// Stores a word to memory.
@@ -25,7 +25,7 @@ use crate::ethereum::frontier::vm::memory::{memory_read_bytes, memory_write};
 pub fn mstore(evm: &mut Evm) -> Result<()> {
     // STACK
     let start_position = stack::pop(&mut evm.stack)?;
-    let value = stack::pop(&mut evm.stack)?.to_bytes_be();
+    let value = u256_to_be_bytes32(&stack::pop(&mut evm.stack)?).to_vec();
     
     // GAS
     let extend_memory = gas::calculate_gas_extend_memory(&evm.memory, [(start_position.clone(), U256::from(value.len()))].to_vec());
@@ -33,7 +33,7 @@ pub fn mstore(evm: &mut Evm) -> Result<()> {
     
     // OPERATION
     evm.memory.extend([0].repeat(usize::try_from(extend_memory.expand_by).unwrap()));
-    memory_write(&mut evm.memory, start_position, value.into_boxed_slice());
+    memory_write(&mut evm.memory, start_position, &value);
     
     // PROGRAM COUNTER
     evm.pc += 1;
@@ -61,7 +61,7 @@ pub fn mstore8(evm: &mut Evm) -> Result<()> {
     // OPERATION
     evm.memory.extend([0].repeat(usize::try_from(extend_memory.expand_by).unwrap()));
     let normalized_bytes_value = (value & U256::from(u8::MAX)).to_bytes_be().into_boxed_slice();
-    memory_write(&mut evm.memory, start_position, normalized_bytes_value);
+    memory_write(&mut evm.memory, start_position, &normalized_bytes_value);
     
     // PROGRAM COUNTER
     evm.pc += 1;
@@ -10,8 +10,6 @@
 //! Machine readable representations of EVM instructions, and a mapping to their
 //! implementations.
 
-// use super::Evm;
-
 pub mod arithmetic;
 pub mod bitwise;
 pub mod block;
@@ -23,10 +21,52 @@ pub mod log;
 pub mod memory;
 pub mod stack;
 pub mod storage;
+pub mod system;
+
+use super::exceptions::{EvmError, Result};
+use super::{gas, Evm};
+use crate::ethereum::base_types::Uint;
+
+macro_rules! opcodes {
+    ($($name:ident = $value:literal,)*) => {
+        /// Enum for EVM Opcodes
+        #[repr(usize)]
+        #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+        pub enum Ops {
+            $($name = $value,)*
+        }
+
+        impl Ops {
+            /// The mnemonic of the opcode, e.g. `"PUSH1"`.
+            pub fn name(&self) -> &'static str {
+                match self {
+                    $(Ops::$name => stringify!($name),)*
+                }
+            }
 
-/// Enum for EVM Opcodes
-#[repr(usize)]
-pub enum Ops {
+            /// Look an opcode up by its mnemonic.
+            pub fn from_name(name: &str) -> Option<Ops> {
+                match name {
+                    $(stringify!($name) => Some(Ops::$name),)*
+                    _ => None,
+                }
+            }
+        }
+
+        impl TryFrom<u8> for Ops {
+            type Error = EvmError;
+
+            fn try_from(value: u8) -> Result<Self, Self::Error> {
+                match value as usize {
+                    $($value => Ok(Ops::$name),)*
+                    _ => Err(EvmError::InvalidOpcode),
+                }
+            }
+        }
+    };
+}
+
+opcodes! {
     // Arithmetic Ops
     ADD = 0x01,
     MUL = 0x02,
@@ -187,4 +227,311 @@ pub enum Ops {
     SELFDESTRUCT = 0xFF,
 }
 
-// pub const OP_IMPLEMENTATIONS: [fn(&mut Evm); 256] = todo!();
+impl Ops {
+    /// The number of immediate bytes following the opcode, non-zero only for
+    /// the `PUSH-N` opcodes.
+    pub fn push_size(&self) -> usize {
+        let value = *self as usize;
+        if (Ops::PUSH1 as usize..=Ops::PUSH32 as usize).contains(&value) {
+            value - Ops::PUSH1 as usize + 1
+        } else {
+            0
+        }
+    }
+
+    /// Every opcode, in the order of their values.
+    pub fn all() -> impl Iterator<Item = Ops> {
+        (0..=u8::MAX).filter_map(|value| Ops::try_from(value).ok())
+    }
+
+    /// The gas charged for the opcode whatever its operands. Dynamic costs,
+    /// such as memory expansion, copied words, `EXP` exponent bytes, `LOG`
+    /// data, the call gas and value transfer of `CALL`, or `SSTORE` as a
+    /// whole, come on top.
+    pub fn base_gas(&self) -> Uint {
+        match self {
+            Ops::STOP | Ops::RETURN | Ops::SELFDESTRUCT | Ops::SSTORE => gas::GAS_ZERO(),
+            Ops::JUMPDEST => gas::GAS_JUMPDEST(),
+            Ops::ADDRESS | Ops::ORIGIN | Ops::CALLER | Ops::CALLVALUE | Ops::CALLDATASIZE | Ops::CODESIZE
+            | Ops::GASPRICE | Ops::COINBASE | Ops::TIMESTAMP | Ops::NUMBER | Ops::DIFFICULTY
+            | Ops::GASLIMIT | Ops::POP | Ops::PC | Ops::MSIZE | Ops::GAS => gas::GAS_BASE(),
+            Ops::MUL | Ops::DIV | Ops::SDIV | Ops::MOD | Ops::SMOD | Ops::SIGNEXTEND => gas::GAS_LOW(),
+            Ops::ADDMOD | Ops::MULMOD | Ops::JUMP => gas::GAS_MID(),
+            Ops::JUMPI => gas::GAS_HIGH(),
+            Ops::EXP => gas::GAS_EXPONENTIATION(),
+            Ops::KECCAK => gas::GAS_KECCAK256(),
+            Ops::BALANCE => gas::GAS_BALANCE(),
+            Ops::EXTCODESIZE | Ops::EXTCODECOPY => gas::GAS_EXTERNAL(),
+            Ops::BLOCKHASH => gas::GAS_BLOCK_HASH(),
+            Ops::SLOAD => gas::GAS_SLOAD(),
+            Ops::LOG0 | Ops::LOG1 | Ops::LOG2 | Ops::LOG3 | Ops::LOG4 => {
+                gas::GAS_LOG() + gas::GAS_LOG_TOPIC() * Uint::from(*self as usize - Ops::LOG0 as usize)
+            }
+            Ops::CREATE => gas::GAS_CREATE(),
+            Ops::CALL | Ops::CALLCODE => gas::GAS_CALL(),
+            _ => gas::GAS_VERY_LOW(),
+        }
+    }
+
+    /// The number of items the opcode pops from the stack, and the number
+    /// it pushes.
+    pub fn stack_io(&self) -> (usize, usize) {
+        let value = *self as usize;
+        match self {
+            Ops::STOP | Ops::JUMPDEST => (0, 0),
+            Ops::ADD | Ops::MUL | Ops::SUB | Ops::DIV | Ops::SDIV | Ops::MOD | Ops::SMOD | Ops::EXP
+            | Ops::SIGNEXTEND | Ops::LT | Ops::GT | Ops::SLT | Ops::SGT | Ops::EQ | Ops::AND | Ops::OR
+            | Ops::XOR | Ops::BYTE | Ops::KECCAK => (2, 1),
+            Ops::ADDMOD | Ops::MULMOD => (3, 1),
+            Ops::ISZERO | Ops::NOT | Ops::BALANCE | Ops::CALLDATALOAD | Ops::EXTCODESIZE | Ops::BLOCKHASH
+            | Ops::SLOAD | Ops::MLOAD => (1, 1),
+            Ops::ADDRESS | Ops::ORIGIN | Ops::CALLER | Ops::CALLVALUE | Ops::CALLDATASIZE | Ops::CODESIZE
+            | Ops::GASPRICE | Ops::COINBASE | Ops::TIMESTAMP | Ops::NUMBER | Ops::DIFFICULTY
+            | Ops::GASLIMIT | Ops::PC | Ops::MSIZE | Ops::GAS => (0, 1),
+            Ops::CALLDATACOPY | Ops::CODECOPY => (3, 0),
+            Ops::EXTCODECOPY => (4, 0),
+            Ops::POP | Ops::JUMP | Ops::SELFDESTRUCT => (1, 0),
+            Ops::JUMPI | Ops::SSTORE | Ops::MSTORE | Ops::MSTORE8 | Ops::RETURN => (2, 0),
+            Ops::CREATE => (3, 1),
+            Ops::CALL | Ops::CALLCODE => (7, 1),
+            Ops::LOG0 | Ops::LOG1 | Ops::LOG2 | Ops::LOG3 | Ops::LOG4 => (2 + value - Ops::LOG0 as usize, 0),
+            _ if self.push_size() > 0 => (0, 1),
+            _ if (Ops::DUP1 as usize..=Ops::DUP16 as usize).contains(&value) => {
+                let n = value - Ops::DUP1 as usize + 1;
+                (n, n + 1)
+            }
+            // The SWAPs are left.
+            _ => {
+                let n = value - Ops::SWAP1 as usize + 2;
+                (n, n)
+            }
+        }
+    }
+}
+
+/// The function executing an opcode against an EVM frame.
+pub type Instruction = fn(&mut Evm) -> Result<()>;
+
+///
+///     The implementations of the opcodes of a fork, indexed by opcode.
+///
+///     A fork declares its table as the table of the fork before it with
+///     the opcodes it adds, changes or removes, and inherits the rest:
+///
+///     ```ignore
+///     pub const HOMESTEAD: InstructionTable = instruction_table!(FRONTIER, {
+///         DELEGATECALL => system::delegatecall,
+///     });
+///     ```
+///
+///     Gas constants are layered the same way, by a fork's `constants`
+///     glob importing those of the fork before it and defining the ones it
+///     changes, which shadow them. Rules with more than one parameter have
+///     their own structs, like `create_policy::CreatePolicy`.
+///
+#[derive(Clone, Copy)]
+pub struct InstructionTable([Option<Instruction>; 256]);
+
+impl InstructionTable {
+    /// A table without any opcode.
+    pub const EMPTY: InstructionTable = InstructionTable([None; 256]);
+
+    ///
+    ///     The table with `op` executed by `instruction`, whether or not it
+    ///     already had `op`.
+    ///
+    pub const fn with(mut self, op: Ops, instruction: Instruction) -> Self {
+        self.0[op as usize] = Some(instruction);
+        self
+    }
+
+    ///
+    ///     The table without `op`.
+    ///
+    pub const fn without(mut self, op: Ops) -> Self {
+        self.0[op as usize] = None;
+        self
+    }
+
+    ///
+    ///     The implementation of an opcode.
+    ///
+    ///     Parameters
+    ///     ----------
+    ///     op :
+    ///         The opcode to look up.
+    ///
+    ///     Returns
+    ///     -------
+    ///     instruction : `Option<Instruction>`
+    ///         The implementation, or `None` if the fork doesn't have `op`.
+    ///
+    pub fn get(&self, op: Ops) -> Option<Instruction> {
+        self.0[op as usize]
+    }
+
+    ///
+    ///     The opcodes in the table, in order of their value.
+    ///
+    pub fn ops(&self) -> impl Iterator<Item = Ops> + '_ {
+        Ops::all().filter(|op| self.0[*op as usize].is_some())
+    }
+}
+
+/// The table `$base` with each opcode set to its implementation.
+macro_rules! instruction_table {
+    ($base:expr, { $($op:ident => $instruction:path,)* }) => {
+        $base$(.with(Ops::$op, $instruction))*
+    };
+}
+// For the tables of later forks.
+#[allow(unused_imports)]
+pub(crate) use instruction_table;
+
+/// The opcodes of Frontier.
+pub const FRONTIER: InstructionTable = instruction_table!(InstructionTable::EMPTY, {
+    ADD => arithmetic::add,
+    MUL => arithmetic::mul,
+    SUB => arithmetic::sub,
+    DIV => arithmetic::div,
+    SDIV => arithmetic::sdiv,
+    MOD => arithmetic::r#mod,
+    SMOD => arithmetic::smod,
+    ADDMOD => arithmetic::addmod,
+    MULMOD => arithmetic::mulmod,
+    EXP => arithmetic::exp,
+    SIGNEXTEND => arithmetic::signextend,
+    LT => comparison::less_than,
+    GT => comparison::greater_than,
+    SLT => comparison::signed_less_than,
+    SGT => comparison::signed_greater_than,
+    EQ => comparison::equal,
+    ISZERO => comparison::is_zero,
+    AND => bitwise::bitwise_and,
+    OR => bitwise::bitwise_or,
+    XOR => bitwise::bitwise_xor,
+    NOT => bitwise::bitwise_not,
+    BYTE => bitwise::get_byte,
+    KECCAK => keccak::keccak,
+    ADDRESS => environment::address,
+    BALANCE => environment::balance,
+    ORIGIN => environment::origin,
+    CALLER => environment::caller,
+    CALLVALUE => environment::callvalue,
+    CALLDATALOAD => environment::calldataload,
+    CALLDATASIZE => environment::calldatasize,
+    CALLDATACOPY => environment::calldatacopy,
+    CODESIZE => environment::codesize,
+    CODECOPY => environment::codecopy,
+    GASPRICE => environment::gasprice,
+    EXTCODESIZE => environment::extcodesize,
+    EXTCODECOPY => environment::extcodecopy,
+    BLOCKHASH => block::block_hash,
+    COINBASE => block::coinbase,
+    TIMESTAMP => block::timestamp,
+    NUMBER => block::number,
+    DIFFICULTY => block::difficulty,
+    GASLIMIT => block::gas_limit,
+    STOP => control_flow::stop,
+    JUMP => control_flow::jump,
+    JUMPI => control_flow::jumpi,
+    PC => control_flow::pc,
+    GAS => control_flow::gas_left,
+    JUMPDEST => control_flow::jumpdest,
+    SLOAD => storage::sload,
+    SSTORE => storage::sstore,
+    POP => stack::pop,
+    PUSH1 => stack::push1,
+    PUSH2 => stack::push2,
+    PUSH3 => stack::push3,
+    PUSH4 => stack::push4,
+    PUSH5 => stack::push5,
+    PUSH6 => stack::push6,
+    PUSH7 => stack::push7,
+    PUSH8 => stack::push8,
+    PUSH9 => stack::push9,
+    PUSH10 => stack::push10,
+    PUSH11 => stack::push11,
+    PUSH12 => stack::push12,
+    PUSH13 => stack::push13,
+    PUSH14 => stack::push14,
+    PUSH15 => stack::push15,
+    PUSH16 => stack::push16,
+    PUSH17 => stack::push17,
+    PUSH18 => stack::push18,
+    PUSH19 => stack::push19,
+    PUSH20 => stack::push20,
+    PUSH21 => stack::push21,
+    PUSH22 => stack::push22,
+    PUSH23 => stack::push23,
+    PUSH24 => stack::push24,
+    PUSH25 => stack::push25,
+    PUSH26 => stack::push26,
+    PUSH27 => stack::push27,
+    PUSH28 => stack::push28,
+    PUSH29 => stack::push29,
+    PUSH30 => stack::push30,
+    PUSH31 => stack::push31,
+    PUSH32 => stack::push32,
+    DUP1 => stack::dup1,
+    DUP2 => stack::dup2,
+    DUP3 => stack::dup3,
+    DUP4 => stack::dup4,
+    DUP5 => stack::dup5,
+    DUP6 => stack::dup6,
+    DUP7 => stack::dup7,
+    DUP8 => stack::dup8,
+    DUP9 => stack::dup9,
+    DUP10 => stack::dup10,
+    DUP11 => stack::dup11,
+    DUP12 => stack::dup12,
+    DUP13 => stack::dup13,
+    DUP14 => stack::dup14,
+    DUP15 => stack::dup15,
+    DUP16 => stack::dup16,
+    SWAP1 => stack::swap1,
+    SWAP2 => stack::swap2,
+    SWAP3 => stack::swap3,
+    SWAP4 => stack::swap4,
+    SWAP5 => stack::swap5,
+    SWAP6 => stack::swap6,
+    SWAP7 => stack::swap7,
+    SWAP8 => stack::swap8,
+    SWAP9 => stack::swap9,
+    SWAP10 => stack::swap10,
+    SWAP11 => stack::swap11,
+    SWAP12 => stack::swap12,
+    SWAP13 => stack::swap13,
+    SWAP14 => stack::swap14,
+    SWAP15 => stack::swap15,
+    SWAP16 => stack::swap16,
+    MLOAD => memory::mload,
+    MSTORE => memory::mstore,
+    MSTORE8 => memory::mstore8,
+    MSIZE => memory::msize,
+    LOG0 => log::log0,
+    LOG1 => log::log1,
+    LOG2 => log::log2,
+    LOG3 => log::log3,
+    LOG4 => log::log4,
+    CREATE => system::create,
+    RETURN => system::return_,
+    CALL => system::call,
+    CALLCODE => system::callcode,
+    SELFDESTRUCT => system::selfdestruct,
+});
+
+/// The implementation of each opcode.
+///
+/// Parameters
+/// ----------
+/// op :
+///     The opcode to look up.
+///
+/// Returns
+/// -------
+/// implementation : `fn(&mut Evm) -> Result<()>`
+///     The function executing `op` against an EVM frame.
+pub fn op_implementation(op: Ops) -> Instruction {
+    FRONTIER.get(op).expect("every opcode is in the Frontier table")
+}
@@ -86,7 +86,7 @@ pub fn dup_n(evm: &mut Evm, item_number: usize) -> Result<()> {
 
     // OPERATION
     if !(item_number < evm.stack.len()) {
-        return Err(EvmError::StackOverflow);
+        return Err(EvmError::StackUnderflow);
     }
     let data_to_duplicate = evm.stack[evm.stack.len() - 1 - item_number].clone();
     stack::push(&mut evm.stack, data_to_duplicate)?;
@@ -119,7 +119,7 @@ pub fn swap_n(evm: &mut Evm, item_number: usize) -> Result<()> {
 
     // OPERATION
     if !(item_number < evm.stack.len()) {
-        return Err(EvmError::StackOverflow);
+        return Err(EvmError::StackUnderflow);
     }
     let other_idx = evm.stack.len() - 1 - item_number;
     let last = evm.stack.last().unwrap().clone();
@@ -134,7 +134,7 @@ pub fn swap_n(evm: &mut Evm, item_number: usize) -> Result<()> {
 
 macro_rules! make_fn {
     ($evm:ident $($fn:ident $call:expr;)*) => {$(
-        fn $fn($evm: &mut Evm) -> Result<()> {
+        pub fn $fn($evm: &mut Evm) -> Result<()> {
             $call
         }
     )*};
@@ -11,8 +11,9 @@
 //! Implementations of the EVM storage related instructions.
 
 use super::super::{exceptions::Result, gas, stack, Evm};
-use crate::ethereum::base_types::U256;
-use crate::ethereum::frontier::state::{get_storage, set_storage};
+use crate::ethereum::base_types::{u256_to_be_bytes32, U256};
+use crate::ethereum::frontier::state::{get_storage, get_storage_original, set_storage};
+use crate::ethereum::storage_gas;
 
 /// Loads to the stack, the value corresponding to a certain key from the
 /// storage of the current account.
@@ -23,10 +24,7 @@ use crate::ethereum::frontier::state::{get_storage, set_storage};
 ///     The current EVM frame.
 pub fn sload(evm: &mut Evm) -> Result<()> {
     // STACK
-    let key = stack::pop(&mut evm.stack)?
-        .to_bytes_be()
-        .try_into()
-        .unwrap();
+    let key = u256_to_be_bytes32(&stack::pop(&mut evm.stack)?);
 
     // GAS
     gas::charge_gas(evm, gas::GAS_SLOAD())?;
@@ -48,25 +46,31 @@ pub fn sload(evm: &mut Evm) -> Result<()> {
 ///     The current EVM frame.
 pub fn sstore(evm: &mut Evm) -> Result<()> {
     // STACK
-    let key = stack::pop(&mut evm.stack)?
-        .to_bytes_be()
-        .try_into()
-        .unwrap();
+    let key = u256_to_be_bytes32(&stack::pop(&mut evm.stack)?);
     let new_value = stack::pop(&mut evm.stack)?;
 
     // GAS
+    let schedule = &storage_gas::FRONTIER;
     let current_value = get_storage(&evm.env.state, &evm.message.current_target, &key);
-    let gas_cost = if new_value != U256::from(0u8) && current_value == U256::from(0u8) {
-        gas::GAS_STORAGE_SET()
+    // Only net metering prices a store by the value the slot had when the
+    // transaction started.
+    let original_value = if schedule.net_metering {
+        get_storage_original(&evm.env.state, &evm.message.current_target, &key)
     } else {
-        gas::GAS_STORAGE_UPDATE()
+        current_value.clone()
     };
-    gas::charge_gas(evm, gas_cost)?;
+    let sstore_gas = storage_gas::sstore_gas(
+        schedule,
+        &original_value,
+        &current_value,
+        &new_value,
+        false,
+    );
+    gas::charge_gas(evm, U256::from(sstore_gas.cost))?;
 
     // OPERATION
-    if new_value == U256::from(0u8) && current_value != U256::from(0u8) {
-        evm.refund_counter += gas::GAS_STORAGE_CLEAR_REFUND();
-    }
+    evm.refund_counter +=
+        U256::from(u64::try_from(sstore_gas.refund).expect("Frontier refunds are never negative"));
     set_storage(
         &mut evm.env.state,
         evm.message.current_target,
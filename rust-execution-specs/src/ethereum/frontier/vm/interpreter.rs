@@ -1,188 +1,348 @@
-// /// 
-// /// Ethereum Virtual Machine (EVM) Interpreter
-// /// ^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^
-// /// 
-// /// .. contents:: Table of Contents
-// ///     :backlinks: none
-// ///     :local:
-// /// 
-// /// Introduction
-// /// ------------
-// /// 
-// /// A straightforward interpreter that executes EVM code.
-// /// 
-// use ::dataclasses::{dataclass};
-// use ::typing::{Set, Tuple};
-// use ::ethereum::{evm_trace};
-// use ::ethereum::base_types::{U256, Bytes0, Uint};
-// use super::super::fork_types::{Address, Log};
-// use super::super::state::{account_has_code_or_nonce, begin_transaction, commit_transaction, move_ether, rollback_transaction, set_code, touch_account};
-// use super::super::vm::{Message};
-// use super::super::vm::gas::{GAS_CODE_DEPOSIT, REFUND_SELF_DESTRUCT, charge_gas};
-// use super::super::vm::precompiled_contracts::mapping::{PRE_COMPILED_CONTRACTS};
-// use super::::{Environment, Evm};
-// use super::exceptions::{ExceptionalHalt, InvalidOpcode, StackDepthLimitError};
-// use super::instructions::{Ops, op_implementation};
-// use super::runtime::{get_valid_jump_destinations};
-// STACK_DEPTH_LIMIT = U256(1024)?;
-// /// 
-// ///     Output of a particular message call
-// /// 
-// ///     Contains the following:
-// /// 
-// ///           1. `gas_left`: remaining gas after execution.
-// ///           2. `refund_counter`: gas to refund after execution.
-// ///           3. `logs`: list of `Log` generated during execution.
-// ///           4. `accounts_to_delete`: Contracts which have self-destructed.
-// ///           5. `has_erred`: True if execution has caused an error.
-// ///     
-// struct MessageCallOutput {
-//     gas_left: U256,
-//     refund_counter: U256,
-//     logs: Tuple[Log][...],
-//     accounts_to_delete: Set[Address],
-//     has_erred: bool,
-// }
-
-
-// impl MessageCallOutput {
-// }
-
-
-// /// 
-// ///     If `message.current` is empty then it creates a smart contract
-// ///     else it executes a call from the `message.caller` to the `message.target`.
-// /// 
-// ///     Parameters
-// ///     ----------
-// ///     message :
-// ///         Transaction specific items.
-// /// 
-// ///     env :
-// ///         External items required for EVM execution.
-// /// 
-// ///     Returns
-// ///     -------
-// ///     output : `MessageCallOutput`
-// ///         Output of the message call
-// ///     
-// pub fn process_message_call(message: Message, env: Environment) -> Result<MessageCallOutput, Error> {
-//     if message.target == Bytes0([])? {
-//         is_collision = account_has_code_or_nonce(env.state, message.current_target)?;
-//         if is_collision {
-//             return Ok(MessageCallOutput(U256(0)?, U256(0)?, tuple()?, set()?, true)?);
-//         } else {
-//             evm = process_create_message(message, env)?;
-//         }
-//     } else {
-//         evm = process_message(message, env)?;
-//     }
-//     if evm.has_erred {
-//         // TypedAssignment unsupported
-//         accounts_to_delete = set()?;
-//         refund_counter = U256(0)?;
-//     } else {
-//         logs = evm.logs;
-//         accounts_to_delete = evm.accounts_to_delete;
-//         refund_counter = evm.refund_counter + REFUND_SELF_DESTRUCT * len(evm.accounts_to_delete)?;
-//     }
-//     return Ok(MessageCallOutput(gas_left = evm.gas_left, refund_counter = refund_counter, logs = logs, accounts_to_delete = accounts_to_delete, has_erred = evm.has_erred)?);
-// }
-
-
-// /// 
-// ///     Executes a call to create a smart contract.
-// /// 
-// ///     Parameters
-// ///     ----------
-// ///     message :
-// ///         Transaction specific items.
-// ///     env :
-// ///         External items required for EVM execution.
-// /// 
-// ///     Returns
-// ///     -------
-// ///     evm: :py:class:`~ethereum.frontier.vm.Evm`
-// ///         Items containing execution specific objects.
-// ///     
-// pub fn process_create_message(message: Message, env: Environment) -> Result<Evm, Error> {
-//     evm = process_message(message, env)?;
-//     if !(evm.has_erred) {
-//         contract_code = evm.output;
-//         contract_code_gas = len(contract_code)? * GAS_CODE_DEPOSIT;
-//         // Try 
-//             charge_gas(evm, contract_code_gas)?;
-//     }
-//     return Ok(evm);
-// }
-
-
-// /// 
-// ///     Executes a call to create a smart contract.
-// /// 
-// ///     Parameters
-// ///     ----------
-// ///     message :
-// ///         Transaction specific items.
-// ///     env :
-// ///         External items required for EVM execution.
-// /// 
-// ///     Returns
-// ///     -------
-// ///     evm: :py:class:`~ethereum.frontier.vm.Evm`
-// ///         Items containing execution specific objects
-// ///     
-// pub fn process_message(message: Message, env: Environment) -> Result<Evm, Error> {
-//     if message.depth > STACK_DEPTH_LIMIT {
-//         return Err(Error::StackDepthLimitError("Stack depth limit reached")?);
-//     }
-//     begin_transaction(env.state)?;
-//     touch_account(env.state, message.current_target)?;
-//     if message.value != 0 {
-//         move_ether(env.state, message.caller, message.current_target, message.value)?;
-//     }
-//     evm = execute_code(message, env)?;
-//     if evm.has_erred {
-//         rollback_transaction(env.state)?;
-//     } else {
-//         commit_transaction(env.state)?;
-//     }
-//     return Ok(evm);
-// }
-
-
-// /// 
-// ///     Executes bytecode present in the `message`.
-// /// 
-// ///     Parameters
-// ///     ----------
-// ///     message :
-// ///         Transaction specific items.
-// ///     env :
-// ///         External items required for EVM execution.
-// /// 
-// ///     Returns
-// ///     -------
-// ///     evm: `ethereum.vm.EVM`
-// ///         Items containing execution specific objects
-// ///     
-// pub fn execute_code(message: Message, env: Environment) -> Result<Evm, Error> {
-//     code = message.code;
-//     valid_jump_destinations = get_valid_jump_destinations(code)?;
-//     evm = Evm(pc = Uint(0)?, stack = [], memory = bytearray()?, code = code, gas_left = message.gas, env = env, valid_jump_destinations = valid_jump_destinations, logs = (), refund_counter = U256(0)?, running = true, message = message, output = [], accounts_to_delete = set()?, has_erred = false)?;
-//     // Try 
-//         if (evm.message.code_address).contains(PRE_COMPILED_CONTRACTS) {
-//             evm_trace(evm, evm.message.code_address)?;
-//             PRE_COMPILED_CONTRACTS[evm.message.code_address](evm)?;
-//             return Ok(evm);
-//         }
-//         while evm.running && evm.pc < len(evm.code)? {
-//             // Try 
-//                 op = Ops(evm.code[evm.pc])?;
-//             evm_trace(evm, op)?;
-//             op_implementation[op](evm)?;
-//         }
-//     return Ok(evm);
-// }
+//! Ethereum Virtual Machine (EVM) Interpreter
+//! ^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^
+//!
+//! .. contents:: Table of Contents
+//!     :backlinks: none
+//!     :local:
+//!
+//! Introduction
+//! ------------
+//!
+//! A straightforward interpreter that executes EVM code.
 
+use crate::prelude::*;
 
+use super::{
+    evm_trace,
+    exceptions::EvmError,
+    gas::charge_gas,
+    instructions::{op_implementation, Ops},
+    precompiled_contracts::mapping::pre_compiled_contract,
+    runtime::get_valid_jump_destinations,
+    Environment, Evm, Message,
+};
+use crate::ethereum::{
+    base_types::{Bytes, Uint, U256},
+    create_policy::{self, code_deposit, CodeDeposit, CreateFailure},
+    frontier::{
+        fork_types::{Address, Log},
+        state::{
+            begin_transaction, commit_transaction, is_create_collision, move_ether,
+            rollback_transaction, set_code, touch_account,
+        },
+    },
+    selfdestruct,
+    trace::TraceEvent,
+};
+
+pub use crate::ethereum::frontier::constants::STACK_DEPTH_LIMIT;
+#[cfg(feature = "strict-invariants")]
+use crate::ethereum::frontier::constants::STACK_SIZE_LIMIT;
+
+/// Output of a particular message call
+///
+/// Contains the following:
+///
+/// 1. `gas_left`: remaining gas after execution.
+/// 2. `refund_counter`: gas to refund after execution.
+/// 3. `logs`: list of `Log` generated during execution.
+/// 4. `accounts_to_delete`: Contracts which have self-destructed.
+/// 5. `error`: The error from the execution if any.
+/// 6. `return_data`: The output of the execution.
+pub struct MessageCallOutput {
+    pub gas_left: U256,
+    pub refund_counter: U256,
+    pub logs: Vec<Log>,
+    pub accounts_to_delete: HashSet<Address>,
+    pub error: Option<EvmError>,
+    pub return_data: Bytes,
+}
+
+/// If `message.current` is empty then it creates a smart contract
+/// else it executes a call from the `message.caller` to the `message.target`.
+///
+/// Parameters
+/// ----------
+/// message :
+///     Transaction specific items.
+///
+/// env :
+///     External items required for EVM execution.
+///
+/// Returns
+/// -------
+/// output : `MessageCallOutput`
+///     Output of the message call
+pub fn process_message_call(message: Message, env: &mut Environment) -> MessageCallOutput {
+    let gas = message.gas.clone();
+    let mut evm = if message.target.is_none() {
+        let is_collision = is_create_collision(&env.state, &message.current_target);
+        if is_collision {
+            return MessageCallOutput {
+                gas_left: U256::from(0u8),
+                refund_counter: U256::from(0u8),
+                logs: vec![],
+                accounts_to_delete: HashSet::new(),
+                error: Some(EvmError::AddressCollision),
+                return_data: Bytes::default(),
+            };
+        }
+        process_create_message(message, core::mem::take(env))
+    } else {
+        process_message(message, core::mem::take(env))
+    };
+
+    let (logs, accounts_to_delete, refund_counter) = if evm.error.is_some() {
+        (vec![], HashSet::new(), U256::from(0u8))
+    } else {
+        let refund_counter = &evm.refund_counter
+            + selfdestruct::selfdestruct_refund(&selfdestruct::FRONTIER, evm.accounts_to_delete.len());
+        (
+            core::mem::take(&mut evm.logs),
+            core::mem::take(&mut evm.accounts_to_delete),
+            refund_counter,
+        )
+    };
+
+    let tx_end = TraceEvent::TransactionEnd {
+        gas_used: gas - &evm.gas_left,
+        output: evm.output.clone(),
+        error: evm.error.map(|error| error.to_string()),
+    };
+    evm_trace(&mut evm, tx_end);
+
+    *env = core::mem::take(&mut evm.env);
+    MessageCallOutput {
+        gas_left: evm.gas_left,
+        refund_counter,
+        logs,
+        accounts_to_delete,
+        error: evm.error,
+        return_data: evm.output,
+    }
+}
+
+/// Executes a call to create a smart contract.
+///
+/// Parameters
+/// ----------
+/// message :
+///     Transaction specific items.
+/// env :
+///     External items required for EVM execution.
+///
+/// Returns
+/// -------
+/// evm: :py:class:`~ethereum.frontier.vm.Evm`
+///     Items containing execution specific objects.
+pub fn process_create_message(message: Message, mut env: Environment) -> Evm {
+    // take snapshot of state before processing the message
+    begin_transaction(&mut env.state);
+    let mut evm = process_message(message, env);
+    if evm.error.is_none() {
+        let contract_code = evm.output.clone();
+        match code_deposit(&create_policy::FRONTIER, &contract_code, &evm.gas_left) {
+            CodeDeposit::Store(contract_code_gas) => {
+                charge_gas(&mut evm, contract_code_gas).expect("the deposit is within the gas left");
+                set_code(&mut evm.env.state, evm.message.current_target, contract_code);
+                commit_transaction(&mut evm.env.state);
+            }
+            // In Frontier, running out of gas for the code deposit is not an
+            // error: the contract is created without code.
+            CodeDeposit::Empty => {
+                evm.output = Bytes::default();
+                commit_transaction(&mut evm.env.state);
+            }
+            CodeDeposit::Fail(failure) => {
+                rollback_transaction(&mut evm.env.state);
+                evm.gas_left = U256::from(0u8);
+                evm.output = Bytes::default();
+                evm.error = Some(match failure {
+                    CreateFailure::OutOfGas => EvmError::OutOfGas,
+                    // Frontier deploys code starting with `0xEF`.
+                    CreateFailure::InvalidContractPrefix => EvmError::Halt,
+                });
+            }
+        }
+    } else {
+        rollback_transaction(&mut evm.env.state);
+    }
+    // A message past the depth limit never started a frame.
+    if evm.message.depth <= Uint::from(STACK_DEPTH_LIMIT) {
+        trace_message_end(&mut evm);
+    }
+    evm
+}
+
+/// Executes a call to create a smart contract.
+///
+/// Parameters
+/// ----------
+/// message :
+///     Transaction specific items.
+/// env :
+///     External items required for EVM execution.
+///
+/// Returns
+/// -------
+/// evm: :py:class:`~ethereum.frontier.vm.Evm`
+///     Items containing execution specific objects
+pub fn process_message(message: Message, mut env: Environment) -> Evm {
+    if message.depth > Uint::from(STACK_DEPTH_LIMIT) {
+        // The python spec raises `StackDepthLimitError` here. `CALL` and
+        // `CREATE` check the depth before recursing, so this can't be
+        // reached from the EVM; the frame fails without running instead.
+        let mut evm = new_evm(message, env);
+        evm.gas_left = U256::from(0u8);
+        evm.error = Some(EvmError::StackDepthLimit);
+        return evm;
+    }
+
+    begin_transaction(&mut env.state);
+
+    touch_account(&mut env.state, message.current_target);
+
+    if message.value != U256::from(0u8) {
+        move_ether(
+            &mut env.state,
+            message.caller,
+            message.current_target,
+            message.value.clone(),
+        );
+    }
+
+    let mut evm = execute_code(message, env);
+    if evm.error.is_some() {
+        rollback_transaction(&mut evm.env.state);
+    } else {
+        commit_transaction(&mut evm.env.state);
+    }
+    // A create ends once its code is deposited.
+    if evm.message.target.is_some() {
+        trace_message_end(&mut evm);
+    }
+    evm
+}
+
+fn trace_message_end(evm: &mut Evm) {
+    let message_end = TraceEvent::MessageEnd {
+        depth: evm.message.depth.clone(),
+        error: evm.error.as_ref().map(|error| error.to_string()),
+    };
+    evm_trace(evm, message_end);
+}
+
+/// Executes bytecode present in the `message`.
+///
+/// Parameters
+/// ----------
+/// message :
+///     Transaction specific items.
+/// env :
+///     External items required for EVM execution.
+///
+/// Returns
+/// -------
+/// evm: `ethereum.vm.EVM`
+///     Items containing execution specific objects
+pub fn execute_code(message: Message, env: Environment) -> Evm {
+    #[cfg(feature = "metrics")]
+    crate::ethereum::metrics::EVM_MESSAGES.increment();
+    let mut evm = new_evm(message, env);
+    let message_start = TraceEvent::MessageStart { depth: evm.message.depth.clone() };
+    evm_trace(&mut evm, message_start);
+    if let Err(error) = run(&mut evm) {
+        evm_trace(&mut evm, TraceEvent::OpException { error: error.to_string() });
+        evm.gas_left = U256::from(0u8);
+        evm.error = Some(error);
+    }
+    evm
+}
+
+pub(crate) fn new_evm(message: Message, env: Environment) -> Evm {
+    let code = message.code.clone();
+    let valid_jump_destinations = get_valid_jump_destinations(&code);
+    Evm {
+        pc: 0,
+        stack: vec![],
+        memory: vec![],
+        code,
+        gas_left: message.gas.clone(),
+        env,
+        valid_jump_destinations,
+        logs: vec![],
+        refund_counter: U256::from(0u8),
+        running: true,
+        message,
+        output: Bytes::default(),
+        accounts_to_delete: HashSet::new(),
+        error: None,
+    }
+}
+
+/// Check what an instruction that succeeded must leave true of the frame,
+/// so that a mistake in porting it fails where it is made rather than as a
+/// wrong state root. Checked in debug builds with the `strict-invariants`
+/// feature.
+///
+/// Parameters
+/// ----------
+/// evm :
+///     The current EVM frame, after the instruction.
+/// op :
+///     The instruction.
+/// pc :
+///     Where the instruction is in the code.
+#[cfg(feature = "strict-invariants")]
+fn check_invariants(evm: &Evm, op: Ops, pc: usize) {
+    debug_assert!(
+        !evm.running
+            || evm.pc == pc + 1 + op.push_size()
+            || evm.valid_jump_destinations.contains(&U256::from(evm.pc)),
+        "{op:?} at {pc} moved the pc to {}, which is not a JUMPDEST",
+        evm.pc
+    );
+    debug_assert!(
+        evm.stack.len() <= STACK_SIZE_LIMIT,
+        "{op:?} at {pc} left {} items on the stack",
+        evm.stack.len()
+    );
+    debug_assert!(
+        evm.gas_left <= evm.message.gas,
+        "{op:?} at {pc} left {} gas of the {} given",
+        evm.gas_left,
+        evm.message.gas
+    );
+    debug_assert!(
+        evm.memory.len().is_multiple_of(32),
+        "{op:?} at {pc} left {} bytes of memory, not whole words",
+        evm.memory.len()
+    );
+}
+
+fn run(evm: &mut Evm) -> Result<(), EvmError> {
+    if let Some(code_address) = evm.message.code_address {
+        if let Some(contract) = pre_compiled_contract(&code_address) {
+            evm_trace(evm, TraceEvent::PrecompileStart { address: code_address.into() });
+            contract(evm)?;
+            evm_trace(evm, TraceEvent::PrecompileEnd);
+            return Ok(());
+        }
+    }
+
+    while evm.running && evm.pc < evm.code.len() {
+        let op = Ops::try_from(evm.code[evm.pc])?;
+        evm_trace(evm, TraceEvent::OpStart { op: op as u8 });
+        #[cfg(feature = "metrics")]
+        crate::ethereum::metrics::EVM_INSTRUCTIONS.increment();
+        #[cfg(feature = "strict-invariants")]
+        let pc = evm.pc;
+        op_implementation(op)(evm)?;
+        #[cfg(feature = "strict-invariants")]
+        check_invariants(evm, op, pc);
+        evm_trace(evm, TraceEvent::OpEnd);
+    }
+
+    evm_trace(evm, TraceEvent::EvmStop { op: Ops::STOP as u8 });
+    Ok(())
+}
@@ -10,8 +10,9 @@
 //!
 //! EVM memory operations.
 
+use crate::prelude::*;
 use crate::ethereum::{
-    base_types::{Bytes, U256},
+    base_types::{Bytes, U256, U256Ext},
     utils::byte::right_pad_zero_bytes,
 };
 
@@ -25,15 +26,19 @@ use crate::ethereum::{
 ///     Starting pointer to the memory.
 /// value :
 ///     Data to write to memory.
-pub fn memory_write(memory: &mut Vec<u8>, start_position: U256, value: Bytes) {
+pub fn memory_write(memory: &mut Vec<u8>, start_position: U256, value: &[u8]) {
+    // Writing nothing never extends memory, however far away it is.
+    if value.is_empty() {
+        return;
+    }
     let start_position = usize::try_from(start_position).unwrap();
 
     if memory.len() < start_position + value.len() {
         let missing = start_position + value.len() - memory.len();
-        memory.extend(std::iter::repeat(0).take(missing));
+        memory.extend(core::iter::repeat(0).take(missing));
     }
 
-    memory[start_position..(start_position + value.len())].copy_from_slice(&value);
+    memory[start_position..(start_position + value.len())].copy_from_slice(value);
 }
 
 /// Read bytes from memory.
@@ -52,6 +57,9 @@ pub fn memory_write(memory: &mut Vec<u8>, start_position: U256, value: Bytes) {
 /// data_bytes :
 ///     Data read from memory.
 pub fn memory_read_bytes(memory: &[u8], start_position: U256, size: U256) -> &[u8] {
+    if size == U256::from(0u8) {
+        return &[];
+    }
     let start_position = usize::try_from(start_position).unwrap();
     let size = usize::try_from(size).unwrap();
     &memory[start_position..(start_position + size)]
@@ -73,11 +81,13 @@ pub fn memory_read_bytes(memory: &[u8], start_position: U256, size: U256) -> &[u
 /// data_bytes :
 ///     Data read from memory.
 pub fn buffer_read(buffer: Bytes, start_position: U256, size: U256) -> Bytes {
-    let start_position = usize::try_from(start_position).unwrap();
     let size = usize::try_from(size).unwrap();
+    // Like a python slice, reading past the end of `buffer` yields nothing.
+    let start_position = start_position.saturating_to_usize().min(buffer.len());
+    let end_position = start_position.saturating_add(size).min(buffer.len());
 
     right_pad_zero_bytes(
-        buffer[start_position..(start_position + size)]
+        buffer[start_position..end_position]
             .to_vec()
             .into_boxed_slice(),
         size.into(),
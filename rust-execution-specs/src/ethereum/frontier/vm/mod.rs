@@ -1,29 +1,55 @@
-use std::collections::HashSet;
+use crate::prelude::*;
 
+use self::exceptions::EvmError;
 use super::{
     fork_types::{Address, Hash32, Log},
     state::State,
 };
-use crate::ethereum::base_types::{Bytes, Uint, U256};
+use crate::ethereum::{
+    base_types::{Bytes, Uint, U256},
+    trace::{TraceEvent, Tracer},
+};
 
+pub mod call;
 pub mod instructions;
 pub mod exceptions;
 pub mod gas;
+pub mod interpreter;
 pub mod memory;
+pub mod precompiled_contracts;
+pub mod runtime;
 pub mod stack;
 
-/// Items external to the virtual machine itself, provided by the environment.
-pub struct Environment {
-    pub caller: Address,
+/// Items external to the virtual machine itself, provided by the block.
+///
+/// Later forks add their own fields here (`base_fee_per_gas`,
+/// `prev_randao`, the blob gas fields, ...); Frontier only has these.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct BlockEnvironment {
     pub block_hashes: Vec<Hash32>,
-    pub origin: Address,
     pub coinbase: Address,
     pub number: Uint,
     pub gas_limit: Uint,
-    pub gas_price: U256,
     pub time: U256,
     pub difficulty: Uint,
+}
+
+/// Items external to the virtual machine itself, provided by the
+/// transaction.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TxEnvironment {
+    pub origin: Address,
+    pub gas_price: U256,
+}
+
+/// Items external to the virtual machine itself, provided by the environment.
+#[derive(Default)]
+pub struct Environment {
+    pub block: BlockEnvironment,
+    pub tx: TxEnvironment,
     pub state: State,
+    /// Receives the [TraceEvent]s of every frame, if set.
+    pub tracer: Option<Box<dyn Tracer<Evm>>>,
 }
 
 /// Items that are used by contract creation or message call.
@@ -54,7 +80,7 @@ pub struct Evm {
     pub message: Message,
     pub output: Bytes,
     pub accounts_to_delete: HashSet<Address>,
-    pub has_erred: bool,
+    pub error: Option<EvmError>,
 }
 
 /// Incorporate the state of a successful `child_evm` into the parent `evm`.
@@ -86,3 +112,21 @@ pub fn incorporate_child_on_success(evm: &mut Evm, child_evm: &Evm) {
 pub fn incorporate_child_on_error(evm: &mut Evm, child_evm: &Evm) {
     evm.gas_left += &child_evm.gas_left;
 }
+
+/// Report `event` to the tracer of `evm`, if there is one.
+///
+/// Parameters
+/// ----------
+/// evm :
+///     The frame the event happened in.
+/// event :
+///     What happened.
+///
+pub fn evm_trace(evm: &mut Evm, event: TraceEvent) {
+    // The tracer lives in the environment the frame owns, so it is taken out
+    // for the duration of the call.
+    if let Some(mut tracer) = evm.env.tracer.take() {
+        tracer.trace_mut(evm, &event);
+        evm.env.tracer = Some(tracer);
+    }
+}
@@ -1,33 +1,41 @@
-// /// 
-// /// Ethereum Virtual Machine (EVM) IDENTITY PRECOMPILED CONTRACT
-// /// ^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^
-// /// 
-// /// .. contents:: Table of Contents
-// ///     :backlinks: none
-// ///     :local:
-// /// 
-// /// Introduction
-// /// ------------
-// /// 
-// /// Implementation of the `IDENTITY` precompiled contract.
-// /// 
-// use ::ethereum::base_types::{Uint};
-// use ::ethereum::utils::numeric::{ceil32};
-// use super::super::super::vm::{Evm};
-// use super::super::super::vm::gas::{GAS_IDENTITY, GAS_IDENTITY_WORD, charge_gas};
-// /// 
-// ///     Writes the message data to output.
-// /// 
-// ///     Parameters
-// ///     ----------
-// ///     evm :
-// ///         The current EVM frame.
-// ///     
-// pub fn identity(evm: Evm) -> Result<(), Error> {
-//     data = evm.message.data;
-//     word_count = (ceil32(Uint(len(data)?)?)?).floordiv(32);
-//     charge_gas(evm, GAS_IDENTITY + GAS_IDENTITY_WORD * word_count)?;
-//     evm.output = data;
-// }
+//! Ethereum Virtual Machine (EVM) IDENTITY PRECOMPILED CONTRACT
+//! ^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^
+//!
+//! .. contents:: Table of Contents
+//!     :backlinks: none
+//!     :local:
+//!
+//! Introduction
+//! ------------
+//!
+//! Implementation of the `IDENTITY` precompiled contract.
 
+use super::super::{
+    exceptions::Result,
+    gas::{charge_gas, GAS_IDENTITY, GAS_IDENTITY_WORD},
+    Evm,
+};
+use crate::ethereum::{
+    base_types::{Uint, U256},
+    utils::numeric::ceil32,
+};
 
+/// Writes the message data to output.
+///
+/// The data is moved rather than copied: nothing reads it once the
+/// precompile has run, and the caller copies the output straight into its
+/// memory.
+///
+/// Parameters
+/// ----------
+/// evm :
+///     The current EVM frame.
+pub fn identity(evm: &mut Evm) -> Result<()> {
+    // GAS
+    let word_count = ceil32(Uint::from(evm.message.data.len())) / U256::from(32u8);
+    charge_gas(evm, GAS_IDENTITY() + GAS_IDENTITY_WORD() * word_count)?;
+
+    // OPERATION
+    evm.output = core::mem::take(&mut evm.message.data);
+    Ok(())
+}
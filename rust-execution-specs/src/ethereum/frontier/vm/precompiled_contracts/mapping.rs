@@ -1,21 +1,39 @@
-// /// 
-// /// Precompiled Contract Addresses
-// /// ^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^
-// /// 
-// /// .. contents:: Table of Contents
-// ///     :backlinks: none
-// ///     :local:
-// /// 
-// /// Introduction
-// /// ------------
-// /// 
-// /// Mapping of precompiled contracts their implementations.
-// /// 
-// use ::typing::{Callable, Dict};
-// use super::super::super::fork_types::{Address};
-// use super::::{ECRECOVER_ADDRESS, IDENTITY_ADDRESS, RIPEMD160_ADDRESS, SHA256_ADDRESS};
-// use super::ecrecover::{ecrecover};
-// use super::identity::{identity};
-// use super::ripemd160::{ripemd160};
-// use super::sha256::{sha256};
-// // TypedAssignment unsupported
+//! Precompiled Contract Addresses
+//! ^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^
+//!
+//! .. contents:: Table of Contents
+//!     :backlinks: none
+//!     :local:
+//!
+//! Introduction
+//! ------------
+//!
+//! Mapping of precompiled contracts their implementations.
+
+use super::{
+    super::{exceptions::Result, Evm},
+    identity::identity,
+    ripemd160::ripemd160,
+    sha256::sha256,
+    IDENTITY_ADDRESS, RIPEMD160_ADDRESS, SHA256_ADDRESS,
+};
+use crate::ethereum::frontier::fork_types::Address;
+
+/// A precompiled contract, run instead of code when its address is called.
+pub type PrecompiledContract = fn(&mut Evm) -> Result<()>;
+
+// TODO: ECRECOVER_ADDRESS is not ported yet, calls to it run its (empty)
+// code.
+pub const PRE_COMPILED_CONTRACTS: &[(Address, PrecompiledContract)] = &[
+    (SHA256_ADDRESS, sha256),
+    (RIPEMD160_ADDRESS, ripemd160),
+    (IDENTITY_ADDRESS, identity),
+];
+
+/// Look up the precompiled contract at `address`, if there is one.
+pub fn pre_compiled_contract(address: &Address) -> Option<PrecompiledContract> {
+    PRE_COMPILED_CONTRACTS
+        .iter()
+        .find(|(precompile_address, _)| precompile_address == address)
+        .map(|(_, contract)| *contract)
+}
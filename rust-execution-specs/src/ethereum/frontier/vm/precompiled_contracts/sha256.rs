@@ -1,34 +1,38 @@
-// /// 
-// /// Ethereum Virtual Machine (EVM) SHA256 PRECOMPILED CONTRACT
-// /// ^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^
-// /// 
-// /// .. contents:: Table of Contents
-// ///     :backlinks: none
-// ///     :local:
-// /// 
-// /// Introduction
-// /// ------------
-// /// 
-// /// Implementation of the `SHA256` precompiled contract.
-// /// 
-// // NOTE: Import::Import unsupported
-// use ::ethereum::base_types::{Uint};
-// use ::ethereum::utils::numeric::{ceil32};
-// use super::super::super::vm::{Evm};
-// use super::super::super::vm::gas::{GAS_SHA256, GAS_SHA256_WORD, charge_gas};
-// /// 
-// ///     Writes the sha256 hash to output.
-// /// 
-// ///     Parameters
-// ///     ----------
-// ///     evm :
-// ///         The current EVM frame.
-// ///     
-// pub fn sha256(evm: Evm) -> Result<(), Error> {
-//     data = evm.message.data;
-//     word_count = (ceil32(Uint(len(data)?)?)?).floordiv(32);
-//     charge_gas(evm, GAS_SHA256 + GAS_SHA256_WORD * word_count)?;
-//     evm.output = hashlib.sha256(data)?.digest()?;
-// }
+//! Ethereum Virtual Machine (EVM) SHA256 PRECOMPILED CONTRACT
+//! ^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^
+//!
+//! .. contents:: Table of Contents
+//!     :backlinks: none
+//!     :local:
+//!
+//! Introduction
+//! ------------
+//!
+//! Implementation of the `SHA256` precompiled contract.
 
+use super::super::{
+    exceptions::Result,
+    gas::{charge_gas, GAS_SHA256, GAS_SHA256_WORD},
+    Evm,
+};
+use crate::ethereum::{
+    base_types::{Uint, U256},
+    crypto::hash,
+    utils::numeric::ceil32,
+};
 
+/// Writes the sha256 hash to output.
+///
+/// Parameters
+/// ----------
+/// evm :
+///     The current EVM frame.
+pub fn sha256(evm: &mut Evm) -> Result<()> {
+    // GAS
+    let word_count = ceil32(Uint::from(evm.message.data.len())) / U256::from(32u8);
+    charge_gas(evm, GAS_SHA256() + GAS_SHA256_WORD() * word_count)?;
+
+    // OPERATION
+    evm.output = hash::sha256(&evm.message.data).into();
+    Ok(())
+}
@@ -10,7 +10,9 @@
 //!
 //! Implementation of the stack operators for the EVM.
 
+use crate::prelude::*;
 use super::exceptions::{EvmError, Result};
+use super::super::constants::STACK_SIZE_LIMIT;
 use crate::ethereum::base_types::U256;
 
 /// Pops the top item off of `stack`.
@@ -29,7 +31,7 @@ pub fn pop(stack: &mut Vec<U256>) -> Result<U256> {
 }
 
 pub fn push(stack: &mut Vec<U256>, value: U256) -> Result<()> {
-    if stack.len() == 1024 {
+    if stack.len() == STACK_SIZE_LIMIT {
         return Err(EvmError::StackOverflow);
     }
     stack.push(value);
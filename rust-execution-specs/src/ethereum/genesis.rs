@@ -6,20 +6,18 @@
 //! Functionalities and entities to obtain the genesis configurations for
 //! different chains.
 //!
-use std::{collections::HashMap, todo};
+use crate::prelude::*;
 
 use num_bigint::BigUint;
 
-use crate::{
-    ethereum::{
-        frontier::{
-            fork_types::{Hash32, Header},
-            state::{self, state_root, State},
-            trie::{root, Trie, dummy_root},
-        },
-        rlp,
+use crate::ethereum::{
+    frontier::{
+        fork_types::{Block, Hash32, Header},
+        state::{self, state_root},
+        trie::EMPTY_TRIE_ROOT,
     },
-    ethereum_spec_tools::forks::HardFork,
+    hard_fork::HardFork,
+    rlp,
 };
 
 use super::{
@@ -64,13 +62,32 @@ pub struct GenesisConfiguration {
 ///     configuration : `GenesisConfiguration`
 ///         The genesis configuration obtained from the json genesis file.
 ///
+#[cfg(feature = "fs")]
 pub fn get_genesis_configuration(
     genesis_file: &str,
 ) -> Result<GenesisConfiguration, EthereumException> {
     let path = format!("./assets/{genesis_file}");
     let file = std::fs::read_to_string(&path).map_err(|_| EthereumException::FileNotFound(path))?;
+    parse_genesis_configuration(&file)
+}
 
-    let value: serde_json::Value = serde_json::from_str(&file)
+///
+///     Parse a genesis configuration from the contents of a genesis json
+///     file, for when there is no file system to read `assets` from.
+///
+///     Parameters
+///     ----------
+///     json :
+///         The parameters for the genesis block and the pre-sale allocation
+///         data, as json.
+///
+///     Returns
+///     -------
+///     configuration : `GenesisConfiguration`
+///         The genesis configuration obtained from the json.
+///
+pub fn parse_genesis_configuration(json: &str) -> Result<GenesisConfiguration, EthereumException> {
+    let value: serde_json::Value = serde_json::from_str(json)
         .map_err(|e| EthereumException::JsonDecodeError(e.to_string()))?;
 
     let mut res = GenesisConfiguration::default();
@@ -138,21 +155,20 @@ pub fn get_genesis_configuration(
 ///
 pub fn add_genesis_block(
     _hardfork: HardFork,
-    chain: BlockChain,
+    chain: &mut BlockChain,
     genesis: GenesisConfiguration,
 ) -> Result<(), EthereumException> {
-    let mut state = State::default();
     for (account, balance) in genesis.initial_balances {
-        state::create_ether(&mut state, account, balance);
+        state::create_ether(&mut chain.state, account, balance);
     }
 
-    let _genesis_header = Header {
+    let genesis_header = Header {
         parent_hash: Hash32::default(),
         ommers_hash: rlp::rlp_hash(&()),
         coinbase: Address::default(),
         state_root: state_root(&chain.state),
-        transactions_root: root(&Trie::<Address, _>::new(false, ()), dummy_root),
-        receipt_root: root(&Trie::<Address, _>::new(false, ()), dummy_root),
+        transactions_root: EMPTY_TRIE_ROOT(),
+        receipt_root: EMPTY_TRIE_ROOT(),
         bloom: [0; 256],
         difficulty: genesis.difficulty,
         number: BigUint::default(),
@@ -163,8 +179,12 @@ pub fn add_genesis_block(
         mix_digest: Hash32::default(),
         nonce: genesis.nonce,
     };
-    // genesis_block = hardfork.eth_types.Block(header = genesis_header, transactions = (), ommers = ())?;
-    // chain.blocks.append(genesis_block)?;
-    // chain.chain_id = genesis.chain_id;
-    todo!()
+    let genesis_block = Block {
+        header: genesis_header,
+        transactions: vec![],
+        ommers: vec![],
+    };
+    chain.blocks.push(genesis_block);
+    chain.chain_id = genesis.chain_id;
+    Ok(())
 }
@@ -0,0 +1,110 @@
+//!
+//! # Hard Forks
+//!
+//! ## Introduction
+//!
+//! The forks the spec implements, which the rules shared by the forks, such
+//! as `intrinsic_gas` or `transaction_signature`, are picked by, and the
+//! names fixtures and genesis files give the forks of mainnet.
+//!
+//! When a fork activates is up to a chain, see
+//! `ethereum_spec_tools::forks::ForkSchedule`.
+//!
+
+use crate::prelude::*;
+
+use super::exceptions::EthereumException;
+
+/// A hard fork of the Ethereum blockchain the spec implements.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Default)]
+pub enum HardFork {
+    #[default]
+    Frontier,
+    // Homestead,
+    // DaoFork,
+    // TangerineWhistle,
+    // SpuriousDragon,
+    // Byzantium,
+    // Constantinople,
+    // Petersburg,
+    // Istanbul,
+    // MuirGlacier,
+    // Berlin,
+    // London,
+    // ArrowGlacier,
+    // GrayGlacier,
+    // Paris,
+    // #[default]
+    // Shanghai,
+}
+
+/// Every fork of mainnet, in order, with the other names fixtures give it.
+const KNOWN_FORKS: [(&str, &[&str]); 18] = [
+    ("Frontier", &[]),
+    ("Homestead", &[]),
+    ("DAO Fork", &[]),
+    ("Tangerine Whistle", &["EIP150"]),
+    ("Spurious Dragon", &["EIP158"]),
+    ("Byzantium", &[]),
+    ("Constantinople", &[]),
+    ("Petersburg", &["ConstantinopleFix"]),
+    ("Istanbul", &[]),
+    ("Muir Glacier", &[]),
+    ("Berlin", &[]),
+    ("London", &[]),
+    ("Arrow Glacier", &[]),
+    ("Gray Glacier", &[]),
+    ("Paris", &["Merge"]),
+    ("Shanghai", &[]),
+    ("Cancun", &[]),
+    ("Prague", &[]),
+];
+
+impl HardFork {
+    ///
+    ///     Find the implementation of a fork by its name.
+    ///
+    ///     Case and spaces don't matter, and the names fixtures use, such as
+    ///     "EIP150", are understood. Each fork is compiled only with its
+    ///     cargo feature, `all-forks` turning on all of them.
+    ///
+    ///     Parameters
+    ///     ----------
+    ///     name :
+    ///         The name of the fork.
+    ///
+    ///     Returns
+    ///     -------
+    ///     fork : `HardFork`
+    ///         The implementation of the fork.
+    ///
+    ///     Raises
+    ///     ------
+    ///     UnsupportedFork :
+    ///         With the name of the fork, if the spec doesn't implement it or
+    ///         its feature is off.
+    ///     ValueError :
+    ///         If no fork has that name.
+    ///
+    pub fn from_name(name: &str) -> Result<Self, EthereumException> {
+        let normalize = |name: &str| name.replace(' ', "").to_ascii_lowercase();
+        let (canonical, _) = KNOWN_FORKS
+            .iter()
+            .find(|(known, aliases)| {
+                normalize(known) == normalize(name) || aliases.iter().any(|alias| normalize(alias) == normalize(name))
+            })
+            .ok_or(EthereumException::ValueError)?;
+        match *canonical {
+            #[cfg(feature = "frontier")]
+            "Frontier" => Ok(HardFork::Frontier),
+            _ => Err(EthereumException::UnsupportedFork(canonical.to_string())),
+        }
+    }
+
+    ///
+    ///     The forks compiled in, in the order they activate.
+    ///
+    pub fn enabled() -> Vec<Self> {
+        KNOWN_FORKS.iter().filter_map(|(name, _)| Self::from_name(name).ok()).collect()
+    }
+}
@@ -0,0 +1,151 @@
+//!
+//! # Intrinsic Gas
+//!
+//! ## Introduction
+//!
+//! The gas a transaction is charged before any of its code runs, which
+//! `validate_transaction` checks the transaction's gas against and
+//! `process_transaction` charges up front.
+//!
+//! Frontier charges every transaction a base cost and a cost per byte of
+//! data, zero bytes being cheaper. An [IntrinsicGasSchedule] also has the
+//! prices later forks added, for creating a contract, for the entries of an
+//! access list, for each word of initcode and for each authorization tuple,
+//! which are zero in Frontier.
+//!
+
+use crate::ethereum::{
+    base_types::Uint, frontier::fork_types::Transaction, utils::numeric::ceil32,
+};
+use crate::ethereum::hard_fork::HardFork;
+
+/// The prices of one intrinsic gas rule set.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct IntrinsicGasSchedule {
+    /// Cost of every transaction.
+    pub base: u64,
+    /// Cost per zero byte of data.
+    pub data_zero: u64,
+    /// Cost per non-zero byte of data.
+    pub data_non_zero: u64,
+    /// Surcharge for a contract creating transaction.
+    pub create: u64,
+    /// Cost per address in the access list.
+    pub access_list_address: u64,
+    /// Cost per storage key in the access list.
+    pub access_list_storage_key: u64,
+    /// Cost per 32 byte word of initcode.
+    pub initcode_word: u64,
+    /// Cost per authorization tuple.
+    pub authorization: u64,
+}
+
+/// Frontier.
+pub const FRONTIER: IntrinsicGasSchedule = IntrinsicGasSchedule {
+    base: 21000,
+    data_zero: 4,
+    data_non_zero: 68,
+    create: 0,
+    access_list_address: 0,
+    access_list_storage_key: 0,
+    initcode_word: 0,
+    authorization: 0,
+};
+
+/// The parts of a transaction its intrinsic cost depends on, in any fork.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct IntrinsicGasInputs<'a> {
+    /// Call data, or initcode when creating a contract.
+    pub data: &'a [u8],
+    /// Whether the transaction creates a contract.
+    pub create: bool,
+    /// Number of addresses in the access list.
+    pub access_list_addresses: u64,
+    /// Number of storage keys in the access list, over all addresses.
+    pub access_list_storage_keys: u64,
+    /// Number of authorization tuples.
+    pub authorizations: u64,
+}
+
+impl<'a> From<&'a Transaction> for IntrinsicGasInputs<'a> {
+    fn from(tx: &'a Transaction) -> Self {
+        Self {
+            data: &tx.data,
+            create: tx.to.is_none(),
+            ..Default::default()
+        }
+    }
+}
+
+///
+///     Get the intrinsic gas rule set of a fork.
+///
+///     Parameters
+///     ----------
+///     fork :
+///         The fork.
+///
+///     Returns
+///     -------
+///     schedule : `IntrinsicGasSchedule`
+///         The prices the fork charges.
+///
+pub fn intrinsic_gas_schedule(fork: HardFork) -> &'static IntrinsicGasSchedule {
+    match fork {
+        HardFork::Frontier => &FRONTIER,
+    }
+}
+
+///
+///     Calculate the intrinsic cost of a transaction under a rule set.
+///
+///     Parameters
+///     ----------
+///     schedule :
+///         The rule set to price the transaction with.
+///     tx :
+///         The parts of the transaction the cost depends on.
+///
+///     Returns
+///     -------
+///     cost : `ethereum.base_types.Uint`
+///         The intrinsic cost of the transaction.
+///
+pub fn intrinsic_cost(schedule: &IntrinsicGasSchedule, tx: &IntrinsicGasInputs) -> Uint {
+    let zeros = tx.data.iter().filter(|byte| **byte == 0).count() as u64;
+    let non_zeros = tx.data.len() as u64 - zeros;
+
+    let mut cost = Uint::from(schedule.base);
+    cost += Uint::from(zeros) * schedule.data_zero;
+    cost += Uint::from(non_zeros) * schedule.data_non_zero;
+    if tx.create {
+        let initcode_words = ceil32(Uint::from(tx.data.len())) / 32u8;
+        cost += schedule.create;
+        cost += initcode_words * schedule.initcode_word;
+    }
+    cost += Uint::from(tx.access_list_addresses) * schedule.access_list_address;
+    cost += Uint::from(tx.access_list_storage_keys) * schedule.access_list_storage_key;
+    cost += Uint::from(tx.authorizations) * schedule.authorization;
+    cost
+}
+
+///
+///     Calculate the gas that is charged for a transaction before execution
+///     starts, as the given fork charges it.
+///
+///     Parameters
+///     ----------
+///     tx :
+///         The parts of the transaction the cost depends on. A fork's
+///         transaction converts into these with `into()`.
+///     fork :
+///         The fork whose rules apply.
+///
+///     Returns
+///     -------
+///     cost : `ethereum.base_types.Uint`
+///         The intrinsic cost of the transaction.
+///
+pub fn calculate_intrinsic_cost<'a>(tx: impl Into<IntrinsicGasInputs<'a>>, fork: HardFork) -> Uint {
+    intrinsic_cost(intrinsic_gas_schedule(fork), &tx.into())
+}
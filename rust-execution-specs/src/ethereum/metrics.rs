@@ -0,0 +1,236 @@
+//!
+//! # Metrics
+//!
+//! Counters, gauges and histograms of what the spec does, for watching the
+//! throughput of long runs, such as a sync, and how well its caches work.
+//! They are shared by the whole process, and [render] writes them in the
+//! text format Prometheus scrapes, see `ethereum_spec_tools::metrics` for
+//! the endpoint.
+//!
+//! Counting costs an atomic addition, in the interpreter an addition per
+//! instruction, so nothing is counted without the `metrics` feature.
+//!
+
+use std::{
+    fmt::Write,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex,
+    },
+};
+
+/// A metric, which renders itself in the Prometheus text format.
+pub trait Metric: Sync {
+    fn render(&self, out: &mut String);
+}
+
+fn header(out: &mut String, name: &str, help: &str, kind: &str) {
+    let _ = writeln!(out, "# HELP {name} {help}");
+    let _ = writeln!(out, "# TYPE {name} {kind}");
+}
+
+/// A count that only goes up.
+#[derive(Debug)]
+pub struct Counter {
+    name: &'static str,
+    help: &'static str,
+    value: AtomicU64,
+}
+
+impl Counter {
+    pub const fn new(name: &'static str, help: &'static str) -> Self {
+        Self {
+            name,
+            help,
+            value: AtomicU64::new(0),
+        }
+    }
+
+    pub fn increment(&self) {
+        self.add(1);
+    }
+
+    pub fn add(&self, amount: u64) {
+        self.value.fetch_add(amount, Ordering::Relaxed);
+    }
+
+    pub fn get(&self) -> u64 {
+        self.value.load(Ordering::Relaxed)
+    }
+}
+
+impl Metric for Counter {
+    fn render(&self, out: &mut String) {
+        header(out, self.name, self.help, "counter");
+        let _ = writeln!(out, "{} {}", self.name, self.get());
+    }
+}
+
+/// A value that goes up and down, as the last one set.
+#[derive(Debug)]
+pub struct Gauge {
+    name: &'static str,
+    help: &'static str,
+    /// The bits of the `f64`.
+    value: AtomicU64,
+}
+
+impl Gauge {
+    pub const fn new(name: &'static str, help: &'static str) -> Self {
+        Self {
+            name,
+            help,
+            value: AtomicU64::new(0),
+        }
+    }
+
+    pub fn set(&self, value: f64) {
+        self.value.store(value.to_bits(), Ordering::Relaxed);
+    }
+
+    pub fn get(&self) -> f64 {
+        f64::from_bits(self.value.load(Ordering::Relaxed))
+    }
+}
+
+impl Metric for Gauge {
+    fn render(&self, out: &mut String) {
+        header(out, self.name, self.help, "gauge");
+        let _ = writeln!(out, "{} {}", self.name, self.get());
+    }
+}
+
+/// The observations of a histogram so far.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Observations {
+    /// How many observations fell at or below each bound, and not below
+    /// the one before.
+    pub buckets: Vec<u64>,
+    pub sum: f64,
+    pub count: u64,
+}
+
+/// How the values observed are distributed among buckets.
+#[derive(Debug)]
+pub struct Histogram {
+    name: &'static str,
+    help: &'static str,
+    /// The upper bounds of the buckets, ascending. The last bucket, up to
+    /// infinity, is implied.
+    bounds: &'static [f64],
+    observations: Mutex<Observations>,
+}
+
+impl Histogram {
+    pub const fn new(name: &'static str, help: &'static str, bounds: &'static [f64]) -> Self {
+        Self {
+            name,
+            help,
+            bounds,
+            observations: Mutex::new(Observations {
+                buckets: Vec::new(),
+                sum: 0.0,
+                count: 0,
+            }),
+        }
+    }
+
+    pub fn observe(&self, value: f64) {
+        let mut observations = self.observations();
+        if observations.buckets.is_empty() {
+            observations.buckets = vec![0; self.bounds.len()];
+        }
+        if let Some(bucket) = self.bounds.iter().position(|bound| value <= *bound) {
+            observations.buckets[bucket] += 1;
+        }
+        observations.sum += value;
+        observations.count += 1;
+    }
+
+    pub fn get(&self) -> Observations {
+        self.observations().clone()
+    }
+
+    fn observations(&self) -> std::sync::MutexGuard<'_, Observations> {
+        self.observations
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl Metric for Histogram {
+    fn render(&self, out: &mut String) {
+        header(out, self.name, self.help, "histogram");
+        let observations = self.get();
+        // Prometheus buckets are cumulative.
+        let mut cumulative = 0;
+        for (n, bound) in self.bounds.iter().enumerate() {
+            cumulative += observations.buckets.get(n).copied().unwrap_or_default();
+            let _ = writeln!(out, "{}_bucket{{le=\"{bound}\"}} {cumulative}", self.name);
+        }
+        let _ = writeln!(out, "{}_bucket{{le=\"+Inf\"}} {}", self.name, observations.count);
+        let _ = writeln!(out, "{}_sum {}", self.name, observations.sum);
+        let _ = writeln!(out, "{}_count {}", self.name, observations.count);
+    }
+}
+
+/// Instructions the interpreter executed.
+pub static EVM_INSTRUCTIONS: Counter = Counter::new("evm_instructions_total", "Instructions executed.");
+
+/// Frames the interpreter ran, calls and creates, precompiles included.
+pub static EVM_MESSAGES: Counter = Counter::new("evm_messages_total", "Message calls and creates executed.");
+
+/// Storage roots found computed already when the state root was computed.
+pub static STORAGE_ROOT_CACHE_HITS: Counter = Counter::new(
+    "state_storage_root_cache_hits_total",
+    "Storage roots the state had computed already.",
+);
+
+/// Storage roots computed when the state root was computed.
+pub static STORAGE_ROOT_CACHE_MISSES: Counter = Counter::new(
+    "state_storage_root_cache_misses_total",
+    "Storage roots the state computed.",
+);
+
+/// Reads through a `CachedBackend` found in its cache.
+pub static BACKEND_CACHE_HITS: Counter = Counter::new(
+    "backend_cache_hits_total",
+    "Reads of cached state backends served from the cache.",
+);
+
+/// Reads through a `CachedBackend` that went to the store behind it.
+pub static BACKEND_CACHE_MISSES: Counter = Counter::new(
+    "backend_cache_misses_total",
+    "Reads of cached state backends served from the store behind.",
+);
+
+/// The metrics of the spec itself.
+pub static SPEC_METRICS: &[&dyn Metric] = &[
+    &EVM_INSTRUCTIONS,
+    &EVM_MESSAGES,
+    &STORAGE_ROOT_CACHE_HITS,
+    &STORAGE_ROOT_CACHE_MISSES,
+    &BACKEND_CACHE_HITS,
+    &BACKEND_CACHE_MISSES,
+];
+
+///
+///     Render metrics in the Prometheus text format.
+///
+///     Parameters
+///     ----------
+///     metrics :
+///         The metrics, such as [SPEC_METRICS].
+///
+///     Returns
+///     -------
+///     text : `String`
+///         Each metric with its help and type.
+///
+pub fn render(metrics: &[&dyn Metric]) -> String {
+    let mut out = String::new();
+    for metric in metrics {
+        metric.render(&mut out);
+    }
+    out
+}
@@ -0,0 +1,90 @@
+//!
+//! # Mining Reward
+//!
+//! ## Introduction
+//!
+//! The ether created for the miner of a block and the miners of the ommers it
+//! includes, which `pay_rewards` adds to the state at the end of a block.
+//!
+//! The miner is paid the block reward, and a thirty-second of it for each
+//! ommer included. The miner of an ommer `n` blocks older than the block is
+//! paid `(8 - n) / 8` of the block reward. The amount of the block reward is
+//! a [RewardSchedule]; Frontier's is 5 ether.
+//!
+
+use crate::ethereum::base_types::{Uint, U256};
+use crate::ethereum::hard_fork::HardFork;
+
+/// The rewards of one rule set.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RewardSchedule {
+    /// Reward for mining a block, in wei.
+    pub block_reward: u64,
+}
+
+/// Frontier.
+pub const FRONTIER: RewardSchedule = RewardSchedule {
+    block_reward: 5_000_000_000_000_000_000,
+};
+
+///
+///     Get the reward rule set of a fork.
+///
+///     Parameters
+///     ----------
+///     fork :
+///         The fork.
+///
+///     Returns
+///     -------
+///     schedule : `RewardSchedule`
+///         The rewards the fork pays.
+///
+pub fn reward_schedule(fork: HardFork) -> &'static RewardSchedule {
+    match fork {
+        HardFork::Frontier => &FRONTIER,
+    }
+}
+
+///
+///     Calculate the reward of the miner of a block.
+///
+///     Parameters
+///     ----------
+///     schedule :
+///         The rule set of the fork.
+///     ommer_count :
+///         Number of ommers the block includes.
+///
+///     Returns
+///     -------
+///     reward : `ethereum.base_types.U256`
+///         The ether created for the miner.
+///
+pub fn miner_reward(schedule: &RewardSchedule, ommer_count: usize) -> U256 {
+    let block_reward = U256::from(schedule.block_reward);
+    &block_reward + ommer_count * (&block_reward / 32u8)
+}
+
+///
+///     Calculate the reward of the miner of an ommer.
+///
+///     Parameters
+///     ----------
+///     schedule :
+///         The rule set of the fork.
+///     block_number :
+///         Number of the block including the ommer.
+///     ommer_number :
+///         Number of the ommer. It must be at most 7 blocks older than the
+///         block, which `validate_ommers` makes sure of.
+///
+///     Returns
+///     -------
+///     reward : `ethereum.base_types.U256`
+///         The ether created for the ommer's miner.
+///
+pub fn ommer_reward(schedule: &RewardSchedule, block_number: &Uint, ommer_number: &Uint) -> U256 {
+    let ommer_age = block_number - ommer_number;
+    (U256::from(8u8) - ommer_age) * schedule.block_reward / 8u8
+}
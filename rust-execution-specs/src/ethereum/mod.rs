@@ -17,14 +17,41 @@
 //! possible, to aid in defining the behavior of Ethereum clients.
 //!
 
+pub mod access_list;
+pub mod backend;
+pub mod base_fee;
 pub mod base_types;
+pub mod clique;
+pub mod create_policy;
+pub mod crypto;
 pub mod exceptions;
+pub mod fee_summary;
+pub mod fork_constants;
 pub mod rlp;
 pub mod genesis;
+pub mod hard_fork;
 pub mod ethash;
+pub mod intrinsic_gas;
+#[cfg(feature = "metrics")]
+pub mod metrics;
+pub mod mining_reward;
+pub mod selfdestruct;
+pub mod storage_gas;
+pub mod trace;
+pub mod transaction_signature;
+pub mod transient_storage;
+pub mod experimental;
+#[cfg(feature = "arbitrary")]
+pub mod arbitrary;
 
 // Helpers
 pub mod utils;
 
 // Hardforks
 pub mod frontier;
+#[cfg(feature = "eof")]
+pub mod eof;
+
+// Execution profiles
+#[cfg(feature = "zkvm")]
+pub mod zkvm;
@@ -5,10 +5,11 @@
 //! Defines the serialization and deserialization format used throughout Ethereum.
 //!
 
-use super::{base_types::{strip_leading_zeros, Bytes, Uint, U32, U64}, frontier::fork_types::{keccak256, Hash32}};
+use crate::prelude::*;
+use super::{base_types::{strip_leading_zeros, Bytes, Uint, U32, U64}, exceptions::EthereumException, frontier::fork_types::{keccak256, Hash32}, utils::ensure::ensure};
 
 /// Trait for converting objects to RLP-encoded byte arrays.
-pub trait RLP : std::fmt::Debug {
+pub trait RLP : core::fmt::Debug {
     /// Encode an object into some Bytes.
     fn encode(&self) -> Bytes;
 }
@@ -262,4 +263,199 @@ pub fn encode_sequence(joined_encodings: &[u8]) -> Bytes {
 pub fn rlp_hash<R: ?Sized + RLP>(raw_data: &R) -> Hash32{
     let data = encode(raw_data);
     return keccak256(&data)
-}
\ No newline at end of file
+}
+
+
+///
+///     A decoded RLP item: either a byte string or a sequence of items.
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Simple {
+    Bytes(Bytes),
+    Sequence(Vec<Simple>),
+}
+
+impl RLP for Simple {
+    fn encode(&self) -> Bytes {
+        match self {
+            Simple::Bytes(bytes) => encode_bytes(bytes),
+            Simple::Sequence(items) => items.encode(),
+        }
+    }
+}
+
+//
+// RLP Decode
+//
+
+///
+///     Decodes an integer, byte sequence, or list of RLP encodable objects
+///     from the byte sequence `encoded_data`, using RLP.
+///
+///     Parameters
+///     ----------
+///     encoded_data :
+///         A sequence of bytes, in RLP form.
+///
+///     Returns
+///     -------
+///     decoded_data : `RLP`
+///         Object decoded from `encoded_data`.
+///
+pub fn decode(encoded_data: &[u8]) -> Result<Simple, EthereumException> {
+    ensure(!encoded_data.is_empty(), EthereumException::RLPDecodingError)?;
+    if encoded_data[0] <= 0xbf {
+        Ok(Simple::Bytes(decode_to_bytes(encoded_data)?.into()))
+    } else {
+        Ok(Simple::Sequence(decode_to_sequence(encoded_data)?))
+    }
+}
+
+///
+///     Decodes a rlp encoded byte stream assuming that the decoded data
+///     should be of type `bytes`.
+///
+///     Parameters
+///     ----------
+///     encoded_bytes :
+///         RLP encoded byte stream.
+///
+///     Returns
+///     -------
+///     decoded : `ethereum.base_types.Bytes`
+///         RLP decoded Bytes data
+///
+pub fn decode_to_bytes(encoded_bytes: &[u8]) -> Result<&[u8], EthereumException> {
+    if encoded_bytes[0] < 0x80 {
+        // Trailing bytes are ignored, as for every other top level item.
+        Ok(&encoded_bytes[..1])
+    } else if encoded_bytes[0] <= 0xb7 {
+        let len_raw_data = (encoded_bytes[0] - 0x80) as usize;
+        ensure(len_raw_data < encoded_bytes.len(), EthereumException::RLPDecodingError)?;
+        let raw_data = &encoded_bytes[1..1 + len_raw_data];
+        ensure(!(len_raw_data == 1 && raw_data[0] < 0x80), EthereumException::RLPDecodingError)?;
+        Ok(raw_data)
+    } else {
+        // This is the index in the encoded data at which decoded data
+        // starts from.
+        let decoded_data_start_idx = 1 + (encoded_bytes[0] - 0xb7) as usize;
+        let len_decoded_data = decode_length(encoded_bytes, decoded_data_start_idx)?;
+        let decoded_data_end_idx = decoded_data_start_idx + len_decoded_data;
+        ensure(decoded_data_end_idx - 1 < encoded_bytes.len(), EthereumException::RLPDecodingError)?;
+        Ok(&encoded_bytes[decoded_data_start_idx..decoded_data_end_idx])
+    }
+}
+
+///
+///     Decodes a rlp encoded byte stream assuming that the decoded data
+///     should be of type `Sequence` of objects.
+///
+///     Parameters
+///     ----------
+///     encoded_sequence :
+///         An RLP encoded Sequence.
+///
+///     Returns
+///     -------
+///     decoded : `Sequence[RLP]`
+///         Sequence of RLP decoded objects.
+///
+pub fn decode_to_sequence(encoded_sequence: &[u8]) -> Result<Vec<Simple>, EthereumException> {
+    let joined_encodings = if encoded_sequence[0] <= 0xf7 {
+        let len_joined_encodings = (encoded_sequence[0] - 0xc0) as usize;
+        ensure(len_joined_encodings < encoded_sequence.len(), EthereumException::RLPDecodingError)?;
+        &encoded_sequence[1..1 + len_joined_encodings]
+    } else {
+        let joined_encodings_start_idx = 1 + (encoded_sequence[0] - 0xf7) as usize;
+        let len_joined_encodings = decode_length(encoded_sequence, joined_encodings_start_idx)?;
+        let joined_encodings_end_idx = joined_encodings_start_idx + len_joined_encodings;
+        ensure(joined_encodings_end_idx - 1 < encoded_sequence.len(), EthereumException::RLPDecodingError)?;
+        &encoded_sequence[joined_encodings_start_idx..joined_encodings_end_idx]
+    };
+    decode_joined_encodings(joined_encodings)
+}
+
+///
+///     Decodes `joined_encodings`, which is a concatenation of RLP encoded
+///     objects.
+///
+///     Parameters
+///     ----------
+///     joined_encodings :
+///         concatenation of RLP encoded objects
+///
+///     Returns
+///     -------
+///     decoded : `List[RLP]`
+///         A list of objects decoded from `joined_encodings`.
+///
+pub fn decode_joined_encodings(joined_encodings: &[u8]) -> Result<Vec<Simple>, EthereumException> {
+    let mut decoded_sequence = vec![];
+    let mut item_start_idx = 0;
+    while item_start_idx < joined_encodings.len() {
+        let encoded_item_length = decode_item_length(&joined_encodings[item_start_idx..])?;
+        let item_end_idx = item_start_idx + encoded_item_length;
+        ensure(item_end_idx - 1 < joined_encodings.len(), EthereumException::RLPDecodingError)?;
+        let encoded_item = &joined_encodings[item_start_idx..item_end_idx];
+        decoded_sequence.push(decode(encoded_item)?);
+        item_start_idx = item_end_idx;
+    }
+    Ok(decoded_sequence)
+}
+
+///
+///     Find the length of the rlp encoding for the first object in the
+///     encoded sequence.
+///     Here `encoded_data` refers to concatenation of rlp encoding for each
+///     item in a sequence.
+///
+///     NOTE - This is a helper function not described in the spec. It was
+///     introduced as the spec doesn't discuss about decoding the RLP encoded
+///     data.
+///
+///     Parameters
+///     ----------
+///     encoded_data :
+///         RLP encoded data for a sequence of objects.
+///
+///     Returns
+///     -------
+///     rlp_length : `int`
+///
+pub fn decode_item_length(encoded_data: &[u8]) -> Result<usize, EthereumException> {
+    ensure(!encoded_data.is_empty(), EthereumException::RLPDecodingError)?;
+    let first_rlp_byte = encoded_data[0];
+    let (length_length, decoded_data_length) = match first_rlp_byte {
+        0x00..=0x7f => return Ok(1),
+        0x80..=0xb7 => (0, (first_rlp_byte - 0x80) as usize),
+        0xb8..=0xbf => {
+            let length_length = (first_rlp_byte - 0xb7) as usize;
+            (length_length, decode_length(encoded_data, 1 + length_length)?)
+        }
+        0xc0..=0xf7 => (0, (first_rlp_byte - 0xc0) as usize),
+        0xf8..=0xff => {
+            let length_length = (first_rlp_byte - 0xf7) as usize;
+            (length_length, decode_length(encoded_data, 1 + length_length)?)
+        }
+    };
+    1usize
+        .checked_add(length_length)
+        .and_then(|len| len.checked_add(decoded_data_length))
+        .ok_or(EthereumException::RLPDecodingError)
+}
+
+// Reads the big endian length stored in `encoded_data[1..start_idx]` of a
+// long string or long list, rejecting non-canonical encodings.
+fn decode_length(encoded_data: &[u8], start_idx: usize) -> Result<usize, EthereumException> {
+    ensure(start_idx - 1 < encoded_data.len(), EthereumException::RLPDecodingError)?;
+    // Expectation is that the big endian bytes shouldn't start with 0
+    // while trying to decode using RLP, in which case is an error.
+    ensure(encoded_data[1] != 0, EthereumException::RLPDecodingError)?;
+    let length_bytes = &encoded_data[1..start_idx];
+    ensure(length_bytes.len() <= core::mem::size_of::<usize>(), EthereumException::RLPDecodingError)?;
+    let length = length_bytes.iter().fold(0usize, |acc, byte| acc << 8 | *byte as usize);
+    ensure(length >= 0x38, EthereumException::RLPDecodingError)?;
+    // Guard the `start_idx + length` computed by the callers.
+    ensure(length < usize::MAX - start_idx, EthereumException::RLPDecodingError)?;
+    Ok(length)
+}
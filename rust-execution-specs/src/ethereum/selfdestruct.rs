@@ -0,0 +1,141 @@
+//!
+//! # Selfdestruct
+//!
+//! ## Introduction
+//!
+//! The cost, refund and effect of `SELFDESTRUCT`, which the instruction
+//! charges through [selfdestruct_gas] and the interpreter refunds through
+//! [selfdestruct_refund].
+//!
+//! Frontier charges nothing for the instruction, refunds each account
+//! destroyed in a transaction, and credits the balance to the beneficiary
+//! before zeroing the originator, so an account naming itself burns its
+//! ether. A [SelfdestructSchedule] can also describe what later forks
+//! changed: a price and a new account surcharge (EIP-150), charging that
+//! surcharge only for dead beneficiaries that receive value and clearing the
+//! touched empty accounts (EIP-161), removing the refund (EIP-3529), and
+//! destroying only the accounts created in the same transaction (EIP-6780),
+//! which makes a self-destruct to itself keep its balance.
+//!
+
+use crate::ethereum::{
+    base_types::{Uint, U256},
+    frontier::fork_types::{Account, EMPTY_ACCOUNT},
+};
+
+/// Which beneficiaries `SELFDESTRUCT` pays the new account surcharge for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NewAccount {
+    /// A beneficiary that doesn't exist, EIP-150.
+    Nonexistent,
+    /// A beneficiary that doesn't exist or is empty, when the balance sent
+    /// isn't zero, EIP-161.
+    DeadWithValue,
+}
+
+/// The rules and prices of `SELFDESTRUCT` of a fork.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SelfdestructSchedule {
+    /// Cost of the instruction.
+    pub cost: u64,
+    /// Surcharge for sending the balance to a new account.
+    pub new_account_cost: u64,
+    /// Which beneficiaries count as new accounts.
+    pub new_account: NewAccount,
+    /// Refund for each account destroyed in a transaction.
+    pub refund: u64,
+    /// Whether the accounts a transaction touched and left empty, the
+    /// beneficiary among them, are cleared at its end.
+    pub clear_touched: bool,
+    /// Whether only an account created in the same transaction is
+    /// destroyed. Any other only sends its balance to the beneficiary.
+    pub only_in_creating_transaction: bool,
+}
+
+/// Frontier.
+pub const FRONTIER: SelfdestructSchedule = SelfdestructSchedule {
+    cost: 0,
+    new_account_cost: 0,
+    new_account: NewAccount::Nonexistent,
+    refund: 24000,
+    clear_touched: false,
+    only_in_creating_transaction: false,
+};
+
+///
+///     Calculate the gas charged for `SELFDESTRUCT`.
+///
+///     Parameters
+///     ----------
+///     schedule :
+///         The rules of the fork.
+///     value :
+///         Balance of the account self-destructing.
+///     beneficiary :
+///         Looks up the beneficiary, if it exists. Only called when the
+///         fork has a new account surcharge.
+///
+///     Returns
+///     -------
+///     cost : `u64`
+///         The gas to charge.
+///
+pub fn selfdestruct_gas(
+    schedule: &SelfdestructSchedule,
+    value: &U256,
+    beneficiary: impl FnOnce() -> Option<Account>,
+) -> u64 {
+    if schedule.new_account_cost == 0 {
+        return schedule.cost;
+    }
+    let is_new = match schedule.new_account {
+        NewAccount::Nonexistent => beneficiary().is_none(),
+        NewAccount::DeadWithValue => {
+            *value != U256::from(0u8) && beneficiary().is_none_or(|account| account == EMPTY_ACCOUNT())
+        }
+    };
+    if is_new {
+        schedule.cost + schedule.new_account_cost
+    } else {
+        schedule.cost
+    }
+}
+
+///
+///     Calculate the refund for the accounts destroyed by a transaction.
+///
+///     Parameters
+///     ----------
+///     schedule :
+///         The rules of the fork.
+///     destroyed :
+///         Number of distinct accounts destroyed.
+///
+///     Returns
+///     -------
+///     refund : `Uint`
+///         The gas added to the refund counter.
+///
+pub fn selfdestruct_refund(schedule: &SelfdestructSchedule, destroyed: usize) -> Uint {
+    Uint::from(schedule.refund) * destroyed
+}
+
+///
+///     Decide whether `SELFDESTRUCT` destroys the account executing it.
+///
+///     Parameters
+///     ----------
+///     schedule :
+///         The rules of the fork.
+///     created_in_transaction :
+///         Whether the account was created in the current transaction. Only
+///         called when the fork restricts destruction to those.
+///
+///     Returns
+///     -------
+///     destroys : `bool`
+///         True if the account is destroyed at the end of the transaction.
+///
+pub fn selfdestruct_destroys(schedule: &SelfdestructSchedule, created_in_transaction: impl FnOnce() -> bool) -> bool {
+    !schedule.only_in_creating_transaction || created_in_transaction()
+}
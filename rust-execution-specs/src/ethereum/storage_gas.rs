@@ -0,0 +1,166 @@
+//!
+//! # Storage Gas
+//!
+//! ## Introduction
+//!
+//! The dynamic cost and refund of `SSTORE`, which the instruction charges
+//! through [sstore_gas] and [sstore_allowed].
+//!
+//! Frontier prices a store by the current value of the slot alone: setting a
+//! zero slot costs more than any other change, and clearing a slot earns a
+//! refund. A [StorageGasSchedule] can also describe the "net metered" rules
+//! of EIP-1283 and its successors, where the cost and refund also depend on
+//! the value the slot had when the transaction started (its *original*
+//! value), so that writing a slot back and forth within a transaction costs
+//! no more than the final change.
+//!
+
+use crate::ethereum::base_types::U256;
+
+/// The rules and prices of one `SSTORE` rule set.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct StorageGasSchedule {
+    /// Whether costs depend on the original value of the slot.
+    pub net_metering: bool,
+    /// Cost of a store that doesn't change the slot, or changes a slot that
+    /// is already dirty. Only used with net metering.
+    pub no_op: u64,
+    /// Cost of setting a clean slot from zero.
+    pub set: u64,
+    /// Cost of any other change of a clean slot.
+    pub reset: u64,
+    /// Refund for clearing a slot.
+    pub clear_refund: u64,
+    /// Surcharge for the first access to the slot in the transaction.
+    pub cold_access: u64,
+    /// A store fails if no more than this much gas is left (EIP-2200).
+    pub sentry: Option<u64>,
+}
+
+/// Frontier.
+pub const FRONTIER: StorageGasSchedule = StorageGasSchedule {
+    net_metering: false,
+    no_op: 0,
+    set: 20000,
+    reset: 5000,
+    clear_refund: 15000,
+    cold_access: 0,
+    sentry: None,
+};
+
+/// The gas charged for a store, and the change it makes to the refund
+/// counter. The refund can be negative when a store undoes an earlier
+/// refunded clear.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SstoreGas {
+    pub cost: u64,
+    pub refund: i64,
+}
+
+///
+///     Calculate the cost and refund of storing `new` in a slot.
+///
+///     Parameters
+///     ----------
+///     schedule :
+///         The rule set of the fork.
+///     original :
+///         Value of the slot at the start of the transaction.
+///     current :
+///         Value of the slot before this store.
+///     new :
+///         Value being stored.
+///     is_cold :
+///         Whether this is the first access to the slot in the transaction.
+///
+///     Returns
+///     -------
+///     gas : `SstoreGas`
+///         The cost to charge and the change to the refund counter.
+///
+pub fn sstore_gas(
+    schedule: &StorageGasSchedule,
+    original: &U256,
+    current: &U256,
+    new: &U256,
+    is_cold: bool,
+) -> SstoreGas {
+    let zero = U256::from(0u8);
+    let mut gas = SstoreGas::default();
+    if is_cold {
+        gas.cost += schedule.cold_access;
+    }
+
+    if !schedule.net_metering {
+        gas.cost += if *current == zero && *new != zero {
+            schedule.set
+        } else {
+            schedule.reset
+        };
+        if *current != zero && *new == zero {
+            gas.refund += schedule.clear_refund as i64;
+        }
+        return gas;
+    }
+
+    if current == new {
+        gas.cost += schedule.no_op;
+        return gas;
+    }
+
+    if original == current {
+        // The slot is clean.
+        if *original == zero {
+            gas.cost += schedule.set;
+        } else {
+            gas.cost += schedule.reset;
+            if *new == zero {
+                gas.refund += schedule.clear_refund as i64;
+            }
+        }
+        return gas;
+    }
+
+    // The slot is dirty: the first change has been paid for already.
+    gas.cost += schedule.no_op;
+    if *original != zero {
+        if *current == zero {
+            // Take back the refund for clearing the slot earlier.
+            gas.refund -= schedule.clear_refund as i64;
+        } else if *new == zero {
+            gas.refund += schedule.clear_refund as i64;
+        }
+    }
+    if original == new {
+        // The slot is restored: refund what was charged on top of a no-op.
+        if *original == zero {
+            gas.refund += (schedule.set - schedule.no_op) as i64;
+        } else {
+            gas.refund += (schedule.reset - schedule.no_op) as i64;
+        }
+    }
+    gas
+}
+
+///
+///     Check the EIP-2200 stipend sentry, which stops calls made with only
+///     the call stipend from changing storage.
+///
+///     Parameters
+///     ----------
+///     schedule :
+///         The rule set of the fork.
+///     gas_left :
+///         Gas left in the frame, before charging the store.
+///
+///     Returns
+///     -------
+///     allowed : `bool`
+///         False if the store must fail with out of gas.
+///
+pub fn sstore_allowed(schedule: &StorageGasSchedule, gas_left: &U256) -> bool {
+    match schedule.sentry {
+        Some(sentry) => *gas_left > U256::from(sentry),
+        None => true,
+    }
+}
@@ -0,0 +1,64 @@
+//!
+//! # EVM Trace
+//!
+//! ## Introduction
+//!
+//! Defines the functions required for creating EVM traces during execution.
+//!
+//! A fork's interpreter reports [TraceEvent]s to an optional [Tracer] stored
+//! in its `Environment`. Nothing is traced unless a tracer is installed.
+//!
+//! A tracer only looks at the frame, unless it overrides [Tracer::trace_mut],
+//! which can change it between opcodes, as a debugger setting the gas left
+//! does.
+//!
+
+use crate::prelude::*;
+use super::base_types::{Bytes, Uint};
+
+/// Something the interpreter reports while executing.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TraceEvent {
+    /// The top level message call finished.
+    TransactionEnd {
+        gas_used: Uint,
+        output: Bytes,
+        error: Option<String>,
+    },
+    /// A message starts running in a new frame. `depth` is `0` for the
+    /// message of the transaction and goes up by one for every nested call
+    /// or create.
+    MessageStart { depth: Uint },
+    /// The frame of a message finished, and its state changes were either
+    /// kept or, on `error`, rolled back. A create finishes once its code is
+    /// deposited.
+    MessageEnd { depth: Uint, error: Option<String> },
+    /// A precompiled contract is about to run.
+    PrecompileStart { address: Bytes },
+    /// A precompiled contract finished without an exceptional halt.
+    PrecompileEnd,
+    /// An opcode is about to run. Reported before any of its effects.
+    OpStart { op: u8 },
+    /// The last opcode finished without an exceptional halt.
+    OpEnd,
+    /// The frame halted exceptionally.
+    OpException { error: String },
+    /// The frame stopped running, either because an opcode stopped it or
+    /// because execution ran past the end of the code (an implicit `op`).
+    EvmStop { op: u8 },
+    /// Gas was charged.
+    GasAndRefund { gas_cost: Uint },
+}
+
+/// Receives [TraceEvent]s from an interpreter. `E` is the fork's `Evm`, so the
+/// tracer can look at the frame (pc, stack, memory, ...) the event happened
+/// in.
+pub trait Tracer<E> {
+    fn trace(&mut self, evm: &E, event: &TraceEvent);
+
+    /// Receive an event with the frame mutable. This is what the interpreter
+    /// calls; by default it hands the event to [Tracer::trace].
+    fn trace_mut(&mut self, evm: &mut E, event: &TraceEvent) {
+        self.trace(evm, event);
+    }
+}
@@ -0,0 +1,211 @@
+//!
+//! # Transaction Signature
+//!
+//! ## Introduction
+//!
+//! Signing legacy transactions and recovering their senders.
+//!
+//! A Frontier signature is over the six fields of the transaction, with `v`
+//! being 27 or 28, and is valid on every chain. The replay protection of
+//! EIP-155 signs the chain id as well, and commits to it in `v` as
+//! `35 + 2 * chain_id` or one more; a [SignatureSchedule] says whether such
+//! signatures are accepted, which Frontier's doesn't. The engine API
+//! recovers the senders of a payload with [recover_sender] and the schedule
+//! of its fork.
+//!
+//! [sign_transaction] makes either kind of signature, for writing tests and
+//! fixtures.
+//!
+
+use crate::ethereum::{
+    base_types::{Bytes32, U256, U64},
+    crypto::elliptic_curve::{secp256k1_recover, secp256k1_sign, SECP256K1N},
+    exceptions::EthereumException,
+    frontier::fork_types::{keccak256, Address, Hash32, Transaction},
+    rlp,
+    utils::ensure::ensure,
+};
+use crate::ethereum::hard_fork::HardFork;
+
+/// The signatures one rule set accepts.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SignatureSchedule {
+    /// Whether signatures may commit to a chain id (EIP-155).
+    pub eip155: bool,
+}
+
+/// Frontier.
+pub const FRONTIER: SignatureSchedule = SignatureSchedule { eip155: false };
+
+///
+///     Get the signature rule set of a fork.
+///
+///     Parameters
+///     ----------
+///     fork :
+///         The fork.
+///
+///     Returns
+///     -------
+///     schedule : `SignatureSchedule`
+///         The signatures the fork accepts.
+///
+pub fn signature_schedule(fork: HardFork) -> &'static SignatureSchedule {
+    match fork {
+        HardFork::Frontier => &FRONTIER,
+    }
+}
+
+fn encoded_to(tx: &Transaction) -> &[u8] {
+    match &tx.to {
+        Some(to) => to,
+        None => &[],
+    }
+}
+
+///
+///     Compute the hash of a transaction used in a signature that doesn't
+///     commit to a chain.
+///
+///     Parameters
+///     ----------
+///     tx :
+///         Transaction of interest.
+///
+///     Returns
+///     -------
+///     hash : `ethereum.crypto.hash.Hash32`
+///         Hash of the transaction.
+///
+pub fn signing_hash_pre155(tx: &Transaction) -> Hash32 {
+    keccak256(&rlp::encode(&(&tx.nonce, &tx.gas_price, &tx.gas, encoded_to(tx), &tx.value, &tx.data)))
+}
+
+///
+///     Compute the hash of a transaction used in an EIP-155 signature.
+///
+///     Parameters
+///     ----------
+///     tx :
+///         Transaction of interest.
+///     chain_id :
+///         The id of the chain the signature is valid on.
+///
+///     Returns
+///     -------
+///     hash : `ethereum.crypto.hash.Hash32`
+///         Hash of the transaction.
+///
+pub fn signing_hash_155(tx: &Transaction, chain_id: U64) -> Hash32 {
+    let empty: &[u8] = &[];
+    keccak256(&rlp::encode(&(
+        &tx.nonce,
+        &tx.gas_price,
+        &tx.gas,
+        encoded_to(tx),
+        &tx.value,
+        &tx.data,
+        U256::from(chain_id),
+        empty,
+        empty,
+    )))
+}
+
+///
+///     Find the chain a transaction's signature commits to.
+///
+///     Parameters
+///     ----------
+///     tx :
+///         Transaction of interest.
+///
+///     Returns
+///     -------
+///     chain_id : `Option<U64>`
+///         The chain id of an EIP-155 signature, `None` for a signature
+///         that is valid on every chain, or a malformed `v`.
+///
+pub fn signature_chain_id(tx: &Transaction) -> Option<U64> {
+    if tx.v < U256::from(35u8) {
+        return None;
+    }
+    u64::try_from((&tx.v - 35u8) / 2u8).ok()
+}
+
+///
+///     Extracts the sender address from a legacy transaction.
+///
+///     Parameters
+///     ----------
+///     schedule :
+///         The signature rule set of the fork.
+///     chain_id :
+///         The id of the chain the transaction is included on. Only EIP-155
+///         signatures for this chain are accepted.
+///     tx :
+///         Transaction of interest.
+///
+///     Returns
+///     -------
+///     sender : `ethereum.fork_types.Address`
+///         The address of the account that signed the transaction.
+///
+pub fn recover_sender(
+    schedule: &SignatureSchedule,
+    chain_id: U64,
+    tx: &Transaction,
+) -> Result<Address, EthereumException> {
+    let (v, r, s) = (&tx.v, &tx.r, &tx.s);
+    let zero = U256::from(0u8);
+    ensure(zero < *r && *r < SECP256K1N(), EthereumException::InvalidBlock)?;
+    ensure(zero < *s && *s < SECP256K1N(), EthereumException::InvalidBlock)?;
+
+    let protected = U256::from(35u8) + 2u8 * U256::from(chain_id);
+    let (recovery_id, hash) = if *v == U256::from(27u8) || *v == U256::from(28u8) {
+        (v - 27u8, signing_hash_pre155(tx))
+    } else if schedule.eip155 && (*v == protected || *v == &protected + 1u8) {
+        (v - protected, signing_hash_155(tx, chain_id))
+    } else {
+        return Err(EthereumException::InvalidBlock);
+    };
+
+    let public_key =
+        secp256k1_recover(r, s, &recovery_id, &hash).map_err(|_| EthereumException::InvalidBlock)?;
+    Ok(keccak256(&public_key)[12..32].try_into().unwrap())
+}
+
+///
+///     Sign a legacy transaction.
+///
+///     Parameters
+///     ----------
+///     tx :
+///         The transaction. Its signature is ignored.
+///     private_key :
+///         The key of the sender.
+///     chain_id :
+///         The chain to sign for with EIP-155, or `None` for a signature
+///         that is valid on every chain.
+///
+///     Returns
+///     -------
+///     signed : `ethereum.fork_types.Transaction`
+///         The transaction with `v`, `r` and `s` set.
+///
+pub fn sign_transaction(
+    tx: &Transaction,
+    private_key: &Bytes32,
+    chain_id: Option<U64>,
+) -> Result<Transaction, EthereumException> {
+    let (hash, v_offset) = match chain_id {
+        Some(chain_id) => (signing_hash_155(tx, chain_id), U256::from(35u8) + 2u8 * U256::from(chain_id)),
+        None => (signing_hash_pre155(tx), U256::from(27u8)),
+    };
+    let (r, s, recovery_id) = secp256k1_sign(&hash, private_key)?;
+    Ok(Transaction {
+        v: v_offset + recovery_id,
+        r,
+        s,
+        ..tx.clone()
+    })
+}
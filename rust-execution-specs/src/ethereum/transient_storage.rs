@@ -0,0 +1,152 @@
+//!
+//! # Transient Storage
+//!
+//! ## Introduction
+//!
+//! The storage read and written by `TLOAD` and `TSTORE` (EIP-1153), shared
+//! by the forks from Cancun on.
+//!
+//! Transient storage is keyed like account storage, but it is never written
+//! to the state trie: it lasts for one transaction and is then discarded.
+//! Within the transaction it follows the call frames. A frame that reverts
+//! undoes its writes, and those of the frames it called, just as it does
+//! for the state.
+//!
+//! Rather than copying the whole storage when a frame starts, as the
+//! [state](crate::ethereum::frontier::state) does, each frame keeps a
+//! journal of the values it overwrote, which a revert puts back.
+//!
+//! Only Frontier is implemented, and it has neither opcode. A fork adding
+//! them keeps a [TransientStorage] for the transaction, calls
+//! [begin_transaction], [commit_transaction] and [rollback_transaction]
+//! wherever it does for the state, charges [GAS_WARM_ACCESS] for either
+//! opcode, and fails a `TSTORE` in a static frame.
+//!
+
+use crate::prelude::*;
+
+use crate::ethereum::{
+    base_types::{Bytes32, U256},
+    frontier::fork_types::Address,
+};
+
+/// `TLOAD`, EIP-1153.
+pub const TLOAD: u8 = 0x5C;
+/// `TSTORE`, EIP-1153.
+pub const TSTORE: u8 = 0x5D;
+/// The cost of either opcode, whatever the slot holds.
+pub const GAS_WARM_ACCESS: u64 = 100;
+
+/// The value a slot held before a frame wrote to it.
+#[derive(Clone, Debug)]
+struct JournalEntry {
+    address: Address,
+    key: Bytes32,
+    previous: U256,
+}
+
+/// Contains all information that is preserved between message calls within
+/// a transaction.
+#[derive(Clone, Debug, Default)]
+pub struct TransientStorage {
+    slots: HashMap<(Address, Bytes32), U256>,
+    /// The writes of each open frame, innermost last.
+    journals: Vec<Vec<JournalEntry>>,
+}
+
+/// Start a frame, whose writes can be rolled back.
+///
+/// Parameters
+/// ----------
+/// transient_storage : `TransientStorage`
+///     The transient storage.
+pub fn begin_transaction(transient_storage: &mut TransientStorage) {
+    transient_storage.journals.push(Vec::new());
+}
+
+/// Keep the writes of the innermost frame. They are rolled back if the frame
+/// that called it is.
+///
+/// Parameters
+/// ----------
+/// transient_storage : `TransientStorage`
+///     The transient storage.
+pub fn commit_transaction(transient_storage: &mut TransientStorage) {
+    let journal = transient_storage.journals.pop().expect("No ongoing transaction");
+    if let Some(parent) = transient_storage.journals.last_mut() {
+        parent.extend(journal);
+    }
+}
+
+/// Undo the writes of the innermost frame, and of the frames it called.
+///
+/// Parameters
+/// ----------
+/// transient_storage : `TransientStorage`
+///     The transient storage.
+pub fn rollback_transaction(transient_storage: &mut TransientStorage) {
+    let journal = transient_storage.journals.pop().expect("No ongoing transaction");
+    for entry in journal.into_iter().rev() {
+        write(&mut transient_storage.slots, entry.address, entry.key, entry.previous);
+    }
+}
+
+/// Get a value at a key on an account. Returns `U256(0)` if the key has not
+/// been set in the transaction.
+///
+/// Parameters
+/// ----------
+/// transient_storage : `TransientStorage`
+///     The transient storage.
+/// address : `Address`
+///     Address of the account.
+/// key : `Bytes32`
+///     Key to lookup.
+///
+/// Returns
+/// -------
+/// value : `U256`
+///     Value at the key.
+pub fn get_transient_storage(transient_storage: &TransientStorage, address: &Address, key: &Bytes32) -> U256 {
+    transient_storage.slots.get(&(*address, *key)).cloned().unwrap_or_default()
+}
+
+/// Set a value at a key on an account. Setting to `U256(0)` deletes the key.
+///
+/// Parameters
+/// ----------
+/// transient_storage : `TransientStorage`
+///     The transient storage.
+/// address : `Address`
+///     Address of the account.
+/// key : `Bytes32`
+///     Key to set.
+/// value : `U256`
+///     Value to set at the key.
+pub fn set_transient_storage(transient_storage: &mut TransientStorage, address: Address, key: &Bytes32, value: U256) {
+    let previous = write(&mut transient_storage.slots, address, *key, value);
+    if let Some(journal) = transient_storage.journals.last_mut() {
+        journal.push(JournalEntry { address, key: *key, previous });
+    }
+}
+
+/// Discard everything at the end of the transaction.
+///
+/// Parameters
+/// ----------
+/// transient_storage : `TransientStorage`
+///     The transient storage.
+pub fn clear_transient_storage(transient_storage: &mut TransientStorage) {
+    assert!(transient_storage.journals.is_empty(), "a frame is still open");
+    transient_storage.slots.clear();
+}
+
+/// Store a value, leaving zeros out, and return the one it replaced.
+fn write(slots: &mut HashMap<(Address, Bytes32), U256>, address: Address, key: Bytes32, value: U256) -> U256 {
+    let previous = if value == U256::default() {
+        slots.remove(&(address, key))
+    } else {
+        slots.insert((address, key), value)
+    };
+    previous.unwrap_or_default()
+}
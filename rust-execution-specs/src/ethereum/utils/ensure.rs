@@ -6,25 +6,22 @@
 //! Functions that simplify checking assertions and raising exceptions.
 //! 
 
-// use ::typing::{Type, Union};
-// /// 
-// ///     Does nothing if `value` is truthy, otherwise raises the exception returned
-// ///     by `exception_class`.
-// /// 
-// ///     Parameters
-// ///     ----------
-// /// 
-// ///     value :
-// ///         Value that should be true.
-// /// 
-// ///     exception :
-// ///         Constructor for the exception to raise.
-// ///     
-// pub fn ensure(value: bool, exception: Union[Type[BaseException]][BaseException]) -> Result<(), Error> {
-//     if value {
-//         return;
-//     }
-//     return Err(Error::exception);
-// }
-
-
+/// 
+///     Does nothing if `value` is truthy, otherwise raises the exception returned
+///     by `exception_class`.
+/// 
+///     Parameters
+///     ----------
+/// 
+///     value :
+///         Value that should be true.
+/// 
+///     exception :
+///         Constructor for the exception to raise.
+///     
+pub fn ensure<E>(value: bool, exception: E) -> Result<(), E> {
+    if value {
+        return Ok(());
+    }
+    Err(exception)
+}
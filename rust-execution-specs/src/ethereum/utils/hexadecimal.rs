@@ -9,9 +9,10 @@
 // use ::ethereum::base_types::{U64, U256, Bytes, Bytes8, Bytes20, Bytes32, Bytes256, Uint};
 // use ::ethereum::crypto::hash::{Hash32};
 
+use crate::prelude::*;
 use num_traits::Num;
 
-use crate::ethereum::{exceptions::EthereumException, base_types::{Bytes, Bytes8, Bytes20, Bytes32, Bytes256, Uint, U64, U256}, frontier::fork_types::Hash32};
+use crate::ethereum::{exceptions::EthereumException, base_types::{Bytes, Bytes8, Bytes20, Bytes32, Bytes48, Bytes64, Bytes96, Bytes256, Uint, U64, U256}, frontier::fork_types::Hash32};
 
 /// 
 ///     Check if a hex string starts with hex prefix (0x).
@@ -79,7 +80,7 @@ fn from_hex(hex_string: &str) -> Result<Bytes, EthereumException> {
     let mut res = vec![];
     for c in hex_string.as_bytes().chunks(2) {
         // Safety: from_str_radix will fail if not uft8.
-        let src = unsafe { std::str::from_utf8_unchecked(c) };
+        let src = unsafe { core::str::from_utf8_unchecked(c) };
         let b = u8::from_str_radix(src, 16)
             .map_err(|_| EthereumException::BadHexString(hex_string.to_owned()))?;
         res.push(b);
@@ -152,6 +153,60 @@ pub fn hex_to_bytes32(hex_string: &str) -> Result<Bytes32, EthereumException> {
 }
 
 
+/// 
+///     Convert hex string to 48 bytes.
+/// 
+///     Parameters
+///     ----------
+///     hex_string :
+///         The hexadecimal string to be converted to 48 bytes.
+/// 
+///     Returns
+///     -------
+///     48_byte_stream : `Bytes48`
+///         48-byte stream corresponding to the given hexadecimal string.
+///     
+pub fn hex_to_bytes48(hex_string: &str) -> Result<Bytes48, EthereumException> {
+    to_bytes(hex_string)
+}
+
+
+/// 
+///     Convert hex string to 64 bytes.
+/// 
+///     Parameters
+///     ----------
+///     hex_string :
+///         The hexadecimal string to be converted to 64 bytes.
+/// 
+///     Returns
+///     -------
+///     64_byte_stream : `Bytes64`
+///         64-byte stream corresponding to the given hexadecimal string.
+///     
+pub fn hex_to_bytes64(hex_string: &str) -> Result<Bytes64, EthereumException> {
+    to_bytes(hex_string)
+}
+
+
+/// 
+///     Convert hex string to 96 bytes.
+/// 
+///     Parameters
+///     ----------
+///     hex_string :
+///         The hexadecimal string to be converted to 96 bytes.
+/// 
+///     Returns
+///     -------
+///     96_byte_stream : `Bytes96`
+///         96-byte stream corresponding to the given hexadecimal string.
+///     
+pub fn hex_to_bytes96(hex_string: &str) -> Result<Bytes96, EthereumException> {
+    to_bytes(hex_string)
+}
+
+
 /// 
 ///     Convert hex string to 256 bytes.
 /// 
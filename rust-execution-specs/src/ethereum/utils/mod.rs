@@ -7,5 +7,6 @@
 //! 
 
 pub mod byte;
+pub mod ensure;
 pub mod hexadecimal;
 pub mod numeric;
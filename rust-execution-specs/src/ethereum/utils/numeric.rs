@@ -6,6 +6,7 @@
 //! Numeric operations specific utility functions used in this specification.
 //!
 
+use crate::prelude::*;
 use num_bigint::BigInt;
 use crate::ethereum::{base_types::{Uint, Bytes}, exceptions::EthereumException};
 
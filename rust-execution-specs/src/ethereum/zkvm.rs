@@ -0,0 +1,196 @@
+//!
+//! # zkVM Execution
+//!
+//! ## Introduction
+//!
+//! A single entry point to execute a Frontier block from a witness, for
+//! zkVM guests: [execute_block] takes the block and what it needs of the
+//! chain, and gives the state root after it, or why the block is invalid.
+//!
+//! The profile, the `zkvm` feature, is meant to be built without `std`, and
+//! leaves out what isn't deterministic: there are no threads, and nothing
+//! reads the time. Where `parallel` is enabled next to it, as in the tests
+//! of a host, [execute_block] still runs the block on a single thread. The
+//! block runs on the part of the state the witness proves, whose tries are
+//! opened no further than the proofs go, so memory grows with the witness
+//! rather than the state.
+//!
+//! Two checks of `state_transition` are left out: the proof of work, which
+//! the host can check more cheaply, see `validate_proof_of_work`, and the
+//! receipts root, which holds the state root after each transaction, see
+//! `stateless`.
+//!
+//! A witness is generated by the host with [prepare_block].
+//!
+
+use crate::prelude::*;
+
+use crate::ethereum::{
+    exceptions::EthereumException,
+    frontier::{
+        ancestors::{Ancestors, ANCESTOR_DEPTH, BLOCK_HASH_DEPTH},
+        fork::{validate_header, validate_ommers_with_ancestors, BlockChain},
+        fork_types::{Block, Root},
+        stateless::{apply_body_with_witness, run_on_witness, witness_state, witness_state_root, Witness},
+        vm::BlockEnvironment,
+    },
+    rlp,
+    utils::ensure::ensure,
+};
+
+///
+///     What a block needs of the chain to be executed.
+///
+#[derive(Clone, Debug)]
+pub struct BlockWitness {
+    /// The last blocks before the block, oldest first, up to 256 of them.
+    /// Their transactions aren't needed and may be left out, but the
+    /// ommers of the last `ANCESTOR_DEPTH` of them are.
+    pub ancestors: Vec<Block>,
+    /// The part of the state of the parent the block accesses.
+    pub state: Witness,
+}
+
+///
+///     Executes a block from a witness and computes the state root after
+///     it.
+///
+///     Parameters
+///     ----------
+///     witness :
+///         The ancestors of the block and the part of the state of its
+///         parent it accesses.
+///     block :
+///         The block to execute.
+///
+///     Returns
+///     -------
+///     state_root : `Root`
+///         The state root after the block, which is that of its header.
+///
+///     Raises
+///     ------
+///     ValueError :
+///         If the ancestors don't chain up, or a proof doesn't hold against
+///         the state root of the parent.
+///     MissingWitness :
+///         If the block needs an account, storage slot, code or trie node
+///         the witness doesn't have.
+///     InvalidBlock :
+///         If the block is invalid.
+///
+pub fn execute_block(witness: &BlockWitness, block: &Block) -> Result<Root, EthereumException> {
+    #[cfg(feature = "parallel")]
+    {
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(1)
+            .build()
+            .expect("a thread to execute the block on");
+        pool.install(|| execute(witness, block))
+    }
+    #[cfg(not(feature = "parallel"))]
+    execute(witness, block)
+}
+
+/// [execute_block], on the current thread.
+fn execute(witness: &BlockWitness, block: &Block) -> Result<Root, EthereumException> {
+    let parent = witness.ancestors.last().ok_or(EthereumException::ValueError)?;
+    // The hashes of the ancestors, which `BLOCKHASH` gives, are those their
+    // children commit to.
+    for pair in witness.ancestors.windows(2) {
+        ensure(
+            pair[1].header.parent_hash == pair[0].hash(),
+            EthereumException::ValueError,
+        )?;
+    }
+    let recent = &witness.ancestors[witness.ancestors.len().saturating_sub(ANCESTOR_DEPTH)..];
+    for ancestor in recent {
+        ensure(
+            rlp::rlp_hash(&ancestor.ommers[..]) == ancestor.header.ommers_hash,
+            EthereumException::ValueError,
+        )?;
+    }
+
+    validate_header(&block.header, parent.header.clone())?;
+    let ancestors = Ancestors::new(&witness.ancestors);
+    validate_ommers_with_ancestors(&block.ommers, &block.header, &ancestors)?;
+    let block_env = BlockEnvironment {
+        block_hashes: ancestors.block_hashes(),
+        coinbase: block.header.coinbase,
+        number: block.header.number.clone(),
+        gas_limit: block.header.gas_limit.clone(),
+        time: block.header.timestamp.clone(),
+        difficulty: block.header.difficulty.clone(),
+    };
+
+    let parent_state_root = &parent.header.state_root;
+    let mut state = witness_state(parent_state_root, &witness.state)?;
+    let (output, accesses) = run_on_witness(
+        &mut state,
+        &block_env,
+        &block.transactions,
+        &block.ommers,
+        &witness.state,
+    )?;
+    let (gas_used, transactions_root, _, block_logs_bloom, _, _, _) = output;
+    ensure(gas_used == block.header.gas_used, EthereumException::InvalidBlock)?;
+    ensure(
+        transactions_root == block.header.transactions_root,
+        EthereumException::InvalidBlock,
+    )?;
+    ensure(block_logs_bloom == block.header.bloom, EthereumException::InvalidBlock)?;
+
+    let state_root = witness_state_root(parent_state_root, &witness.state, &state, &accesses)?;
+    ensure(state_root == block.header.state_root, EthereumException::InvalidBlock)?;
+    Ok(state_root)
+}
+
+///
+///     Generates the witness to execute a block with [execute_block], by
+///     executing it on the state of the chain, which is left as it was.
+///
+///     Parameters
+///     ----------
+///     chain :
+///         The chain the block extends.
+///     block :
+///         The block.
+///
+///     Raises
+///     ------
+///     InvalidBlock :
+///         If a transaction of the block is invalid. The header isn't
+///         checked.
+///
+pub fn prepare_block(chain: &BlockChain, block: &Block) -> Result<BlockWitness, EthereumException> {
+    let ancestors = Ancestors::new(&chain.blocks);
+    let block_env = BlockEnvironment {
+        block_hashes: ancestors.block_hashes(),
+        coinbase: block.header.coinbase,
+        number: block.header.number.clone(),
+        gas_limit: block.header.gas_limit.clone(),
+        time: block.header.timestamp.clone(),
+        difficulty: block.header.difficulty.clone(),
+    };
+    let mut state = chain.state.clone();
+    let (_, witness) = apply_body_with_witness(&mut state, &block_env, &block.transactions, &block.ommers)?;
+
+    let first = chain.blocks.len().saturating_sub(BLOCK_HASH_DEPTH);
+    let ancestors = chain.blocks[first..]
+        .iter()
+        .enumerate()
+        .map(|(n, ancestor)| Block {
+            header: ancestor.header.clone(),
+            transactions: Vec::new(),
+            ommers: if chain.blocks.len() - first - n <= ANCESTOR_DEPTH {
+                ancestor.ommers.clone()
+            } else {
+                Vec::new()
+            },
+        })
+        .collect();
+    Ok(BlockWitness {
+        ancestors,
+        state: witness,
+    })
+}
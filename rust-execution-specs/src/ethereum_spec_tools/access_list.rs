@@ -0,0 +1,70 @@
+//!
+//! # Access Lists
+//!
+//! The EIP-2930 access list of a call, from the accounts and storage slots
+//! the state recorded it accessed (see `record_accesses`), as
+//! `eth_createAccessList` returns it, as the [AccessList] the forks share.
+//!
+//! Like geth, the list leaves out the sender, the recipient or the created
+//! contract, and the precompiled contracts, which are warm from the start
+//! of a transaction from Berlin on. Frontier has no access lists: the gas a
+//! list is reported with is what the transaction uses with it under
+//! Frontier's rules, which charge nothing for it and warm nothing.
+//!
+
+use std::collections::BTreeSet;
+
+use crate::ethereum::{
+    access_list::{AccessList, AccessListEntry},
+    frontier::{
+        fork_types::Address,
+        state::StateAccesses,
+        vm::precompiled_contracts::{ECRECOVER_ADDRESS, IDENTITY_ADDRESS, RIPEMD160_ADDRESS, SHA256_ADDRESS},
+    },
+};
+
+///
+///     The access list of what a call accessed.
+///
+///     Parameters
+///     ----------
+///     accesses :
+///         The accounts and storage slots the call accessed.
+///     sender :
+///         The sender of the call.
+///     recipient :
+///         The account called, or the contract created.
+///
+///     Returns
+///     -------
+///     access_list : `AccessList`
+///         The accounts accessed, ordered by address, each with its storage
+///         keys accessed, ordered.
+///
+pub fn access_list(accesses: &StateAccesses, sender: &Address, recipient: &Address) -> AccessList {
+    let excluded = [
+        *sender,
+        *recipient,
+        ECRECOVER_ADDRESS,
+        SHA256_ADDRESS,
+        RIPEMD160_ADDRESS,
+        IDENTITY_ADDRESS,
+    ];
+    let addresses: BTreeSet<&Address> = accesses
+        .accounts
+        .iter()
+        .chain(accesses.storage.keys())
+        .filter(|address| !excluded.contains(address))
+        .collect();
+    addresses
+        .into_iter()
+        .map(|address| AccessListEntry {
+            address: *address,
+            storage_keys: accesses
+                .storage
+                .get(address)
+                .map(|keys| keys.iter().copied().collect())
+                .unwrap_or_default(),
+        })
+        .collect()
+}
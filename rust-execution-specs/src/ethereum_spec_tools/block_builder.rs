@@ -0,0 +1,258 @@
+//!
+//! # Block Builder
+//!
+//! A reference block builder: given candidate transactions and the chain to
+//! extend, it orders them greedily by the fee they pay the coinbase,
+//! executes them with the spec, leaves out those that can't be included and
+//! seals the block, so that semantics that depend on the order of
+//! transactions can be tested on blocks built the way miners build them.
+//!
+//! Before London all of the gas price is paid to the coinbase, so the
+//! priority fee of a transaction is its gas price. The transactions of a
+//! sender are kept in the order of their nonces: the next one of each sender
+//! competes with the next ones of the others, and the one paying most is
+//! included first, the earliest candidate winning a tie.
+//!
+//! The block is sealed as far as execution goes: its header has every root
+//! and is valid on its parent, but its proof of work is left empty, as
+//! `validate_proof_of_work` checks it apart. A Clique chain can sign it with
+//! [seal_header](crate::ethereum::clique::seal_header).
+//!
+
+use std::collections::{HashMap, VecDeque};
+
+use crate::ethereum::{
+    base_types::{Bytes, Uint, U256},
+    exceptions::{EthereumException, InvalidTransactionReason},
+    fee_summary::BlockFeeSummary,
+    frontier::{
+        bloom::logs_bloom,
+        fork::{
+            calculate_block_difficulty, check_transaction, compute_receipts_root, compute_transactions_root,
+            get_last_256_block_hashes, make_receipt, pay_rewards, process_transaction, recover_sender, validate_header,
+            BlockChain,
+        },
+        fork_types::{Address, Block, Header, Receipt, Transaction},
+        state::{get_account, state_root, State},
+        vm::{BlockEnvironment, TxEnvironment},
+    },
+    rlp,
+};
+
+/// What the builder chooses for the block, besides its transactions.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BlockAttributes {
+    pub coinbase: Address,
+    /// Later than the parent's.
+    pub timestamp: U256,
+    /// Within the adjustment allowed from the parent's.
+    pub gas_limit: Uint,
+    pub extra_data: Bytes,
+}
+
+/// Why a candidate was left out of the block.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SkipReason {
+    /// No sender can be recovered from the signature.
+    InvalidSignature,
+    /// The transaction wants more gas than is left in the block.
+    GasAllowanceExceeded,
+    /// The transaction breaks a rule when its turn comes, such as a nonce
+    /// its sender has not reached.
+    Invalid(InvalidTransactionReason),
+}
+
+/// A candidate left out of the block.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SkippedTransaction {
+    /// The position of the transaction among the candidates.
+    pub index: usize,
+    pub reason: SkipReason,
+}
+
+/// A block built by `build_block`.
+#[derive(Clone)]
+pub struct BuiltBlock {
+    pub block: Block,
+    /// The state after the block.
+    pub state: State,
+    /// The position among the candidates of each transaction of the block.
+    pub included: Vec<usize>,
+    pub skipped: Vec<SkippedTransaction>,
+    pub receipts: Vec<Receipt>,
+    pub fee_summary: BlockFeeSummary,
+}
+
+/// The candidates of one sender, in the order of their nonces.
+struct SenderQueue<'a> {
+    sender: Address,
+    transactions: VecDeque<(usize, &'a Transaction)>,
+}
+
+///
+///     Group the candidates by sender, leaving out those with an invalid
+///     signature.
+///
+fn sender_queues<'a>(candidates: &'a [Transaction], skipped: &mut Vec<SkippedTransaction>) -> Vec<SenderQueue<'a>> {
+    let mut queues: Vec<SenderQueue> = vec![];
+    let mut positions = HashMap::new();
+    for (index, tx) in candidates.iter().enumerate() {
+        let Ok(sender) = recover_sender(tx) else {
+            skipped.push(SkippedTransaction {
+                index,
+                reason: SkipReason::InvalidSignature,
+            });
+            continue;
+        };
+        let position = *positions.entry(sender).or_insert_with(|| {
+            queues.push(SenderQueue {
+                sender,
+                transactions: VecDeque::new(),
+            });
+            queues.len() - 1
+        });
+        queues[position].transactions.push_back((index, tx));
+    }
+    for queue in &mut queues {
+        // Stable, so candidates with the same nonce keep their order.
+        queue
+            .transactions
+            .make_contiguous()
+            .sort_by(|(_, a), (_, b)| a.nonce.cmp(&b.nonce));
+    }
+    queues
+}
+
+///
+///     Take the next transaction to try: the one paying the highest gas
+///     price among the next transaction of each sender.
+///
+fn next_transaction<'a>(queues: &mut [SenderQueue<'a>]) -> Option<(Address, usize, &'a Transaction)> {
+    let queue = queues
+        .iter_mut()
+        .filter(|queue| !queue.transactions.is_empty())
+        .min_by(|a, b| {
+            let (a_index, a_tx) = a.transactions[0];
+            let (b_index, b_tx) = b.transactions[0];
+            b_tx.gas_price.cmp(&a_tx.gas_price).then(a_index.cmp(&b_index))
+        })?;
+    let (index, tx) = queue.transactions.pop_front()?;
+    Some((queue.sender, index, tx))
+}
+
+///
+///     Build the next block of a chain from candidate transactions.
+///
+///     Parameters
+///     ----------
+///     chain :
+///         The chain to extend, with the state after its last block.
+///     candidates :
+///         Signed transactions that may be included, in any order.
+///     attributes :
+///         The coinbase, timestamp, gas limit and extra data of the block.
+///
+///     Returns
+///     -------
+///     built : `BuiltBlock`
+///         The sealed block, the state after it and what became of each
+///         candidate.
+///
+///     Raises
+///     ------
+///     InvalidBlock :
+///         If the chain is empty, or the attributes don't make a valid
+///         header on its last block.
+///
+pub fn build_block(
+    chain: &BlockChain,
+    candidates: &[Transaction],
+    attributes: &BlockAttributes,
+) -> Result<BuiltBlock, EthereumException> {
+    let parent = &chain.blocks.last().ok_or(EthereumException::InvalidBlock)?.header;
+    let number = &parent.number + 1u8;
+    let difficulty = calculate_block_difficulty(&number, &attributes.timestamp, &parent.timestamp, &parent.difficulty)?;
+    let block_env = BlockEnvironment {
+        block_hashes: get_last_256_block_hashes(chain)?,
+        coinbase: attributes.coinbase,
+        number: number.clone(),
+        gas_limit: attributes.gas_limit.clone(),
+        time: attributes.timestamp.clone(),
+        difficulty: difficulty.clone(),
+    };
+
+    let mut state = chain.state.clone();
+    let mut gas_available = block_env.gas_limit.clone();
+    let mut transactions = vec![];
+    let mut included = vec![];
+    let mut skipped = vec![];
+    let mut receipts = vec![];
+    let mut block_logs = vec![];
+    let mut fee_summary = BlockFeeSummary::default();
+
+    let mut queues = sender_queues(candidates, &mut skipped);
+    while let Some((sender, index, tx)) = next_transaction(&mut queues) {
+        let reason = if tx.gas > gas_available {
+            Some(SkipReason::GasAllowanceExceeded)
+        } else {
+            check_transaction(&block_env, &get_account(&state, &sender), tx)
+                .err()
+                .map(SkipReason::Invalid)
+        };
+        if let Some(reason) = reason {
+            skipped.push(SkippedTransaction { index, reason });
+            continue;
+        }
+        let tx_env = TxEnvironment {
+            origin: sender,
+            gas_price: tx.gas_price.clone(),
+        };
+        let (gas_used, logs) = process_transaction(&block_env, &tx_env, &mut state, tx)?;
+        fee_summary.add_transaction(&gas_used, &tx.gas_price, &U256::default());
+        gas_available -= gas_used;
+
+        receipts.push(make_receipt(
+            state_root(&state),
+            &block_env.gas_limit - &gas_available,
+            &logs,
+        ));
+        block_logs.extend(logs);
+        transactions.push(tx.clone());
+        included.push(index);
+    }
+    skipped.sort_by_key(|skipped| skipped.index);
+
+    (fee_summary.miner_reward, fee_summary.ommer_rewards) = pay_rewards(&mut state, &number, attributes.coinbase, &[]);
+
+    let header = Header {
+        parent_hash: parent.hash(),
+        ommers_hash: rlp::rlp_hash(&Vec::<Header>::new()),
+        coinbase: attributes.coinbase,
+        state_root: state_root(&state),
+        transactions_root: compute_transactions_root(&transactions),
+        receipt_root: compute_receipts_root(&receipts),
+        bloom: logs_bloom(&block_logs),
+        difficulty,
+        number,
+        gas_limit: attributes.gas_limit.clone(),
+        gas_used: &block_env.gas_limit - gas_available,
+        timestamp: attributes.timestamp.clone(),
+        extra_data: attributes.extra_data.clone(),
+        mix_digest: [0; 32],
+        nonce: [0; 8],
+    };
+    validate_header(&header, parent.clone())?;
+
+    Ok(BuiltBlock {
+        block: Block {
+            header,
+            transactions,
+            ommers: vec![],
+        },
+        state,
+        included,
+        skipped,
+        receipts,
+        fee_summary,
+    })
+}
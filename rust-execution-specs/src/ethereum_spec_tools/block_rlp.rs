@@ -0,0 +1,126 @@
+//!
+//! # Block RLP
+//!
+//! Decoding blocks and headers from their RLP, the encoding clients
+//! exchange them in, as `debug_getRawBlock` and `debug_getRawHeader` give
+//! them and `chain.rlp` files and blockchain tests hold them.
+//!
+//! The layout depends on the fork: London appends the base fee to the
+//! header, Shanghai the withdrawals root and Cancun the blob fields, see
+//! `engine::HeaderExtensions`, and Shanghai appends the withdrawals to the
+//! body. Each layout comes with the implementation of its fork, so an
+//! encoding of another fork's layout fails to decode.
+//!
+
+use num_bigint::BigUint;
+
+use super::{engine::decode_transaction, forks::HardFork};
+use crate::ethereum::{
+    base_types::Bytes,
+    exceptions::EthereumException,
+    frontier::fork_types::{Block, Header},
+    rlp::{self, Simple},
+};
+
+fn rlp_bytes(item: Simple) -> Result<Bytes, EthereumException> {
+    match item {
+        Simple::Bytes(bytes) => Ok(bytes),
+        Simple::Sequence(_) => Err(EthereumException::RLPDecodingError),
+    }
+}
+
+fn rlp_sequence(item: Simple) -> Result<Vec<Simple>, EthereumException> {
+    match item {
+        Simple::Sequence(items) => Ok(items),
+        Simple::Bytes(_) => Err(EthereumException::RLPDecodingError),
+    }
+}
+
+fn bytes_array<const N: usize>(bytes: Bytes) -> Result<[u8; N], EthereumException> {
+    bytes
+        .as_ref()
+        .try_into()
+        .map_err(|_| EthereumException::RLPDecodingError)
+}
+
+fn frontier_header(item: Simple) -> Result<Header, EthereumException> {
+    let fields = rlp_sequence(item)?
+        .into_iter()
+        .map(rlp_bytes)
+        .collect::<Result<Vec<_>, _>>()?;
+    let [parent_hash, ommers_hash, coinbase, state_root, transactions_root, receipt_root, bloom, difficulty, number, gas_limit, gas_used, timestamp, extra_data, mix_digest, nonce] =
+        <[Bytes; 15]>::try_from(fields).map_err(|_| EthereumException::RLPDecodingError)?;
+    Ok(Header {
+        parent_hash: bytes_array(parent_hash)?,
+        ommers_hash: bytes_array(ommers_hash)?,
+        coinbase: bytes_array(coinbase)?,
+        state_root: bytes_array(state_root)?,
+        transactions_root: bytes_array(transactions_root)?,
+        receipt_root: bytes_array(receipt_root)?,
+        bloom: bytes_array(bloom)?,
+        difficulty: BigUint::from_bytes_be(&difficulty),
+        number: BigUint::from_bytes_be(&number),
+        gas_limit: BigUint::from_bytes_be(&gas_limit),
+        gas_used: BigUint::from_bytes_be(&gas_used),
+        timestamp: BigUint::from_bytes_be(&timestamp),
+        extra_data,
+        mix_digest: bytes_array(mix_digest)?,
+        nonce: bytes_array(nonce)?,
+    })
+}
+
+fn header(item: Simple, fork: HardFork) -> Result<Header, EthereumException> {
+    match fork {
+        HardFork::Frontier => frontier_header(item),
+    }
+}
+
+///
+///     Decode a block from its RLP.
+///
+///     Parameters
+///     ----------
+///     encoded :
+///         The RLP of the block, as `debug_getRawBlock` gives it.
+///     fork :
+///         The fork of the block, whose layout it has.
+///
+///     Raises
+///     ------
+///     RLPDecodingError :
+///         If the block doesn't have the layout of the fork.
+///
+pub fn decode_block(encoded: &[u8], fork: HardFork) -> Result<Block, EthereumException> {
+    let [header, transactions, ommers] = <[Simple; 3]>::try_from(rlp_sequence(rlp::decode(encoded)?)?)
+        .map_err(|_| EthereumException::RLPDecodingError)?;
+    Ok(Block {
+        header: self::header(header, fork)?,
+        transactions: rlp_sequence(transactions)?
+            .iter()
+            .map(|transaction| decode_transaction(&rlp::encode(transaction)))
+            .collect::<Result<_, _>>()?,
+        ommers: rlp_sequence(ommers)?
+            .into_iter()
+            .map(|ommer| self::header(ommer, fork))
+            .collect::<Result<_, _>>()?,
+    })
+}
+
+///
+///     Decode a header from its RLP.
+///
+///     Parameters
+///     ----------
+///     encoded :
+///         The RLP of the header, as `debug_getRawHeader` gives it.
+///     fork :
+///         The fork of the header, whose layout it has.
+///
+///     Raises
+///     ------
+///     RLPDecodingError :
+///         If the header doesn't have the layout of the fork.
+///
+pub fn decode_header(encoded: &[u8], fork: HardFork) -> Result<Header, EthereumException> {
+    header(rlp::decode(encoded)?, fork)
+}
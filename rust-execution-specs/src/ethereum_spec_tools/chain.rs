@@ -0,0 +1,704 @@
+//!
+//! # Chain
+//!
+//! A [Chain] runs blocks through the rules of whichever fork its
+//! [ForkSchedule] has active at each block's number, timestamp or total
+//! difficulty. On entering a fork it lets the fork's `apply_fork` upgrade the
+//! chain, which is where irregular state transitions such as the DAO fork
+//! belong. Blocks of forks the spec doesn't implement are rejected with
+//! `EthereumException::UnsupportedFork`.
+//!
+//! The `sync` tool drives one of these, and so should anything else that
+//! executes a whole chain.
+//!
+//! A chain made [Chain::with_reorg_depth] keeps itself as it was before each
+//! of its last blocks, so that when the source of its blocks switches to
+//! another branch it can [Chain::rewind] to where the branches meet and
+//! apply the new one.
+//!
+//! Like the spec, a chain keeps only its last 255 blocks, as many as
+//! `BLOCKHASH` and the ommer checks look back on. The blocks it prunes are
+//! kept as long as it can rewind past them, and their headers then go to its
+//! [HeaderArchive], if it has one.
+//!
+//! A chain also indexes the transactions it applies by hash, see
+//! [Chain::get_transaction] and [Chain::get_transaction_receipt]. The
+//! receipts are found for the blocks it still holds.
+//!
+//! A chain made [Chain::with_state_history] commits the state after each
+//! block to a `NodeStore`, so that the state of any block from then on can
+//! be read, see [Chain::state_at]. The states of the blocks it rewinds past
+//! are pruned.
+//!
+
+use std::{collections::VecDeque, mem};
+
+use serde_json::Value;
+
+use super::{
+    chain_info::{ChainInfo, Consensus},
+    chain_spec::ChainSpec,
+    forks::{ForkSchedule, HardFork, ScheduledFork},
+    header_archive::HeaderArchive,
+    transaction_index::{transaction_receipt, IndexedTransaction, TransactionIndex, TransactionReceipt},
+};
+use crate::ethereum::{
+    base_types::Uint,
+    exceptions::{ErrorContext, EthereumException},
+    frontier::{
+        self,
+        ancestors::Ancestors,
+        fork::{recover_sender, BlockChain},
+        fork_types::{Block, Hash32, Header},
+        historical_state::{commit_state, commit_whole_state, StateAtRoot},
+        state::{record_accesses, take_accesses, State},
+        trie::persistent::NodeStore,
+    },
+    genesis::{add_genesis_block, parse_genesis_configuration, GenesisConfiguration},
+    trace::{TraceEvent, Tracer},
+};
+
+///
+///     A block chain that moves through the forks of its schedule.
+///
+#[derive(Clone)]
+pub struct Chain {
+    chain: BlockChain,
+    schedule: ForkSchedule,
+    /// The position in `schedule` of the fork of the last block.
+    fork: usize,
+    total_difficulty: Uint,
+    /// The chain before each of its last blocks, oldest first.
+    history: VecDeque<Snapshot>,
+    reorg_depth: usize,
+    /// How the chain is sealed before the merge.
+    consensus: Consensus,
+    /// The last blocks of the chain, for the next block.
+    ancestors: Ancestors,
+    /// The blocks pruned from `chain` since the oldest block the chain can
+    /// rewind to, oldest first.
+    pruned: VecDeque<Block>,
+    archive: Option<HeaderArchive>,
+    transactions: TransactionIndex,
+    /// The states after the blocks, if kept.
+    state_history: Option<NodeStore>,
+}
+
+/// The blocks the spec keeps, see `state_transition`.
+pub const RETAINED_BLOCKS: usize = 255;
+
+/// A chain as it was before a block. Its blocks are those of the chain,
+/// without those that came after.
+#[derive(Clone)]
+struct Snapshot {
+    state: State,
+    fork: usize,
+    total_difficulty: Uint,
+}
+
+fn to_u64(number: &Uint) -> u64 {
+    u64::try_from(number).unwrap_or(u64::MAX)
+}
+
+fn implementation(fork: &ScheduledFork) -> Result<HardFork, EthereumException> {
+    fork.fork
+        .ok_or_else(|| EthereumException::UnsupportedFork(fork.name.to_owned()))
+}
+
+impl Chain {
+    ///
+    ///     Follow `schedule` from the head of `chain`.
+    ///
+    ///     Parameters
+    ///     ----------
+    ///     chain :
+    ///         A chain holding at least its genesis block.
+    ///     schedule :
+    ///         The forks of the chain.
+    ///
+    pub fn new(chain: BlockChain, schedule: ForkSchedule) -> Result<Self, EthereumException> {
+        let total_difficulty = chain.blocks.iter().map(|block| &block.header.difficulty).sum();
+        Self::resume(chain, schedule, total_difficulty)
+    }
+
+    ///
+    ///     Follow `schedule` from the head of a chain that holds only its
+    ///     last blocks, as one restored from a state dump does. It needs the
+    ///     255 blocks before its head for `BLOCKHASH` and for checking
+    ///     ommers, and the total difficulty of those it lacks. Blocks before
+    ///     those are dropped.
+    ///
+    ///     Parameters
+    ///     ----------
+    ///     chain :
+    ///         The last blocks of the chain, and the state after its head.
+    ///     schedule :
+    ///         The forks of the chain.
+    ///     total_difficulty :
+    ///         The total difficulty of the chain up to and including its
+    ///         head.
+    ///
+    pub fn resume(
+        mut chain: BlockChain,
+        schedule: ForkSchedule,
+        total_difficulty: Uint,
+    ) -> Result<Self, EthereumException> {
+        let excess = chain.blocks.len().saturating_sub(RETAINED_BLOCKS);
+        chain.blocks.drain(..excess);
+        let head = &chain.blocks.last().ok_or(EthereumException::ValueError)?.header;
+        let parent_total_difficulty = &total_difficulty - &head.difficulty;
+        let fork = schedule.active(to_u64(&head.number), to_u64(&head.timestamp), &parent_total_difficulty);
+        implementation(&schedule.forks()[fork])?;
+        let mut transactions = TransactionIndex::default();
+        for block in &chain.blocks {
+            transactions.add_block(to_u64(&block.header.number), block, None);
+        }
+        Ok(Self {
+            ancestors: Ancestors::new(&chain.blocks),
+            chain,
+            schedule,
+            fork,
+            total_difficulty,
+            history: VecDeque::new(),
+            reorg_depth: 0,
+            consensus: Consensus::ProofOfWork,
+            pruned: VecDeque::new(),
+            archive: None,
+            transactions,
+            state_history: None,
+        })
+    }
+
+    ///
+    ///     Keep the chain as it was before each of its last `depth` blocks,
+    ///     to be able to rewind that far. Each costs a copy of the state.
+    ///
+    pub fn with_reorg_depth(mut self, depth: usize) -> Self {
+        self.reorg_depth = depth;
+        let excess = self.history.len().saturating_sub(depth);
+        self.history.drain(..excess);
+        self
+    }
+
+    ///
+    ///     Keep the headers of the blocks the chain prunes in `archive`.
+    ///
+    ///     Raises
+    ///     ------
+    ///     ValueError :
+    ///         If the archive ends before the oldest block the chain holds,
+    ///         which would leave a gap in it.
+    ///
+    pub fn with_archive(mut self, archive: HeaderArchive) -> Result<Self, EthereumException> {
+        let oldest = self.pruned.front().unwrap_or(&self.chain.blocks[0]);
+        if !archive.is_empty() && archive.range().end < to_u64(&oldest.header.number) {
+            return Err(EthereumException::ValueError);
+        }
+        self.archive = Some(archive);
+        Ok(self)
+    }
+
+    ///
+    ///     Keep the state after each block from the head on in `store`, made
+    ///     with `NodeStore::for_state`. A store kept from an earlier run
+    ///     carries on, if it has the state after the head.
+    ///
+    ///     Raises
+    ///     ------
+    ///     ValueError :
+    ///         If the state doesn't have the state root of the head.
+    ///
+    pub fn with_state_history(mut self, mut store: NodeStore) -> Result<Self, EthereumException> {
+        let head = &self.chain.blocks.last().expect("a chain has a head").header;
+        if store.get(&head.state_root).is_none() {
+            let root = commit_whole_state(&mut store, &self.chain.state)?;
+            if root != head.state_root {
+                return Err(EthereumException::ValueError);
+            }
+        }
+        self.state_history = Some(store);
+        Ok(self)
+    }
+
+    ///
+    ///     Seal the chain by `consensus` before the merge, rather than by
+    ///     proof of work.
+    ///
+    pub fn with_consensus(mut self, consensus: Consensus) -> Self {
+        self.consensus = consensus;
+        self
+    }
+
+    ///
+    ///     Start a chain from a geth style genesis file, with the forks and
+    ///     the consensus of its `config`.
+    ///
+    pub fn from_genesis(json: &str) -> Result<Self, EthereumException> {
+        let genesis: Value =
+            serde_json::from_str(json).map_err(|e| EthereumException::JsonDecodeError(e.to_string()))?;
+        let schedule = ForkSchedule::from_config(&genesis["config"])?;
+        let consensus = Consensus::from_config(&genesis["config"])?;
+        Ok(Self::start(parse_genesis_configuration(json)?, schedule)?.with_consensus(consensus))
+    }
+
+    ///
+    ///     Start a network from its spec. Its fee parameters are left to the
+    ///     forks that have a fee market.
+    ///
+    pub fn from_spec(spec: ChainSpec) -> Result<Self, EthereumException> {
+        Ok(Self::start(spec.genesis, spec.schedule)?.with_consensus(spec.consensus))
+    }
+
+    fn start(genesis: GenesisConfiguration, schedule: ForkSchedule) -> Result<Self, EthereumException> {
+        let mut chain = BlockChain {
+            blocks: vec![],
+            state: State::default(),
+            chain_id: 0,
+        };
+        add_genesis_block(implementation(&schedule.forks()[0])?, &mut chain, genesis)?;
+        Self::new(chain, schedule)
+    }
+
+    pub fn blockchain(&self) -> &BlockChain {
+        &self.chain
+    }
+
+    pub fn blocks(&self) -> &[Block] {
+        &self.chain.blocks
+    }
+
+    pub fn state(&self) -> &State {
+        &self.chain.state
+    }
+
+    pub fn schedule(&self) -> &ForkSchedule {
+        &self.schedule
+    }
+
+    ///
+    ///     The fork of the last block.
+    ///
+    pub fn fork(&self) -> &ScheduledFork {
+        &self.schedule.forks()[self.fork]
+    }
+
+    ///
+    ///     The chain id, fork, consensus and schedule of the chain, as of
+    ///     its head.
+    ///
+    pub fn info(&self) -> ChainInfo {
+        ChainInfo::new(self.chain.chain_id, self.schedule.clone(), self.fork, self.consensus)
+    }
+
+    ///
+    ///     The hashes of the last 256 blocks, as the block after the head
+    ///     sees them in `BLOCKHASH`.
+    ///
+    pub fn block_hashes(&self) -> Vec<Hash32> {
+        self.ancestors.block_hashes()
+    }
+
+    pub fn total_difficulty(&self) -> &Uint {
+        &self.total_difficulty
+    }
+
+    pub fn archive(&self) -> Option<&HeaderArchive> {
+        self.archive.as_ref()
+    }
+
+    /// The store of the states after the blocks, if they are kept.
+    pub fn state_history(&self) -> Option<&NodeStore> {
+        self.state_history.as_ref()
+    }
+
+    ///
+    ///     Open the state after a block, from the states the chain keeps.
+    ///
+    ///     Parameters
+    ///     ----------
+    ///     number :
+    ///         The number of the block.
+    ///
+    ///     Returns
+    ///     -------
+    ///     state : `Option<StateAtRoot>`
+    ///         The state, or `None` if the chain doesn't keep states or
+    ///         can't find the header of the block.
+    ///
+    ///     Raises
+    ///     ------
+    ///     ValueError :
+    ///         If the state of the block isn't kept, being from before the
+    ///         chain kept states.
+    ///
+    pub fn state_at(&self, number: u64) -> Result<Option<StateAtRoot<'_>>, EthereumException> {
+        let Some(store) = &self.state_history else {
+            return Ok(None);
+        };
+        let Some(header) = self.header(number)? else {
+            return Ok(None);
+        };
+        StateAtRoot::open(store, header.state_root).map(Some)
+    }
+
+    ///
+    ///     Find the header of a block of the chain, among the blocks it holds
+    ///     and then in its archive.
+    ///
+    ///     Parameters
+    ///     ----------
+    ///     number :
+    ///         The number of the block.
+    ///
+    ///     Returns
+    ///     -------
+    ///     header : `Option<Header>`
+    ///         The header, or `None` if the chain pruned the block without
+    ///         archiving it, or isn't there yet.
+    ///
+    pub fn header(&self, number: u64) -> Result<Option<Header>, EthereumException> {
+        if let Some(block) = self.block(number) {
+            return Ok(Some(block.header.clone()));
+        }
+        match &self.archive {
+            Some(archive) => archive.get(number),
+            None => Ok(None),
+        }
+    }
+
+    ///
+    ///     Find a block of the chain among the blocks it holds, those it
+    ///     keeps for `BLOCKHASH` and those it can rewind past.
+    ///
+    pub fn block(&self, number: u64) -> Option<&Block> {
+        let oldest = self.pruned.front().unwrap_or(&self.chain.blocks[0]);
+        let index = number.checked_sub(to_u64(&oldest.header.number))?;
+        self.pruned.iter().chain(&self.chain.blocks).nth(index as usize)
+    }
+
+    ///
+    ///     The transactions of the chain by hash, and the receipts of the
+    ///     blocks it holds.
+    ///
+    pub fn transactions(&self) -> &TransactionIndex {
+        &self.transactions
+    }
+
+    ///
+    ///     Find a transaction of the chain, as `eth_getTransactionByHash`
+    ///     does.
+    ///
+    ///     Parameters
+    ///     ----------
+    ///     hash :
+    ///         The hash of the transaction.
+    ///
+    ///     Returns
+    ///     -------
+    ///     transaction : `Option<IndexedTransaction>`
+    ///         The transaction, or `None` if it isn't in the chain or its
+    ///         block was pruned since.
+    ///
+    ///     Raises
+    ///     ------
+    ///     InvalidBlock :
+    ///         If the sender of the transaction can't be recovered, which a
+    ///         block the chain applied can't have.
+    ///
+    pub fn get_transaction(&self, hash: &Hash32) -> Result<Option<IndexedTransaction>, EthereumException> {
+        let Some(&location) = self.transactions.location(hash) else {
+            return Ok(None);
+        };
+        let Some(block) = self.block(location.block_number) else {
+            return Ok(None);
+        };
+        let transaction = block.transactions[location.index].clone();
+        Ok(Some(IndexedTransaction {
+            from: recover_sender(&transaction)?,
+            transaction,
+            hash: *hash,
+            block_hash: block.hash(),
+            location,
+        }))
+    }
+
+    ///
+    ///     Find the receipt of a transaction of the chain, as
+    ///     `eth_getTransactionReceipt` does.
+    ///
+    ///     Parameters
+    ///     ----------
+    ///     hash :
+    ///         The hash of the transaction.
+    ///
+    ///     Returns
+    ///     -------
+    ///     receipt : `Option<TransactionReceipt>`
+    ///         The receipt, or `None` if the transaction isn't in the chain,
+    ///         or its block was pruned or wasn't executed by the chain.
+    ///
+    ///     Raises
+    ///     ------
+    ///     InvalidBlock :
+    ///         If the sender of the transaction can't be recovered, which a
+    ///         block the chain applied can't have.
+    ///
+    pub fn get_transaction_receipt(&self, hash: &Hash32) -> Result<Option<TransactionReceipt>, EthereumException> {
+        let Some(transaction) = self.get_transaction(hash)? else {
+            return Ok(None);
+        };
+        let receipts = self.transactions.receipts(transaction.location.block_number);
+        Ok(receipts.and_then(|receipts| transaction_receipt(transaction, receipts)))
+    }
+
+    ///
+    ///     The oldest block the chain can rewind to.
+    ///
+    pub fn rewindable_to(&self) -> u64 {
+        let head = to_u64(&self.chain.blocks.last().expect("a chain has a head").header.number);
+        head - self.history.len() as u64
+    }
+
+    ///
+    ///     Go back to the chain as it was when block `number` was its head,
+    ///     to apply another branch from there.
+    ///
+    ///     Parameters
+    ///     ----------
+    ///     number :
+    ///         The block to make the head again, at most `reorg_depth`
+    ///         blocks before the head.
+    ///
+    ///     Raises
+    ///     ------
+    ///     ValueError :
+    ///         If the chain doesn't go back that far, or isn't there yet.
+    ///
+    pub fn rewind(&mut self, number: u64) -> Result<(), EthereumException> {
+        let head = to_u64(&self.chain.blocks.last().expect("a chain has a head").header.number);
+        if number < self.rewindable_to() || number > head {
+            return Err(EthereumException::ValueError);
+        }
+        if number == head {
+            return Ok(());
+        }
+        let kept = self.history.len() - (head - number) as usize;
+        let snapshot = self.history.drain(kept..).next().expect("the head is after `number`");
+        for _ in number..head {
+            let block = match self.chain.blocks.pop() {
+                Some(block) => block,
+                None => self.pruned.pop_back().expect("the chain holds the blocks it can rewind"),
+            };
+            self.transactions.remove_block(to_u64(&block.header.number), &block);
+            if let Some(store) = &mut self.state_history {
+                store.dereference(&block.header.state_root);
+            }
+        }
+        while self.chain.blocks.len() < RETAINED_BLOCKS {
+            match self.pruned.pop_back() {
+                Some(block) => self.chain.blocks.insert(0, block),
+                None => break,
+            }
+        }
+        self.restore(snapshot);
+        Ok(())
+    }
+
+    fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            state: self.chain.state.clone(),
+            fork: self.fork,
+            total_difficulty: self.total_difficulty.clone(),
+        }
+    }
+
+    /// Go back to `snapshot`, whose blocks are those of the chain.
+    fn restore(&mut self, snapshot: Snapshot) {
+        self.chain.state = snapshot.state;
+        self.fork = snapshot.fork;
+        self.total_difficulty = snapshot.total_difficulty;
+        self.ancestors = Ancestors::new(&self.chain.blocks);
+    }
+
+    ///
+    ///     Find the fork whose rules apply to the block after the head.
+    ///
+    pub fn fork_of(&self, header: &Header) -> &ScheduledFork {
+        let index = self.schedule.active(to_u64(&header.number), to_u64(&header.timestamp), &self.total_difficulty);
+        &self.schedule.forks()[index.max(self.fork)]
+    }
+
+    ///
+    ///     Apply the next block, first entering every fork that activates
+    ///     with it.
+    ///
+    ///     The forks are checked to be implemented before any of them
+    ///     changes the chain, but a chain that entered a fork stays in it
+    ///     even if the block turns out to be invalid, as the next block
+    ///     offered would be governed by the same fork. A block that doesn't
+    ///     name the head as its parent is rejected before that.
+    ///
+    ///     Parameters
+    ///     ----------
+    ///     block :
+    ///         Block to apply to the chain.
+    ///
+    ///     Raises
+    ///     ------
+    ///     InvalidBlock :
+    ///         If the block isn't a valid child of the head. Errors of the
+    ///         block itself are in the context of its number.
+    ///     FileNotFound :
+    ///         If the archive can't be written. The block is applied all the
+    ///         same.
+    ///     ValueError :
+    ///         If the state history doesn't have the state of the parent. The
+    ///         block is applied all the same.
+    ///
+    pub fn apply_block(&mut self, block: Block) -> Result<(), EthereumException> {
+        self.apply_block_traced(block, &mut None)
+    }
+
+    ///
+    ///     Apply the next block, like [Chain::apply_block], reporting the
+    ///     execution of its transactions to a tracer.
+    ///
+    ///     Parameters
+    ///     ----------
+    ///     tracer :
+    ///         Receives the trace events of the transactions of Frontier
+    ///         blocks, if set. It is left in place afterwards.
+    ///
+    pub fn apply_block_traced(
+        &mut self,
+        block: Block,
+        tracer: &mut Option<Box<dyn Tracer<frontier::vm::Evm>>>,
+    ) -> Result<(), EthereumException> {
+        // A rejected block leaves the state as it was, but not the forks it
+        // activates, which are applied to the chain first.
+        let header = &block.header;
+        let activates =
+            self.schedule.active(to_u64(&header.number), to_u64(&header.timestamp), &self.total_difficulty) > self.fork;
+        let snapshot = (self.reorg_depth > 0 || activates).then(|| self.snapshot());
+        // `state_transition` prunes the oldest block once the chain holds
+        // as many as it keeps.
+        let oldest = (self.chain.blocks.len() >= RETAINED_BLOCKS).then(|| self.chain.blocks[0].clone());
+        let number = block.header.number.clone();
+        let parent_root = self.chain.blocks.last().expect("a chain has a head").header.state_root;
+        if self.state_history.is_some() {
+            record_accesses(&mut self.chain.state);
+        }
+        let result = self.advance(block, tracer);
+        let accesses = take_accesses(&mut self.chain.state);
+        if let Err(error) = result {
+            if let Some(snapshot) = snapshot {
+                self.restore(snapshot);
+            }
+            return Err(error).in_block(&number);
+        }
+        let committed = match (&mut self.state_history, accesses) {
+            (Some(store), Some(accesses)) => commit_state(store, &parent_root, &self.chain.state, &accesses).map(drop),
+            _ => Ok(()),
+        };
+        if let Some(snapshot) = snapshot.filter(|_| self.reorg_depth > 0) {
+            if self.history.len() == self.reorg_depth {
+                self.history.pop_front();
+            }
+            self.history.push_back(snapshot);
+        }
+        let pruned = match oldest {
+            Some(oldest) => self.prune(oldest),
+            None => Ok(()),
+        };
+        committed.in_block(&number)?;
+        pruned
+    }
+
+    /// Keep a pruned block while the chain can rewind past it, and archive
+    /// the blocks it no longer can.
+    fn prune(&mut self, block: Block) -> Result<(), EthereumException> {
+        self.pruned.push_back(block);
+        while self.pruned.len() > self.reorg_depth {
+            let block = self.pruned.pop_front().expect("more blocks than the reorg depth");
+            self.transactions.drop_receipts(to_u64(&block.header.number));
+            if let Some(archive) = &mut self.archive {
+                // An archive kept from an earlier run may have it already.
+                if to_u64(&block.header.number) >= archive.range().end {
+                    archive.append(&block.header)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn advance(
+        &mut self,
+        block: Block,
+        tracer: &mut Option<Box<dyn Tracer<frontier::vm::Evm>>>,
+    ) -> Result<(), EthereumException> {
+        let header = &block.header;
+        if header.parent_hash != self.chain.blocks.last().expect("a chain has a head").hash() {
+            return Err(EthereumException::InvalidBlock);
+        }
+        let next = self
+            .schedule
+            .active(to_u64(&header.number), to_u64(&header.timestamp), &self.total_difficulty)
+            .max(self.fork);
+        let forks = &self.schedule.forks()[self.fork + 1..=next];
+        let upgrades = forks.iter().map(implementation).collect::<Result<Vec<_>, _>>()?;
+        for upgrade in upgrades {
+            let old = mem::replace(
+                &mut self.chain,
+                BlockChain {
+                    blocks: vec![],
+                    state: State::default(),
+                    chain_id: 0,
+                },
+            );
+            self.chain = match upgrade {
+                HardFork::Frontier => frontier::fork::apply_fork(old)?,
+            };
+        }
+        self.fork = next;
+
+        let difficulty = header.difficulty.clone();
+        let number = to_u64(&header.number);
+        let _span = tracing::info_span!("block", number).entered();
+        let transactions = block.transactions.len();
+        let gas_used = to_u64(&header.gas_used);
+        let receipts = match implementation(self.fork())? {
+            HardFork::Frontier => {
+                // Tracing every opcode only to see the ends of the
+                // transactions costs too much to do unasked.
+                let mut logger: Option<Box<dyn Tracer<frontier::vm::Evm>>> =
+                    match tracer.is_none() && tracing::enabled!(tracing::Level::DEBUG) {
+                        true => Some(Box::new(TransactionLogger::default())),
+                        false => None,
+                    };
+                let tracer = if tracer.is_some() { tracer } else { &mut logger };
+                frontier::fork::state_transition_with_ancestors(&mut self.chain, &mut self.ancestors, block, tracer)?
+            }
+        };
+        let block = self.chain.blocks.last().expect("the block was applied");
+        self.transactions.add_block(number, block, Some(receipts));
+        self.total_difficulty += difficulty;
+        tracing::debug!(transactions, gas_used, "applied");
+        Ok(())
+    }
+}
+
+/// Logs the end of every transaction of a block, at debug.
+#[derive(Default)]
+struct TransactionLogger {
+    index: usize,
+}
+
+impl<E> Tracer<E> for TransactionLogger {
+    fn trace(&mut self, _evm: &E, event: &TraceEvent) {
+        if let TraceEvent::TransactionEnd { gas_used, error, .. } = event {
+            let _span = tracing::info_span!("tx", index = self.index).entered();
+            tracing::debug!(gas_used = to_u64(gas_used), error = error.as_deref(), "executed");
+            self.index += 1;
+        }
+    }
+}
@@ -0,0 +1,159 @@
+//!
+//! # Chain Info
+//!
+//! What tools need to know about the network a [Chain](super::chain::Chain)
+//! or a [ChainSpec](super::chain_spec::ChainSpec) follows: its chain id, the
+//! fork it is on, how its blocks are sealed and its fork schedule.
+//!
+//! A chain is sealed by proof of work, or by the signers of Clique (EIP-225)
+//! if the `config` of its genesis has a `clique` section, until Paris, and by
+//! proof of stake from then on.
+//!
+
+use serde_json::{json, Value};
+
+use super::{
+    evm_tools::alloc::json_uint,
+    forks::{ForkCriteria, ForkSchedule, ScheduledFork},
+};
+use crate::ethereum::exceptions::EthereumException;
+
+/// The blocks between Clique checkpoints when the configuration doesn't
+/// say.
+pub const CLIQUE_DEFAULT_EPOCH: u64 = 30_000;
+
+/// How the blocks of a chain are sealed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Consensus {
+    ProofOfWork,
+    /// Clique, EIP-225, whose headers a
+    /// [Snapshot](crate::ethereum::clique::Snapshot) checks.
+    ProofOfAuthority {
+        /// The seconds between blocks.
+        period: u64,
+        /// The blocks between checkpoints, which reset the votes and list
+        /// the signers.
+        epoch: u64,
+    },
+    ProofOfStake,
+}
+
+impl Consensus {
+    /// The short name of the consensus.
+    pub fn name(self) -> &'static str {
+        match self {
+            Consensus::ProofOfWork => "PoW",
+            Consensus::ProofOfAuthority { .. } => "PoA",
+            Consensus::ProofOfStake => "PoS",
+        }
+    }
+
+    ///
+    ///     Read how a chain is sealed before the merge from the `config` of a
+    ///     geth style genesis file.
+    ///
+    ///     Parameters
+    ///     ----------
+    ///     config :
+    ///         The chain configuration.
+    ///
+    ///     Returns
+    ///     -------
+    ///     consensus : `Consensus`
+    ///         Proof of authority if the configuration has a `clique`
+    ///         section, and proof of work otherwise.
+    ///
+    pub fn from_config(config: &Value) -> Result<Self, EthereumException> {
+        let clique = &config["clique"];
+        if clique.is_null() {
+            return Ok(Consensus::ProofOfWork);
+        }
+        let field = |name: &str, default: u64| match &clique[name] {
+            Value::Null => Ok(default),
+            value => u64::try_from(&json_uint(value)?).map_err(|_| EthereumException::ValueError),
+        };
+        Ok(Consensus::ProofOfAuthority {
+            period: field("period", 0)?,
+            epoch: field("epoch", CLIQUE_DEFAULT_EPOCH)?,
+        })
+    }
+}
+
+///
+///     The network a chain follows.
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ChainInfo {
+    pub chain_id: u64,
+    /// The fork of the head of the chain.
+    pub fork: ScheduledFork,
+    /// How the head of the chain is sealed.
+    pub consensus: Consensus,
+    pub schedule: ForkSchedule,
+}
+
+impl ChainInfo {
+    ///
+    ///     Describe a chain on one of the forks of its schedule.
+    ///
+    ///     Parameters
+    ///     ----------
+    ///     chain_id :
+    ///         The chain id.
+    ///     schedule :
+    ///         The forks of the chain.
+    ///     fork :
+    ///         The position in `schedule` of the fork the chain is on.
+    ///     sealing :
+    ///         How the chain is sealed before the merge.
+    ///
+    pub fn new(chain_id: u64, schedule: ForkSchedule, fork: usize, sealing: Consensus) -> Self {
+        let merged = schedule.forks()[..=fork].iter().any(|fork| fork.name == "Paris");
+        Self {
+            chain_id,
+            fork: schedule.forks()[fork].clone(),
+            consensus: if merged { Consensus::ProofOfStake } else { sealing },
+            schedule,
+        }
+    }
+
+    ///
+    ///     Serialize the info for tools, with the forks in the order they
+    ///     activate:
+    ///
+    ///     ```json
+    ///     {
+    ///         "chainId": 1,
+    ///         "fork": "Frontier",
+    ///         "consensus": "PoW",
+    ///         "forks": [{ "name": "Frontier", "block": 0 }, ...]
+    ///     }
+    ///     ```
+    ///
+    ///     Clique chains add their `period` and `epoch` under `clique`.
+    ///
+    pub fn to_json(&self) -> Value {
+        let forks: Vec<Value> = self
+            .schedule
+            .forks()
+            .iter()
+            .map(|fork| match &fork.criteria {
+                ForkCriteria::ByBlockNumber(number) => json!({ "name": fork.name, "block": number }),
+                ForkCriteria::ByTimestamp(time) => json!({ "name": fork.name, "timestamp": time }),
+                ForkCriteria::ByTotalDifficulty(difficulty) => {
+                    json!({ "name": fork.name, "totalDifficulty": format!("{difficulty:#x}") })
+                }
+            })
+            .collect();
+        let mut info = json!({
+            "chainId": self.chain_id,
+            "fork": self.fork.name,
+            "consensus": self.consensus.name(),
+            "forks": forks,
+        });
+        if let Consensus::ProofOfAuthority { period, epoch } = self.consensus {
+            info["clique"] = json!({ "period": period, "epoch": epoch });
+        }
+        info
+    }
+}
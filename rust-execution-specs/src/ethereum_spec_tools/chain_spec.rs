@@ -0,0 +1,330 @@
+//!
+//! # Chain Spec
+//!
+//! A [ChainSpec] describes a network: its genesis, when its forks activate
+//! and its fee parameters, so that the spec can follow Sepolia, Holesky or a
+//! devnet as well as mainnet. It is read from JSON or, with the `toml`
+//! feature, from TOML:
+//!
+//! ```toml
+//! name = "devnet"
+//! chainId = 1337
+//! # A geth style genesis file, relative to the spec, or the genesis itself
+//! # as a table.
+//! genesis = "genesis.json"
+//!
+//! [forks]
+//! Homestead = { block = 0 }
+//! TangerineWhistle = { block = 10 }
+//! Paris = { totalDifficulty = "0x20000" }
+//! Shanghai = { timestamp = 1700000000 }
+//!
+//! [params]
+//! elasticityMultiplier = 4
+//! blobSchedule = { Cancun = { target = 3, max = 6, baseFeeUpdateFraction = 3338477 } }
+//! ```
+//!
+//! Without `forks`, the `config` of the genesis schedules them, as in
+//! [ForkSchedule::from_config]. The parameters not given are mainnet's.
+//!
+
+use std::collections::BTreeMap;
+#[cfg(feature = "fs")]
+use std::path::Path;
+
+use serde_json::Value;
+
+use super::{
+    chain_info::{ChainInfo, Consensus},
+    evm_tools::alloc::{json_str, json_uint},
+    forks::{ForkCriteria, ForkSchedule},
+};
+use crate::ethereum::{
+    base_types::Uint,
+    base_fee::{BaseFeeSchedule, BlobFeeSchedule, CANCUN, GAS_PER_BLOB},
+    exceptions::EthereumException,
+    genesis::{parse_genesis_configuration, GenesisConfiguration},
+};
+
+///
+///     The blobs a fork allows in a block, as in EIP-7840.
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BlobSchedule {
+    pub target: u64,
+    pub max: u64,
+    pub base_fee_update_fraction: u64,
+}
+
+impl BlobSchedule {
+    ///
+    ///     The blob fee market of a fork with this schedule.
+    ///
+    pub fn fee_schedule(&self) -> BlobFeeSchedule {
+        BlobFeeSchedule {
+            target_blob_gas_per_block: self.target * GAS_PER_BLOB,
+            base_fee_update_fraction: self.base_fee_update_fraction,
+            ..CANCUN
+        }
+    }
+}
+
+///
+///     The fee market parameters of a chain. Frontier has neither a base fee
+///     nor blobs, so only the forks that do read them.
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FeeParams {
+    /// The base fee of the first London block, in wei (EIP-1559).
+    pub initial_base_fee: u64,
+    /// The base fee changes by at most `1 / base_fee_max_change_denominator`
+    /// from one block to the next (EIP-1559).
+    pub base_fee_max_change_denominator: u64,
+    /// The gas limit of a block is its gas target times this (EIP-1559).
+    pub elasticity_multiplier: u64,
+    /// The blob schedule of each fork with blobs, by fork name.
+    pub blob_schedule: BTreeMap<String, BlobSchedule>,
+}
+
+impl FeeParams {
+    ///
+    ///     The fee market of the forks with a base fee.
+    ///
+    pub fn base_fee_schedule(&self) -> BaseFeeSchedule {
+        BaseFeeSchedule {
+            elasticity_multiplier: self.elasticity_multiplier,
+            base_fee_max_change_denominator: self.base_fee_max_change_denominator,
+        }
+    }
+}
+
+impl Default for FeeParams {
+    ///
+    ///     Mainnet's parameters.
+    ///
+    fn default() -> Self {
+        Self {
+            initial_base_fee: 1_000_000_000,
+            base_fee_max_change_denominator: 8,
+            elasticity_multiplier: 2,
+            blob_schedule: BTreeMap::from([
+                (
+                    "Cancun".to_owned(),
+                    BlobSchedule {
+                        target: 3,
+                        max: 6,
+                        base_fee_update_fraction: 3_338_477,
+                    },
+                ),
+                (
+                    "Prague".to_owned(),
+                    BlobSchedule {
+                        target: 6,
+                        max: 9,
+                        base_fee_update_fraction: 5_007_716,
+                    },
+                ),
+            ]),
+        }
+    }
+}
+
+///
+///     A network the spec can run: its genesis, forks and fee parameters.
+///
+pub struct ChainSpec {
+    pub name: String,
+    pub genesis: GenesisConfiguration,
+    pub schedule: ForkSchedule,
+    pub params: FeeParams,
+    /// How the chain is sealed before the merge, from the `config` of its
+    /// genesis.
+    pub consensus: Consensus,
+}
+
+fn decode_error(message: String) -> EthereumException {
+    EthereumException::JsonDecodeError(message)
+}
+
+fn json_u64(value: &Value) -> Result<u64, EthereumException> {
+    u64::try_from(&json_uint(value)?).map_err(|_| EthereumException::ValueError)
+}
+
+fn parse_activation(name: &str, activation: &Value) -> Result<ForkCriteria, EthereumException> {
+    let (criterion, value) = match activation.as_object().map(|fields| fields.iter().collect::<Vec<_>>()) {
+        Some(fields) if fields.len() == 1 => fields[0],
+        _ => return Err(decode_error(format!("the activation of {name} needs exactly one criterion"))),
+    };
+    match (criterion.as_str(), value) {
+        ("block", number) => Ok(ForkCriteria::ByBlockNumber(json_u64(number)?)),
+        ("timestamp", time) => Ok(ForkCriteria::ByTimestamp(json_u64(time)?)),
+        ("totalDifficulty", difficulty) => Ok(ForkCriteria::ByTotalDifficulty(json_uint(difficulty)?)),
+        (criterion, _) => Err(decode_error(format!("unknown activation {criterion} of {name}"))),
+    }
+}
+
+fn parse_blob_schedule(schedule: &Value) -> Result<BTreeMap<String, BlobSchedule>, EthereumException> {
+    let forks = schedule.as_object().ok_or_else(|| decode_error(format!("expected a table, got {schedule}")))?;
+    forks
+        .iter()
+        .map(|(fork, blobs)| {
+            let blobs = BlobSchedule {
+                target: json_u64(&blobs["target"])?,
+                max: json_u64(&blobs["max"])?,
+                base_fee_update_fraction: json_u64(&blobs["baseFeeUpdateFraction"])?,
+            };
+            Ok((fork.clone(), blobs))
+        })
+        .collect()
+}
+
+fn parse_params(params: &Value, genesis_config: &Value) -> Result<FeeParams, EthereumException> {
+    let mut fee_params = FeeParams::default();
+    if let Some(fee) = params.get("initialBaseFee") {
+        fee_params.initial_base_fee = json_u64(fee)?;
+    }
+    if let Some(denominator) = params.get("baseFeeMaxChangeDenominator") {
+        fee_params.base_fee_max_change_denominator = json_u64(denominator)?;
+    }
+    if let Some(multiplier) = params.get("elasticityMultiplier") {
+        fee_params.elasticity_multiplier = json_u64(multiplier)?;
+    }
+    // geth keeps the blob schedule in the chain configuration.
+    let blob_schedule = params.get("blobSchedule").or(genesis_config.get("blobSchedule"));
+    if let Some(blob_schedule) = blob_schedule {
+        fee_params.blob_schedule.extend(parse_blob_schedule(blob_schedule)?);
+    }
+    Ok(fee_params)
+}
+
+impl ChainSpec {
+    ///
+    ///     Read a spec from its JSON (or TOML) value, with its genesis
+    ///     already in place of any file name.
+    ///
+    fn from_value(spec: &Value) -> Result<Self, EthereumException> {
+        let mut genesis = spec["genesis"].clone();
+        if !genesis.is_object() {
+            return Err(decode_error(format!("expected a genesis, got {genesis}")));
+        }
+        if let Some(chain_id) = spec.get("chainId") {
+            genesis["config"]["chainId"] = chain_id.clone();
+        }
+        if !genesis["config"]["chainId"].is_u64() {
+            return Err(decode_error("the chain has no chain id".to_owned()));
+        }
+
+        let schedule = match spec.get("forks") {
+            Some(forks) => {
+                let forks = forks.as_object().ok_or_else(|| decode_error(format!("expected a table, got {forks}")))?;
+                let activations = forks
+                    .iter()
+                    .map(|(name, activation)| Ok((name.as_str(), parse_activation(name, activation)?)))
+                    .collect::<Result<Vec<_>, EthereumException>>()?;
+                ForkSchedule::from_activations(activations)?
+            }
+            None => ForkSchedule::from_config(&genesis["config"])?,
+        };
+        let params = parse_params(spec.get("params").unwrap_or(&Value::Null), &genesis["config"])?;
+        let consensus = Consensus::from_config(&genesis["config"])?;
+        let name = match spec.get("name") {
+            Some(name) => json_str(name)?.to_owned(),
+            None => String::new(),
+        };
+
+        Ok(Self {
+            name,
+            genesis: parse_genesis_configuration(&genesis.to_string())?,
+            schedule,
+            params,
+            consensus,
+        })
+    }
+
+    ///
+    ///     The chain id, fork, consensus and schedule of the network at its
+    ///     genesis.
+    ///
+    pub fn info(&self) -> ChainInfo {
+        let genesis_time = u64::try_from(&self.genesis.timestamp).unwrap_or(u64::MAX);
+        let fork = self.schedule.active(0, genesis_time, &Uint::from(0u8));
+        ChainInfo::new(self.genesis.chain_id, self.schedule.clone(), fork, self.consensus)
+    }
+
+    ///
+    ///     Parse a spec from JSON. The genesis must be given in full.
+    ///
+    ///     Parameters
+    ///     ----------
+    ///     json :
+    ///         The spec.
+    ///
+    ///     Returns
+    ///     -------
+    ///     spec : `ChainSpec`
+    ///         The network described.
+    ///
+    pub fn from_json(json: &str) -> Result<Self, EthereumException> {
+        let spec: Value = serde_json::from_str(json).map_err(|e| decode_error(e.to_string()))?;
+        Self::from_value(&spec)
+    }
+
+    ///
+    ///     Parse a spec from TOML. The genesis must be given in full.
+    ///
+    ///     Parameters
+    ///     ----------
+    ///     toml :
+    ///         The spec.
+    ///
+    ///     Returns
+    ///     -------
+    ///     spec : `ChainSpec`
+    ///         The network described.
+    ///
+    #[cfg(feature = "toml")]
+    pub fn from_toml(toml: &str) -> Result<Self, EthereumException> {
+        Self::from_value(&toml_to_json(toml)?)
+    }
+
+    ///
+    ///     Read a spec from a file, as TOML if its extension is `.toml` and as
+    ///     JSON otherwise. A genesis given as a file name is read relative to
+    ///     the spec.
+    ///
+    ///     Parameters
+    ///     ----------
+    ///     path :
+    ///         The spec file.
+    ///
+    ///     Returns
+    ///     -------
+    ///     spec : `ChainSpec`
+    ///         The network described.
+    ///
+    #[cfg(feature = "fs")]
+    pub fn load(path: &Path) -> Result<Self, EthereumException> {
+        let read = |path: &Path| {
+            std::fs::read_to_string(path).map_err(|_| EthereumException::FileNotFound(path.display().to_string()))
+        };
+        let contents = read(path)?;
+        let mut spec: Value = match path.extension().is_some_and(|extension| extension == "toml") {
+            #[cfg(feature = "toml")]
+            true => toml_to_json(&contents)?,
+            #[cfg(not(feature = "toml"))]
+            true => return Err(decode_error("TOML chain specs need the `toml` feature".to_owned())),
+            false => serde_json::from_str(&contents).map_err(|e| decode_error(e.to_string()))?,
+        };
+        if let Value::String(genesis_file) = &spec["genesis"] {
+            let genesis_path = path.parent().unwrap_or(Path::new(".")).join(genesis_file);
+            spec["genesis"] = serde_json::from_str(&read(&genesis_path)?).map_err(|e| decode_error(e.to_string()))?;
+        }
+        Self::from_value(&spec)
+    }
+}
+
+#[cfg(feature = "toml")]
+fn toml_to_json(toml: &str) -> Result<Value, EthereumException> {
+    let spec: toml::Value = toml::from_str(toml).map_err(|e| decode_error(e.to_string()))?;
+    serde_json::to_value(spec).map_err(|e| decode_error(e.to_string()))
+}
@@ -0,0 +1,847 @@
+//!
+//! # Engine API
+//!
+//! The part of the Engine API a consensus client drives block processing
+//! with, `engine_newPayloadV1` to `V3` and `engine_forkchoiceUpdatedV1` to
+//! `V3`, so the spec can stand in for an execution client in a test harness
+//! such as the hive engine simulators.
+//!
+//! The spec only implements Frontier, and payloads are executed with its
+//! rules, except that there is no block reward after the merge:
+//!
+//! - `prevRandao` is what the `DIFFICULTY` opcode returns;
+//! - withdrawals are credited to their addresses;
+//! - the base fee and the blob gas fields are part of the block hash, but are
+//!   not otherwise checked;
+//! - only legacy transactions can be decoded, and their senders are
+//!   recovered by the signature rules of the fork, so without the replay
+//!   protection of EIP-155.
+//!
+//! The handler follows a single chain. Payloads extending it are executed,
+//! those extending any other known block are accepted without executing
+//! them, and a fork choice of anything but the tip or one of its ancestors
+//! is answered with `SYNCING`. Payloads are never built, so
+//! `payloadAttributes` are ignored, and requests are not authenticated.
+//!
+
+use std::{collections::HashMap, io, net::TcpListener};
+
+use num_bigint::BigUint;
+use serde_json::{json, Value};
+
+use super::{
+    evm_tools::alloc::{json_str, json_uint},
+    forks::HardFork,
+    rpc::RpcError,
+    rpc_server::{fault, handle_requests, serve, METHOD_NOT_FOUND},
+};
+use crate::ethereum::{
+    base_types::{Bytes, Bytes8, Uint, U256, U64},
+    exceptions::EthereumException,
+    frontier::{
+        bloom::logs_bloom,
+        fork::{check_gas_limit, compute_receipts_root, make_receipt, process_transaction, BlockChain},
+        fork_types::{keccak256, Address, Bloom, Hash32, Header, Root, Transaction},
+        state::{create_ether, state_root, State},
+        trie::{dummy_root, root, trie_set, Trie},
+        vm::{BlockEnvironment, TxEnvironment},
+    },
+    rlp::{self, Simple, RLP},
+    transaction_signature::{self, signature_schedule, SignatureSchedule},
+    utils::{
+        ensure::ensure,
+        hexadecimal::{hex, hex_to_bytes, hex_to_bytes20, hex_to_bytes256, hex_to_hash},
+    },
+};
+
+/// Wei in a gwei, the unit of withdrawal amounts.
+const GWEI: u64 = 1_000_000_000;
+
+/// The Engine API error for a fork choice naming unknown blocks.
+const INVALID_FORKCHOICE_STATE: i64 = -38002;
+
+fn invalid_params(message: impl Into<String>) -> RpcError {
+    fault(-32602, message)
+}
+
+fn decode_error(error: EthereumException) -> RpcError {
+    invalid_params(format!("invalid params: {error:?}"))
+}
+
+/// A withdrawal from the consensus layer, credited to `address`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Withdrawal {
+    pub index: U64,
+    pub validator_index: U64,
+    pub address: Address,
+    /// In gwei.
+    pub amount: U64,
+}
+
+impl RLP for Withdrawal {
+    fn encode(&self) -> Bytes {
+        (self.index, self.validator_index, self.address, self.amount).encode()
+    }
+}
+
+impl Withdrawal {
+    pub fn from_json(value: &Value) -> Result<Self, EthereumException> {
+        let number = |name: &str| {
+            u64::try_from(json_uint(&value[name])?)
+                .map_err(|_| EthereumException::JsonDecodeError(format!("`{name}` does not fit in 64 bits")))
+        };
+        Ok(Self {
+            index: number("index")?,
+            validator_index: number("validatorIndex")?,
+            address: hex_to_bytes20(json_str(&value["address"])?)?,
+            amount: number("amount")?,
+        })
+    }
+
+    pub fn to_json(&self) -> Value {
+        json!({
+            "index": format!("{:#x}", self.index),
+            "validatorIndex": format!("{:#x}", self.validator_index),
+            "address": hex(&self.address),
+            "amount": format!("{:#x}", self.amount),
+        })
+    }
+}
+
+/// The fields forks after the merge append to a header, in the order they
+/// are hashed after the fields of a Frontier `Header`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct HeaderExtensions {
+    /// From London.
+    pub base_fee_per_gas: Option<Uint>,
+    /// From Shanghai.
+    pub withdrawals_root: Option<Root>,
+    /// From Cancun, as are the next two.
+    pub blob_gas_used: Option<Uint>,
+    pub excess_blob_gas: Option<Uint>,
+    pub parent_beacon_block_root: Option<Hash32>,
+}
+
+impl HeaderExtensions {
+    ///
+    ///     Compute the hash of `header` extended with these fields.
+    ///
+    pub fn hash(&self, header: &Header) -> Hash32 {
+        let mut fields: Vec<&dyn RLP> = vec![
+            &header.parent_hash,
+            &header.ommers_hash,
+            &header.coinbase,
+            &header.state_root,
+            &header.transactions_root,
+            &header.receipt_root,
+            &header.bloom,
+            &header.difficulty,
+            &header.number,
+            &header.gas_limit,
+            &header.gas_used,
+            &header.timestamp,
+            &header.extra_data,
+            &header.mix_digest,
+            &header.nonce,
+        ];
+        let optional: [Option<&dyn RLP>; 5] = [
+            self.base_fee_per_gas.as_ref().map(|field| field as &dyn RLP),
+            self.withdrawals_root.as_ref().map(|field| field as &dyn RLP),
+            self.blob_gas_used.as_ref().map(|field| field as &dyn RLP),
+            self.excess_blob_gas.as_ref().map(|field| field as &dyn RLP),
+            self.parent_beacon_block_root.as_ref().map(|field| field as &dyn RLP),
+        ];
+        fields.extend(optional.into_iter().flatten());
+        keccak256(&rlp::encode_iter(fields))
+    }
+}
+
+/// The `ExecutionPayload` of every version up to `V3`; the fields of later
+/// versions are `None` in earlier ones.
+#[derive(Clone, Debug)]
+pub struct ExecutionPayload {
+    pub parent_hash: Hash32,
+    pub fee_recipient: Address,
+    pub state_root: Root,
+    pub receipts_root: Root,
+    pub logs_bloom: Bloom,
+    pub prev_randao: Hash32,
+    pub block_number: Uint,
+    pub gas_limit: Uint,
+    pub gas_used: Uint,
+    pub timestamp: U256,
+    pub extra_data: Bytes,
+    pub base_fee_per_gas: Uint,
+    pub block_hash: Hash32,
+    /// The encoded transactions.
+    pub transactions: Vec<Bytes>,
+    pub withdrawals: Option<Vec<Withdrawal>>,
+    pub blob_gas_used: Option<Uint>,
+    pub excess_blob_gas: Option<Uint>,
+}
+
+impl ExecutionPayload {
+    pub fn from_json(value: &Value) -> Result<Self, EthereumException> {
+        let hash = |name: &str| hex_to_hash(json_str(&value[name])?);
+        let optional_uint = |name: &str| match value.get(name) {
+            Some(number) if !number.is_null() => json_uint(number).map(Some),
+            _ => Ok(None),
+        };
+        let transactions = match &value["transactions"] {
+            Value::Array(transactions) => transactions
+                .iter()
+                .map(|tx| hex_to_bytes(json_str(tx)?))
+                .collect::<Result<_, _>>()?,
+            _ => return Err(EthereumException::JsonDecodeError("missing field `transactions`".to_owned())),
+        };
+        let withdrawals = match value.get("withdrawals") {
+            Some(Value::Array(withdrawals)) => Some(
+                withdrawals
+                    .iter()
+                    .map(Withdrawal::from_json)
+                    .collect::<Result<_, _>>()?,
+            ),
+            _ => None,
+        };
+        Ok(Self {
+            parent_hash: hash("parentHash")?,
+            fee_recipient: hex_to_bytes20(json_str(&value["feeRecipient"])?)?,
+            state_root: hash("stateRoot")?,
+            receipts_root: hash("receiptsRoot")?,
+            logs_bloom: hex_to_bytes256(json_str(&value["logsBloom"])?)?,
+            prev_randao: hash("prevRandao")?,
+            block_number: json_uint(&value["blockNumber"])?,
+            gas_limit: json_uint(&value["gasLimit"])?,
+            gas_used: json_uint(&value["gasUsed"])?,
+            timestamp: json_uint(&value["timestamp"])?,
+            extra_data: hex_to_bytes(json_str(&value["extraData"])?)?,
+            base_fee_per_gas: json_uint(&value["baseFeePerGas"])?,
+            block_hash: hash("blockHash")?,
+            transactions,
+            withdrawals,
+            blob_gas_used: optional_uint("blobGasUsed")?,
+            excess_blob_gas: optional_uint("excessBlobGas")?,
+        })
+    }
+
+    /// The root of the trie of the encoded transactions.
+    pub fn transactions_root(&self) -> Root {
+        let mut trie = Trie::<Bytes, Bytes>::new(false, Bytes::default());
+        for (i, tx) in self.transactions.iter().enumerate() {
+            trie_set(&mut trie, rlp::encode(&Uint::from(i)), tx.clone());
+        }
+        root(&trie, dummy_root)
+    }
+
+    /// The root of the trie of the withdrawals, if the payload has them.
+    pub fn withdrawals_root(&self) -> Option<Root> {
+        let withdrawals = self.withdrawals.as_ref()?;
+        let mut trie = Trie::<Bytes, Bytes>::new(false, Bytes::default());
+        for (i, withdrawal) in withdrawals.iter().enumerate() {
+            trie_set(&mut trie, rlp::encode(&Uint::from(i)), rlp::encode(withdrawal));
+        }
+        Some(root(&trie, dummy_root))
+    }
+
+    /// The Frontier fields of the header of the payload's block.
+    pub fn header(&self) -> Header {
+        Header {
+            parent_hash: self.parent_hash,
+            ommers_hash: keccak256(&rlp::encode_iter(Vec::<Header>::new())),
+            coinbase: self.fee_recipient,
+            state_root: self.state_root,
+            transactions_root: self.transactions_root(),
+            receipt_root: self.receipts_root,
+            bloom: self.logs_bloom,
+            difficulty: Uint::default(),
+            number: self.block_number.clone(),
+            gas_limit: self.gas_limit.clone(),
+            gas_used: self.gas_used.clone(),
+            timestamp: self.timestamp.clone(),
+            extra_data: self.extra_data.clone(),
+            mix_digest: self.prev_randao,
+            nonce: Bytes8::default(),
+        }
+    }
+
+    ///
+    ///     The fields of the payload's block header added after the merge.
+    ///
+    ///     Parameters
+    ///     ----------
+    ///     parent_beacon_block_root :
+    ///         The root passed with the payload to `engine_newPayloadV3`,
+    ///         which is part of the header from then on.
+    ///
+    pub fn extensions(&self, parent_beacon_block_root: Option<&Hash32>) -> HeaderExtensions {
+        let has_blob_fields = self.blob_gas_used.is_some() && self.excess_blob_gas.is_some();
+        HeaderExtensions {
+            base_fee_per_gas: Some(self.base_fee_per_gas.clone()),
+            withdrawals_root: self.withdrawals_root(),
+            blob_gas_used: self.blob_gas_used.clone().filter(|_| has_blob_fields),
+            excess_blob_gas: self.excess_blob_gas.clone().filter(|_| has_blob_fields),
+            parent_beacon_block_root: parent_beacon_block_root.copied(),
+        }
+    }
+
+    ///
+    ///     Compute the hash of the block the payload is the body of, with the
+    ///     fields of every fork the payload has.
+    ///
+    pub fn compute_block_hash(&self, parent_beacon_block_root: Option<&Hash32>) -> Hash32 {
+        self.extensions(parent_beacon_block_root).hash(&self.header())
+    }
+}
+
+/// Decode a legacy transaction, the only kind the spec knows.
+pub(crate) fn decode_transaction(encoded: &[u8]) -> Result<Transaction, EthereumException> {
+    let Simple::Sequence(fields) = rlp::decode(encoded)? else {
+        return Err(EthereumException::RLPDecodingError);
+    };
+    let fields = fields
+        .into_iter()
+        .map(|field| match field {
+            Simple::Bytes(bytes) => Ok(bytes),
+            Simple::Sequence(_) => Err(EthereumException::RLPDecodingError),
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let [nonce, gas_price, gas, to, value, data, v, r, s] = <[Bytes; 9]>::try_from(fields)
+        .map_err(|_| EthereumException::RLPDecodingError)?;
+    let to = match to.len() {
+        0 => None,
+        20 => Some(Address::try_from(&*to).unwrap()),
+        _ => return Err(EthereumException::RLPDecodingError),
+    };
+    Ok(Transaction {
+        nonce: BigUint::from_bytes_be(&nonce),
+        gas_price: BigUint::from_bytes_be(&gas_price),
+        gas: BigUint::from_bytes_be(&gas),
+        to,
+        value: BigUint::from_bytes_be(&value),
+        data,
+        v: BigUint::from_bytes_be(&v),
+        r: BigUint::from_bytes_be(&r),
+        s: BigUint::from_bytes_be(&s),
+    })
+}
+
+/// The `status` of a `PayloadStatus`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PayloadStatusKind {
+    Valid,
+    Invalid,
+    Syncing,
+    Accepted,
+    /// Only returned by `engine_newPayloadV1`; later versions say
+    /// `Invalid` instead.
+    InvalidBlockHash,
+}
+
+impl PayloadStatusKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Valid => "VALID",
+            Self::Invalid => "INVALID",
+            Self::Syncing => "SYNCING",
+            Self::Accepted => "ACCEPTED",
+            Self::InvalidBlockHash => "INVALID_BLOCK_HASH",
+        }
+    }
+}
+
+/// What the execution layer makes of a payload or fork choice.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PayloadStatus {
+    pub status: PayloadStatusKind,
+    /// The most recent valid block in the chain of the payload.
+    pub latest_valid_hash: Option<Hash32>,
+    pub validation_error: Option<String>,
+}
+
+impl PayloadStatus {
+    fn new(status: PayloadStatusKind, latest_valid_hash: Option<Hash32>) -> Self {
+        Self {
+            status,
+            latest_valid_hash,
+            validation_error: None,
+        }
+    }
+
+    fn invalid(latest_valid_hash: Hash32, error: impl Into<String>) -> Self {
+        Self {
+            status: PayloadStatusKind::Invalid,
+            latest_valid_hash: Some(latest_valid_hash),
+            validation_error: Some(error.into()),
+        }
+    }
+
+    pub fn to_json(&self) -> Value {
+        json!({
+            "status": self.status.as_str(),
+            "latestValidHash": self.latest_valid_hash.as_ref().map(|hash| hex(hash)),
+            "validationError": self.validation_error,
+        })
+    }
+}
+
+/// The `ForkchoiceStateV1` of `engine_forkchoiceUpdated`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ForkchoiceState {
+    pub head_block_hash: Hash32,
+    pub safe_block_hash: Hash32,
+    pub finalized_block_hash: Hash32,
+}
+
+impl ForkchoiceState {
+    pub fn from_json(value: &Value) -> Result<Self, EthereumException> {
+        let hash = |name: &str| hex_to_hash(json_str(&value[name])?);
+        Ok(Self {
+            head_block_hash: hash("headBlockHash")?,
+            safe_block_hash: hash("safeBlockHash")?,
+            finalized_block_hash: hash("finalizedBlockHash")?,
+        })
+    }
+}
+
+/// A block the handler knows of.
+#[derive(Clone, Debug)]
+struct BlockInfo {
+    header: Header,
+    extensions: HeaderExtensions,
+    transaction_hashes: Vec<Hash32>,
+    withdrawals: Option<Vec<Withdrawal>>,
+    /// Whether the block was executed, rather than only accepted.
+    executed: bool,
+}
+
+impl BlockInfo {
+    fn from_payload(payload: &ExecutionPayload, extensions: HeaderExtensions, executed: bool) -> Self {
+        Self {
+            header: payload.header(),
+            extensions,
+            transaction_hashes: payload.transactions.iter().map(|tx| keccak256(tx)).collect(),
+            withdrawals: payload.withdrawals.clone(),
+            executed,
+        }
+    }
+
+    /// The block as `eth_getBlockByHash` returns it, with the hashes of its
+    /// transactions.
+    fn to_json(&self, hash: &Hash32) -> Value {
+        let header = &self.header;
+        let mut block = json!({
+            "hash": hex(hash),
+            "parentHash": hex(&header.parent_hash),
+            "sha3Uncles": hex(&header.ommers_hash),
+            "miner": hex(&header.coinbase),
+            "stateRoot": hex(&header.state_root),
+            "transactionsRoot": hex(&header.transactions_root),
+            "receiptsRoot": hex(&header.receipt_root),
+            "logsBloom": hex(&header.bloom),
+            "difficulty": format!("{:#x}", header.difficulty),
+            "number": format!("{:#x}", header.number),
+            "gasLimit": format!("{:#x}", header.gas_limit),
+            "gasUsed": format!("{:#x}", header.gas_used),
+            "timestamp": format!("{:#x}", header.timestamp),
+            "extraData": hex(&header.extra_data),
+            "mixHash": hex(&header.mix_digest),
+            "nonce": hex(&header.nonce),
+            "transactions": self.transaction_hashes.iter().map(|hash| hex(hash)).collect::<Vec<_>>(),
+            "uncles": [],
+        });
+        let extensions = &self.extensions;
+        if let Some(base_fee) = &extensions.base_fee_per_gas {
+            block["baseFeePerGas"] = json!(format!("{base_fee:#x}"));
+        }
+        if let Some(root) = &extensions.withdrawals_root {
+            block["withdrawalsRoot"] = json!(hex(root));
+            let withdrawals = self.withdrawals.iter().flatten().map(Withdrawal::to_json);
+            block["withdrawals"] = withdrawals.collect();
+        }
+        if let Some(blob_gas_used) = &extensions.blob_gas_used {
+            block["blobGasUsed"] = json!(format!("{blob_gas_used:#x}"));
+        }
+        if let Some(excess_blob_gas) = &extensions.excess_blob_gas {
+            block["excessBlobGas"] = json!(format!("{excess_blob_gas:#x}"));
+        }
+        if let Some(root) = &extensions.parent_beacon_block_root {
+            block["parentBeaconBlockRoot"] = json!(hex(root));
+        }
+        block
+    }
+}
+
+/// An Engine API handler following a chain from its genesis.
+pub struct EngineApi {
+    chain_id: U64,
+    state: State,
+    blocks: HashMap<Hash32, BlockInfo>,
+    head: Hash32,
+    /// The hashes of the most recent 256 blocks of the chain, for
+    /// `BLOCKHASH`.
+    block_hashes: Vec<Hash32>,
+    forkchoice: ForkchoiceState,
+    /// The fork whose rules payloads are executed with.
+    fork: HardFork,
+}
+
+impl EngineApi {
+    ///
+    ///     Start following `chain` from its last block.
+    ///
+    ///     Parameters
+    ///     ----------
+    ///     chain :
+    ///         The chain, usually just its genesis block.
+    ///     extensions :
+    ///         The fields of the last block's header added after the merge,
+    ///         for a genesis of a later fork.
+    ///
+    pub fn new(chain: BlockChain, extensions: HeaderExtensions) -> Result<Self, EthereumException> {
+        let last = chain.blocks.last().ok_or(EthereumException::InvalidBlock)?;
+        let head = extensions.hash(&last.header);
+        let mut block_hashes: Vec<Hash32> = chain.blocks[chain.blocks.len().saturating_sub(256)..]
+            .iter()
+            .map(|block| rlp::rlp_hash(&block.header))
+            .collect();
+        *block_hashes.last_mut().unwrap() = head;
+        let info = BlockInfo {
+            header: last.header.clone(),
+            extensions,
+            transaction_hashes: last.transactions.iter().map(rlp::rlp_hash).collect(),
+            withdrawals: None,
+            executed: true,
+        };
+        Ok(Self {
+            chain_id: chain.chain_id,
+            state: chain.state,
+            blocks: HashMap::from([(head, info)]),
+            head,
+            block_hashes,
+            forkchoice: ForkchoiceState {
+                head_block_hash: head,
+                ..Default::default()
+            },
+            fork: HardFork::Frontier,
+        })
+    }
+
+    /// The state after the tip of the chain.
+    pub fn state(&self) -> &State {
+        &self.state
+    }
+
+    /// The hash of the tip of the chain.
+    pub fn head(&self) -> Hash32 {
+        self.head
+    }
+
+    /// The last fork choice of the consensus layer.
+    pub fn forkchoice(&self) -> &ForkchoiceState {
+        &self.forkchoice
+    }
+
+    pub fn chain_id(&self) -> U64 {
+        self.chain_id
+    }
+
+    /// The environment of the tip of the chain, for calls against its state.
+    pub fn block_environment(&self) -> BlockEnvironment {
+        let header = &self.blocks[&self.head].header;
+        BlockEnvironment {
+            block_hashes: self.block_hashes[..self.block_hashes.len() - 1].to_vec(),
+            coinbase: header.coinbase,
+            number: header.number.clone(),
+            gas_limit: header.gas_limit.clone(),
+            time: header.timestamp.clone(),
+            difficulty: match header.difficulty == Uint::default() {
+                true => BigUint::from_bytes_be(&header.mix_digest),
+                false => header.difficulty.clone(),
+            },
+        }
+    }
+
+    /// The hash of the block numbered `number` in the chain.
+    fn canonical_hash(&self, number: &Uint) -> Option<Hash32> {
+        let mut current = self.head;
+        while let Some(block) = self.blocks.get(&current) {
+            if block.header.number == *number {
+                return Some(current);
+            }
+            current = block.header.parent_hash;
+        }
+        None
+    }
+
+    /// Find the block `tag` refers to: a number, or `latest`, `pending`,
+    /// `earliest`, `safe` or `finalized`.
+    fn block_by_tag(&self, tag: &Value) -> Result<Option<Hash32>, RpcError> {
+        let forkchoice_block = |hash: Hash32| Some(hash).filter(|hash| *hash != Hash32::default());
+        match tag.as_str() {
+            Some("latest" | "pending") => Ok(Some(self.head)),
+            Some("earliest") => Ok(self.canonical_hash(&Uint::default())),
+            Some("safe") => Ok(forkchoice_block(self.forkchoice.safe_block_hash)),
+            Some("finalized") => Ok(forkchoice_block(self.forkchoice.finalized_block_hash)),
+            _ => Ok(self.canonical_hash(&json_uint(tag).map_err(decode_error)?)),
+        }
+    }
+
+    /// Whether `hash` is the tip of the chain or one of its ancestors.
+    fn is_canonical(&self, hash: &Hash32) -> bool {
+        let mut current = self.head;
+        while let Some(block) = self.blocks.get(&current) {
+            if current == *hash {
+                return true;
+            }
+            current = block.header.parent_hash;
+        }
+        false
+    }
+
+    ///
+    ///     Execute the payload on top of the tip, and make it the new tip if
+    ///     it is valid.
+    ///
+    fn execute(&mut self, payload: &ExecutionPayload) -> Result<(), String> {
+        let parent = &self.blocks[&self.head].header;
+        let check = |valid: bool, error: &str| ensure(valid, error.to_owned());
+        check(payload.block_number == &parent.number + 1u8, "invalid block number")?;
+        check(payload.timestamp > parent.timestamp, "invalid timestamp")?;
+        check(
+            check_gas_limit(&payload.gas_limit, &parent.gas_limit).unwrap_or(false),
+            "invalid gas limit",
+        )?;
+        check(payload.extra_data.len() <= 32, "extra data too long")?;
+
+        let transactions = payload
+            .transactions
+            .iter()
+            .map(|tx| decode_transaction(tx))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| format!("invalid transaction: {e:?}"))?;
+        let block_env = BlockEnvironment {
+            block_hashes: self.block_hashes.clone(),
+            coinbase: payload.fee_recipient,
+            number: payload.block_number.clone(),
+            gas_limit: payload.gas_limit.clone(),
+            time: payload.timestamp.clone(),
+            difficulty: BigUint::from_bytes_be(&payload.prev_randao),
+        };
+
+        // The state is only replaced once the whole payload checks out.
+        let mut state = self.state.clone();
+        let signatures = signature_schedule(self.fork);
+        apply_payload(&mut state, &block_env, signatures, self.chain_id, &transactions, payload)?;
+        self.state = state;
+        Ok(())
+    }
+
+    ///
+    ///     Handle `engine_newPayloadVx`.
+    ///
+    ///     Parameters
+    ///     ----------
+    ///     version :
+    ///         The `x` of the method.
+    ///     payload :
+    ///         The payload.
+    ///     parent_beacon_block_root :
+    ///         The root passed with the payload from `V3`.
+    ///
+    ///     Returns
+    ///     -------
+    ///     status : `PayloadStatus`
+    ///         `VALID` or `INVALID` for a payload extending the tip,
+    ///         `ACCEPTED` for one extending another known block, and
+    ///         `SYNCING` for one whose parent is unknown.
+    ///
+    pub fn new_payload(
+        &mut self,
+        version: u8,
+        payload: &ExecutionPayload,
+        parent_beacon_block_root: Option<&Hash32>,
+    ) -> Result<PayloadStatus, RpcError> {
+        let expected = match version {
+            1 => (false, false),
+            2 => (payload.withdrawals.is_some(), false),
+            3 => (true, true),
+            _ => return Err(fault(METHOD_NOT_FOUND, format!("engine_newPayloadV{version} is not supported"))),
+        };
+        let has_blob_fields = payload.blob_gas_used.is_some() && payload.excess_blob_gas.is_some();
+        if (payload.withdrawals.is_some(), has_blob_fields) != expected
+            || parent_beacon_block_root.is_some() != (version == 3)
+        {
+            return Err(invalid_params(format!(
+                "the payload does not match engine_newPayloadV{version}"
+            )));
+        }
+
+        let extensions = payload.extensions(parent_beacon_block_root);
+        let block_hash = extensions.hash(&payload.header());
+        if block_hash != payload.block_hash {
+            let status = if version == 1 {
+                PayloadStatusKind::InvalidBlockHash
+            } else {
+                PayloadStatusKind::Invalid
+            };
+            return Ok(PayloadStatus {
+                status,
+                latest_valid_hash: None,
+                validation_error: Some(format!("the block hash is {}", hex(&block_hash))),
+            });
+        }
+
+        if self.blocks.get(&block_hash).is_some_and(|block| block.executed) {
+            return Ok(PayloadStatus::new(PayloadStatusKind::Valid, Some(block_hash)));
+        }
+        if payload.parent_hash != self.head {
+            if !self.blocks.contains_key(&payload.parent_hash) {
+                return Ok(PayloadStatus::new(PayloadStatusKind::Syncing, None));
+            }
+            let info = BlockInfo::from_payload(payload, extensions, false);
+            self.blocks.insert(block_hash, info);
+            return Ok(PayloadStatus::new(PayloadStatusKind::Accepted, None));
+        }
+
+        if let Err(error) = self.execute(payload) {
+            return Ok(PayloadStatus::invalid(self.head, error));
+        }
+        self.blocks.insert(block_hash, BlockInfo::from_payload(payload, extensions, true));
+        self.head = block_hash;
+        self.block_hashes.push(block_hash);
+        if self.block_hashes.len() > 256 {
+            self.block_hashes.remove(0);
+        }
+        Ok(PayloadStatus::new(PayloadStatusKind::Valid, Some(block_hash)))
+    }
+
+    ///
+    ///     Handle `engine_forkchoiceUpdatedVx`, ignoring payload attributes.
+    ///
+    ///     Returns
+    ///     -------
+    ///     status : `PayloadStatus`
+    ///         `VALID` if the head is the tip or one of its ancestors, and
+    ///         `SYNCING` otherwise.
+    ///
+    pub fn forkchoice_updated(&mut self, forkchoice: ForkchoiceState) -> Result<PayloadStatus, RpcError> {
+        let head = forkchoice.head_block_hash;
+        if !self.is_canonical(&head) {
+            return Ok(PayloadStatus::new(PayloadStatusKind::Syncing, None));
+        }
+        // A zero hash means there is no such block yet.
+        for hash in [&forkchoice.safe_block_hash, &forkchoice.finalized_block_hash] {
+            if *hash != Hash32::default() && !self.is_canonical(hash) {
+                return Err(fault(INVALID_FORKCHOICE_STATE, "Invalid forkchoice state"));
+            }
+        }
+        self.forkchoice = forkchoice;
+        Ok(PayloadStatus::new(PayloadStatusKind::Valid, Some(head)))
+    }
+
+    pub(crate) fn dispatch(&mut self, method: &str, params: &Value) -> Result<Value, RpcError> {
+        let version = |prefix: &str| method.strip_prefix(prefix).and_then(|v| v.parse::<u8>().ok());
+        if let Some(version) = version("engine_newPayloadV") {
+            let payload = ExecutionPayload::from_json(&params[0]).map_err(decode_error)?;
+            let parent_beacon_block_root = match &params[2] {
+                Value::Null => None,
+                root => Some(json_str(root).and_then(hex_to_hash).map_err(decode_error)?),
+            };
+            let status = self.new_payload(version, &payload, parent_beacon_block_root.as_ref())?;
+            return Ok(status.to_json());
+        }
+        if let Some(1..=3) = version("engine_forkchoiceUpdatedV") {
+            let forkchoice = ForkchoiceState::from_json(&params[0]).map_err(decode_error)?;
+            let status = self.forkchoice_updated(forkchoice)?;
+            return Ok(json!({ "payloadStatus": status.to_json(), "payloadId": null }));
+        }
+        match method {
+            "engine_exchangeCapabilities" => Ok(json!([
+                "engine_newPayloadV1",
+                "engine_newPayloadV2",
+                "engine_newPayloadV3",
+                "engine_forkchoiceUpdatedV1",
+                "engine_forkchoiceUpdatedV2",
+                "engine_forkchoiceUpdatedV3",
+            ])),
+            // Consensus clients ask these of the engine endpoint too.
+            "eth_chainId" => Ok(json!(format!("{:#x}", self.chain_id))),
+            "eth_blockNumber" => Ok(json!(format!("{:#x}", self.blocks[&self.head].header.number))),
+            "eth_getBlockByNumber" => {
+                let hash = self.block_by_tag(&params[0])?;
+                Ok(hash.map_or(Value::Null, |hash| self.blocks[&hash].to_json(&hash)))
+            }
+            "eth_getBlockByHash" => {
+                let hash = json_str(&params[0]).and_then(hex_to_hash).map_err(decode_error)?;
+                let block = self.blocks.get(&hash).filter(|block| block.executed);
+                Ok(block.map_or(Value::Null, |block| block.to_json(&hash)))
+            }
+            _ => Err(fault(METHOD_NOT_FOUND, format!("the method {method} does not exist/is not available"))),
+        }
+    }
+
+    ///
+    ///     Answer a request, or a batch of them.
+    ///
+    pub fn handle(&mut self, request: &Value) -> Value {
+        handle_requests(request, |method, params| self.dispatch(method, params))
+    }
+
+    ///
+    ///     Answer HTTP requests on `listener`, one connection at a time.
+    ///
+    pub fn serve(&mut self, listener: TcpListener) -> io::Result<()> {
+        serve(listener, |request| self.handle(request))
+    }
+}
+
+///
+///     Apply the transactions and withdrawals of a payload, and check the
+///     results against it.
+///
+fn apply_payload(
+    state: &mut State,
+    block_env: &BlockEnvironment,
+    signatures: &SignatureSchedule,
+    chain_id: U64,
+    transactions: &[Transaction],
+    payload: &ExecutionPayload,
+) -> Result<(), String> {
+    let mut gas_available = block_env.gas_limit.clone();
+    let mut receipts = vec![];
+    let mut block_logs = vec![];
+
+    let senders = transactions
+        .iter()
+        .map(|tx| transaction_signature::recover_sender(signatures, chain_id, tx))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("invalid transaction: {e:?}"))?;
+    for (i, (tx, sender)) in transactions.iter().zip(senders).enumerate() {
+        ensure(tx.gas <= gas_available, format!("transaction {i} exceeds the gas limit"))?;
+        let tx_env = TxEnvironment {
+            origin: sender,
+            gas_price: tx.gas_price.clone(),
+        };
+        let (gas_used, logs) = process_transaction(block_env, &tx_env, state, tx)
+            .map_err(|e| format!("transaction {i} is invalid: {e:?}"))?;
+        gas_available -= gas_used;
+
+        receipts.push(make_receipt(state_root(state), &block_env.gas_limit - &gas_available, &logs));
+        block_logs.extend(logs);
+    }
+
+    for withdrawal in payload.withdrawals.iter().flatten() {
+        create_ether(state, withdrawal.address, Uint::from(withdrawal.amount) * GWEI);
+    }
+
+    let gas_used = &block_env.gas_limit - gas_available;
+    ensure(gas_used == payload.gas_used, format!("the gas used is {gas_used}"))?;
+    let state_root = state_root(state);
+    ensure(state_root == payload.state_root, format!("the state root is {}", hex(&state_root)))?;
+    let receipts_root = compute_receipts_root(&receipts);
+    ensure(
+        receipts_root == payload.receipts_root,
+        format!("the receipts root is {}", hex(&receipts_root)),
+    )?;
+    ensure(logs_bloom(&block_logs) == payload.logs_bloom, "invalid logs bloom".to_owned())
+}
@@ -0,0 +1,400 @@
+//!
+//! # evm
+//!
+//! Command line tool for running and inspecting EVM bytecode, modelled on
+//! geth's `evm`.
+//!
+//! ```text
+//! evm run --code 0x6001600201 --json
+//! evm run --code 0x6001600055 --dump
+//! evm run --code 0x6001600055 --profile csv
+//! evm run --code 0x6001600055 --debug --break SSTORE
+//! evm disasm --code 0x6001600201
+//! evm t8n-diff --input.alloc alloc.json --input.env env.json \
+//!     --input.txs txs.json --external /usr/local/bin/evm
+//! evm fixtures tests/GeneralStateTests --results results.json --only-failed
+//! evm fixtures tests/BlockchainTests --threads 8 --junit report.xml
+//! evm fixtures tests/GeneralStateTests --all --coverage coverage.json
+//! ```
+//!
+
+use std::{
+    io,
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
+
+use clap::{Args, Parser, Subcommand};
+use execution_specs_rs::{
+    ethereum::{
+        base_types::{Bytes, Uint, U256},
+        exceptions::EthereumException,
+        trace::Tracer,
+        utils::hexadecimal::{hex, hex_to_bytes, hex_to_bytes20},
+    },
+    ethereum_spec_tools::{
+        evm_tools::{
+            alloc::{load_alloc, parse_uint},
+            debugger::{Breakpoint, Debugger},
+            disasm::disassemble_to_string,
+            dump::dump_state,
+            eip3155::Eip3155Tracer,
+            fixtures::{run_fixtures_parallel, run_fixtures_with_coverage, RunMode, RunSummary},
+            gas_profile::GasProfiler,
+            precompiles::run_precompile_vectors,
+            results::ResultsDb,
+            run::{run, RunConfig, DEFAULT_RECEIVER, DEFAULT_SENDER},
+            t8n::{differential, ExternalT8n, T8nInput},
+        },
+        forks::HardFork,
+        logging::LogArgs,
+    },
+};
+
+#[derive(Parser, Debug)]
+#[command(about = "Run and inspect EVM bytecode")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Run bytecode as a message call, or as init code with `--create`.
+    Run(RunArgs),
+    /// Print the instructions of some bytecode.
+    Disasm(CodeArgs),
+    /// Run a transition here and with another client's `t8n`, and report
+    /// where the results differ.
+    #[command(name = "t8n-diff")]
+    T8nDiff(T8nDiffArgs),
+    /// Run state and blockchain test fixtures.
+    Fixtures(FixturesArgs),
+    /// Run precompiles directly on go-ethereum's precompile test vectors.
+    Precompiles(PrecompilesArgs),
+}
+
+#[derive(Args, Debug)]
+struct CodeArgs {
+    /// The bytecode, in hex.
+    #[arg(long, conflicts_with = "codefile")]
+    code: Option<String>,
+    /// A file holding the bytecode in hex, `-` for stdin.
+    #[arg(long)]
+    codefile: Option<PathBuf>,
+    /// The fork whose instruction set to use.
+    #[arg(long, default_value = "frontier", value_parser = parse_fork)]
+    fork: HardFork,
+}
+
+#[derive(Args, Debug)]
+struct RunArgs {
+    #[command(flatten)]
+    code: CodeArgs,
+    /// The gas available to the execution.
+    #[arg(long, default_value = "10000000000", value_parser = parse_uint_arg)]
+    gas: Uint,
+    /// The price of gas, as seen by `GASPRICE`.
+    #[arg(long, default_value = "0", value_parser = parse_uint_arg)]
+    price: U256,
+    /// The call data, in hex.
+    #[arg(long, default_value = "0x", value_parser = parse_bytes_arg)]
+    input: Bytes,
+    /// The value sent along. The sender is credited with it first.
+    #[arg(long, default_value = "0", value_parser = parse_uint_arg)]
+    value: U256,
+    /// A JSON allocation to use as the pre-state.
+    #[arg(long)]
+    prestate: Option<PathBuf>,
+    /// The caller.
+    #[arg(long, value_parser = parse_address_arg)]
+    sender: Option<[u8; 20]>,
+    /// The account the code runs in.
+    #[arg(long, value_parser = parse_address_arg)]
+    receiver: Option<[u8; 20]>,
+    /// Run the code as init code, creating a contract.
+    #[arg(long)]
+    create: bool,
+    /// Write an EIP-3155 trace to stderr.
+    #[arg(long)]
+    json: bool,
+    /// Print the gas used per opcode and per contract, as `json` or `csv`.
+    #[arg(long, conflicts_with = "json", value_parser = ["json", "csv"])]
+    profile: Option<String>,
+    /// Step through the execution interactively, reading commands from
+    /// stdin. Type `help` at the prompt for the commands.
+    #[arg(long, conflicts_with_all = ["json", "profile"])]
+    debug: bool,
+    /// Run to a breakpoint, at a pc or an opcode, rather than stopping at the
+    /// first opcode. Can be repeated.
+    #[arg(long = "break", requires = "debug", value_parser = Breakpoint::parse)]
+    breakpoints: Vec<Breakpoint>,
+    /// Print a geth style dump of the state after the execution.
+    #[arg(long)]
+    dump: bool,
+}
+
+#[derive(Args, Debug)]
+struct T8nDiffArgs {
+    /// The pre-state, as a JSON allocation.
+    #[arg(long = "input.alloc")]
+    alloc: PathBuf,
+    /// The block environment, as JSON.
+    #[arg(long = "input.env")]
+    env: PathBuf,
+    /// The transactions, as a JSON list.
+    #[arg(long = "input.txs")]
+    txs: PathBuf,
+    /// The fork to run in.
+    #[arg(long = "state.fork", default_value = "frontier", value_parser = parse_fork)]
+    fork: HardFork,
+    /// The block reward, or -1 for none.
+    #[arg(long = "state.reward", default_value = "-1", allow_hyphen_values = true)]
+    reward: String,
+    /// The other client's transition tool.
+    #[arg(long)]
+    external: PathBuf,
+    /// The arguments selecting its `t8n` command.
+    #[arg(long = "external-arg", default_value = "t8n", allow_hyphen_values = true)]
+    external_args: Vec<String>,
+    /// Where to write the other tool's inputs and outputs.
+    #[arg(long)]
+    workdir: Option<PathBuf>,
+}
+
+#[derive(Args, Debug)]
+struct FixturesArgs {
+    /// Fixture files, or directories to search for them.
+    #[arg(required = true)]
+    paths: Vec<PathBuf>,
+    /// A results database to resume from and update after every file.
+    /// Tests that passed and haven't changed since are skipped.
+    #[arg(long)]
+    results: Option<PathBuf>,
+    /// Only run the tests that failed last time.
+    #[arg(long, requires = "results", conflicts_with = "all")]
+    only_failed: bool,
+    /// Run the tests that passed last time too.
+    #[arg(long)]
+    all: bool,
+    /// How many files to run at once, by default one per core.
+    #[arg(long)]
+    threads: Option<usize>,
+    /// Write a JUnit XML report of the run there.
+    #[arg(long)]
+    junit: Option<PathBuf>,
+    /// Write the opcodes, precompiles and exceptions the tests that ran went
+    /// through, and those they didn't, per fork, as JSON there.
+    #[arg(long)]
+    coverage: Option<PathBuf>,
+    #[command(flatten)]
+    log: LogArgs,
+}
+
+#[derive(Args, Debug)]
+struct PrecompilesArgs {
+    /// Vector files, named after their precompile, or directories to search
+    /// for them.
+    #[arg(required = true)]
+    paths: Vec<PathBuf>,
+    /// Write a JUnit XML report of the run there.
+    #[arg(long)]
+    junit: Option<PathBuf>,
+}
+
+fn parse_fork(fork: &str) -> Result<HardFork, String> {
+    HardFork::from_name(fork).map_err(|e| match e {
+        EthereumException::UnsupportedFork(name) => {
+            format!("unsupported fork `{name}`, the spec doesn't implement it or it wasn't compiled in")
+        }
+        _ => format!("unknown fork `{fork}`"),
+    })
+}
+
+fn parse_uint_arg(number: &str) -> Result<Uint, String> {
+    parse_uint(number).map_err(|_| format!("invalid number `{number}`"))
+}
+
+fn parse_bytes_arg(bytes: &str) -> Result<Bytes, String> {
+    hex_to_bytes(bytes.trim()).map_err(|_| format!("invalid hex `{bytes}`"))
+}
+
+fn parse_address_arg(address: &str) -> Result<[u8; 20], String> {
+    hex_to_bytes20(address).map_err(|_| format!("invalid address `{address}`"))
+}
+
+fn read_code(args: &CodeArgs) -> Result<Bytes, String> {
+    let code = match (&args.code, &args.codefile) {
+        (Some(code), _) => code.clone(),
+        (None, Some(path)) if path.as_os_str() == "-" => {
+            io::read_to_string(io::stdin()).map_err(|e| e.to_string())?
+        }
+        (None, Some(path)) => std::fs::read_to_string(path)
+            .map_err(|e| format!("cannot read {}: {e}", path.display()))?,
+        (None, None) => String::new(),
+    };
+    parse_bytes_arg(&code)
+}
+
+fn run_command(args: RunArgs) -> Result<ExitCode, String> {
+    let code = read_code(&args.code)?;
+    let state = match &args.prestate {
+        Some(path) => {
+            let alloc = std::fs::read_to_string(path)
+                .map_err(|e| format!("cannot read {}: {e}", path.display()))?;
+            load_alloc(&alloc).map_err(|e| format!("bad prestate: {e:?}"))?
+        }
+        None => Default::default(),
+    };
+    let profiler = GasProfiler::new();
+    let profile = profiler.profile();
+    let tracer: Option<Box<dyn Tracer<_>>> = if args.json {
+        Some(Box::new(Eip3155Tracer::new(Box::new(io::stderr()))))
+    } else if args.debug {
+        let mut debugger = Debugger::new(Box::new(io::stdin().lock()), Box::new(io::stderr()));
+        for breakpoint in &args.breakpoints {
+            debugger.add_breakpoint(*breakpoint);
+        }
+        if !args.breakpoints.is_empty() {
+            debugger = debugger.run_to_breakpoint();
+        }
+        Some(Box::new(debugger))
+    } else if args.profile.is_some() {
+        Some(Box::new(profiler))
+    } else {
+        None
+    };
+
+    let config = RunConfig {
+        fork: args.code.fork,
+        code,
+        input: args.input,
+        gas: args.gas,
+        gas_price: args.price,
+        value: args.value,
+        sender: args.sender.unwrap_or(DEFAULT_SENDER),
+        receiver: args.receiver.unwrap_or(DEFAULT_RECEIVER),
+        create: args.create,
+        state,
+        ..Default::default()
+    };
+    let output = run(config, tracer);
+
+    println!("{}", hex(&output.output));
+    println!("gas used: {}", output.gas_used);
+    if args.dump {
+        println!("{:#}", dump_state(&output.state));
+    }
+    match args.profile.as_deref() {
+        Some("json") => println!("{:#}", profile.borrow().to_json()),
+        Some(_) => println!("{}\n{}", profile.borrow().opcodes_csv(), profile.borrow().callees_csv()),
+        None => {}
+    }
+    match output.error {
+        Some(error) => {
+            println!("error: {error}");
+            Ok(ExitCode::FAILURE)
+        }
+        None => Ok(ExitCode::SUCCESS),
+    }
+}
+
+fn t8n_diff_command(args: T8nDiffArgs) -> Result<ExitCode, String> {
+    let read = |path: &PathBuf| {
+        std::fs::read_to_string(path).map_err(|e| format!("cannot read {}: {e}", path.display()))
+    };
+    let mut input = T8nInput::from_json(&read(&args.alloc)?, &read(&args.env)?, &read(&args.txs)?)
+        .map_err(|e| format!("bad input: {e:?}"))?;
+    input.fork = args.fork;
+    input.reward = match args.reward.as_str() {
+        "-1" => None,
+        reward => Some(parse_uint_arg(reward)?),
+    };
+
+    let external = ExternalT8n {
+        program: args.external,
+        args: args.external_args,
+    };
+    let work_dir = args
+        .workdir
+        .unwrap_or_else(|| std::env::temp_dir().join(format!("t8n-diff-{}", std::process::id())));
+    let diff = differential(&input, &external, &work_dir).map_err(|e| e.to_string())?;
+    print!("{diff}");
+    Ok(if diff.is_empty() { ExitCode::SUCCESS } else { ExitCode::FAILURE })
+}
+
+fn fixtures_command(args: FixturesArgs) -> Result<ExitCode, String> {
+    args.log.init();
+    let mut db = match &args.results {
+        Some(path) => ResultsDb::open(path).map_err(|e| format!("{e:?}"))?,
+        None => ResultsDb::new(),
+    };
+    let mode = match (args.all, args.only_failed) {
+        (true, _) => RunMode::All,
+        (_, true) => RunMode::OnlyFailed,
+        _ => RunMode::Changed,
+    };
+    let threads = args
+        .threads
+        .unwrap_or_else(|| std::thread::available_parallelism().map_or(1, |threads| threads.get()));
+    let summary = match args.coverage {
+        Some(_) => run_fixtures_with_coverage(&args.paths, &mut db, mode, threads),
+        None => run_fixtures_parallel(&args.paths, &mut db, mode, threads),
+    }
+    .map_err(|e| format!("{e:?}"))?;
+    if let Some(path) = &args.coverage {
+        let coverage = summary.coverage_report();
+        std::fs::write(path, format!("{coverage:#}\n")).map_err(|e| format!("cannot write {}: {e}", path.display()))?;
+        for (fork, report) in coverage.as_object().into_iter().flatten() {
+            let count = |section: &str| format!("{}/{}", report[section]["covered"], report[section]["total"]);
+            println!(
+                "{fork}: {} opcodes, {} precompiles, {} exceptions covered",
+                count("opcodes"),
+                count("precompiles"),
+                count("exceptions")
+            );
+        }
+    }
+    report(&summary, args.junit.as_deref())
+}
+
+fn precompiles_command(args: PrecompilesArgs) -> Result<ExitCode, String> {
+    let summary = run_precompile_vectors(&args.paths).map_err(|e| format!("{e:?}"))?;
+    report(&summary, args.junit.as_deref())
+}
+
+/// Print the failures and totals of a run, and write its JUnit report.
+fn report(summary: &RunSummary, junit: Option<&Path>) -> Result<ExitCode, String> {
+    if let Some(path) = junit {
+        std::fs::write(path, summary.junit_xml()).map_err(|e| format!("cannot write {}: {e}", path.display()))?;
+    }
+    for (id, message) in summary.failures() {
+        println!("FAIL {id}: {message}");
+    }
+    println!(
+        "{} passed, {} failed, {} skipped, {} unsupported",
+        summary.passed(),
+        summary.failed(),
+        summary.skipped(),
+        summary.unsupported()
+    );
+    Ok(if summary.failed() == 0 { ExitCode::SUCCESS } else { ExitCode::FAILURE })
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    let result = match cli.command {
+        Command::Run(args) => run_command(args),
+        Command::Disasm(args) => read_code(&args).map(|code| {
+            print!("{}", disassemble_to_string(&args.fork, &code));
+            ExitCode::SUCCESS
+        }),
+        Command::T8nDiff(args) => t8n_diff_command(args),
+        Command::Fixtures(args) => fixtures_command(args),
+        Command::Precompiles(args) => precompiles_command(args),
+    };
+    result.unwrap_or_else(|error| {
+        eprintln!("error: {error}");
+        ExitCode::FAILURE
+    })
+}
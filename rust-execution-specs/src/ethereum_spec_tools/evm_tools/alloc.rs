@@ -0,0 +1,163 @@
+//!
+//! # Allocations
+//!
+//! Loads the pre-state of an execution from a geth style allocation, as used
+//! by the `alloc` of a genesis file or geth's `evm run --prestate`:
+//!
+//! ```json
+//! {
+//!     "0x1000000000000000000000000000000000000000": {
+//!         "balance": "0x0de0b6b3a7640000",
+//!         "nonce": "0x1",
+//!         "code": "0x6000",
+//!         "storage": { "0x01": "0x02" }
+//!     }
+//! }
+//! ```
+//!
+//! Every field of an account is optional. Numbers may be hex (`0x` prefixed)
+//! or decimal strings, or JSON numbers. [dump_alloc] writes a state back in
+//! this format, with every field and numbers in hex.
+//!
+
+use serde_json::{json, Map, Value};
+
+use crate::ethereum::{
+    base_types::{u256_to_be_bytes32, Uint, U256},
+    exceptions::EthereumException,
+    frontier::{
+        fork_types::Account,
+        state::{get_accounts, get_storage_slots, set_account, set_storage, State},
+    },
+    utils::hexadecimal::{has_hex_prefix, hex, hex_to_bytes, hex_to_bytes20, hex_to_uint},
+};
+
+///
+///     Parse a number given in hex (`0x` prefixed) or decimal.
+///
+///     Parameters
+///     ----------
+///     number :
+///         The string to parse.
+///
+///     Returns
+///     -------
+///     number : `Uint`
+///         The parsed number.
+///
+pub fn parse_uint(number: &str) -> Result<Uint, EthereumException> {
+    if has_hex_prefix(number) {
+        hex_to_uint(number)
+    } else {
+        number
+            .parse()
+            .map_err(|_| EthereumException::ValueError)
+    }
+}
+
+pub(crate) fn json_uint(value: &Value) -> Result<Uint, EthereumException> {
+    match value {
+        Value::String(number) => parse_uint(number),
+        Value::Number(number) => number
+            .as_u64()
+            .map(Uint::from)
+            .ok_or(EthereumException::ValueError),
+        _ => Err(EthereumException::JsonDecodeError(format!(
+            "expected a number, got {value}"
+        ))),
+    }
+}
+
+pub(crate) fn json_str(value: &Value) -> Result<&str, EthereumException> {
+    value.as_str().ok_or_else(|| {
+        EthereumException::JsonDecodeError(format!("expected a string, got {value}"))
+    })
+}
+
+///
+///     Build the state described by an allocation.
+///
+///     Parameters
+///     ----------
+///     alloc :
+///         The allocation, as JSON.
+///
+///     Returns
+///     -------
+///     state : `State`
+///         A state holding exactly the accounts of `alloc`.
+///
+pub fn load_alloc(alloc: &str) -> Result<State, EthereumException> {
+    let value: serde_json::Value = serde_json::from_str(alloc)
+        .map_err(|e| EthereumException::JsonDecodeError(e.to_string()))?;
+    let accounts = value.as_object().ok_or_else(|| {
+        EthereumException::JsonDecodeError("an allocation must be an object".to_owned())
+    })?;
+
+    let mut state = State::default();
+    for (address, account) in accounts {
+        let address = hex_to_bytes20(address)?;
+
+        let balance = match account.get("balance") {
+            Some(balance) => json_uint(balance)?,
+            None => U256::from(0u8),
+        };
+        let nonce = match account.get("nonce") {
+            Some(nonce) => json_uint(nonce)?,
+            None => Uint::from(0u8),
+        };
+        let code = match account.get("code") {
+            Some(code) => hex_to_bytes(json_str(code)?)?,
+            None => Default::default(),
+        };
+        set_account(
+            &mut state,
+            address,
+            Some(Account {
+                nonce,
+                balance,
+                code: code.into(),
+            }),
+        );
+
+        if let Some(storage) = account.get("storage").and_then(Value::as_object) {
+            for (key, value) in storage {
+                let key = u256_to_be_bytes32(&parse_uint(key)?);
+                set_storage(&mut state, address, &key, json_uint(value)?);
+            }
+        }
+    }
+    Ok(state)
+}
+
+///
+///     Write a state as an allocation, which [load_alloc] reads back.
+///
+///     Parameters
+///     ----------
+///     state :
+///         The state.
+///
+///     Returns
+///     -------
+///     alloc : `serde_json::Value`
+///         Every account of `state`, with its balance, nonce, code and
+///         storage. Numbers and storage keys are hex without leading zeros.
+///
+pub fn dump_alloc(state: &State) -> Value {
+    let mut accounts = Map::new();
+    for (address, account) in get_accounts(state) {
+        let storage: Map<_, _> = get_storage_slots(state, &address)
+            .into_iter()
+            .map(|(key, value)| (format!("{:#x}", Uint::from_bytes_be(&key)), json!(format!("{value:#x}"))))
+            .collect();
+        let dumped = json!({
+            "balance": format!("{:#x}", account.balance),
+            "code": hex(&account.code),
+            "nonce": format!("{:#x}", account.nonce),
+            "storage": storage,
+        });
+        accounts.insert(hex(&address), dumped);
+    }
+    Value::Object(accounts)
+}
@@ -0,0 +1,226 @@
+//!
+//! # Call Tracer
+//!
+//! A [Tracer] recording the tree of message calls of each transaction, with
+//! the fields and JSON of geth's `callTracer`, so that its output can be
+//! compared with `debug_traceTransaction`:
+//!
+//! ```json
+//! {
+//!     "type": "CALL",
+//!     "from": "0x2020202020202020202020202020202020202020",
+//!     "to": "0x1010101010101010101010101010101010101010",
+//!     "value": "0x0",
+//!     "gas": "0x186a0",
+//!     "gasUsed": "0x5208",
+//!     "input": "0x",
+//!     "output": "0x",
+//!     "calls": [...]
+//! }
+//! ```
+//!
+//! The tracer is moved into the execution, so the frames are read through
+//! the handle [CallTracer::calls] returns.
+//!
+//! geth gives the top level frame the gas limit of the transaction and the
+//! gas used by it after refunds. The tracer only sees the message, so the
+//! gas of that frame is what is left after the intrinsic cost, and its gas
+//! used is before refunds; set them from the transaction and its receipt to
+//! compare. Errors get geth's messages, without the details geth adds to
+//! some of them, such as the opcode that is invalid.
+//!
+
+use std::{cell::RefCell, rc::Rc};
+
+use serde_json::{json, Map, Value};
+
+use crate::ethereum::{
+    base_types::{Bytes, U256},
+    frontier::{
+        fork_types::Address,
+        state::get_account,
+        utils::address::to_address,
+        vm::{instructions::Ops, Evm, Message},
+    },
+    trace::{TraceEvent, Tracer},
+    utils::hexadecimal::hex,
+};
+
+/// How a frame was entered.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CallKind {
+    Call,
+    CallCode,
+    Create,
+    /// A `SELFDESTRUCT`, sending the balance of `from` to `to`. It runs no
+    /// code and uses no gas of its own.
+    SelfDestruct,
+}
+
+impl CallKind {
+    /// The name geth gives the kind of frame.
+    pub fn name(self) -> &'static str {
+        match self {
+            CallKind::Call => "CALL",
+            CallKind::CallCode => "CALLCODE",
+            CallKind::Create => "CREATE",
+            CallKind::SelfDestruct => "SELFDESTRUCT",
+        }
+    }
+}
+
+/// A message call and the calls it made.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CallFrame {
+    pub kind: CallKind,
+    pub from: Address,
+    /// The called account, the account whose code runs for a `CALLCODE`,
+    /// or the created contract. `None` for a create that failed.
+    pub to: Option<Address>,
+    pub value: U256,
+    pub gas: U256,
+    pub gas_used: U256,
+    /// The call data, or the init code of a create.
+    pub input: Bytes,
+    /// The return data, or the deployed code of a create. Empty if the call
+    /// failed.
+    pub output: Bytes,
+    pub error: Option<String>,
+    pub calls: Vec<CallFrame>,
+}
+
+impl CallFrame {
+    fn start(message: &Message) -> Self {
+        let (kind, to, input) = match message.target {
+            None => (CallKind::Create, message.current_target, &message.code),
+            Some(_) => match message.code_address {
+                Some(code_address) if code_address != message.current_target => {
+                    (CallKind::CallCode, code_address, &message.data)
+                }
+                _ => (CallKind::Call, message.current_target, &message.data),
+            },
+        };
+        Self {
+            kind,
+            from: message.caller,
+            to: Some(to),
+            value: message.value.clone(),
+            gas: message.gas.clone(),
+            gas_used: U256::default(),
+            input: input.clone(),
+            output: Bytes::default(),
+            error: None,
+            calls: vec![],
+        }
+    }
+
+    ///
+    ///     Serialize the frame and the calls it made as geth's `callTracer`
+    ///     does.
+    ///
+    pub fn to_json(&self) -> Value {
+        let mut frame = Map::new();
+        frame.insert("type".into(), json!(self.kind.name()));
+        frame.insert("from".into(), json!(hex(&self.from)));
+        if let Some(to) = &self.to {
+            frame.insert("to".into(), json!(hex(to)));
+        }
+        frame.insert("value".into(), json!(format!("{:#x}", self.value)));
+        frame.insert("gas".into(), json!(format!("{:#x}", self.gas)));
+        frame.insert("gasUsed".into(), json!(format!("{:#x}", self.gas_used)));
+        frame.insert("input".into(), json!(hex(&self.input)));
+        if !self.output.is_empty() {
+            frame.insert("output".into(), json!(hex(&self.output)));
+        }
+        if let Some(error) = &self.error {
+            frame.insert("error".into(), json!(error));
+        }
+        if !self.calls.is_empty() {
+            frame.insert("calls".into(), self.calls.iter().map(CallFrame::to_json).collect());
+        }
+        Value::Object(frame)
+    }
+}
+
+/// The message geth gives an error the interpreter reports.
+fn geth_error(error: &str) -> String {
+    match error {
+        "OutOfGas" => "out of gas",
+        "StackUnderflow" => "stack underflow",
+        "StackOverflow" => "stack limit reached 1024",
+        "InvalidOpcode" => "invalid opcode",
+        "InvalidJumpDest" => "invalid jump destination",
+        "StackDepthLimit" => "max call depth exceeded",
+        "AddressCollision" => "contract address collision",
+        error => error,
+    }
+    .to_owned()
+}
+
+/// A [Tracer] recording the [CallFrame]s of every transaction.
+#[derive(Default)]
+pub struct CallTracer {
+    calls: Rc<RefCell<Vec<CallFrame>>>,
+    /// The frames that are running, innermost last.
+    frames: Vec<CallFrame>,
+}
+
+impl CallTracer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The top level frame of each transaction traced, to read once the
+    /// tracer has been moved into an execution.
+    pub fn calls(&self) -> Rc<RefCell<Vec<CallFrame>>> {
+        self.calls.clone()
+    }
+}
+
+impl Tracer<Evm> for CallTracer {
+    fn trace(&mut self, evm: &Evm, event: &TraceEvent) {
+        match event {
+            TraceEvent::MessageStart { .. } => self.frames.push(CallFrame::start(&evm.message)),
+            TraceEvent::OpStart { op } if *op == Ops::SELFDESTRUCT as u8 => {
+                // Frontier's `SELFDESTRUCT` costs nothing, so it can only
+                // fail for want of a beneficiary.
+                let (Some(frame), Some(beneficiary)) = (self.frames.last_mut(), evm.stack.last()) else {
+                    return;
+                };
+                let address = evm.message.current_target;
+                frame.calls.push(CallFrame {
+                    kind: CallKind::SelfDestruct,
+                    from: address,
+                    to: Some(to_address(beneficiary)),
+                    value: get_account(&evm.env.state, &address).balance,
+                    gas: U256::default(),
+                    gas_used: U256::default(),
+                    input: Bytes::default(),
+                    output: Bytes::default(),
+                    error: None,
+                    calls: vec![],
+                });
+            }
+            TraceEvent::MessageEnd { error, .. } => {
+                let Some(mut frame) = self.frames.pop() else {
+                    return;
+                };
+                frame.gas_used = &frame.gas - &evm.gas_left;
+                match error {
+                    None => frame.output = evm.output.clone(),
+                    Some(error) => {
+                        frame.error = Some(geth_error(error));
+                        if frame.kind == CallKind::Create {
+                            frame.to = None;
+                        }
+                    }
+                }
+                match self.frames.last_mut() {
+                    Some(parent) => parent.calls.push(frame),
+                    None => self.calls.borrow_mut().push(frame),
+                }
+            }
+            _ => {}
+        }
+    }
+}
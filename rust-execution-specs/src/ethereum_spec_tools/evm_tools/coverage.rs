@@ -0,0 +1,189 @@
+//!
+//! # Coverage
+//!
+//! A [Tracer] counting the opcodes, precompiled contracts and exceptional
+//! halts an execution went through, and reports comparing the counts with
+//! everything a fork has, so that the parts of a newly ported fork that no
+//! fixture exercises stand out.
+//!
+//! Opcodes are counted when they start, precompiles when they are called
+//! and exceptions when the frame they end fails. `AddressCollision` and
+//! `StackDepthLimit` are decided before a frame starts, so they are not
+//! reported.
+//!
+//! ```text
+//! evm fixtures tests/GeneralStateTests --coverage coverage.json
+//! ```
+//!
+
+use std::{cell::RefCell, collections::BTreeMap, rc::Rc};
+
+use serde_json::{json, Map, Value};
+
+use super::disasm::opcode_table;
+use crate::{
+    ethereum::{
+        frontier::{
+            fork_types::Address,
+            vm::{exceptions::EvmError, precompiled_contracts::mapping::PRE_COMPILED_CONTRACTS, Evm},
+        },
+        trace::{TraceEvent, Tracer},
+        utils::hexadecimal::hex,
+    },
+    ethereum_spec_tools::forks::HardFork,
+};
+
+/// What executions went through, with how many times.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Coverage {
+    pub opcodes: BTreeMap<u8, u64>,
+    pub precompiles: BTreeMap<Address, u64>,
+    /// By the name of the `EvmError`.
+    pub exceptions: BTreeMap<String, u64>,
+}
+
+/// The exceptional halts a frame of `fork` can end with.
+fn frame_exceptions(fork: HardFork) -> Vec<EvmError> {
+    match fork {
+        HardFork::Frontier => vec![
+            EvmError::Halt,
+            EvmError::StackUnderflow,
+            EvmError::StackOverflow,
+            EvmError::OutOfGas,
+            EvmError::InvalidOpcode,
+            EvmError::InvalidJumpDest,
+        ],
+    }
+}
+
+/// The addresses of the precompiled contracts of `fork`.
+fn precompile_addresses(fork: HardFork) -> Vec<Address> {
+    match fork {
+        HardFork::Frontier => PRE_COMPILED_CONTRACTS.iter().map(|(address, _)| *address).collect(),
+    }
+}
+
+/// One section of a report: the counts of what was covered and what wasn't.
+fn section(covered: Vec<(String, u64)>, missing: Vec<String>) -> Value {
+    let total = covered.len() + missing.len();
+    json!({
+        "covered": covered.len(),
+        "total": total,
+        "counts": covered.into_iter().map(|(name, count)| (name, json!(count))).collect::<Map<String, Value>>(),
+        "missing": missing,
+    })
+}
+
+impl Coverage {
+    ///
+    ///     Add the counts of another coverage to these.
+    ///
+    ///     Parameters
+    ///     ----------
+    ///     other :
+    ///         The coverage of other executions.
+    ///
+    pub fn merge(&mut self, other: &Coverage) {
+        for (op, count) in &other.opcodes {
+            *self.opcodes.entry(*op).or_default() += count;
+        }
+        for (address, count) in &other.precompiles {
+            *self.precompiles.entry(*address).or_default() += count;
+        }
+        for (error, count) in &other.exceptions {
+            *self.exceptions.entry(error.clone()).or_default() += count;
+        }
+    }
+
+    ///
+    ///     Compare the coverage with what a fork has:
+    ///
+    ///     ```json
+    ///     {
+    ///         "opcodes": { "covered": 2, "total": 140, "counts": { "ADD": 3, "STOP": 1 }, "missing": ["MUL", ...] },
+    ///         "precompiles": { ... },
+    ///         "exceptions": { ... }
+    ///     }
+    ///     ```
+    ///
+    ///     Precompiles are named by their address. Counts of anything the
+    ///     fork doesn't have are left out.
+    ///
+    ///     Parameters
+    ///     ----------
+    ///     fork :
+    ///         The fork the executions ran in.
+    ///
+    ///     Returns
+    ///     -------
+    ///     report : `Value`
+    ///         The covered and missing opcodes, precompiles and exceptions.
+    ///
+    pub fn report(&self, fork: HardFork) -> Value {
+        let (mut covered, mut missing) = (vec![], vec![]);
+        for opcode in opcode_table(&fork) {
+            match self.opcodes.get(&opcode.value) {
+                Some(&count) => covered.push((opcode.mnemonic.to_owned(), count)),
+                None => missing.push(opcode.mnemonic.to_owned()),
+            }
+        }
+        let opcodes = section(covered, missing);
+
+        let (mut covered, mut missing) = (vec![], vec![]);
+        for address in precompile_addresses(fork) {
+            match self.precompiles.get(&address) {
+                Some(&count) => covered.push((hex(&address), count)),
+                None => missing.push(hex(&address)),
+            }
+        }
+        let precompiles = section(covered, missing);
+
+        let (mut covered, mut missing) = (vec![], vec![]);
+        for error in frame_exceptions(fork) {
+            let name = error.to_string();
+            match self.exceptions.get(&name) {
+                Some(&count) => covered.push((name, count)),
+                None => missing.push(name),
+            }
+        }
+        let exceptions = section(covered, missing);
+
+        json!({ "opcodes": opcodes, "precompiles": precompiles, "exceptions": exceptions })
+    }
+}
+
+/// A [Tracer] filling a [Coverage].
+#[derive(Default)]
+pub struct CoverageTracer {
+    coverage: Rc<RefCell<Coverage>>,
+}
+
+impl CoverageTracer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The coverage being filled, to read once the tracer has been moved
+    /// into an execution.
+    pub fn coverage(&self) -> Rc<RefCell<Coverage>> {
+        self.coverage.clone()
+    }
+}
+
+impl Tracer<Evm> for CoverageTracer {
+    fn trace(&mut self, _evm: &Evm, event: &TraceEvent) {
+        let mut coverage = self.coverage.borrow_mut();
+        match event {
+            TraceEvent::OpStart { op } => *coverage.opcodes.entry(*op).or_default() += 1,
+            TraceEvent::PrecompileStart { address } => {
+                if let Ok(address) = Address::try_from(&address[..]) {
+                    *coverage.precompiles.entry(address).or_default() += 1;
+                }
+            }
+            TraceEvent::MessageEnd { error: Some(error), .. } => {
+                *coverage.exceptions.entry(error.clone()).or_default() += 1;
+            }
+            _ => {}
+        }
+    }
+}
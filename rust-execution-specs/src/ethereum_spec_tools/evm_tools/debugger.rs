@@ -0,0 +1,407 @@
+//!
+//! # Debugger
+//!
+//! An interactive step debugger for `evm run --debug`.
+//!
+//! The [Debugger] is a [Tracer] overriding [Tracer::trace_mut]: when an
+//! opcode is about to run and the debugger should stop there, it reads
+//! commands until one of them resumes the execution. Commands can look at
+//! the frame and change its gas left.
+//!
+//! ```text
+//! step, s               run one opcode
+//! next, n               run one opcode, stepping over calls and creates
+//! continue, c           run until a breakpoint
+//! break, b [PC|OPCODE]  add a breakpoint, or list them
+//! delete, d N           remove breakpoint N
+//! where, w              show the opcode about to run
+//! stack                 show the stack, top first
+//! memory [OFFSET [LEN]] show the memory
+//! storage [SLOT]        show the storage of the account the code runs in
+//! gas [GAS]             show or set the gas left
+//! quit, q               run to the end without stopping
+//! ```
+//!
+//! Numbers are decimal or `0x` prefixed hex.
+//!
+
+use std::io::{BufRead, Write};
+
+use super::alloc::parse_uint;
+use crate::ethereum::{
+    base_types::{u256_to_be_bytes32, Uint, U256},
+    frontier::{
+        state::{get_storage, get_storage_slots},
+        vm::{instructions::Ops, Evm},
+    },
+    trace::{TraceEvent, Tracer},
+    utils::hexadecimal::hex,
+};
+
+/// Where the debugger stops even when running.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Breakpoint {
+    /// Before the opcode at this position runs, in any frame.
+    Pc(usize),
+    /// Before any opcode with this value runs.
+    Op(u8),
+}
+
+impl Breakpoint {
+    ///
+    ///     Parse a breakpoint, a pc or the mnemonic of an opcode.
+    ///
+    ///     Parameters
+    ///     ----------
+    ///     target :
+    ///         Where to stop.
+    ///
+    ///     Returns
+    ///     -------
+    ///     breakpoint : `Breakpoint`
+    ///         The breakpoint, or why `target` isn't one.
+    ///
+    pub fn parse(target: &str) -> Result<Self, String> {
+        if let Some(op) = Ops::from_name(&target.to_ascii_uppercase()) {
+            return Ok(Breakpoint::Op(op as u8));
+        }
+        let pc = parse_uint(target).map_err(|_| format!("invalid breakpoint `{target}`"))?;
+        usize::try_from(&pc)
+            .map(Breakpoint::Pc)
+            .map_err(|_| format!("`{target}` is too large"))
+    }
+
+    fn hit(&self, evm: &Evm, op: u8) -> bool {
+        match *self {
+            Breakpoint::Pc(pc) => evm.pc == pc,
+            Breakpoint::Op(value) => op == value,
+        }
+    }
+}
+
+/// A command read at the prompt.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Command {
+    Step,
+    Next,
+    Continue,
+    Break(Option<Breakpoint>),
+    Delete(usize),
+    Where,
+    Stack,
+    Memory { offset: usize, length: Option<usize> },
+    Storage(Option<U256>),
+    Gas(Option<Uint>),
+    Quit,
+    Help,
+}
+
+impl Command {
+    ///
+    ///     Parse a line typed at the prompt.
+    ///
+    ///     Parameters
+    ///     ----------
+    ///     line :
+    ///         The command and its arguments.
+    ///
+    ///     Returns
+    ///     -------
+    ///     command : `Command`
+    ///         The command, or why the line isn't one.
+    ///
+    pub fn parse(line: &str) -> Result<Self, String> {
+        let mut words = line.split_whitespace();
+        let name = words.next().unwrap_or("step");
+        let args: Vec<&str> = words.collect();
+        let number = |word: &str| parse_uint(word).map_err(|_| format!("invalid number `{word}`"));
+        let small = |word: &str| {
+            number(word).and_then(|number| usize::try_from(&number).map_err(|_| format!("`{word}` is too large")))
+        };
+
+        let command = match (name, &args[..]) {
+            ("step" | "s", []) => Command::Step,
+            ("next" | "n", []) => Command::Next,
+            ("continue" | "c", []) => Command::Continue,
+            ("break" | "b", []) => Command::Break(None),
+            ("break" | "b", [target]) => Command::Break(Some(Breakpoint::parse(target)?)),
+            ("delete" | "d", [index]) => Command::Delete(small(index)?),
+            ("where" | "w", []) => Command::Where,
+            ("stack", []) => Command::Stack,
+            ("memory" | "mem", []) => Command::Memory {
+                offset: 0,
+                length: None,
+            },
+            ("memory" | "mem", [offset]) => Command::Memory {
+                offset: small(offset)?,
+                length: None,
+            },
+            ("memory" | "mem", [offset, length]) => Command::Memory {
+                offset: small(offset)?,
+                length: Some(small(length)?),
+            },
+            ("storage", []) => Command::Storage(None),
+            ("storage", [slot]) => match number(slot)? {
+                slot if slot.bits() > 256 => return Err(format!("`{}` is not a storage slot", args[0])),
+                slot => Command::Storage(Some(slot)),
+            },
+            ("gas", []) => Command::Gas(None),
+            ("gas", [gas]) => Command::Gas(Some(number(gas)?)),
+            ("quit" | "q", []) => Command::Quit,
+            ("help" | "h" | "?", []) => Command::Help,
+            _ => return Err(format!("unknown command `{}`, try `help`", line.trim())),
+        };
+        Ok(command)
+    }
+}
+
+const HELP: &str = "\
+step, s               run one opcode
+next, n               run one opcode, stepping over calls and creates
+continue, c           run until a breakpoint
+break, b [PC|OPCODE]  add a breakpoint, or list them
+delete, d N           remove breakpoint N
+where, w              show the opcode about to run
+stack                 show the stack, top first
+memory [OFFSET [LEN]] show the memory
+storage [SLOT]        show the storage of the account the code runs in
+gas [GAS]             show or set the gas left
+quit, q               run to the end without stopping";
+
+/// When the debugger stops next.
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Mode {
+    /// Before the next opcode.
+    Step,
+    /// Before the next opcode in a frame at most this deep.
+    Next(Uint),
+    /// At the next breakpoint.
+    Continue,
+    /// Never, after `quit` or the end of the input.
+    Detached,
+}
+
+/// A [Tracer] stopping the execution to read commands from `input`, and
+/// writing to `out`.
+pub struct Debugger {
+    input: Box<dyn BufRead>,
+    out: Box<dyn Write>,
+    breakpoints: Vec<Breakpoint>,
+    mode: Mode,
+}
+
+impl Debugger {
+    /// A debugger stopping before the first opcode.
+    pub fn new(input: Box<dyn BufRead>, out: Box<dyn Write>) -> Self {
+        Self {
+            input,
+            out,
+            breakpoints: vec![],
+            mode: Mode::Step,
+        }
+    }
+
+    /// Add a breakpoint before the execution starts.
+    pub fn add_breakpoint(&mut self, breakpoint: Breakpoint) {
+        self.breakpoints.push(breakpoint);
+    }
+
+    /// Run until the first breakpoint rather than stopping before the first
+    /// opcode.
+    pub fn run_to_breakpoint(mut self) -> Self {
+        self.mode = Mode::Continue;
+        self
+    }
+
+    fn should_stop(&self, evm: &Evm, op: u8) -> bool {
+        let at_breakpoint = || self.breakpoints.iter().any(|breakpoint| breakpoint.hit(evm, op));
+        match &self.mode {
+            Mode::Step => true,
+            Mode::Next(depth) => evm.message.depth <= *depth || at_breakpoint(),
+            Mode::Continue => at_breakpoint(),
+            Mode::Detached => false,
+        }
+    }
+
+    // Writes are best effort: a closed terminal should not abort the
+    // execution being debugged.
+    fn print(&mut self, text: &str) {
+        let _ = writeln!(self.out, "{text}");
+    }
+
+    fn print_list(&mut self, lines: &[String]) {
+        if lines.is_empty() {
+            self.print("(empty)");
+        } else {
+            self.print(&lines.join("\n"));
+        }
+    }
+
+    fn print_position(&mut self, evm: &Evm, op: u8) {
+        let instruction = match Ops::try_from(op) {
+            Ok(op) if op.push_size() > 0 => {
+                let start = (evm.pc + 1).min(evm.code.len());
+                let end = (start + op.push_size()).min(evm.code.len());
+                format!("{} {}", op.name(), hex(&evm.code[start..end]))
+            }
+            Ok(op) => op.name().to_owned(),
+            Err(_) => format!("INVALID {}", hex(&[op])),
+        };
+        let line = format!(
+            "[depth {}] pc {:#06x} {instruction:<24} gas {}",
+            evm.message.depth, evm.pc, evm.gas_left
+        );
+        self.print(&line);
+    }
+
+    /// Read and run commands until one resumes the execution.
+    fn prompt(&mut self, evm: &mut Evm, op: u8) {
+        self.print_position(evm, op);
+        loop {
+            let _ = write!(self.out, "(debug) ");
+            let _ = self.out.flush();
+            let mut line = String::new();
+            match self.input.read_line(&mut line) {
+                Ok(0) | Err(_) => {
+                    self.mode = Mode::Detached;
+                    return;
+                }
+                Ok(_) => {}
+            }
+            let command = match Command::parse(&line) {
+                Ok(command) => command,
+                Err(error) => {
+                    self.print(&error);
+                    continue;
+                }
+            };
+            if self.execute(evm, op, command) {
+                return;
+            }
+        }
+    }
+
+    /// Run a command, returning whether it resumes the execution.
+    fn execute(&mut self, evm: &mut Evm, op: u8, command: Command) -> bool {
+        match command {
+            Command::Step => self.mode = Mode::Step,
+            Command::Next => self.mode = Mode::Next(evm.message.depth.clone()),
+            Command::Continue => self.mode = Mode::Continue,
+            Command::Quit => self.mode = Mode::Detached,
+            Command::Break(Some(breakpoint)) => {
+                self.breakpoints.push(breakpoint);
+                self.print(&format!("breakpoint {}", self.breakpoints.len() - 1));
+                return false;
+            }
+            Command::Break(None) => {
+                let list: Vec<String> = self
+                    .breakpoints
+                    .iter()
+                    .enumerate()
+                    .map(|(index, breakpoint)| match breakpoint {
+                        Breakpoint::Pc(pc) => format!("{index}: pc {pc:#x}"),
+                        Breakpoint::Op(op) => match Ops::try_from(*op) {
+                            Ok(op) => format!("{index}: {}", op.name()),
+                            Err(_) => format!("{index}: {}", hex(&[*op])),
+                        },
+                    })
+                    .collect();
+                self.print_list(&list);
+                return false;
+            }
+            Command::Delete(index) => {
+                if index < self.breakpoints.len() {
+                    self.breakpoints.remove(index);
+                } else {
+                    self.print(&format!("no breakpoint {index}"));
+                }
+                return false;
+            }
+            Command::Where => {
+                self.print_position(evm, op);
+                return false;
+            }
+            Command::Stack => {
+                let stack: Vec<String> = evm
+                    .stack
+                    .iter()
+                    .rev()
+                    .enumerate()
+                    .map(|(index, value)| format!("{index}: {value:#x}"))
+                    .collect();
+                self.print_list(&stack);
+                return false;
+            }
+            Command::Memory { offset, length } => {
+                let start = offset.min(evm.memory.len());
+                let end = length.map_or(evm.memory.len(), |length| (start + length).min(evm.memory.len()));
+                let rows: Vec<String> = evm.memory[start..end]
+                    .chunks(32)
+                    .enumerate()
+                    .map(|(row, bytes)| format!("{:#06x}: {}", start + row * 32, hex(bytes)))
+                    .collect();
+                self.print_list(&rows);
+                return false;
+            }
+            Command::Storage(slot) => {
+                let address = evm.message.current_target;
+                let slots = match slot {
+                    Some(slot) => {
+                        let key = u256_to_be_bytes32(&slot);
+                        vec![(key, get_storage(&evm.env.state, &address, &key))]
+                    }
+                    None => get_storage_slots(&evm.env.state, &address).into_iter().collect(),
+                };
+                let lines: Vec<String> = slots
+                    .iter()
+                    .map(|(key, value)| format!("{}: {value:#x}", hex(key)))
+                    .collect();
+                self.print_list(&lines);
+                return false;
+            }
+            Command::Gas(None) => {
+                self.print(&evm.gas_left.to_string());
+                return false;
+            }
+            Command::Gas(Some(gas)) => {
+                evm.gas_left = gas;
+                return false;
+            }
+            Command::Help => {
+                self.print(HELP);
+                return false;
+            }
+        }
+        true
+    }
+}
+
+impl Tracer<Evm> for Debugger {
+    fn trace(&mut self, _: &Evm, _: &TraceEvent) {}
+
+    fn trace_mut(&mut self, evm: &mut Evm, event: &TraceEvent) {
+        if self.mode == Mode::Detached {
+            return;
+        }
+        match event {
+            TraceEvent::OpStart { op } if self.should_stop(evm, *op) => self.prompt(evm, *op),
+            TraceEvent::OpException { error } => {
+                self.print(&format!(
+                    "[depth {}] pc {:#06x} halted: {error}",
+                    evm.message.depth, evm.pc
+                ));
+            }
+            TraceEvent::TransactionEnd {
+                gas_used,
+                output,
+                error,
+            } => {
+                self.print(&format!("done, gas used {gas_used}, output {}", hex(output)));
+                if let Some(error) = error {
+                    self.print(&format!("error: {error}"));
+                }
+            }
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,257 @@
+//!
+//! # Disassembler / Assembler
+//!
+//! Converts bytecode to a readable opcode listing and back, so small test
+//! programs can be written without hand-hexing them.
+//!
+//! A listing has one instruction per line:
+//!
+//! ```text
+//! 00000: PUSH1 0x80
+//! 00002: PUSH1 0x40
+//! 00004: MSTORE
+//! 00005: INVALID 0xfe
+//! ```
+//!
+//! The assembler accepts the same format. The `NNNNN:` offsets are optional,
+//! `;` starts a comment, `PUSH-N` immediates may be given in hex (`0x..`) or
+//! decimal and are left padded to `N` bytes, `INVALID 0x..` emits a raw
+//! opcode byte and `DATA 0x..` emits raw bytes.
+//!
+//! The instruction set of each fork can be looked up with [opcode],
+//! [opcode_by_name] and [opcode_table], with the immediate size, base gas
+//! and stack effect of every opcode, so tools built on the crate don't need
+//! a table of their own.
+//!
+
+use std::fmt;
+
+use crate::{
+    ethereum::{
+        base_types::{Bytes, U256},
+        frontier::vm::instructions::Ops,
+        utils::hexadecimal::{hex, hex_to_bytes},
+    },
+    ethereum_spec_tools::forks::HardFork,
+};
+
+/// A decoded opcode, independent of the fork it came from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Opcode {
+    pub value: u8,
+    pub mnemonic: &'static str,
+    pub push_size: usize,
+    /// The gas charged whatever the operands, before any dynamic cost.
+    pub base_gas: u64,
+    /// The number of stack items popped.
+    pub stack_inputs: usize,
+    /// The number of stack items pushed.
+    pub stack_outputs: usize,
+}
+
+fn frontier_opcode(op: Ops) -> Opcode {
+    let (stack_inputs, stack_outputs) = op.stack_io();
+    Opcode {
+        value: op as u8,
+        mnemonic: op.name(),
+        push_size: op.push_size(),
+        base_gas: u64::try_from(&op.base_gas()).expect("base costs fit in 64 bits"),
+        stack_inputs,
+        stack_outputs,
+    }
+}
+
+/// Look up `value` in the instruction set of `fork`.
+pub fn opcode(fork: &HardFork, value: u8) -> Option<Opcode> {
+    match fork {
+        HardFork::Frontier => Ops::try_from(value).ok().map(frontier_opcode),
+    }
+}
+
+/// Look up a mnemonic in the instruction set of `fork`.
+pub fn opcode_by_name(fork: &HardFork, mnemonic: &str) -> Option<Opcode> {
+    match fork {
+        HardFork::Frontier => Ops::from_name(mnemonic).map(frontier_opcode),
+    }
+}
+
+/// The instruction set of `fork`, in the order of the opcodes' values.
+pub fn opcode_table(fork: &HardFork) -> Vec<Opcode> {
+    match fork {
+        HardFork::Frontier => Ops::all().map(frontier_opcode).collect(),
+    }
+}
+
+/// A single line of a listing.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Instruction {
+    /// A defined opcode, with its immediate if it is a `PUSH-N`.
+    Op { opcode: Opcode, immediate: Bytes },
+    /// A byte that is not an opcode in this fork.
+    Invalid(u8),
+    /// A `PUSH-N` whose immediate runs past the end of the code. At runtime
+    /// the missing bytes read as zero, but the bytes are kept as they are.
+    Truncated { opcode: Opcode, immediate: Bytes },
+}
+
+impl Instruction {
+    /// The number of code bytes this instruction occupies.
+    pub fn size(&self) -> usize {
+        match self {
+            Instruction::Op { immediate, .. } | Instruction::Truncated { immediate, .. } => {
+                1 + immediate.len()
+            }
+            Instruction::Invalid(_) => 1,
+        }
+    }
+
+    /// The raw bytes of this instruction.
+    pub fn bytes(&self) -> Bytes {
+        match self {
+            Instruction::Op { opcode, immediate } | Instruction::Truncated { opcode, immediate } => {
+                [opcode.value].iter().chain(immediate.iter()).copied().collect()
+            }
+            Instruction::Invalid(value) => Bytes::from([*value]),
+        }
+    }
+}
+
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Instruction::Op { opcode, immediate } if opcode.push_size > 0 => {
+                write!(f, "{} {}", opcode.mnemonic, hex(immediate))
+            }
+            Instruction::Op { opcode, .. } => f.write_str(opcode.mnemonic),
+            Instruction::Invalid(value) => write!(f, "INVALID {}", hex(&[*value])),
+            // Written as raw data so the listing assembles back to the same
+            // bytes.
+            Instruction::Truncated { opcode, .. } => {
+                write!(f, "DATA {} ; truncated {}", hex(&self.bytes()), opcode.mnemonic)
+            }
+        }
+    }
+}
+
+/// Decode `code` into `(pc, instruction)` pairs.
+pub fn disassemble(fork: &HardFork, code: &[u8]) -> Vec<(usize, Instruction)> {
+    let mut instructions = vec![];
+    let mut pc = 0;
+    while pc < code.len() {
+        let instruction = match opcode(fork, code[pc]) {
+            None => Instruction::Invalid(code[pc]),
+            Some(opcode) => {
+                let start = pc + 1;
+                let end = start + opcode.push_size;
+                if end <= code.len() {
+                    Instruction::Op { opcode, immediate: code[start..end].into() }
+                } else {
+                    Instruction::Truncated { opcode, immediate: code[start..].into() }
+                }
+            }
+        };
+        let size = instruction.size();
+        instructions.push((pc, instruction));
+        pc += size;
+    }
+    instructions
+}
+
+/// Render `code` as a listing, one instruction per line.
+pub fn disassemble_to_string(fork: &HardFork, code: &[u8]) -> String {
+    disassemble(fork, code)
+        .into_iter()
+        .map(|(pc, instruction)| format!("{:05x}: {}\n", pc, instruction))
+        .collect()
+}
+
+/// An error in an assembly listing.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AssembleError {
+    /// 1-based line number of the offending line.
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for AssembleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for AssembleError {}
+
+/// Assemble a listing in the format produced by [disassemble_to_string].
+pub fn assemble(fork: &HardFork, source: &str) -> Result<Bytes, AssembleError> {
+    let mut code = vec![];
+    for (index, line) in source.lines().enumerate() {
+        let error = |message: String| AssembleError { line: index + 1, message };
+
+        let line = line.split(';').next().unwrap_or_default();
+        // Drop an optional `NNNNN:` offset.
+        let line = match line.split_once(':') {
+            Some((_, rest)) => rest,
+            None => line,
+        };
+        let mut words = line.split_whitespace();
+        let Some(mnemonic) = words.next() else {
+            continue;
+        };
+        let argument = words.next();
+        if let Some(extra) = words.next() {
+            return Err(error(format!("unexpected `{}`", extra)));
+        }
+
+        match (mnemonic.to_ascii_uppercase().as_str(), argument) {
+            ("DATA", Some(data)) => {
+                code.extend(parse_hex(data).map_err(error)?.iter());
+            }
+            ("INVALID", Some(value)) => {
+                let value = parse_hex(value).map_err(error)?;
+                if value.len() != 1 {
+                    return Err(error(format!("`{}` is not a single byte", hex(&value))));
+                }
+                code.push(value[0]);
+            }
+            (name, argument) => {
+                let opcode = opcode_by_name(fork, name)
+                    .ok_or_else(|| error(format!("unknown opcode `{}` in {:?}", mnemonic, fork)))?;
+                code.push(opcode.value);
+                match (opcode.push_size, argument) {
+                    (0, None) => {}
+                    (0, Some(argument)) => {
+                        return Err(error(format!("`{}` takes no immediate, got `{}`", name, argument)));
+                    }
+                    (_, None) => return Err(error(format!("`{}` needs an immediate", name))),
+                    (size, Some(argument)) => {
+                        code.extend(parse_immediate(argument, size).map_err(error)?.iter());
+                    }
+                }
+            }
+        }
+    }
+    Ok(code.into())
+}
+
+fn parse_hex(value: &str) -> Result<Bytes, String> {
+    if !value.starts_with("0x") {
+        return Err(format!("expected a 0x prefixed hex string, got `{}`", value));
+    }
+    hex_to_bytes(value).map_err(|_| format!("bad hex string `{}`", value))
+}
+
+// Parses a hex or decimal `PUSH-N` immediate and left pads it to `size` bytes.
+fn parse_immediate(value: &str, size: usize) -> Result<Vec<u8>, String> {
+    let number = match value.strip_prefix("0x") {
+        Some(digits) => U256::parse_bytes(digits.as_bytes(), 16),
+        None => U256::parse_bytes(value.as_bytes(), 10),
+    }
+    .ok_or_else(|| format!("bad immediate `{}`", value))?;
+    let bytes = if number == U256::from(0u8) { vec![] } else { number.to_bytes_be() };
+    if bytes.len() > size {
+        return Err(format!("immediate `{}` does not fit in {} bytes", value, size));
+    }
+    let mut immediate = vec![0; size - bytes.len()];
+    immediate.extend(bytes);
+    Ok(immediate)
+}
@@ -0,0 +1,153 @@
+//!
+//! # State Dumps
+//!
+//! Writes and reads the whole state in the format of geth's `debug_dumpBlock`
+//! and `geth dump`:
+//!
+//! ```json
+//! {
+//!     "root": "0x...",
+//!     "accounts": {
+//!         "0x1000000000000000000000000000000000000000": {
+//!             "balance": "1000000000000000000",
+//!             "nonce": 1,
+//!             "root": "0x...",
+//!             "codeHash": "0x...",
+//!             "code": "0x6000",
+//!             "storage": {
+//!                 "0x0000000000000000000000000000000000000000000000000000000000000001": "02"
+//!             },
+//!             "address": "0x1000000000000000000000000000000000000000",
+//!             "key": "0x..."
+//!         }
+//!     }
+//! }
+//! ```
+//!
+//! As in geth, balances are decimal, `code` and `storage` are left out when
+//! empty, and storage values are unprefixed hex without leading zeros. The
+//! importer also takes `0x` prefixed values, so it accepts hand written
+//! dumps.
+//!
+
+use serde_json::{json, Map, Value};
+
+use super::alloc::{json_str, json_uint, parse_uint};
+use crate::ethereum::{
+    base_types::{u256_to_be_bytes32, Uint, U256},
+    exceptions::EthereumException,
+    frontier::{
+        fork_types::{keccak256, Account},
+        state::{get_accounts, get_storage_slots, set_account, set_storage, state_root, storage_root, State},
+    },
+    utils::{
+        ensure::ensure,
+        hexadecimal::{hex, hex_to_bytes, hex_to_bytes20, hex_to_hash, hex_to_u256},
+    },
+};
+
+///
+///     Dump every account of the state, with its code and storage.
+///
+///     Parameters
+///     ----------
+///     state :
+///         The state, with no open transactions.
+///
+///     Returns
+///     -------
+///     dump : `serde_json::Value`
+///         The dump.
+///
+pub fn dump_state(state: &State) -> Value {
+    let mut accounts = Map::new();
+    for (address, account) in get_accounts(state) {
+        let mut dumped = Map::new();
+        dumped.insert("balance".into(), json!(account.balance.to_string()));
+        dumped.insert("nonce".into(), json!(u64::try_from(&account.nonce).expect("nonces fit in 64 bits")));
+        dumped.insert("root".into(), json!(hex(&storage_root(state, &address))));
+        dumped.insert("codeHash".into(), json!(hex(&keccak256(&account.code))));
+        if !account.code.is_empty() {
+            dumped.insert("code".into(), json!(hex(&account.code)));
+        }
+        let storage = get_storage_slots(state, &address);
+        if !storage.is_empty() {
+            let storage: Map<_, _> = storage
+                .into_iter()
+                .map(|(key, value)| (hex(&key), json!(hex(&value.to_bytes_be())[2..])))
+                .collect();
+            dumped.insert("storage".into(), Value::Object(storage));
+        }
+        dumped.insert("address".into(), json!(hex(&address)));
+        dumped.insert("key".into(), json!(hex(&keccak256(&address))));
+        accounts.insert(hex(&address), Value::Object(dumped));
+    }
+    json!({
+        "root": hex(&state_root(state)),
+        "accounts": accounts,
+    })
+}
+
+///
+///     Rebuild a state from a dump.
+///
+///     If the dump has a `root`, the rebuilt state must have that root, which
+///     catches truncated or edited dumps.
+///
+///     Parameters
+///     ----------
+///     dump :
+///         The dump, as JSON.
+///
+///     Returns
+///     -------
+///     state : `State`
+///         A state holding exactly the accounts of the dump.
+///
+pub fn load_state_dump(dump: &str) -> Result<State, EthereumException> {
+    let value: Value = serde_json::from_str(dump)
+        .map_err(|e| EthereumException::JsonDecodeError(e.to_string()))?;
+    let accounts = value["accounts"].as_object().ok_or_else(|| {
+        EthereumException::JsonDecodeError("a dump must have an `accounts` object".to_owned())
+    })?;
+
+    let mut state = State::default();
+    for (address, account) in accounts {
+        let address = hex_to_bytes20(address)?;
+        let balance = match account.get("balance") {
+            Some(balance) => json_uint(balance)?,
+            None => U256::from(0u8),
+        };
+        let nonce = match account.get("nonce") {
+            Some(nonce) => json_uint(nonce)?,
+            None => Uint::from(0u8),
+        };
+        let code = match account.get("code") {
+            Some(code) => hex_to_bytes(json_str(code)?)?,
+            None => Default::default(),
+        };
+        if let Some(code_hash) = account.get("codeHash") {
+            ensure(
+                hex_to_hash(json_str(code_hash)?)? == keccak256(&code),
+                EthereumException::ValueError,
+            )?;
+        }
+        set_account(&mut state, address, Some(Account { nonce, balance, code: code.into() }));
+
+        if let Some(storage) = account.get("storage").and_then(Value::as_object) {
+            for (key, value) in storage {
+                let key = u256_to_be_bytes32(&parse_uint(key)?);
+                let value = hex_to_u256(json_str(value)?)?;
+                set_storage(&mut state, address, &key, value);
+            }
+        }
+    }
+
+    if let Some(root) = value.get("root") {
+        ensure(
+            hex_to_hash(json_str(root)?)? == state_root(&state),
+            EthereumException::ValueError,
+        )?;
+    }
+    Ok(state)
+}
@@ -0,0 +1,153 @@
+//!
+//! # EIP-3155 Tracer
+//!
+//! Writes an [EIP-3155](https://eips.ethereum.org/EIPS/eip-3155) trace of the
+//! execution as JSON lines, in the format of geth's `--json` flag.
+//!
+//! There is one line per executed opcode, written once the cost of the opcode
+//! is known, and a final summary line:
+//!
+//! ```text
+//! {"pc":0,"op":96,"gas":"0x2710","gasCost":"0x3","memSize":0,"stack":[],"depth":1,"refund":0,"opName":"PUSH1"}
+//! {"output":"0x","gasUsed":"0x3"}
+//! ```
+//!
+
+use std::io::Write;
+
+use serde_json::{json, Map, Value};
+
+use crate::ethereum::{
+    base_types::Uint,
+    frontier::vm::{instructions::Ops, Evm},
+    trace::{TraceEvent, Tracer},
+    utils::hexadecimal::hex,
+};
+
+/// The trace line of an opcode that has started but whose line hasn't been
+/// written yet.
+struct Step {
+    pc: usize,
+    depth: Uint,
+    line: Map<String, Value>,
+    gas_cost_traced: bool,
+}
+
+/// A [Tracer] writing an EIP-3155 trace to `out`.
+pub struct Eip3155Tracer {
+    out: Box<dyn Write>,
+    pending: Option<Step>,
+}
+
+impl Eip3155Tracer {
+    pub fn new(out: Box<dyn Write>) -> Self {
+        Self { out, pending: None }
+    }
+
+    fn step(evm: &Evm, op: u8) -> Step {
+        let op_name = match Ops::try_from(op) {
+            Ok(op) => op.name().to_owned(),
+            Err(_) => format!("opcode {} not defined", hex(&[op])),
+        };
+        let stack: Vec<Value> = evm
+            .stack
+            .iter()
+            .map(|value| Value::from(format!("{value:#x}")))
+            .collect();
+
+        let mut line = Map::new();
+        line.insert("pc".into(), json!(evm.pc));
+        line.insert("op".into(), json!(op));
+        line.insert("gas".into(), json!(format!("{:#x}", evm.gas_left)));
+        line.insert("gasCost".into(), json!("0x0"));
+        line.insert("memSize".into(), json!(evm.memory.len()));
+        line.insert("stack".into(), Value::Array(stack));
+        line.insert("depth".into(), json!(u64::try_from(&evm.message.depth + 1u8).unwrap()));
+        line.insert("refund".into(), json!(u64::try_from(&evm.refund_counter).unwrap()));
+        line.insert("opName".into(), json!(op_name));
+
+        Step {
+            pc: evm.pc,
+            depth: evm.message.depth.clone(),
+            line,
+            gas_cost_traced: false,
+        }
+    }
+
+    fn write_line(&mut self, line: Value) {
+        // A broken pipe should not abort the execution being traced.
+        let _ = writeln!(self.out, "{line}");
+    }
+
+    fn flush_pending(&mut self) {
+        if let Some(step) = self.pending.take() {
+            self.write_line(Value::Object(step.line));
+        }
+    }
+}
+
+impl Tracer<Evm> for Eip3155Tracer {
+    fn trace(&mut self, evm: &Evm, event: &TraceEvent) {
+        match event {
+            TraceEvent::OpStart { op } => {
+                self.flush_pending();
+                self.pending = Some(Self::step(evm, *op));
+            }
+            TraceEvent::GasAndRefund { gas_cost } => {
+                if let Some(step) = self.pending.as_mut().filter(|step| !step.gas_cost_traced) {
+                    step.line.insert("gasCost".into(), json!(format!("{gas_cost:#x}")));
+                    step.line.insert(
+                        "refund".into(),
+                        json!(u64::try_from(&evm.refund_counter).unwrap()),
+                    );
+                    step.gas_cost_traced = true;
+                }
+            }
+            TraceEvent::OpException { error } => {
+                let is_current = self
+                    .pending
+                    .as_ref()
+                    .is_some_and(|step| step.pc == evm.pc && step.depth == evm.message.depth);
+                if !is_current {
+                    // The opcode failed before it started, e.g. because it is
+                    // undefined.
+                    self.flush_pending();
+                    let op = evm.code.get(evm.pc).copied().unwrap_or(Ops::STOP as u8);
+                    self.pending = Some(Self::step(evm, op));
+                }
+                if let Some(step) = self.pending.as_mut() {
+                    step.line.insert("error".into(), json!(error));
+                }
+            }
+            TraceEvent::EvmStop { op } => {
+                // Running past the end of the code is an implicit `STOP`,
+                // which geth traces unless the code is empty.
+                if evm.running && !evm.code.is_empty() {
+                    self.trace(evm, &TraceEvent::OpStart { op: *op });
+                }
+            }
+            TraceEvent::TransactionEnd {
+                gas_used,
+                output,
+                error,
+            } => {
+                self.flush_pending();
+                let mut line = Map::new();
+                line.insert("output".into(), json!(hex(output)));
+                line.insert("gasUsed".into(), json!(format!("{gas_used:#x}")));
+                if let Some(error) = error {
+                    line.insert("error".into(), json!(error));
+                }
+                self.write_line(Value::Object(line));
+                let _ = self.out.flush();
+            }
+            // Like geth, precompiles are not traced. Frames show up in the
+            // `depth` of their steps.
+            TraceEvent::MessageStart { .. }
+            | TraceEvent::MessageEnd { .. }
+            | TraceEvent::PrecompileStart { .. }
+            | TraceEvent::PrecompileEnd
+            | TraceEvent::OpEnd => {}
+        }
+    }
+}
@@ -0,0 +1,712 @@
+//!
+//! # EVMC
+//!
+//! The spec interpreter as an [EVMC](https://github.com/ethereum/evmc) VM, so
+//! it can be loaded by clients and testing tools that speak EVMC, such as
+//! evmone's `evmc run` and state tests, or geth's `--vm.evm`. Build it as a
+//! shared library with
+//!
+//! ```text
+//! cargo rustc --lib --release --features evmc --crate-type cdylib
+//! ```
+//!
+//! which exports `evmc_create_execution_specs_rs`, the name the EVMC loader
+//! looks for in `libexecution_specs_rs.so`. Only `EVMC_FRONTIER` is
+//! supported; other revisions are rejected.
+//!
+//! EVMC splits the work between the VM, which runs the code of a single
+//! frame, and the host, which owns the state and runs nested calls. The
+//! instructions of the spec read and write a `State`, so each frame gets a
+//! scratch one: before an instruction that reads the state runs, the values
+//! it reads are loaded from the host, and after one that writes, the change
+//! is passed on. Calls and creations are handed to the host instead of
+//! recursing. The gas rules are those of the instructions themselves.
+//!
+//! The types mirror those of `evmc/evmc.h`, ABI version 10.
+//!
+
+use std::{
+    ffi::{c_char, c_int},
+    ptr, slice,
+};
+
+use crate::ethereum::{
+    base_types::{u256_to_be_bytes32, Bytes, Bytes32, Uint, U256},
+    frontier::{
+        fork_types::{EMPTY_ACCOUNT, Account, Address, Log},
+        state::{set_account, set_storage},
+        utils::address::to_address,
+        vm::{
+            exceptions::{EvmError, Result},
+            gas::{self, calculate_gas_extend_memory, calculate_message_call_gas, charge_gas},
+            instructions::{op_implementation, Ops},
+            interpreter::{new_evm, STACK_DEPTH_LIMIT},
+            memory::{memory_read_bytes, memory_write},
+            stack, Environment, Evm, Message,
+        },
+    },
+};
+
+/// The EVMC ABI version these types follow.
+pub const EVMC_ABI_VERSION: c_int = 10;
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct EvmcBytes32 {
+    pub bytes: [u8; 32],
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct EvmcAddress {
+    pub bytes: [u8; 20],
+}
+
+/// An `enum evmc_call_kind`.
+pub type EvmcCallKind = c_int;
+pub const EVMC_CALL: EvmcCallKind = 0;
+pub const EVMC_DELEGATECALL: EvmcCallKind = 1;
+pub const EVMC_CALLCODE: EvmcCallKind = 2;
+pub const EVMC_CREATE: EvmcCallKind = 3;
+pub const EVMC_CREATE2: EvmcCallKind = 4;
+
+/// An `enum evmc_status_code`.
+pub type EvmcStatusCode = c_int;
+pub const EVMC_SUCCESS: EvmcStatusCode = 0;
+pub const EVMC_FAILURE: EvmcStatusCode = 1;
+pub const EVMC_REVERT: EvmcStatusCode = 2;
+pub const EVMC_OUT_OF_GAS: EvmcStatusCode = 3;
+pub const EVMC_UNDEFINED_INSTRUCTION: EvmcStatusCode = 5;
+pub const EVMC_STACK_OVERFLOW: EvmcStatusCode = 6;
+pub const EVMC_STACK_UNDERFLOW: EvmcStatusCode = 7;
+pub const EVMC_BAD_JUMP_DESTINATION: EvmcStatusCode = 8;
+pub const EVMC_CALL_DEPTH_EXCEEDED: EvmcStatusCode = 10;
+pub const EVMC_REJECTED: EvmcStatusCode = -2;
+
+/// An `enum evmc_revision`.
+pub type EvmcRevision = c_int;
+pub const EVMC_FRONTIER: EvmcRevision = 0;
+
+/// An `enum evmc_storage_status`, which Frontier gas doesn't depend on.
+pub type EvmcStorageStatus = c_int;
+
+/// An `enum evmc_access_status`, which Frontier gas doesn't depend on.
+pub type EvmcAccessStatus = c_int;
+
+pub const EVMC_CAPABILITY_EVM1: u32 = 1;
+
+/// An `enum evmc_set_option_result`.
+pub type EvmcSetOptionResult = c_int;
+pub const EVMC_SET_OPTION_INVALID_NAME: EvmcSetOptionResult = 1;
+
+/// The message of a call or contract creation.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct EvmcMessage {
+    pub kind: EvmcCallKind,
+    pub flags: u32,
+    pub depth: i32,
+    pub gas: i64,
+    pub recipient: EvmcAddress,
+    pub sender: EvmcAddress,
+    pub input_data: *const u8,
+    pub input_size: usize,
+    pub value: EvmcBytes32,
+    pub create2_salt: EvmcBytes32,
+    pub code_address: EvmcAddress,
+}
+
+/// The outcome of a call, whose output is freed by calling `release`.
+#[repr(C)]
+pub struct EvmcResult {
+    pub status_code: EvmcStatusCode,
+    pub gas_left: i64,
+    pub gas_refund: i64,
+    pub output_data: *const u8,
+    pub output_size: usize,
+    pub release: Option<unsafe extern "C" fn(result: *const EvmcResult)>,
+    pub create_address: EvmcAddress,
+    pub padding: [u8; 4],
+}
+
+/// The transaction and block a call runs in.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct EvmcTxContext {
+    pub tx_gas_price: EvmcBytes32,
+    pub tx_origin: EvmcAddress,
+    pub block_coinbase: EvmcAddress,
+    pub block_number: i64,
+    pub block_timestamp: i64,
+    pub block_gas_limit: i64,
+    /// The difficulty, before the merge.
+    pub block_prev_randao: EvmcBytes32,
+    pub chain_id: EvmcBytes32,
+    pub block_base_fee: EvmcBytes32,
+}
+
+/// The state of the host, opaque to the VM.
+#[repr(C)]
+pub struct EvmcHostContext {
+    _private: [u8; 0],
+}
+
+type Context = *mut EvmcHostContext;
+
+/// The callbacks of the host.
+#[repr(C)]
+pub struct EvmcHostInterface {
+    pub account_exists: unsafe extern "C" fn(Context, *const EvmcAddress) -> bool,
+    pub get_storage:
+        unsafe extern "C" fn(Context, *const EvmcAddress, *const EvmcBytes32) -> EvmcBytes32,
+    pub set_storage: unsafe extern "C" fn(
+        Context,
+        *const EvmcAddress,
+        *const EvmcBytes32,
+        *const EvmcBytes32,
+    ) -> EvmcStorageStatus,
+    pub get_balance: unsafe extern "C" fn(Context, *const EvmcAddress) -> EvmcBytes32,
+    pub get_code_size: unsafe extern "C" fn(Context, *const EvmcAddress) -> usize,
+    pub get_code_hash: unsafe extern "C" fn(Context, *const EvmcAddress) -> EvmcBytes32,
+    pub copy_code:
+        unsafe extern "C" fn(Context, *const EvmcAddress, usize, *mut u8, usize) -> usize,
+    pub selfdestruct: unsafe extern "C" fn(Context, *const EvmcAddress, *const EvmcAddress) -> bool,
+    pub call: unsafe extern "C" fn(Context, *const EvmcMessage) -> EvmcResult,
+    pub get_tx_context: unsafe extern "C" fn(Context) -> EvmcTxContext,
+    pub get_block_hash: unsafe extern "C" fn(Context, i64) -> EvmcBytes32,
+    pub emit_log: unsafe extern "C" fn(
+        Context,
+        *const EvmcAddress,
+        *const u8,
+        usize,
+        *const EvmcBytes32,
+        usize,
+    ),
+    pub access_account: unsafe extern "C" fn(Context, *const EvmcAddress) -> EvmcAccessStatus,
+    pub access_storage:
+        unsafe extern "C" fn(Context, *const EvmcAddress, *const EvmcBytes32) -> EvmcAccessStatus,
+}
+
+/// An EVMC VM instance, as returned by `evmc_create_execution_specs_rs`.
+#[repr(C)]
+pub struct EvmcVm {
+    pub abi_version: c_int,
+    pub name: *const c_char,
+    pub version: *const c_char,
+    pub destroy: Option<unsafe extern "C" fn(vm: *mut EvmcVm)>,
+    #[allow(clippy::type_complexity)]
+    pub execute: Option<
+        unsafe extern "C" fn(
+            vm: *mut EvmcVm,
+            host: *const EvmcHostInterface,
+            context: Context,
+            rev: EvmcRevision,
+            msg: *const EvmcMessage,
+            code: *const u8,
+            code_size: usize,
+        ) -> EvmcResult,
+    >,
+    pub get_capabilities: Option<unsafe extern "C" fn(vm: *mut EvmcVm) -> u32>,
+    pub set_option: Option<
+        unsafe extern "C" fn(
+            vm: *mut EvmcVm,
+            name: *const c_char,
+            value: *const c_char,
+        ) -> EvmcSetOptionResult,
+    >,
+}
+
+fn to_bytes32(value: &U256) -> EvmcBytes32 {
+    EvmcBytes32 {
+        bytes: u256_to_be_bytes32(value),
+    }
+}
+
+fn from_bytes32(value: EvmcBytes32) -> U256 {
+    U256::from_bytes_be(&value.bytes)
+}
+
+fn to_i64(gas: &U256) -> i64 {
+    i64::try_from(gas).expect("gas is bounded by the gas of the message")
+}
+
+/// # Safety
+///
+/// `data` must be null with `size` zero, or point to `size` bytes.
+unsafe fn bytes_from_raw(data: *const u8, size: usize) -> Bytes {
+    if size == 0 {
+        Bytes::default()
+    } else {
+        slice::from_raw_parts(data, size).into()
+    }
+}
+
+/// The host of a frame, with safe wrappers around its callbacks.
+struct Host<'a> {
+    interface: &'a EvmcHostInterface,
+    context: Context,
+}
+
+impl Host<'_> {
+    fn account_exists(&self, address: &Address) -> bool {
+        let address = EvmcAddress { bytes: *address };
+        unsafe { (self.interface.account_exists)(self.context, &address) }
+    }
+
+    fn get_storage(&self, address: &Address, key: &Bytes32) -> U256 {
+        let address = EvmcAddress { bytes: *address };
+        let key = EvmcBytes32 { bytes: *key };
+        from_bytes32(unsafe { (self.interface.get_storage)(self.context, &address, &key) })
+    }
+
+    fn set_storage(&self, address: &Address, key: &Bytes32, value: &U256) {
+        let address = EvmcAddress { bytes: *address };
+        let key = EvmcBytes32 { bytes: *key };
+        unsafe { (self.interface.set_storage)(self.context, &address, &key, &to_bytes32(value)) };
+    }
+
+    fn get_account(&self, address: &Address) -> Option<Account> {
+        if !self.account_exists(address) {
+            return None;
+        }
+        let evmc_address = EvmcAddress { bytes: *address };
+        let balance = unsafe { (self.interface.get_balance)(self.context, &evmc_address) };
+        let code_size = unsafe { (self.interface.get_code_size)(self.context, &evmc_address) };
+        let mut code = vec![0; code_size];
+        let copied = unsafe {
+            (self.interface.copy_code)(self.context, &evmc_address, 0, code.as_mut_ptr(), code_size)
+        };
+        code.truncate(copied);
+        Some(Account {
+            // The nonce isn't visible to EVMC VMs, and no instruction reads it.
+            nonce: Uint::from(0u8),
+            balance: from_bytes32(balance),
+            code: code.into(),
+        })
+    }
+
+    fn get_balance(&self, address: &Address) -> U256 {
+        let address = EvmcAddress { bytes: *address };
+        from_bytes32(unsafe { (self.interface.get_balance)(self.context, &address) })
+    }
+
+    fn selfdestruct(&self, address: &Address, beneficiary: &Address) -> bool {
+        let address = EvmcAddress { bytes: *address };
+        let beneficiary = EvmcAddress { bytes: *beneficiary };
+        unsafe { (self.interface.selfdestruct)(self.context, &address, &beneficiary) }
+    }
+
+    /// Run `message` on the host, returning its status, gas left, refund,
+    /// output and created address.
+    fn call(&self, message: &EvmcMessage) -> (EvmcStatusCode, i64, i64, Bytes, Address) {
+        unsafe {
+            let result = (self.interface.call)(self.context, message);
+            let output = bytes_from_raw(result.output_data, result.output_size);
+            if let Some(release) = result.release {
+                release(&result);
+            }
+            (
+                result.status_code,
+                result.gas_left,
+                result.gas_refund,
+                output,
+                result.create_address.bytes,
+            )
+        }
+    }
+
+    fn get_tx_context(&self) -> EvmcTxContext {
+        unsafe { (self.interface.get_tx_context)(self.context) }
+    }
+
+    fn get_block_hash(&self, number: i64) -> Bytes32 {
+        unsafe { (self.interface.get_block_hash)(self.context, number).bytes }
+    }
+
+    fn emit_log(&self, log: &Log) {
+        let address = EvmcAddress { bytes: log.address };
+        let topics: Vec<EvmcBytes32> =
+            log.topics.iter().map(|topic| EvmcBytes32 { bytes: *topic }).collect();
+        unsafe {
+            (self.interface.emit_log)(
+                self.context,
+                &address,
+                log.data.as_ptr(),
+                log.data.len(),
+                topics.as_ptr(),
+                topics.len(),
+            )
+        };
+    }
+}
+
+/// Copy the transaction and block of the host into the environment.
+fn load_tx_context(evm: &mut Evm, host: &Host) {
+    let context = host.get_tx_context();
+    evm.env.tx.origin = context.tx_origin.bytes;
+    evm.env.tx.gas_price = from_bytes32(context.tx_gas_price);
+    evm.env.block.coinbase = context.block_coinbase.bytes;
+    evm.env.block.number = Uint::from(context.block_number.max(0) as u64);
+    evm.env.block.time = U256::from(context.block_timestamp.max(0) as u64);
+    evm.env.block.gas_limit = Uint::from(context.block_gas_limit.max(0) as u64);
+    evm.env.block.difficulty = from_bytes32(context.block_prev_randao);
+}
+
+/// Make the hash `BLOCKHASH` is about to read the only one in the
+/// environment, at the depth it is read from.
+fn load_block_hash(evm: &mut Evm, host: &Host) {
+    let Some(block_number) = evm.stack.last() else {
+        return;
+    };
+    let number = &evm.env.block.number;
+    evm.env.block.block_hashes = vec![];
+    if number > block_number && *number <= block_number + U256::from(256u16) {
+        let depth = usize::try_from(number - block_number).unwrap();
+        let block_number = i64::try_from(block_number).unwrap();
+        evm.env.block.block_hashes = vec![[0; 32]; depth];
+        evm.env.block.block_hashes[0] = host.get_block_hash(block_number);
+    }
+}
+
+fn load_account(evm: &mut Evm, host: &Host, address: Address) {
+    set_account(&mut evm.env.state, address, host.get_account(&address));
+}
+
+fn load_storage(evm: &mut Evm, host: &Host, key: &Bytes32) {
+    let address = evm.message.current_target;
+    set_storage(&mut evm.env.state, address, key, host.get_storage(&address, key));
+}
+
+/// `CALL` and `CALLCODE`, with the child frame run by the host.
+fn call(evm: &mut Evm, host: &Host, op: Ops) -> Result<()> {
+    // STACK
+    let gas = stack::pop(&mut evm.stack)?;
+    let code_address = to_address(&stack::pop(&mut evm.stack)?);
+    let value = stack::pop(&mut evm.stack)?;
+    let memory_input_start_position = stack::pop(&mut evm.stack)?;
+    let memory_input_size = stack::pop(&mut evm.stack)?;
+    let memory_output_start_position = stack::pop(&mut evm.stack)?;
+    let memory_output_size = stack::pop(&mut evm.stack)?;
+
+    // GAS
+    let to = match op {
+        Ops::CALLCODE => evm.message.current_target,
+        _ => code_address,
+    };
+    let extend_memory = calculate_gas_extend_memory(
+        &evm.memory,
+        [
+            (memory_input_start_position.clone(), memory_input_size.clone()),
+            (memory_output_start_position.clone(), memory_output_size.clone()),
+        ]
+        .to_vec(),
+    );
+    load_account(evm, host, to);
+    let message_call_gas = calculate_message_call_gas(&evm.env.state, gas, &to, value.clone());
+    charge_gas(evm, message_call_gas.cost + extend_memory.cost)?;
+
+    // OPERATION
+    evm.memory
+        .extend([0].repeat(usize::try_from(extend_memory.expand_by).unwrap()));
+    let sender_balance = host.get_balance(&evm.message.current_target);
+    if sender_balance < value || &evm.message.depth + 1u8 > Uint::from(STACK_DEPTH_LIMIT) {
+        stack::push(&mut evm.stack, U256::from(0u8))?;
+        evm.gas_left += message_call_gas.stipend;
+    } else {
+        let input = memory_read_bytes(&evm.memory, memory_input_start_position, memory_input_size);
+        let message = EvmcMessage {
+            kind: if op == Ops::CALLCODE { EVMC_CALLCODE } else { EVMC_CALL },
+            flags: 0,
+            depth: i32::try_from(&evm.message.depth + 1u8).unwrap(),
+            gas: to_i64(&message_call_gas.stipend),
+            recipient: EvmcAddress { bytes: to },
+            sender: EvmcAddress {
+                bytes: evm.message.current_target,
+            },
+            input_data: input.as_ptr(),
+            input_size: input.len(),
+            value: to_bytes32(&value),
+            create2_salt: EvmcBytes32::default(),
+            code_address: EvmcAddress {
+                bytes: code_address,
+            },
+        };
+        let (status_code, gas_left, gas_refund, output, _) = host.call(&message);
+
+        evm.gas_left += U256::from(gas_left.max(0) as u64);
+        if status_code == EVMC_SUCCESS {
+            evm.refund_counter += U256::from(gas_refund.max(0) as u64);
+            stack::push(&mut evm.stack, U256::from(1u8))?;
+        } else {
+            stack::push(&mut evm.stack, U256::from(0u8))?;
+        }
+        let actual_output_size = memory_output_size.min(U256::from(output.len()));
+        let actual_output_size = usize::try_from(actual_output_size).unwrap();
+        memory_write(
+            &mut evm.memory,
+            memory_output_start_position,
+            &output[..actual_output_size],
+        );
+    }
+
+    // PROGRAM COUNTER
+    evm.pc += 1;
+    Ok(())
+}
+
+/// `CREATE`, with the init code run by the host.
+fn create(evm: &mut Evm, host: &Host) -> Result<()> {
+    // STACK
+    let endowment = stack::pop(&mut evm.stack)?;
+    let memory_start_position = stack::pop(&mut evm.stack)?;
+    let memory_size = stack::pop(&mut evm.stack)?;
+
+    // GAS
+    let extend_memory = calculate_gas_extend_memory(
+        &evm.memory,
+        [(memory_start_position.clone(), memory_size.clone())].to_vec(),
+    );
+    charge_gas(evm, gas::GAS_CREATE() + extend_memory.cost)?;
+
+    let create_message_gas = std::mem::take(&mut evm.gas_left);
+
+    // OPERATION
+    evm.memory
+        .extend([0].repeat(usize::try_from(extend_memory.expand_by).unwrap()));
+    let sender_balance = host.get_balance(&evm.message.current_target);
+    if sender_balance < endowment || &evm.message.depth + 1u8 > Uint::from(STACK_DEPTH_LIMIT) {
+        stack::push(&mut evm.stack, U256::from(0u8))?;
+        evm.gas_left += create_message_gas;
+    } else {
+        let init_code = memory_read_bytes(&evm.memory, memory_start_position, memory_size);
+        let message = EvmcMessage {
+            kind: EVMC_CREATE,
+            flags: 0,
+            depth: i32::try_from(&evm.message.depth + 1u8).unwrap(),
+            gas: to_i64(&create_message_gas),
+            recipient: EvmcAddress::default(),
+            sender: EvmcAddress {
+                bytes: evm.message.current_target,
+            },
+            input_data: init_code.as_ptr(),
+            input_size: init_code.len(),
+            value: to_bytes32(&endowment),
+            create2_salt: EvmcBytes32::default(),
+            code_address: EvmcAddress::default(),
+        };
+        let (status_code, gas_left, gas_refund, _, create_address) = host.call(&message);
+
+        evm.gas_left += U256::from(gas_left.max(0) as u64);
+        if status_code == EVMC_SUCCESS {
+            evm.refund_counter += U256::from(gas_refund.max(0) as u64);
+            stack::push(&mut evm.stack, U256::from_bytes_be(&create_address))?;
+        } else {
+            stack::push(&mut evm.stack, U256::from(0u8))?;
+        }
+    }
+
+    // PROGRAM COUNTER
+    evm.pc += 1;
+    Ok(())
+}
+
+/// `SELFDESTRUCT`, with the balance moved by the host.
+fn selfdestruct(evm: &mut Evm, host: &Host) -> Result<()> {
+    // STACK
+    let beneficiary = to_address(&stack::pop(&mut evm.stack)?);
+
+    // OPERATION
+    // The refund is only given the first time an account is destroyed.
+    if host.selfdestruct(&evm.message.current_target, &beneficiary) {
+        evm.refund_counter += gas::REFUND_SELF_DESTRUCT();
+    }
+    evm.running = false;
+    Ok(())
+}
+
+fn run(evm: &mut Evm, host: &Host) -> Result<()> {
+    let mut loaded_tx_context = false;
+    while evm.running && evm.pc < evm.code.len() {
+        let op = Ops::try_from(evm.code[evm.pc])?;
+        match op {
+            Ops::ORIGIN
+            | Ops::GASPRICE
+            | Ops::BLOCKHASH
+            | Ops::COINBASE
+            | Ops::TIMESTAMP
+            | Ops::NUMBER
+            | Ops::DIFFICULTY
+            | Ops::GASLIMIT => {
+                if !loaded_tx_context {
+                    load_tx_context(evm, host);
+                    loaded_tx_context = true;
+                }
+                if op == Ops::BLOCKHASH {
+                    load_block_hash(evm, host);
+                }
+                op_implementation(op)(evm)?;
+            }
+            Ops::BALANCE | Ops::EXTCODESIZE | Ops::EXTCODECOPY => {
+                if let Some(address) = evm.stack.last() {
+                    load_account(evm, host, to_address(address));
+                }
+                op_implementation(op)(evm)?;
+            }
+            Ops::SLOAD => {
+                if let Some(key) = evm.stack.last() {
+                    load_storage(evm, host, &u256_to_be_bytes32(key));
+                }
+                op_implementation(op)(evm)?;
+            }
+            Ops::SSTORE => {
+                let [.., new_value, key] = evm.stack.as_slice() else {
+                    return Err(EvmError::StackUnderflow);
+                };
+                let (key, new_value) = (u256_to_be_bytes32(key), new_value.clone());
+                load_storage(evm, host, &key);
+                op_implementation(op)(evm)?;
+                host.set_storage(&evm.message.current_target, &key, &new_value);
+            }
+            Ops::LOG0 | Ops::LOG1 | Ops::LOG2 | Ops::LOG3 | Ops::LOG4 => {
+                op_implementation(op)(evm)?;
+                for log in evm.logs.drain(..) {
+                    host.emit_log(&log);
+                }
+            }
+            Ops::CALL | Ops::CALLCODE => call(evm, host, op)?,
+            Ops::CREATE => create(evm, host)?,
+            Ops::SELFDESTRUCT => selfdestruct(evm, host)?,
+            _ => op_implementation(op)(evm)?,
+        }
+    }
+    Ok(())
+}
+
+fn status_code(error: EvmError) -> EvmcStatusCode {
+    match error {
+        EvmError::OutOfGas => EVMC_OUT_OF_GAS,
+        EvmError::InvalidOpcode => EVMC_UNDEFINED_INSTRUCTION,
+        EvmError::StackOverflow => EVMC_STACK_OVERFLOW,
+        EvmError::StackUnderflow => EVMC_STACK_UNDERFLOW,
+        EvmError::InvalidJumpDest => EVMC_BAD_JUMP_DESTINATION,
+        EvmError::StackDepthLimit => EVMC_CALL_DEPTH_EXCEEDED,
+        EvmError::Halt | EvmError::AddressCollision => EVMC_FAILURE,
+    }
+}
+
+unsafe extern "C" fn release_result(result: *const EvmcResult) {
+    let result = &*result;
+    if !result.output_data.is_null() {
+        let output = ptr::slice_from_raw_parts_mut(result.output_data as *mut u8, result.output_size);
+        drop(Box::from_raw(output));
+    }
+}
+
+fn evmc_result(status_code: EvmcStatusCode, gas_left: i64, gas_refund: i64, output: Bytes) -> EvmcResult {
+    let (output_data, output_size) = if output.is_empty() {
+        (ptr::null(), 0)
+    } else {
+        let output_size = output.len();
+        (Box::into_raw(output) as *const u8, output_size)
+    };
+    EvmcResult {
+        status_code,
+        gas_left,
+        gas_refund,
+        output_data,
+        output_size,
+        release: Some(release_result),
+        create_address: EvmcAddress::default(),
+        padding: [0; 4],
+    }
+}
+
+///
+///     Execute the code of a frame.
+///
+///     Safety
+///     ------
+///     `host` and `msg` must be valid, and `code` must point to `code_size`
+///     bytes, as EVMC requires of hosts.
+///
+unsafe extern "C" fn execute(
+    _vm: *mut EvmcVm,
+    host: *const EvmcHostInterface,
+    context: Context,
+    rev: EvmcRevision,
+    msg: *const EvmcMessage,
+    code: *const u8,
+    code_size: usize,
+) -> EvmcResult {
+    if rev != EVMC_FRONTIER {
+        return evmc_result(EVMC_REJECTED, 0, 0, Bytes::default());
+    }
+    let msg = &*msg;
+    let host = Host {
+        interface: &*host,
+        context,
+    };
+
+    let current_target = msg.recipient.bytes;
+    let message = Message {
+        caller: msg.sender.bytes,
+        target: (msg.kind != EVMC_CREATE).then_some(current_target),
+        current_target,
+        gas: U256::from(msg.gas.max(0) as u64),
+        value: from_bytes32(msg.value),
+        data: bytes_from_raw(msg.input_data, msg.input_size),
+        code_address: Some(msg.code_address.bytes),
+        code: bytes_from_raw(code, code_size),
+        depth: Uint::from(msg.depth.max(0) as u32),
+    };
+    let mut evm = new_evm(message, Environment::default());
+    set_account(&mut evm.env.state, current_target, Some(EMPTY_ACCOUNT()));
+
+    match run(&mut evm, &host) {
+        Ok(()) => evmc_result(
+            EVMC_SUCCESS,
+            to_i64(&evm.gas_left),
+            to_i64(&evm.refund_counter),
+            std::mem::take(&mut evm.output),
+        ),
+        Err(error) => evmc_result(status_code(error), 0, 0, Bytes::default()),
+    }
+}
+
+unsafe extern "C" fn destroy(vm: *mut EvmcVm) {
+    drop(Box::from_raw(vm));
+}
+
+unsafe extern "C" fn get_capabilities(_vm: *mut EvmcVm) -> u32 {
+    EVMC_CAPABILITY_EVM1
+}
+
+unsafe extern "C" fn set_option(
+    _vm: *mut EvmcVm,
+    _name: *const c_char,
+    _value: *const c_char,
+) -> EvmcSetOptionResult {
+    EVMC_SET_OPTION_INVALID_NAME
+}
+
+///
+///     Create an instance of the VM.
+///
+///     Returns
+///     -------
+///     vm : `struct evmc_vm *`
+///         The VM, to be released with its `destroy` function.
+///
+#[no_mangle]
+pub extern "C" fn evmc_create_execution_specs_rs() -> *mut EvmcVm {
+    let vm = EvmcVm {
+        abi_version: EVMC_ABI_VERSION,
+        name: c"execution-specs-rs".as_ptr(),
+        version: concat!(env!("CARGO_PKG_VERSION"), "\0").as_ptr() as *const c_char,
+        destroy: Some(destroy),
+        execute: Some(execute),
+        get_capabilities: Some(get_capabilities),
+        set_option: Some(set_option),
+    };
+    Box::into_raw(Box::new(vm))
+}
@@ -0,0 +1,279 @@
+//!
+//! # C Interface
+//!
+//! A minimal C ABI for driving the spec EVM from other languages, such as
+//! differential testers written in C or C++. Build it as a shared library
+//! with
+//!
+//! ```text
+//! cargo rustc --lib --release --features cdylib --crate-type cdylib
+//! ```
+//!
+//! and include `include/execution_specs.h`. A VM holds a state, and runs
+//! Frontier transactions against it one at a time:
+//!
+//! ```c
+//! EsVm *vm = es_vm_new();
+//! es_vm_load_prestate(vm, "{\"0xaa...\": {\"balance\": \"0x100000\"}}");
+//! if (es_vm_execute(vm, "{\"from\": \"0xaa...\", \"to\": \"0xcc...\", \"gas\": \"0x5208\"}") != 0) {
+//!     fprintf(stderr, "%s\n", es_vm_result(vm));
+//! }
+//! es_vm_free(vm);
+//! ```
+//!
+//! Transactions are JSON objects with the fields of a Frontier transaction,
+//! as in `eth_sendTransaction`: `nonce`, `gasPrice`, `gas`, `to`, `value` and
+//! `input` (or `data`). Missing fields default to zero, and a missing `to`
+//! creates a contract. The sender is `from` if given, and otherwise it is
+//! recovered from `v`, `r` and `s`.
+//!
+
+use std::{
+    ffi::{c_char, c_int, CStr, CString},
+    ptr,
+};
+
+use serde_json::{json, Value};
+
+use super::{
+    alloc::{json_str, json_uint, load_alloc},
+    dump::dump_state,
+    run::RunConfig,
+};
+use crate::ethereum::{
+    base_types::U256,
+    exceptions::EthereumException,
+    frontier::{
+        fork::{check_transaction, process_transaction, recover_sender},
+        fork_types::{Address, Transaction},
+        state::{get_account, state_root, State},
+        vm::{BlockEnvironment, TxEnvironment},
+    },
+    utils::hexadecimal::{hex, hex_to_bytes, hex_to_bytes20},
+};
+
+/// Returned when a call succeeded.
+pub const ES_OK: c_int = 0;
+/// Returned when an argument is not valid JSON of the expected shape.
+pub const ES_INVALID_INPUT: c_int = 1;
+/// Returned when the transaction can't be included in a block.
+pub const ES_INVALID_TRANSACTION: c_int = 2;
+
+/// A state and the block the transactions run in, along with the result of
+/// the last call.
+pub struct EsVm {
+    state: State,
+    block: BlockEnvironment,
+    result: CString,
+}
+
+impl EsVm {
+    fn set_result(&mut self, result: Value) {
+        self.result = CString::new(result.to_string()).expect("JSON has no NUL bytes");
+    }
+
+    fn fail(&mut self, code: c_int, error: String) -> c_int {
+        self.set_result(json!({ "error": error }));
+        code
+    }
+}
+
+fn parse_transaction(tx: &str) -> Result<(Transaction, Option<Address>), EthereumException> {
+    let value: Value = serde_json::from_str(tx)
+        .map_err(|e| EthereumException::JsonDecodeError(e.to_string()))?;
+    let uint = |field: &str| match value.get(field) {
+        Some(number) if !number.is_null() => json_uint(number),
+        _ => Ok(U256::from(0u8)),
+    };
+    let address = |field: &str| match value.get(field) {
+        Some(address) if !address.is_null() => hex_to_bytes20(json_str(address)?).map(Some),
+        _ => Ok(None),
+    };
+    let data = match value.get("input").or_else(|| value.get("data")) {
+        Some(data) => hex_to_bytes(json_str(data)?)?,
+        None => Default::default(),
+    };
+
+    let tx = Transaction {
+        nonce: uint("nonce")?,
+        gas_price: uint("gasPrice")?,
+        gas: uint("gas")?,
+        to: address("to")?,
+        value: uint("value")?,
+        data,
+        v: uint("v")?,
+        r: uint("r")?,
+        s: uint("s")?,
+    };
+    Ok((tx, address("from")?))
+}
+
+/// # Safety
+///
+/// `string` must be null or point to a NUL terminated string.
+unsafe fn read_str<'a>(string: *const c_char) -> Result<&'a str, String> {
+    if string.is_null() {
+        return Err("null argument".to_owned());
+    }
+    CStr::from_ptr(string)
+        .to_str()
+        .map_err(|_| "argument is not UTF-8".to_owned())
+}
+
+///
+///     Create a VM with an empty state, for blocks with the same environment
+///     as `evm run`.
+///
+///     Returns
+///     -------
+///     vm : `EsVm *`
+///         The VM, to be released with `es_vm_free`.
+///
+#[no_mangle]
+pub extern "C" fn es_vm_new() -> *mut EsVm {
+    let vm = EsVm {
+        state: State::default(),
+        block: RunConfig::default().block,
+        result: CString::default(),
+    };
+    Box::into_raw(Box::new(vm))
+}
+
+///
+///     Release a VM.
+///
+///     Safety
+///     ------
+///     `vm` must be null or come from `es_vm_new`, and not be used afterwards.
+///
+#[no_mangle]
+pub unsafe extern "C" fn es_vm_free(vm: *mut EsVm) {
+    if !vm.is_null() {
+        drop(Box::from_raw(vm));
+    }
+}
+
+///
+///     Replace the state of the VM with a geth style allocation.
+///
+///     Parameters
+///     ----------
+///     vm :
+///         The VM.
+///     alloc :
+///         The allocation, as JSON.
+///
+///     Returns
+///     -------
+///     status : `int`
+///         `ES_OK`, or `ES_INVALID_INPUT` with the reason in the result.
+///
+///     Safety
+///     ------
+///     `vm` must come from `es_vm_new`, and `alloc` must point to a NUL
+///     terminated string.
+///
+#[no_mangle]
+pub unsafe extern "C" fn es_vm_load_prestate(vm: *mut EsVm, alloc: *const c_char) -> c_int {
+    let vm = &mut *vm;
+    let state = read_str(alloc).and_then(|alloc| load_alloc(alloc).map_err(|e| format!("{e:?}")));
+    match state {
+        Ok(state) => {
+            vm.state = state;
+            vm.set_result(json!({ "stateRoot": hex(&state_root(&vm.state)) }));
+            ES_OK
+        }
+        Err(error) => vm.fail(ES_INVALID_INPUT, error),
+    }
+}
+
+///
+///     Run a transaction against the state of the VM, and keep the result.
+///
+///     Parameters
+///     ----------
+///     vm :
+///         The VM.
+///     tx :
+///         The transaction, as JSON.
+///
+///     Returns
+///     -------
+///     status : `int`
+///         `ES_OK`, or `ES_INVALID_INPUT` or `ES_INVALID_TRANSACTION` with
+///         the reason in the result. The state is only changed on success.
+///
+///     Safety
+///     ------
+///     `vm` must come from `es_vm_new`, and `tx` must point to a NUL terminated
+///     string.
+///
+#[no_mangle]
+pub unsafe extern "C" fn es_vm_execute(vm: *mut EsVm, tx: *const c_char) -> c_int {
+    let vm = &mut *vm;
+    let parsed = read_str(tx).and_then(|tx| parse_transaction(tx).map_err(|e| format!("{e:?}")));
+    let (tx, from) = match parsed {
+        Ok(parsed) => parsed,
+        Err(error) => return vm.fail(ES_INVALID_INPUT, error),
+    };
+    let sender = match from {
+        Some(from) => from,
+        None => match recover_sender(&tx) {
+            Ok(sender) => sender,
+            Err(_) => return vm.fail(ES_INVALID_TRANSACTION, "invalid signature".to_owned()),
+        },
+    };
+
+    let sender_account = get_account(&vm.state, &sender);
+    if let Err(reason) = check_transaction(&vm.block, &sender_account, &tx) {
+        return vm.fail(ES_INVALID_TRANSACTION, format!("{reason:?}"));
+    }
+    let tx_env = TxEnvironment {
+        origin: sender,
+        gas_price: tx.gas_price.clone(),
+    };
+    let (gas_used, logs) = process_transaction(&vm.block, &tx_env, &mut vm.state, &tx)
+        .expect("the transaction has been checked");
+
+    let logs: Vec<Value> = logs
+        .iter()
+        .map(|log| {
+            json!({
+                "address": hex(&log.address),
+                "topics": log.topics.iter().map(|topic| hex(topic)).collect::<Vec<_>>(),
+                "data": hex(&log.data),
+            })
+        })
+        .collect();
+    let result = json!({
+        "gasUsed": format!("{gas_used:#x}"),
+        "logs": logs,
+        "stateRoot": hex(&state_root(&vm.state)),
+        "postState": dump_state(&vm.state),
+    });
+    vm.set_result(result);
+    ES_OK
+}
+
+///
+///     The JSON result of the last call on the VM: the `stateRoot` after
+///     loading a pre-state; the `gasUsed`, `logs`, `stateRoot` and geth style
+///     `postState` after a transaction; or the `error` of a failed call.
+///
+///     Returns
+///     -------
+///     result : `const char *`
+///         The result, owned by the VM and valid until its next call. Empty
+///         before the first call.
+///
+///     Safety
+///     ------
+///     `vm` must be null or come from `es_vm_new`.
+///
+#[no_mangle]
+pub unsafe extern "C" fn es_vm_result(vm: *const EsVm) -> *const c_char {
+    match vm.as_ref() {
+        Some(vm) => vm.result.as_ptr(),
+        None => ptr::null(),
+    }
+}
@@ -0,0 +1,748 @@
+//!
+//! # Fixture Runner
+//!
+//! Runs the `GeneralStateTests` and `BlockchainTests` fixtures of
+//! ethereum/tests and execution-spec-tests against the spec, one test per
+//! fork (or network, for blockchain tests) at a time:
+//!
+//! ```text
+//! evm fixtures tests/fixtures/GeneralStateTests --results results.json
+//! ```
+//!
+//! Each outcome is recorded in a [ResultsDb], saved after every file, so an
+//! interrupted run resumes where it stopped: by default a test that passed
+//! and whose fixture is unchanged is skipped, [RunMode::OnlyFailed] retries
+//! only what failed last time and [RunMode::All] runs everything again.
+//!
+//! Forks the spec doesn't implement are reported as unsupported, not as
+//! failures.
+//!
+//! [run_fixtures_parallel] shares the files out among threads. Every test
+//! builds its own state and chain, and one that panics fails alone. The
+//! outcomes can be written as a JUnit report for CI with
+//! [RunSummary::junit_xml].
+//!
+//! [run_fixtures_with_coverage] also records the opcodes, precompiles and
+//! exceptions the tests went through in each fork, reported by
+//! [RunSummary::coverage_report].
+//!
+
+use std::{
+    collections::BTreeMap,
+    fmt::Write,
+    fs,
+    panic::{self, AssertUnwindSafe},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        mpsc,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use serde_json::{json, Value};
+
+use super::{
+    alloc::{json_str, json_uint, load_alloc},
+    coverage::{Coverage, CoverageTracer},
+    results::{fingerprint, ResultsDb, TestId, TestRecord},
+    state_test::apply_transaction,
+    t8n::parse_env,
+};
+use crate::{
+    ethereum::{
+        base_types::U256,
+        exceptions::EthereumException,
+        frontier::{
+            fork::{recover_sender, BlockChain},
+            fork_types::{keccak256, Transaction},
+            state::state_root,
+            vm::Evm,
+        },
+        rlp,
+        trace::Tracer,
+        transaction_signature::sign_transaction,
+        utils::hexadecimal::{hex, hex_to_bytes, hex_to_bytes20, hex_to_hash},
+    },
+    ethereum_spec_tools::{
+        block_rlp::decode_block,
+        chain::Chain,
+        forks::{ForkSchedule, HardFork},
+    },
+};
+
+/// The kinds of fixtures the runner knows.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FixtureKind {
+    State,
+    Blockchain,
+}
+
+/// What kind of fixture a test is, if the runner knows it.
+pub fn fixture_kind(test: &Value) -> Option<FixtureKind> {
+    if test.get("blocks").is_some() {
+        Some(FixtureKind::Blockchain)
+    } else if test.get("transaction").is_some() && test.get("post").is_some() {
+        Some(FixtureKind::State)
+    } else {
+        None
+    }
+}
+
+/// The forks a test is for: those of the `post` of a state test, or the
+/// network of a blockchain test.
+pub fn test_forks(test: &Value) -> Vec<String> {
+    match fixture_kind(test) {
+        Some(FixtureKind::State) => test["post"].as_object().map(|post| post.keys().cloned().collect()).unwrap_or_default(),
+        Some(FixtureKind::Blockchain) => test["network"].as_str().map(|network| vec![network.to_owned()]).unwrap_or_default(),
+        None => vec![],
+    }
+}
+
+/// The implementation of a fork, by the name fixtures give it.
+fn hard_fork(name: &str) -> Option<HardFork> {
+    HardFork::from_name(name).ok()
+}
+
+/// How a test went.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Status {
+    Passed,
+    Failed(String),
+    /// The spec doesn't implement the fork.
+    Unsupported,
+    /// It passed before and is unchanged, or didn't fail before.
+    Skipped,
+}
+
+///
+///     Run a test in one of its forks.
+///
+///     Parameters
+///     ----------
+///     test :
+///         A state or blockchain test.
+///     fork :
+///         One of [test_forks].
+///
+pub fn run_test(test: &Value, fork: &str) -> Status {
+    run_test_traced(test, fork, &mut None)
+}
+
+///
+///     Run a test in one of its forks, like [run_test], recording what its
+///     executions went through.
+///
+///     Parameters
+///     ----------
+///     test :
+///         A state or blockchain test.
+///     fork :
+///         One of [test_forks].
+///
+///     Returns
+///     -------
+///     outcome : `(Status, Coverage)`
+///         How the test went, and its coverage, empty unless the fork is
+///         supported.
+///
+pub fn run_test_with_coverage(test: &Value, fork: &str) -> (Status, Coverage) {
+    let coverage_tracer = CoverageTracer::new();
+    let coverage = coverage_tracer.coverage();
+    let status = run_test_traced(test, fork, &mut Some(Box::new(coverage_tracer)));
+    let coverage = coverage.borrow().clone();
+    (status, coverage)
+}
+
+///
+///     Run a test in one of its forks, like [run_test], with a tracer
+///     following its executions.
+///
+///     Parameters
+///     ----------
+///     test :
+///         A state or blockchain test.
+///     fork :
+///         One of [test_forks].
+///     tracer :
+///         The tracer, left untouched unless the fork is supported.
+///
+pub fn run_test_with_tracer(test: &Value, fork: &str, tracer: Box<dyn Tracer<Evm>>) -> Status {
+    run_test_traced(test, fork, &mut Some(tracer))
+}
+
+fn run_test_traced(test: &Value, fork: &str, tracer: &mut Option<Box<dyn Tracer<Evm>>>) -> Status {
+    let (Some(kind), Some(hard_fork)) = (fixture_kind(test), hard_fork(fork)) else {
+        return Status::Unsupported;
+    };
+    let result = match kind {
+        FixtureKind::State => run_state_test(test, hard_fork, fork, tracer),
+        FixtureKind::Blockchain => run_blockchain_test(test, hard_fork, tracer),
+    };
+    match result {
+        Ok(()) => Status::Passed,
+        Err(message) => Status::Failed(message),
+    }
+}
+
+fn debug(exception: EthereumException) -> String {
+    format!("{exception:?}")
+}
+
+/// Run every combination of the transaction of a state test that the
+/// fixture has a result for in `fork`.
+fn run_state_test(
+    test: &Value,
+    fork: HardFork,
+    fork_name: &str,
+    tracer: &mut Option<Box<dyn Tracer<Evm>>>,
+) -> Result<(), String> {
+    let pre = load_alloc(&test["pre"].to_string()).map_err(debug)?;
+    let env = parse_env(&test["env"]).map_err(debug)?;
+    let tx = &test["transaction"];
+    let pick = |name: &str, index: &Value| -> Result<&Value, String> {
+        let index = index.as_u64().ok_or_else(|| format!("bad `{name}` index"))?;
+        tx[name].get(index as usize).ok_or_else(|| format!("no `{name}` {index}"))
+    };
+    let to = match json_str(&tx["to"]).map_err(debug)? {
+        "" => None,
+        to => Some(hex_to_bytes20(to).map_err(debug)?),
+    };
+    let secret_key = hex_to_hash(json_str(&tx["secretKey"]).map_err(debug)?).map_err(debug)?;
+
+    let results = test["post"][fork_name].as_array().ok_or("the results must be a list")?;
+    for result in results {
+        let indexes = &result["indexes"];
+        let label = format!("data {} gas {} value {}", indexes["data"], indexes["gas"], indexes["value"]);
+        let unsigned = Transaction {
+            nonce: json_uint(&tx["nonce"]).map_err(debug)?,
+            gas_price: json_uint(&tx["gasPrice"]).map_err(debug)?,
+            gas: json_uint(pick("gasLimit", &indexes["gas"])?).map_err(debug)?,
+            to,
+            value: json_uint(pick("value", &indexes["value"])?).map_err(debug)?,
+            data: hex_to_bytes(json_str(pick("data", &indexes["data"])?).map_err(debug)?).map_err(debug)?,
+            v: U256::default(),
+            r: U256::default(),
+            s: U256::default(),
+        };
+        let signed = sign_transaction(&unsigned, &secret_key, None).map_err(debug)?;
+        let sender = recover_sender(&signed).map_err(debug)?;
+
+        let mut state = pre.clone();
+        let (exception, logs) =
+            apply_transaction(fork, &env, &mut state, sender, &signed, tracer).map_err(|e| format!("{label}: {e:?}"))?;
+        match (&result["expectException"], exception) {
+            (Value::Null, Some(exception)) => return Err(format!("{label}: unexpected {exception}")),
+            (Value::String(expected), None) => {
+                return Err(format!("{label}: expected {expected}, but the transaction is valid"))
+            }
+            _ => {}
+        }
+        let root = hex(&state_root(&state));
+        if result["hash"] != root {
+            return Err(format!("{label}: state root {root} instead of {}", result["hash"]));
+        }
+        let logs_hash = hex(&keccak256(&rlp::encode(&logs[..])));
+        if result["logs"] != logs_hash {
+            return Err(format!("{label}: logs hash {logs_hash} instead of {}", result["logs"]));
+        }
+    }
+    Ok(())
+}
+
+/// Apply the blocks of a blockchain test on its genesis, checking that the
+/// invalid ones are rejected, then compare the head and the post-state.
+fn run_blockchain_test(
+    test: &Value,
+    fork: HardFork,
+    tracer: &mut Option<Box<dyn Tracer<Evm>>>,
+) -> Result<(), String> {
+    let pre = load_alloc(&test["pre"].to_string()).map_err(debug)?;
+    let genesis = decode_block(&hex_to_bytes(json_str(&test["genesisRLP"]).map_err(debug)?).map_err(debug)?, fork)
+        .map_err(|e| format!("genesis: {e:?}"))?;
+    if state_root(&pre) != genesis.header.state_root {
+        return Err("the pre-state doesn't have the root of the genesis".to_owned());
+    }
+    let schedule = match fork {
+        HardFork::Frontier => ForkSchedule::frontier(),
+    };
+    let blockchain = BlockChain {
+        blocks: vec![genesis],
+        state: pre,
+        chain_id: 1,
+    };
+    let mut chain = Chain::new(blockchain, schedule).map_err(debug)?;
+
+    let blocks = test["blocks"].as_array().ok_or("the blocks must be a list")?;
+    for (index, block) in blocks.iter().enumerate() {
+        let invalid = block.get("expectException").is_some();
+        let decoded = json_str(&block["rlp"]).and_then(hex_to_bytes).and_then(|rlp| decode_block(&rlp, fork));
+        let result = decoded.and_then(|decoded| {
+            // A rejected block may have changed the state before failing.
+            let before = invalid.then(|| chain.clone());
+            chain.apply_block_traced(decoded, tracer).inspect_err(|_| {
+                if let Some(before) = before {
+                    chain = before;
+                }
+            })
+        });
+        match (result, invalid) {
+            (Ok(()), true) => return Err(format!("block {index} is valid, but expected {}", block["expectException"])),
+            (Err(exception), false) => return Err(format!("block {index} is invalid: {exception:?}")),
+            _ => {}
+        }
+    }
+
+    let head = hex(&rlp::rlp_hash(&chain.blocks().last().unwrap().header));
+    if let Some(expected) = test.get("lastblockhash").filter(|&expected| *expected != head) {
+        return Err(format!("head {head} instead of {expected}"));
+    }
+    let root = hex(&state_root(chain.state()));
+    let expected = match (test.get("postState"), test.get("postStateHash")) {
+        (Some(post), _) => Some(json!(hex(&state_root(&load_alloc(&post.to_string()).map_err(debug)?)))),
+        (None, hash) => hash.cloned(),
+    };
+    match expected {
+        Some(expected) if expected != root => Err(format!("state root {root} instead of {expected}")),
+        _ => Ok(()),
+    }
+}
+
+/// Which tests a run goes through.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RunMode {
+    /// Every test.
+    All,
+    /// Every test but those that passed and are unchanged since.
+    #[default]
+    Changed,
+    /// Only the tests that failed last time.
+    OnlyFailed,
+}
+
+impl RunMode {
+    fn runs(self, db: &ResultsDb, id: &TestId, fingerprint: &[u8; 32]) -> bool {
+        match self {
+            RunMode::All => true,
+            RunMode::Changed => !db.passed(id, fingerprint),
+            RunMode::OnlyFailed => db.failed(id),
+        }
+    }
+}
+
+/// A test of a run and how it went.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TestOutcome {
+    pub id: TestId,
+    pub status: Status,
+    /// How long it took to run.
+    pub duration: Duration,
+}
+
+/// The outcomes of a run.
+#[derive(Clone, Debug, Default)]
+pub struct RunSummary {
+    pub outcomes: Vec<TestOutcome>,
+    /// What the tests went through, by fork, if the run recorded it.
+    pub coverage: BTreeMap<String, Coverage>,
+}
+
+impl RunSummary {
+    fn count(&self, status: impl Fn(&Status) -> bool) -> usize {
+        self.outcomes.iter().filter(|outcome| status(&outcome.status)).count()
+    }
+
+    pub fn passed(&self) -> usize {
+        self.count(|status| *status == Status::Passed)
+    }
+
+    pub fn failed(&self) -> usize {
+        self.count(|status| matches!(status, Status::Failed(_)))
+    }
+
+    pub fn skipped(&self) -> usize {
+        self.count(|status| *status == Status::Skipped)
+    }
+
+    pub fn unsupported(&self) -> usize {
+        self.count(|status| *status == Status::Unsupported)
+    }
+
+    ///
+    ///     The outcomes as a JUnit XML report, with a test suite per file
+    ///     and a test case per test and fork, named `test[fork]`. Skipped
+    ///     and unsupported tests are reported as skipped.
+    ///
+    pub fn junit_xml(&self) -> String {
+        let seconds = |duration: Duration| format!("{:.3}", duration.as_secs_f64());
+        let mut files: Vec<(&str, Vec<&TestOutcome>)> = vec![];
+        for outcome in &self.outcomes {
+            match files.last_mut() {
+                Some((file, outcomes)) if *file == outcome.id.file => outcomes.push(outcome),
+                _ => files.push((&outcome.id.file, vec![outcome])),
+            }
+        }
+
+        let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        let total: Duration = self.outcomes.iter().map(|outcome| outcome.duration).sum();
+        let _ = writeln!(
+            xml,
+            "<testsuites name=\"{}\" tests=\"{}\" failures=\"{}\" skipped=\"{}\" time=\"{}\">",
+            super::state_test::FILLING_TOOL,
+            self.outcomes.len(),
+            self.failed(),
+            self.skipped() + self.unsupported(),
+            seconds(total)
+        );
+        for (file, outcomes) in files {
+            let summary = RunSummary {
+                outcomes: outcomes.iter().map(|&outcome| outcome.clone()).collect(),
+                ..Default::default()
+            };
+            let time: Duration = outcomes.iter().map(|outcome| outcome.duration).sum();
+            let _ = writeln!(
+                xml,
+                "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{}\" skipped=\"{}\" time=\"{}\">",
+                xml_escape(file),
+                outcomes.len(),
+                summary.failed(),
+                summary.skipped() + summary.unsupported(),
+                seconds(time)
+            );
+            for outcome in outcomes {
+                let name = format!("{}[{}]", outcome.id.name, outcome.id.fork);
+                let _ = write!(
+                    xml,
+                    "    <testcase classname=\"{}\" name=\"{}\" time=\"{}\"",
+                    xml_escape(file),
+                    xml_escape(&name),
+                    seconds(outcome.duration)
+                );
+                let _ = match &outcome.status {
+                    Status::Passed => writeln!(xml, "/>"),
+                    Status::Failed(message) => {
+                        let message = xml_escape(message);
+                        writeln!(xml, ">\n      <failure message=\"{message}\">{message}</failure>\n    </testcase>")
+                    }
+                    Status::Skipped => writeln!(xml, ">\n      <skipped message=\"not selected\"/>\n    </testcase>"),
+                    Status::Unsupported => writeln!(xml, ">\n      <skipped message=\"unsupported fork\"/>\n    </testcase>"),
+                };
+            }
+            xml += "  </testsuite>\n";
+        }
+        xml += "</testsuites>\n";
+        xml
+    }
+
+    ///
+    ///     The coverage of the run as JSON, with the report of
+    ///     `Coverage::report` for each fork the tests ran in.
+    ///
+    pub fn coverage_report(&self) -> Value {
+        let reports = self
+            .coverage
+            .iter()
+            .filter_map(|(name, coverage)| Some((name.clone(), coverage.report(hard_fork(name)?))));
+        Value::Object(reports.collect())
+    }
+
+    /// The tests that failed, and why.
+    pub fn failures(&self) -> impl Iterator<Item = (&TestId, &str)> {
+        self.outcomes.iter().filter_map(|outcome| match &outcome.status {
+            Status::Failed(message) => Some((&outcome.id, message.as_str())),
+            _ => None,
+        })
+    }
+}
+
+fn xml_escape(text: &str) -> String {
+    text.chars()
+        .map(|c| match c {
+            '&' => "&amp;".to_owned(),
+            '<' => "&lt;".to_owned(),
+            '>' => "&gt;".to_owned(),
+            '"' => "&quot;".to_owned(),
+            '\'' => "&apos;".to_owned(),
+            c => c.to_string(),
+        })
+        .collect()
+}
+
+///
+///     The fixture files under some paths.
+///
+///     Parameters
+///     ----------
+///     paths :
+///         Files, taken as they are, and directories, searched recursively
+///         for `.json` files.
+///
+///     Returns
+///     -------
+///     files : `Vec<PathBuf>`
+///         The files, in the order given and sorted within directories.
+///
+pub fn fixture_files(paths: &[PathBuf]) -> Result<Vec<PathBuf>, EthereumException> {
+    let mut files = vec![];
+    for path in paths {
+        if !path.is_dir() {
+            files.push(path.clone());
+            continue;
+        }
+        let mut found = vec![];
+        let mut directories = vec![path.clone()];
+        while let Some(directory) = directories.pop() {
+            let entries = fs::read_dir(&directory)
+                .map_err(|e| EthereumException::FileNotFound(format!("{}: {e}", directory.display())))?;
+            for entry in entries {
+                let path = entry.map_err(|e| EthereumException::FileNotFound(e.to_string()))?.path();
+                if path.is_dir() {
+                    directories.push(path);
+                } else if path.extension().is_some_and(|extension| extension == "json") {
+                    found.push(path);
+                }
+            }
+        }
+        found.sort();
+        files.extend(found);
+    }
+    Ok(files)
+}
+
+///
+///     Run the tests of a fixture file, recording their outcomes.
+///
+///     Parameters
+///     ----------
+///     path :
+///         The file, an object of tests by name.
+///     db :
+///         The results of past runs, updated with those of this one. It
+///         isn't saved.
+///     mode :
+///         Which tests to run.
+///
+///     Returns
+///     -------
+///     outcomes : `Vec<TestOutcome>`
+///         How each test went in each of its forks. A file that can't be
+///         read fails as a whole, with an empty name and fork.
+///
+pub fn run_file(path: &Path, db: &mut ResultsDb, mode: RunMode) -> Vec<TestOutcome> {
+    let results = test_file(path, db, mode, None);
+    record(db, &results);
+    results.into_iter().map(|(outcome, _)| outcome).collect()
+}
+
+/// Run the tests of a file, with what to record of each, adding to
+/// `coverage` if given.
+fn test_file(
+    path: &Path,
+    db: &ResultsDb,
+    mode: RunMode,
+    mut coverage: Option<&mut BTreeMap<String, Coverage>>,
+) -> Vec<(TestOutcome, Option<TestRecord>)> {
+    let file = path.display().to_string();
+    let _span = tracing::info_span!("file", path = file).entered();
+    let tests = fs::read_to_string(path)
+        .map_err(|e| e.to_string())
+        .and_then(|text| serde_json::from_str::<Value>(&text).map_err(|e| e.to_string()));
+    let tests = match tests {
+        Ok(Value::Object(tests)) => tests,
+        Ok(_) => return vec![(unreadable(file, "not an object of tests".to_owned()), None)],
+        Err(message) => return vec![(unreadable(file, message), None)],
+    };
+
+    let mut results = vec![];
+    for (name, test) in &tests {
+        let fingerprint = fingerprint(test);
+        for fork in test_forks(test) {
+            let id = TestId {
+                file: file.clone(),
+                name: name.clone(),
+                fork,
+            };
+            let start = Instant::now();
+            let status = match (mode.runs(db, &id, &fingerprint), coverage.as_deref_mut()) {
+                (true, Some(coverage)) => {
+                    let mut covered = Coverage::default();
+                    let status = run_isolated(test, &id.fork, Some(&mut covered));
+                    if status != Status::Unsupported {
+                        coverage.entry(id.fork.clone()).or_default().merge(&covered);
+                    }
+                    status
+                }
+                (true, None) => run_isolated(test, &id.fork, None),
+                (false, _) => Status::Skipped,
+            };
+            let duration = start.elapsed();
+            let record = match &status {
+                Status::Passed => Some(TestRecord { fingerprint, passed: true, message: None }),
+                Status::Failed(message) => {
+                    tracing::warn!(test = id.name, fork = id.fork, reason = message, "failed");
+                    Some(TestRecord { fingerprint, passed: false, message: Some(message.clone()) })
+                }
+                Status::Unsupported | Status::Skipped => None,
+            };
+            tracing::debug!(test = id.name, fork = id.fork, status = ?status, "ran");
+            results.push((TestOutcome { id, status, duration }, record));
+        }
+    }
+    results
+}
+
+/// Run a test, failing it rather than the run if it panics. The coverage
+/// of a test that panics is lost.
+fn run_isolated(test: &Value, fork: &str, coverage: Option<&mut Coverage>) -> Status {
+    let run = || match coverage {
+        Some(coverage) => {
+            let (status, covered) = run_test_with_coverage(test, fork);
+            *coverage = covered;
+            status
+        }
+        None => run_test(test, fork),
+    };
+    panic::catch_unwind(AssertUnwindSafe(run)).unwrap_or_else(|payload| {
+        let message = match (payload.downcast_ref::<&str>(), payload.downcast_ref::<String>()) {
+            (Some(message), _) => message.to_string(),
+            (_, Some(message)) => message.clone(),
+            _ => "unknown".to_owned(),
+        };
+        Status::Failed(format!("panicked: {message}"))
+    })
+}
+
+fn record(db: &mut ResultsDb, results: &[(TestOutcome, Option<TestRecord>)]) {
+    for (outcome, record) in results {
+        if let Some(record) = record {
+            db.record(&outcome.id, record.clone());
+        }
+    }
+}
+
+fn unreadable(file: String, message: String) -> TestOutcome {
+    TestOutcome {
+        id: TestId {
+            file,
+            name: String::new(),
+            fork: String::new(),
+        },
+        status: Status::Failed(message),
+        duration: Duration::ZERO,
+    }
+}
+
+///
+///     Run the fixtures under some paths, saving the results database
+///     after each file so that an interrupted run can be resumed.
+///
+///     Parameters
+///     ----------
+///     paths :
+///         Fixture files and directories of them, as for [fixture_files].
+///     db :
+///         The results of past runs, updated with those of this one.
+///     mode :
+///         Which tests to run.
+///
+pub fn run_fixtures(paths: &[PathBuf], db: &mut ResultsDb, mode: RunMode) -> Result<RunSummary, EthereumException> {
+    run_fixtures_parallel(paths, db, mode, 1)
+}
+
+///
+///     Run the fixtures under some paths like [run_fixtures], sharing the
+///     files out among worker threads.
+///
+///     Which tests run is decided from the results as they were before the
+///     run. The outcomes are in the order of the files, whichever thread ran
+///     them.
+///
+///     Parameters
+///     ----------
+///     threads :
+///         How many files to run at once, at least one.
+///
+pub fn run_fixtures_parallel(
+    paths: &[PathBuf],
+    db: &mut ResultsDb,
+    mode: RunMode,
+    threads: usize,
+) -> Result<RunSummary, EthereumException> {
+    run_fixtures_recording(paths, db, mode, threads, false)
+}
+
+///
+///     Run the fixtures under some paths like [run_fixtures_parallel],
+///     recording the coverage of the tests that run in the summary.
+///
+pub fn run_fixtures_with_coverage(
+    paths: &[PathBuf],
+    db: &mut ResultsDb,
+    mode: RunMode,
+    threads: usize,
+) -> Result<RunSummary, EthereumException> {
+    run_fixtures_recording(paths, db, mode, threads, true)
+}
+
+fn run_fixtures_recording(
+    paths: &[PathBuf],
+    db: &mut ResultsDb,
+    mode: RunMode,
+    threads: usize,
+    record_coverage: bool,
+) -> Result<RunSummary, EthereumException> {
+    let files = fixture_files(paths)?;
+    let previous = db.clone();
+    let next = AtomicUsize::new(0);
+    let mut by_file = vec![vec![]; files.len()];
+    let coverage = thread::scope(|scope| {
+        let (sender, receiver) = mpsc::channel();
+        let workers: Vec<_> = (0..threads.max(1))
+            .map(|_| {
+                let (sender, files, previous, next) = (sender.clone(), &files, &previous, &next);
+                scope.spawn(move || {
+                    let mut coverage = record_coverage.then(BTreeMap::new);
+                    loop {
+                        let index = next.fetch_add(1, Ordering::Relaxed);
+                        let Some(path) = files.get(index) else {
+                            break;
+                        };
+                        if sender.send((index, test_file(path, previous, mode, coverage.as_mut()))).is_err() {
+                            break;
+                        }
+                    }
+                    coverage.unwrap_or_default()
+                })
+            })
+            .collect();
+        drop(sender);
+        // Checkpoint as each file finishes. On an error the receiver is
+        // dropped, which stops the workers after their current file.
+        for (index, results) in receiver {
+            record(db, &results);
+            db.save()?;
+            by_file[index] = results;
+        }
+        let mut coverage: BTreeMap<String, Coverage> = BTreeMap::new();
+        for worker in workers {
+            for (fork, covered) in worker.join().expect("the tests' panics are caught") {
+                coverage.entry(fork).or_default().merge(&covered);
+            }
+        }
+        Ok::<_, EthereumException>(coverage)
+    })?;
+
+    let summary = RunSummary {
+        outcomes: by_file.into_iter().flatten().map(|(outcome, _)| outcome).collect(),
+        coverage,
+    };
+    tracing::info!(
+        passed = summary.passed(),
+        failed = summary.failed(),
+        skipped = summary.skipped(),
+        unsupported = summary.unsupported(),
+        "ran fixtures"
+    );
+    Ok(summary)
+}
@@ -0,0 +1,289 @@
+//!
+//! # Gas Profile
+//!
+//! A [Tracer] that adds up the gas used and the instructions executed, per
+//! opcode and per called contract, over any number of transactions.
+//!
+//! Gas is attributed exclusively: the cost of a `CALL` or `CREATE` is what
+//! the caller loses to it, without the gas the callee uses, which goes to the
+//! callee's own instructions. A contract is also credited the gas used by its
+//! calls including their callees, as `inclusive_gas`. Contracts are named by
+//! the address of their code, and the intrinsic gas of transactions isn't
+//! part of the profile.
+//!
+//! The profiler is moved into the execution, so the profile is read through
+//! the handle [GasProfiler::profile] returns. To profile a whole block, pass
+//! the profiler to `apply_body_traced`.
+//!
+//! ```
+//! # use execution_specs_rs::ethereum::frontier::{state::State, vm::Evm};
+//! # use execution_specs_rs::ethereum_spec_tools::evm_tools::gas_profile::GasProfiler;
+//! let profiler = GasProfiler::new();
+//! let profile = profiler.profile();
+//! let mut state = State::default();
+//! Evm::call()
+//!     .to([0x10; 20])
+//!     .gas(100_000u32.into())
+//!     .tracer(Box::new(profiler))
+//!     .run(&mut state)
+//!     .unwrap();
+//! println!("{}", profile.borrow().to_json());
+//! ```
+//!
+
+use std::{cell::RefCell, collections::BTreeMap, fmt::Write, rc::Rc};
+
+use serde_json::{json, Value};
+
+use crate::ethereum::{
+    base_types::{Uint, U256},
+    frontier::{
+        fork_types::Address,
+        vm::{instructions::Ops, Evm},
+    },
+    trace::{TraceEvent, Tracer},
+    utils::hexadecimal::hex,
+};
+
+/// The executions of one opcode.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct OpcodeProfile {
+    /// Times the opcode ran.
+    pub count: u64,
+    /// Gas the opcode used, without that of the frames it started.
+    pub gas: U256,
+}
+
+/// The calls to one contract.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CalleeProfile {
+    /// Frames that ran the contract's code, the top level ones included.
+    pub calls: u64,
+    /// Instructions the contract's code executed.
+    pub instructions: u64,
+    /// Gas used by the contract's instructions.
+    pub gas: U256,
+    /// Gas used by the calls to the contract, including their callees.
+    pub inclusive_gas: U256,
+}
+
+/// Gas and instruction counts, per opcode and per contract.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct GasProfile {
+    pub opcodes: BTreeMap<u8, OpcodeProfile>,
+    pub callees: BTreeMap<Address, CalleeProfile>,
+}
+
+fn op_name(op: u8) -> String {
+    match Ops::try_from(op) {
+        Ok(op) => op.name().to_owned(),
+        Err(_) => format!("opcode {} not defined", hex(&[op])),
+    }
+}
+
+impl GasProfile {
+    ///
+    ///     Serialize the profile as JSON, with gas in hex like EIP-3155
+    ///     traces.
+    ///
+    pub fn to_json(&self) -> Value {
+        let opcodes: Vec<Value> = self
+            .opcodes
+            .iter()
+            .map(|(op, profile)| {
+                json!({
+                    "op": op,
+                    "opName": op_name(*op),
+                    "count": profile.count,
+                    "gas": format!("{:#x}", profile.gas),
+                })
+            })
+            .collect();
+        let callees: Vec<Value> = self
+            .callees
+            .iter()
+            .map(|(address, profile)| {
+                json!({
+                    "address": hex(address),
+                    "calls": profile.calls,
+                    "instructions": profile.instructions,
+                    "gas": format!("{:#x}", profile.gas),
+                    "inclusiveGas": format!("{:#x}", profile.inclusive_gas),
+                })
+            })
+            .collect();
+        json!({ "opcodes": opcodes, "callees": callees })
+    }
+
+    ///
+    ///     The per opcode profile as CSV, with a header line.
+    ///
+    pub fn opcodes_csv(&self) -> String {
+        let mut csv = String::from("op,op_name,count,gas\n");
+        for (op, profile) in &self.opcodes {
+            writeln!(csv, "{op},{},{},{}", op_name(*op), profile.count, profile.gas).unwrap();
+        }
+        csv
+    }
+
+    ///
+    ///     The per contract profile as CSV, with a header line.
+    ///
+    pub fn callees_csv(&self) -> String {
+        let mut csv = String::from("address,calls,instructions,gas,inclusive_gas\n");
+        for (address, profile) in &self.callees {
+            writeln!(
+                csv,
+                "{},{},{},{},{}",
+                hex(address),
+                profile.calls,
+                profile.instructions,
+                profile.gas,
+                profile.inclusive_gas
+            )
+            .unwrap();
+        }
+        csv
+    }
+
+    fn add_op(&mut self, address: Address, op: u8, gas: U256) {
+        let opcode = self.opcodes.entry(op).or_default();
+        opcode.gas += &gas;
+        self.callees.entry(address).or_default().gas += gas;
+    }
+}
+
+fn gas_spent(before: &U256, after: &U256) -> U256 {
+    if after < before {
+        before - after
+    } else {
+        U256::default()
+    }
+}
+
+/// The opcode a frame is running.
+struct PendingOp {
+    op: u8,
+    gas_left: U256,
+    /// Gas used by the frames the opcode started.
+    children: U256,
+}
+
+/// A frame that is running.
+struct Frame {
+    depth: Uint,
+    address: Address,
+    start_gas: U256,
+    gas_left: U256,
+    pending: Option<PendingOp>,
+    precompile: bool,
+}
+
+/// A [Tracer] filling a [GasProfile].
+#[derive(Default)]
+pub struct GasProfiler {
+    profile: Rc<RefCell<GasProfile>>,
+    frames: Vec<Frame>,
+}
+
+impl GasProfiler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The profile being filled, to read once the tracer has been moved
+    /// into an execution.
+    pub fn profile(&self) -> Rc<RefCell<GasProfile>> {
+        self.profile.clone()
+    }
+
+    fn close_frame(&mut self) {
+        let frame = self.frames.pop().unwrap();
+        let used = gas_spent(&frame.start_gas, &frame.gas_left);
+        self.profile.borrow_mut().callees.entry(frame.address).or_default().inclusive_gas += &used;
+        if let Some(op) = self.frames.last_mut().and_then(|parent| parent.pending.as_mut()) {
+            op.children += used;
+        }
+    }
+
+    fn finish_op(&mut self, gas_left: &U256) {
+        let frame = self.frames.last_mut().unwrap();
+        if let Some(op) = frame.pending.take() {
+            let gas = gas_spent(&gas_spent(&op.gas_left, gas_left), &op.children);
+            self.profile.borrow_mut().add_op(frame.address, op.op, gas);
+        }
+    }
+
+    fn start_op(&mut self, evm: &Evm, op: u8) {
+        let frame = self.frames.last_mut().unwrap();
+        frame.pending = Some(PendingOp {
+            op,
+            gas_left: evm.gas_left.clone(),
+            children: U256::default(),
+        });
+        let mut profile = self.profile.borrow_mut();
+        profile.opcodes.entry(op).or_default().count += 1;
+        profile.callees.entry(frame.address).or_default().instructions += 1;
+    }
+}
+
+impl Tracer<Evm> for GasProfiler {
+    fn trace(&mut self, evm: &Evm, event: &TraceEvent) {
+        if let TraceEvent::TransactionEnd { .. } = event {
+            if let Some(frame) = self.frames.first_mut() {
+                frame.gas_left = evm.gas_left.clone();
+            }
+            while !self.frames.is_empty() {
+                self.close_frame();
+            }
+            return;
+        }
+
+        let depth = &evm.message.depth;
+        while self.frames.last().is_some_and(|frame| frame.depth > *depth) {
+            self.close_frame();
+        }
+        if self.frames.last().is_none_or(|frame| frame.depth < *depth) {
+            let address = evm.message.code_address.unwrap_or(evm.message.current_target);
+            self.profile.borrow_mut().callees.entry(address).or_default().calls += 1;
+            self.frames.push(Frame {
+                depth: depth.clone(),
+                address,
+                start_gas: evm.gas_left.clone(),
+                gas_left: evm.gas_left.clone(),
+                pending: None,
+                precompile: false,
+            });
+        }
+        self.frames.last_mut().unwrap().gas_left = evm.gas_left.clone();
+
+        match event {
+            TraceEvent::OpStart { op } => {
+                self.finish_op(&evm.gas_left);
+                self.start_op(evm, *op);
+            }
+            TraceEvent::OpEnd => self.finish_op(&evm.gas_left),
+            TraceEvent::PrecompileStart { .. } => self.frames.last_mut().unwrap().precompile = true,
+            TraceEvent::OpException { .. } => {
+                // The frame loses all of its gas once the event is reported.
+                let frame = self.frames.last_mut().unwrap();
+                if frame.pending.is_none() && !frame.precompile {
+                    // The opcode failed before it started, e.g. because it
+                    // is undefined.
+                    if let Some(&op) = evm.code.get(evm.pc) {
+                        self.start_op(evm, op);
+                    }
+                }
+                let zero = U256::default();
+                self.finish_op(&zero);
+                self.frames.last_mut().unwrap().gas_left = zero;
+            }
+            TraceEvent::MessageStart { .. }
+            | TraceEvent::MessageEnd { .. }
+            | TraceEvent::EvmStop { .. }
+            | TraceEvent::PrecompileEnd
+            | TraceEvent::GasAndRefund { .. } => {}
+            TraceEvent::TransactionEnd { .. } => unreachable!(),
+        }
+    }
+}
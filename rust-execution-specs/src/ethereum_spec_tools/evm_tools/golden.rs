@@ -0,0 +1,167 @@
+//!
+//! # Golden Traces
+//!
+//! A regression corpus: state tests whose EIP-3155 traces are kept next to
+//! them, so that a change to the interpreter or the gas engine that alters
+//! an execution in any way fails, even if the post-state happens to be the
+//! same.
+//!
+//! A corpus is a directory of fixtures in the format of `GeneralStateTests`,
+//! each test with the post-state root of every fork it was recorded in.
+//! Beside each fixture, a `<test>.<fork>.jsonl` file holds the traces of the
+//! combinations of a test in a fork, one after another:
+//!
+//! ```text
+//! tests/golden/transfer.json
+//! tests/golden/transfer.Frontier.jsonl
+//! ```
+//!
+//! The post-state roots are the reference and are never rewritten. A
+//! trace is only rewritten when asked to, and only if the test reaches its
+//! post-state roots, so a trace of a wrong execution can't be recorded.
+//! Tests in forks the spec doesn't implement yet are reported as
+//! unsupported, and checked once the fork lands.
+//!
+
+use std::{
+    cell::RefCell,
+    fs,
+    io::{self, Write},
+    path::Path,
+    rc::Rc,
+};
+
+use serde_json::Value;
+
+use super::{
+    eip3155::Eip3155Tracer,
+    fixtures::{fixture_files, run_test_with_tracer, test_forks, Status},
+    results::TestId,
+};
+use crate::ethereum::exceptions::EthereumException;
+
+/// A writer whose contents can still be read after it was handed to the
+/// tracer.
+#[derive(Clone, Default)]
+struct SharedBuffer(Rc<RefCell<Vec<u8>>>);
+
+impl Write for SharedBuffer {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.borrow_mut().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// How a test of the corpus compared with its golden trace.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GoldenOutcome {
+    pub id: TestId,
+    /// `Passed` if the trace and post-state roots match, or the trace was
+    /// rewritten.
+    pub status: Status,
+}
+
+///
+///     The first line two traces differ at, as a message.
+///
+fn trace_difference(expected: &str, actual: &str) -> Option<String> {
+    let mut expected_lines = expected.lines();
+    let mut actual_lines = actual.lines();
+    for line in 1.. {
+        match (expected_lines.next(), actual_lines.next()) {
+            (None, None) => return None,
+            (expected, actual) if expected == actual => {}
+            (expected, actual) => {
+                return Some(format!(
+                    "trace differs at line {line}:\n  expected: {}\n  actual:   {}",
+                    expected.unwrap_or("(end of trace)"),
+                    actual.unwrap_or("(end of trace)"),
+                ))
+            }
+        }
+    }
+    unreachable!()
+}
+
+///
+///     Run a test in one of its forks, comparing its trace with the golden
+///     one.
+///
+fn check_test(test: &Value, fork: &str, trace_path: &Path, update: bool) -> Status {
+    let buffer = SharedBuffer::default();
+    let status = run_test_with_tracer(test, fork, Box::new(Eip3155Tracer::new(Box::new(buffer.clone()))));
+    if status != Status::Passed {
+        return status;
+    }
+    let actual = String::from_utf8(buffer.0.take()).expect("traces are JSON");
+
+    let expected = fs::read_to_string(trace_path).ok();
+    if expected.as_deref() == Some(&actual[..]) {
+        return Status::Passed;
+    }
+    if update {
+        return match fs::write(trace_path, &actual) {
+            Ok(()) => Status::Passed,
+            Err(error) => Status::Failed(format!("{}: {error}", trace_path.display())),
+        };
+    }
+    match expected {
+        None => Status::Failed(format!("no golden trace {}", trace_path.display())),
+        Some(expected) => Status::Failed(trace_difference(&expected, &actual).unwrap_or_default()),
+    }
+}
+
+///
+///     Check every test of a corpus against its golden trace and post-state
+///     roots.
+///
+///     Parameters
+///     ----------
+///     directory :
+///         The corpus.
+///     update :
+///         Whether to rewrite the traces that are missing or differ, for
+///         the tests that reach their post-state roots.
+///
+///     Returns
+///     -------
+///     outcomes : `Vec<GoldenOutcome>`
+///         How each test went in each of its forks, in the order of the
+///         files.
+///
+///     Raises
+///     ------
+///     FileNotFound :
+///         If the directory or a fixture can't be read.
+///     ValueError :
+///         If a fixture isn't an object of tests.
+///
+pub fn check_corpus(directory: &Path, update: bool) -> Result<Vec<GoldenOutcome>, EthereumException> {
+    let mut outcomes = vec![];
+    for path in fixture_files(&[directory.to_owned()])? {
+        let file = path.display().to_string();
+        let text =
+            fs::read_to_string(&path).map_err(|error| EthereumException::FileNotFound(format!("{file}: {error}")))?;
+        let Ok(Value::Object(tests)) = serde_json::from_str::<Value>(&text) else {
+            return Err(EthereumException::ValueError);
+        };
+        for (name, test) in &tests {
+            for fork in test_forks(test) {
+                let trace_path = path.with_file_name(format!("{name}.{fork}.jsonl"));
+                let status = check_test(test, &fork, &trace_path, update);
+                outcomes.push(GoldenOutcome {
+                    id: TestId {
+                        file: file.clone(),
+                        name: name.clone(),
+                        fork,
+                    },
+                    status,
+                });
+            }
+        }
+    }
+    Ok(outcomes)
+}
@@ -0,0 +1,26 @@
+//!
+//! # EVM Tools
+//!
+//! Command line and library helpers for working with EVM bytecode outside of
+//! a block, in the spirit of geth's `evm` tool.
+//!
+pub mod alloc;
+pub mod call_tracer;
+pub mod coverage;
+pub mod debugger;
+pub mod disasm;
+pub mod dump;
+pub mod eip3155;
+#[cfg(feature = "evmc")]
+pub mod evmc;
+#[cfg(feature = "cdylib")]
+pub mod ffi;
+pub mod fixtures;
+pub mod gas_profile;
+pub mod golden;
+pub mod precompiles;
+pub mod prestate;
+pub mod results;
+pub mod run;
+pub mod state_test;
+pub mod t8n;
@@ -0,0 +1,250 @@
+//!
+//! # Precompile Vectors
+//!
+//! Runs precompiled contracts directly on an input, without a transaction
+//! or a call around them, and compares the output and gas with known
+//! vectors. That is much faster than running whole state tests, and points
+//! at the precompile when it is wrong.
+//!
+//! The vectors are in go-ethereum's format, one file per precompile:
+//!
+//! ```json
+//! [
+//!   { "Name": "…", "Input": "…", "Expected": "…", "Gas": 3000 },
+//!   { "Name": "…", "Input": "…", "ExpectedError": "invalid input length" }
+//! ]
+//! ```
+//!
+//! The file is named after the precompile, such as `modexp.json`,
+//! `bn256Pairing.json`, `blake2F.json`, `pointEvaluation.json` or
+//! `blsG1Add.json`, and vectors that must fail are in `fail-` files, such as
+//! `fail-blake2f.json`. A vector that must fail is only checked to fail: the
+//! messages are go-ethereum's.
+//!
+//! The `GeneralStateTests` exercising the precompiles only record the state
+//! root after the transaction, not what the precompile returned, so they
+//! can't be run this way.
+//!
+//! The vectors are run against the precompiles of Frontier, the only fork
+//! implemented. Those of the other precompiles are reported as unsupported,
+//! as are those of the Frontier precompiles not ported yet.
+//!
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+use serde_json::Value;
+
+use super::{
+    alloc::json_str,
+    fixtures::{fixture_files, RunSummary, Status, TestOutcome},
+    results::TestId,
+};
+use crate::ethereum::{
+    base_types::{Bytes, Uint, U256},
+    exceptions::EthereumException,
+    frontier::{
+        fork_types::Address,
+        vm::{
+            interpreter::new_evm, precompiled_contracts::mapping::pre_compiled_contract, Environment, Message,
+        },
+    },
+    utils::hexadecimal::{hex, hex_to_bytes},
+};
+
+/// The gas given to a vector that doesn't say how much it needs.
+const GAS_LIMIT: u64 = 100_000_000;
+
+/// The fork whose precompiles are run.
+const FORK: &str = "Frontier";
+
+/// An input of a precompile and what it must give.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PrecompileVector {
+    pub name: String,
+    pub input: Bytes,
+    /// The output, or why the precompile must fail.
+    pub expected: Result<Bytes, String>,
+    /// The gas the precompile must use, if it succeeds.
+    pub gas: Option<u64>,
+}
+
+///
+///     The address of a precompile, by the name of its vectors file.
+///
+///     Parameters
+///     ----------
+///     name :
+///         The file name without its extension, such as `bn256Add`,
+///         `modexp_eip2565` or `fail-blake2f`.
+///
+pub fn precompile_address(name: &str) -> Option<Address> {
+    let name = name.strip_prefix("fail-").unwrap_or(name).to_ascii_lowercase();
+    let index = match name.split('_').next()? {
+        "ecrecover" => 0x01,
+        "sha256" => 0x02,
+        "ripemd160" => 0x03,
+        "identity" => 0x04,
+        "modexp" => 0x05,
+        "bn256add" => 0x06,
+        "bn256scalarmul" => 0x07,
+        "bn256pairing" => 0x08,
+        "blake2f" => 0x09,
+        "pointevaluation" => 0x0a,
+        "blsg1add" => 0x0b,
+        "blsg1mul" | "blsg1multiexp" | "blsg1msm" => 0x0c,
+        "blsg2add" => 0x0d,
+        "blsg2mul" | "blsg2multiexp" | "blsg2msm" => 0x0e,
+        "blspairing" => 0x0f,
+        "blsmapg1" => 0x10,
+        "blsmapg2" => 0x11,
+        _ => return None,
+    };
+    let mut address = [0; 20];
+    address[19] = index;
+    Some(address)
+}
+
+fn json_error(message: impl Into<String>) -> EthereumException {
+    EthereumException::JsonDecodeError(message.into())
+}
+
+///
+///     Parse the vectors of a precompile.
+///
+///     Parameters
+///     ----------
+///     json :
+///         A list of vectors, in go-ethereum's format.
+///
+pub fn load_vectors(json: &str) -> Result<Vec<PrecompileVector>, EthereumException> {
+    let value: Value = serde_json::from_str(json).map_err(|e| json_error(e.to_string()))?;
+    let vectors = value.as_array().ok_or_else(|| json_error("the vectors must be a list"))?;
+    vectors
+        .iter()
+        .map(|vector| {
+            let expected = match (&vector["Expected"], &vector["ExpectedError"]) {
+                (Value::String(output), _) => Ok(hex_to_bytes(output)?),
+                (_, Value::String(error)) => Err(error.clone()),
+                _ => return Err(json_error("a vector needs `Expected` or `ExpectedError`")),
+            };
+            Ok(PrecompileVector {
+                name: json_str(&vector["Name"])?.to_owned(),
+                input: hex_to_bytes(json_str(&vector["Input"])?)?,
+                expected,
+                gas: vector["Gas"].as_u64(),
+            })
+        })
+        .collect()
+}
+
+///
+///     Run a vector against the precompile at an address.
+///
+///     Parameters
+///     ----------
+///     address :
+///         The precompile.
+///     vector :
+///         Its input and what it must give.
+///
+///     Returns
+///     -------
+///     status : `Status`
+///         [Status::Unsupported] if there is no precompile at `address`.
+///
+pub fn run_vector(address: &Address, vector: &PrecompileVector) -> Status {
+    let Some(contract) = pre_compiled_contract(address) else {
+        return Status::Unsupported;
+    };
+    let gas = U256::from(vector.gas.unwrap_or(GAS_LIMIT));
+    let message = Message {
+        caller: [0; 20],
+        target: Some(*address),
+        current_target: *address,
+        gas: gas.clone(),
+        value: U256::default(),
+        data: vector.input.clone(),
+        code_address: Some(*address),
+        code: Bytes::default(),
+        depth: Uint::default(),
+    };
+    let mut evm = new_evm(message, Environment::default());
+    let result = contract(&mut evm);
+
+    match (&vector.expected, result) {
+        (Ok(expected), Ok(())) => {
+            let used = gas - &evm.gas_left;
+            if evm.output != *expected {
+                Status::Failed(format!("output {} instead of {}", hex(&evm.output), hex(expected)))
+            } else if vector.gas.is_some_and(|gas| used != U256::from(gas)) {
+                Status::Failed(format!("used {used} gas instead of {}", vector.gas.unwrap_or_default()))
+            } else {
+                Status::Passed
+            }
+        }
+        (Ok(_), Err(error)) => Status::Failed(format!("failed with {error}")),
+        (Err(expected), Ok(())) => Status::Failed(format!("returned {} instead of failing with {expected}", hex(&evm.output))),
+        (Err(_), Err(_)) => Status::Passed,
+    }
+}
+
+/// Run the vectors of a file, named after their precompile.
+fn run_vectors_file(path: &Path) -> Vec<TestOutcome> {
+    let file = path.display().to_string();
+    let id = |name: &str| TestId {
+        file: file.clone(),
+        name: name.to_owned(),
+        fork: FORK.to_owned(),
+    };
+    let failed = |message: String| {
+        vec![TestOutcome {
+            id: id(""),
+            status: Status::Failed(message),
+            duration: Duration::ZERO,
+        }]
+    };
+    let name = path.file_stem().unwrap_or_default().to_string_lossy();
+    let Some(address) = precompile_address(&name) else {
+        return failed(format!("no precompile is named `{name}`"));
+    };
+    let vectors = match fs::read_to_string(path).map_err(|e| e.to_string()) {
+        Ok(json) => load_vectors(&json).map_err(|e| format!("{e:?}")),
+        Err(message) => Err(message),
+    };
+    let vectors = match vectors {
+        Ok(vectors) => vectors,
+        Err(message) => return failed(message),
+    };
+    vectors
+        .iter()
+        .map(|vector| {
+            let start = Instant::now();
+            let status = run_vector(&address, vector);
+            TestOutcome {
+                id: id(&vector.name),
+                status,
+                duration: start.elapsed(),
+            }
+        })
+        .collect()
+}
+
+///
+///     Run the precompile vectors under some paths.
+///
+///     Parameters
+///     ----------
+///     paths :
+///         Vector files and directories of them, as for [fixture_files].
+///
+pub fn run_precompile_vectors(paths: &[PathBuf]) -> Result<RunSummary, EthereumException> {
+    let mut summary = RunSummary::default();
+    for path in fixture_files(paths)? {
+        summary.outcomes.extend(run_vectors_file(&path));
+    }
+    Ok(summary)
+}
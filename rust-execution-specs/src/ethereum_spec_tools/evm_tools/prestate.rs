@@ -0,0 +1,122 @@
+//!
+//! # Prestate Tracer
+//!
+//! Writes a [StateDiff] as geth's `prestateTracer` does with `diffMode`
+//! set, so that the changes the spec makes in a transaction or a block can
+//! be compared with the result of `debug_traceTransaction` or
+//! `debug_traceBlockByNumber`:
+//!
+//! ```json
+//! {
+//!     "pre": {
+//!         "0x1000000000000000000000000000000000000000": {
+//!             "balance": "0xde0b6b3a7640000",
+//!             "nonce": 1,
+//!             "code": "0x6000",
+//!             "storage": {
+//!                 "0x0000000000000000000000000000000000000000000000000000000000000001":
+//!                     "0x0000000000000000000000000000000000000000000000000000000000000002"
+//!             }
+//!         }
+//!     },
+//!     "post": {
+//!         "0x1000000000000000000000000000000000000000": {
+//!             "balance": "0xde0b6b3a763ffff"
+//!         }
+//!     }
+//! }
+//! ```
+//!
+//! As in geth, `pre` holds the accounts that changed as they were, with only
+//! the storage slots that changed, and leaves out the accounts that didn't
+//! exist. `post` holds only the fields that changed, and leaves out the
+//! accounts that were destroyed. Zero storage values are left out of both,
+//! as are a zero nonce and empty code.
+//!
+
+use serde_json::{json, Map, Value};
+
+use crate::ethereum::{
+    base_types::{u256_to_be_bytes32, U256},
+    frontier::{
+        fork_types::{Account, EMPTY_ACCOUNT},
+        state::{AccountDiff, StateDiff},
+    },
+    utils::hexadecimal::hex,
+};
+
+fn quantity(number: &U256) -> Value {
+    Value::String(format!("{number:#x}"))
+}
+
+/// The storage slots, among those that changed, whose value is not zero.
+fn storage(diff: &AccountDiff, value: impl Fn(&(U256, U256)) -> &U256) -> Map<String, Value> {
+    diff.storage
+        .iter()
+        .filter(|(_, values)| *value(values) != U256::from(0u8))
+        .map(|(key, values)| (hex(key), json!(hex(&u256_to_be_bytes32(value(values))))))
+        .collect()
+}
+
+fn pre_account(account: &Account, diff: &AccountDiff) -> Value {
+    let mut pre = Map::new();
+    pre.insert("balance".into(), quantity(&account.balance));
+    if account.nonce != U256::from(0u8) {
+        pre.insert("nonce".into(), json!(u64::try_from(&account.nonce).expect("nonces fit in 64 bits")));
+    }
+    if !account.code.is_empty() {
+        pre.insert("code".into(), json!(hex(&account.code)));
+    }
+    let storage = storage(diff, |(pre, _)| pre);
+    if !storage.is_empty() {
+        pre.insert("storage".into(), Value::Object(storage));
+    }
+    Value::Object(pre)
+}
+
+fn post_account(pre: &Account, account: &Account, diff: &AccountDiff) -> Value {
+    let mut post = Map::new();
+    if account.balance != pre.balance {
+        post.insert("balance".into(), quantity(&account.balance));
+    }
+    if account.nonce != pre.nonce {
+        post.insert("nonce".into(), json!(u64::try_from(&account.nonce).expect("nonces fit in 64 bits")));
+    }
+    if account.code != pre.code {
+        post.insert("code".into(), json!(hex(&account.code)));
+    }
+    let storage = storage(diff, |(_, post)| post);
+    if !storage.is_empty() {
+        post.insert("storage".into(), Value::Object(storage));
+    }
+    Value::Object(post)
+}
+
+///
+///     Write a state diff as the `prestateTracer` of geth does in
+///     `diffMode`.
+///
+///     Parameters
+///     ----------
+///     diff :
+///         The changes, from `transaction_diff` or `state_diff`.
+///
+///     Returns
+///     -------
+///     trace : `serde_json::Value`
+///         An object with the `pre` and `post` state of the accounts.
+///
+pub fn diff_mode_json(diff: &StateDiff) -> Value {
+    let mut pre = Map::new();
+    let mut post = Map::new();
+    for (address, account_diff) in diff {
+        if let Some(account) = &account_diff.pre {
+            pre.insert(hex(address), pre_account(account, account_diff));
+        }
+        if let Some(account) = &account_diff.post {
+            let pre_account = account_diff.pre.clone().unwrap_or_else(EMPTY_ACCOUNT);
+            post.insert(hex(address), post_account(&pre_account, account, account_diff));
+        }
+    }
+    json!({ "pre": pre, "post": post })
+}
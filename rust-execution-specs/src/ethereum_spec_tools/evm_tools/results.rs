@@ -0,0 +1,198 @@
+//!
+//! # Results Database
+//!
+//! Remembers whether each test passed in each fork, so that a run of
+//! thousands of fixtures can be interrupted and resumed, skip the tests
+//! that passed and haven't changed since, or retry only those that failed.
+//!
+//! The database is a JSON file:
+//!
+//! ```json
+//! {
+//!   "version": 1,
+//!   "tests": {
+//!     "GeneralStateTests/stExample/add11.json::add11::Frontier": {
+//!       "fingerprint": "0x…",
+//!       "passed": true
+//!     }
+//!   }
+//! }
+//! ```
+//!
+//! A test's fingerprint is the hash of its fixture, so editing or
+//! regenerating a fixture runs it again.
+//!
+
+use std::{
+    collections::BTreeMap,
+    fmt, fs,
+    path::{Path, PathBuf},
+};
+
+use serde_json::{json, Map, Value};
+
+use super::alloc::json_str;
+use crate::ethereum::{
+    exceptions::EthereumException,
+    frontier::fork_types::{keccak256, Hash32},
+    utils::hexadecimal::{hex, hex_to_hash},
+};
+
+/// The format of the file, written along to catch incompatible changes.
+const VERSION: u64 = 1;
+
+/// A test of a fixture file in a fork.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TestId {
+    /// The fixture file, as it was given to the runner.
+    pub file: String,
+    /// The test's name in the file.
+    pub name: String,
+    /// The fork, or the network of a blockchain test.
+    pub fork: String,
+}
+
+impl fmt::Display for TestId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}::{}::{}", self.file, self.name, self.fork)
+    }
+}
+
+/// What is remembered of a test.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TestRecord {
+    /// The fixture the result is for, as computed by [fingerprint].
+    pub fingerprint: Hash32,
+    pub passed: bool,
+    /// Why it failed.
+    pub message: Option<String>,
+}
+
+/// The fingerprint of a test: the hash of its fixture.
+pub fn fingerprint(test: &Value) -> Hash32 {
+    keccak256(test.to_string().as_bytes())
+}
+
+fn json_error(message: String) -> EthereumException {
+    EthereumException::JsonDecodeError(message)
+}
+
+///
+///     The results of past runs, possibly kept in a file.
+///
+#[derive(Clone, Debug, Default)]
+pub struct ResultsDb {
+    path: Option<PathBuf>,
+    records: BTreeMap<String, TestRecord>,
+}
+
+impl ResultsDb {
+    /// A database that isn't saved.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    ///
+    ///     Load the database kept in a file, or start one there if the file
+    ///     doesn't exist yet.
+    ///
+    ///     Parameters
+    ///     ----------
+    ///     path :
+    ///         Where the database is kept.
+    ///
+    pub fn open(path: &Path) -> Result<Self, EthereumException> {
+        let mut db = Self {
+            path: Some(path.to_owned()),
+            records: BTreeMap::new(),
+        };
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => return Ok(db),
+            Err(error) => return Err(EthereumException::FileNotFound(format!("{}: {error}", path.display()))),
+        };
+        let value: Value = serde_json::from_str(&text).map_err(|e| json_error(e.to_string()))?;
+        if value["version"] != VERSION {
+            return Err(json_error(format!("{}: not a version {VERSION} results database", path.display())));
+        }
+        let tests = value["tests"].as_object().ok_or_else(|| json_error("missing `tests`".to_owned()))?;
+        for (id, record) in tests {
+            let record = TestRecord {
+                fingerprint: hex_to_hash(json_str(&record["fingerprint"])?)?,
+                passed: record["passed"].as_bool().ok_or(EthereumException::ValueError)?,
+                message: record["message"].as_str().map(str::to_owned),
+            };
+            db.records.insert(id.clone(), record);
+        }
+        Ok(db)
+    }
+
+    /// The file the database is kept in, if any.
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    /// What was last recorded of a test.
+    pub fn get(&self, id: &TestId) -> Option<&TestRecord> {
+        self.records.get(&id.to_string())
+    }
+
+    /// Record the outcome of a test, replacing the previous one.
+    pub fn record(&mut self, id: &TestId, record: TestRecord) {
+        self.records.insert(id.to_string(), record);
+    }
+
+    /// Whether a test passed last time and is unchanged since.
+    pub fn passed(&self, id: &TestId, fingerprint: &Hash32) -> bool {
+        self.get(id).is_some_and(|record| record.passed && record.fingerprint == *fingerprint)
+    }
+
+    /// Whether a test failed last time, whether or not it has changed.
+    pub fn failed(&self, id: &TestId) -> bool {
+        self.get(id).is_some_and(|record| !record.passed)
+    }
+
+    /// How many tests are recorded.
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+
+    /// The database as it is written.
+    pub fn to_json(&self) -> Value {
+        let tests: Map<_, _> = self
+            .records
+            .iter()
+            .map(|(id, record)| {
+                let mut value = json!({
+                    "fingerprint": hex(&record.fingerprint),
+                    "passed": record.passed,
+                });
+                if let Some(message) = &record.message {
+                    value["message"] = json!(message);
+                }
+                (id.clone(), value)
+            })
+            .collect();
+        json!({ "version": VERSION, "tests": tests })
+    }
+
+    ///
+    ///     Write the database to its file, if it has one. The file is
+    ///     replaced at once, so an interrupted run leaves the previous
+    ///     checkpoint intact.
+    ///
+    pub fn save(&self) -> Result<(), EthereumException> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let io_error = |error: std::io::Error| EthereumException::FileNotFound(format!("{}: {error}", path.display()));
+        let temporary = path.with_extension("tmp");
+        let json = serde_json::to_string_pretty(&self.to_json()).expect("results serialize");
+        fs::write(&temporary, json + "\n").map_err(io_error)?;
+        fs::rename(&temporary, path).map_err(io_error)
+    }
+}
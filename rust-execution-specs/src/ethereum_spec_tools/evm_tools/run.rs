@@ -0,0 +1,137 @@
+//!
+//! # Run
+//!
+//! Executes raw bytecode outside of a block, like geth's `evm run`.
+//!
+//! The code runs as a single message call from `sender` to `receiver`, or as
+//! the init code of a contract created by `sender`, through the fork's
+//! `Evm::call` builder.
+//!
+
+use crate::{
+    ethereum::{
+        base_types::{Bytes, Uint, U256},
+        frontier::{
+            fork_types::Address,
+            state::{create_ether, set_code, State},
+            vm::{exceptions::EvmError, BlockEnvironment, Evm},
+        },
+        trace::Tracer,
+    },
+    ethereum_spec_tools::forks::HardFork,
+};
+
+/// The sender used when none is given, the same as geth's (`"sender"`).
+pub const DEFAULT_SENDER: Address = hex_literal::hex!("000000000000000000000000000073656e646572");
+
+/// The receiver used when none is given, the same as geth's (`"receiver"`).
+pub const DEFAULT_RECEIVER: Address = hex_literal::hex!("0000000000000000000000007265636569766572");
+
+/// What to run, and in which environment.
+pub struct RunConfig {
+    pub fork: HardFork,
+    /// The code to run. When empty, the code already deployed at `receiver`
+    /// in `state` runs instead.
+    pub code: Bytes,
+    pub input: Bytes,
+    pub gas: Uint,
+    pub gas_price: U256,
+    pub value: U256,
+    pub sender: Address,
+    pub receiver: Address,
+    /// Run `code` (followed by `input`) as init code instead of calling it.
+    pub create: bool,
+    /// The pre-state.
+    pub state: State,
+    pub block: BlockEnvironment,
+}
+
+impl Default for RunConfig {
+    fn default() -> Self {
+        Self {
+            fork: HardFork::default(),
+            code: Bytes::default(),
+            input: Bytes::default(),
+            gas: Uint::from(10_000_000_000u64),
+            gas_price: U256::from(0u8),
+            value: U256::from(0u8),
+            sender: DEFAULT_SENDER,
+            receiver: DEFAULT_RECEIVER,
+            create: false,
+            state: State::default(),
+            block: BlockEnvironment {
+                gas_limit: Uint::from(10_000_000_000u64),
+                ..Default::default()
+            },
+        }
+    }
+}
+
+/// The result of [run].
+pub struct RunOutput {
+    pub output: Bytes,
+    pub gas_used: Uint,
+    pub error: Option<EvmError>,
+    /// The state after the execution.
+    pub state: State,
+}
+
+///
+///     Run `config.code`.
+///
+///     Parameters
+///     ----------
+///     config :
+///         What to run, and in which environment.
+///     tracer :
+///         Receives the trace of the execution, if given.
+///
+///     Returns
+///     -------
+///     output : `RunOutput`
+///         The return data, gas used and post-state.
+///
+pub fn run(config: RunConfig, tracer: Option<Box<dyn Tracer<Evm>>>) -> RunOutput {
+    match config.fork {
+        HardFork::Frontier => run_frontier(config, tracer),
+    }
+}
+
+fn run_frontier(config: RunConfig, tracer: Option<Box<dyn Tracer<Evm>>>) -> RunOutput {
+    let mut state = config.state;
+
+    // The sender can always afford the value, so that experiments don't need
+    // a pre-state.
+    if config.value != U256::from(0u8) {
+        create_ether(&mut state, config.sender, config.value.clone());
+    }
+
+    let call = Evm::call()
+        .origin(config.sender)
+        .value(config.value)
+        .gas(config.gas)
+        .gas_price(config.gas_price)
+        .block(config.block);
+    let call = if config.create {
+        call.create().data([config.code, config.input].concat().into())
+    } else {
+        if !config.code.is_empty() {
+            set_code(&mut state, config.receiver, config.code);
+        }
+        call.to(config.receiver).data(config.input)
+    };
+    let call = match tracer {
+        Some(tracer) => call.tracer(tracer),
+        None => call,
+    };
+
+    let result = call
+        .run(&mut state)
+        .expect("the sender has been credited with the value");
+    RunOutput {
+        output: result.output,
+        gas_used: result.gas_used,
+        error: result.error,
+        state,
+    }
+}
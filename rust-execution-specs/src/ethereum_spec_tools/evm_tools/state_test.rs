@@ -0,0 +1,612 @@
+//!
+//! # State Test Filler
+//!
+//! Fills state tests defined in Rust into fixtures in the format of the
+//! `GeneralStateTests` of ethereum/tests and execution-spec-tests, so that
+//! other clients can run them:
+//!
+//! ```
+//! use execution_specs_rs::{
+//!     ethereum::base_types::U256,
+//!     ethereum_spec_tools::{
+//!         evm_tools::state_test::{Expect, StateTestFiller, StateTestTx},
+//!         forks::HardFork,
+//!         test_env::slot,
+//!     },
+//! };
+//!
+//! let contract = [0xcc; 20];
+//! let fixture = StateTestFiller::new("sstore")
+//!     // PUSH1 1, PUSH1 0, SSTORE
+//!     .with_code(contract, [0x60, 0x01, 0x60, 0x00, 0x55])
+//!     .with_transaction(StateTestTx::call(contract))
+//!     .expect(Expect::new().storage(contract, slot(0), U256::from(1u8)))
+//!     .fill(&[HardFork::Frontier])
+//!     .unwrap();
+//! assert!(fixture["sstore"]["post"]["Frontier"][0]["hash"].is_string());
+//! ```
+//!
+//! A filler holds a pre-state, a block environment and a transaction whose
+//! call data, gas limit and value may each have several variants. Every
+//! combination is run in each fork, and the fixture records the state root
+//! and logs hash after it, or the exception that made the transaction
+//! invalid. Expectations are checked while filling, like the `expect`
+//! section of a test filler, so a fixture is only written once the scenario
+//! does what its author meant.
+//!
+//! The previous block hashes are left empty, so `BLOCKHASH` returns zero.
+//!
+
+use std::{fmt, fs, path::Path};
+
+use serde_json::{json, Map, Value};
+
+use super::alloc::dump_alloc;
+use crate::{
+    ethereum::{
+        base_types::{Bytes, Bytes32, Uint, U256, U64},
+        exceptions::{EthereumException, InvalidTransactionReason},
+        frontier::{
+            fork::{check_transaction, process_transaction_traced},
+            fork_types::{keccak256, Account, Address, Log, Transaction},
+            state::{get_account, get_account_optional, get_storage, set_account, set_storage, state_root, State},
+            vm::{BlockEnvironment, Evm, TxEnvironment},
+        },
+        rlp,
+        trace::Tracer,
+        transaction_signature::{recover_sender, sign_transaction, FRONTIER},
+        utils::hexadecimal::hex,
+    },
+    ethereum_spec_tools::forks::HardFork,
+};
+
+/// Written to the `_info` of each fixture.
+pub const FILLING_TOOL: &str = "execution-specs-rs";
+
+/// The key signing a [StateTestTx] unless another is given, the usual
+/// sender of ethereum/tests, `0xa94f5374fce5edbc8e2a8697c15331677e6ebf0b`.
+pub const SECRET_KEY: Bytes32 = hex_literal::hex!("45a915e4d060149eb4365960e6a7a45f334393093061116b197e3240065ff2d8");
+
+/// Why a filler couldn't be filled.
+#[derive(Debug)]
+pub enum FillError {
+    /// The filler can't be run, such as without a transaction.
+    Exception(EthereumException),
+    /// The outcome of a combination isn't the one expected.
+    Expectation {
+        fork: HardFork,
+        indexes: Indexes,
+        message: String,
+    },
+}
+
+impl From<EthereumException> for FillError {
+    fn from(exception: EthereumException) -> Self {
+        FillError::Exception(exception)
+    }
+}
+
+impl fmt::Display for FillError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FillError::Exception(exception) => write!(f, "{exception:?}"),
+            FillError::Expectation { fork, indexes, message } => write!(
+                f,
+                "{fork:?}, data {} gas {} value {}: {message}",
+                indexes.data, indexes.gas, indexes.value
+            ),
+        }
+    }
+}
+
+/// Which variant of the call data, gas limit and value a combination uses.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Indexes {
+    pub data: usize,
+    pub gas: usize,
+    pub value: usize,
+}
+
+/// The transaction of a [StateTestFiller], signed with [SECRET_KEY] and
+/// with the sender's nonce by default.
+#[derive(Clone, Debug)]
+pub struct StateTestTx {
+    secret_key: Bytes32,
+    to: Option<Address>,
+    nonce: Option<U256>,
+    gas_price: U256,
+    data: Vec<Bytes>,
+    gas_limit: Vec<U256>,
+    value: Vec<U256>,
+}
+
+impl StateTestTx {
+    fn new(to: Option<Address>, data: Bytes) -> Self {
+        Self {
+            secret_key: SECRET_KEY,
+            to,
+            nonce: None,
+            gas_price: U256::from(10u8),
+            data: vec![data],
+            gas_limit: vec![U256::from(1_000_000u32)],
+            value: vec![U256::default()],
+        }
+    }
+
+    /// A message call to `to`.
+    pub fn call(to: Address) -> Self {
+        Self::new(Some(to), Bytes::default())
+    }
+
+    /// The creation of a contract, running `init_code`.
+    pub fn create(init_code: impl AsRef<[u8]>) -> Self {
+        Self::new(None, init_code.as_ref().into())
+    }
+
+    pub fn secret_key(mut self, secret_key: Bytes32) -> Self {
+        self.secret_key = secret_key;
+        self
+    }
+
+    pub fn nonce(mut self, nonce: U256) -> Self {
+        self.nonce = Some(nonce);
+        self
+    }
+
+    pub fn gas_price(mut self, gas_price: U256) -> Self {
+        self.gas_price = gas_price;
+        self
+    }
+
+    /// The variants of the call data, or of the init code of a creation.
+    pub fn data<D: AsRef<[u8]>>(mut self, data: impl IntoIterator<Item = D>) -> Self {
+        self.data = data.into_iter().map(|data| data.as_ref().into()).collect();
+        self
+    }
+
+    /// The variants of the gas limit.
+    pub fn gas_limit(mut self, gas_limit: impl IntoIterator<Item = U256>) -> Self {
+        self.gas_limit = gas_limit.into_iter().collect();
+        self
+    }
+
+    /// The variants of the value.
+    pub fn value(mut self, value: impl IntoIterator<Item = U256>) -> Self {
+        self.value = value.into_iter().collect();
+        self
+    }
+}
+
+/// What the state must be after some of the combinations of a filler.
+#[derive(Clone, Debug, Default)]
+pub struct Expect {
+    data: Option<usize>,
+    gas: Option<usize>,
+    value: Option<usize>,
+    exception: bool,
+    balances: Vec<(Address, U256)>,
+    nonces: Vec<(Address, Uint)>,
+    codes: Vec<(Address, Bytes)>,
+    storage: Vec<(Address, Bytes32, U256)>,
+    absent: Vec<Address>,
+}
+
+impl Expect {
+    /// An expectation for every combination, until restricted to some
+    /// indexes.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn data_index(mut self, index: usize) -> Self {
+        self.data = Some(index);
+        self
+    }
+
+    pub fn gas_index(mut self, index: usize) -> Self {
+        self.gas = Some(index);
+        self
+    }
+
+    pub fn value_index(mut self, index: usize) -> Self {
+        self.value = Some(index);
+        self
+    }
+
+    /// The transaction is invalid, so the state is unchanged.
+    pub fn exception(mut self) -> Self {
+        self.exception = true;
+        self
+    }
+
+    pub fn balance(mut self, address: Address, balance: U256) -> Self {
+        self.balances.push((address, balance));
+        self
+    }
+
+    pub fn nonce(mut self, address: Address, nonce: Uint) -> Self {
+        self.nonces.push((address, nonce));
+        self
+    }
+
+    pub fn code(mut self, address: Address, code: impl AsRef<[u8]>) -> Self {
+        self.codes.push((address, code.as_ref().into()));
+        self
+    }
+
+    pub fn storage(mut self, address: Address, key: Bytes32, value: U256) -> Self {
+        self.storage.push((address, key, value));
+        self
+    }
+
+    /// The account doesn't exist.
+    pub fn absent(mut self, address: Address) -> Self {
+        self.absent.push(address);
+        self
+    }
+
+    fn applies(&self, indexes: &Indexes) -> bool {
+        [(self.data, indexes.data), (self.gas, indexes.gas), (self.value, indexes.value)]
+            .iter()
+            .all(|(expected, index)| expected.is_none_or(|expected| expected == *index))
+    }
+
+    /// The first way `state` differs from the expectation.
+    fn check(&self, state: &State, exception: Option<&str>) -> Result<(), String> {
+        match (self.exception, exception) {
+            (true, None) => return Err("expected an invalid transaction".to_owned()),
+            (false, Some(exception)) => return Err(format!("unexpected {exception}")),
+            _ => {}
+        }
+        for (address, balance) in &self.balances {
+            let actual = get_account(state, address).balance;
+            if actual != *balance {
+                return Err(format!("balance of {}: expected {balance}, got {actual}", hex(address)));
+            }
+        }
+        for (address, nonce) in &self.nonces {
+            let actual = get_account(state, address).nonce;
+            if actual != *nonce {
+                return Err(format!("nonce of {}: expected {nonce}, got {actual}", hex(address)));
+            }
+        }
+        for (address, code) in &self.codes {
+            let actual = get_account(state, address).code;
+            if actual[..] != code[..] {
+                return Err(format!("code of {}: expected {}, got {}", hex(address), hex(code), hex(&actual)));
+            }
+        }
+        for (address, key, value) in &self.storage {
+            let actual = get_storage(state, address, key);
+            if actual != *value {
+                return Err(format!("{}[{}]: expected {value:#x}, got {actual:#x}", hex(address), hex(key)));
+            }
+        }
+        for address in &self.absent {
+            if get_account_optional(state, address).is_some() {
+                return Err(format!("{} exists", hex(address)));
+            }
+        }
+        Ok(())
+    }
+}
+
+///
+///     The name execution-spec-tests gives the reason a transaction is
+///     invalid, for the `expectException` of a fixture.
+///
+pub fn exception_name(reason: &InvalidTransactionReason) -> &'static str {
+    match reason {
+        InvalidTransactionReason::IntrinsicGasTooLow { .. } => "TransactionException.INTRINSIC_GAS_TOO_LOW",
+        InvalidTransactionReason::GasAboveBlockLimit { .. } => "TransactionException.GAS_ALLOWANCE_EXCEEDED",
+        InvalidTransactionReason::NonceTooLarge => "TransactionException.NONCE_IS_MAX",
+        InvalidTransactionReason::NonceMismatch { expected, nonce } if nonce > expected => {
+            "TransactionException.NONCE_MISMATCH_TOO_HIGH"
+        }
+        InvalidTransactionReason::NonceMismatch { .. } => "TransactionException.NONCE_MISMATCH_TOO_LOW",
+        InvalidTransactionReason::InsufficientBalance { .. } => "TransactionException.INSUFFICIENT_ACCOUNT_FUNDS",
+        InvalidTransactionReason::SenderNotEoa => "TransactionException.SENDER_NOT_EOA",
+    }
+}
+
+fn hex_uint(number: &Uint) -> String {
+    format!("{number:#x}")
+}
+
+/// A state test, defined in Rust, to fill into a fixture.
+pub struct StateTestFiller {
+    name: String,
+    comment: String,
+    pre: State,
+    env: BlockEnvironment,
+    tx: Option<StateTestTx>,
+    expect: Vec<Expect>,
+}
+
+impl StateTestFiller {
+    /// A test with an empty pre-state, in block 1 with a gas limit of a
+    /// hundred million, like those of execution-spec-tests.
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            comment: String::new(),
+            pre: State::default(),
+            env: BlockEnvironment {
+                block_hashes: vec![],
+                coinbase: hex_literal::hex!("2adc25665018aa1fe0e6bc666dac8fc2697ff9ba"),
+                number: Uint::from(1u8),
+                gas_limit: Uint::from(100_000_000u32),
+                time: U256::from(1000u32),
+                difficulty: Uint::from(0x20000u32),
+            },
+            tx: None,
+            expect: vec![],
+        }
+    }
+
+    /// A comment for the `_info` of the fixture.
+    pub fn with_comment(mut self, comment: impl Into<String>) -> Self {
+        self.comment = comment.into();
+        self
+    }
+
+    /// Set an account, replacing any there is.
+    pub fn with_account(mut self, address: Address, account: Account) -> Self {
+        set_account(&mut self.pre, address, Some(account));
+        self
+    }
+
+    fn modify(self, address: Address, f: impl FnOnce(&mut Account)) -> Self {
+        let mut account = get_account_optional(&self.pre, &address).unwrap_or_default();
+        f(&mut account);
+        self.with_account(address, account)
+    }
+
+    /// Set the balance of an account, creating it if needed.
+    pub fn with_balance(self, address: Address, balance: U256) -> Self {
+        self.modify(address, |account| account.balance = balance)
+    }
+
+    /// Set the code of an account, creating it if needed.
+    pub fn with_code(self, address: Address, code: impl AsRef<[u8]>) -> Self {
+        self.modify(address, |account| account.code = code.as_ref().into())
+    }
+
+    /// Set a storage slot of an account, creating the account if needed.
+    pub fn with_storage(self, address: Address, key: Bytes32, value: U256) -> Self {
+        let mut filler = self.modify(address, |_| {});
+        set_storage(&mut filler.pre, address, &key, value);
+        filler
+    }
+
+    /// Run in another block.
+    pub fn with_env(mut self, env: BlockEnvironment) -> Self {
+        self.env = env;
+        self
+    }
+
+    /// The transaction to run. Its sender is funded with one ether unless
+    /// the pre-state has it.
+    pub fn with_transaction(mut self, tx: StateTestTx) -> Self {
+        self.tx = Some(tx);
+        self
+    }
+
+    /// Check the state after the combinations `expect` applies to.
+    pub fn expect(mut self, expect: Expect) -> Self {
+        self.expect.push(expect);
+        self
+    }
+
+    ///
+    ///     Run every combination of the transaction in each fork.
+    ///
+    ///     Parameters
+    ///     ----------
+    ///     forks :
+    ///         The forks to fill for.
+    ///
+    ///     Returns
+    ///     -------
+    ///     fixture : `Result<serde_json::Value, FillError>`
+    ///         The fixture, an object with the test under its name, or the
+    ///         first expectation a combination doesn't meet.
+    ///
+    pub fn fill(&self, forks: &[HardFork]) -> Result<Value, FillError> {
+        let _span = tracing::info_span!("test", name = self.name).entered();
+        let tx = self.tx.as_ref().ok_or(EthereumException::ValueError)?;
+        let sender = self.sender(tx)?;
+        let mut pre = self.pre.clone();
+        if get_account_optional(&pre, &sender).is_none() {
+            let balance = U256::from(10u8).pow(18);
+            set_account(&mut pre, sender, Some(Account { balance, ..Default::default() }));
+        }
+        let nonce = tx.nonce.clone().unwrap_or_else(|| get_account(&pre, &sender).nonce);
+
+        let mut post = Map::new();
+        for fork in forks {
+            let mut results = vec![];
+            for (data_index, data) in tx.data.iter().enumerate() {
+                for (gas_index, gas) in tx.gas_limit.iter().enumerate() {
+                    for (value_index, value) in tx.value.iter().enumerate() {
+                        let indexes = Indexes {
+                            data: data_index,
+                            gas: gas_index,
+                            value: value_index,
+                        };
+                        let unsigned = Transaction {
+                            nonce: nonce.clone(),
+                            gas_price: tx.gas_price.clone(),
+                            gas: gas.clone(),
+                            to: tx.to,
+                            value: value.clone(),
+                            data: data.clone(),
+                            v: U256::default(),
+                            r: U256::default(),
+                            s: U256::default(),
+                        };
+                        let signed = sign_transaction(&unsigned, &tx.secret_key, None)?;
+                        results.push(self.fill_one(*fork, &pre, sender, &signed, indexes)?);
+                    }
+                }
+            }
+            post.insert(format!("{fork:?}"), Value::Array(results));
+        }
+
+        let to = tx.to.map(|to| hex(&to)).unwrap_or_default();
+        let test = json!({
+            "_info": {
+                "comment": self.comment,
+                "filling-tool": FILLING_TOOL,
+            },
+            "env": {
+                "currentCoinbase": hex(&self.env.coinbase),
+                "currentDifficulty": hex_uint(&self.env.difficulty),
+                "currentGasLimit": hex_uint(&self.env.gas_limit),
+                "currentNumber": hex_uint(&self.env.number),
+                "currentTimestamp": hex_uint(&self.env.time),
+            },
+            "pre": dump_alloc(&pre),
+            "transaction": {
+                "data": tx.data.iter().map(|data| hex(data)).collect::<Vec<_>>(),
+                "gasLimit": tx.gas_limit.iter().map(hex_uint).collect::<Vec<_>>(),
+                "gasPrice": hex_uint(&tx.gas_price),
+                "nonce": hex_uint(&nonce),
+                "secretKey": hex(&tx.secret_key),
+                "sender": hex(&sender),
+                "to": to,
+                "value": tx.value.iter().map(hex_uint).collect::<Vec<_>>(),
+            },
+            "post": post,
+        });
+        Ok(json!({ self.name.clone(): test }))
+    }
+
+    fn sender(&self, tx: &StateTestTx) -> Result<Address, EthereumException> {
+        let unsigned = Transaction {
+            nonce: U256::default(),
+            gas_price: U256::default(),
+            gas: U256::default(),
+            to: None,
+            value: U256::default(),
+            data: Bytes::default(),
+            v: U256::default(),
+            r: U256::default(),
+            s: U256::default(),
+        };
+        let signed = sign_transaction(&unsigned, &tx.secret_key, None)?;
+        recover_sender(&FRONTIER, U64::from(1u8), &signed)
+    }
+
+    /// Run one combination on a copy of the pre-state.
+    fn fill_one(
+        &self,
+        fork: HardFork,
+        pre: &State,
+        sender: Address,
+        tx: &Transaction,
+        indexes: Indexes,
+    ) -> Result<Value, FillError> {
+        let mut state = pre.clone();
+        let (exception, logs) = apply_transaction(fork, &self.env, &mut state, sender, tx, &mut None)?;
+
+        tracing::debug!(
+            fork = ?fork,
+            data = indexes.data,
+            gas = indexes.gas,
+            value = indexes.value,
+            exception,
+            "filled"
+        );
+        for expect in self.expect.iter().filter(|expect| expect.applies(&indexes)) {
+            expect
+                .check(&state, exception)
+                .map_err(|message| FillError::Expectation { fork, indexes, message })?;
+        }
+
+        let mut result = json!({
+            "hash": hex(&state_root(&state)),
+            "logs": hex(&keccak256(&rlp::encode(&logs[..]))),
+            "txbytes": hex(&rlp::encode(tx)),
+            "indexes": {
+                "data": indexes.data,
+                "gas": indexes.gas,
+                "value": indexes.value,
+            },
+            "state": dump_alloc(&state),
+        });
+        if let Some(exception) = exception {
+            result["expectException"] = json!(exception);
+        }
+        Ok(result)
+    }
+}
+
+///
+///     Run the transaction of a state test, unless it is invalid.
+///
+///     Parameters
+///     ----------
+///     fork :
+///         The fork whose rules apply.
+///     env :
+///         The block it is in.
+///     state :
+///         The pre-state, left as the post-state.
+///     sender :
+///         The account that signed it.
+///     tx :
+///         The transaction.
+///     tracer :
+///         Receives the trace events of the transaction, if set.
+///
+///     Returns
+///     -------
+///     outcome : `(Option<&'static str>, Vec<Log>)`
+///         The name of the exception making it invalid, if it is, and the
+///         logs it emitted otherwise.
+///
+pub(crate) fn apply_transaction(
+    fork: HardFork,
+    env: &BlockEnvironment,
+    state: &mut State,
+    sender: Address,
+    tx: &Transaction,
+    tracer: &mut Option<Box<dyn Tracer<Evm>>>,
+) -> Result<(Option<&'static str>, Vec<Log>), EthereumException> {
+    let tx_env = TxEnvironment {
+        origin: sender,
+        gas_price: tx.gas_price.clone(),
+    };
+    Ok(match fork {
+        HardFork::Frontier => match check_transaction(env, &get_account(state, &sender), tx) {
+            Ok(()) => (None, process_transaction_traced(env, &tx_env, state, tx, tracer)?.1),
+            Err(reason) => (Some(exception_name(&reason)), vec![]),
+        },
+    })
+}
+
+///
+///     Fill state tests and write each to `<name>.json` in a directory.
+///
+///     Parameters
+///     ----------
+///     fillers :
+///         The tests.
+///     forks :
+///         The forks to fill for.
+///     directory :
+///         Where to write the fixtures, created if needed.
+///
+pub fn fill_to_directory(fillers: &[StateTestFiller], forks: &[HardFork], directory: &Path) -> Result<(), FillError> {
+    let io_error = |error: std::io::Error| EthereumException::FileNotFound(error.to_string());
+    fs::create_dir_all(directory).map_err(io_error)?;
+    for filler in fillers {
+        let fixture = filler.fill(forks)?;
+        let json = serde_json::to_string_pretty(&fixture).expect("fixtures serialize");
+        let path = directory.join(format!("{}.json", filler.name));
+        fs::write(&path, json + "\n").map_err(io_error)?;
+        tracing::info!(path = %path.display(), "wrote");
+    }
+    Ok(())
+}
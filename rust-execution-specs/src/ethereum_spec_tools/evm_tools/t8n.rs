@@ -0,0 +1,764 @@
+//!
+//! # Transition Tool Differential
+//!
+//! Runs the same pre-state, block environment and transactions through this
+//! crate and through the `t8n` tool of another client (geth's `evm t8n`,
+//! besu's `evmtool t8n`, nethermind's `nethtest t8n`), and reports where the
+//! results differ.
+//!
+//! The inputs are those of geth's `t8n`: an allocation, an environment
+//!
+//! ```json
+//! {
+//!     "currentCoinbase": "0x2adc25665018aa1fe0e6bc666dac8fc2697ff9ba",
+//!     "currentDifficulty": "0x20000",
+//!     "currentGasLimit": "0x5f5e100",
+//!     "currentNumber": "0x1",
+//!     "currentTimestamp": "0x3e8",
+//!     "blockHashes": { "0": "0x..." }
+//! }
+//! ```
+//!
+//! and a list of legacy transactions, each signed (`v`, `r` and `s`) or
+//! given a `secretKey` to be signed with. Both tools are given the same
+//! files, written from the parsed inputs.
+//!
+//! The state root, transactions and receipts roots, logs hash and bloom and
+//! gas used are compared, then each receipt, and which transactions were
+//! rejected; only the indexes of those, since every client words its errors
+//! differently. When the post-states
+//! differ, the first account that differs is found with [find_divergence].
+//!
+
+use std::{collections::HashMap, fmt, fs, path::{Path, PathBuf}, process::Command};
+
+use serde_json::{json, Map, Value};
+
+use super::{
+    alloc::{dump_alloc, json_str, json_uint, load_alloc},
+    state_test::exception_name,
+};
+use crate::{
+    ethereum::{
+        base_types::{Bytes, U256, Uint},
+        exceptions::EthereumException,
+        frontier::{
+            bloom::logs_bloom,
+            fork::{
+                check_transaction, compute_receipts_root, compute_transactions_root, make_receipt, process_transaction,
+                recover_sender,
+            },
+            fork_types::{keccak256, Bloom, Hash32, Log, Root, Transaction},
+            state::{create_ether, get_account, state_root, State},
+            vm::{BlockEnvironment, TxEnvironment},
+        },
+        rlp,
+        transaction_signature::sign_transaction,
+        utils::hexadecimal::{hex, hex_to_bytes, hex_to_bytes20, hex_to_bytes256, hex_to_hash},
+    },
+    ethereum_spec_tools::{
+        forks::HardFork,
+        state_divergence::{find_divergence, Divergence},
+    },
+};
+
+/// Why a transition couldn't be run or compared.
+#[derive(Debug)]
+pub enum T8nError {
+    /// An input or output couldn't be parsed.
+    Exception(EthereumException),
+    /// The external tool couldn't be run or failed.
+    External(String),
+}
+
+impl From<EthereumException> for T8nError {
+    fn from(exception: EthereumException) -> Self {
+        T8nError::Exception(exception)
+    }
+}
+
+impl fmt::Display for T8nError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            T8nError::Exception(exception) => write!(f, "{exception:?}"),
+            T8nError::External(message) => write!(f, "{message}"),
+        }
+    }
+}
+
+fn json_error(message: String) -> EthereumException {
+    EthereumException::JsonDecodeError(message)
+}
+
+fn parse_json(text: &str) -> Result<Value, EthereumException> {
+    serde_json::from_str(text).map_err(|e| json_error(e.to_string()))
+}
+
+fn field<'a>(object: &'a Value, name: &str) -> Result<&'a Value, EthereumException> {
+    object.get(name).ok_or_else(|| json_error(format!("missing `{name}`")))
+}
+
+fn hex_uint(number: &Uint) -> String {
+    format!("{number:#x}")
+}
+
+/// What a transition runs: the pre-state, the block and its transactions.
+#[derive(Clone)]
+pub struct T8nInput {
+    pub alloc: State,
+    pub env: BlockEnvironment,
+    pub txs: Vec<Transaction>,
+    pub fork: HardFork,
+    /// The block reward paid to the coinbase, or `None` for none, like
+    /// `--state.reward -1`.
+    pub reward: Option<U256>,
+}
+
+impl T8nInput {
+    ///
+    ///     Parse the inputs of geth's `t8n`.
+    ///
+    ///     Parameters
+    ///     ----------
+    ///     alloc :
+    ///         The allocation, as JSON.
+    ///     env :
+    ///         The environment, as JSON.
+    ///     txs :
+    ///         The transactions, as a JSON list.
+    ///
+    ///     Returns
+    ///     -------
+    ///     input : `Result<T8nInput, EthereumException>`
+    ///         The inputs for Frontier without a block reward, with the
+    ///         transactions that had a `secretKey` signed.
+    ///
+    pub fn from_json(alloc: &str, env: &str, txs: &str) -> Result<Self, EthereumException> {
+        Ok(Self {
+            alloc: load_alloc(alloc)?,
+            env: parse_env(&parse_json(env)?)?,
+            txs: parse_txs(&parse_json(txs)?)?,
+            fork: HardFork::Frontier,
+            reward: None,
+        })
+    }
+
+    /// The environment, as JSON.
+    pub fn env_json(&self) -> Value {
+        let env = &self.env;
+        let first = env.number.clone() - Uint::from(env.block_hashes.len());
+        let block_hashes: Map<_, _> = env
+            .block_hashes
+            .iter()
+            .enumerate()
+            .map(|(i, hash)| ((&first + i).to_string(), json!(hex(hash))))
+            .collect();
+        json!({
+            "currentCoinbase": hex(&env.coinbase),
+            "currentDifficulty": hex_uint(&env.difficulty),
+            "currentGasLimit": hex_uint(&env.gas_limit),
+            "currentNumber": hex_uint(&env.number),
+            "currentTimestamp": hex_uint(&env.time),
+            "blockHashes": block_hashes,
+        })
+    }
+
+    /// The signed transactions, as a JSON list.
+    pub fn txs_json(&self) -> Value {
+        let txs = self.txs.iter().map(|tx| {
+            json!({
+                "nonce": hex_uint(&tx.nonce),
+                "gasPrice": hex_uint(&tx.gas_price),
+                "gas": hex_uint(&tx.gas),
+                "to": tx.to.map(|to| hex(&to)),
+                "value": hex_uint(&tx.value),
+                "input": hex(&tx.data),
+                "v": hex_uint(&tx.v),
+                "r": hex_uint(&tx.r),
+                "s": hex_uint(&tx.s),
+                "hash": hex(&transaction_hash(tx)),
+            })
+        });
+        Value::Array(txs.collect())
+    }
+}
+
+///
+///     Parse the environment of geth's `t8n`.
+///
+///     Parameters
+///     ----------
+///     env :
+///         The environment.
+///
+///     Returns
+///     -------
+///     block_env : `Result<BlockEnvironment, EthereumException>`
+///         The block, with the hashes of `blockHashes` up to its parent, zero
+///         where missing.
+///
+pub fn parse_env(env: &Value) -> Result<BlockEnvironment, EthereumException> {
+    let number = json_uint(field(env, "currentNumber")?)?;
+    let mut hashes = HashMap::new();
+    if let Some(block_hashes) = env.get("blockHashes").and_then(Value::as_object) {
+        for (block_number, hash) in block_hashes {
+            let block_number: Uint = block_number.parse().map_err(|_| EthereumException::ValueError)?;
+            hashes.insert(block_number, hex_to_hash(json_str(hash)?)?);
+        }
+    }
+    let first = hashes.keys().filter(|&block| *block < number).min().cloned();
+    let block_hashes = match first {
+        Some(first) => num_iter(&first, &number).map(|block| hashes.get(&block).copied().unwrap_or_default()).collect(),
+        None => vec![],
+    };
+
+    Ok(BlockEnvironment {
+        block_hashes,
+        coinbase: hex_to_bytes20(json_str(field(env, "currentCoinbase")?)?)?,
+        number,
+        gas_limit: json_uint(field(env, "currentGasLimit")?)?,
+        time: json_uint(field(env, "currentTimestamp")?)?,
+        difficulty: json_uint(field(env, "currentDifficulty")?)?,
+    })
+}
+
+/// The numbers from `start` up to, but not including, `end`.
+fn num_iter(start: &Uint, end: &Uint) -> impl Iterator<Item = Uint> {
+    let (mut next, end) = (start.clone(), end.clone());
+    std::iter::from_fn(move || {
+        (next < end).then(|| {
+            next += 1u8;
+            &next - 1u8
+        })
+    })
+}
+
+///
+///     Parse the transactions of geth's `t8n`.
+///
+///     Parameters
+///     ----------
+///     txs :
+///         A list of legacy transactions, with their call data as `input` or
+///         `data`, and either a signature or a `secretKey`.
+///
+///     Returns
+///     -------
+///     txs : `Result<Vec<Transaction>, EthereumException>`
+///         The signed transactions.
+///
+pub fn parse_txs(txs: &Value) -> Result<Vec<Transaction>, EthereumException> {
+    let txs = txs.as_array().ok_or_else(|| json_error("the transactions must be a list".to_owned()))?;
+    let uint = |tx: &Value, name: &str| -> Result<U256, EthereumException> {
+        tx.get(name).map(json_uint).unwrap_or(Ok(U256::default()))
+    };
+    txs.iter()
+        .map(|tx| {
+            let data = match tx.get("input").or_else(|| tx.get("data")) {
+                Some(data) => hex_to_bytes(json_str(data)?)?,
+                None => Bytes::default(),
+            };
+            let to = match tx.get("to") {
+                Some(Value::String(to)) if !to.is_empty() => Some(hex_to_bytes20(to)?),
+                _ => None,
+            };
+            let tx_data = Transaction {
+                nonce: uint(tx, "nonce")?,
+                gas_price: uint(tx, "gasPrice")?,
+                gas: uint(tx, "gas")?,
+                to,
+                value: uint(tx, "value")?,
+                data,
+                v: uint(tx, "v")?,
+                r: uint(tx, "r")?,
+                s: uint(tx, "s")?,
+            };
+            match tx.get("secretKey") {
+                Some(key) => {
+                    let key = hex_to_hash(json_str(key)?)?;
+                    sign_transaction(&tx_data, &key, None)
+                }
+                None => Ok(tx_data),
+            }
+        })
+        .collect()
+}
+
+/// The hash of a transaction, as its receipt refers to it.
+pub fn transaction_hash(tx: &Transaction) -> Hash32 {
+    keccak256(&rlp::encode(tx))
+}
+
+/// The receipt of a transaction of a transition.
+#[derive(Clone, Debug, PartialEq)]
+pub struct T8nReceipt {
+    /// The index of the transaction among the inputs, counting the rejected
+    /// ones.
+    pub index: usize,
+    pub transaction_hash: Hash32,
+    /// The state root after the transaction.
+    pub root: Root,
+    pub cumulative_gas_used: Uint,
+    pub gas_used: Uint,
+    pub logs_bloom: Bloom,
+    pub logs: Vec<Log>,
+}
+
+/// What a transition produced.
+#[derive(Clone)]
+pub struct T8nResult {
+    pub state_root: Root,
+    pub tx_root: Root,
+    pub receipts_root: Root,
+    pub logs_hash: Hash32,
+    pub logs_bloom: Bloom,
+    pub gas_used: Uint,
+    pub receipts: Vec<T8nReceipt>,
+    /// The index of each rejected transaction, with why.
+    pub rejected: Vec<(usize, String)>,
+    /// The state after the block.
+    pub alloc: State,
+}
+
+fn log_json(log: &Log) -> Value {
+    json!({
+        "address": hex(&log.address),
+        "topics": log.topics.iter().map(|topic| hex(topic)).collect::<Vec<_>>(),
+        "data": hex(&log.data),
+    })
+}
+
+fn parse_log(log: &Value) -> Result<Log, EthereumException> {
+    let topics = match field(log, "topics")? {
+        Value::Array(topics) => topics.iter().map(|topic| hex_to_hash(json_str(topic)?)).collect::<Result<_, _>>()?,
+        _ => vec![],
+    };
+    Ok(Log {
+        address: hex_to_bytes20(json_str(field(log, "address")?)?)?,
+        topics,
+        data: hex_to_bytes(json_str(field(log, "data")?)?)?,
+    })
+}
+
+impl T8nResult {
+    /// The result, as geth's `t8n` writes it to `result.json`.
+    pub fn result_json(&self) -> Value {
+        let receipts = self.receipts.iter().enumerate().map(|(position, receipt)| {
+            json!({
+                "root": hex(&receipt.root),
+                "cumulativeGasUsed": hex_uint(&receipt.cumulative_gas_used),
+                "logsBloom": hex(&receipt.logs_bloom),
+                "logs": receipt.logs.iter().map(log_json).collect::<Vec<_>>(),
+                "transactionHash": hex(&receipt.transaction_hash),
+                "gasUsed": hex_uint(&receipt.gas_used),
+                "transactionIndex": hex_uint(&Uint::from(position)),
+            })
+        });
+        let rejected = self.rejected.iter().map(|(index, error)| json!({ "index": index, "error": error }));
+        json!({
+            "stateRoot": hex(&self.state_root),
+            "txRoot": hex(&self.tx_root),
+            "receiptsRoot": hex(&self.receipts_root),
+            "logsHash": hex(&self.logs_hash),
+            "logsBloom": hex(&self.logs_bloom),
+            "receipts": receipts.collect::<Vec<_>>(),
+            "rejected": rejected.collect::<Vec<_>>(),
+            "gasUsed": hex_uint(&self.gas_used),
+        })
+    }
+
+    ///
+    ///     Read the outputs of a `t8n` tool.
+    ///
+    ///     Parameters
+    ///     ----------
+    ///     result :
+    ///         The contents of `result.json`.
+    ///     alloc :
+    ///         The contents of the output allocation.
+    ///     txs :
+    ///         The transactions the tool ran, to match receipts to them.
+    ///
+    ///     Returns
+    ///     -------
+    ///     result : `Result<T8nResult, EthereumException>`
+    ///         The result.
+    ///
+    pub fn from_json(result: &str, alloc: &str, txs: &[Transaction]) -> Result<Self, EthereumException> {
+        let result = parse_json(result)?;
+        let hash_field = |name: &str| -> Result<Hash32, EthereumException> { hex_to_hash(json_str(field(&result, name)?)?) };
+        let bloom = |object: &Value| -> Result<Bloom, EthereumException> {
+            hex_to_bytes256(json_str(field(object, "logsBloom")?)?)
+        };
+
+        let mut rejected = vec![];
+        for reject in result.get("rejected").and_then(Value::as_array).into_iter().flatten() {
+            let index = field(reject, "index")?.as_u64().ok_or(EthereumException::ValueError)?;
+            let error = reject.get("error").and_then(Value::as_str).unwrap_or_default();
+            rejected.push((index as usize, error.to_owned()));
+        }
+
+        // The receipts are those of the transactions that weren't rejected,
+        // in order. Hashes can't tell apart identical transactions.
+        let mut included = (0..txs.len()).filter(|index| rejected.iter().all(|(rejected, _)| rejected != index));
+        let mut receipts = vec![];
+        for receipt in result.get("receipts").and_then(Value::as_array).into_iter().flatten() {
+            let hash = hex_to_hash(json_str(field(receipt, "transactionHash")?)?)?;
+            let index = included
+                .next()
+                .filter(|&index| transaction_hash(&txs[index]) == hash)
+                .ok_or_else(|| json_error(format!("unexpected receipt of {}", hex(&hash))))?;
+            let root = match receipt.get("root").and_then(Value::as_str) {
+                Some(root) if root.len() > 2 => hex_to_hash(root)?,
+                _ => Root::default(),
+            };
+            let logs = match receipt.get("logs") {
+                Some(Value::Array(logs)) => logs.iter().map(parse_log).collect::<Result<_, _>>()?,
+                _ => vec![],
+            };
+            receipts.push(T8nReceipt {
+                index,
+                transaction_hash: hash,
+                root,
+                cumulative_gas_used: json_uint(field(receipt, "cumulativeGasUsed")?)?,
+                gas_used: json_uint(field(receipt, "gasUsed")?)?,
+                logs_bloom: bloom(receipt)?,
+                logs,
+            });
+        }
+
+        Ok(Self {
+            state_root: hash_field("stateRoot")?,
+            tx_root: hash_field("txRoot")?,
+            receipts_root: hash_field("receiptsRoot")?,
+            logs_hash: hash_field("logsHash")?,
+            logs_bloom: bloom(&result)?,
+            gas_used: json_uint(field(&result, "gasUsed")?)?,
+            receipts,
+            rejected,
+            alloc: load_alloc(alloc)?,
+        })
+    }
+}
+
+///
+///     Run a transition with this crate.
+///
+///     Transactions that can't be included are rejected and skipped, as
+///     `t8n` tools do, rather than invalidating the block.
+///
+///     Parameters
+///     ----------
+///     input :
+///         The pre-state, block and transactions.
+///
+///     Returns
+///     -------
+///     result : `T8nResult`
+///         The roots, receipts and post-state.
+///
+pub fn transition(input: &T8nInput) -> T8nResult {
+    let reject = |rejected: &mut Vec<(usize, String)>, index: usize, reason: String| {
+        tracing::debug!(reason, "rejected");
+        rejected.push((index, reason));
+    };
+    let env = &input.env;
+    let mut state = input.alloc.clone();
+    let mut gas_available = env.gas_limit.clone();
+    let mut included = vec![];
+    let mut included_receipts = vec![];
+    let mut block_logs = vec![];
+    let mut receipts = vec![];
+    let mut rejected = vec![];
+
+    for (index, tx) in input.txs.iter().enumerate() {
+        let _span = tracing::info_span!("tx", index).entered();
+        let sender = match input.fork {
+            HardFork::Frontier => recover_sender(tx),
+        };
+        let Ok(sender) = sender else {
+            reject(&mut rejected, index, "invalid signature".to_owned());
+            continue;
+        };
+        if tx.gas > gas_available {
+            reject(&mut rejected, index, "TransactionException.GAS_ALLOWANCE_EXCEEDED".to_owned());
+            continue;
+        }
+        if let Err(reason) = check_transaction(env, &get_account(&state, &sender), tx) {
+            reject(&mut rejected, index, exception_name(&reason).to_owned());
+            continue;
+        }
+        let tx_env = TxEnvironment {
+            origin: sender,
+            gas_price: tx.gas_price.clone(),
+        };
+        let (gas_used, logs) = match process_transaction(env, &tx_env, &mut state, tx) {
+            Ok(result) => result,
+            Err(error) => {
+                reject(&mut rejected, index, format!("{error:?}"));
+                continue;
+            }
+        };
+        gas_available -= &gas_used;
+        tracing::debug!(gas_used = %gas_used, "applied");
+
+        let receipt = make_receipt(state_root(&state), &env.gas_limit - &gas_available, &logs);
+        receipts.push(T8nReceipt {
+            index,
+            transaction_hash: transaction_hash(tx),
+            root: receipt.post_state,
+            cumulative_gas_used: receipt.cumulative_gas_used.clone(),
+            gas_used,
+            logs_bloom: receipt.bloom,
+            logs: logs.clone(),
+        });
+        included.push(tx.clone());
+        included_receipts.push(receipt);
+        block_logs.extend(logs);
+    }
+
+    if let Some(reward) = &input.reward {
+        create_ether(&mut state, env.coinbase, reward.clone());
+    }
+
+    T8nResult {
+        state_root: state_root(&state),
+        tx_root: compute_transactions_root(&included),
+        receipts_root: compute_receipts_root(&included_receipts),
+        logs_hash: keccak256(&rlp::encode(&block_logs[..])),
+        logs_bloom: logs_bloom(&block_logs),
+        gas_used: &env.gas_limit - gas_available,
+        receipts,
+        rejected,
+        alloc: state,
+    }
+}
+
+/// The `t8n` tool of another client.
+#[derive(Clone, Debug)]
+pub struct ExternalT8n {
+    /// The executable.
+    pub program: PathBuf,
+    /// The arguments before the input and output flags, `["t8n"]` for
+    /// geth's `evm`.
+    pub args: Vec<String>,
+}
+
+impl ExternalT8n {
+    /// geth's `evm` at `program`.
+    pub fn new(program: impl Into<PathBuf>) -> Self {
+        Self {
+            program: program.into(),
+            args: vec!["t8n".to_owned()],
+        }
+    }
+
+    ///
+    ///     Run a transition with the external tool.
+    ///
+    ///     Parameters
+    ///     ----------
+    ///     input :
+    ///         The pre-state, block and transactions.
+    ///     work_dir :
+    ///         Where to write the inputs and outputs, created if needed.
+    ///
+    ///     Returns
+    ///     -------
+    ///     result : `Result<T8nResult, T8nError>`
+    ///         What the tool produced, or why it couldn't be run.
+    ///
+    pub fn run(&self, input: &T8nInput, work_dir: &Path) -> Result<T8nResult, T8nError> {
+        let io_error = |error: std::io::Error| T8nError::External(format!("{}: {error}", work_dir.display()));
+        fs::create_dir_all(work_dir).map_err(io_error)?;
+        let write = |name: &str, value: Value| fs::write(work_dir.join(name), value.to_string()).map_err(io_error);
+        write("alloc.json", dump_alloc(&input.alloc))?;
+        write("env.json", input.env_json())?;
+        write("txs.json", input.txs_json())?;
+
+        let reward = input.reward.as_ref().map_or("-1".to_owned(), U256::to_string);
+        let path = |name: &str| work_dir.join(name).display().to_string();
+        let output = Command::new(&self.program)
+            .args(&self.args)
+            .arg(format!("--input.alloc={}", path("alloc.json")))
+            .arg(format!("--input.env={}", path("env.json")))
+            .arg(format!("--input.txs={}", path("txs.json")))
+            .arg(format!("--output.basedir={}", work_dir.display()))
+            .arg("--output.result=result.json")
+            .arg("--output.alloc=post.json")
+            .arg(format!("--state.fork={:?}", input.fork))
+            .arg(format!("--state.reward={reward}"))
+            .output()
+            .map_err(|error| T8nError::External(format!("cannot run {}: {error}", self.program.display())))?;
+        if !output.status.success() {
+            return Err(T8nError::External(format!(
+                "{} failed with {}: {}",
+                self.program.display(),
+                output.status,
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+
+        let result = fs::read_to_string(work_dir.join("result.json")).map_err(io_error)?;
+        let alloc = fs::read_to_string(work_dir.join("post.json")).map_err(io_error)?;
+        Ok(T8nResult::from_json(&result, &alloc, &input.txs)?)
+    }
+}
+
+/// A value that differs between the two results.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Mismatch {
+    /// The index of the transaction among the inputs, `None` for the
+    /// block's totals.
+    pub tx_index: Option<usize>,
+    pub field: String,
+    pub ours: String,
+    pub theirs: String,
+}
+
+impl fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(index) = self.tx_index {
+            write!(f, "tx {index}: ")?;
+        }
+        write!(f, "{}: ours {}, theirs {}", self.field, self.ours, self.theirs)
+    }
+}
+
+/// How two results differ.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct T8nDiff {
+    /// The differing values, per transaction in order, then the totals.
+    pub mismatches: Vec<Mismatch>,
+    /// The first account whose post-state differs.
+    pub divergence: Option<Divergence>,
+}
+
+impl T8nDiff {
+    pub fn is_empty(&self) -> bool {
+        self.mismatches.is_empty() && self.divergence.is_none()
+    }
+
+    /// The first transaction with a mismatch.
+    pub fn first_tx(&self) -> Option<usize> {
+        self.mismatches.iter().find_map(|mismatch| mismatch.tx_index)
+    }
+}
+
+impl fmt::Display for T8nDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return writeln!(f, "no mismatches");
+        }
+        for mismatch in &self.mismatches {
+            writeln!(f, "{mismatch}")?;
+        }
+        if let Some(divergence) = &self.divergence {
+            writeln!(f, "first divergence: {divergence}")?;
+        }
+        Ok(())
+    }
+}
+
+///
+///     Compare two results.
+///
+///     Parameters
+///     ----------
+///     ours :
+///         The result of this crate.
+///     theirs :
+///         The result of the other tool.
+///
+///     Returns
+///     -------
+///     diff : `T8nDiff`
+///         Every differing value and the first divergent account.
+///
+pub fn compare(ours: &T8nResult, theirs: &T8nResult) -> T8nDiff {
+    let mut mismatches = vec![];
+    let mut check = |tx_index: Option<usize>, field: &str, a: String, b: String| {
+        if a != b {
+            mismatches.push(Mismatch {
+                tx_index,
+                field: field.to_owned(),
+                ours: a,
+                theirs: b,
+            });
+        }
+    };
+
+    let included = ours.receipts.iter().chain(&theirs.receipts).map(|receipt| receipt.index);
+    let rejected = ours.rejected.iter().chain(&theirs.rejected).map(|(index, _)| *index);
+    let tx_count = included.chain(rejected).max().map_or(0, |index| index + 1);
+    for index in 0..tx_count {
+        let rejected = |result: &T8nResult| {
+            result.rejected.iter().find(|(i, _)| *i == index).map_or("included".to_owned(), |(_, error)| {
+                format!("rejected ({error})")
+            })
+        };
+        let (our_status, their_status) = (rejected(ours), rejected(theirs));
+        if our_status.starts_with("rejected") != their_status.starts_with("rejected") {
+            check(Some(index), "status", our_status, their_status);
+            continue;
+        }
+        let receipt = |result: &T8nResult| result.receipts.iter().find(|receipt| receipt.index == index).cloned();
+        let (a, b) = match (receipt(ours), receipt(theirs)) {
+            (Some(a), Some(b)) => (a, b),
+            (None, None) => continue,
+            (a, b) => {
+                let describe = |receipt: Option<T8nReceipt>| if receipt.is_some() { "a receipt" } else { "no receipt" };
+                check(Some(index), "receipt", describe(a).to_owned(), describe(b).to_owned());
+                continue;
+            }
+        };
+        check(Some(index), "root", hex(&a.root), hex(&b.root));
+        check(Some(index), "gasUsed", a.gas_used.to_string(), b.gas_used.to_string());
+        check(Some(index), "cumulativeGasUsed", a.cumulative_gas_used.to_string(), b.cumulative_gas_used.to_string());
+        check(Some(index), "logsBloom", hex(&a.logs_bloom), hex(&b.logs_bloom));
+        let logs = |logs: &[Log]| Value::Array(logs.iter().map(log_json).collect()).to_string();
+        check(Some(index), "logs", logs(&a.logs), logs(&b.logs));
+    }
+
+    check(None, "stateRoot", hex(&ours.state_root), hex(&theirs.state_root));
+    check(None, "txRoot", hex(&ours.tx_root), hex(&theirs.tx_root));
+    check(None, "receiptsRoot", hex(&ours.receipts_root), hex(&theirs.receipts_root));
+    check(None, "logsHash", hex(&ours.logs_hash), hex(&theirs.logs_hash));
+    check(None, "logsBloom", hex(&ours.logs_bloom), hex(&theirs.logs_bloom));
+    check(None, "gasUsed", ours.gas_used.to_string(), theirs.gas_used.to_string());
+
+    T8nDiff {
+        mismatches,
+        divergence: find_divergence(&ours.alloc, &theirs.alloc),
+    }
+}
+
+///
+///     Run a transition with this crate and with an external tool, and
+///     compare the results.
+///
+///     Parameters
+///     ----------
+///     input :
+///         The pre-state, block and transactions.
+///     external :
+///         The other tool.
+///     work_dir :
+///         Where the other tool's inputs and outputs are written.
+///
+///     Returns
+///     -------
+///     diff : `Result<T8nDiff, T8nError>`
+///         How the results differ, or why the other tool couldn't be run.
+///
+pub fn differential(input: &T8nInput, external: &ExternalT8n, work_dir: &Path) -> Result<T8nDiff, T8nError> {
+    let theirs = external.run(input, work_dir)?;
+    let diff = compare(&transition(input), &theirs);
+    tracing::info!(
+        program = %external.program.display(),
+        mismatches = diff.mismatches.len(),
+        first_tx = diff.first_tx(),
+        "compared"
+    );
+    Ok(diff)
+}
@@ -1,24 +1,296 @@
-use std::{fmt::Debug};
-
-///! Hard Forks of the Ethereum blockchain.
-#[derive(Debug, Hash, Default)]
-pub enum HardFork {
-    #[default]
-    Frontier,
-    // Homestead,
-    // DaoFork,
-    // TangerineWhistle,
-    // SpuriousDragon,
-    // Byzantium,
-    // Constantinople,
-    // Petersburg,
-    // Istanbul,
-    // MuirGlacier,
-    // Berlin,
-    // London,
-    // ArrowGlacier,
-    // GrayGlacier,
-    // Paris,
-    // #[default]
-    // Shanghai,
+use crate::prelude::*;
+use core::fmt::Debug;
+
+use serde_json::Value;
+
+use super::evm_tools::alloc::json_uint;
+pub use crate::ethereum::hard_fork::HardFork;
+use crate::ethereum::{base_types::Uint, exceptions::EthereumException};
+
+///
+///     What activates a fork.
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ForkCriteria {
+    ///
+    ///     From the block with this number on.
+    ///
+    ByBlockNumber(u64),
+
+    ///
+    ///     From the first block whose parent's total difficulty is at least
+    ///     this, the way the merge activated.
+    ///
+    ByTotalDifficulty(Uint),
+
+    ///
+    ///     From the first block with at least this timestamp.
+    ///
+    ByTimestamp(u64),
+}
+
+impl ForkCriteria {
+    ///
+    ///     Check whether the fork is active for a block.
+    ///
+    ///     Parameters
+    ///     ----------
+    ///     block_number :
+    ///         The number of the block.
+    ///     timestamp :
+    ///         The timestamp of the block.
+    ///     parent_total_difficulty :
+    ///         The total difficulty of the chain up to the block's parent.
+    ///
+    pub fn check(&self, block_number: u64, timestamp: u64, parent_total_difficulty: &Uint) -> bool {
+        match self {
+            ForkCriteria::ByBlockNumber(number) => block_number >= *number,
+            ForkCriteria::ByTotalDifficulty(difficulty) => parent_total_difficulty >= difficulty,
+            ForkCriteria::ByTimestamp(time) => timestamp >= *time,
+        }
+    }
+}
+
+///
+///     A fork in a chain's schedule.
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ScheduledFork {
+    pub name: &'static str,
+    pub criteria: ForkCriteria,
+    ///
+    ///     The implementation of the fork's rules, if the spec has one and
+    ///     it is compiled in.
+    ///
+    pub fork: Option<HardFork>,
+}
+
+///
+///     The forks of a chain, in the order they activate.
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ForkSchedule {
+    forks: Vec<ScheduledFork>,
+}
+
+/// The fields of a geth style chain configuration that schedule forks, in
+/// order. `eip155Block` activates with `eip158Block`, and is left out.
+const CONFIG_FORKS: [(&str, &str); 17] = [
+    ("homesteadBlock", "Homestead"),
+    ("daoForkBlock", "DAO Fork"),
+    ("eip150Block", "Tangerine Whistle"),
+    ("eip158Block", "Spurious Dragon"),
+    ("byzantiumBlock", "Byzantium"),
+    ("constantinopleBlock", "Constantinople"),
+    ("petersburgBlock", "Petersburg"),
+    ("istanbulBlock", "Istanbul"),
+    ("muirGlacierBlock", "Muir Glacier"),
+    ("berlinBlock", "Berlin"),
+    ("londonBlock", "London"),
+    ("arrowGlacierBlock", "Arrow Glacier"),
+    ("grayGlacierBlock", "Gray Glacier"),
+    ("terminalTotalDifficulty", "Paris"),
+    ("shanghaiTime", "Shanghai"),
+    ("cancunTime", "Cancun"),
+    ("pragueTime", "Prague"),
+];
+
+impl ForkSchedule {
+    ///
+    ///     A chain that stays on Frontier.
+    ///
+    pub fn frontier() -> Self {
+        Self {
+            forks: vec![ScheduledFork {
+                name: "Frontier",
+                criteria: ForkCriteria::ByBlockNumber(0),
+                fork: HardFork::from_name("Frontier").ok(),
+            }],
+        }
+    }
+
+    ///
+    ///     The forks of mainnet.
+    ///
+    pub fn mainnet() -> Self {
+        let mut schedule = Self::frontier();
+        for (name, number) in [
+            ("Homestead", 1_150_000),
+            ("DAO Fork", 1_920_000),
+            ("Tangerine Whistle", 2_463_000),
+            ("Spurious Dragon", 2_675_000),
+            ("Byzantium", 4_370_000),
+            ("Constantinople", 7_280_000),
+            ("Petersburg", 7_280_000),
+            ("Istanbul", 9_069_000),
+            ("Muir Glacier", 9_200_000),
+            ("Berlin", 12_244_000),
+            ("London", 12_965_000),
+            ("Arrow Glacier", 13_773_000),
+            ("Gray Glacier", 15_050_000),
+        ] {
+            schedule.push(name, ForkCriteria::ByBlockNumber(number));
+        }
+        let terminal_total_difficulty = "58750000000000000000000".parse().unwrap();
+        schedule.push("Paris", ForkCriteria::ByTotalDifficulty(terminal_total_difficulty));
+        schedule.push("Shanghai", ForkCriteria::ByTimestamp(1_681_338_455));
+        schedule.push("Cancun", ForkCriteria::ByTimestamp(1_710_338_135));
+        schedule.push("Prague", ForkCriteria::ByTimestamp(1_746_612_311));
+        schedule
+    }
+
+    ///
+    ///     The forks of Sepolia, which started on London rules.
+    ///
+    pub fn sepolia() -> Self {
+        let mut schedule = Self::pre_merge_at_genesis("London");
+        let terminal_total_difficulty = "17000000000000000".parse().unwrap();
+        schedule.push("Paris", ForkCriteria::ByTotalDifficulty(terminal_total_difficulty));
+        schedule.push("Shanghai", ForkCriteria::ByTimestamp(1_677_557_088));
+        schedule.push("Cancun", ForkCriteria::ByTimestamp(1_706_655_072));
+        schedule.push("Prague", ForkCriteria::ByTimestamp(1_741_159_776));
+        schedule
+    }
+
+    ///
+    ///     The forks of Holesky, which started on Paris rules.
+    ///
+    pub fn holesky() -> Self {
+        let mut schedule = Self::pre_merge_at_genesis("London");
+        schedule.push("Paris", ForkCriteria::ByTotalDifficulty(Uint::from(0u8)));
+        schedule.push("Shanghai", ForkCriteria::ByTimestamp(1_696_000_704));
+        schedule.push("Cancun", ForkCriteria::ByTimestamp(1_707_305_664));
+        schedule.push("Prague", ForkCriteria::ByTimestamp(1_740_434_112));
+        schedule
+    }
+
+    /// Frontier, then every block numbered fork up to `last` at block 0.
+    fn pre_merge_at_genesis(last: &str) -> Self {
+        let mut schedule = Self::frontier();
+        for (field, name) in CONFIG_FORKS {
+            if field != "daoForkBlock" {
+                schedule.push(name, ForkCriteria::ByBlockNumber(0));
+            }
+            if name == last {
+                break;
+            }
+        }
+        schedule
+    }
+
+    ///
+    ///     Schedule forks by name, for chains described other than by a geth
+    ///     style configuration.
+    ///
+    ///     Chains start on Frontier. Names are those of `forks()`, and case
+    ///     and spaces don't matter ("TangerineWhistle" is "Tangerine
+    ///     Whistle"). The forks are put in the order they have on mainnet,
+    ///     whatever the order they are given in.
+    ///
+    ///     Parameters
+    ///     ----------
+    ///     activations :
+    ///         The forks and what activates them.
+    ///
+    ///     Returns
+    ///     -------
+    ///     schedule : `ForkSchedule`
+    ///         The forks, or `ValueError` if one isn't known.
+    ///
+    pub fn from_activations<'a>(
+        activations: impl IntoIterator<Item = (&'a str, ForkCriteria)>,
+    ) -> Result<Self, EthereumException> {
+        let normalize = |name: &str| name.replace(' ', "").to_ascii_lowercase();
+        let mut scheduled = vec![];
+        for (name, criteria) in activations {
+            let position = CONFIG_FORKS
+                .iter()
+                .position(|(_, known)| normalize(known) == normalize(name))
+                .ok_or(EthereumException::ValueError)?;
+            scheduled.push((position, criteria));
+        }
+        scheduled.sort_by_key(|(position, _)| *position);
+
+        let mut schedule = Self::frontier();
+        for (position, criteria) in scheduled {
+            schedule.push(CONFIG_FORKS[position].1, criteria);
+        }
+        Ok(schedule)
+    }
+
+    ///
+    ///     Read the schedule from the `config` of a geth style genesis file.
+    ///
+    ///     Chains start on Frontier. A `daoForkBlock` is ignored if
+    ///     `daoForkSupport` is false, and the merge is scheduled by
+    ///     `terminalTotalDifficulty`.
+    ///
+    ///     Parameters
+    ///     ----------
+    ///     config :
+    ///         The chain configuration.
+    ///
+    ///     Returns
+    ///     -------
+    ///     schedule : `ForkSchedule`
+    ///         The forks the configuration schedules.
+    ///
+    pub fn from_config(config: &Value) -> Result<Self, EthereumException> {
+        let mut schedule = Self::frontier();
+        for (field, name) in CONFIG_FORKS {
+            let value = &config[field];
+            if value.is_null() || (field == "daoForkBlock" && config["daoForkSupport"] == Value::Bool(false)) {
+                continue;
+            }
+            let activation = json_uint(value)?;
+            let criteria = match field {
+                "terminalTotalDifficulty" => ForkCriteria::ByTotalDifficulty(activation),
+                _ => {
+                    let activation = u64::try_from(&activation).map_err(|_| EthereumException::ValueError)?;
+                    match field.strip_suffix("Time") {
+                        Some(_) => ForkCriteria::ByTimestamp(activation),
+                        None => ForkCriteria::ByBlockNumber(activation),
+                    }
+                }
+            };
+            schedule.push(name, criteria);
+        }
+        Ok(schedule)
+    }
+
+    fn push(&mut self, name: &'static str, criteria: ForkCriteria) {
+        self.forks.push(ScheduledFork {
+            name,
+            criteria,
+            fork: HardFork::from_name(name).ok(),
+        });
+    }
+
+    pub fn forks(&self) -> &[ScheduledFork] {
+        &self.forks
+    }
+
+    ///
+    ///     Find the fork whose rules apply to a block.
+    ///
+    ///     Parameters
+    ///     ----------
+    ///     block_number :
+    ///         The number of the block.
+    ///     timestamp :
+    ///         The timestamp of the block.
+    ///     parent_total_difficulty :
+    ///         The total difficulty of the chain up to the block's parent.
+    ///
+    ///     Returns
+    ///     -------
+    ///     index : `usize`
+    ///         The position of the latest active fork in `forks()`.
+    ///
+    pub fn active(&self, block_number: u64, timestamp: u64, parent_total_difficulty: &Uint) -> usize {
+        self.forks
+            .iter()
+            .rposition(|fork| fork.criteria.check(block_number, timestamp, parent_total_difficulty))
+            .unwrap_or(0)
+    }
 }
@@ -0,0 +1,229 @@
+//!
+//! # Gas Estimation
+//!
+//! A reference for `eth_estimateGas`: the least gas limit a transaction
+//! succeeds with against a state, found by a binary search, for the authors
+//! of estimators to compare theirs with.
+//!
+//! The gas a transaction uses is not the gas it needs. Refunds are only
+//! paid at the end, so the gas they give back had to be there; a call with
+//! value gives the callee a stipend the caller doesn't pay for; and from
+//! Tangerine Whistle on a call can pass on at most 63/64 of the gas left
+//! (EIP-150), so a call asking for all of it needs 1/64 more than it uses.
+//! In Frontier, a call is charged all the gas it asks for, and gives back
+//! what it didn't use. Code can also look at the gas left, so the search
+//! runs the transaction for each limit it tries, assuming only that a
+//! transaction that succeeds with some gas succeeds with more.
+//!
+//! In Frontier, a creation left without the gas to deposit its code still
+//! succeeds, creating a contract without code. A creation only succeeds for
+//! the search if it deposits the code it does with the most gas.
+//!
+//! As geth does, a first guess of the gas used plus a stipend plus 1/64 is
+//! tried before the search, as it is enough for most transactions.
+//!
+
+use crate::ethereum::{
+    base_types::{Bytes, Uint, U256},
+    exceptions::EthereumException,
+    frontier::{
+        constants::GAS_CALL_STIPEND,
+        fork_types::Address,
+        state::{begin_transaction, get_account, rollback_transaction, State},
+        vm::{call::CallResult, exceptions::EvmError, BlockEnvironment, Evm},
+    },
+    intrinsic_gas::{intrinsic_cost, IntrinsicGasInputs, FRONTIER},
+    trace::Tracer,
+};
+
+/// A transaction to run without signing it, as `eth_call` takes it.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CallRequest {
+    pub from: Address,
+    /// `None` to create a contract.
+    pub to: Option<Address>,
+    pub gas_price: U256,
+    pub value: U256,
+    pub data: Bytes,
+}
+
+/// Why a call can't run, or no gas limit makes it succeed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum EstimateError {
+    /// The gas doesn't cover the intrinsic cost of the transaction.
+    IntrinsicGasTooLow { gas: Uint, intrinsic_gas: Uint },
+    /// The sender can't afford the value.
+    InsufficientFunds,
+    /// The transaction runs out of gas even with the most it may have.
+    GasRequiredExceedsAllowance(Uint),
+    /// The transaction fails other than by running out of gas.
+    Failed(EvmError),
+}
+
+impl std::fmt::Display for EstimateError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::IntrinsicGasTooLow { gas, intrinsic_gas } => {
+                write!(f, "intrinsic gas too low: have {gas}, want {intrinsic_gas}")
+            }
+            Self::InsufficientFunds => write!(f, "insufficient funds for transfer"),
+            Self::GasRequiredExceedsAllowance(allowance) => {
+                write!(f, "gas required exceeds allowance ({allowance})")
+            }
+            Self::Failed(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+///
+///     Run a transaction against a state as a call, and undo its changes.
+///
+///     The intrinsic cost is charged, but no fees are paid.
+///
+///     Parameters
+///     ----------
+///     state :
+///         The state, left as it was.
+///     block_env :
+///         The block the call runs in.
+///     request :
+///         The transaction.
+///     gas :
+///         Its gas limit.
+///     tracer :
+///         Receives the trace events of the call, if set.
+///
+///     Returns
+///     -------
+///     result : `CallResult`
+///         The result of the call.
+///     gas_used : `Uint`
+///         The gas used, including the intrinsic cost, before refunds.
+///
+///     Raises
+///     ------
+///     IntrinsicGasTooLow :
+///         If `gas` doesn't cover the intrinsic cost.
+///     InsufficientFunds :
+///         If the sender can't afford the value.
+///
+pub fn run_call(
+    state: &mut State,
+    block_env: &BlockEnvironment,
+    request: &CallRequest,
+    gas: &Uint,
+    tracer: Option<Box<dyn Tracer<Evm>>>,
+) -> Result<(CallResult, Uint), EstimateError> {
+    let intrinsic_gas = intrinsic_cost(
+        &FRONTIER,
+        &IntrinsicGasInputs {
+            data: &request.data,
+            create: request.to.is_none(),
+            ..Default::default()
+        },
+    );
+    if *gas < intrinsic_gas {
+        return Err(EstimateError::IntrinsicGasTooLow {
+            gas: gas.clone(),
+            intrinsic_gas,
+        });
+    }
+
+    let call = Evm::call()
+        .origin(request.from)
+        .value(request.value.clone())
+        .gas(gas - &intrinsic_gas)
+        .gas_price(request.gas_price.clone())
+        .block(block_env.clone())
+        .data(request.data.clone());
+    let call = match request.to {
+        Some(to) => call.to(to),
+        None => call.create(),
+    };
+    let call = match tracer {
+        Some(tracer) => call.tracer(tracer),
+        None => call,
+    };
+
+    begin_transaction(state);
+    let result = call.run(state);
+    rollback_transaction(state);
+    let result = result.map_err(|_: EthereumException| EstimateError::InsufficientFunds)?;
+    let gas_used = intrinsic_gas + &result.gas_used;
+    Ok((result, gas_used))
+}
+
+///
+///     Find the least gas limit a transaction succeeds with.
+///
+///     Parameters
+///     ----------
+///     state :
+///         The state, left as it was.
+///     block_env :
+///         The block the transaction runs in.
+///     request :
+///         The transaction.
+///     cap :
+///         The most gas the transaction may have. It is lowered to what the
+///         sender can pay for at the gas price.
+///
+///     Returns
+///     -------
+///     gas : `Uint`
+///         The estimate.
+///
+///     Raises
+///     ------
+///     GasRequiredExceedsAllowance :
+///         If the transaction runs out of gas even with the cap.
+///     Failed :
+///         If it fails otherwise.
+///     IntrinsicGasTooLow, InsufficientFunds :
+///         If it can't run at all.
+///
+pub fn estimate_gas(
+    state: &mut State,
+    block_env: &BlockEnvironment,
+    request: &CallRequest,
+    cap: &Uint,
+) -> Result<Uint, EstimateError> {
+    let mut hi = cap.clone();
+    if request.gas_price != U256::default() {
+        let balance = get_account(state, &request.from).balance;
+        let available = if balance > request.value {
+            balance - &request.value
+        } else {
+            U256::default()
+        };
+        hi = hi.min(available / &request.gas_price);
+    }
+
+    let (result, gas_used) = run_call(state, block_env, request, &hi, None)?;
+    match result.error {
+        None => {}
+        Some(EvmError::OutOfGas) => return Err(EstimateError::GasRequiredExceedsAllowance(hi)),
+        Some(error) => return Err(EstimateError::Failed(error)),
+    }
+
+    let code = result.output;
+    let mut succeeds = |gas: &Uint| {
+        run_call(state, block_env, request, gas, None)
+            .map(|(result, _)| result.error.is_none() && (request.to.is_some() || result.output == code))
+    };
+    // Whatever a transaction needs, it needs at least what it used.
+    let mut lo = &gas_used - 1u8;
+    let optimistic = (&gas_used + GAS_CALL_STIPEND()) * 64u8 / 63u8;
+    if optimistic < hi && succeeds(&optimistic)? {
+        hi = optimistic;
+    }
+    while &lo + 1u8 < hi {
+        let mid = (&lo + &hi) / 2u8;
+        if succeeds(&mid)? {
+            hi = mid;
+        } else {
+            lo = mid;
+        }
+    }
+    Ok(hi)
+}
@@ -0,0 +1,194 @@
+//!
+//! # Header Archive
+//!
+//! An append-only file of consecutive block headers, for the headers a
+//! [Chain](super::chain::Chain) no longer keeps in memory. Executing a block
+//! needs only the last 255 blocks, but tools looking at a long sync want the
+//! headers before them too.
+//!
+//! Each header is written as its RLP, after its length as four big endian
+//! bytes. The offsets of the headers are found again when the file is
+//! opened; a header cut short by a crash is dropped then.
+//!
+
+use std::{
+    fs::{File, OpenOptions},
+    io::{Read, Seek, SeekFrom, Write},
+    ops::Range,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use super::{block_rlp::decode_header, forks::HardFork};
+use crate::ethereum::{
+    exceptions::EthereumException,
+    frontier::fork_types::{Hash32, Header},
+    rlp,
+};
+
+///
+///     Consecutive block headers in a file.
+///
+#[derive(Clone, Debug)]
+pub struct HeaderArchive {
+    path: PathBuf,
+    /// Clones share the file, but not the offsets: only one of them should
+    /// append.
+    file: Arc<File>,
+    /// The number of the first header.
+    first: u64,
+    /// The offset of each header, and the end of the last.
+    offsets: Vec<u64>,
+    /// The hash of the last header.
+    last_hash: Option<Hash32>,
+}
+
+impl HeaderArchive {
+    ///
+    ///     Open an archive, creating it if it doesn't exist.
+    ///
+    ///     Parameters
+    ///     ----------
+    ///     path :
+    ///         The file of the archive.
+    ///
+    ///     Raises
+    ///     ------
+    ///     FileNotFound :
+    ///         If the file can't be read or written.
+    ///     RLPDecodingError :
+    ///         If a header in it isn't valid RLP.
+    ///
+    pub fn open(path: &Path) -> Result<Self, EthereumException> {
+        let io_error = |error: std::io::Error| EthereumException::FileNotFound(format!("{}: {error}", path.display()));
+        let mut file = OpenOptions::new()
+            .read(true)
+            .append(true)
+            .create(true)
+            .open(path)
+            .map_err(io_error)?;
+        let mut contents = vec![];
+        file.read_to_end(&mut contents).map_err(io_error)?;
+
+        let mut offsets = vec![0];
+        let mut offset = 0;
+        while let Some(length) = contents.get(offset..offset + 4) {
+            let end = offset + 4 + u32::from_be_bytes(length.try_into().unwrap()) as usize;
+            if end > contents.len() {
+                break;
+            }
+            offset = end;
+            offsets.push(offset as u64);
+        }
+        if offset < contents.len() {
+            file.set_len(offset as u64).map_err(io_error)?;
+        }
+
+        let mut archive = Self {
+            path: path.to_owned(),
+            file: Arc::new(file),
+            first: 0,
+            offsets,
+            last_hash: None,
+        };
+        if !archive.is_empty() {
+            let first = archive.read(0)?.expect("the archive has a first header");
+            let last = archive
+                .read(archive.len() as usize - 1)?
+                .expect("the archive has a last header");
+            archive.first = u64::try_from(&first.number).map_err(|_| EthereumException::RLPDecodingError)?;
+            archive.last_hash = Some(last.hash());
+        }
+        Ok(archive)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The number of headers.
+    pub fn len(&self) -> u64 {
+        self.offsets.len() as u64 - 1
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    ///
+    ///     The numbers of the headers in the archive.
+    ///
+    pub fn range(&self) -> Range<u64> {
+        self.first..self.first + self.len()
+    }
+
+    ///
+    ///     Add the header after the last one.
+    ///
+    ///     Parameters
+    ///     ----------
+    ///     header :
+    ///         The next header, or any header if the archive is empty.
+    ///
+    ///     Raises
+    ///     ------
+    ///     ValueError :
+    ///         If the header isn't the child of the last one.
+    ///     FileNotFound :
+    ///         If the file can't be written.
+    ///
+    pub fn append(&mut self, header: &Header) -> Result<(), EthereumException> {
+        let number = u64::try_from(&header.number).map_err(|_| EthereumException::ValueError)?;
+        if let Some(last_hash) = &self.last_hash {
+            if number != self.range().end || header.parent_hash != *last_hash {
+                return Err(EthereumException::ValueError);
+            }
+        }
+        let encoded = rlp::encode(header);
+        let mut record = (encoded.len() as u32).to_be_bytes().to_vec();
+        record.extend_from_slice(&encoded);
+        (&*self.file)
+            .write_all(&record)
+            .map_err(|error| EthereumException::FileNotFound(format!("{}: {error}", self.path.display())))?;
+
+        if self.is_empty() {
+            self.first = number;
+        }
+        self.offsets.push(self.offsets.last().unwrap() + record.len() as u64);
+        self.last_hash = Some(header.hash());
+        Ok(())
+    }
+
+    ///
+    ///     Read the header of a block.
+    ///
+    ///     Parameters
+    ///     ----------
+    ///     number :
+    ///         The number of the block.
+    ///
+    ///     Returns
+    ///     -------
+    ///     header : `Option<Header>`
+    ///         The header, or `None` if it isn't in the archive.
+    ///
+    pub fn get(&self, number: u64) -> Result<Option<Header>, EthereumException> {
+        if !self.range().contains(&number) {
+            return Ok(None);
+        }
+        self.read((number - self.first) as usize)
+    }
+
+    fn read(&self, index: usize) -> Result<Option<Header>, EthereumException> {
+        let (Some(&start), Some(&end)) = (self.offsets.get(index), self.offsets.get(index + 1)) else {
+            return Ok(None);
+        };
+        let io_error =
+            |error: std::io::Error| EthereumException::FileNotFound(format!("{}: {error}", self.path.display()));
+        let mut record = vec![0; (end - start) as usize];
+        let mut file = &*self.file;
+        file.seek(SeekFrom::Start(start)).map_err(io_error)?;
+        file.read_exact(&mut record).map_err(io_error)?;
+        decode_header(&record[4..], HardFork::Frontier).map(Some)
+    }
+}
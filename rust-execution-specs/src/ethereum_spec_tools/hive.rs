@@ -0,0 +1,212 @@
+//!
+//! # Hive
+//!
+//! Runs the spec as a client in [hive](https://github.com/ethereum/hive), so
+//! that its simulators can compare it with production clients. A
+//! [HiveNode] serves the same JSON-RPC methods on the HTTP port (8545) and
+//! on the engine port (8551):
+//!
+//! - the Engine API and the block queries of [EngineApi], which the
+//!   `ethereum/engine` simulators drive the chain with;
+//! - the methods of [SpecRpc], run against the state of the head of the
+//!   chain, for the `ethereum/rpc-compat` simulator;
+//! - `web3_clientVersion`, `net_version` and `eth_syncing`, which the
+//!   simulators ask on connecting.
+//!
+//! The client definition hive builds the docker image from is in `hive/`.
+//!
+
+use std::{
+    io,
+    net::TcpListener,
+    sync::{Arc, Mutex},
+    thread,
+};
+
+use serde_json::{json, Value};
+
+use super::{
+    engine::{EngineApi, HeaderExtensions},
+    evm_tools::alloc::{json_str, json_uint, load_alloc},
+    forks::HardFork,
+    rpc::RpcError,
+    rpc_server::{handle_requests, serve, SpecRpc, METHOD_NOT_FOUND},
+};
+use crate::ethereum::{
+    exceptions::EthereumException,
+    frontier::{
+        fork::BlockChain,
+        fork_types::Hash32,
+        state::{state_root, State},
+        trie::EMPTY_TRIE_ROOT,
+    },
+    genesis::{add_genesis_block, parse_genesis_configuration},
+    utils::hexadecimal::{hex_to_bytes20, hex_to_hash},
+};
+
+/// What `web3_clientVersion` returns.
+pub const CLIENT_VERSION: &str = concat!("execution-specs-rs/v", env!("CARGO_PKG_VERSION"));
+
+/// The base fee of the first London block, EIP-1559's `INITIAL_BASE_FEE`.
+const INITIAL_BASE_FEE: u64 = 1_000_000_000;
+
+///
+///     Build a chain from a geth style genesis file, as hive writes them.
+///
+///     Unlike `parse_genesis_configuration`, the allocation may hold code,
+///     nonces and storage, and the genesis may be of a fork after the merge,
+///     in which case its header has the fields of that fork.
+///
+///     Parameters
+///     ----------
+///     json :
+///         The genesis file.
+///
+///     Returns
+///     -------
+///     chain : `BlockChain`
+///         The chain holding the genesis block.
+///     extensions : `HeaderExtensions`
+///         The fields of the genesis header added after the merge.
+///
+pub fn load_genesis(json: &str) -> Result<(BlockChain, HeaderExtensions), EthereumException> {
+    let mut genesis: Value =
+        serde_json::from_str(json).map_err(|e| EthereumException::JsonDecodeError(e.to_string()))?;
+    // Hive leaves out the fields that are zero.
+    for (field, default) in [("nonce", "0x0"), ("timestamp", "0x0"), ("extraData", "0x"), ("difficulty", "0x0")] {
+        if genesis.get(field).is_none_or(Value::is_null) {
+            genesis[field] = json!(default);
+        }
+    }
+    // The nonce is read as eight bytes, but written as a number.
+    genesis["nonce"] = json!(format!("0x{:016x}", json_uint(&genesis["nonce"])?));
+
+    let mut chain = BlockChain {
+        blocks: vec![],
+        state: State::default(),
+        chain_id: 0,
+    };
+    // The state is loaded separately, as the configuration only has room for
+    // balances.
+    let mut configuration = genesis.clone();
+    configuration["alloc"] = json!({});
+    add_genesis_block(
+        HardFork::Frontier,
+        &mut chain,
+        parse_genesis_configuration(&configuration.to_string())?,
+    )?;
+    chain.state = load_alloc(&genesis["alloc"].to_string())?;
+
+    let header = &mut chain.blocks[0].header;
+    header.state_root = state_root(&chain.state);
+    if let Some(coinbase) = genesis.get("coinbase").filter(|coinbase| !coinbase.is_null()) {
+        header.coinbase = hex_to_bytes20(json_str(coinbase)?)?;
+    }
+    if let Some(mix_hash) = genesis.get("mixHash").filter(|mix_hash| !mix_hash.is_null()) {
+        header.mix_digest = hex_to_hash(json_str(mix_hash)?)?;
+    }
+
+    let config = &genesis["config"];
+    let timestamp = u64::try_from(&header.timestamp).unwrap_or(u64::MAX);
+    let active = |fork: &str| config[fork].as_u64().is_some_and(|activation| activation <= timestamp);
+    let uint_or_zero = |field: &str| match genesis.get(field) {
+        Some(value) if !value.is_null() => json_uint(value),
+        _ => Ok(Default::default()),
+    };
+    let mut extensions = HeaderExtensions::default();
+    if config["londonBlock"].as_u64() == Some(0) {
+        extensions.base_fee_per_gas = Some(match genesis.get("baseFeePerGas") {
+            Some(base_fee) if !base_fee.is_null() => json_uint(base_fee)?,
+            _ => INITIAL_BASE_FEE.into(),
+        });
+    }
+    if active("shanghaiTime") {
+        extensions.withdrawals_root = Some(EMPTY_TRIE_ROOT());
+    }
+    if active("cancunTime") {
+        extensions.blob_gas_used = Some(uint_or_zero("blobGasUsed")?);
+        extensions.excess_blob_gas = Some(uint_or_zero("excessBlobGas")?);
+        extensions.parent_beacon_block_root = Some(Hash32::default());
+    }
+    Ok((chain, extensions))
+}
+
+/// A node answering the requests of hive's simulators.
+pub struct HiveNode {
+    engine: EngineApi,
+    /// Serves the state of the block `rpc_head`.
+    rpc: SpecRpc,
+    rpc_head: Hash32,
+}
+
+/// A server for calls against the state of the head of `engine`.
+fn head_rpc(engine: &EngineApi) -> SpecRpc {
+    SpecRpc::new(engine.state().clone(), engine.block_environment(), engine.chain_id())
+}
+
+impl HiveNode {
+    pub fn new(engine: EngineApi) -> Self {
+        Self {
+            rpc: head_rpc(&engine),
+            rpc_head: engine.head(),
+            engine,
+        }
+    }
+
+    ///
+    ///     Start a node from a genesis file, see [load_genesis].
+    ///
+    pub fn from_genesis(json: &str) -> Result<Self, EthereumException> {
+        let (chain, extensions) = load_genesis(json)?;
+        Ok(Self::new(EngineApi::new(chain, extensions)?))
+    }
+
+    pub fn engine(&self) -> &EngineApi {
+        &self.engine
+    }
+
+    fn dispatch(&mut self, method: &str, params: &Value) -> Result<Value, RpcError> {
+        match method {
+            "web3_clientVersion" => return Ok(json!(CLIENT_VERSION)),
+            "net_version" => return Ok(json!(self.engine.chain_id().to_string())),
+            "eth_syncing" => return Ok(json!(false)),
+            _ => {}
+        }
+        match self.engine.dispatch(method, params) {
+            Err(RpcError::Rpc { code: METHOD_NOT_FOUND, .. }) => {}
+            result => return result,
+        }
+        // Calls see the state of the head, which moves with the engine.
+        if self.rpc_head != self.engine.head() {
+            self.rpc = head_rpc(&self.engine);
+            self.rpc_head = self.engine.head();
+        }
+        self.rpc.dispatch(method, params)
+    }
+
+    ///
+    ///     Answer a request, or a batch of them.
+    ///
+    pub fn handle(&mut self, request: &Value) -> Value {
+        handle_requests(request, |method, params| self.dispatch(method, params))
+    }
+}
+
+///
+///     Serve `node` over HTTP on each of `listeners` at once, until one of
+///     them fails.
+///
+pub fn serve_node(node: HiveNode, listeners: Vec<TcpListener>) -> io::Result<()> {
+    let node = Arc::new(Mutex::new(node));
+    let servers: Vec<_> = listeners
+        .into_iter()
+        .map(|listener| {
+            let node = Arc::clone(&node);
+            thread::spawn(move || serve(listener, |request| node.lock().unwrap().handle(request)))
+        })
+        .collect();
+    for server in servers {
+        server.join().expect("a server thread panicked")?;
+    }
+    Ok(())
+}
@@ -0,0 +1,233 @@
+//!
+//! # Logging
+//!
+//! The command line tools log through `tracing`: events with a level, a
+//! message and fields, inside the spans (a block, a transaction) they
+//! happened in. [LogArgs] installs a `tracing-subscriber` that writes them
+//! to stderr one per line, as text
+//!
+//! ```text
+//!  1.204521s  INFO block{number=1150001}: applied transactions=3 gas_used=63000
+//! ```
+//!
+//! or as JSON. A program using the library installs its own subscriber
+//! instead, and sees the library's events there.
+//!
+//! [Progress] reports blocks and gas per second while a chain is applied.
+//!
+
+use std::{
+    str::FromStr,
+    time::{Duration, Instant},
+};
+
+#[cfg(feature = "cli")]
+use tracing::{Level, Subscriber};
+#[cfg(feature = "cli")]
+use tracing_subscriber::fmt::{time, MakeWriter};
+
+/// How events are written.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LogFormat {
+    /// For people: the time since the subscriber started, the level, the
+    /// spans and the message, then the fields as `key=value`.
+    #[default]
+    Text,
+    /// For machines: a JSON object per line.
+    Json,
+}
+
+impl FromStr for LogFormat {
+    type Err = String;
+
+    fn from_str(format: &str) -> Result<Self, Self::Err> {
+        match format.to_ascii_lowercase().as_str() {
+            "text" => Ok(LogFormat::Text),
+            "json" => Ok(LogFormat::Json),
+            _ => Err(format!("unknown log format `{format}`")),
+        }
+    }
+}
+
+///
+///     Build a subscriber writing the events of at least some level.
+///
+///     Parameters
+///     ----------
+///     level :
+///         The least important events written.
+///     format :
+///         How they are written.
+///     writer :
+///         Where they are written.
+///
+///     Returns
+///     -------
+///     subscriber : `Box<dyn Subscriber + Send + Sync>`
+///         The subscriber, to install with `tracing::subscriber`.
+///
+#[cfg(feature = "cli")]
+pub fn subscriber<W>(level: Level, format: LogFormat, writer: W) -> Box<dyn Subscriber + Send + Sync>
+where
+    W: for<'writer> MakeWriter<'writer> + Send + Sync + 'static,
+{
+    let builder = tracing_subscriber::fmt()
+        .with_max_level(level)
+        .with_target(false)
+        .with_writer(writer);
+    match format {
+        LogFormat::Text => Box::new(builder.with_timer(time::uptime()).finish()),
+        LogFormat::Json => Box::new(builder.json().with_current_span(false).with_span_list(true).finish()),
+    }
+}
+
+/// The command line options choosing the log of a tool.
+#[cfg(feature = "cli")]
+#[derive(clap::Args, Clone, Debug)]
+pub struct LogArgs {
+    /// The least important events to log: error, warn, info, debug or
+    /// trace.
+    #[arg(long, default_value = "info")]
+    pub verbosity: Level,
+    /// How to write the log to stderr: text or json.
+    #[arg(long, default_value = "text")]
+    pub log_format: LogFormat,
+}
+
+#[cfg(feature = "cli")]
+impl LogArgs {
+    /// Install a subscriber writing to stderr as chosen, unless the process
+    /// has one already.
+    pub fn init(&self) {
+        // Logging must not fail the tool.
+        let _ = tracing::subscriber::set_global_default(subscriber(self.verbosity, self.log_format, std::io::stderr));
+    }
+}
+
+/// The blocks, transactions and gas applied over some time.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Throughput {
+    pub blocks: u64,
+    pub transactions: u64,
+    pub gas: u128,
+    pub elapsed: Duration,
+}
+
+impl Throughput {
+    pub fn blocks_per_second(&self) -> f64 {
+        self.blocks as f64 / self.elapsed.as_secs_f64().max(f64::EPSILON)
+    }
+
+    pub fn gas_per_second(&self) -> f64 {
+        self.gas as f64 / self.elapsed.as_secs_f64().max(f64::EPSILON)
+    }
+}
+
+/// Rounded, to keep the lines short.
+fn round(value: f64) -> f64 {
+    (value * 100.0).round() / 100.0
+}
+
+/// Reports throughput at most once per interval while blocks are applied,
+/// then a summary.
+#[derive(Clone, Debug)]
+pub struct Progress {
+    interval: Duration,
+    start: Instant,
+    window_start: Instant,
+    window: Throughput,
+    total: Throughput,
+}
+
+impl Progress {
+    /// Report at most once per `interval`, starting now.
+    pub fn new(interval: Duration) -> Self {
+        Self::starting_at(interval, Instant::now())
+    }
+
+    /// Report at most once per `interval`, counting from `start`.
+    pub fn starting_at(interval: Duration, start: Instant) -> Self {
+        Self {
+            interval,
+            start,
+            window_start: start,
+            window: Throughput::default(),
+            total: Throughput::default(),
+        }
+    }
+
+    /// Count a block applied now, and report if the interval is over.
+    pub fn record_block(&mut self, number: u64, transactions: u64, gas_used: u64) -> Option<Throughput> {
+        self.record_block_at(Instant::now(), number, transactions, gas_used)
+    }
+
+    ///
+    ///     Count a block applied at `now`.
+    ///
+    ///     Parameters
+    ///     ----------
+    ///     now :
+    ///         When the block was applied.
+    ///     number :
+    ///         Its number, reported as the head.
+    ///     transactions :
+    ///         How many transactions it has.
+    ///     gas_used :
+    ///         The gas they used.
+    ///
+    ///     Returns
+    ///     -------
+    ///     throughput : `Option<Throughput>`
+    ///         The throughput since the last report, logged at info, if the
+    ///         interval is over.
+    ///
+    pub fn record_block_at(
+        &mut self,
+        now: Instant,
+        number: u64,
+        transactions: u64,
+        gas_used: u64,
+    ) -> Option<Throughput> {
+        for throughput in [&mut self.window, &mut self.total] {
+            throughput.blocks += 1;
+            throughput.transactions += transactions;
+            throughput.gas += u128::from(gas_used);
+        }
+        #[cfg(feature = "metrics")]
+        super::metrics::record_block(number, transactions, gas_used);
+        let elapsed = now.saturating_duration_since(self.window_start);
+        if elapsed < self.interval {
+            return None;
+        }
+        let report = Throughput { elapsed, ..self.window };
+        tracing::info!(
+            head = number,
+            blocks = report.blocks,
+            transactions = report.transactions,
+            blocks_per_second = round(report.blocks_per_second()),
+            mgas_per_second = round(report.gas_per_second() / 1e6),
+            "progress"
+        );
+        #[cfg(feature = "metrics")]
+        super::metrics::record_throughput(&report);
+        self.window = Throughput::default();
+        self.window_start = now;
+        Some(report)
+    }
+
+    /// The throughput since the start, logged at info.
+    pub fn finish(&self) -> Throughput {
+        let total = Throughput {
+            elapsed: self.start.elapsed(),
+            ..self.total
+        };
+        tracing::info!(
+            blocks = total.blocks,
+            transactions = total.transactions,
+            blocks_per_second = round(total.blocks_per_second()),
+            mgas_per_second = round(total.gas_per_second() / 1e6),
+            "done"
+        );
+        total
+    }
+}
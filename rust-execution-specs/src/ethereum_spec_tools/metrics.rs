@@ -0,0 +1,153 @@
+//!
+//! # Metrics
+//!
+//! The metrics of the tools applying a chain, next to those of the spec in
+//! `ethereum::metrics`, and an HTTP endpoint serving them all to Prometheus:
+//!
+//! ```text
+//! sync --metrics-addr 127.0.0.1:9100
+//! curl http://127.0.0.1:9100/metrics
+//! ```
+//!
+//! The counters of blocks, transactions and gas go up with every block
+//! `logging::Progress` records; the gauges of blocks and gas per second are
+//! set on each of its reports, and so cover its last interval.
+//!
+
+use std::{
+    io::{self, BufRead, BufReader, Write},
+    net::{SocketAddr, TcpListener, TcpStream},
+    thread,
+    time::Duration,
+};
+
+use crate::ethereum::metrics::{render, Counter, Gauge, Histogram, Metric, SPEC_METRICS};
+
+use super::logging::Throughput;
+
+/// Blocks applied, or validated with `sync --validate-only`.
+pub static BLOCKS: Counter = Counter::new("sync_blocks_total", "Blocks applied.");
+
+/// Transactions in the blocks applied.
+pub static TRANSACTIONS: Counter = Counter::new("sync_transactions_total", "Transactions in the blocks applied.");
+
+/// Gas used by the blocks applied.
+pub static GAS: Counter = Counter::new("sync_gas_total", "Gas used by the blocks applied.");
+
+/// The number of the last block applied.
+pub static HEAD: Gauge = Gauge::new("sync_head", "Number of the last block applied.");
+
+/// Blocks applied per second, over the last progress interval.
+pub static BLOCKS_PER_SECOND: Gauge = Gauge::new(
+    "sync_blocks_per_second",
+    "Blocks applied per second over the last progress interval.",
+);
+
+/// Gas used per second, over the last progress interval.
+pub static GAS_PER_SECOND: Gauge = Gauge::new(
+    "sync_gas_per_second",
+    "Gas used per second over the last progress interval.",
+);
+
+/// How long applying a block took, in seconds.
+pub static BLOCK_SECONDS: Histogram = Histogram::new(
+    "sync_block_seconds",
+    "Seconds taken to apply a block.",
+    &[0.001, 0.005, 0.01, 0.05, 0.1, 0.5, 1.0, 5.0],
+);
+
+/// The metrics of the tools.
+pub static TOOL_METRICS: &[&dyn Metric] = &[
+    &BLOCKS,
+    &TRANSACTIONS,
+    &GAS,
+    &HEAD,
+    &BLOCKS_PER_SECOND,
+    &GAS_PER_SECOND,
+    &BLOCK_SECONDS,
+];
+
+/// Count a block, as `Progress` records it.
+pub fn record_block(number: u64, transactions: u64, gas_used: u64) {
+    BLOCKS.increment();
+    TRANSACTIONS.add(transactions);
+    GAS.add(gas_used);
+    HEAD.set(number as f64);
+}
+
+/// Set the gauges of the throughput `Progress` reports.
+pub fn record_throughput(throughput: &Throughput) {
+    BLOCKS_PER_SECOND.set(throughput.blocks_per_second());
+    GAS_PER_SECOND.set(throughput.gas_per_second());
+}
+
+/// Observe how long applying a block took.
+pub fn record_block_time(elapsed: Duration) {
+    BLOCK_SECONDS.observe(elapsed.as_secs_f64());
+}
+
+/// The metrics of the spec and the tools, in the Prometheus text format.
+pub fn render_all() -> String {
+    render(SPEC_METRICS) + &render(TOOL_METRICS)
+}
+
+fn handle_connection(stream: TcpStream) -> io::Result<()> {
+    let mut reader = BufReader::new(&stream);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 || line.trim().is_empty() {
+            break;
+        }
+    }
+
+    let path = request_line.split_whitespace().nth(1).unwrap_or_default();
+    let (status, body) = match (request_line.starts_with("GET "), path) {
+        (true, "/metrics") => ("200 OK", render_all()),
+        (true, _) => ("404 Not Found", String::new()),
+        (false, _) => ("405 Method Not Allowed", String::new()),
+    };
+    write!(
+        &stream,
+        "HTTP/1.1 {status}\r\nContent-Type: text/plain; version=0.0.4\r\n\
+         Content-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    )
+}
+
+///
+///     Answer `GET /metrics` on `listener`, one connection at a time.
+///
+pub fn serve(listener: TcpListener) -> io::Result<()> {
+    for stream in listener.incoming() {
+        // A scraper that goes away only loses its own answer.
+        let _ = handle_connection(stream?);
+    }
+    Ok(())
+}
+
+///
+///     Serve the metrics on `addr` from a thread of their own.
+///
+///     Parameters
+///     ----------
+///     addr :
+///         Where to listen, such as `127.0.0.1:9100`; port 0 picks one.
+///
+///     Returns
+///     -------
+///     addr : `SocketAddr`
+///         Where the endpoint listens.
+///
+///     Raises
+///     ------
+///     io::Error :
+///         If `addr` can't be bound.
+///
+pub fn spawn(addr: SocketAddr) -> io::Result<SocketAddr> {
+    let listener = TcpListener::bind(addr)?;
+    let local_addr = listener.local_addr()?;
+    thread::spawn(move || serve(listener));
+    Ok(local_addr)
+}
@@ -4,4 +4,47 @@
 //! Library of utilities and tools necessary for rendering (or otherwise working
 //! with) the Ethereum specifications.
 //!
-pub mod forks;
\ No newline at end of file
+//! They all need `std`.
+//!
+#[cfg(feature = "std")]
+pub mod access_list;
+#[cfg(feature = "std")]
+pub mod block_builder;
+#[cfg(feature = "std")]
+pub mod block_rlp;
+#[cfg(feature = "std")]
+pub mod chain;
+#[cfg(feature = "std")]
+pub mod chain_info;
+#[cfg(feature = "std")]
+pub mod chain_spec;
+#[cfg(feature = "std")]
+pub mod engine;
+#[cfg(feature = "std")]
+pub mod evm_tools;
+#[cfg(feature = "std")]
+pub mod forks;
+#[cfg(feature = "std")]
+pub mod gas_estimation;
+#[cfg(feature = "std")]
+pub mod header_archive;
+#[cfg(feature = "std")]
+pub mod hive;
+#[cfg(feature = "std")]
+pub mod logging;
+#[cfg(feature = "metrics")]
+pub mod metrics;
+#[cfg(feature = "std")]
+pub mod replay;
+#[cfg(feature = "std")]
+pub mod rpc;
+#[cfg(feature = "std")]
+pub mod rpc_server;
+#[cfg(feature = "std")]
+pub mod state_divergence;
+#[cfg(feature = "std")]
+pub mod test_env;
+#[cfg(feature = "std")]
+pub mod test_fixtures;
+#[cfg(feature = "std")]
+pub mod transaction_index;
@@ -0,0 +1,549 @@
+//!
+//! # Transaction Replay
+//!
+//! Executes a single transaction of a live chain with the spec, and compares
+//! what it did with what the node says it did: the gas it used and its logs,
+//! from `eth_getTransactionReceipt`, and the changes it made to the state,
+//! from `debug_traceTransaction` with the `prestateTracer` in `diffMode`.
+//!
+//! The state before the transaction comes from either
+//!
+//! - [Prestate::Tracer], the `prestateTracer` of `debug_traceTransaction`,
+//!   which gives every account the transaction touches as it was just
+//!   before it, or
+//! - [Prestate::Proofs], `eth_getProof` and `eth_getCode` after the block
+//!   before, checked against its state root. The transactions before it in
+//!   its block are executed first, and the accounts and slots they all read
+//!   are fetched as execution gets to them, see `run_on_demand`. A node that
+//!   doesn't trace can still serve it, but it has to keep the states of
+//!   past blocks, and an account that exists but is empty can't be told
+//!   from one that doesn't.
+//!
+//! Without `debug_traceTransaction`, the state changes aren't compared, see
+//! [Comparison::state_compared].
+//!
+
+use std::{
+    collections::{BTreeSet, HashMap},
+    fmt::{self, LowerHex},
+    str::FromStr,
+};
+
+use serde_json::{json, Map, Value};
+
+use super::{
+    evm_tools::{
+        alloc::{json_uint, load_alloc},
+        prestate::diff_mode_json,
+        t8n::transaction_hash,
+    },
+    forks::{ForkSchedule, HardFork},
+    rpc::{Request, RpcBlock, RpcClient, RpcError, RpcTransactions, Transport},
+};
+use crate::ethereum::{
+    base_types::{Bytes32, Uint, U256},
+    exceptions::EthereumException,
+    frontier::{
+        fork::{process_transaction, recover_sender},
+        fork_types::{keccak256, Account, Address, Hash32, Log, Root, Transaction},
+        historical_state::run_on_demand,
+        proof::verify_proof,
+        state::{
+            begin_transaction, mark_storage, set_account, set_storage, transaction_diff, State, StateAccesses,
+            StateDiff,
+        },
+        trie::EMPTY_TRIE_ROOT,
+        vm::{BlockEnvironment, TxEnvironment},
+    },
+    utils::hexadecimal::hex,
+};
+
+fn quantity(number: impl LowerHex) -> Value {
+    Value::String(format!("{number:#x}"))
+}
+
+fn to_u64(number: &Uint) -> Result<u64, EthereumException> {
+    u64::try_from(number).map_err(|_| EthereumException::ValueError)
+}
+
+/// Why a transaction couldn't be replayed or compared.
+#[derive(Debug)]
+pub enum ReplayError {
+    /// A request to the node failed.
+    Rpc(RpcError),
+    /// The node's answer doesn't hold up, or the spec rejects the
+    /// transaction.
+    Exception(EthereumException),
+    /// The node doesn't have what the replay needs, or the transaction is
+    /// of a fork that isn't implemented.
+    Unavailable(String),
+}
+
+impl From<RpcError> for ReplayError {
+    fn from(error: RpcError) -> Self {
+        ReplayError::Rpc(error)
+    }
+}
+
+impl From<EthereumException> for ReplayError {
+    fn from(exception: EthereumException) -> Self {
+        ReplayError::Exception(exception)
+    }
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReplayError::Rpc(error) => write!(f, "{error}"),
+            ReplayError::Exception(exception) => write!(f, "{exception:?}"),
+            ReplayError::Unavailable(message) => write!(f, "{message}"),
+        }
+    }
+}
+
+/// Where the state before the transaction comes from.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Prestate {
+    /// `debug_traceTransaction` with the `prestateTracer`.
+    #[default]
+    Tracer,
+    /// `eth_getProof` and `eth_getCode` after the block before.
+    Proofs,
+}
+
+impl FromStr for Prestate {
+    type Err = String;
+
+    fn from_str(prestate: &str) -> Result<Self, Self::Err> {
+        match prestate {
+            "tracer" => Ok(Prestate::Tracer),
+            "proofs" => Ok(Prestate::Proofs),
+            _ => Err(format!(
+                "unknown prestate source `{prestate}`, expected tracer or proofs"
+            )),
+        }
+    }
+}
+
+/// What the spec made of a transaction.
+#[derive(Clone, Debug)]
+pub struct Replay {
+    pub hash: Hash32,
+    pub block_number: u64,
+    /// The position of the transaction in its block.
+    pub index: usize,
+    pub gas_used: Uint,
+    pub logs: Vec<Log>,
+    /// The changes the transaction made to the state.
+    pub diff: StateDiff,
+}
+
+fn logs_json(logs: &[Log]) -> Value {
+    logs.iter()
+        .map(|log| {
+            json!({
+                "address": hex(&log.address),
+                "topics": log.topics.iter().map(|topic| hex(topic)).collect::<Vec<_>>(),
+                "data": hex(&log.data),
+            })
+        })
+        .collect()
+}
+
+impl Replay {
+    /// The replay, with the state changes as the `prestateTracer` writes
+    /// them in `diffMode`.
+    pub fn to_json(&self) -> Value {
+        json!({
+            "transactionHash": hex(&self.hash),
+            "blockNumber": quantity(self.block_number),
+            "transactionIndex": quantity(self.index),
+            "gasUsed": quantity(&self.gas_used),
+            "logs": logs_json(&self.logs),
+            "stateDiff": diff_mode_json(&self.diff),
+        })
+    }
+}
+
+/// A value the spec and the node disagree on.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Difference {
+    /// Where the value is, such as `stateDiff.post.0x….balance`.
+    pub field: String,
+    pub spec: Value,
+    pub network: Value,
+}
+
+impl fmt::Display for Difference {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: the spec has {}, the node {}",
+            self.field, self.spec, self.network
+        )
+    }
+}
+
+/// How a replay compares with the node.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Comparison {
+    pub differences: Vec<Difference>,
+    /// Whether the node traced the state changes to compare them with.
+    pub state_compared: bool,
+}
+
+/// The transaction, its block and where it is in it.
+struct Located {
+    block: RpcBlock,
+    transactions: Vec<Transaction>,
+    index: usize,
+}
+
+fn locate<T: Transport>(client: &RpcClient<T>, hash: &Hash32) -> Result<Located, ReplayError> {
+    let transaction = client.call(&Request::get_transaction_by_hash(hash))?;
+    if transaction.is_null() {
+        return Err(ReplayError::Unavailable(format!(
+            "the node doesn't have transaction {}",
+            hex(hash)
+        )));
+    }
+    if transaction["blockNumber"].is_null() {
+        return Err(ReplayError::Unavailable(format!(
+            "transaction {} isn't in a block yet",
+            hex(hash)
+        )));
+    }
+    let number = to_u64(&json_uint(&transaction["blockNumber"])?)?;
+    let index = to_u64(&json_uint(&transaction["transactionIndex"])?)? as usize;
+
+    let block = client
+        .get_block_by_number(number, true)?
+        .ok_or_else(|| ReplayError::Unavailable(format!("the node doesn't have block {number}")))?;
+    let transactions = match &block.transactions {
+        RpcTransactions::Full(transactions) => transactions.clone(),
+        RpcTransactions::Hashes(hashes) if hashes.is_empty() => vec![],
+        RpcTransactions::Hashes(_) => {
+            return Err(EthereumException::JsonDecodeError("expected full transactions".to_owned()).into())
+        }
+    };
+    if transactions.get(index).map(transaction_hash) != Some(*hash) {
+        return Err(ReplayError::Unavailable(format!(
+            "block {number} doesn't have transaction {} at {index}",
+            hex(hash)
+        )));
+    }
+    Ok(Located {
+        block,
+        transactions,
+        index,
+    })
+}
+
+/// The hashes of the 256 blocks before `number`, oldest first, as
+/// `BLOCKHASH` sees them.
+fn block_hashes<T: Transport>(client: &RpcClient<T>, number: u64) -> Result<Vec<Hash32>, ReplayError> {
+    let first = number.saturating_sub(256);
+    client
+        .get_blocks_by_number(first..number, false)?
+        .into_iter()
+        .zip(first..)
+        .map(|(block, number)| {
+            block
+                .map(|block| block.hash)
+                .ok_or_else(|| ReplayError::Unavailable(format!("the node doesn't have block {number}")))
+        })
+        .collect()
+}
+
+/// The accounts and slots fetched with `eth_getProof` so far.
+struct Proofs<'a, T> {
+    client: &'a RpcClient<T>,
+    number: u64,
+    state_root: Root,
+    /// Each account, `None` if it is empty, and whether it has storage.
+    accounts: HashMap<Address, (Option<Account>, bool)>,
+    slots: HashMap<(Address, Bytes32), U256>,
+}
+
+impl<T: Transport> Proofs<'_, T> {
+    /// Fetch and check what `load` needs that isn't fetched yet.
+    fn fetch(&mut self, address: &Address, keys: &BTreeSet<Bytes32>) -> Result<(), ReplayError> {
+        let keys: Vec<Bytes32> = keys
+            .iter()
+            .filter(|key| !self.slots.contains_key(&(*address, **key)))
+            .copied()
+            .collect();
+        if self.accounts.contains_key(address) && keys.is_empty() {
+            return Ok(());
+        }
+        let proof = self.client.get_proof(address, &keys, self.number)?;
+        verify_proof(&self.state_root, &proof)?;
+        let code = match proof.code_hash == keccak256(&[]) {
+            true => Default::default(),
+            false => {
+                let code = self.client.get_code(address, self.number)?;
+                if keccak256(&code) != proof.code_hash {
+                    return Err(EthereumException::ValueError.into());
+                }
+                code
+            }
+        };
+        let is_empty = proof.nonce == Uint::default()
+            && proof.balance == U256::default()
+            && code.is_empty()
+            && proof.storage_hash == EMPTY_TRIE_ROOT();
+        let account = (!is_empty).then(|| Account {
+            nonce: proof.nonce.clone(),
+            balance: proof.balance.clone(),
+            code: code.into(),
+        });
+        self.accounts
+            .insert(*address, (account, proof.storage_hash != EMPTY_TRIE_ROOT()));
+        for slot in proof.storage_proof {
+            self.slots.insert((*address, slot.key), slot.value);
+        }
+        Ok(())
+    }
+
+    /// The part of the state after the block before with `accesses`.
+    fn load(&mut self, accesses: &StateAccesses) -> Result<State, ReplayError> {
+        let mut addresses = accesses.accounts.clone();
+        addresses.extend(accesses.storage.keys());
+        addresses.extend(&accesses.storage_presence);
+
+        let mut state = State::default();
+        for address in &addresses {
+            let keys = accesses.storage.get(address).cloned().unwrap_or_default();
+            self.fetch(address, &keys)?;
+            let (account, has_storage) = &self.accounts[address];
+            let Some(account) = account else {
+                continue;
+            };
+            set_account(&mut state, *address, Some(account.clone()));
+            for key in &keys {
+                let value = &self.slots[&(*address, *key)];
+                if *value != U256::default() {
+                    set_storage(&mut state, *address, key, value.clone());
+                }
+            }
+            if *has_storage {
+                mark_storage(&mut state, *address);
+            }
+        }
+        Ok(state)
+    }
+}
+
+///
+///     Replay a transaction of a live chain with the spec.
+///
+///     Parameters
+///     ----------
+///     client :
+///         The node, which must keep the state of the block of the
+///         transaction, as archive nodes do.
+///     schedule :
+///         The forks of the chain.
+///     hash :
+///         The hash of the transaction.
+///     prestate :
+///         Where the state before the transaction comes from.
+///
+///     Returns
+///     -------
+///     replay : `Replay`
+///         The gas the transaction used, its logs and the changes it made.
+///
+///     Raises
+///     ------
+///     Unavailable :
+///         If the node doesn't have the transaction, its block or the
+///         blocks before it, or the block is of a fork that isn't
+///         implemented.
+///     Exception :
+///         If a proof doesn't match the state root of the block before, or
+///         the spec rejects the transaction, or one before it.
+///
+pub fn replay_transaction<T: Transport>(
+    client: &RpcClient<T>,
+    schedule: &ForkSchedule,
+    hash: &Hash32,
+    prestate: Prestate,
+) -> Result<Replay, ReplayError> {
+    let Located {
+        block,
+        transactions,
+        index,
+    } = locate(client, hash)?;
+    let header = &block.header;
+    let number = to_u64(&header.number)?;
+    let timestamp = u64::try_from(&header.timestamp).unwrap_or(u64::MAX);
+    let parent_total_difficulty = block
+        .total_difficulty
+        .as_ref()
+        .map(|total| total.clone() - &header.difficulty)
+        .unwrap_or_default();
+    let fork = &schedule.forks()[schedule.active(number, timestamp, &parent_total_difficulty)];
+    if fork.fork != Some(HardFork::Frontier) {
+        return Err(ReplayError::Unavailable(format!(
+            "block {number} is of {}, which isn't implemented",
+            fork.name
+        )));
+    }
+
+    let block_env = BlockEnvironment {
+        block_hashes: block_hashes(client, number)?,
+        coinbase: header.coinbase,
+        number: header.number.clone(),
+        gas_limit: header.gas_limit.clone(),
+        time: header.timestamp.clone(),
+        difficulty: header.difficulty.clone(),
+    };
+    // With proofs, the transactions before it in the block run first.
+    let earlier = match prestate {
+        Prestate::Tracer => &transactions[..0],
+        Prestate::Proofs => &transactions[..index],
+    };
+    let tx_env = |tx: &Transaction| -> Result<TxEnvironment, EthereumException> {
+        Ok(TxEnvironment {
+            origin: recover_sender(tx)?,
+            gas_price: tx.gas_price.clone(),
+        })
+    };
+    let tx_envs = earlier.iter().map(tx_env).collect::<Result<Vec<_>, _>>()?;
+    let target = &transactions[index];
+    let target_env = tx_env(target)?;
+    let execute = |state: &mut State| -> Result<(Uint, Vec<Log>, StateDiff), EthereumException> {
+        for (tx, tx_env) in earlier.iter().zip(&tx_envs) {
+            process_transaction(&block_env, tx_env, state, tx)?;
+        }
+        begin_transaction(state);
+        let (gas_used, logs) = process_transaction(&block_env, &target_env, state, target)?;
+        Ok((gas_used, logs, transaction_diff(state)))
+    };
+
+    let (gas_used, logs, diff) = match prestate {
+        Prestate::Tracer => {
+            let trace = client.call(&Request::trace_transaction(hash, json!({ "tracer": "prestateTracer" })))?;
+            let mut state = load_alloc(&trace.to_string())?;
+            execute(&mut state)?
+        }
+        Prestate::Proofs => {
+            let parent = number
+                .checked_sub(1)
+                .ok_or_else(|| ReplayError::Unavailable("the genesis block has no transactions".to_owned()))?;
+            let parent_header = client
+                .get_block_by_number(parent, false)?
+                .ok_or_else(|| ReplayError::Unavailable(format!("the node doesn't have block {parent}")))?
+                .header;
+            let mut proofs = Proofs {
+                client,
+                number: parent,
+                state_root: parent_header.state_root,
+                accounts: HashMap::new(),
+                slots: HashMap::new(),
+            };
+            run_on_demand(|accesses| proofs.load(accesses), execute)??
+        }
+    };
+    Ok(Replay {
+        hash: *hash,
+        block_number: number,
+        index,
+        gas_used,
+        logs,
+        diff,
+    })
+}
+
+/// Note every value that differs between `spec` and `network`, field by
+/// field, under `field`.
+fn diff_json(field: &str, spec: &Value, network: &Value, differences: &mut Vec<Difference>) {
+    match (spec, network) {
+        (Value::Object(spec), Value::Object(network)) => {
+            let keys: BTreeSet<&String> = spec.keys().chain(network.keys()).collect();
+            for key in keys {
+                let (spec, network) = (
+                    spec.get(key).unwrap_or(&Value::Null),
+                    network.get(key).unwrap_or(&Value::Null),
+                );
+                diff_json(&format!("{field}.{key}"), spec, network, differences);
+            }
+        }
+        (Value::Array(spec_items), Value::Array(network_items)) if spec_items.len() == network_items.len() => {
+            for (n, (spec, network)) in spec_items.iter().zip(network_items).enumerate() {
+                diff_json(&format!("{field}[{n}]"), spec, network, differences);
+            }
+        }
+        (spec, network) if spec != network => differences.push(Difference {
+            field: field.to_owned(),
+            spec: spec.clone(),
+            network: network.clone(),
+        }),
+        _ => {}
+    }
+}
+
+///
+///     Compare a replay with what the node says the transaction did.
+///
+///     Parameters
+///     ----------
+///     client :
+///         The node.
+///     replay :
+///         The replay, from [replay_transaction].
+///
+///     Returns
+///     -------
+///     comparison : `Comparison`
+///         Every value of the receipt and, if the node traces, of the state
+///         changes the spec and the node disagree on.
+///
+///     Raises
+///     ------
+///     Unavailable :
+///         If the node has no receipt for the transaction.
+///
+pub fn compare<T: Transport>(client: &RpcClient<T>, replay: &Replay) -> Result<Comparison, ReplayError> {
+    let receipt = client.call(&Request::get_transaction_receipt(&replay.hash))?;
+    if receipt.is_null() {
+        return Err(ReplayError::Unavailable(format!(
+            "the node has no receipt for {}",
+            hex(&replay.hash)
+        )));
+    }
+    let mut differences = vec![];
+    diff_json(
+        "gasUsed",
+        &quantity(&replay.gas_used),
+        &quantity(&json_uint(&receipt["gasUsed"])?),
+        &mut differences,
+    );
+    // Only what the transaction logged, not where in the chain.
+    let logs: Value = receipt["logs"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .map(|log| {
+            let fields = ["address", "topics", "data"].map(|name| (name.to_owned(), log[name].clone()));
+            Value::Object(Map::from_iter(fields))
+        })
+        .collect();
+    diff_json("logs", &logs_json(&replay.logs), &logs, &mut differences);
+
+    let config = json!({ "tracer": "prestateTracer", "tracerConfig": { "diffMode": true } });
+    let state_compared = match client.call(&Request::trace_transaction(&replay.hash, config)) {
+        Ok(trace) => {
+            diff_json("stateDiff", &diff_mode_json(&replay.diff), &trace, &mut differences);
+            true
+        }
+        // The node doesn't trace.
+        Err(RpcError::Rpc { .. }) => false,
+        Err(error) => return Err(error.into()),
+    };
+    Ok(Comparison {
+        differences,
+        state_compared,
+    })
+}
@@ -0,0 +1,108 @@
+//!
+//! # replay-tx
+//!
+//! Replays a transaction of a live chain with the spec, and compares the gas
+//! it used, its logs and the changes it made to the state with what an
+//! archive node says:
+//!
+//! ```text
+//! replay-tx --rpc-url http://localhost:8545 0x5c504ed432cb51138bcf09aa5e8a410dd4a1e204ef84bfed1be16dfba1b22060
+//! ```
+//!
+//! The replay is written to stdout as JSON, and each value the spec and the
+//! node disagree on is logged; the exit status says whether there was any.
+//!
+//! The state before the transaction comes from `debug_traceTransaction`, or
+//! with `--prestate proofs` from `eth_getProof`, for nodes that don't trace.
+//!
+
+use std::{path::PathBuf, process::ExitCode};
+
+use clap::Parser;
+use execution_specs_rs::{
+    ethereum::{frontier::fork_types::Hash32, utils::hexadecimal::hex_to_hash},
+    ethereum_spec_tools::{
+        chain::Chain,
+        forks::ForkSchedule,
+        logging::LogArgs,
+        replay::{compare, replay_transaction, Prestate},
+        rpc::RpcClient,
+    },
+};
+
+#[derive(Parser, Debug)]
+#[command(about = "Replay a transaction from a JSON-RPC node with the spec, and compare it with the node")]
+struct Args {
+    /// The hash of the transaction.
+    #[arg(value_parser = parse_hash)]
+    hash: Hash32,
+    /// The http:// endpoint of the node, which must keep past states.
+    #[arg(long, default_value = "http://localhost:8545")]
+    rpc_url: String,
+    /// Where the state before the transaction comes from: tracer, for
+    /// `debug_traceTransaction`, or proofs, for `eth_getProof`.
+    #[arg(long, default_value = "tracer")]
+    prestate: Prestate,
+    /// A genesis file scheduling the forks of the chain, instead of
+    /// mainnet's.
+    #[arg(long)]
+    genesis: Option<PathBuf>,
+    #[command(flatten)]
+    log: LogArgs,
+}
+
+fn parse_hash(hash: &str) -> Result<Hash32, String> {
+    hex_to_hash(hash).map_err(|e| format!("{e:?}"))
+}
+
+fn schedule(args: &Args) -> Result<ForkSchedule, String> {
+    match &args.genesis {
+        Some(path) => {
+            let genesis = std::fs::read_to_string(path).map_err(|e| format!("{}: {e}", path.display()))?;
+            let chain = Chain::from_genesis(&genesis).map_err(|e| format!("{}: {e:?}", path.display()))?;
+            Ok(chain.schedule().clone())
+        }
+        None => Ok(ForkSchedule::mainnet()),
+    }
+}
+
+/// Replay the transaction, and whether the node agrees.
+fn replay(args: Args) -> Result<bool, String> {
+    let client = RpcClient::new(&args.rpc_url).map_err(|e| e.to_string())?;
+    let replay =
+        replay_transaction(&client, &schedule(&args)?, &args.hash, args.prestate).map_err(|e| e.to_string())?;
+    println!(
+        "{}",
+        serde_json::to_string_pretty(&replay.to_json()).expect("JSON values serialize")
+    );
+
+    let comparison = compare(&client, &replay).map_err(|e| e.to_string())?;
+    if !comparison.state_compared {
+        tracing::warn!("the node doesn't trace, the state changes aren't compared");
+    }
+    for difference in &comparison.differences {
+        tracing::error!(
+            field = %difference.field,
+            spec = %difference.spec,
+            node = %difference.network,
+            "differs from the node"
+        );
+    }
+    if comparison.differences.is_empty() {
+        tracing::info!(number = replay.block_number, index = replay.index, "matches the node");
+    }
+    Ok(comparison.differences.is_empty())
+}
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+    args.log.init();
+    match replay(args) {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+        Err(error) => {
+            tracing::error!("{error}");
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,727 @@
+//!
+//! # JSON-RPC Client
+//!
+//! A small blocking client for the JSON-RPC API of an Ethereum node, with
+//! typed wrappers for the methods the spec tools need to fetch blocks:
+//!
+//! ```no_run
+//! use execution_specs_rs::ethereum_spec_tools::rpc::RpcClient;
+//!
+//! let client = RpcClient::new("http://localhost:8545").unwrap();
+//! let block = client.get_block(1).unwrap().expect("block 1 is known");
+//! println!("{} transactions", block.transactions.len());
+//! ```
+//!
+//! Requests that fail for a reason that may go away, such as a dropped
+//! connection or an overloaded server, are retried with exponential backoff.
+//! Several requests can be sent at once with [RpcClient::batch].
+//!
+//! The built-in [HttpTransport] speaks plain HTTP/1.1, which is what local
+//! nodes serve. For anything else, such as TLS, implement [Transport].
+//!
+
+use std::{
+    fmt,
+    io::{Read, Write},
+    net::TcpStream,
+    sync::atomic::{AtomicU64, Ordering},
+    thread,
+    time::Duration,
+};
+
+use serde_json::{json, Value};
+
+use super::evm_tools::alloc::{json_str, json_uint};
+use crate::ethereum::{
+    base_types::{Bytes, Bytes32, Uint},
+    exceptions::EthereumException,
+    frontier::{
+        fork_types::{Address, Block, Hash32, Header, Transaction},
+        proof::AccountProof,
+    },
+    utils::hexadecimal::{
+        hex, hex_to_bytes, hex_to_bytes20, hex_to_bytes256, hex_to_bytes8, hex_to_hash,
+    },
+};
+
+/// Why a request failed.
+#[derive(Debug)]
+pub enum RpcError {
+    /// The request couldn't be sent, or its response couldn't be read.
+    Transport(String),
+    /// The server answered with an HTTP error status.
+    Http(u16),
+    /// The server answered with a JSON-RPC error.
+    Rpc { code: i64, message: String },
+    /// The response isn't of the expected shape.
+    Decode(EthereumException),
+}
+
+impl RpcError {
+    /// Whether sending the request again may succeed.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            RpcError::Transport(_) => true,
+            RpcError::Http(status) => *status == 429 || *status >= 500,
+            RpcError::Rpc { .. } | RpcError::Decode(_) => false,
+        }
+    }
+}
+
+impl fmt::Display for RpcError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RpcError::Transport(error) => write!(f, "transport error: {error}"),
+            RpcError::Http(status) => write!(f, "HTTP status {status}"),
+            RpcError::Rpc { code, message } => write!(f, "RPC error {code}: {message}"),
+            RpcError::Decode(error) => write!(f, "unexpected response: {error:?}"),
+        }
+    }
+}
+
+impl std::error::Error for RpcError {}
+
+impl From<EthereumException> for RpcError {
+    fn from(error: EthereumException) -> Self {
+        RpcError::Decode(error)
+    }
+}
+
+pub type Result<T, E = RpcError> = std::result::Result<T, E>;
+
+/// Sends the JSON body of a request, and returns the JSON body of the
+/// response.
+pub trait Transport {
+    fn post(&self, body: &str) -> Result<String>;
+}
+
+/// A [Transport] over plain HTTP/1.1, opening a connection per request.
+#[derive(Clone, Debug)]
+pub struct HttpTransport {
+    host: String,
+    port: u16,
+    path: String,
+    timeout: Duration,
+}
+
+impl HttpTransport {
+    ///
+    ///     Parameters
+    ///     ----------
+    ///     url :
+    ///         The endpoint, as `http://host[:port][/path]`.
+    ///
+    pub fn new(url: &str) -> Result<Self> {
+        let Some(rest) = url.strip_prefix("http://") else {
+            return Err(RpcError::Transport(format!(
+                "`{url}` is not an http:// URL; other schemes need their own Transport"
+            )));
+        };
+        let (authority, path) = match rest.find('/') {
+            Some(index) => (&rest[..index], &rest[index..]),
+            None => (rest, "/"),
+        };
+        let (host, port) = match authority.rsplit_once(':') {
+            Some((host, port)) => {
+                let port = port
+                    .parse()
+                    .map_err(|_| RpcError::Transport(format!("invalid port in `{url}`")))?;
+                (host, port)
+            }
+            None => (authority, 80),
+        };
+        Ok(Self {
+            host: host.to_owned(),
+            port,
+            path: path.to_owned(),
+            timeout: Duration::from_secs(30),
+        })
+    }
+
+    /// Give up on reading a response after `timeout`.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+}
+
+fn transport_error(error: std::io::Error) -> RpcError {
+    RpcError::Transport(error.to_string())
+}
+
+/// Decode a body sent with `Transfer-Encoding: chunked`.
+fn decode_chunked(mut body: &[u8]) -> Result<Vec<u8>> {
+    let invalid = || RpcError::Transport("invalid chunked body".to_owned());
+    let mut decoded = vec![];
+    loop {
+        let line_end = body.windows(2).position(|w| w == b"\r\n").ok_or_else(invalid)?;
+        let size = std::str::from_utf8(&body[..line_end]).map_err(|_| invalid())?;
+        let size = size.split(';').next().unwrap_or_default().trim();
+        let size = usize::from_str_radix(size, 16).map_err(|_| invalid())?;
+        body = &body[line_end + 2..];
+        if size == 0 {
+            return Ok(decoded);
+        }
+        decoded.extend_from_slice(body.get(..size).ok_or_else(invalid)?);
+        body = body.get(size + 2..).ok_or_else(invalid)?;
+    }
+}
+
+impl Transport for HttpTransport {
+    fn post(&self, body: &str) -> Result<String> {
+        let mut stream =
+            TcpStream::connect((self.host.as_str(), self.port)).map_err(transport_error)?;
+        stream.set_read_timeout(Some(self.timeout)).map_err(transport_error)?;
+        let request = format!(
+            "POST {} HTTP/1.1\r\nHost: {}:{}\r\nContent-Type: application/json\r\n\
+             Content-Length: {}\r\nConnection: close\r\n\r\n{body}",
+            self.path,
+            self.host,
+            self.port,
+            body.len()
+        );
+        stream.write_all(request.as_bytes()).map_err(transport_error)?;
+        let mut response = vec![];
+        stream.read_to_end(&mut response).map_err(transport_error)?;
+
+        let head_end = response
+            .windows(4)
+            .position(|w| w == b"\r\n\r\n")
+            .ok_or_else(|| RpcError::Transport("truncated HTTP response".to_owned()))?;
+        let head = String::from_utf8_lossy(&response[..head_end]).to_ascii_lowercase();
+        let body = &response[head_end + 4..];
+        let status = head
+            .split_whitespace()
+            .nth(1)
+            .and_then(|status| status.parse().ok())
+            .ok_or_else(|| RpcError::Transport("invalid HTTP status line".to_owned()))?;
+        if !(200..300).contains(&status) {
+            return Err(RpcError::Http(status));
+        }
+
+        let mut headers = head.lines().skip(1).filter_map(|line| line.split_once(':'));
+        let body = if headers.any(|(name, value)| {
+            name.trim() == "transfer-encoding" && value.trim() == "chunked"
+        }) {
+            decode_chunked(body)?
+        } else {
+            body.to_vec()
+        };
+        String::from_utf8(body).map_err(|_| RpcError::Transport("body is not UTF-8".to_owned()))
+    }
+}
+
+/// How often, and how patiently, failed requests are retried.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// The number of times a request is sent before giving up.
+    pub attempts: u32,
+    /// The wait before the first retry, doubled for each one after it.
+    pub initial_backoff: Duration,
+    /// The longest wait between two attempts.
+    pub max_backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            attempts: 5,
+            initial_backoff: Duration::from_millis(250),
+            max_backoff: Duration::from_secs(10),
+        }
+    }
+}
+
+/// A JSON-RPC method call, for sending alone or in a batch.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Request {
+    pub method: String,
+    pub params: Value,
+}
+
+fn quantity(number: u64) -> Value {
+    Value::String(format!("{number:#x}"))
+}
+
+impl Request {
+    pub fn new(method: &str, params: Value) -> Self {
+        Self {
+            method: method.to_owned(),
+            params,
+        }
+    }
+
+    /// `eth_blockNumber`.
+    pub fn block_number() -> Self {
+        Self::new("eth_blockNumber", json!([]))
+    }
+
+    /// `eth_getBlockByNumber`, with the full transactions or only their
+    /// hashes.
+    pub fn get_block_by_number(number: u64, full_transactions: bool) -> Self {
+        Self::new("eth_getBlockByNumber", json!([quantity(number), full_transactions]))
+    }
+
+    /// `eth_getUncleByBlockHashAndIndex`.
+    pub fn get_uncle_by_block_hash_and_index(block_hash: &Hash32, index: u64) -> Self {
+        Self::new(
+            "eth_getUncleByBlockHashAndIndex",
+            json!([hex(block_hash), quantity(index)]),
+        )
+    }
+
+    /// `debug_getRawHeader`.
+    pub fn get_raw_header(number: u64) -> Self {
+        Self::new("debug_getRawHeader", json!([quantity(number)]))
+    }
+
+    /// `debug_getRawBlock`.
+    pub fn get_raw_block(number: u64) -> Self {
+        Self::new("debug_getRawBlock", json!([quantity(number)]))
+    }
+
+    /// `debug_getRawReceipts`.
+    pub fn get_raw_receipts(number: u64) -> Self {
+        Self::new("debug_getRawReceipts", json!([quantity(number)]))
+    }
+
+    /// `eth_getProof`, of an account and some of its storage slots after
+    /// the block `number`.
+    pub fn get_proof(address: &Address, keys: &[Bytes32], number: u64) -> Self {
+        let keys: Vec<String> = keys.iter().map(|key| hex(key)).collect();
+        Self::new("eth_getProof", json!([hex(address), keys, quantity(number)]))
+    }
+
+    /// `eth_getCode`, after the block `number`.
+    pub fn get_code(address: &Address, number: u64) -> Self {
+        Self::new("eth_getCode", json!([hex(address), quantity(number)]))
+    }
+
+    /// `eth_getTransactionByHash`.
+    pub fn get_transaction_by_hash(hash: &Hash32) -> Self {
+        Self::new("eth_getTransactionByHash", json!([hex(hash)]))
+    }
+
+    /// `eth_getTransactionReceipt`.
+    pub fn get_transaction_receipt(hash: &Hash32) -> Self {
+        Self::new("eth_getTransactionReceipt", json!([hex(hash)]))
+    }
+
+    /// `debug_traceTransaction`, with a tracer and its options such as
+    /// `{"tracer": "prestateTracer"}`.
+    pub fn trace_transaction(hash: &Hash32, config: Value) -> Self {
+        Self::new("debug_traceTransaction", json!([hex(hash), config]))
+    }
+}
+
+/// The transactions of an `eth_getBlockByNumber` result.
+#[derive(Clone, Debug)]
+pub enum RpcTransactions {
+    Hashes(Vec<Hash32>),
+    Full(Vec<Transaction>),
+}
+
+/// A block as returned by `eth_getBlockByNumber`, with its ommers only
+/// named by hash.
+#[derive(Clone, Debug)]
+pub struct RpcBlock {
+    pub hash: Hash32,
+    pub header: Header,
+    pub transactions: RpcTransactions,
+    pub uncles: Vec<Hash32>,
+    /// The total difficulty of the chain up to the block, which nodes
+    /// stopped returning after the merge.
+    pub total_difficulty: Option<Uint>,
+}
+
+fn field<'a>(value: &'a Value, name: &str) -> Result<&'a str, EthereumException> {
+    json_str(&value[name])
+        .map_err(|_| EthereumException::JsonDecodeError(format!("missing field `{name}`")))
+}
+
+///
+///     Read a header from the JSON of a block or an uncle.
+///
+///     Parameters
+///     ----------
+///     value :
+///         The block, with the field names of `eth_getBlockByNumber`.
+///
+///     Returns
+///     -------
+///     hash : `Hash32`
+///         The hash the node gives the block.
+///     header : `Header`
+///         The header of the block.
+///
+pub fn parse_header(value: &Value) -> Result<(Hash32, Header), EthereumException> {
+    let header = Header {
+        parent_hash: hex_to_hash(field(value, "parentHash")?)?,
+        ommers_hash: hex_to_hash(field(value, "sha3Uncles")?)?,
+        coinbase: hex_to_bytes20(field(value, "miner")?)?,
+        state_root: hex_to_hash(field(value, "stateRoot")?)?,
+        transactions_root: hex_to_hash(field(value, "transactionsRoot")?)?,
+        receipt_root: hex_to_hash(field(value, "receiptsRoot")?)?,
+        bloom: hex_to_bytes256(field(value, "logsBloom")?)?,
+        difficulty: json_uint(&value["difficulty"])?,
+        number: json_uint(&value["number"])?,
+        gas_limit: json_uint(&value["gasLimit"])?,
+        gas_used: json_uint(&value["gasUsed"])?,
+        timestamp: json_uint(&value["timestamp"])?,
+        extra_data: hex_to_bytes(field(value, "extraData")?)?,
+        mix_digest: hex_to_hash(field(value, "mixHash")?)?,
+        nonce: hex_to_bytes8(field(value, "nonce")?)?,
+    };
+    Ok((hex_to_hash(field(value, "hash")?)?, header))
+}
+
+///
+///     Read a transaction from its JSON, as in the full transactions of
+///     `eth_getBlockByNumber`.
+///
+///     Parameters
+///     ----------
+///     value :
+///         The transaction.
+///
+///     Returns
+///     -------
+///     transaction : `Transaction`
+///         The transaction, without the fields nodes derive from it such as
+///         `from` and `hash`.
+///
+pub fn parse_transaction(value: &Value) -> Result<Transaction, EthereumException> {
+    let to = match &value["to"] {
+        Value::Null => None,
+        to => Some(hex_to_bytes20(json_str(to)?)?),
+    };
+    Ok(Transaction {
+        nonce: json_uint(&value["nonce"])?,
+        gas_price: json_uint(&value["gasPrice"])?,
+        gas: json_uint(&value["gas"])?,
+        to,
+        value: json_uint(&value["value"])?,
+        data: hex_to_bytes(field(value, "input")?)?,
+        v: json_uint(&value["v"])?,
+        r: json_uint(&value["r"])?,
+        s: json_uint(&value["s"])?,
+    })
+}
+
+impl RpcBlock {
+    ///
+    ///     Read a block from the result of `eth_getBlockByNumber`.
+    ///
+    pub fn from_json(value: &Value) -> Result<Self, EthereumException> {
+        let (hash, header) = parse_header(value)?;
+        let transactions = value["transactions"].as_array().ok_or_else(|| {
+            EthereumException::JsonDecodeError("missing field `transactions`".to_owned())
+        })?;
+        let transactions = if transactions.iter().all(Value::is_string) {
+            RpcTransactions::Hashes(
+                transactions
+                    .iter()
+                    .map(|hash| hex_to_hash(json_str(hash)?))
+                    .collect::<Result<_, _>>()?,
+            )
+        } else {
+            RpcTransactions::Full(transactions.iter().map(parse_transaction).collect::<Result<_, _>>()?)
+        };
+        let uncles = match &value["uncles"] {
+            Value::Array(uncles) => uncles
+                .iter()
+                .map(|hash| hex_to_hash(json_str(hash)?))
+                .collect::<Result<_, _>>()?,
+            _ => vec![],
+        };
+        let total_difficulty = match value.get("totalDifficulty") {
+            None | Some(Value::Null) => None,
+            Some(total_difficulty) => Some(json_uint(total_difficulty)?),
+        };
+        Ok(Self {
+            hash,
+            header,
+            transactions,
+            uncles,
+            total_difficulty,
+        })
+    }
+}
+
+/// A JSON-RPC client, sending requests through a [Transport].
+pub struct RpcClient<T = HttpTransport> {
+    transport: T,
+    retry: RetryPolicy,
+    next_id: AtomicU64,
+}
+
+impl RpcClient<HttpTransport> {
+    ///
+    ///     Create a client for an http:// endpoint.
+    ///
+    pub fn new(url: &str) -> Result<Self> {
+        Ok(Self::with_transport(HttpTransport::new(url)?))
+    }
+}
+
+/// Split a JSON-RPC response into its result or error.
+fn parse_response(response: &Value) -> Result<Value> {
+    if let Some(error) = response.get("error").filter(|error| !error.is_null()) {
+        return Err(RpcError::Rpc {
+            code: error["code"].as_i64().unwrap_or_default(),
+            message: error["message"].as_str().unwrap_or_default().to_owned(),
+        });
+    }
+    match response.get("result") {
+        Some(result) => Ok(result.clone()),
+        None => Err(EthereumException::JsonDecodeError(format!(
+            "response has no result: {response}"
+        ))
+        .into()),
+    }
+}
+
+fn parse_bytes(value: &Value) -> Result<Bytes> {
+    Ok(hex_to_bytes(json_str(value)?)?)
+}
+
+fn parse_optional<T>(
+    value: &Value,
+    parse: impl FnOnce(&Value) -> Result<T, EthereumException>,
+) -> Result<Option<T>> {
+    match value {
+        Value::Null => Ok(None),
+        value => Ok(Some(parse(value)?)),
+    }
+}
+
+impl<T: Transport> RpcClient<T> {
+    pub fn with_transport(transport: T) -> Self {
+        Self {
+            transport,
+            retry: RetryPolicy::default(),
+            next_id: AtomicU64::new(1),
+        }
+    }
+
+    /// Retry failed requests according to `retry`.
+    pub fn with_retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// The transport requests are sent through.
+    pub fn transport(&self) -> &T {
+        &self.transport
+    }
+
+    /// Send `body`, retrying with backoff while the failure is retryable.
+    fn post(&self, body: &Value) -> Result<Value> {
+        let body = body.to_string();
+        let mut backoff = self.retry.initial_backoff;
+        let mut attempt = 1;
+        loop {
+            let response = self.transport.post(&body).and_then(|response| {
+                serde_json::from_str(&response)
+                    .map_err(|e| EthereumException::JsonDecodeError(e.to_string()).into())
+            });
+            match response {
+                Err(error) if error.is_retryable() && attempt < self.retry.attempts => {
+                    thread::sleep(backoff);
+                    backoff = (backoff * 2).min(self.retry.max_backoff);
+                    attempt += 1;
+                }
+                response => return response,
+            }
+        }
+    }
+
+    fn envelope(&self, request: &Request) -> Value {
+        json!({
+            "jsonrpc": "2.0",
+            "id": self.next_id.fetch_add(1, Ordering::Relaxed),
+            "method": request.method,
+            "params": request.params,
+        })
+    }
+
+    ///
+    ///     Send a request.
+    ///
+    ///     Returns
+    ///     -------
+    ///     result : `Value`
+    ///         The `result` of the response.
+    ///
+    pub fn call(&self, request: &Request) -> Result<Value> {
+        parse_response(&self.post(&self.envelope(request))?)
+    }
+
+    ///
+    ///     Send several requests in one batch.
+    ///
+    ///     Returns
+    ///     -------
+    ///     results : `Vec<Result<Value>>`
+    ///         The result or error of each request, in the order of
+    ///         `requests`.
+    ///
+    pub fn batch(&self, requests: &[Request]) -> Result<Vec<Result<Value>>> {
+        if requests.is_empty() {
+            return Ok(vec![]);
+        }
+        let envelopes: Vec<Value> = requests.iter().map(|request| self.envelope(request)).collect();
+        let responses = self.post(&Value::Array(envelopes.clone()))?;
+        let Value::Array(responses) = responses else {
+            // Servers answer a batch they can't handle with a single error.
+            parse_response(&responses)?;
+            return Err(EthereumException::JsonDecodeError(
+                "batch response is not an array".to_owned(),
+            )
+            .into());
+        };
+        // Responses may come back in any order, and are matched by id.
+        Ok(envelopes
+            .iter()
+            .map(|envelope| {
+                match responses.iter().find(|response| response["id"] == envelope["id"]) {
+                    Some(response) => parse_response(response),
+                    None => Err(EthereumException::JsonDecodeError(format!(
+                        "no response to request {}",
+                        envelope["id"]
+                    ))
+                    .into()),
+                }
+            })
+            .collect())
+    }
+
+    /// The number of the latest block, from `eth_blockNumber`.
+    pub fn block_number(&self) -> Result<Uint> {
+        Ok(json_uint(&self.call(&Request::block_number())?)?)
+    }
+
+    /// The block `number`, from `eth_getBlockByNumber`, or `None` if the node
+    /// doesn't have it.
+    pub fn get_block_by_number(&self, number: u64, full_transactions: bool) -> Result<Option<RpcBlock>> {
+        let result = self.call(&Request::get_block_by_number(number, full_transactions))?;
+        parse_optional(&result, RpcBlock::from_json)
+    }
+
+    /// The blocks `numbers`, fetched in one batch.
+    pub fn get_blocks_by_number(
+        &self,
+        numbers: impl IntoIterator<Item = u64>,
+        full_transactions: bool,
+    ) -> Result<Vec<Option<RpcBlock>>> {
+        let requests: Vec<Request> = numbers
+            .into_iter()
+            .map(|number| Request::get_block_by_number(number, full_transactions))
+            .collect();
+        self.batch(&requests)?
+            .into_iter()
+            .map(|result| parse_optional(&result?, RpcBlock::from_json))
+            .collect()
+    }
+
+    /// The header of an ommer of `block_hash`, from
+    /// `eth_getUncleByBlockHashAndIndex`.
+    pub fn get_uncle_by_block_hash_and_index(&self, block_hash: &Hash32, index: u64) -> Result<Option<Header>> {
+        let result = self.call(&Request::get_uncle_by_block_hash_and_index(block_hash, index))?;
+        parse_optional(&result, |value| Ok(parse_header(value)?.1))
+    }
+
+    /// The RLP of the header `number`, from `debug_getRawHeader`.
+    pub fn get_raw_header(&self, number: u64) -> Result<Bytes> {
+        parse_bytes(&self.call(&Request::get_raw_header(number))?)
+    }
+
+    /// The RLP of the block `number`, from `debug_getRawBlock`.
+    pub fn get_raw_block(&self, number: u64) -> Result<Bytes> {
+        parse_bytes(&self.call(&Request::get_raw_block(number))?)
+    }
+
+    /// The RLP of each receipt of the block `number`, from
+    /// `debug_getRawReceipts`.
+    pub fn get_raw_receipts(&self, number: u64) -> Result<Vec<Bytes>> {
+        let result = self.call(&Request::get_raw_receipts(number))?;
+        let receipts = result.as_array().ok_or_else(|| {
+            EthereumException::JsonDecodeError(format!("expected an array, got {result}"))
+        })?;
+        receipts.iter().map(parse_bytes).collect()
+    }
+
+    /// The proof of an account and some of its storage slots after the
+    /// block `number`, from `eth_getProof`.
+    pub fn get_proof(&self, address: &Address, keys: &[Bytes32], number: u64) -> Result<AccountProof> {
+        Ok(AccountProof::from_json(&self.call(&Request::get_proof(address, keys, number))?)?)
+    }
+
+    /// The code of an account after the block `number`, from `eth_getCode`.
+    pub fn get_code(&self, address: &Address, number: u64) -> Result<Bytes> {
+        parse_bytes(&self.call(&Request::get_code(address, number))?)
+    }
+
+    ///
+    ///     Fetch the blocks `numbers` in full, with their ommers, in two
+    ///     batches.
+    ///
+    ///     Returns
+    ///     -------
+    ///     blocks : `Vec<(Hash32, Block)>`
+    ///         The hash the node gives each block, and the block. It stops
+    ///         at the first block the node doesn't have.
+    ///
+    pub fn get_blocks(&self, numbers: impl IntoIterator<Item = u64>) -> Result<Vec<(Hash32, Block)>> {
+        let blocks: Vec<RpcBlock> = self
+            .get_blocks_by_number(numbers, true)?
+            .into_iter()
+            .map_while(|block| block)
+            .collect();
+
+        let uncle_requests: Vec<Request> = blocks
+            .iter()
+            .flat_map(|block| {
+                (0..block.uncles.len() as u64)
+                    .map(|index| Request::get_uncle_by_block_hash_and_index(&block.hash, index))
+            })
+            .collect();
+        let mut uncles = self.batch(&uncle_requests)?.into_iter();
+
+        let mut result = vec![];
+        for block in blocks {
+            let mut ommers = vec![];
+            for _ in &block.uncles {
+                let uncle = uncles.next().expect("one response per request")?;
+                ommers.push(parse_header(&uncle)?.1);
+            }
+            // Without transactions, the two forms can't be told apart.
+            let transactions = match block.transactions {
+                RpcTransactions::Full(transactions) => transactions,
+                RpcTransactions::Hashes(hashes) if hashes.is_empty() => vec![],
+                RpcTransactions::Hashes(_) => {
+                    return Err(EthereumException::JsonDecodeError(
+                        "expected full transactions".to_owned(),
+                    )
+                    .into())
+                }
+            };
+            result.push((
+                block.hash,
+                Block {
+                    header: block.header,
+                    transactions,
+                    ommers,
+                },
+            ));
+        }
+        Ok(result)
+    }
+
+    ///
+    ///     Fetch a block in full, with its ommers.
+    ///
+    pub fn get_block(&self, number: u64) -> Result<Option<Block>> {
+        Ok(self.get_blocks([number])?.pop().map(|(_, block)| block))
+    }
+}
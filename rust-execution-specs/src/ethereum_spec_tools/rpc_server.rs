@@ -0,0 +1,622 @@
+//!
+//! # JSON-RPC Server
+//!
+//! Serves the read-only execution methods of the JSON-RPC API from a fixed
+//! state, so that wallets and developer tools can be pointed at the spec to
+//! compare its behaviour with a client's:
+//!
+//! - `eth_call`, `eth_estimateGas`, `eth_createAccessList` and
+//!   `debug_traceCall`, which run a call against the state without changing
+//!   it;
+//! - `eth_getProof`, `eth_getBalance`, `eth_getCode`,
+//!   `eth_getTransactionCount` and `eth_getStorageAt`;
+//! - `debug_storageRangeAt`, which lists an account's storage slots in the
+//!   order of its storage trie;
+//! - `eth_chainId` and `eth_blockNumber`, which tools ask for on connecting;
+//! - `eth_getTransactionByHash` and `eth_getTransactionReceipt`, for the
+//!   transactions of a server started [SpecRpc::from_chain].
+//!
+//! Block tags are accepted and ignored, and every call runs in the one block
+//! the server was started with. The state can be a geth style allocation, a
+//! state dump, or the `pre` of a state test fixture, see [load_state], or
+//! that after the head of a chain.
+//!
+//! A chain that keeps the states of its blocks, see
+//! `Chain::with_state_history`, is served at the blocks before its head as
+//! well: `eth_call` and the methods reading an account or a storage slot
+//! take the number of a block, and then run after it, on its state.
+//!
+
+use std::{
+    cell::RefCell,
+    io::{self, BufRead, BufReader, Read, Write},
+    net::{TcpListener, TcpStream},
+    rc::Rc,
+};
+
+use serde_json::{json, Value};
+
+use super::{
+    access_list::access_list,
+    evm_tools::{
+        alloc::{json_str, json_uint, load_alloc},
+        dump::load_state_dump,
+        eip3155::Eip3155Tracer,
+    },
+    chain::Chain,
+    gas_estimation::{estimate_gas, run_call, CallRequest},
+    rpc::RpcError,
+};
+use crate::ethereum::{
+    access_list::access_list_counts,
+    base_types::{u256_to_be_bytes32, Bytes, Uint},
+    exceptions::EthereumException,
+    frontier::{
+        fork_types::{Account, Hash32, Header, EMPTY_ACCOUNT},
+        historical_state::StateAtRoot,
+        proof::get_proof,
+        state::{get_account, get_storage, iter_storage, record_accesses, take_accesses, State},
+        utils::address::compute_contract_address,
+        vm::{call::CallResult, BlockEnvironment, Evm},
+    },
+    intrinsic_gas::{intrinsic_cost, IntrinsicGasInputs, FRONTIER},
+    trace::Tracer,
+    utils::hexadecimal::{hex, hex_to_bytes, hex_to_bytes20, hex_to_hash, hex_to_u256},
+};
+
+/// The error code geth uses for calls that fail to execute.
+const EXECUTION_ERROR: i64 = -32000;
+
+pub(crate) const METHOD_NOT_FOUND: i64 = -32601;
+
+pub(crate) fn fault(code: i64, message: impl Into<String>) -> RpcError {
+    RpcError::Rpc {
+        code,
+        message: message.into(),
+    }
+}
+
+fn invalid_params(error: EthereumException) -> RpcError {
+    fault(-32602, format!("invalid params: {error:?}"))
+}
+
+fn quantity(number: &Uint) -> Value {
+    Value::String(format!("{number:#x}"))
+}
+
+///
+///     Load the state to serve, and the block to run calls in if the file
+///     has one.
+///
+///     Parameters
+///     ----------
+///     json :
+///         A state dump (with an `accounts` object), a state test fixture
+///         (whose first test's `pre` and `env` are used), or a geth style
+///         allocation.
+///
+///     Returns
+///     -------
+///     state : `State`
+///         The state.
+///     block : `Option<BlockEnvironment>`
+///         The `env` of a state test fixture.
+///
+pub fn load_state(json: &str) -> Result<(State, Option<BlockEnvironment>), EthereumException> {
+    let value: Value =
+        serde_json::from_str(json).map_err(|e| EthereumException::JsonDecodeError(e.to_string()))?;
+    if value.get("accounts").is_some() {
+        return Ok((load_state_dump(json)?, None));
+    }
+    let fixture = value
+        .as_object()
+        .and_then(|tests| tests.values().next())
+        .filter(|test| test.get("pre").is_some());
+    let Some(test) = fixture else {
+        return Ok((load_alloc(json)?, None));
+    };
+
+    let env = &test["env"];
+    let block = BlockEnvironment {
+        block_hashes: vec![],
+        coinbase: hex_to_bytes20(json_str(&env["currentCoinbase"])?)?,
+        number: json_uint(&env["currentNumber"])?,
+        gas_limit: json_uint(&env["currentGasLimit"])?,
+        time: json_uint(&env["currentTimestamp"])?,
+        difficulty: json_uint(&env["currentDifficulty"])?,
+    };
+    Ok((load_alloc(&test["pre"].to_string())?, Some(block)))
+}
+
+/// The block after `header`, as calls on the state after it run in.
+fn block_after(header: &Header, block_hashes: Vec<Hash32>) -> BlockEnvironment {
+    BlockEnvironment {
+        block_hashes,
+        coinbase: header.coinbase,
+        number: &header.number + 1u8,
+        gas_limit: header.gas_limit.clone(),
+        time: header.timestamp.clone(),
+        difficulty: header.difficulty.clone(),
+    }
+}
+
+/// The arguments of `eth_call` and the methods like it.
+struct CallArgs {
+    request: CallRequest,
+    gas: Option<Uint>,
+}
+
+impl CallArgs {
+    fn from_json(value: &Value) -> Result<Self, EthereumException> {
+        let uint = |name: &str| match value.get(name) {
+            Some(number) if !number.is_null() => json_uint(number).map(Some),
+            _ => Ok(None),
+        };
+        let address = |name: &str| match value.get(name) {
+            Some(address) if !address.is_null() => hex_to_bytes20(json_str(address)?).map(Some),
+            _ => Ok(None),
+        };
+        let data = match value.get("input").or_else(|| value.get("data")) {
+            Some(data) if !data.is_null() => hex_to_bytes(json_str(data)?)?,
+            _ => Bytes::default(),
+        };
+        Ok(Self {
+            request: CallRequest {
+                from: address("from")?.unwrap_or_default(),
+                to: address("to")?,
+                gas_price: uint("gasPrice")?.unwrap_or_default(),
+                value: uint("value")?.unwrap_or_default(),
+                data,
+            },
+            gas: uint("gas")?,
+        })
+    }
+}
+
+/// Collects what a tracer writes.
+#[derive(Clone, Default)]
+struct SharedBuffer(Rc<RefCell<Vec<u8>>>);
+
+impl Write for SharedBuffer {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.borrow_mut().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Turn an EIP-3155 trace into the `structLogs` of geth's default tracer.
+fn struct_logs(trace: &[u8]) -> Vec<Value> {
+    let number = |value: &Value| {
+        let value = value.as_str().and_then(|value| u64::from_str_radix(&value[2..], 16).ok());
+        json!(value.unwrap_or_default())
+    };
+    String::from_utf8_lossy(trace)
+        .lines()
+        .filter_map(|line| serde_json::from_str::<Value>(line).ok())
+        .filter(|step| step.get("pc").is_some())
+        .map(|step| {
+            let mut log = json!({
+                "pc": step["pc"],
+                "op": step["opName"],
+                "gas": number(&step["gas"]),
+                "gasCost": number(&step["gasCost"]),
+                "depth": step["depth"],
+                "stack": step["stack"],
+            });
+            if let Some(error) = step.get("error") {
+                log["error"] = error.clone();
+            }
+            log
+        })
+        .collect()
+}
+
+/// A JSON-RPC server executing calls against a fixed state.
+pub struct SpecRpc {
+    state: State,
+    block: BlockEnvironment,
+    chain_id: u64,
+    /// The chain whose transactions are served, if any.
+    chain: Option<Chain>,
+}
+
+impl SpecRpc {
+    pub fn new(state: State, block: BlockEnvironment, chain_id: u64) -> Self {
+        Self {
+            state,
+            block,
+            chain_id,
+            chain: None,
+        }
+    }
+
+    ///
+    ///     Serve the state after the head of a chain, running calls in the
+    ///     block after it, and the transactions of the chain.
+    ///
+    pub fn from_chain(chain: Chain) -> Self {
+        let head = &chain.blocks().last().expect("a chain has a head").header;
+        let block = block_after(head, chain.block_hashes());
+        Self {
+            state: chain.state().clone(),
+            block,
+            chain_id: chain.blockchain().chain_id,
+            chain: Some(chain),
+        }
+    }
+
+    /// The state calls run against.
+    pub fn state(&self) -> &State {
+        &self.state
+    }
+
+    ///
+    ///     Run a call against the state, and undo its changes.
+    ///
+    ///     Returns
+    ///     -------
+    ///     result : `CallResult`
+    ///         The result of the call.
+    ///     gas_used : `Uint`
+    ///         The gas used, including the intrinsic cost of the transaction.
+    ///
+    fn run_call(
+        &mut self,
+        args: CallArgs,
+        tracer: Option<Box<dyn Tracer<Evm>>>,
+    ) -> Result<(CallResult, Uint), RpcError> {
+        let gas = args.gas.unwrap_or_else(|| self.block.gas_limit.clone());
+        run_call(&mut self.state, &self.block, &args.request, &gas, tracer)
+            .map_err(|error| fault(EXECUTION_ERROR, error.to_string()))
+    }
+
+    ///
+    ///     The number of the block a request names, if it is one before the
+    ///     head of the chain served, whose state is then read instead.
+    ///
+    fn past_block(&self, block: &Value) -> Result<Option<u64>, RpcError> {
+        if self.chain.is_none() {
+            return Ok(None);
+        }
+        let number = match block.as_str() {
+            Some("earliest") => 0,
+            Some(number) if number.starts_with("0x") => {
+                let number = json_uint(block).map_err(invalid_params)?;
+                u64::try_from(&number).map_err(|_| invalid_params(EthereumException::ValueError))?
+            }
+            _ => return Ok(None),
+        };
+        let head = u64::try_from(&self.block.number - 1u8).unwrap_or(u64::MAX);
+        Ok((number < head).then_some(number))
+    }
+
+    ///
+    ///     Open the state after a block before the head of the chain.
+    ///
+    fn state_at(&self, number: u64) -> Result<StateAtRoot<'_>, RpcError> {
+        let chain = self.chain.as_ref().expect("only a chain has past blocks");
+        let unavailable = || fault(EXECUTION_ERROR, format!("the state of block {number} isn't kept"));
+        chain.state_at(number).map_err(|_| unavailable())?.ok_or_else(unavailable)
+    }
+
+    ///
+    ///     Run a call after a block before the head, on its state.
+    ///
+    fn call_at(&self, number: u64, args: CallArgs) -> Result<CallResult, RpcError> {
+        let chain = self.chain.as_ref().expect("only a chain has past blocks");
+        let state = self.state_at(number)?;
+        let header = |number| chain.header(number).ok().flatten();
+        let block_hashes = (number.saturating_sub(255)..=number)
+            .filter_map(|number| header(number).map(|header| header.hash()))
+            .collect();
+        let block = block_after(&header(number).expect("the state of the block is kept"), block_hashes);
+        let gas = args.gas.unwrap_or_else(|| block.gas_limit.clone());
+        let result = state
+            .run(|state| run_call(state, &block, &args.request, &gas, None))
+            .map_err(|error| fault(EXECUTION_ERROR, error.to_string()))?;
+        let (result, _) = result.map_err(|error| fault(EXECUTION_ERROR, error.to_string()))?;
+        Ok(result)
+    }
+
+    fn call(&mut self, params: &Value) -> Result<Value, RpcError> {
+        let args = CallArgs::from_json(&params[0]).map_err(invalid_params)?;
+        let result = match self.past_block(&params[1])? {
+            Some(number) => self.call_at(number, args)?,
+            None => self.run_call(args, None)?.0,
+        };
+        match result.error {
+            Some(error) => Err(fault(EXECUTION_ERROR, error.to_string())),
+            None => Ok(json!(hex(&result.output))),
+        }
+    }
+
+    fn estimate_gas(&mut self, params: &Value) -> Result<Value, RpcError> {
+        let args = CallArgs::from_json(&params[0]).map_err(invalid_params)?;
+        let cap = args.gas.unwrap_or_else(|| self.block.gas_limit.clone());
+        estimate_gas(&mut self.state, &self.block, &args.request, &cap)
+            .map(|gas| quantity(&gas))
+            .map_err(|error| fault(EXECUTION_ERROR, error.to_string()))
+    }
+
+    ///
+    ///     Run a call recording what it accesses, and answer with the access
+    ///     list of the accounts and storage slots it accessed and the gas it
+    ///     uses with that list.
+    ///
+    fn create_access_list(&mut self, params: &Value) -> Result<Value, RpcError> {
+        let args = CallArgs::from_json(&params[0]).map_err(invalid_params)?;
+        let from = args.request.from;
+        let recipient = args
+            .request
+            .to
+            .unwrap_or_else(|| compute_contract_address(&from, &get_account(&self.state, &from).nonce));
+        let (data, create) = (args.request.data.clone(), args.request.to.is_none());
+
+        record_accesses(&mut self.state);
+        let result = self.run_call(args, None);
+        let accesses = take_accesses(&mut self.state).unwrap_or_default();
+        let (result, _) = result?;
+
+        // A list warms nothing in Frontier, so the call goes the same way
+        // with it, and only its intrinsic cost could change.
+        let list = access_list(&accesses, &from, &recipient);
+        let (access_list_addresses, access_list_storage_keys) = access_list_counts(&list);
+        let intrinsic_gas = intrinsic_cost(
+            &FRONTIER,
+            &IntrinsicGasInputs {
+                data: &data,
+                create,
+                access_list_addresses,
+                access_list_storage_keys,
+                ..Default::default()
+            },
+        );
+        let mut response = json!({
+            "accessList": list.iter().map(|entry| entry.to_json()).collect::<Vec<_>>(),
+            "gasUsed": quantity(&(intrinsic_gas + &result.gas_used)),
+        });
+        if let Some(error) = result.error {
+            response["error"] = json!(error.to_string());
+        }
+        Ok(response)
+    }
+
+    fn trace_call(&mut self, params: &Value) -> Result<Value, RpcError> {
+        let args = CallArgs::from_json(&params[0]).map_err(invalid_params)?;
+        let buffer = SharedBuffer::default();
+        let tracer = Eip3155Tracer::new(Box::new(buffer.clone()));
+        let (result, gas_used) = self.run_call(args, Some(Box::new(tracer)))?;
+        let trace = buffer.0.borrow();
+        Ok(json!({
+            "gas": u64::try_from(&gas_used).unwrap_or(u64::MAX),
+            "failed": result.error.is_some(),
+            "returnValue": hex(&result.output),
+            "structLogs": struct_logs(&trace),
+        }))
+    }
+
+    fn get_proof(&self, params: &Value) -> Result<Value, RpcError> {
+        let address = json_str(&params[0])
+            .and_then(hex_to_bytes20)
+            .map_err(invalid_params)?;
+        let keys = match &params[1] {
+            Value::Array(keys) => keys
+                .iter()
+                .map(|key| Ok(u256_to_be_bytes32(&hex_to_u256(json_str(key)?)?)))
+                .collect::<Result<Vec<_>, EthereumException>>()
+                .map_err(invalid_params)?,
+            _ => vec![],
+        };
+        Ok(get_proof(&self.state, &address, &keys).to_json())
+    }
+
+    fn get_account(&self, params: &Value) -> Result<Account, RpcError> {
+        let address = json_str(&params[0])
+            .and_then(hex_to_bytes20)
+            .map_err(invalid_params)?;
+        match self.past_block(&params[1])? {
+            Some(number) => Ok(self
+                .state_at(number)?
+                .get_account_optional(&address)
+                .map_err(|error| fault(EXECUTION_ERROR, error.to_string()))?
+                .unwrap_or_else(EMPTY_ACCOUNT)),
+            None => Ok(get_account(&self.state, &address)),
+        }
+    }
+
+    fn get_storage_at(&self, params: &Value) -> Result<Value, RpcError> {
+        let address = json_str(&params[0])
+            .and_then(hex_to_bytes20)
+            .map_err(invalid_params)?;
+        let key = json_str(&params[1])
+            .and_then(hex_to_u256)
+            .map_err(invalid_params)?;
+        let key = u256_to_be_bytes32(&key);
+        let value = match self.past_block(&params[2])? {
+            Some(number) => self
+                .state_at(number)?
+                .get_storage(&address, &key)
+                .map_err(|error| fault(EXECUTION_ERROR, error.to_string()))?,
+            None => get_storage(&self.state, &address, &key),
+        };
+        Ok(json!(hex(&u256_to_be_bytes32(&value))))
+    }
+
+    ///
+    ///     List storage slots of an account from a hash of a key on, the
+    ///     way geth's `debug_storageRangeAt` does. The block hash and
+    ///     transaction index are ignored.
+    ///
+    fn storage_range_at(&self, params: &Value) -> Result<Value, RpcError> {
+        let address = json_str(&params[2])
+            .and_then(hex_to_bytes20)
+            .map_err(invalid_params)?;
+        let start = json_str(&params[3])
+            .and_then(hex_to_u256)
+            .map_err(invalid_params)?;
+        let start = u256_to_be_bytes32(&start);
+        let limit = params[4]
+            .as_u64()
+            .ok_or_else(|| invalid_params(EthereumException::ValueError))?;
+
+        let mut slots = iter_storage(&self.state, &address).skip_while(|(hash, _, _)| *hash < start);
+        let mut storage = serde_json::Map::new();
+        for (hash, key, value) in slots.by_ref().take(limit as usize) {
+            storage.insert(hex(&hash), json!({ "key": hex(&key), "value": hex(&u256_to_be_bytes32(&value)) }));
+        }
+        let next_key = slots.next().map(|(hash, _, _)| hex(&hash));
+        Ok(json!({ "storage": storage, "nextKey": next_key }))
+    }
+
+    fn get_transaction(&self, params: &Value) -> Result<Value, RpcError> {
+        let hash = json_str(&params[0]).and_then(hex_to_hash).map_err(invalid_params)?;
+        let Some(chain) = &self.chain else {
+            return Ok(Value::Null);
+        };
+        let transaction = chain.get_transaction(&hash).map_err(invalid_params)?;
+        Ok(transaction.map_or(Value::Null, |transaction| transaction.to_json()))
+    }
+
+    fn get_transaction_receipt(&self, params: &Value) -> Result<Value, RpcError> {
+        let hash = json_str(&params[0]).and_then(hex_to_hash).map_err(invalid_params)?;
+        let Some(chain) = &self.chain else {
+            return Ok(Value::Null);
+        };
+        let receipt = chain.get_transaction_receipt(&hash).map_err(invalid_params)?;
+        Ok(receipt.map_or(Value::Null, |receipt| receipt.to_json()))
+    }
+
+    pub(crate) fn dispatch(&mut self, method: &str, params: &Value) -> Result<Value, RpcError> {
+        match method {
+            "eth_chainId" => Ok(quantity(&Uint::from(self.chain_id))),
+            "eth_blockNumber" => Ok(quantity(&self.block.number)),
+            "eth_call" => self.call(params),
+            "eth_estimateGas" => self.estimate_gas(params),
+            "eth_createAccessList" => self.create_access_list(params),
+            "debug_traceCall" => self.trace_call(params),
+            "eth_getProof" => self.get_proof(params),
+            "eth_getBalance" => Ok(quantity(&self.get_account(params)?.balance)),
+            "eth_getTransactionCount" => Ok(quantity(&self.get_account(params)?.nonce)),
+            "eth_getCode" => Ok(json!(hex(&self.get_account(params)?.code))),
+            "eth_getStorageAt" => self.get_storage_at(params),
+            "debug_storageRangeAt" => self.storage_range_at(params),
+            "eth_getTransactionByHash" => self.get_transaction(params),
+            "eth_getTransactionReceipt" => self.get_transaction_receipt(params),
+            _ => Err(fault(METHOD_NOT_FOUND, format!("the method {method} does not exist/is not available"))),
+        }
+    }
+
+    ///
+    ///     Answer a request, or a batch of them.
+    ///
+    ///     Parameters
+    ///     ----------
+    ///     request :
+    ///         The JSON-RPC request object, or an array of them.
+    ///
+    ///     Returns
+    ///     -------
+    ///     response : `Value`
+    ///         The response, or an array of them for a batch.
+    ///
+    pub fn handle(&mut self, request: &Value) -> Value {
+        handle_requests(request, |method, params| self.dispatch(method, params))
+    }
+
+    ///
+    ///     Answer HTTP requests on `listener`, one connection at a time.
+    ///
+    pub fn serve(&mut self, listener: TcpListener) -> io::Result<()> {
+        serve(listener, |request| self.handle(request))
+    }
+}
+
+/// The response to the request `id`.
+pub(crate) fn response(id: &Value, result: Result<Value, RpcError>) -> Value {
+    match result {
+        Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+        Err(RpcError::Rpc { code, message }) => json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "error": { "code": code, "message": message },
+        }),
+        Err(error) => json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "error": { "code": -32603, "message": error.to_string() },
+        }),
+    }
+}
+
+/// Answer a request, or a batch of them, with `dispatch` computing the
+/// result of each from its method and params.
+pub(crate) fn handle_requests(
+    request: &Value,
+    mut dispatch: impl FnMut(&str, &Value) -> Result<Value, RpcError>,
+) -> Value {
+    let mut handle_one = |request: &Value| {
+        let result = match request["method"].as_str() {
+            Some(method) => dispatch(method, &request["params"]),
+            None => Err(fault(-32600, "invalid request")),
+        };
+        response(&request["id"], result)
+    };
+    match request {
+        Value::Array(requests) => requests.iter().map(handle_one).collect(),
+        request => handle_one(request),
+    }
+}
+
+fn handle_connection(stream: TcpStream, handle: &mut impl FnMut(&Value) -> Value) -> io::Result<()> {
+    let mut reader = BufReader::new(&stream);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    let mut content_length = 0;
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 || line.trim().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("content-length") {
+                content_length = value.trim().parse().unwrap_or_default();
+            }
+        }
+    }
+
+    // Browsers ask before sending JSON across origins.
+    let (status, body) = if request_line.starts_with("OPTIONS") {
+        ("204 No Content", String::new())
+    } else {
+        let mut body = vec![0; content_length];
+        reader.read_exact(&mut body)?;
+        let response = match serde_json::from_slice::<Value>(&body) {
+            Ok(request) => handle(&request),
+            Err(_) => response(&Value::Null, Err(fault(-32700, "parse error"))),
+        };
+        ("200 OK", response.to_string())
+    };
+    write!(
+        &stream,
+        "HTTP/1.1 {status}\r\nContent-Type: application/json\r\n\
+         Access-Control-Allow-Origin: *\r\nAccess-Control-Allow-Headers: Content-Type\r\n\
+         Content-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    )
+}
+
+///
+///     Answer JSON-RPC requests over HTTP on `listener`, one connection at a
+///     time, with `handle` answering each request body.
+///
+pub(crate) fn serve(listener: TcpListener, mut handle: impl FnMut(&Value) -> Value) -> io::Result<()> {
+    for stream in listener.incoming() {
+        // A client that goes away mid-request only loses its own answer.
+        let _ = handle_connection(stream?, &mut handle);
+    }
+    Ok(())
+}
@@ -0,0 +1,65 @@
+//!
+//! # spec-hive
+//!
+//! The entrypoint of the hive client image: builds the chain from the
+//! genesis file hive provides, and serves it on the HTTP and engine ports.
+//!
+//! ```text
+//! spec-hive --genesis /genesis.json
+//! ```
+//!
+
+use std::{net::TcpListener, path::PathBuf, process::ExitCode};
+
+use clap::Parser;
+use execution_specs_rs::ethereum_spec_tools::{
+    hive::{serve_node, HiveNode},
+    logging::LogArgs,
+};
+
+#[derive(Parser, Debug)]
+#[command(version, about = "Run the spec as a hive client")]
+struct Args {
+    /// A geth style genesis file.
+    #[arg(long, default_value = "/genesis.json")]
+    genesis: PathBuf,
+    #[arg(long, default_value = "0.0.0.0")]
+    host: String,
+    #[arg(long, default_value_t = 8545)]
+    http_port: u16,
+    #[arg(long, default_value_t = 8551)]
+    engine_port: u16,
+    #[command(flatten)]
+    log: LogArgs,
+}
+
+fn run(args: Args) -> Result<(), String> {
+    let genesis = std::fs::read_to_string(&args.genesis)
+        .map_err(|e| format!("{}: {e}", args.genesis.display()))?;
+    let node = HiveNode::from_genesis(&genesis).map_err(|e| format!("{e:?}"))?;
+
+    let listeners = [args.http_port, args.engine_port]
+        .into_iter()
+        .map(|port| TcpListener::bind((args.host.as_str(), port)).map_err(|e| format!("port {port}: {e}")))
+        .collect::<Result<Vec<_>, _>>()?;
+    tracing::info!(
+        chain_id = node.engine().chain_id(),
+        genesis = %args.genesis.display(),
+        http_port = args.http_port,
+        engine_port = args.engine_port,
+        "serving"
+    );
+    serve_node(node, listeners).map_err(|e| e.to_string())
+}
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+    args.log.init();
+    match run(args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            tracing::error!("{error}");
+            ExitCode::FAILURE
+        }
+    }
+}
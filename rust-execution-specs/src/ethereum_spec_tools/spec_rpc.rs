@@ -0,0 +1,63 @@
+//!
+//! # spec-rpc
+//!
+//! Serves `eth_call`, `eth_estimateGas`, `debug_traceCall` and
+//! `eth_getProof` over JSON-RPC, against a state loaded from an allocation,
+//! a state dump or a state test fixture.
+//!
+//! ```text
+//! spec-rpc --state alloc.json --port 8545
+//! ```
+//!
+
+use std::{net::TcpListener, path::PathBuf, process::ExitCode};
+
+use clap::Parser;
+use execution_specs_rs::ethereum_spec_tools::{
+    evm_tools::run::RunConfig,
+    logging::LogArgs,
+    rpc_server::{load_state, SpecRpc},
+};
+
+#[derive(Parser, Debug)]
+#[command(about = "Serve JSON-RPC calls against a state")]
+struct Args {
+    /// An allocation, state dump or state test fixture.
+    #[arg(long)]
+    state: PathBuf,
+    #[arg(long, default_value = "127.0.0.1")]
+    host: String,
+    #[arg(long, default_value_t = 8545)]
+    port: u16,
+    /// The chain id returned by `eth_chainId`.
+    #[arg(long, default_value_t = 1)]
+    chain_id: u64,
+    #[command(flatten)]
+    log: LogArgs,
+}
+
+fn serve(args: Args) -> Result<(), String> {
+    let json = std::fs::read_to_string(&args.state)
+        .map_err(|e| format!("{}: {e}", args.state.display()))?;
+    let (state, block) = load_state(&json).map_err(|e| format!("{e:?}"))?;
+    let block = block.unwrap_or_else(|| RunConfig::default().block);
+
+    let listener = TcpListener::bind((args.host.as_str(), args.port)).map_err(|e| e.to_string())?;
+    let address = listener.local_addr().map_err(|e| e.to_string())?;
+    tracing::info!(url = %format!("http://{address}"), "listening");
+    SpecRpc::new(state, block, args.chain_id)
+        .serve(listener)
+        .map_err(|e| e.to_string())
+}
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+    args.log.init();
+    match serve(args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            tracing::error!("{error}");
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,311 @@
+//!
+//! # State Divergence
+//!
+//! Finds where a state differs from a reference, for when a block's state
+//! root doesn't match.
+//!
+//! A root mismatch alone doesn't say what went wrong. The reference is either
+//! a whole state, such as one read with `load_state_dump`, or `eth_getProof`
+//! results from a node, checked against the expected root. Accounts are
+//! compared in the order of the state trie, by the hash of their address, and
+//! the storage of an account in the order of its storage trie, so the first
+//! divergence reported is the same on every run.
+//!
+
+use std::{
+    collections::{BTreeSet, HashMap},
+    fmt,
+};
+
+use crate::ethereum::{
+    base_types::{Bytes32, Uint, U256},
+    exceptions::EthereumException,
+    frontier::{
+        fork::recover_sender,
+        fork_types::{keccak256, Address, Block, Hash32, Root},
+        proof::{verify_proof, AccountProof},
+        state::{get_account, get_account_optional, get_accounts, get_storage, get_storage_slots, storage_root, State},
+        trie::verify_trie_proof,
+        utils::address::compute_contract_address,
+    },
+    utils::hexadecimal::hex,
+};
+
+/// The fields of an account that the state root commits to.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AccountFields {
+    pub nonce: Uint,
+    pub balance: U256,
+    pub code_hash: Hash32,
+    pub storage_root: Root,
+}
+
+impl AccountFields {
+    ///
+    ///     Read the fields of an account, or `None` if it doesn't exist.
+    ///
+    pub fn of(state: &State, address: &Address) -> Option<Self> {
+        let account = get_account_optional(state, address)?;
+        Some(Self {
+            nonce: account.nonce,
+            balance: account.balance,
+            code_hash: keccak256(&account.code),
+            storage_root: storage_root(state, address),
+        })
+    }
+
+    fn from_proof(proof: &AccountProof) -> Self {
+        Self {
+            nonce: proof.nonce.clone(),
+            balance: proof.balance.clone(),
+            code_hash: proof.code_hash,
+            storage_root: proof.storage_hash,
+        }
+    }
+
+    /// Whether the fields other than the storage root are the same.
+    fn same_header(&self, other: &Self) -> bool {
+        self.nonce == other.nonce && self.balance == other.balance && self.code_hash == other.code_hash
+    }
+}
+
+impl fmt::Display for AccountFields {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "nonce {}, balance {}, code hash {}, storage root {}",
+            self.nonce,
+            self.balance,
+            hex(&self.code_hash),
+            hex(&self.storage_root)
+        )
+    }
+}
+
+/// The first difference between a state and its reference.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Divergence {
+    /// The account exists on one side only, or its fields differ.
+    Account {
+        address: Address,
+        ours: Option<AccountFields>,
+        theirs: Option<AccountFields>,
+    },
+    /// A storage slot of an account holds different values.
+    Storage {
+        address: Address,
+        key: Bytes32,
+        ours: U256,
+        theirs: U256,
+    },
+}
+
+impl fmt::Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Divergence::Account { address, ours: None, theirs: Some(theirs) } => {
+                write!(f, "account {} is missing, expected {theirs}", hex(address))
+            }
+            Divergence::Account { address, ours: Some(ours), theirs: None } => {
+                write!(f, "account {} ({ours}) shouldn't exist", hex(address))
+            }
+            Divergence::Account { address, ours, theirs } => {
+                let (ours, theirs) = (ours.as_ref().unwrap(), theirs.as_ref().unwrap());
+                write!(f, "account {} differs:", hex(address))?;
+                if ours.nonce != theirs.nonce {
+                    write!(f, " nonce {}, expected {};", ours.nonce, theirs.nonce)?;
+                }
+                if ours.balance != theirs.balance {
+                    write!(f, " balance {}, expected {};", ours.balance, theirs.balance)?;
+                }
+                if ours.code_hash != theirs.code_hash {
+                    write!(f, " code hash {}, expected {};", hex(&ours.code_hash), hex(&theirs.code_hash))?;
+                }
+                if ours.storage_root != theirs.storage_root {
+                    write!(
+                        f,
+                        " storage root {}, expected {};",
+                        hex(&ours.storage_root),
+                        hex(&theirs.storage_root)
+                    )?;
+                }
+                Ok(())
+            }
+            Divergence::Storage { address, key, ours, theirs } => write!(
+                f,
+                "storage slot {} of account {} is {ours:#x}, expected {theirs:#x}",
+                hex(key),
+                hex(address)
+            ),
+        }
+    }
+}
+
+/// Sort items by the hash of their key, the order of a secured trie.
+fn in_trie_order<T, K: AsRef<[u8]>>(items: impl IntoIterator<Item = T>, key: impl Fn(&T) -> K) -> Vec<T> {
+    let mut items: Vec<(Hash32, T)> = items.into_iter().map(|item| (keccak256(key(&item).as_ref()), item)).collect();
+    items.sort_by_key(|(a, _)| *a);
+    items.into_iter().map(|(_, item)| item).collect()
+}
+
+///
+///     Find the first difference between two states.
+///
+///     Parameters
+///     ----------
+///     ours :
+///         The state computed, with no open transactions.
+///     theirs :
+///         The reference state, with no open transactions.
+///
+///     Returns
+///     -------
+///     divergence : `Option<Divergence>`
+///         The first account, in trie order, whose nonce, balance, code or
+///         existence differs, or failing that the first differing slot of
+///         it. `None` if the states are the same.
+///
+pub fn find_divergence(ours: &State, theirs: &State) -> Option<Divergence> {
+    let addresses: BTreeSet<Address> = get_accounts(ours).into_keys().chain(get_accounts(theirs).into_keys()).collect();
+    for address in in_trie_order(addresses, |address| *address) {
+        let (our_fields, their_fields) = (AccountFields::of(ours, &address), AccountFields::of(theirs, &address));
+        match (&our_fields, &their_fields) {
+            (Some(a), Some(b)) if a.same_header(b) => {}
+            _ => {
+                return Some(Divergence::Account {
+                    address,
+                    ours: our_fields,
+                    theirs: their_fields,
+                })
+            }
+        }
+
+        let keys: BTreeSet<Bytes32> = get_storage_slots(ours, &address)
+            .into_keys()
+            .chain(get_storage_slots(theirs, &address).into_keys())
+            .collect();
+        for key in in_trie_order(keys, |key| *key) {
+            let (our_value, their_value) = (get_storage(ours, &address, &key), get_storage(theirs, &address, &key));
+            if our_value != their_value {
+                return Some(Divergence::Storage {
+                    address,
+                    key,
+                    ours: our_value,
+                    theirs: their_value,
+                });
+            }
+        }
+    }
+    None
+}
+
+///
+///     Find the first difference between a state and proofs of the
+///     expected one.
+///
+///     Only the proven accounts and slots can be compared. A proven account
+///     whose slots all match but whose storage root doesn't is reported as
+///     an account divergence, since the differing slot isn't known.
+///
+///     Parameters
+///     ----------
+///     ours :
+///         The state computed, with no open transactions.
+///     expected_root :
+///         The state root the proofs are from.
+///     proofs :
+///         `eth_getProof` results at `expected_root`.
+///
+///     Returns
+///     -------
+///     divergence : `Result<Option<Divergence>, EthereumException>`
+///         The first divergence among the proven accounts, in trie order,
+///         or `None`. `ValueError` if a proof isn't valid under
+///         `expected_root`.
+///
+pub fn find_divergence_in_proofs(
+    ours: &State,
+    expected_root: &Root,
+    proofs: &[AccountProof],
+) -> Result<Option<Divergence>, EthereumException> {
+    for proof in proofs {
+        verify_proof(expected_root, proof)?;
+    }
+
+    for proof in in_trie_order(proofs, |proof| proof.address) {
+        let address = proof.address;
+        let exists = verify_trie_proof(expected_root, &keccak256(&address), &proof.account_proof)?.is_some();
+        let their_fields = exists.then(|| AccountFields::from_proof(proof));
+        let our_fields = AccountFields::of(ours, &address);
+        match (&our_fields, &their_fields) {
+            (Some(a), Some(b)) if a.same_header(b) => {}
+            (None, None) => continue,
+            _ => {
+                return Ok(Some(Divergence::Account {
+                    address,
+                    ours: our_fields,
+                    theirs: their_fields,
+                }))
+            }
+        }
+
+        for slot in in_trie_order(&proof.storage_proof, |slot| slot.key) {
+            let value = get_storage(ours, &address, &slot.key);
+            if value != slot.value {
+                return Ok(Some(Divergence::Storage {
+                    address,
+                    key: slot.key,
+                    ours: value,
+                    theirs: slot.value.clone(),
+                }));
+            }
+        }
+
+        if our_fields != their_fields {
+            return Ok(Some(Divergence::Account {
+                address,
+                ours: our_fields,
+                theirs: their_fields,
+            }));
+        }
+    }
+    Ok(None)
+}
+
+///
+///     List the accounts a block touches directly: its coinbase, the
+///     coinbases of its ommers, and the senders, recipients and created
+///     contracts of its transactions. Accounts only reached by message
+///     calls aren't included.
+///
+///     Parameters
+///     ----------
+///     state :
+///         The state before the block.
+///     block :
+///         The block.
+///
+///     Returns
+///     -------
+///     addresses : `Result<Vec<Address>, EthereumException>`
+///         The addresses, without duplicates. `InvalidBlock` if a sender
+///         can't be recovered.
+///
+pub fn block_addresses(state: &State, block: &Block) -> Result<Vec<Address>, EthereumException> {
+    let mut addresses = BTreeSet::from([block.header.coinbase]);
+    addresses.extend(block.ommers.iter().map(|ommer| ommer.coinbase));
+
+    // Senders may send several transactions, each creating at the next nonce.
+    let mut nonces = HashMap::new();
+    for tx in block.transactions.iter() {
+        let sender = recover_sender(tx)?;
+        let nonce = nonces.entry(sender).or_insert_with(|| get_account(state, &sender).nonce);
+        addresses.insert(sender);
+        addresses.insert(match tx.to {
+            Some(to) => to,
+            None => compute_contract_address(&sender, nonce),
+        });
+        *nonce += 1u8;
+    }
+    Ok(addresses.into_iter().collect())
+}
@@ -1,31 +1,639 @@
+//!
+//! # sync
+//!
+//! Downloads blocks from a node over JSON-RPC and applies them to a chain
+//! starting from genesis, stopping at the first block the spec rejects or
+//! the first fork it doesn't implement.
+//!
+//! ```text
+//! sync --rpc-url http://localhost:8545
+//! ```
+//!
+//! With `--diagnose`, a block whose state root doesn't match is followed by
+//! the first account or slot that differs from the node, found with
+//! `eth_getProof` for the accounts the block touches.
+//!
+//! Progress goes to stderr every `--progress-interval` seconds, as text or,
+//! with `--log-format json`, as JSON lines; `--verbosity debug` adds every
+//! block and transaction.
+//!
+//! To look into a block deep in the chain without executing everything
+//! before it, stop short of it once with a checkpoint, then start from the
+//! checkpoint as often as needed:
+//!
+//! ```text
+//! sync --stop-at 1149999 --checkpoint 1149999.json
+//! sync --state 1149999.json --stop-at 1150000 --verbosity debug
+//! ```
+//!
+//! A checkpoint is a state dump, as `geth dump` writes, with the number of
+//! its block; `--start-at` names the block of a dump without one. The 255
+//! blocks before it are fetched from the node for `BLOCKHASH` and the ommer
+//! checks.
+//!
+//! With `--validate-only`, the headers and their proofs of work are checked
+//! against their parents, and nothing is executed.
+//!
+//! When the node switches to another branch, the sync goes back to the last
+//! block the branches share and applies the new one, if that is at most
+//! `--reorg-depth` blocks back.
+//!
+//! Only the last 255 blocks are kept in memory; with `--header-archive` the
+//! headers of the blocks before them are written to a file, which later runs
+//! carry on.
+//!
+//! To spot-check a transaction, name it with `--receipt`: once its block is
+//! applied, its receipt is logged as `eth_getTransactionReceipt` returns it,
+//! and compared with the node's, from `debug_getRawReceipts`.
+//!
+//! ```text
+//! sync --stop-at 46147 --receipt 0x5c504ed432cb51138bcf09aa5e8a410dd4a1e204ef84bfed1be16dfba1b22060
+//! ```
+//!
+//! With `--state-history`, the state after every block is kept in a file,
+//! which later runs carry on, to be read at any of those blocks. Every
+//! `--spot-check` blocks, the account of the coinbase of an earlier block is
+//! then read from it, at that block, and compared with the node's.
+//!
+//! ```text
+//! sync --stop-at 100000 --state-history states.bin --spot-check 1000
+//! ```
+//!
+//! Built with the `metrics` feature, `--metrics-addr` serves the throughput,
+//! the time each block takes and how often the caches hit to Prometheus.
+//!
 
-// use clap::Parser;
-
-// #[derive(Parser, Debug)]
-// #[command(author, version, about, long_about = None)]
-// pub struct Args {
-//     #[arg(short, long)]
-//     rpc_url: String,
-// }
-
-// pub struct BlockDownloader {
-//     rpc_url: String,
-//     queue: VecDeque<u64>,
-// }
-
-// impl BlockDownloader {
-//     fn new(rpc_url: String) -> Self {
-//         let mut downlowder = Self { 
-//             rpc_url,
-//             queue: VecDeque::with_capacity(512)
-//         };
-//         downlowder
-//     }
-
-//     async fn download(&mut self) {
-//     }
-// }
-
-fn main() {
-    // let args = Args::parse();
+use std::{
+    ops::Range,
+    path::{Path, PathBuf},
+    process::ExitCode,
+    time::Duration,
+};
+
+use clap::Parser;
+use execution_specs_rs::{
+    ethereum::{
+        base_types::Uint,
+        ethash,
+        exceptions::EthereumException,
+        frontier::{
+            self,
+            fork::BlockChain,
+            fork_types::{keccak256, Address, Hash32, Header, Root},
+            state::{get_storage_slots, state_root, State},
+            trie::persistent::NodeStore,
+        },
+        genesis::{add_genesis_block, parse_genesis_configuration},
+        rlp,
+        utils::hexadecimal::{hex, hex_to_hash},
+    },
+    ethereum_spec_tools::{
+        chain::Chain,
+        evm_tools::dump::{dump_state, load_state_dump},
+        forks::{ForkSchedule, HardFork},
+        header_archive::HeaderArchive,
+        logging::{LogArgs, Progress},
+        rpc::{RpcClient, RpcTransactions},
+        state_divergence::{block_addresses, find_divergence_in_proofs},
+    },
+};
+#[cfg(feature = "metrics")]
+use execution_specs_rs::ethereum_spec_tools::metrics;
+use serde_json::{json, Value};
+
+#[derive(Parser, Debug)]
+#[command(about = "Sync a chain from a JSON-RPC node, executing every block")]
+struct Args {
+    /// The http:// endpoint of the node.
+    #[arg(long, default_value = "http://localhost:8545")]
+    rpc_url: String,
+    /// A genesis file to start from, instead of the mainnet one.
+    #[arg(long)]
+    genesis: Option<PathBuf>,
+    /// How many blocks to fetch per batch request.
+    #[arg(long, default_value_t = 64)]
+    batch_size: u64,
+    /// On a state root mismatch, find the first account or slot that
+    /// differs from the node, with `eth_getProof`.
+    #[arg(long)]
+    diagnose: bool,
+    /// How often to report progress, in seconds.
+    #[arg(long, default_value_t = 10.0)]
+    progress_interval: f64,
+    /// Start after this block, instead of after genesis. Needs `--state`,
+    /// unless only validating.
+    #[arg(long)]
+    start_at: Option<u64>,
+    /// A state dump or checkpoint of the state after the block to start
+    /// after.
+    #[arg(long)]
+    state: Option<PathBuf>,
+    /// Stop after this block.
+    #[arg(long)]
+    stop_at: Option<u64>,
+    /// When the last block is reached, write the state after it, and its
+    /// number, to this file, to start from with `--state`.
+    #[arg(long)]
+    checkpoint: Option<PathBuf>,
+    /// Check the headers and their proofs of work, without executing the
+    /// blocks.
+    #[arg(long)]
+    validate_only: bool,
+    /// How many blocks back the node may switch branches. Each costs a copy
+    /// of the state.
+    #[arg(long, default_value_t = 8)]
+    reorg_depth: usize,
+    /// A file to keep the headers of the blocks pruned from memory in.
+    #[arg(long)]
+    header_archive: Option<PathBuf>,
+    /// The hash of a transaction whose receipt to log and compare with the
+    /// node's once its block is applied. May be given more than once.
+    #[arg(long, value_parser = parse_hash)]
+    receipt: Vec<Hash32>,
+    /// A file to keep the state after every block in, from the first one
+    /// executed, read at the start if it exists and written at the end.
+    #[arg(long)]
+    state_history: Option<PathBuf>,
+    /// Every this many blocks, compare the account of the coinbase of the
+    /// block this many blocks back, at that block, with the node's. Needs
+    /// `--state-history`.
+    #[arg(long)]
+    spot_check: Option<u64>,
+    /// Serve the metrics to Prometheus at `/metrics` on this address, such
+    /// as 127.0.0.1:9100.
+    #[cfg(feature = "metrics")]
+    #[arg(long)]
+    metrics_addr: Option<std::net::SocketAddr>,
+    #[command(flatten)]
+    log: LogArgs,
+}
+
+fn parse_hash(hash: &str) -> Result<Hash32, String> {
+    hex_to_hash(hash).map_err(|e| format!("{e:?}"))
+}
+
+///
+///     Log the receipts of the transactions of the head named with
+///     `--receipt`, and compare them with the node's.
+///
+fn check_receipts(client: &RpcClient, chain: &Chain, hashes: &[Hash32]) -> Result<(), String> {
+    let head = chain.blocks().last().expect("a chain has a head");
+    let number = u64::try_from(&head.header.number).map_err(|e| e.to_string())?;
+    let mut node_receipts = None;
+    let in_head = hashes.iter().filter(|hash| {
+        let location = chain.transactions().location(hash);
+        location.is_some_and(|location| location.block_number == number)
+    });
+    for hash in in_head {
+        let Some(receipt) = chain.get_transaction_receipt(hash).map_err(|e| format!("{e:?}"))? else {
+            continue;
+        };
+        tracing::info!(receipt = %receipt.to_json(), "receipt");
+        if node_receipts.is_none() {
+            node_receipts = Some(client.get_raw_receipts(number).map_err(|e| e.to_string())?);
+        }
+        let expected = node_receipts.as_ref().and_then(|receipts| receipts.get(receipt.transaction.location.index));
+        let actual = rlp::encode(&receipt.receipt);
+        if expected != Some(&actual) {
+            return Err(format!(
+                "the receipt of {} is {}, but the node has {}",
+                hex(hash),
+                hex(&actual),
+                expected.map_or("none".to_owned(), |expected| hex(expected))
+            ));
+        }
+    }
+    Ok(())
+}
+
+///
+///     Compare the account of the coinbase of block `number`, as the state
+///     history has it after the block, with the node's.
+///
+fn spot_check(client: &RpcClient, chain: &Chain, number: u64) -> Result<(), String> {
+    let state = chain
+        .state_at(number)
+        .map_err(|e| format!("the state of block {number} isn't kept: {e:?}"))?
+        .ok_or_else(|| format!("the chain doesn't have block {number}"))?;
+    let coinbase = chain.header(number).map_err(|e| format!("{e:?}"))?.expect("the state is kept").coinbase;
+    let account = state
+        .get_account_optional(&coinbase)
+        .map_err(|e| format!("{e:?}"))?
+        .unwrap_or_default();
+    let storage_root = state.storage_root(&coinbase).map_err(|e| format!("{e:?}"))?;
+    let proof = client.get_proof(&coinbase, &[], number).map_err(|e| e.to_string())?;
+    let code_hash = keccak256(&account.code);
+    if (account.nonce, account.balance, storage_root, code_hash)
+        != (proof.nonce, proof.balance, proof.storage_hash, proof.code_hash)
+    {
+        return Err(format!(
+            "the state history differs from the node on {} after block {number}",
+            hex(&coinbase)
+        ));
+    }
+    tracing::debug!(number, address = %hex(&coinbase), "spot-checked");
+    Ok(())
+}
+
+/// Compare the accounts of a rejected block, and the slots we have for
+/// them, with the node's proofs of the state after it.
+fn diagnose(
+    client: &RpcClient,
+    state: &State,
+    expected_root: &Root,
+    addresses: &[Address],
+    number: u64,
+) -> Result<String, String> {
+    let proofs = addresses
+        .iter()
+        .map(|address| {
+            let keys: Vec<_> = get_storage_slots(state, address).into_keys().collect();
+            client.get_proof(address, &keys, number).map_err(|e| e.to_string())
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(match find_divergence_in_proofs(state, expected_root, &proofs).map_err(|e| format!("{e:?}"))? {
+        Some(divergence) => divergence.to_string(),
+        None => "the accounts the block touches directly all match the node".to_owned(),
+    })
+}
+
+fn genesis_chain(args: &Args) -> Result<Chain, String> {
+    match &args.genesis {
+        Some(path) => {
+            let genesis = std::fs::read_to_string(path).map_err(|e| format!("{}: {e}", path.display()))?;
+            Chain::from_genesis(&genesis)
+        }
+        // The mainnet genesis file only schedules the forks up to Berlin.
+        None => {
+            let genesis = parse_genesis_configuration(include_str!("../../assets/mainnet.json"))
+                .map_err(|e| format!("{e:?}"))?;
+            let mut chain = BlockChain {
+                blocks: vec![],
+                state: State::default(),
+                chain_id: 0,
+            };
+            add_genesis_block(HardFork::Frontier, &mut chain, genesis).map_err(|e| format!("{e:?}"))?;
+            Chain::new(chain, ForkSchedule::mainnet())
+        }
+    }
+    .map_err(|e| format!("{e:?}"))
+}
+
+/// The block `number` as the node has it, and the total difficulty of the
+/// chain up to it.
+fn get_block_header(client: &RpcClient, number: u64) -> Result<(Header, Uint), String> {
+    let block = client
+        .get_block_by_number(number, false)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("the node doesn't have block {number}"))?;
+    let total_difficulty = block
+        .total_difficulty
+        .ok_or_else(|| format!("the node doesn't give the total difficulty of block {number}"))?;
+    Ok((block.header, total_difficulty))
+}
+
+///
+///     Rebuild the chain after block `number` from the state after it, with
+///     the blocks before it from the node.
+///
+fn resume(client: &RpcClient, args: &Args, genesis: Chain, number: u64, state: State) -> Result<Chain, String> {
+    let (head, total_difficulty) = get_block_header(client, number)?;
+    let root = state_root(&state);
+    if root != head.state_root {
+        return Err(format!(
+            "the state has root {}, but block {number} has {}",
+            hex(&root),
+            hex(&head.state_root)
+        ));
+    }
+
+    // `state_transition` keeps the last 255 blocks.
+    let first = number.saturating_sub(254);
+    let mut blocks = vec![];
+    for start in (first..=number).step_by(args.batch_size.max(1) as usize) {
+        let end = (start + args.batch_size).min(number + 1);
+        blocks.extend(client.get_blocks(start..end).map_err(|e| e.to_string())?);
+    }
+    if blocks.len() as u64 != number + 1 - first {
+        return Err(format!("the node doesn't have all the blocks from {first} to {number}"));
+    }
+    let chain = BlockChain {
+        blocks: blocks.into_iter().map(|(_, block)| block).collect(),
+        state,
+        chain_id: genesis.blockchain().chain_id,
+    };
+    Chain::resume(chain, genesis.schedule().clone(), total_difficulty)
+        .map(|chain| chain.with_consensus(genesis.info().consensus))
+        .map_err(|e| format!("{e:?}"))
+}
+
+/// The blocks after `head` to fetch next, or `None` past `--stop-at`.
+fn next_batch(args: &Args, head: &Header) -> Option<Range<u64>> {
+    let start = u64::try_from(&head.number).unwrap_or(u64::MAX).saturating_add(1);
+    let end = match args.stop_at {
+        Some(stop) => start.saturating_add(args.batch_size).min(stop.saturating_add(1)),
+        None => start.saturating_add(args.batch_size),
+    };
+    (start < end).then_some(start..end)
+}
+
+/// Log why the sync stopped after `head`.
+fn log_end(batch: Option<Range<u64>>, head: &Header) {
+    let message = match batch {
+        Some(_) => "reached the head of the node",
+        None => "reached --stop-at",
+    };
+    tracing::info!(number = %head.number, "{message}");
+}
+
+fn check_hash(number: &Uint, header: &Header, hash: &Hash32) -> Result<(), String> {
+    let computed_hash = header.hash();
+    if computed_hash != *hash {
+        return Err(format!(
+            "block {number} hashes to {}, but the node calls it {}",
+            hex(&computed_hash),
+            hex(hash)
+        ));
+    }
+    Ok(())
+}
+
+///
+///     Check the headers after `parent` against their parents, and their
+///     proofs of work, without executing anything.
+///
+fn validate_headers(
+    client: &RpcClient,
+    args: &Args,
+    schedule: &ForkSchedule,
+    mut parent: Header,
+    mut total_difficulty: Uint,
+    progress: &mut Progress,
+) -> Result<(), String> {
+    // The ethash cache of an epoch, kept for all of its blocks.
+    let mut cache: Option<(u64, Vec<Vec<u32>>)> = None;
+    loop {
+        let batch = next_batch(args, &parent);
+        let blocks = match batch.clone() {
+            Some(numbers) => client.get_blocks_by_number(numbers, false).map_err(|e| e.to_string())?,
+            None => vec![],
+        };
+        let blocks: Vec<_> = blocks.into_iter().map_while(|block| block).collect();
+        if blocks.is_empty() {
+            log_end(batch, &parent);
+            progress.finish();
+            return Ok(());
+        }
+
+        for block in blocks {
+            let header = block.header;
+            let number = header.number.clone();
+            check_hash(&number, &header, &block.hash)?;
+            let number_u64 = u64::try_from(&number).map_err(|e| e.to_string())?;
+            let timestamp = u64::try_from(&header.timestamp).unwrap_or(u64::MAX);
+            let fork = &schedule.forks()[schedule.active(number_u64, timestamp, &total_difficulty)];
+            match fork.fork {
+                Some(HardFork::Frontier) => {
+                    let invalid = |e| {
+                        format!(
+                            "block {number} ({}) is invalid under {}: {e:?}",
+                            hex(&block.hash),
+                            fork.name
+                        )
+                    };
+                    frontier::fork::validate_header(&header, parent).map_err(invalid)?;
+                    if !matches!(&cache, Some((epoch, _)) if *epoch == ethash::epoch(number_u64)) {
+                        tracing::debug!(epoch = ethash::epoch(number_u64), "generating the ethash cache");
+                        cache = Some((ethash::epoch(number_u64), ethash::generate_cache(number_u64)));
+                    }
+                    let (_, epoch_cache) = cache.as_ref().unwrap();
+                    frontier::fork::validate_proof_of_work(&header, epoch_cache).map_err(invalid)?;
+                }
+                None => return Err(format!("block {number} is of {}, which isn't implemented", fork.name)),
+            }
+            let transactions = match &block.transactions {
+                RpcTransactions::Hashes(hashes) => hashes.len() as u64,
+                RpcTransactions::Full(transactions) => transactions.len() as u64,
+            };
+            progress.record_block(
+                number_u64,
+                transactions,
+                u64::try_from(&header.gas_used).unwrap_or(u64::MAX),
+            );
+            total_difficulty += &header.difficulty;
+            parent = header;
+        }
+    }
+}
+
+///
+///     Find the last block of `chain` the node still has, once it has
+///     switched to another branch.
+///
+fn common_ancestor(client: &RpcClient, chain: &Chain) -> Result<u64, String> {
+    let blocks = chain.blocks();
+    let head = u64::try_from(&blocks.last().unwrap().header.number).map_err(|e| e.to_string())?;
+    let lowest = chain.rewindable_to().max(head + 1 - blocks.len() as u64);
+    let theirs = client.get_blocks_by_number(lowest..=head, false).map_err(|e| e.to_string())?;
+    let ours = &blocks[blocks.len() - theirs.len()..];
+    ours.iter()
+        .zip(theirs)
+        .rev()
+        .find(|(ours, theirs)| {
+            theirs
+                .as_ref()
+                .is_some_and(|theirs| theirs.hash == ours.hash())
+        })
+        .map(|(ours, _)| u64::try_from(&ours.header.number).unwrap_or(u64::MAX))
+        .ok_or_else(|| format!("the node switched to a branch leaving before block {lowest}, past --reorg-depth"))
+}
+
+/// Write the state after the head of `chain`, with the head's number.
+fn write_checkpoint(chain: &Chain, path: &Path) -> Result<(), String> {
+    let head = &chain.blocks().last().unwrap().header;
+    let mut checkpoint = dump_state(chain.state());
+    checkpoint["number"] = json!(u64::try_from(&head.number).map_err(|e| e.to_string())?);
+    std::fs::write(path, checkpoint.to_string()).map_err(|e| format!("{}: {e}", path.display()))?;
+    tracing::info!(number = %checkpoint["number"], path = %path.display(), "wrote checkpoint");
+    Ok(())
+}
+
+fn sync(args: Args) -> Result<(), String> {
+    let client = RpcClient::new(&args.rpc_url).map_err(|e| e.to_string())?;
+    let interval =
+        Duration::try_from_secs_f64(args.progress_interval).map_err(|e| format!("--progress-interval: {e}"))?;
+    let mut progress = Progress::new(interval);
+    #[cfg(feature = "metrics")]
+    if let Some(addr) = args.metrics_addr {
+        let addr = metrics::spawn(addr).map_err(|e| format!("--metrics-addr {addr}: {e}"))?;
+        tracing::info!(address = %addr, "serving metrics");
+    }
+
+    let genesis = genesis_chain(&args)?;
+    let info = genesis.info();
+    tracing::info!(
+        chainId = info.chain_id,
+        fork = info.fork.name,
+        consensus = info.consensus.name(),
+        "following chain"
+    );
+    let state = match &args.state {
+        Some(path) => {
+            let dump = std::fs::read_to_string(path).map_err(|e| format!("{}: {e}", path.display()))?;
+            let number = match args.start_at {
+                Some(number) => number,
+                None => {
+                    let checkpoint: Value =
+                        serde_json::from_str(&dump).map_err(|e| format!("{}: {e}", path.display()))?;
+                    checkpoint["number"]
+                        .as_u64()
+                        .ok_or_else(|| format!("{} has no block number, give it with --start-at", path.display()))?
+                }
+            };
+            let state = load_state_dump(&dump).map_err(|e| format!("{}: {e:?}", path.display()))?;
+            Some((number, state))
+        }
+        None => None,
+    };
+
+    if args.validate_only {
+        let (parent, total_difficulty) = match state.as_ref().map(|(number, _)| *number).or(args.start_at) {
+            Some(number) => get_block_header(&client, number)?,
+            None => (genesis.blocks()[0].header.clone(), genesis.total_difficulty().clone()),
+        };
+        return validate_headers(
+            &client,
+            &args,
+            genesis.schedule(),
+            parent,
+            total_difficulty,
+            &mut progress,
+        );
+    }
+    let mut chain = match state {
+        Some((number, state)) => resume(&client, &args, genesis, number, state)?,
+        None if args.start_at.is_some() => return Err("--start-at needs the --state after the block".to_owned()),
+        None => genesis,
+    }
+    .with_reorg_depth(args.reorg_depth);
+    if args.spot_check.is_some() && args.state_history.is_none() {
+        return Err("--spot-check needs --state-history".to_owned());
+    }
+    if let Some(path) = &args.state_history {
+        let store = match path.exists() {
+            true => NodeStore::load(path).map_err(|e| format!("{e:?}"))?,
+            false => NodeStore::for_state(),
+        };
+        chain = chain.with_state_history(store).map_err(|e| format!("{e:?}"))?;
+    }
+    // The states kept from this run on, which are sure to be there.
+    let history_from = u64::try_from(&chain.blocks().last().unwrap().header.number).unwrap_or(u64::MAX);
+    if let Some(path) = &args.header_archive {
+        let archive = HeaderArchive::open(path).map_err(|e| format!("{e:?}"))?;
+        let range = archive.range();
+        chain = chain.with_archive(archive).map_err(|_| {
+            format!(
+                "{} stops before block {}, leaving a gap before the blocks kept",
+                path.display(),
+                range.end
+            )
+        })?;
+    }
+
+    loop {
+        let batch = next_batch(&args, &chain.blocks().last().unwrap().header);
+        let blocks = match batch.clone() {
+            Some(numbers) => client.get_blocks(numbers).map_err(|e| e.to_string())?,
+            None => vec![],
+        };
+        if blocks.is_empty() {
+            log_end(batch, &chain.blocks().last().unwrap().header);
+            progress.finish();
+            if let Some(path) = &args.checkpoint {
+                write_checkpoint(&chain, path)?;
+            }
+            if let (Some(path), Some(store)) = (&args.state_history, chain.state_history()) {
+                store.save(path).map_err(|e| format!("{e:?}"))?;
+            }
+            return Ok(());
+        }
+
+        for (hash, block) in blocks {
+            let number = block.header.number.clone();
+            check_hash(&number, &block.header, &hash)?;
+            let head = &chain.blocks().last().unwrap().header;
+            if block.header.parent_hash != head.hash() {
+                let ancestor = common_ancestor(&client, &chain)?;
+                if Uint::from(ancestor) == head.number {
+                    return Err(format!("block {number} doesn't follow the node's block {ancestor}"));
+                }
+                tracing::warn!(head = %head.number, common_ancestor = %ancestor, "the node switched branches");
+                chain.rewind(ancestor).map_err(|e| format!("{e:?}"))?;
+                // Fetch the new branch from where it leaves ours.
+                break;
+            }
+            let fork = chain.fork_of(&block.header).name;
+            let addresses = match args.diagnose {
+                true => block_addresses(chain.state(), &block).ok(),
+                false => None,
+            };
+            let parent_root = chain.blocks().last().unwrap().header.state_root;
+            let expected_root = block.header.state_root;
+            let transactions = block.transactions.len() as u64;
+            let gas_used = u64::try_from(&block.header.gas_used).unwrap_or(u64::MAX);
+            #[cfg(feature = "metrics")]
+            let started = std::time::Instant::now();
+            let applied = chain.apply_block(block);
+            #[cfg(feature = "metrics")]
+            metrics::record_block_time(started.elapsed());
+            if let Err(e) = applied {
+                let mut message = match e.root_cause() {
+                    EthereumException::UnsupportedFork(fork) => {
+                        return Err(format!("block {number} is of {fork}, which isn't implemented"))
+                    }
+                    _ => format!("block {number} ({}) is invalid under {fork}: {e}", hex(&hash)),
+                };
+                // The state is left after the body only if the header and
+                // ommers were valid.
+                let root = state_root(chain.state());
+                if let (Some(addresses), true) = (addresses, root != parent_root && root != expected_root) {
+                    let number = u64::try_from(&number).map_err(|e| e.to_string())?;
+                    message += &format!(
+                        "\nstate root {} instead of {}: {}",
+                        hex(&root),
+                        hex(&expected_root),
+                        diagnose(&client, chain.state(), &expected_root, &addresses, number)?
+                    );
+                }
+                return Err(message);
+            }
+            if !args.receipt.is_empty() {
+                check_receipts(&client, &chain, &args.receipt)?;
+            }
+            let number_u64 = u64::try_from(&number).unwrap_or(u64::MAX);
+            if let Some(interval) = args.spot_check.filter(|interval| *interval > 0) {
+                if number_u64 % interval == 0 && number_u64.saturating_sub(interval) >= history_from {
+                    spot_check(&client, &chain, number_u64 - interval)?;
+                }
+            }
+            progress.record_block(u64::try_from(&number).unwrap_or(u64::MAX), transactions, gas_used);
+        }
+        tracing::info!(
+            number = %chain.blocks().last().unwrap().header.number,
+            state_root = %hex(&state_root(chain.state())),
+            "synced batch"
+        );
+    }
+}
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+    args.log.init();
+    match sync(args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            tracing::error!("{error}");
+            ExitCode::FAILURE
+        }
+    }
 }
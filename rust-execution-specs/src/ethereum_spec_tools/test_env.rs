@@ -0,0 +1,354 @@
+//!
+//! # Test Environment
+//!
+//! A builder for EVM unit tests: set up accounts, run transactions through
+//! the fork's `process_transaction`, and compare what they did against a
+//! text snapshot.
+//!
+//! ```
+//! use execution_specs_rs::ethereum_spec_tools::test_env::{TestEnv, TestTx};
+//!
+//! let contract = [0xcc; 20];
+//! // PUSH1 1, PUSH1 0, SSTORE
+//! let mut env = TestEnv::new().with_code(contract, [0x60, 0x01, 0x60, 0x00, 0x55]);
+//! env.run_tx(TestTx::call(contract)).unwrap().assert_snapshot(
+//!     "
+//!     gas used: 41006
+//!     0x000000000000000000000000000073656e646572: nonce 0 -> 1
+//!     0xcccccccccccccccccccccccccccccccccccccccc[0x0000000000000000000000000000000000000000000000000000000000000000]: 0x0 -> 0x1
+//!     ",
+//! );
+//! ```
+//!
+//! Transactions aren't signed: the sender is given directly. Gas is free by
+//! default, so the snapshot only shows the balances the execution moves.
+//! The coinbase exists from the start, so it isn't created by the payment of
+//! the fees either.
+//!
+
+use std::{cell::RefCell, fmt::Write, rc::Rc};
+
+use crate::ethereum::{
+    base_types::{Bytes, Bytes32, Uint, U256},
+    exceptions::EthereumException,
+    frontier::{
+        fork::process_transaction_traced,
+        fork_types::{Account, Address, Log, Transaction},
+        state::{
+            begin_transaction, commit_transaction, get_account, get_account_optional, rollback_transaction,
+            set_account, set_storage, transaction_diff, State, StateDiff,
+        },
+        vm::{BlockEnvironment, Evm, TxEnvironment},
+    },
+    trace::{TraceEvent, Tracer},
+    utils::hexadecimal::hex,
+};
+
+/// The sender of a [TestTx] unless another is given, funded by
+/// [TestEnv::new]. The same as geth's `evm` (`"sender"`).
+pub const SENDER: Address = hex_literal::hex!("000000000000000000000000000073656e646572");
+
+/// The coinbase of the block of a [TestEnv].
+pub const COINBASE: Address = hex_literal::hex!("00000000000000000000000000000000000c0ffe");
+
+/// A pre-state and block to run transactions in.
+pub struct TestEnv {
+    state: State,
+    block: BlockEnvironment,
+}
+
+impl Default for TestEnv {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TestEnv {
+    /// Block 1, with a gas limit of ten million, and a [SENDER] holding one
+    /// ether.
+    pub fn new() -> Self {
+        let env = Self {
+            state: State::default(),
+            block: BlockEnvironment {
+                coinbase: COINBASE,
+                number: Uint::from(1u8),
+                gas_limit: Uint::from(10_000_000u32),
+                ..Default::default()
+            },
+        };
+        env.with_account(COINBASE, Account::default())
+            .with_balance(SENDER, U256::from(10u8).pow(18))
+    }
+
+    /// Set an account, replacing any there is.
+    pub fn with_account(mut self, address: Address, account: Account) -> Self {
+        set_account(&mut self.state, address, Some(account));
+        self
+    }
+
+    fn modify(self, address: Address, f: impl FnOnce(&mut Account)) -> Self {
+        let mut account = get_account_optional(&self.state, &address).unwrap_or_default();
+        f(&mut account);
+        self.with_account(address, account)
+    }
+
+    /// Set the balance of an account, creating it if needed.
+    pub fn with_balance(self, address: Address, balance: U256) -> Self {
+        self.modify(address, |account| account.balance = balance)
+    }
+
+    /// Set the code of an account, creating it if needed.
+    pub fn with_code(self, address: Address, code: impl AsRef<[u8]>) -> Self {
+        self.modify(address, |account| account.code = code.as_ref().into())
+    }
+
+    /// Set a storage slot of an account, creating the account if needed.
+    pub fn with_storage(self, address: Address, key: Bytes32, value: U256) -> Self {
+        let mut env = self.modify(address, |_| {});
+        set_storage(&mut env.state, address, &key, value);
+        env
+    }
+
+    /// Run in another block.
+    pub fn with_block(mut self, block: BlockEnvironment) -> Self {
+        self.block = block;
+        self
+    }
+
+    /// The current state.
+    pub fn state(&self) -> &State {
+        &self.state
+    }
+
+    ///
+    ///     Run a transaction, keeping its changes.
+    ///
+    ///     Parameters
+    ///     ----------
+    ///     tx :
+    ///         The transaction. Its nonce is the sender's.
+    ///
+    ///     Returns
+    ///     -------
+    ///     outcome : `Result<TxOutcome, EthereumException>`
+    ///         What the transaction did, or why it couldn't be included.
+    ///
+    pub fn run_tx(&mut self, tx: TestTx) -> Result<TxOutcome, EthereumException> {
+        let transaction = Transaction {
+            nonce: get_account(&self.state, &tx.from).nonce,
+            gas_price: tx.gas_price.clone(),
+            gas: tx.gas,
+            to: tx.to,
+            value: tx.value,
+            data: tx.data,
+            v: U256::default(),
+            r: U256::default(),
+            s: U256::default(),
+        };
+        let tx_env = TxEnvironment {
+            origin: tx.from,
+            gas_price: tx.gas_price,
+        };
+
+        let end = Rc::new(RefCell::new(None));
+        let mut tracer: Option<Box<dyn Tracer<Evm>>> = Some(Box::new(EndTracer(end.clone())));
+        // The outer transaction records the state before, for the diff.
+        begin_transaction(&mut self.state);
+        let (gas_used, logs) =
+            match process_transaction_traced(&self.block, &tx_env, &mut self.state, &transaction, &mut tracer) {
+                Ok(result) => result,
+                Err(error) => {
+                    rollback_transaction(&mut self.state);
+                    return Err(error);
+                }
+            };
+        let state_diff = transaction_diff(&self.state);
+        commit_transaction(&mut self.state);
+
+        let (output, error) = end.borrow_mut().take().unwrap_or_default();
+        Ok(TxOutcome {
+            gas_used,
+            output,
+            error,
+            logs,
+            state_diff,
+        })
+    }
+}
+
+/// The output and error of a transaction.
+type TraceEnd = (Bytes, Option<String>);
+
+/// Keeps the output and error of the transaction.
+struct EndTracer(Rc<RefCell<Option<TraceEnd>>>);
+
+impl Tracer<Evm> for EndTracer {
+    fn trace(&mut self, _: &Evm, event: &TraceEvent) {
+        if let TraceEvent::TransactionEnd { output, error, .. } = event {
+            *self.0.borrow_mut() = Some((output.clone(), error.clone()));
+        }
+    }
+}
+
+/// A transaction for [TestEnv::run_tx], from [SENDER] with a million gas
+/// at no cost by default.
+#[derive(Clone, Debug)]
+pub struct TestTx {
+    from: Address,
+    to: Option<Address>,
+    value: U256,
+    data: Bytes,
+    gas: U256,
+    gas_price: U256,
+}
+
+impl TestTx {
+    fn new(to: Option<Address>, data: Bytes) -> Self {
+        Self {
+            from: SENDER,
+            to,
+            value: U256::default(),
+            data,
+            gas: U256::from(1_000_000u32),
+            gas_price: U256::default(),
+        }
+    }
+
+    /// A message call to `to`.
+    pub fn call(to: Address) -> Self {
+        Self::new(Some(to), Bytes::default())
+    }
+
+    /// The creation of a contract, running `init_code`.
+    pub fn create(init_code: impl AsRef<[u8]>) -> Self {
+        Self::new(None, init_code.as_ref().into())
+    }
+
+    pub fn from(mut self, from: Address) -> Self {
+        self.from = from;
+        self
+    }
+
+    pub fn value(mut self, value: U256) -> Self {
+        self.value = value;
+        self
+    }
+
+    /// The call data, or the init code of a creation.
+    pub fn data(mut self, data: impl AsRef<[u8]>) -> Self {
+        self.data = data.as_ref().into();
+        self
+    }
+
+    pub fn gas(mut self, gas: U256) -> Self {
+        self.gas = gas;
+        self
+    }
+
+    pub fn gas_price(mut self, gas_price: U256) -> Self {
+        self.gas_price = gas_price;
+        self
+    }
+}
+
+/// What a transaction run with [TestEnv::run_tx] did.
+#[derive(Clone, Debug)]
+pub struct TxOutcome {
+    /// Gas used, after refunds.
+    pub gas_used: Uint,
+    /// The return data of the top frame, or the deployed code.
+    pub output: Bytes,
+    /// The exceptional halt of the top frame, if any.
+    pub error: Option<String>,
+    pub logs: Vec<Log>,
+    /// Every account the transaction changed.
+    pub state_diff: StateDiff,
+}
+
+fn account_changes(pre: &Account, post: &Account) -> Vec<String> {
+    let mut changes = vec![];
+    if pre.nonce != post.nonce {
+        changes.push(format!("nonce {} -> {}", pre.nonce, post.nonce));
+    }
+    if pre.balance != post.balance {
+        changes.push(format!("balance {} -> {}", pre.balance, post.balance));
+    }
+    if pre.code != post.code {
+        changes.push(format!("code {} -> {}", hex(&pre.code), hex(&post.code)));
+    }
+    changes
+}
+
+fn describe(account: &Account) -> String {
+    let code = match account.code.is_empty() {
+        true => String::new(),
+        false => format!(", code {}", hex(&account.code)),
+    };
+    format!("nonce {}, balance {}{code}", account.nonce, account.balance)
+}
+
+impl TxOutcome {
+    ///
+    ///     Describe the outcome, one fact per line: the gas used, the error,
+    ///     the output, each log, then each changed account and slot in
+    ///     address order. Accounts are created with all their fields.
+    ///
+    pub fn snapshot(&self) -> String {
+        let mut snapshot = format!("gas used: {}\n", self.gas_used);
+        if let Some(error) = &self.error {
+            writeln!(snapshot, "error: {error}").unwrap();
+        }
+        if !self.output.is_empty() {
+            writeln!(snapshot, "output: {}", hex(&self.output)).unwrap();
+        }
+        for log in &self.logs {
+            let topics: Vec<String> = log.topics.iter().map(|topic| hex(topic)).collect();
+            writeln!(snapshot, "log {}: [{}] {}", hex(&log.address), topics.join(", "), hex(&log.data)).unwrap();
+        }
+        for (address, diff) in &self.state_diff {
+            let change = match (&diff.pre, &diff.post) {
+                (None, Some(post)) => format!("created with {}", describe(post)),
+                (Some(_), None) => "destroyed".to_owned(),
+                (Some(pre), Some(post)) => account_changes(pre, post).join(", "),
+                (None, None) => String::new(),
+            };
+            if !change.is_empty() {
+                writeln!(snapshot, "{}: {change}", hex(address)).unwrap();
+            }
+            for (key, (pre, post)) in &diff.storage {
+                writeln!(snapshot, "{}[{}]: {pre:#x} -> {post:#x}", hex(address), hex(key)).unwrap();
+            }
+        }
+        snapshot
+    }
+
+    ///
+    ///     Check the outcome against a snapshot.
+    ///
+    ///     The indentation and the blank lines around `expected` are
+    ///     ignored, so it can be written as an indented string literal.
+    ///
+    ///     Panics with both snapshots if they differ.
+    ///
+    #[track_caller]
+    pub fn assert_snapshot(&self, expected: &str) {
+        let expected: String = expected
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(|line| format!("{line}\n"))
+            .collect();
+        let actual = self.snapshot();
+        assert!(
+            actual == expected,
+            "snapshot mismatch\n--- expected\n{expected}--- actual\n{actual}"
+        );
+    }
+}
+
+/// The key of the storage slot `index`, for [TestEnv::with_storage].
+pub fn slot(index: u64) -> Bytes32 {
+    let mut key = [0; 32];
+    key[24..].copy_from_slice(&index.to_be_bytes());
+    key
+}
@@ -0,0 +1,165 @@
+//!
+//! # Test Fixtures
+//!
+//! Well-known accounts for tests, with deterministic private keys, and
+//! pre-states that fund them, so that the tests of the crate and of its
+//! users share the same readable scaffolding.
+//!
+//! ```
+//! use execution_specs_rs::ethereum::frontier::state::get_account;
+//! use execution_specs_rs::ethereum_spec_tools::test_fixtures::{funded_state, test_balance, ALICE, BOB};
+//!
+//! let state = funded_state(&[ALICE, BOB]);
+//! assert_eq!(get_account(&state, &BOB.address).balance, test_balance());
+//! ```
+//!
+//! The private keys are the scalars 1, 2, 3 and so on, whose addresses are
+//! as well known as their keys: never send them anything of value.
+//!
+
+use std::collections::HashMap;
+
+use crate::ethereum::{
+    base_types::{Bytes32, U256, U64},
+    crypto::{elliptic_curve::secp256k1_public_key, hash::keccak256},
+    exceptions::EthereumException,
+    frontier::{
+        fork_types::{Account, Address, Transaction},
+        state::{set_account, State},
+    },
+    transaction_signature::sign_transaction,
+};
+
+/// An account whose private key is known.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct TestAccount {
+    pub secret_key: Bytes32,
+    pub address: Address,
+}
+
+/// The private key that is the scalar `n`.
+const fn key(n: u8) -> Bytes32 {
+    let mut key = [0; 32];
+    key[31] = n;
+    key
+}
+
+pub const ALICE: TestAccount = TestAccount {
+    secret_key: key(1),
+    address: hex_literal::hex!("7e5f4552091a69125d5dfcb7b8c2659029395bdf"),
+};
+
+pub const BOB: TestAccount = TestAccount {
+    secret_key: key(2),
+    address: hex_literal::hex!("2b5ad5c4795c026514f8317c7a215e218dccd6cf"),
+};
+
+pub const CAROL: TestAccount = TestAccount {
+    secret_key: key(3),
+    address: hex_literal::hex!("6813eb9362372eef6200f3b1dbc3f819671cba69"),
+};
+
+pub const DAVE: TestAccount = TestAccount {
+    secret_key: key(4),
+    address: hex_literal::hex!("1eff47bc3a10a45d4b230b5d10e37751fe6aa718"),
+};
+
+/// The accounts above, in the order of their keys.
+pub const TEST_ACCOUNTS: [TestAccount; 4] = [ALICE, BOB, CAROL, DAVE];
+
+impl TestAccount {
+    ///
+    ///     The account of a private key.
+    ///
+    ///     Parameters
+    ///     ----------
+    ///     secret_key :
+    ///         The private key.
+    ///
+    ///     Raises
+    ///     ------
+    ///     ValueError :
+    ///         If the key isn't a valid secp256k1 private key.
+    ///
+    pub fn from_secret_key(secret_key: Bytes32) -> Result<Self, EthereumException> {
+        let public_key = secp256k1_public_key(&secret_key)?;
+        Ok(Self {
+            secret_key,
+            address: keccak256(&public_key)[12..].try_into().unwrap(),
+        })
+    }
+
+    ///
+    ///     The `n`th of a sequence of accounts, for tests that need more than
+    ///     [TEST_ACCOUNTS]: the account whose private key is the scalar
+    ///     `n + 1`, so the first ones are those of [TEST_ACCOUNTS].
+    ///
+    pub fn nth(n: u32) -> Self {
+        let mut secret_key = [0; 32];
+        secret_key[28..].copy_from_slice(&(n + 1).to_be_bytes());
+        Self::from_secret_key(secret_key).expect("small scalars are valid keys")
+    }
+
+    ///
+    ///     Sign a legacy transaction with the key of the account.
+    ///
+    ///     Parameters
+    ///     ----------
+    ///     tx :
+    ///         The transaction. Its signature is ignored.
+    ///     chain_id :
+    ///         The chain to sign for with EIP-155, or `None`.
+    ///
+    pub fn sign(&self, tx: &Transaction, chain_id: Option<U64>) -> Transaction {
+        sign_transaction(tx, &self.secret_key, chain_id).expect("test keys are valid")
+    }
+}
+
+/// The balance the accounts of a pre-state are funded with: a thousand
+/// ether.
+pub fn test_balance() -> U256 {
+    U256::from(1000u16) * U256::from(10u8).pow(18)
+}
+
+/// An externally owned account with a balance.
+pub fn funded_account(balance: U256) -> Account {
+    Account {
+        balance,
+        ..Default::default()
+    }
+}
+
+/// A contract with code and nothing else.
+pub fn contract_account(code: impl AsRef<[u8]>) -> Account {
+    Account {
+        code: code.as_ref().into(),
+        ..Default::default()
+    }
+}
+
+/// A state of the given accounts.
+pub fn pre_state(accounts: impl IntoIterator<Item = (Address, Account)>) -> State {
+    let mut state = State::default();
+    for (address, account) in accounts {
+        set_account(&mut state, address, Some(account));
+    }
+    state
+}
+
+/// A state in which each of `accounts` has [test_balance].
+pub fn funded_state(accounts: &[TestAccount]) -> State {
+    pre_state(
+        accounts
+            .iter()
+            .map(|account| (account.address, funded_account(test_balance()))),
+    )
+}
+
+/// The `initial_balances` of a genesis in which each of `accounts` has
+/// [test_balance].
+pub fn initial_balances(accounts: &[TestAccount]) -> HashMap<Address, U256> {
+    accounts
+        .iter()
+        .map(|account| (account.address, test_balance()))
+        .collect()
+}
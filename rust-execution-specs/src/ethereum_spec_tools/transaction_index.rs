@@ -0,0 +1,238 @@
+//!
+//! # Transaction Index
+//!
+//! Where each transaction a [Chain](super::chain::Chain) applied is, by its
+//! hash, and the receipts of the blocks it holds, so that transactions and
+//! their receipts can be looked up the way `eth_getTransactionByHash` and
+//! `eth_getTransactionReceipt` do.
+//!
+//! The locations are kept for the whole chain. The receipts are kept only
+//! for the blocks the chain still holds, as are the transactions they need:
+//! a transaction of a block pruned since is found, but not its receipt.
+//!
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::LowerHex,
+};
+
+use serde_json::{json, Value};
+
+use super::evm_tools::t8n::transaction_hash;
+use crate::ethereum::{
+    base_types::Uint,
+    frontier::{
+        fork_types::{Address, Block, Hash32, Log, Receipt, Transaction},
+        utils::address::compute_contract_address,
+    },
+    utils::hexadecimal::hex,
+};
+
+fn quantity(number: impl LowerHex) -> Value {
+    Value::String(format!("{number:#x}"))
+}
+
+/// Where a transaction is in the chain.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TransactionLocation {
+    pub block_number: u64,
+    /// The position of the transaction in its block.
+    pub index: usize,
+}
+
+///
+///     A transaction of the chain, with where it is and who sent it.
+///
+#[derive(Clone, Debug)]
+pub struct IndexedTransaction {
+    pub transaction: Transaction,
+    pub hash: Hash32,
+    pub block_hash: Hash32,
+    pub location: TransactionLocation,
+    pub from: Address,
+}
+
+impl IndexedTransaction {
+    /// The transaction as `eth_getTransactionByHash` returns it.
+    pub fn to_json(&self) -> Value {
+        let tx = &self.transaction;
+        json!({
+            "hash": hex(&self.hash),
+            "blockHash": hex(&self.block_hash),
+            "blockNumber": quantity(self.location.block_number),
+            "transactionIndex": quantity(self.location.index),
+            "from": hex(&self.from),
+            "to": tx.to.map(|to| hex(&to)),
+            "nonce": quantity(&tx.nonce),
+            "gas": quantity(&tx.gas),
+            "gasPrice": quantity(&tx.gas_price),
+            "value": quantity(&tx.value),
+            "input": hex(&tx.data),
+            "v": quantity(&tx.v),
+            "r": quantity(&tx.r),
+            "s": quantity(&tx.s),
+            "type": "0x0",
+        })
+    }
+}
+
+///
+///     The receipt of a transaction of the chain, with what
+///     `eth_getTransactionReceipt` adds to the receipt of the block.
+///
+#[derive(Clone, Debug)]
+pub struct TransactionReceipt {
+    pub transaction: IndexedTransaction,
+    pub receipt: Receipt,
+    pub gas_used: Uint,
+    /// The address of the contract the transaction created, if it is a
+    /// contract creation.
+    pub contract_address: Option<Address>,
+    /// The position in the block of the first log of the transaction.
+    pub first_log_index: usize,
+}
+
+impl TransactionReceipt {
+    fn log_json(&self, position: usize, log: &Log) -> Value {
+        let transaction = &self.transaction;
+        json!({
+            "address": hex(&log.address),
+            "topics": log.topics.iter().map(|topic| hex(topic)).collect::<Vec<_>>(),
+            "data": hex(&log.data),
+            "blockNumber": quantity(transaction.location.block_number),
+            "blockHash": hex(&transaction.block_hash),
+            "transactionHash": hex(&transaction.hash),
+            "transactionIndex": quantity(transaction.location.index),
+            "logIndex": quantity(self.first_log_index + position),
+            "removed": false,
+        })
+    }
+
+    /// The receipt as `eth_getTransactionReceipt` returns it, with the
+    /// state root of a receipt from before Byzantium.
+    pub fn to_json(&self) -> Value {
+        let transaction = &self.transaction;
+        json!({
+            "transactionHash": hex(&transaction.hash),
+            "transactionIndex": quantity(transaction.location.index),
+            "blockHash": hex(&transaction.block_hash),
+            "blockNumber": quantity(transaction.location.block_number),
+            "from": hex(&transaction.from),
+            "to": transaction.transaction.to.map(|to| hex(&to)),
+            "cumulativeGasUsed": quantity(&self.receipt.cumulative_gas_used),
+            "gasUsed": quantity(&self.gas_used),
+            "effectiveGasPrice": quantity(&transaction.transaction.gas_price),
+            "contractAddress": self.contract_address.map(|address| hex(&address)),
+            "logs": self.receipt.logs.iter().enumerate().map(|(position, log)| self.log_json(position, log)).collect::<Vec<_>>(),
+            "logsBloom": hex(&self.receipt.bloom),
+            "root": hex(&self.receipt.post_state),
+            "type": "0x0",
+        })
+    }
+}
+
+///
+///     The transactions of a chain by hash, and the receipts of its recent
+///     blocks.
+///
+#[derive(Clone, Debug, Default)]
+pub struct TransactionIndex {
+    locations: HashMap<Hash32, TransactionLocation>,
+    /// The receipts of each block, by number.
+    receipts: BTreeMap<u64, Vec<Receipt>>,
+}
+
+impl TransactionIndex {
+    ///
+    ///     Index the transactions of a block.
+    ///
+    ///     Parameters
+    ///     ----------
+    ///     block :
+    ///         The block, of number `number`.
+    ///     receipts :
+    ///         The receipts of its transactions, if it was executed.
+    ///
+    pub fn add_block(&mut self, number: u64, block: &Block, receipts: Option<Vec<Receipt>>) {
+        for (index, tx) in block.transactions.iter().enumerate() {
+            let location = TransactionLocation {
+                block_number: number,
+                index,
+            };
+            self.locations.insert(transaction_hash(tx), location);
+        }
+        if let Some(receipts) = receipts {
+            self.receipts.insert(number, receipts);
+        }
+    }
+
+    ///
+    ///     Forget the transactions of a block, taken off the chain.
+    ///
+    pub fn remove_block(&mut self, number: u64, block: &Block) {
+        for tx in &block.transactions {
+            self.locations.remove(&transaction_hash(tx));
+        }
+        self.receipts.remove(&number);
+    }
+
+    ///
+    ///     Forget the receipts of a block, keeping where its transactions
+    ///     are.
+    ///
+    pub fn drop_receipts(&mut self, number: u64) {
+        self.receipts.remove(&number);
+    }
+
+    /// The number of transactions indexed.
+    pub fn len(&self) -> usize {
+        self.locations.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.locations.is_empty()
+    }
+
+    pub fn location(&self, hash: &Hash32) -> Option<&TransactionLocation> {
+        self.locations.get(hash)
+    }
+
+    /// The receipts of the block `number`, if they are kept.
+    pub fn receipts(&self, number: u64) -> Option<&[Receipt]> {
+        self.receipts.get(&number).map(|receipts| &receipts[..])
+    }
+}
+
+///
+///     Complete the receipt of a transaction of a block.
+///
+///     Parameters
+///     ----------
+///     transaction :
+///         The transaction.
+///     receipts :
+///         The receipts of the block.
+///
+pub(crate) fn transaction_receipt(transaction: IndexedTransaction, receipts: &[Receipt]) -> Option<TransactionReceipt> {
+    let index = transaction.location.index;
+    let receipt = receipts.get(index)?.clone();
+    let previous = &receipts[..index];
+    let gas_used = match previous.last() {
+        Some(previous) => &receipt.cumulative_gas_used - &previous.cumulative_gas_used,
+        None => receipt.cumulative_gas_used.clone(),
+    };
+    let contract_address = match transaction.transaction.to {
+        Some(_) => None,
+        None => Some(compute_contract_address(
+            &transaction.from,
+            &transaction.transaction.nonce,
+        )),
+    };
+    Some(TransactionReceipt {
+        first_log_index: previous.iter().map(|receipt| receipt.logs.len()).sum(),
+        transaction,
+        receipt,
+        gas_used,
+        contract_address,
+    })
+}
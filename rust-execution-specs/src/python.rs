@@ -0,0 +1,466 @@
+//!
+//! # Python Bindings
+//!
+//! The `execution_specs_rs` Python module, with the same functions and
+//! signatures as the Python execution-specs, so a test script can run
+//! against either implementation:
+//!
+//! ```text
+//! from ethereum import rlp                      import execution_specs_rs.rlp as rlp
+//! from ethereum.frontier.trie import root       from execution_specs_rs.trie import root
+//! ```
+//!
+//! Build it as an extension module with
+//!
+//! ```text
+//! cargo rustc --lib --release --features python --crate-type cdylib
+//! cp target/release/libexecution_specs_rs.so execution_specs_rs.so
+//! ```
+//!
+//! Values are converted by their shape rather than their class, so the
+//! dataclasses of the Python spec can be passed in directly: a trie is
+//! anything with `secured` and `_data`, an account anything with `nonce`,
+//! `balance` and `code`, and a block anything with the fields of a Frontier
+//! `Block`.
+//!
+
+// The `#[pyfunction]` expansion of pyo3 0.22 converts errors into themselves.
+#![allow(clippy::useless_conversion)]
+
+use num_bigint::BigUint;
+use pyo3::{
+    create_exception,
+    exceptions::PyException,
+    prelude::*,
+    types::{PyBool, PyBytes, PyDict, PyInt, PyList, PyString, PyTuple},
+};
+
+use crate::{
+    ethereum::{
+        base_types::Bytes,
+        exceptions,
+        frontier::{
+            fork::{self, BlockChain as RustBlockChain},
+            fork_types::{encode_account, Account, Block, Header, Root, Transaction},
+            state::{state_root, State},
+            trie::{self, dummy_root, trie_set as rust_trie_set},
+        },
+        genesis::{add_genesis_block, parse_genesis_configuration},
+        rlp::{self, Simple},
+    },
+    ethereum_spec_tools::forks::HardFork,
+};
+
+create_exception!(execution_specs_rs, EthereumException, PyException);
+create_exception!(execution_specs_rs, InvalidBlock, EthereumException);
+create_exception!(execution_specs_rs, RLPDecodingError, EthereumException);
+create_exception!(execution_specs_rs, RLPEncodingError, EthereumException);
+
+fn to_py_err(exception: exceptions::EthereumException) -> PyErr {
+    match exception.root_cause() {
+        exceptions::EthereumException::InvalidBlock => InvalidBlock::new_err(exception.to_string()),
+        exceptions::EthereumException::RLPDecodingError => RLPDecodingError::new_err(exception.to_string()),
+        exceptions::EthereumException::RLPEncodingError => RLPEncodingError::new_err(exception.to_string()),
+        _ => EthereumException::new_err(exception.to_string()),
+    }
+}
+
+fn uint_to_bytes(number: &BigUint) -> Bytes {
+    if *number == BigUint::default() {
+        Bytes::default()
+    } else {
+        number.to_bytes_be().into()
+    }
+}
+
+fn to_simple(value: &Bound<PyAny>) -> PyResult<Simple> {
+    let py = value.py();
+    if let Ok(bytes) = value.downcast::<PyBytes>() {
+        return Ok(Simple::Bytes(bytes.as_bytes().into()));
+    }
+    if value.is_instance_of::<PyBool>() {
+        let bytes: &[u8] = if value.is_truthy()? { &[1] } else { &[] };
+        return Ok(Simple::Bytes(bytes.into()));
+    }
+    if value.is_instance_of::<PyInt>() {
+        let number: BigUint = value
+            .extract()
+            .map_err(|_| RLPEncodingError::new_err("negative integer"))?;
+        return Ok(Simple::Bytes(uint_to_bytes(&number)));
+    }
+    if let Ok(string) = value.downcast::<PyString>() {
+        return Ok(Simple::Bytes(string.to_str()?.as_bytes().into()));
+    }
+    if value.is_instance_of::<PyList>() || value.is_instance_of::<PyTuple>() {
+        let items = value.iter()?.map(|item| to_simple(&item?)).collect::<PyResult<_>>()?;
+        return Ok(Simple::Sequence(items));
+    }
+    let dataclasses = py.import_bound("dataclasses")?;
+    if dataclasses.call_method1("is_dataclass", (value,))?.is_truthy()? {
+        let mut items = vec![];
+        for field in dataclasses.call_method1("fields", (value,))?.iter()? {
+            let name = field?.getattr("name")?;
+            items.push(to_simple(&value.getattr(name.downcast::<PyString>()?)?)?);
+        }
+        return Ok(Simple::Sequence(items));
+    }
+    Err(RLPEncodingError::new_err(format!(
+        "RLP Encoding of type {} is not supported",
+        value.get_type().name()?
+    )))
+}
+
+fn from_simple(py: Python, value: Simple) -> PyObject {
+    match value {
+        Simple::Bytes(bytes) => PyBytes::new_bound(py, &bytes).into_py(py),
+        Simple::Sequence(items) => {
+            let items: Vec<PyObject> = items.into_iter().map(|item| from_simple(py, item)).collect();
+            PyList::new_bound(py, items).into_py(py)
+        }
+    }
+}
+
+///
+///     Encodes `raw_data` into a sequence of bytes using RLP.
+///
+///     Parameters
+///     ----------
+///     raw_data :
+///         A `bytes`, `int`, `bool`, `str`, sequence of them, or a dataclass
+///         whose fields are.
+///
+///     Returns
+///     -------
+///     encoded : `bytes`
+///         The RLP encoded bytes representing `raw_data`.
+///
+#[pyfunction]
+fn encode<'py>(py: Python<'py>, raw_data: &Bound<'py, PyAny>) -> PyResult<Bound<'py, PyBytes>> {
+    Ok(PyBytes::new_bound(py, &rlp::encode(&to_simple(raw_data)?)))
+}
+
+///
+///     Decodes an integer, byte sequence, or list of RLP encodable objects
+///     from the byte sequence `encoded_data`, using RLP.
+///
+///     Parameters
+///     ----------
+///     encoded_data :
+///         A sequence of bytes, in RLP form.
+///
+///     Returns
+///     -------
+///     decoded_data : `bytes | list`
+///         Object decoded from `encoded_data`.
+///
+#[pyfunction]
+fn decode(py: Python, encoded_data: &[u8]) -> PyResult<PyObject> {
+    let decoded = rlp::decode(encoded_data).map_err(to_py_err)?;
+    Ok(from_simple(py, decoded))
+}
+
+///
+///     The Merkle Trie, with the fields of the spec's `Trie` dataclass.
+///
+#[pyclass(module = "execution_specs_rs.trie")]
+struct Trie {
+    #[pyo3(get, set)]
+    secured: bool,
+    #[pyo3(get, set)]
+    default: PyObject,
+    #[pyo3(get, name = "_data")]
+    data: Py<PyDict>,
+}
+
+#[pymethods]
+impl Trie {
+    #[new]
+    #[pyo3(signature = (secured, default, _data = None))]
+    fn new(py: Python, secured: bool, default: PyObject, _data: Option<Py<PyDict>>) -> Self {
+        Self {
+            secured,
+            default,
+            data: _data.unwrap_or_else(|| PyDict::new_bound(py).unbind()),
+        }
+    }
+}
+
+///
+///     Stores an item in a Merkle Trie.
+///
+///     This method deletes the key if `value == trie.default`, because the
+///     Merkle Trie represents the default value by omitting it from the trie.
+///
+///     Parameters
+///     ----------
+///     trie :
+///         Trie to store in.
+///     key :
+///         Key to lookup.
+///     value :
+///         Node to insert at `key`.
+///
+#[pyfunction]
+fn trie_set(trie: &Bound<PyAny>, key: &Bound<PyAny>, value: &Bound<PyAny>) -> PyResult<()> {
+    let data = trie.getattr("_data")?;
+    if value.eq(trie.getattr("default")?)? {
+        if data.contains(key)? {
+            data.del_item(key)?;
+        }
+    } else {
+        data.set_item(key, value)?;
+    }
+    Ok(())
+}
+
+///
+///     Gets an item from the Merkle Trie.
+///
+///     This method returns `trie.default` if the key is missing.
+///
+///     Parameters
+///     ----------
+///     trie :
+///         Trie to lookup in.
+///     key :
+///         Key to lookup.
+///
+///     Returns
+///     -------
+///     node :
+///         Node at `key` in the trie.
+///
+#[pyfunction]
+fn trie_get<'py>(trie: &Bound<'py, PyAny>, key: &Bound<'py, PyAny>) -> PyResult<Bound<'py, PyAny>> {
+    trie.getattr("_data")?
+        .call_method1("get", (key, trie.getattr("default")?))
+}
+
+fn encode_trie_value(
+    key: &[u8],
+    value: &Bound<PyAny>,
+    get_storage_root: Option<&Bound<PyAny>>,
+) -> PyResult<Bytes> {
+    if let Ok(bytes) = value.downcast::<PyBytes>() {
+        return Ok(bytes.as_bytes().into());
+    }
+    if value.is_instance_of::<PyInt>() {
+        return Ok(rlp::encode(&value.extract::<BigUint>()?));
+    }
+    if value.hasattr("nonce")? && value.hasattr("balance")? && value.hasattr("code")? {
+        let get_storage_root = get_storage_root.ok_or_else(|| {
+            EthereumException::new_err("get_storage_root must be given to encode accounts")
+        })?;
+        let storage_root: Root = get_storage_root
+            .call1((PyBytes::new_bound(value.py(), key),))?
+            .extract::<&[u8]>()?
+            .try_into()
+            .map_err(|_| EthereumException::new_err("storage roots are 32 bytes"))?;
+        let account = Account {
+            nonce: value.getattr("nonce")?.extract()?,
+            balance: value.getattr("balance")?.extract()?,
+            code: value.getattr("code")?.extract::<&[u8]>()?.into(),
+        };
+        return Ok(encode_account(account, storage_root));
+    }
+    Err(EthereumException::new_err(format!(
+        "encoding for {} is not currently implemented",
+        value.get_type().name()?
+    )))
+}
+
+///
+///     Computes the root of a modified merkle patricia trie (MPT).
+///
+///     Parameters
+///     ----------
+///     trie :
+///         `Trie` to get the root of.
+///     get_storage_root :
+///         Function to get the storage root of an account. Needed to encode
+///         `Account` objects.
+///
+///     Returns
+///     -------
+///     root : `bytes`
+///         MPT root of the underlying key-value pairs.
+///
+#[pyfunction]
+#[pyo3(signature = (trie, get_storage_root = None))]
+fn root<'py>(
+    py: Python<'py>,
+    trie: &Bound<'py, PyAny>,
+    get_storage_root: Option<&Bound<'py, PyAny>>,
+) -> PyResult<Bound<'py, PyBytes>> {
+    let mut encoded = trie::Trie::<Bytes, Bytes>::new(trie.getattr("secured")?.is_truthy()?, Bytes::default());
+    for item in trie.getattr("_data")?.call_method0("items")?.iter()? {
+        let (key, value): (Bound<PyAny>, Bound<PyAny>) = item?.extract()?;
+        let key: Bytes = key.extract::<&[u8]>()?.into();
+        let value = encode_trie_value(&key, &value, get_storage_root)?;
+        rust_trie_set(&mut encoded, key, value);
+    }
+    Ok(PyBytes::new_bound(py, &trie::root(&encoded, dummy_root)))
+}
+
+fn extract_header(header: &Bound<PyAny>) -> PyResult<Header> {
+    let field = |name: &str| header.getattr(name);
+    let fixed = |name: &str| -> PyResult<Vec<u8>> { Ok(field(name)?.extract::<&[u8]>()?.to_vec()) };
+    let invalid = |name: &str| EthereumException::new_err(format!("header {name} has the wrong length"));
+    Ok(Header {
+        parent_hash: fixed("parent_hash")?.try_into().map_err(|_| invalid("parent_hash"))?,
+        ommers_hash: fixed("ommers_hash")?.try_into().map_err(|_| invalid("ommers_hash"))?,
+        coinbase: fixed("coinbase")?.try_into().map_err(|_| invalid("coinbase"))?,
+        state_root: fixed("state_root")?.try_into().map_err(|_| invalid("state_root"))?,
+        transactions_root: fixed("transactions_root")?
+            .try_into()
+            .map_err(|_| invalid("transactions_root"))?,
+        receipt_root: fixed("receipt_root")?.try_into().map_err(|_| invalid("receipt_root"))?,
+        bloom: fixed("bloom")?.try_into().map_err(|_| invalid("bloom"))?,
+        difficulty: field("difficulty")?.extract()?,
+        number: field("number")?.extract()?,
+        gas_limit: field("gas_limit")?.extract()?,
+        gas_used: field("gas_used")?.extract()?,
+        timestamp: field("timestamp")?.extract()?,
+        extra_data: fixed("extra_data")?.into(),
+        mix_digest: fixed("mix_digest")?.try_into().map_err(|_| invalid("mix_digest"))?,
+        nonce: fixed("nonce")?.try_into().map_err(|_| invalid("nonce"))?,
+    })
+}
+
+fn extract_transaction(tx: &Bound<PyAny>) -> PyResult<Transaction> {
+    let field = |name: &str| tx.getattr(name);
+    // The spec uses empty bytes for the `to` of a contract creation.
+    let to = field("to")?;
+    let to = to.extract::<&[u8]>()?;
+    let to = match to.len() {
+        0 => None,
+        _ => Some(to.try_into().map_err(|_| EthereumException::new_err("addresses are 20 bytes"))?),
+    };
+    Ok(Transaction {
+        nonce: field("nonce")?.extract()?,
+        gas_price: field("gas_price")?.extract()?,
+        gas: field("gas")?.extract()?,
+        to,
+        value: field("value")?.extract()?,
+        data: field("data")?.extract::<&[u8]>()?.into(),
+        v: field("v")?.extract()?,
+        r: field("r")?.extract()?,
+        s: field("s")?.extract()?,
+    })
+}
+
+fn extract_block(block: &Bound<PyAny>) -> PyResult<Block> {
+    Ok(Block {
+        header: extract_header(&block.getattr("header")?)?,
+        transactions: block
+            .getattr("transactions")?
+            .iter()?
+            .map(|tx| extract_transaction(&tx?))
+            .collect::<PyResult<_>>()?,
+        ommers: block
+            .getattr("ommers")?
+            .iter()?
+            .map(|ommer| extract_header(&ommer?))
+            .collect::<PyResult<_>>()?,
+    })
+}
+
+///
+///     History and current state of the block chain.
+///
+#[pyclass(module = "execution_specs_rs.fork")]
+struct BlockChain {
+    chain: RustBlockChain,
+}
+
+#[pymethods]
+impl BlockChain {
+    ///
+    ///     Start a chain from the genesis block of a genesis json file.
+    ///
+    #[staticmethod]
+    fn from_genesis(genesis: &str) -> PyResult<Self> {
+        let mut chain = RustBlockChain {
+            blocks: vec![],
+            state: State::default(),
+            chain_id: 0,
+        };
+        let genesis = parse_genesis_configuration(genesis).map_err(to_py_err)?;
+        add_genesis_block(HardFork::Frontier, &mut chain, genesis).map_err(to_py_err)?;
+        Ok(Self { chain })
+    }
+
+    #[getter]
+    fn chain_id(&self) -> u64 {
+        self.chain.chain_id
+    }
+
+    /// The root of the current state.
+    #[getter]
+    fn state_root<'py>(&self, py: Python<'py>) -> Bound<'py, PyBytes> {
+        PyBytes::new_bound(py, &state_root(&self.chain.state))
+    }
+
+    /// The number of the last block.
+    #[getter]
+    fn head_number(&self) -> BigUint {
+        let last = self.chain.blocks.last().expect("the chain has a genesis block");
+        last.header.number.clone()
+    }
+}
+
+///
+///     Attempts to apply a block to an existing block chain.
+///
+///     Parameters
+///     ----------
+///     chain :
+///         History and current state.
+///     block :
+///         Block to apply to `chain`.
+///
+#[pyfunction]
+fn state_transition(chain: &Bound<BlockChain>, block: &Bound<PyAny>) -> PyResult<()> {
+    let block = extract_block(block)?;
+    fork::state_transition(&mut chain.borrow_mut().chain, block).map_err(to_py_err)
+}
+
+fn add_submodule<'py>(
+    parent: &Bound<'py, PyModule>,
+    name: &str,
+    init: impl FnOnce(&Bound<'py, PyModule>) -> PyResult<()>,
+) -> PyResult<()> {
+    let py = parent.py();
+    let module = PyModule::new_bound(py, name)?;
+    init(&module)?;
+    parent.add_submodule(&module)?;
+    // Let `import execution_specs_rs.rlp` find it.
+    py.import_bound("sys")?
+        .getattr("modules")?
+        .set_item(format!("execution_specs_rs.{name}"), &module)?;
+    Ok(())
+}
+
+#[pymodule]
+fn execution_specs_rs(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    let py = m.py();
+    m.add("EthereumException", py.get_type_bound::<EthereumException>())?;
+    m.add("InvalidBlock", py.get_type_bound::<InvalidBlock>())?;
+    m.add("RLPDecodingError", py.get_type_bound::<RLPDecodingError>())?;
+    m.add("RLPEncodingError", py.get_type_bound::<RLPEncodingError>())?;
+
+    add_submodule(m, "rlp", |m| {
+        m.add_function(wrap_pyfunction!(encode, m)?)?;
+        m.add_function(wrap_pyfunction!(decode, m)?)
+    })?;
+    add_submodule(m, "trie", |m| {
+        m.add_class::<Trie>()?;
+        m.add_function(wrap_pyfunction!(trie_set, m)?)?;
+        m.add_function(wrap_pyfunction!(trie_get, m)?)?;
+        m.add_function(wrap_pyfunction!(root, m)?)
+    })?;
+    add_submodule(m, "fork", |m| {
+        m.add_class::<BlockChain>()?;
+        m.add_function(wrap_pyfunction!(state_transition, m)?)
+    })
+}
@@ -1 +1,15 @@
+pub mod test_ancestors;
+pub mod test_block;
+pub mod test_call;
+pub mod test_historical_state;
+pub mod test_log_filter;
+pub mod test_partial_trie;
+pub mod test_persistent_trie;
+pub mod test_proof;
+pub mod test_selfdestruct;
+pub mod test_state;
+pub mod test_stateless;
+pub mod test_state_transition;
 pub mod test_trie;
+pub mod test_trie_debug;
+pub mod test_vm;
@@ -0,0 +1,96 @@
+use execution_specs_rs::ethereum::{
+    base_types::{Uint, U256},
+    frontier::{
+        ancestors::{Ancestors, ANCESTOR_DEPTH, BLOCK_HASH_DEPTH},
+        fork::{get_last_256_block_hashes, BlockChain},
+        fork_types::{Block, Header},
+        state::State,
+    },
+    rlp,
+};
+
+/// A chain of `length` blocks, each with an ommer of its own.
+fn blocks(length: u64) -> Vec<Block> {
+    let mut blocks: Vec<Block> = vec![];
+    for number in 0..length {
+        let header = |coinbase| Header {
+            parent_hash: blocks.last().map_or([0; 32], Block::hash),
+            ommers_hash: [0; 32],
+            coinbase,
+            state_root: [0; 32],
+            transactions_root: [0; 32],
+            receipt_root: [0; 32],
+            bloom: [0; 256],
+            difficulty: Uint::from(0x20000u32),
+            number: Uint::from(number),
+            gas_limit: Uint::from(5000u32),
+            gas_used: Uint::from(0u8),
+            timestamp: U256::from(number * 15),
+            extra_data: vec![].into(),
+            mix_digest: [0; 32],
+            nonce: [0; 8],
+        };
+        let ommers = vec![header([0xcc; 20])];
+        let mut header = header([0xbb; 20]);
+        header.ommers_hash = rlp::rlp_hash(&ommers[..]);
+        blocks.push(Block {
+            header,
+            transactions: vec![],
+            ommers,
+        });
+    }
+    blocks
+}
+
+#[test]
+fn test_ancestors() {
+    let blocks = blocks(10);
+    let ancestors = Ancestors::new(&blocks);
+    assert_eq!(ancestors.head_hash(), Some(&blocks[9].hash()));
+    for depth in 0..ANCESTOR_DEPTH {
+        let block = &blocks[9 - depth];
+        assert_eq!(ancestors.header(depth).unwrap().number, block.header.number);
+        assert_eq!(ancestors.hash(depth), Some(&block.hash()));
+        assert_eq!(ancestors.ommers(depth).unwrap(), [block.ommers[0].hash()]);
+    }
+    assert!(ancestors.header(ANCESTOR_DEPTH).is_none());
+
+    // The hashes of every block, and of the parent of genesis.
+    let hashes: Vec<_> = [[0; 32]].into_iter().chain(blocks.iter().map(Block::hash)).collect();
+    assert_eq!(ancestors.block_hashes(), hashes);
+
+    let empty = Ancestors::new(&[]);
+    assert_eq!(empty.head_hash(), None);
+    assert!(empty.block_hashes().is_empty());
+}
+
+#[test]
+fn test_ancestors_push() {
+    let blocks = blocks(300);
+    let mut ancestors = Ancestors::new(&blocks[..1]);
+    for block in &blocks[1..] {
+        ancestors.push(block, block.hash());
+    }
+    let collected = Ancestors::new(&blocks);
+    assert_eq!(ancestors.block_hashes(), collected.block_hashes());
+    assert_eq!(ancestors.block_hashes().len(), BLOCK_HASH_DEPTH);
+    for depth in 0..=ANCESTOR_DEPTH {
+        assert_eq!(ancestors.hash(depth), collected.hash(depth));
+        assert_eq!(ancestors.ommers(depth), collected.ommers(depth));
+    }
+}
+
+#[test]
+fn test_last_256_block_hashes() {
+    // In order of increasing block number, as `BLOCKHASH` reads them.
+    let chain = BlockChain {
+        blocks: blocks(300)[45..].to_vec(),
+        state: State::default(),
+        chain_id: 1,
+    };
+    let hashes = get_last_256_block_hashes(&chain).unwrap();
+    assert_eq!(hashes.len(), 256);
+    assert_eq!(hashes[255], chain.blocks[254].hash());
+    assert_eq!(hashes[254], chain.blocks[253].hash());
+    assert_eq!(hashes[0], chain.blocks[0].header.parent_hash);
+}
@@ -0,0 +1,647 @@
+use std::slice;
+
+use execution_specs_rs::{
+    ethereum::{
+        base_types::{u256_to_be_bytes32, Uint, U256},
+        exceptions::EthereumException,
+        frontier::{
+            fork::{
+                apply_body, apply_body_partial, apply_body_traced, calculate_block_difficulty, compute_receipts_root,
+                compute_transactions_root, get_last_256_block_hashes, make_receipt, prevalidate_transactions,
+                process_transaction, recover_sender, signing_hash, state_transition, validate_ommers, BlockChain,
+                ExecutionLimit, BLOCK_REWARD,
+            },
+            fork_types::{keccak256, Account, Address, Block, Header, Transaction},
+            log_filter::LogFilter,
+            state::{get_account, set_account, state_root, State},
+            trie::EMPTY_TRIE_ROOT,
+            vm::{BlockEnvironment, Evm, TxEnvironment},
+        },
+        genesis::{add_genesis_block, get_genesis_configuration, GenesisConfiguration},
+        rlp,
+        trace::Tracer,
+    },
+    ethereum_spec_tools::{evm_tools::gas_profile::GasProfiler, forks::HardFork, rpc::parse_header},
+};
+use serde_json::json;
+use hex_literal::hex;
+use k256::ecdsa::SigningKey;
+
+const RECIPIENT: [u8; 20] = [0xcc; 20];
+const COINBASE: [u8; 20] = [0xbb; 20];
+
+fn signing_key() -> SigningKey {
+    SigningKey::from_slice(&[0x45; 32]).unwrap()
+}
+
+fn address(key: &SigningKey) -> Address {
+    let point = key.verifying_key().to_encoded_point(false);
+    keccak256(&point.as_bytes()[1..])[12..].try_into().unwrap()
+}
+
+fn sign(mut tx: Transaction, key: &SigningKey) -> Transaction {
+    let (signature, recovery_id) = key.sign_prehash_recoverable(&signing_hash(&tx)).unwrap();
+    let (r, s) = signature.split_bytes();
+    tx.v = U256::from(27 + recovery_id.to_byte());
+    tx.r = U256::from_bytes_be(&r);
+    tx.s = U256::from_bytes_be(&s);
+    tx
+}
+
+fn transfer(nonce: u8, value: u32) -> Transaction {
+    sign(
+        Transaction {
+            nonce: U256::from(nonce),
+            gas_price: U256::from(2u8),
+            gas: U256::from(21000u32),
+            to: Some(RECIPIENT),
+            value: U256::from(value),
+            data: vec![].into(),
+            v: U256::from(0u8),
+            r: U256::from(0u8),
+            s: U256::from(0u8),
+        },
+        &signing_key(),
+    )
+}
+
+fn block_env() -> BlockEnvironment {
+    BlockEnvironment {
+        coinbase: COINBASE,
+        number: Uint::from(1u8),
+        gas_limit: Uint::from(1_000_000u32),
+        ..Default::default()
+    }
+}
+
+fn state() -> State {
+    let mut state = State::default();
+    set_account(
+        &mut state,
+        address(&signing_key()),
+        Some(Account {
+            balance: U256::from(1_000_000u32),
+            ..Default::default()
+        }),
+    );
+    state
+}
+
+/// The first transaction on mainnet, the only one of block 46147.
+fn mainnet_transaction() -> Transaction {
+    Transaction {
+        nonce: U256::from(0u8),
+        gas_price: U256::from(50_000_000_000_000u64),
+        gas: U256::from(21000u32),
+        to: Some(hex!("5df9b87991262f6ba471f09758cde1c0fc1de734")),
+        value: U256::from(31337u32),
+        data: vec![].into(),
+        v: U256::from(28u8),
+        r: U256::from_bytes_be(&hex!(
+            "88ff6cf0fefd94db46111149ae4bfc179e9b94721fffd821d38d16464b3f71d0"
+        )),
+        s: U256::from_bytes_be(&hex!(
+            "45e0aff800961cfce805daef7016b9b675c137a6a41a548f7b60a3484c06a33a"
+        )),
+    }
+}
+
+#[test]
+fn test_recover_mainnet_sender() {
+    assert_eq!(
+        recover_sender(&mainnet_transaction()).unwrap(),
+        hex!("a1e4380a3b1f749673e270229993ee55f35663b4")
+    );
+}
+
+#[test]
+fn test_compute_roots() {
+    assert_eq!(compute_transactions_root(&[]), EMPTY_TRIE_ROOT());
+    assert_eq!(compute_receipts_root(&[]), EMPTY_TRIE_ROOT());
+    // The `transactionsRoot` of block 46147.
+    assert_eq!(
+        compute_transactions_root(&[mainnet_transaction()]),
+        hex!("4513310fcb9f6f616972a3b948dc5d547f280849a87ebb5af0191f98b87be598")
+    );
+
+    // The roots `apply_body` checks blocks against.
+    let mut state = state();
+    let transactions = [transfer(0, 5), transfer(1, 7)];
+    let (_, transactions_root, receipt_root, _, _, logs, block_receipts) =
+        apply_body(&mut state, &block_env(), &transactions, &[]).unwrap();
+    assert_eq!(transactions_root, compute_transactions_root(&transactions));
+
+    let mut state = self::state();
+    let receipts: Vec<_> = transactions
+        .iter()
+        .zip(logs)
+        .scan(Uint::from(0u8), |cumulative_gas_used, (tx, logs)| {
+            let block_env = block_env();
+            let tx_env = TxEnvironment {
+                origin: address(&signing_key()),
+                gas_price: tx.gas_price.clone(),
+            };
+            let (gas_used, _) = process_transaction(&block_env, &tx_env, &mut state, tx).unwrap();
+            *cumulative_gas_used += gas_used;
+            Some(make_receipt(state_root(&state), cumulative_gas_used.clone(), &logs))
+        })
+        .collect();
+    assert_eq!(receipt_root, compute_receipts_root(&receipts));
+    assert_eq!(block_receipts, receipts);
+    assert_ne!(receipt_root, compute_receipts_root(&receipts[..1]));
+}
+
+#[test]
+fn test_recover_sender_rejects_bad_signatures() {
+    let mut tx = transfer(0, 1);
+    tx.v = U256::from(29u8);
+    assert!(matches!(recover_sender(&tx), Err(EthereumException::InvalidBlock)));
+
+    let mut tx = transfer(0, 1);
+    tx.r = U256::from(0u8);
+    assert!(matches!(recover_sender(&tx), Err(EthereumException::InvalidBlock)));
+
+    // A changed value recovers some other sender.
+    let mut tx = transfer(0, 1);
+    tx.value = U256::from(2u8);
+    assert_ne!(recover_sender(&tx).ok(), Some(address(&signing_key())));
+}
+
+#[test]
+fn test_prevalidate_transactions() {
+    let transactions: Vec<_> = (0..8).map(|nonce| transfer(nonce, 1)).collect();
+    let senders = prevalidate_transactions(&transactions).unwrap();
+    assert_eq!(senders, vec![address(&signing_key()); 8]);
+
+    let mut transactions = transactions;
+    transactions[5].gas = U256::from(20999u32);
+    let error = prevalidate_transactions(&transactions).unwrap_err();
+    assert!(matches!(error.root_cause(), EthereumException::InvalidBlock));
+    assert_eq!(error.transaction_index(), Some(5));
+
+    // With several invalid, the first is reported, also under `parallel`.
+    transactions[2].gas = U256::from(20999u32);
+    let error = prevalidate_transactions(&transactions).unwrap_err();
+    assert_eq!(error.transaction_index(), Some(2));
+}
+
+#[test]
+fn test_apply_body() {
+    let mut state = state();
+    let transactions = [transfer(0, 5), transfer(1, 7)];
+    let (gas_used, _, _, bloom, fees, logs, _) =
+        apply_body(&mut state, &block_env(), &transactions, &[]).unwrap();
+
+    assert_eq!(gas_used, Uint::from(42000u32));
+    assert_eq!(bloom, [0; 256]);
+    assert_eq!(logs, vec![vec![], vec![]]);
+    assert_eq!(get_account(&state, &RECIPIENT).balance, U256::from(12u8));
+    let sender = get_account(&state, &address(&signing_key()));
+    assert_eq!(sender.nonce, Uint::from(2u8));
+    assert_eq!(sender.balance, U256::from(1_000_000u32 - 12 - 2 * 42000));
+    assert_eq!(
+        get_account(&state, &COINBASE).balance,
+        BLOCK_REWARD() + 2u32 * 42000
+    );
+    assert_eq!(fees.transaction_fees, U256::from(2u32 * 42000));
+    assert_eq!(fees.priority_fees, fees.transaction_fees);
+    assert_eq!(fees.burned(), U256::from(0u8));
+    assert_eq!(fees.issuance(), BLOCK_REWARD());
+    assert_eq!(fees.coinbase_income(), get_account(&state, &COINBASE).balance);
+}
+
+#[test]
+fn test_apply_body_logs() {
+    let mut state = state();
+    // LOG1(0, 0, CALLVALUE)
+    set_account(&mut state, RECIPIENT, Some(Account { code: hex!("34 6000 80 a1").into(), ..Default::default() }));
+    let transactions: Vec<_> = [5, 7]
+        .into_iter()
+        .enumerate()
+        .map(|(nonce, value)| {
+            let mut tx = transfer(nonce as u8, value);
+            tx.gas = U256::from(30000u32);
+            sign(tx, &signing_key())
+        })
+        .collect();
+    let (_, _, _, bloom, _, logs, _) = apply_body(&mut state, &block_env(), &transactions, &[]).unwrap();
+
+    assert_eq!(logs.len(), 2);
+    assert_eq!(logs[0].len(), 1);
+    assert_eq!(logs[0][0].address, RECIPIENT);
+    assert_eq!(logs[1][0].topics, vec![u256_to_be_bytes32(&U256::from(7u8))]);
+
+    let filter = LogFilter::new().address(RECIPIENT).topic(0, [u256_to_be_bytes32(&U256::from(7u8))]);
+    assert!(filter.may_match(&bloom));
+    let selected: Vec<_> = filter.filter(logs.iter().flatten()).collect();
+    assert_eq!(selected, vec![&logs[1][0]]);
+    assert!(!LogFilter::new().address(COINBASE).may_match(&bloom));
+}
+
+#[test]
+fn test_apply_body_traced() {
+    let transactions = [transfer(0, 5), transfer(1, 7)];
+    let profiler = GasProfiler::new();
+    let profile = profiler.profile();
+    let mut tracer: Option<Box<dyn Tracer<Evm>>> = Some(Box::new(profiler));
+
+    let mut traced = state();
+    let result = apply_body_traced(&mut traced, &block_env(), &transactions, &[], &mut tracer).unwrap();
+    let mut untraced = state();
+    assert_eq!(result, apply_body(&mut untraced, &block_env(), &transactions, &[]).unwrap());
+    assert_eq!(state_root(&traced), state_root(&untraced));
+
+    // The profile covers every transaction, and the tracer is handed back.
+    assert!(tracer.is_some());
+    let recipient = &profile.borrow().callees[&RECIPIENT];
+    assert_eq!((recipient.calls, recipient.instructions), (2, 0));
+}
+
+#[test]
+fn test_apply_body_ommer_rewards() {
+    let mut state = state();
+    let ommer = Header {
+        parent_hash: [0; 32],
+        ommers_hash: [0; 32],
+        coinbase: [0xdd; 20],
+        state_root: [0; 32],
+        transactions_root: [0; 32],
+        receipt_root: [0; 32],
+        bloom: [0; 256],
+        difficulty: Uint::from(0u8),
+        number: Uint::from(0u8),
+        gas_limit: Uint::from(0u8),
+        gas_used: Uint::from(0u8),
+        timestamp: U256::from(0u8),
+        extra_data: vec![].into(),
+        mix_digest: [0; 32],
+        nonce: [0; 8],
+    };
+    let mut block_env = block_env();
+    block_env.number = Uint::from(2u8);
+    let (_, _, _, _, fees, _, _) = apply_body(&mut state, &block_env, &[], &[ommer]).unwrap();
+
+    assert_eq!(fees.miner_reward, BLOCK_REWARD() + BLOCK_REWARD() / 32u8);
+    assert_eq!(fees.ommer_rewards, BLOCK_REWARD() * 6u8 / 8u8);
+    assert_eq!(fees.transaction_fees, U256::from(0u8));
+    assert_eq!(
+        get_account(&state, &COINBASE).balance,
+        BLOCK_REWARD() + BLOCK_REWARD() / 32u8
+    );
+    assert_eq!(
+        get_account(&state, &[0xdd; 20]).balance,
+        BLOCK_REWARD() * 6u8 / 8u8
+    );
+}
+
+#[test]
+fn test_apply_body_invalid() {
+    // Each transaction fits the block alone, but not both together.
+    let mut block_env = block_env();
+    block_env.gas_limit = Uint::from(30000u32);
+    let result = apply_body(&mut state(), &block_env, &[transfer(0, 1), transfer(1, 1)], &[]);
+    let error = result.unwrap_err();
+    assert!(matches!(error.root_cause(), EthereumException::InvalidBlock));
+    assert_eq!(error.transaction_index(), Some(1));
+
+    let mut tx = transfer(0, 1);
+    tx.s = U256::from(0u8);
+    let result = apply_body(&mut state(), &block_env, &[tx], &[]);
+    assert!(matches!(result, Err(e) if matches!(e.root_cause(), EthereumException::InvalidBlock)));
+}
+
+#[test]
+fn test_apply_body_partial() {
+    let transactions = [transfer(0, 5), transfer(1, 7), transfer(2, 9)];
+    let partial = |limit: ExecutionLimit| {
+        let mut state = state();
+        let body = apply_body_partial(&mut state, &block_env(), &transactions, &limit, &mut None).unwrap();
+        assert_eq!(body.state_root, state_root(&state));
+        (body, state)
+    };
+
+    // Without a limit, it is the block without its rewards.
+    let (body, _) = partial(ExecutionLimit::default());
+    let mut state = state();
+    let (gas_used, _, receipt_root, _, fees, _, receipts) =
+        apply_body(&mut state, &block_env(), &transactions, &[]).unwrap();
+    assert_eq!(body.executed, 3);
+    assert_eq!(body.gas_used, gas_used);
+    assert_eq!(body.receipts, receipts);
+    assert_eq!(compute_receipts_root(&body.receipts), receipt_root);
+    assert_eq!(body.fee_summary.transaction_fees, fees.transaction_fees);
+    assert_eq!(body.fee_summary.issuance(), U256::from(0u8));
+
+    let (body, state) = partial(ExecutionLimit { transactions: Some(2), gas: None });
+    assert_eq!(body.executed, 2);
+    assert_eq!(body.gas_used, Uint::from(42000u32));
+    assert_eq!(body.state_root, receipts[1].post_state);
+    assert_eq!(body.fee_summary.transaction_fees, U256::from(2u32 * 42000));
+    assert_eq!(get_account(&state, &RECIPIENT).balance, U256::from(12u8));
+    assert_eq!(get_account(&state, &COINBASE).balance, U256::from(2u32 * 42000));
+
+    // The transaction that reaches the gas threshold is the last executed.
+    let (body, _) = partial(ExecutionLimit { transactions: None, gas: Some(Uint::from(21001u32)) });
+    assert_eq!(body.executed, 2);
+    let (body, _) = partial(ExecutionLimit { transactions: None, gas: Some(Uint::from(21000u32)) });
+    assert_eq!(body.executed, 1);
+    let (body, _) = partial(ExecutionLimit { transactions: Some(0), gas: None });
+    assert_eq!((body.executed, body.gas_used), (0, Uint::from(0u8)));
+    assert_eq!(body.state_root, state_root(&self::state()));
+
+    // The transactions after the limit are not checked.
+    let mut invalid = transfer(3, 1);
+    invalid.s = U256::from(0u8);
+    let mut state = self::state();
+    let limit = ExecutionLimit { transactions: Some(3), gas: None };
+    let body = apply_body_partial(&mut state, &block_env(), &[&transactions[..], &[invalid]].concat(), &limit, &mut None);
+    assert_eq!(body.unwrap().executed, 3);
+}
+
+fn genesis_chain() -> BlockChain {
+    let mut chain = BlockChain {
+        blocks: vec![],
+        state: State::default(),
+        chain_id: 0,
+    };
+    let genesis = GenesisConfiguration {
+        chain_id: 1,
+        difficulty: Uint::from(0x20000u32),
+        gas_limit: Uint::from(3_141_592u32),
+        initial_balances: [(address(&signing_key()), U256::from(1_000_000u32))].into(),
+        ..Default::default()
+    };
+    add_genesis_block(HardFork::Frontier, &mut chain, genesis).unwrap();
+    chain
+}
+
+/// Build the next block of `genesis_chain()` holding `transactions`, by
+/// executing them on a chain of its own.
+fn next_block(transactions: Vec<Transaction>) -> Block {
+    let mut chain = genesis_chain();
+    let parent = chain.blocks[0].header.clone();
+    let timestamp = U256::from(15u8);
+    let difficulty =
+        calculate_block_difficulty(&Uint::from(1u8), &timestamp, &parent.timestamp, &parent.difficulty)
+            .unwrap();
+    let block_env = BlockEnvironment {
+        block_hashes: get_last_256_block_hashes(&chain).unwrap(),
+        coinbase: COINBASE,
+        number: Uint::from(1u8),
+        gas_limit: parent.gas_limit.clone(),
+        time: timestamp.clone(),
+        difficulty: difficulty.clone(),
+    };
+    let (gas_used, transactions_root, receipt_root, bloom, _, _, _) =
+        apply_body(&mut chain.state, &block_env, &transactions, &[]).unwrap();
+
+    let header = Header {
+        parent_hash: rlp::rlp_hash(&parent),
+        ommers_hash: rlp::rlp_hash(&()),
+        coinbase: COINBASE,
+        state_root: state_root(&chain.state),
+        transactions_root,
+        receipt_root,
+        bloom,
+        difficulty,
+        number: Uint::from(1u8),
+        gas_limit: parent.gas_limit,
+        gas_used,
+        timestamp,
+        extra_data: vec![].into(),
+        mix_digest: [0; 32],
+        nonce: [0; 8],
+    };
+    Block {
+        header,
+        transactions,
+        ommers: vec![],
+    }
+}
+
+#[test]
+fn test_state_transition() {
+    let mut chain = genesis_chain();
+    let block = next_block(vec![transfer(0, 5), transfer(1, 7)]);
+    state_transition(&mut chain, block.clone()).unwrap();
+
+    assert_eq!(chain.blocks.len(), 2);
+    assert_eq!(state_root(&chain.state), block.header.state_root);
+    assert_eq!(get_account(&chain.state, &RECIPIENT).balance, U256::from(12u8));
+}
+
+#[test]
+fn test_state_transition_invalid() {
+    let block = next_block(vec![transfer(0, 5)]);
+
+    let mut invalid = block.clone();
+    invalid.header.state_root = [0; 32];
+    let result = state_transition(&mut genesis_chain(), invalid);
+    assert!(matches!(result, Err(EthereumException::InvalidBlock)));
+
+    let mut invalid = block.clone();
+    invalid.header.difficulty += 1u8;
+    let result = state_transition(&mut genesis_chain(), invalid);
+    assert!(matches!(result, Err(EthereumException::InvalidBlock)));
+
+    let mut invalid = block;
+    invalid.header.gas_limit = Uint::from(4_000_000u32);
+    let result = state_transition(&mut genesis_chain(), invalid);
+    assert!(matches!(result, Err(EthereumException::InvalidBlock)));
+}
+
+#[test]
+fn test_state_transition_invalid_leaves_chain() {
+    let mut chain = genesis_chain();
+    let genesis_root = state_root(&chain.state);
+    let block = next_block(vec![transfer(0, 5)]);
+
+    // Each is only found wrong after the transactions ran.
+    let mut bad_state_root = block.clone();
+    bad_state_root.header.state_root = [0; 32];
+    let mut bad_gas_used = block.clone();
+    bad_gas_used.header.gas_used += 1u8;
+    let mut bad_receipt_root = block.clone();
+    bad_receipt_root.header.receipt_root = [0; 32];
+    for invalid in [bad_state_root, bad_gas_used, bad_receipt_root] {
+        let result = state_transition(&mut chain, invalid);
+        assert!(matches!(result, Err(EthereumException::InvalidBlock)));
+        assert_eq!(chain.blocks.len(), 1);
+        assert_eq!(state_root(&chain.state), genesis_root);
+    }
+
+    state_transition(&mut chain, block.clone()).unwrap();
+    assert_eq!(state_root(&chain.state), block.header.state_root);
+}
+
+#[test]
+fn test_mainnet_block_hashes() {
+    let mut chain = BlockChain {
+        blocks: vec![],
+        state: State::default(),
+        chain_id: 0,
+    };
+    add_genesis_block(HardFork::Frontier, &mut chain, get_genesis_configuration("mainnet.json").unwrap()).unwrap();
+    let genesis = &chain.blocks[0];
+    assert_eq!(genesis.hash(), hex!("d4e56740f876aef8c010b86a40d5f56745a118d0906a34e69aec8c0db1cb8fa3"));
+
+    let (block_1, mut header) = parse_header(&json!({
+        "difficulty": "0x3ff800000",
+        "extraData": "0x476574682f76312e302e302f6c696e75782f676f312e342e32",
+        "gasLimit": "0x1388",
+        "gasUsed": "0x0",
+        "hash": "0x88e96d4537bea4d9c05d12549907b32561d3bf31f45aae734cdc119f13406cb6",
+        "logsBloom": format!("0x{}", "00".repeat(256)),
+        "miner": "0x05a56e2d52c817161883f50c441c3228cfe54d9f",
+        "mixHash": "0x969b900de27b6ac6a67742365dd65f55a0526c41fd18e1b16f1a1215c2e66f59",
+        "nonce": "0x539bd4979fef1ec4",
+        "number": "0x1",
+        "parentHash": "0xd4e56740f876aef8c010b86a40d5f56745a118d0906a34e69aec8c0db1cb8fa3",
+        "receiptsRoot": "0x56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421",
+        "sha3Uncles": "0x1dcc4de8dec75d7aab85b567b6ccd41ad312451b948a7413f0a142fd40d49347",
+        "stateRoot": "0xd67e4d450343046425ae4271474353857ab860dbc0a1dde64b41b5cd3a532bf3",
+        "timestamp": "0x55ba4224",
+        "transactionsRoot": "0x56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421",
+    }))
+    .unwrap();
+    assert_eq!(header.parent_hash, genesis.hash());
+    assert_eq!(header.hash(), block_1);
+    let block = Block {
+        header: header.clone(),
+        transactions: vec![],
+        ommers: vec![],
+    };
+    assert_eq!(block.hash(), block_1);
+
+    // Every field is hashed, the proof of work included.
+    header.nonce[7] ^= 1;
+    assert_ne!(header.hash(), block_1);
+}
+
+#[test]
+fn test_block_difficulty() {
+    let parent = Uint::from(0x400000000u64);
+    let delta = &parent / 2048u32;
+    let difficulty = |number: u32, timestamp: u8| {
+        calculate_block_difficulty(&Uint::from(number), &U256::from(timestamp), &U256::from(0u8), &parent)
+            .unwrap()
+    };
+    assert_eq!(difficulty(1, 12), &parent + &delta);
+    assert_eq!(difficulty(1, 13), &parent - &delta);
+    // The difficulty bomb, from block 200000.
+    assert_eq!(difficulty(200_000, 13), &parent - &delta + 1u8);
+    assert_eq!(difficulty(500_000, 13), &parent - &delta + 8u8);
+}
+
+/// A header that is valid after `parent`, without a body.
+fn child_header(parent: &Header, timestamp: u8, coinbase: Address, ommers: &[Header]) -> Header {
+    let number = &parent.number + 1u8;
+    let timestamp = &parent.timestamp + timestamp;
+    Header {
+        parent_hash: rlp::rlp_hash(parent),
+        ommers_hash: rlp::rlp_hash(ommers),
+        coinbase,
+        state_root: parent.state_root,
+        transactions_root: parent.transactions_root,
+        receipt_root: parent.receipt_root,
+        bloom: [0; 256],
+        difficulty: calculate_block_difficulty(&number, &timestamp, &parent.timestamp, &parent.difficulty)
+            .unwrap(),
+        number,
+        gas_limit: parent.gas_limit.clone(),
+        gas_used: Uint::from(0u8),
+        timestamp,
+        extra_data: vec![].into(),
+        mix_digest: [0; 32],
+        nonce: [0; 8],
+    }
+}
+
+/// `genesis_chain()` with `length` empty blocks after genesis, and the
+/// header of the next block including `ommers`.
+fn chain_with_ommers(length: usize, ommers: &[Header]) -> (BlockChain, Header) {
+    let mut chain = genesis_chain();
+    for _ in 0..length {
+        let header = child_header(&chain.blocks.last().unwrap().header, 15, COINBASE, &[]);
+        chain.blocks.push(Block {
+            header,
+            transactions: vec![],
+            ommers: vec![],
+        });
+    }
+    let header = child_header(&chain.blocks.last().unwrap().header, 15, COINBASE, ommers);
+    (chain, header)
+}
+
+#[test]
+fn test_validate_ommers() {
+    let genesis = genesis_chain().blocks[0].header.clone();
+    // Mined on genesis at the same time as block 1, by someone else.
+    let uncle = child_header(&genesis, 14, [0xdd; 20], &[]);
+    let (chain, header) = chain_with_ommers(1, slice::from_ref(&uncle));
+    validate_ommers(&vec![uncle.clone()], header.clone(), &chain).unwrap();
+
+    let invalid = |chain: &BlockChain, ommers: Vec<Header>| {
+        let header = child_header(&chain.blocks.last().unwrap().header, 15, COINBASE, &ommers);
+        matches!(validate_ommers(&ommers, header, chain), Err(EthereumException::InvalidBlock))
+    };
+
+    // The ommers must match the header.
+    let result = validate_ommers(&vec![], header, &chain);
+    assert!(matches!(result, Err(EthereumException::InvalidBlock)));
+    // No duplicates, and no more than two.
+    assert!(invalid(&chain, vec![uncle.clone(), uncle.clone()]));
+    let other_uncles: Vec<_> = (10..13).map(|timestamp| child_header(&genesis, timestamp, [0xdd; 20], &[])).collect();
+    assert!(!invalid(&chain, other_uncles[..2].to_vec()));
+    assert!(invalid(&chain, other_uncles));
+    // Not a canonical block, nor a sibling of the parent.
+    assert!(invalid(&chain, vec![chain.blocks[1].header.clone()]));
+    let (short_chain, _) = chain_with_ommers(0, &[]);
+    assert!(invalid(&short_chain, vec![uncle.clone()]));
+    // Not an ommer of a block of the chain already.
+    let (mut included, _) = chain_with_ommers(1, &[]);
+    included.blocks.push(Block {
+        header: child_header(&included.blocks[1].header, 15, COINBASE, slice::from_ref(&uncle)),
+        transactions: vec![],
+        ommers: vec![uncle.clone()],
+    });
+    assert!(invalid(&included, vec![uncle.clone()]));
+    // The ommer itself must be a valid header.
+    let mut bad_difficulty = uncle.clone();
+    bad_difficulty.difficulty += 1u8;
+    assert!(invalid(&chain, vec![bad_difficulty]));
+}
+
+#[test]
+fn test_validate_ommers_depth() {
+    let genesis = genesis_chain().blocks[0].header.clone();
+    let uncle = child_header(&genesis, 14, [0xdd; 20], &[]);
+    // Block 7 can include an ommer of block 1, block 8 can't.
+    let (chain, header) = chain_with_ommers(6, slice::from_ref(&uncle));
+    validate_ommers(&vec![uncle.clone()], header, &chain).unwrap();
+    let (chain, header) = chain_with_ommers(7, slice::from_ref(&uncle));
+    let result = validate_ommers(&vec![uncle], header, &chain);
+    assert!(matches!(result, Err(EthereumException::InvalidBlock)));
+}
+
+#[test]
+fn test_state_transition_keeps_255_blocks() {
+    // Genesis and 255 blocks, and the block that makes the chain too long.
+    let (mut chain, mut header) = chain_with_ommers(255, &[]);
+    let mut state = chain.state.clone();
+    let mut coinbase = get_account(&state, &COINBASE);
+    coinbase.balance += BLOCK_REWARD();
+    set_account(&mut state, COINBASE, Some(coinbase));
+    header.state_root = state_root(&state);
+    let block = Block {
+        header,
+        transactions: vec![],
+        ommers: vec![],
+    };
+    state_transition(&mut chain, block).unwrap();
+
+    // Only genesis and block 1 are dropped.
+    assert_eq!(chain.blocks.len(), 255);
+    assert_eq!(chain.blocks[0].header.number, Uint::from(2u8));
+    assert_eq!(chain.blocks[254].header.number, Uint::from(256u16));
+}
@@ -0,0 +1,271 @@
+use std::{cell::RefCell, rc::Rc};
+
+use execution_specs_rs::ethereum::{
+    base_types::{Uint, U256},
+    exceptions::EthereumException,
+    frontier::{
+        fork_types::Account,
+        state::{get_account, get_storage, set_account, State},
+        vm::{call::CallResult, exceptions::EvmError, Evm},
+    },
+    trace::{TraceEvent, Tracer},
+};
+use hex_literal::hex;
+
+const ORIGIN: [u8; 20] = [0xaa; 20];
+const CONTRACT: [u8; 20] = [0xcc; 20];
+
+fn slot(n: u8) -> [u8; 32] {
+    let mut key = [0; 32];
+    key[31] = n;
+    key
+}
+
+fn state_with_contract(code: &[u8]) -> State {
+    let mut state = State::default();
+    set_account(
+        &mut state,
+        ORIGIN,
+        Some(Account {
+            balance: U256::from(1000u32),
+            ..Default::default()
+        }),
+    );
+    set_account(
+        &mut state,
+        CONTRACT,
+        Some(Account {
+            code: code.into(),
+            ..Default::default()
+        }),
+    );
+    state
+}
+
+#[test]
+fn test_call_storage_logs_and_diff() {
+    // SSTORE(1, CALLVALUE) LOG1(0, 0, topic 7) RETURN(0, 0)
+    let mut state = state_with_contract(&hex!("34 6001 55 6007 6000 6000 a1 6000 6000 f3"));
+    let result = Evm::call()
+        .origin(ORIGIN)
+        .to(CONTRACT)
+        .value(U256::from(5u8))
+        .gas(Uint::from(100_000u32))
+        .run(&mut state)
+        .unwrap();
+
+    assert_eq!(result.error, None);
+    assert_eq!(result.gas_used + &result.gas_left, Uint::from(100_000u32));
+    assert_eq!(result.logs.len(), 1);
+    assert_eq!(result.logs[0].address, CONTRACT);
+    assert_eq!(result.logs[0].topics, vec![slot(7)]);
+    assert_eq!(get_storage(&state, &CONTRACT, &slot(1)), U256::from(5u8));
+
+    assert_eq!(result.state_diff.len(), 2);
+    let origin = &result.state_diff[&ORIGIN];
+    assert_eq!(origin.pre.as_ref().unwrap().balance, U256::from(1000u32));
+    assert_eq!(origin.post.as_ref().unwrap().balance, U256::from(995u32));
+    assert!(origin.storage.is_empty());
+    let contract = &result.state_diff[&CONTRACT];
+    assert_eq!(
+        contract.storage[&slot(1)],
+        (U256::from(0u8), U256::from(5u8))
+    );
+}
+
+#[test]
+fn test_call_failure_reverts_state() {
+    // SSTORE(1, 1) then an invalid jump.
+    let mut state = state_with_contract(&hex!("6001 6001 55 6000 56"));
+    let result = Evm::call()
+        .origin(ORIGIN)
+        .to(CONTRACT)
+        .gas(Uint::from(100_000u32))
+        .run(&mut state)
+        .unwrap();
+
+    assert_eq!(result.error, Some(EvmError::InvalidJumpDest));
+    assert_eq!(result.gas_left, Uint::from(0u8));
+    assert!(result.state_diff.is_empty());
+    assert_eq!(get_storage(&state, &CONTRACT, &slot(1)), U256::from(0u8));
+}
+
+#[test]
+fn test_call_create() {
+    let mut state = state_with_contract(&[]);
+    // Deploys `0x01`.
+    let result = Evm::call()
+        .origin(ORIGIN)
+        .create()
+        .data(hex!("6001 6000 53 6001 6000 f3").into())
+        .run(&mut state)
+        .unwrap();
+
+    assert_eq!(result.error, None);
+    let address = result.created_address.unwrap();
+    assert_eq!(&*get_account(&state, &address).code, &[1]);
+    assert_eq!(get_account(&state, &ORIGIN).nonce, Uint::from(1u8));
+    assert!(result.state_diff[&address].pre.is_none());
+}
+
+#[test]
+fn test_call_insufficient_balance() {
+    let mut state = state_with_contract(&[]);
+    let result = Evm::call()
+        .origin(ORIGIN)
+        .to(CONTRACT)
+        .value(U256::from(1001u32))
+        .run(&mut state);
+    assert!(matches!(result, Err(EthereumException::InvalidTransaction)));
+}
+
+/// Counts its frames in slot 0, calls itself and stores the result of the
+/// call in the slot of the frame's number, as the `Call1024` tests of
+/// `stCallCreateCallCodeTest` do.
+const RECURSIVE_CALL: [u8; 26] =
+    hex!("6000 54 6001 01 80 6000 55 6000 80 80 80 80 30 611000 5a 03 f1 90 55 00");
+
+fn call_at_depth(code: &[u8], depth: u32) -> (CallResult, State) {
+    let mut state = state_with_contract(code);
+    let result = Evm::call()
+        .origin(ORIGIN)
+        .to(CONTRACT)
+        .gas(Uint::from(1_000_000u32))
+        .depth(Uint::from(depth))
+        .run(&mut state)
+        .unwrap();
+    (result, state)
+}
+
+#[test]
+fn test_call_depth_limit() {
+    // The frame at depth 1024 can't call any further: the call pushes 0
+    // instead of failing the frame.
+    let (result, state) = call_at_depth(&RECURSIVE_CALL, 1022);
+    assert_eq!(result.error, None);
+    assert_eq!(get_storage(&state, &CONTRACT, &slot(0)), U256::from(3u8));
+    assert_eq!(get_storage(&state, &CONTRACT, &slot(1)), U256::from(1u8));
+    assert_eq!(get_storage(&state, &CONTRACT, &slot(2)), U256::from(1u8));
+    assert_eq!(get_storage(&state, &CONTRACT, &slot(3)), U256::from(0u8));
+    assert!(!result.state_diff[&CONTRACT].storage.contains_key(&slot(3)));
+}
+
+#[test]
+fn test_call_depth_limit_returns_gas() {
+    // CALL(50000, CONTRACT, 0, 0, 0, 0, 0) from the deepest frame.
+    let code = hex!("6000 80 80 80 80 73cccccccccccccccccccccccccccccccccccccccc 61c350 f1 00");
+    let (result, _) = call_at_depth(&code, 1024);
+    assert_eq!(result.error, None);
+    // 5 PUSHes/DUPs, the PUSH20 and PUSH2 and the 40 of the call: the 50000
+    // given to the call were not spent.
+    assert_eq!(result.gas_used, Uint::from(3u8 * 7 + 40));
+}
+
+#[test]
+fn test_create_depth_limit() {
+    // SSTORE(0, CREATE(0, 0, 0))
+    let code = hex!("6000 80 80 f0 6000 55");
+    let (result, state) = call_at_depth(&code, 1024);
+    assert_eq!(result.error, None);
+    assert_eq!(get_storage(&state, &CONTRACT, &slot(0)), U256::from(0u8));
+    // The nonce is left alone when the create doesn't happen.
+    assert_eq!(get_account(&state, &CONTRACT).nonce, Uint::from(0u8));
+
+    let (result, state) = call_at_depth(&code, 1023);
+    assert_eq!(result.error, None);
+    assert_ne!(get_storage(&state, &CONTRACT, &slot(0)), U256::from(0u8));
+    assert_eq!(get_account(&state, &CONTRACT).nonce, Uint::from(1u8));
+}
+
+#[test]
+fn test_nested_call_results() {
+    const CALLEE: [u8; 20] = [0xdd; 20];
+    // CALL(10000, CALLEE, 0, 0, 0, 0, 32), SSTORE(0, result),
+    // SSTORE(1, MLOAD(0))
+    let code = hex!("6020 6000 80 80 80 73dddddddddddddddddddddddddddddddddddddddd 612710 f1 6000 55 6000 51 6001 55 00");
+
+    // The callee returns 0x2a, which is copied to the caller's memory.
+    let mut state = state_with_contract(&code);
+    let returns = hex!("602a 6000 52 6020 6000 f3");
+    set_account(
+        &mut state,
+        CALLEE,
+        Some(Account {
+            code: returns[..].into(),
+            ..Default::default()
+        }),
+    );
+    let result = Evm::call()
+        .origin(ORIGIN)
+        .to(CONTRACT)
+        .gas(Uint::from(100_000u32))
+        .run(&mut state)
+        .unwrap();
+    assert_eq!(result.error, None);
+    assert_eq!(get_storage(&state, &CONTRACT, &slot(0)), U256::from(1u8));
+    assert_eq!(get_storage(&state, &CONTRACT, &slot(1)), U256::from(0x2au8));
+
+    // The callee halts exceptionally after storing: its storage is rolled
+    // back, all the gas it was given is spent, and the caller carries on.
+    let mut state = state_with_contract(&code);
+    let fails = hex!("6001 6000 55 fe");
+    set_account(
+        &mut state,
+        CALLEE,
+        Some(Account {
+            code: fails[..].into(),
+            ..Default::default()
+        }),
+    );
+    let result = Evm::call()
+        .origin(ORIGIN)
+        .to(CONTRACT)
+        .gas(Uint::from(100_000u32))
+        .run(&mut state)
+        .unwrap();
+    assert_eq!(result.error, None);
+    assert_eq!(get_storage(&state, &CONTRACT, &slot(0)), U256::from(0u8));
+    assert_eq!(get_storage(&state, &CALLEE, &slot(0)), U256::from(0u8));
+    assert!(result.gas_used > Uint::from(10_000u32));
+}
+
+struct FrameTracer(Rc<RefCell<Vec<TraceEvent>>>);
+
+impl Tracer<Evm> for FrameTracer {
+    fn trace(&mut self, _: &Evm, event: &TraceEvent) {
+        if let TraceEvent::MessageStart { .. } | TraceEvent::MessageEnd { .. } = event {
+            self.0.borrow_mut().push(event.clone());
+        }
+    }
+}
+
+#[test]
+fn test_trace_message_depth() {
+    let events = Rc::new(RefCell::new(vec![]));
+    let mut state = state_with_contract(&RECURSIVE_CALL);
+    Evm::call()
+        .origin(ORIGIN)
+        .to(CONTRACT)
+        .gas(Uint::from(1_000_000u32))
+        .depth(Uint::from(1023u32))
+        .tracer(Box::new(FrameTracer(events.clone())))
+        .run(&mut state)
+        .unwrap();
+
+    let depth = |depth: u32| Uint::from(depth);
+    assert_eq!(
+        *events.borrow(),
+        vec![
+            TraceEvent::MessageStart { depth: depth(1023) },
+            TraceEvent::MessageStart { depth: depth(1024) },
+            TraceEvent::MessageEnd {
+                depth: depth(1024),
+                error: None
+            },
+            TraceEvent::MessageEnd {
+                depth: depth(1023),
+                error: None
+            },
+        ]
+    );
+}
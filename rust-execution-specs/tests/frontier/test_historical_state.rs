@@ -0,0 +1,142 @@
+use execution_specs_rs::ethereum::{
+    base_types::{Bytes32, Uint, U256},
+    exceptions::EthereumException,
+    frontier::{
+        fork::{apply_body, recover_sender},
+        fork_types::{Account, Address, Transaction},
+        historical_state::{commit_state, commit_whole_state, StateAtRoot},
+        state::{get_storage, record_accesses, set_account, set_storage, state_root, take_accesses, State},
+        trie::persistent::NodeStore,
+        vm::BlockEnvironment,
+    },
+    transaction_signature::sign_transaction,
+};
+use hex_literal::hex;
+
+const KEY: Bytes32 = [0x45; 32];
+const CONTRACT: [u8; 20] = [0xcc; 20];
+const COINBASE: [u8; 20] = [0xbb; 20];
+
+fn slot(n: u8) -> Bytes32 {
+    let mut key = [0; 32];
+    key[31] = n;
+    key
+}
+
+/// A call to `CONTRACT`, which stores the value it is sent in slot 0.
+fn transaction() -> Transaction {
+    let unsigned = Transaction {
+        nonce: U256::from(0u8),
+        gas_price: U256::from(1u8),
+        gas: U256::from(50000u32),
+        to: Some(CONTRACT),
+        value: U256::from(5u8),
+        data: vec![].into(),
+        v: U256::default(),
+        r: U256::default(),
+        s: U256::default(),
+    };
+    sign_transaction(&unsigned, &KEY, None).unwrap()
+}
+
+fn sender() -> Address {
+    recover_sender(&transaction()).unwrap()
+}
+
+fn block_env() -> BlockEnvironment {
+    BlockEnvironment {
+        coinbase: COINBASE,
+        number: Uint::from(1u8),
+        gas_limit: Uint::from(1_000_000u32),
+        ..Default::default()
+    }
+}
+
+fn state() -> State {
+    let mut state = State::default();
+    let funded = Account {
+        balance: U256::from(1_000_000u32),
+        ..Default::default()
+    };
+    set_account(&mut state, sender(), Some(funded));
+    // SSTORE(0, CALLVALUE)
+    let contract = Account {
+        code: hex!("34 6000 55").into(),
+        ..Default::default()
+    };
+    set_account(&mut state, CONTRACT, Some(contract));
+    set_storage(&mut state, CONTRACT, &slot(1), U256::from(7u8));
+    for i in 1..=10u8 {
+        set_account(&mut state, [i; 20], Some(Account::default()));
+    }
+    state
+}
+
+#[test]
+fn test_commit_state() {
+    let mut state = state();
+    let mut store = NodeStore::for_state();
+    let before = commit_whole_state(&mut store, &state).unwrap();
+    assert_eq!(before, state_root(&state));
+
+    record_accesses(&mut state);
+    apply_body(&mut state, &block_env(), &[transaction()], &[]).unwrap();
+    let accesses = take_accesses(&mut state).unwrap();
+    let after = commit_state(&mut store, &before, &state, &accesses).unwrap();
+    assert_eq!(after, state_root(&state));
+
+    let old = StateAtRoot::open(&store, before).unwrap();
+    let new = StateAtRoot::open(&store, after).unwrap();
+    assert_eq!(old.get_storage(&CONTRACT, &slot(0)).unwrap(), U256::default());
+    assert_eq!(new.get_storage(&CONTRACT, &slot(0)).unwrap(), U256::from(5u8));
+    assert_eq!(
+        old.get_account_optional(&sender()).unwrap().unwrap().balance,
+        U256::from(1_000_000u32)
+    );
+    assert_eq!(
+        new.get_account_optional(&sender()).unwrap().unwrap().nonce,
+        Uint::from(1u8)
+    );
+    assert_eq!(
+        new.get_account_optional(&CONTRACT).unwrap().unwrap().code[..],
+        hex!("34 6000 55")
+    );
+    assert!(new.get_account_optional(&[0xdd; 20]).unwrap().is_none());
+
+    // The slot the transaction left alone is in a node both states share.
+    store.dereference(&before);
+    assert!(StateAtRoot::open(&store, before).is_err());
+    let new = StateAtRoot::open(&store, after).unwrap();
+    assert_eq!(new.get_storage(&CONTRACT, &slot(1)).unwrap(), U256::from(7u8));
+    assert_eq!(new.get_account_optional(&[3; 20]).unwrap(), Some(Account::default()));
+
+    store.dereference(&after);
+    assert!(store.is_empty());
+}
+
+#[test]
+fn test_state_at_root_run() {
+    let state = state();
+    let mut store = NodeStore::for_state();
+    let root = commit_whole_state(&mut store, &state).unwrap();
+
+    let mut expected = state.clone();
+    let expected_gas = apply_body(&mut expected, &block_env(), &[transaction()], &[])
+        .unwrap()
+        .0;
+    let mut runs = 0;
+    // The first runs don't have the sender, whose transaction is then
+    // invalid.
+    let (gas_used, value) = StateAtRoot::open(&store, root)
+        .unwrap()
+        .run(|state| {
+            runs += 1;
+            let output = apply_body(state, &block_env(), &[transaction()], &[])?;
+            Ok::<_, EthereumException>((output.0, get_storage(state, &CONTRACT, &slot(0))))
+        })
+        .unwrap()
+        .unwrap();
+    assert_eq!(gas_used, expected_gas);
+    assert_eq!(value, U256::from(5u8));
+    assert!(runs > 1);
+}
@@ -0,0 +1,42 @@
+use execution_specs_rs::ethereum::frontier::{bloom::logs_bloom, fork_types::Log, log_filter::LogFilter};
+
+const A: [u8; 20] = [0xaa; 20];
+const B: [u8; 20] = [0xbb; 20];
+
+fn log(address: [u8; 20], topics: &[u8]) -> Log {
+    Log {
+        address,
+        topics: topics.iter().map(|&topic| [topic; 32]).collect(),
+        data: Box::default(),
+    }
+}
+
+#[test]
+fn test_matches() {
+    let logs = [log(A, &[]), log(A, &[1, 2]), log(B, &[1]), log(B, &[3, 2, 1])];
+    let matching = |filter: LogFilter| -> Vec<usize> {
+        (0..logs.len()).filter(|&i| filter.matches(&logs[i])).collect()
+    };
+
+    assert_eq!(matching(LogFilter::new()), vec![0, 1, 2, 3]);
+    assert_eq!(matching(LogFilter::new().address(B)), vec![2, 3]);
+    assert_eq!(matching(LogFilter::new().address(A).address(B)), vec![0, 1, 2, 3]);
+    assert_eq!(matching(LogFilter::new().topic(0, [[1; 32]])), vec![1, 2]);
+    assert_eq!(matching(LogFilter::new().topic(0, [[1; 32], [3; 32]])), vec![1, 2, 3]);
+    // Positions before the one set match anything, but must be there.
+    assert_eq!(matching(LogFilter::new().topic(1, [[2; 32]])), vec![1, 3]);
+    assert_eq!(matching(LogFilter::new().topic(1, [])), vec![1, 3]);
+    assert_eq!(matching(LogFilter::new().address(A).topic(1, [[2; 32]])), vec![1]);
+    assert_eq!(matching(LogFilter::new().topic(0, [[2; 32]])), Vec::<usize>::new());
+}
+
+#[test]
+fn test_may_match() {
+    let bloom = logs_bloom(&[log(A, &[1, 2])]);
+    assert!(LogFilter::new().may_match(&bloom));
+    // The bloom doesn't know the positions of the topics.
+    assert!(LogFilter::new().address(A).topic(1, [[1; 32], [9; 32]]).may_match(&bloom));
+    assert!(!LogFilter::new().address(B).may_match(&bloom));
+    assert!(!LogFilter::new().topic(0, [[9; 32]]).may_match(&bloom));
+    assert!(LogFilter::new().address(B).may_match(&[0xff; 256]));
+}
@@ -0,0 +1,74 @@
+use std::collections::HashMap;
+
+use execution_specs_rs::ethereum::{
+    base_types::Bytes,
+    crypto::hash::keccak256,
+    frontier::trie::{
+        dummy_root,
+        partial::{MissingNode, PartialTrie},
+        root, trie_proof, trie_set, Trie,
+    },
+};
+
+/// A value too long for its leaf to be embedded in its parent.
+fn value(byte: u8) -> Bytes {
+    vec![byte; 40].into()
+}
+
+fn trie(keys: &[&str]) -> Trie<Bytes, Bytes> {
+    let mut trie = Trie::new(false, Bytes::default());
+    for (n, key) in keys.iter().enumerate() {
+        trie_set(&mut trie, key.as_bytes().into(), value(n as u8));
+    }
+    trie
+}
+
+/// The nodes of the proofs of `keys`, by their hash.
+fn proofs(trie: &Trie<Bytes, Bytes>, keys: &[&str]) -> Vec<Bytes> {
+    keys.iter()
+        .flat_map(|key| trie_proof(trie, &Bytes::from(key.as_bytes()), dummy_root))
+        .collect()
+}
+
+fn open(trie: &Trie<Bytes, Bytes>, nodes: &[Bytes]) -> PartialTrie {
+    let nodes: HashMap<_, _> = nodes.iter().map(|node| (keccak256(node), &node[..])).collect();
+    PartialTrie::new(&root(trie, dummy_root), &nodes).unwrap()
+}
+
+#[test]
+fn test_partial_trie_set() {
+    let mut full = trie(&["do", "dog", "doge", "horse", "cat"]);
+    let mut partial = open(&full, &proofs(&full, &["dog", "horse"]));
+    assert_eq!(partial.root(), root(&full, dummy_root));
+
+    for (key, value) in [("dog", value(9)), ("dot", value(10)), ("horse", Bytes::default())] {
+        partial.set(key.as_bytes(), value.clone()).unwrap();
+        trie_set(&mut full, key.as_bytes().into(), value);
+        assert_eq!(partial.root(), root(&full, dummy_root), "{key}");
+    }
+}
+
+#[test]
+fn test_partial_trie_missing_node() {
+    let mut full = trie(&["dog", "horse"]);
+    let mut partial = open(&full, &proofs(&full, &["dog"]));
+    let before = partial.root();
+
+    // The leaf of `horse` is known by its hash, and deleting `dog` merges
+    // their branch with it.
+    let horse = partial.set(b"horse", value(9)).unwrap_err();
+    let dog = partial.set(b"dog", Bytes::default()).unwrap_err();
+    assert_eq!(
+        horse,
+        MissingNode {
+            path: vec![6, 8].into()
+        }
+    );
+    assert_eq!(dog, horse);
+    assert_eq!(partial.root(), before);
+
+    let mut partial = open(&full, &proofs(&full, &["dog", "horse"]));
+    partial.set(b"dog", Bytes::default()).unwrap();
+    trie_set(&mut full, "dog".as_bytes().into(), Bytes::default());
+    assert_eq!(partial.root(), root(&full, dummy_root));
+}
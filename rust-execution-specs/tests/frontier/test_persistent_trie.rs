@@ -0,0 +1,94 @@
+use execution_specs_rs::ethereum::{
+    base_types::Bytes,
+    frontier::trie::{
+        dummy_root,
+        persistent::{NodeStore, PersistentTrie},
+        root, trie_set, Trie, EMPTY_TRIE_ROOT,
+    },
+};
+
+/// A value too long for its leaf to be embedded in its parent.
+fn value(byte: u8) -> Bytes {
+    vec![byte; 40].into()
+}
+
+fn changes(changes: &[(&str, Bytes)]) -> Vec<(Bytes, Bytes)> {
+    changes
+        .iter()
+        .map(|(key, value)| (key.as_bytes().into(), value.clone()))
+        .collect()
+}
+
+#[test]
+fn test_persistent_trie_update() {
+    let mut full = Trie::new(false, Bytes::default());
+    let mut trie = PersistentTrie::default();
+    assert_eq!(trie.root(), EMPTY_TRIE_ROOT());
+
+    let versions = [
+        changes(&[
+            ("do", value(0)),
+            ("dog", value(1)),
+            ("doge", value(2)),
+            ("horse", value(3)),
+        ]),
+        changes(&[("dog", value(4)), ("cat", value(5))]),
+        changes(&[("horse", Bytes::default()), ("doge", Bytes::default())]),
+    ];
+    let mut roots = Vec::new();
+    for version in versions {
+        for (key, value) in version.clone() {
+            trie_set(&mut full, key, value);
+        }
+        roots.push(trie.update(version).unwrap());
+        assert_eq!(trie.root(), root(&full, dummy_root));
+    }
+
+    assert_eq!(trie.get(b"dog").unwrap(), Some(value(4)));
+    assert_eq!(trie.get(b"horse").unwrap(), None);
+    assert_eq!(trie.get_at(&roots[0], b"dog").unwrap(), Some(value(1)));
+    assert_eq!(trie.get_at(&roots[0], b"horse").unwrap(), Some(value(3)));
+    assert_eq!(trie.get_at(&roots[1], b"cat").unwrap(), Some(value(5)));
+}
+
+#[test]
+fn test_persistent_trie_prune() {
+    let mut trie = PersistentTrie::default();
+    let first = trie.update(changes(&[("dog", value(0)), ("horse", value(1))])).unwrap();
+    let second = trie.update(changes(&[("dog", value(2))])).unwrap();
+
+    let mut versions = PersistentTrie::default();
+    versions
+        .update(changes(&[("dog", value(2)), ("horse", value(1))]))
+        .unwrap();
+    assert!(trie.store().len() > versions.store().len());
+
+    // The leaf of `horse` is shared, and stays with the second version.
+    trie.prune(&first);
+    assert_eq!(trie.store().len(), versions.store().len());
+    assert!(trie.get_at(&first, b"dog").is_err());
+    assert_eq!(trie.get_at(&second, b"horse").unwrap(), Some(value(1)));
+
+    trie.prune(&second);
+    assert!(trie.store().is_empty());
+}
+
+#[test]
+fn test_persistent_trie_save_load() {
+    let path = std::env::temp_dir().join(format!("persistent-trie-{}", std::process::id()));
+    let mut trie = PersistentTrie::default();
+    let first = trie.update(changes(&[("dog", value(0)), ("horse", value(1))])).unwrap();
+    let second = trie.update(changes(&[("cat", value(2))])).unwrap();
+    trie.store().save(&path).unwrap();
+
+    let store = NodeStore::load(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(store.len(), trie.store().len());
+    assert_eq!(store.references(&first), 1);
+
+    let mut reopened = PersistentTrie::open(store, first).unwrap();
+    assert_eq!(reopened.get(b"cat").unwrap(), None);
+    assert_eq!(reopened.get_at(&second, b"cat").unwrap(), Some(value(2)));
+    assert_eq!(reopened.update(changes(&[("cat", value(2))])).unwrap(), second);
+    assert!(PersistentTrie::open(NodeStore::default(), first).is_err());
+}
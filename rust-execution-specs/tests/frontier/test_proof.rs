@@ -0,0 +1,138 @@
+use execution_specs_rs::ethereum::{
+    base_types::{Uint, U256},
+    frontier::{
+        fork_types::{keccak256, Account},
+        proof::{get_proof, verify_proof, AccountProof},
+        state::{set_account, set_storage, state_root, State},
+        trie::{dummy_root, root, trie_proof, trie_set, verify_trie_proof, Trie, EMPTY_TRIE_ROOT},
+    },
+};
+use proptest::prelude::*;
+
+fn slot(n: u8) -> [u8; 32] {
+    let mut key = [0; 32];
+    key[31] = n;
+    key
+}
+
+fn state() -> State {
+    let mut state = State::default();
+    for i in 1..=20u8 {
+        set_account(
+            &mut state,
+            [i; 20],
+            Some(Account {
+                nonce: Uint::from(i),
+                balance: U256::from(i) * 1000u32,
+                code: vec![i; i as usize].into(),
+            }),
+        );
+    }
+    for i in 1..=10u8 {
+        set_storage(&mut state, [1; 20], &slot(i), U256::from(i));
+    }
+    state
+}
+
+#[test]
+fn test_single_account() {
+    let mut state = State::default();
+    set_account(&mut state, [1; 20], Some(Account::default()));
+    let proof = get_proof(&state, &[1; 20], &[]);
+    assert_eq!(proof.account_proof.len(), 1);
+    assert_eq!(keccak256(&proof.account_proof[0]), state_root(&state));
+    verify_proof(&state_root(&state), &proof).unwrap();
+}
+
+#[test]
+fn test_account_and_storage_proofs() {
+    let state = state();
+    let root = state_root(&state);
+
+    let proof = get_proof(&state, &[1; 20], &[slot(3), slot(42)]);
+    assert!(proof.account_proof.len() > 1);
+    assert_eq!(proof.nonce, Uint::from(1u8));
+    assert_eq!(proof.code_hash, keccak256(&[1]));
+    assert_eq!(proof.storage_proof[0].value, U256::from(3u8));
+    assert_eq!(proof.storage_proof[1].value, U256::from(0u8));
+    verify_proof(&root, &proof).unwrap();
+
+    // An account without storage.
+    let proof = get_proof(&state, &[2; 20], &[slot(1)]);
+    assert_eq!(proof.storage_hash, EMPTY_TRIE_ROOT());
+    verify_proof(&root, &proof).unwrap();
+}
+
+#[test]
+fn test_absent_account() {
+    let state = state();
+    let proof = get_proof(&state, &[0xee; 20], &[slot(1)]);
+    assert_eq!(proof.balance, U256::from(0u8));
+    assert_eq!(proof.code_hash, keccak256(&[]));
+    verify_proof(&state_root(&state), &proof).unwrap();
+}
+
+#[test]
+fn test_tampered_proofs_fail() {
+    let state = state();
+    let root = state_root(&state);
+    let proof = get_proof(&state, &[1; 20], &[slot(3)]);
+
+    let mut bad = proof.clone();
+    bad.balance += 1u8;
+    assert!(verify_proof(&root, &bad).is_err());
+
+    let mut bad = proof.clone();
+    bad.storage_proof[0].value = U256::from(4u8);
+    assert!(verify_proof(&root, &bad).is_err());
+
+    let mut bad = proof.clone();
+    bad.account_proof.pop();
+    assert!(verify_proof(&root, &bad).is_err());
+
+    // Claiming an existing account is absent.
+    let mut bad = get_proof(&state, &[0xee; 20], &[]);
+    bad.account_proof = proof.account_proof.clone();
+    assert!(verify_proof(&root, &bad).is_err());
+
+    assert!(verify_proof(&[0; 32], &proof).is_err());
+}
+
+#[test]
+fn test_json_round_trip() {
+    let state = state();
+    let proof = get_proof(&state, &[1; 20], &[slot(3)]);
+    let json = proof.to_json();
+    assert_eq!(json["balance"], "0x3e8");
+    assert_eq!(json["nonce"], "0x1");
+    assert_eq!(json["storageProof"][0]["value"], "0x3");
+    assert_eq!(AccountProof::from_json(&json).unwrap(), proof);
+}
+
+proptest! {
+    #[test]
+    fn test_trie_proofs(
+        entries in prop::collection::vec(
+            (prop::collection::vec(0u8..4, 0..4), prop::collection::vec(any::<u8>(), 1..40)),
+            0..30,
+        ),
+        absent in prop::collection::vec(0u8..4, 0..4),
+    ) {
+        // Short unsecured keys give extension nodes, embedded nodes and
+        // values in branches.
+        let mut trie = Trie::<Box<[u8]>, Box<[u8]>>::new(false, Box::default());
+        for (key, value) in &entries {
+            trie_set(&mut trie, key.clone().into(), value.clone().into());
+        }
+        let trie_root = root(&trie, dummy_root);
+        for (key, value) in &trie.data {
+            let proof = trie_proof(&trie, key, dummy_root);
+            prop_assert_eq!(verify_trie_proof(&trie_root, key, &proof).unwrap(), Some(value.clone()));
+        }
+        let absent: Box<[u8]> = absent.into();
+        if !trie.data.contains_key(&absent) {
+            let proof = trie_proof(&trie, &absent, dummy_root);
+            prop_assert_eq!(verify_trie_proof(&trie_root, &absent, &proof).unwrap(), None);
+        }
+    }
+}
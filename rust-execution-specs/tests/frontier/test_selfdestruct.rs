@@ -0,0 +1,130 @@
+//! The accrued substate of `SELFDESTRUCT`: who is credited, in which order,
+//! and what is left of the accounts at the end of the transaction.
+
+use execution_specs_rs::{
+    ethereum::{base_types::U256, frontier::vm::BlockEnvironment},
+    ethereum_spec_tools::test_env::{TestEnv, TestTx, COINBASE},
+};
+use hex_literal::hex;
+
+const CONTRACT: [u8; 20] = [0xcc; 20];
+const CALLEE: [u8; 20] = [0xdd; 20];
+
+/// `SELFDESTRUCT(beneficiary)`
+fn selfdestruct_to(beneficiary: [u8; 20]) -> Vec<u8> {
+    [&[0x73][..], &beneficiary, &[0xff]].concat()
+}
+
+#[test]
+fn test_selfdestruct_to_self() {
+    // The balance is zeroed after the beneficiary is credited, so it is
+    // burnt along with the account.
+    let mut env = TestEnv::new()
+        .with_code(CONTRACT, selfdestruct_to(CONTRACT))
+        .with_balance(CONTRACT, U256::from(100u8));
+    env.run_tx(TestTx::call(CONTRACT)).unwrap().assert_snapshot(
+        "
+        gas used: 10502
+        0x000000000000000000000000000073656e646572: nonce 0 -> 1
+        0xcccccccccccccccccccccccccccccccccccccccc: destroyed
+        ",
+    );
+}
+
+#[test]
+fn test_selfdestruct_to_coinbase() {
+    // The coinbase gets the balance during execution and the fee after it.
+    let mut env = TestEnv::new()
+        .with_code(CONTRACT, selfdestruct_to(COINBASE))
+        .with_balance(CONTRACT, U256::from(100u8));
+    env.run_tx(TestTx::call(CONTRACT).gas_price(U256::from(1u8))).unwrap().assert_snapshot(
+        "
+        gas used: 10502
+        0x00000000000000000000000000000000000c0ffe: balance 0 -> 10602
+        0x000000000000000000000000000073656e646572: nonce 0 -> 1, balance 1000000000000000000 -> 999999999999989498
+        0xcccccccccccccccccccccccccccccccccccccccc: destroyed
+        ",
+    );
+}
+
+#[test]
+fn test_coinbase_selfdestructs() {
+    // The fee is paid before the self-destructed accounts are removed, so a
+    // coinbase that destroys itself loses it too.
+    let mut env = TestEnv::new()
+        .with_code(COINBASE, selfdestruct_to(CONTRACT))
+        .with_balance(COINBASE, U256::from(100u8));
+    env.run_tx(TestTx::call(COINBASE).gas_price(U256::from(1u8))).unwrap().assert_snapshot(
+        "
+        gas used: 10502
+        0x00000000000000000000000000000000000c0ffe: destroyed
+        0x000000000000000000000000000073656e646572: nonce 0 -> 1, balance 1000000000000000000 -> 999999999999989498
+        0xcccccccccccccccccccccccccccccccccccccccc: created with nonce 0, balance 100
+        ",
+    );
+}
+
+#[test]
+fn test_selfdestruct_twice() {
+    // SSTORE(1, 1), SSTORE(2, 1), so the refund isn't capped at half the
+    // gas used, then CALL(0xffff, CALLEE, 0, 0, 0, 0, 0) twice: the callee
+    // is still there until the end of the transaction, but is refunded
+    // once.
+    let stores = hex!("6001 6001 55 6001 6002 55");
+    let call = hex!("6000 80 80 80 80 73dddddddddddddddddddddddddddddddddddddddd 61ffff f1 50");
+    let mut env = TestEnv::new()
+        .with_code(CONTRACT, [&stores[..], &call[..], &call[..]].concat())
+        .with_code(CALLEE, selfdestruct_to(COINBASE))
+        .with_balance(CALLEE, U256::from(100u8));
+    env.run_tx(TestTx::call(CONTRACT)).unwrap().assert_snapshot(
+        // 61144 less a single refund of 24000.
+        "
+        gas used: 37144
+        0x00000000000000000000000000000000000c0ffe: balance 0 -> 100
+        0x000000000000000000000000000073656e646572: nonce 0 -> 1
+        0xcccccccccccccccccccccccccccccccccccccccc[0x0000000000000000000000000000000000000000000000000000000000000001]: 0x0 -> 0x1
+        0xcccccccccccccccccccccccccccccccccccccccc[0x0000000000000000000000000000000000000000000000000000000000000002]: 0x0 -> 0x1
+        0xdddddddddddddddddddddddddddddddddddddddd: destroyed
+        ",
+    );
+}
+
+#[test]
+fn test_selfdestruct_in_failed_call() {
+    // The callee self-destructs, then its caller halts exceptionally:
+    // nothing is deleted, credited or refunded.
+    let call = hex!("6000 80 80 80 80 73dddddddddddddddddddddddddddddddddddddddd 61ffff f1 50 fe");
+    let mut env = TestEnv::new()
+        .with_code(CONTRACT, call)
+        .with_code(CALLEE, selfdestruct_to(COINBASE))
+        .with_balance(CALLEE, U256::from(100u8));
+    env.run_tx(TestTx::call(CONTRACT)).unwrap().assert_snapshot(
+        "
+        gas used: 1000000
+        error: InvalidOpcode
+        0x000000000000000000000000000073656e646572: nonce 0 -> 1
+        ",
+    );
+}
+
+#[test]
+fn test_empty_coinbase_is_created() {
+    // Frontier keeps empty accounts (they are cleared from Spurious Dragon
+    // on), so a coinbase paid nothing is created empty, like the account
+    // called.
+    let coinbase = [0xee; 20];
+    let mut env = TestEnv::new().with_block(BlockEnvironment {
+        coinbase,
+        number: 1u8.into(),
+        gas_limit: 10_000_000u32.into(),
+        ..Default::default()
+    });
+    env.run_tx(TestTx::call(CONTRACT)).unwrap().assert_snapshot(
+        "
+        gas used: 21000
+        0x000000000000000000000000000073656e646572: nonce 0 -> 1
+        0xcccccccccccccccccccccccccccccccccccccccc: created with nonce 0, balance 0
+        0xeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeee: created with nonce 0, balance 0
+        ",
+    );
+}
@@ -0,0 +1,84 @@
+use execution_specs_rs::ethereum::{
+    base_types::U256,
+    frontier::{
+        fork_types::{keccak256, Account, Code},
+        state::{
+            begin_transaction, commit_transaction, destroy_account, get_account, get_code_by_hash,
+            rollback_transaction, set_account, set_code, set_storage, state_root, State,
+        },
+    },
+};
+
+const PROXY: [u8; 4] = [0x60, 0x00, 0x35, 0xf4];
+
+fn contract(code: &[u8]) -> Option<Account> {
+    Some(Account {
+        code: code.into(),
+        ..Default::default()
+    })
+}
+
+#[test]
+fn test_shared_code() {
+    let mut state = State::default();
+    set_account(&mut state, [1; 20], contract(&PROXY));
+    set_account(&mut state, [2; 20], contract(&PROXY));
+    set_code(&mut state, [3; 20], PROXY.into());
+
+    let code = get_account(&state, &[1; 20]).code;
+    assert!(Code::ptr_eq(&code, &get_account(&state, &[2; 20]).code));
+    assert!(Code::ptr_eq(&code, &get_account(&state, &[3; 20]).code));
+    let hash = keccak256(&PROXY);
+    assert!(Code::ptr_eq(&code, &get_code_by_hash(&state, &hash).unwrap()));
+    assert_eq!(get_code_by_hash(&state, &keccak256(&[])), Some(Code::default()));
+    assert_eq!(get_code_by_hash(&state, &keccak256(&[0xfe])), None);
+
+    drop(code);
+
+    // The code stays while an account holds it.
+    destroy_account(&mut state, &[1; 20]);
+    set_code(&mut state, [2; 20], [0xfe].into());
+    assert!(get_code_by_hash(&state, &hash).is_some());
+    destroy_account(&mut state, &[3; 20]);
+    assert_eq!(get_code_by_hash(&state, &hash), None);
+    assert_eq!(&*get_code_by_hash(&state, &keccak256(&[0xfe])).unwrap(), &[0xfe]);
+}
+
+#[test]
+fn test_shared_code_in_transactions() {
+    let mut state = State::default();
+    set_account(&mut state, [1; 20], contract(&PROXY));
+    let hash = keccak256(&PROXY);
+
+    // Rolled back, the account holds the code again.
+    begin_transaction(&mut state);
+    destroy_account(&mut state, &[1; 20]);
+    assert!(get_code_by_hash(&state, &hash).is_some());
+    rollback_transaction(&mut state);
+    assert!(get_code_by_hash(&state, &hash).is_some());
+
+    // Committed, the code goes with the account.
+    begin_transaction(&mut state);
+    begin_transaction(&mut state);
+    destroy_account(&mut state, &[1; 20]);
+    commit_transaction(&mut state);
+    assert!(get_code_by_hash(&state, &hash).is_some());
+    commit_transaction(&mut state);
+    assert_eq!(get_code_by_hash(&state, &hash), None);
+}
+
+#[test]
+fn test_state_root_in_transaction() {
+    let mut state = State::default();
+    set_account(&mut state, [1; 20], contract(&PROXY));
+    set_storage(&mut state, [1; 20], &[0; 32], U256::from(1u8));
+    let root = state_root(&state);
+
+    // A root computed inside the transaction is forgotten when it is rolled
+    // back.
+    begin_transaction(&mut state);
+    set_storage(&mut state, [1; 20], &[0; 32], U256::from(2u8));
+    assert_ne!(state_root(&state), root);
+    rollback_transaction(&mut state);
+    assert_eq!(state_root(&state), root);
+}
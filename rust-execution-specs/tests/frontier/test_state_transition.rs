@@ -0,0 +1,307 @@
+use execution_specs_rs::ethereum::{
+    base_types::{Uint, U256},
+    exceptions::{EthereumException, InvalidTransactionReason},
+    frontier::{
+        fork::{
+            calculate_intrinsic_cost, check_transaction, process_transaction, validate_transaction,
+        },
+        fork_types::{keccak256, Account, Transaction, EMPTY_ACCOUNT},
+        state::{
+            account_exists, account_exists_and_is_empty, account_has_storage, begin_transaction,
+            commit_transaction, get_account, get_storage, get_storage_original, is_account_alive,
+            is_account_empty, is_create_collision, iter_storage, set_account, set_storage, State,
+        },
+        utils::address::compute_contract_address,
+        vm::{BlockEnvironment, TxEnvironment},
+    },
+};
+use hex_literal::hex;
+
+const SENDER: [u8; 20] = [0xaa; 20];
+const CONTRACT: [u8; 20] = [0xcc; 20];
+const COINBASE: [u8; 20] = [0xbb; 20];
+
+fn block_env() -> BlockEnvironment {
+    BlockEnvironment {
+        coinbase: COINBASE,
+        number: Uint::from(1u8),
+        gas_limit: Uint::from(1_000_000u32),
+        ..Default::default()
+    }
+}
+
+fn tx_env() -> TxEnvironment {
+    TxEnvironment {
+        origin: SENDER,
+        gas_price: U256::from(2u8),
+    }
+}
+
+fn transaction(data: &[u8]) -> Transaction {
+    Transaction {
+        nonce: U256::from(0u8),
+        gas_price: U256::from(2u8),
+        gas: U256::from(100_000u32),
+        to: Some(CONTRACT),
+        value: U256::from(0u8),
+        data: data.into(),
+        v: U256::from(27u8),
+        r: U256::from(1u8),
+        s: U256::from(1u8),
+    }
+}
+
+fn state(code: &[u8]) -> State {
+    let mut state = State::default();
+    set_account(
+        &mut state,
+        SENDER,
+        Some(Account {
+            balance: U256::from(1_000_000u32),
+            ..Default::default()
+        }),
+    );
+    set_account(
+        &mut state,
+        CONTRACT,
+        Some(Account {
+            code: code.into(),
+            ..Default::default()
+        }),
+    );
+    state
+}
+
+#[test]
+fn test_intrinsic_cost() {
+    let tx = transaction(&[0, 1, 0, 2]);
+    assert_eq!(calculate_intrinsic_cost(&tx), Uint::from(21000u32 + 2 * 4 + 2 * 68));
+    assert!(validate_transaction(&tx));
+
+    let mut tx = transaction(&[]);
+    tx.gas = U256::from(20999u32);
+    assert!(!validate_transaction(&tx));
+}
+
+#[test]
+fn test_process_transaction_fees_and_refund() {
+    // SSTORE(0, 1), SSTORE(0, 0): the second store earns a refund.
+    let mut state = state(&hex!("6001 6000 55 6000 6000 55"));
+    let (gas_used, logs) =
+        process_transaction(&block_env(), &tx_env(), &mut state, &transaction(&[])).unwrap();
+
+    // Intrinsic cost, 4 pushes, SSTORE from zero and SSTORE to zero.
+    let execution_gas: u32 = 21000 + 4 * 3 + 20000 + 5000;
+    let refund = 15000;
+    assert_eq!(gas_used, Uint::from(execution_gas - refund));
+    assert!(logs.is_empty());
+    assert_eq!(get_storage(&state, &CONTRACT, &[0; 32]), U256::from(0u8));
+
+    let fee = U256::from(2u8) * &gas_used;
+    let sender = get_account(&state, &SENDER);
+    assert_eq!(sender.nonce, Uint::from(1u8));
+    assert_eq!(sender.balance, U256::from(1_000_000u32) - &fee);
+    assert_eq!(get_account(&state, &COINBASE).balance, fee);
+}
+
+#[test]
+fn test_process_transaction_selfdestruct() {
+    // SELFDESTRUCT(COINBASE)
+    let mut code = vec![0x73];
+    code.extend(COINBASE);
+    code.push(0xff);
+    let mut state = state(&code);
+    set_account(
+        &mut state,
+        CONTRACT,
+        Some(Account {
+            balance: U256::from(7u8),
+            code: code.into(),
+            ..Default::default()
+        }),
+    );
+    let (gas_used, _) =
+        process_transaction(&block_env(), &tx_env(), &mut state, &transaction(&[])).unwrap();
+
+    assert!(!account_exists(&state, &CONTRACT));
+    // The 24000 refund of the destroyed account is capped at half the gas
+    // used.
+    let execution_gas: u32 = 21000 + 3;
+    assert_eq!(gas_used, Uint::from(execution_gas - execution_gas / 2));
+    let fee = U256::from(2u8) * &gas_used;
+    assert_eq!(get_account(&state, &COINBASE).balance, fee + 7u8);
+}
+
+#[test]
+fn test_process_transaction_selfdestruct_to_self() {
+    // SELFDESTRUCT(CONTRACT)
+    let mut code = vec![0x73];
+    code.extend(CONTRACT);
+    code.push(0xff);
+    let mut state = state(&code);
+    set_account(
+        &mut state,
+        CONTRACT,
+        Some(Account {
+            balance: U256::from(7u8),
+            code: code.into(),
+            ..Default::default()
+        }),
+    );
+    let total_supply = |state: &State| -> U256 {
+        [SENDER, CONTRACT, COINBASE].iter().map(|address| get_account(state, address).balance).sum()
+    };
+    let supply_before = total_supply(&state);
+    let (gas_used, _) =
+        process_transaction(&block_env(), &tx_env(), &mut state, &transaction(&[])).unwrap();
+
+    // The balance is credited to the beneficiary before the originator is
+    // zeroed, so naming itself as the beneficiary burns the ether: the
+    // coinbase only gets the fee, and the supply drops by 7 wei.
+    assert!(!account_exists(&state, &CONTRACT));
+    let fee = U256::from(2u8) * &gas_used;
+    assert_eq!(get_account(&state, &COINBASE).balance, fee);
+    assert_eq!(total_supply(&state) + 7u8, supply_before);
+}
+
+#[test]
+fn test_process_transaction_invalid() {
+    let mut tx = transaction(&[]);
+    tx.nonce = U256::from(1u8);
+    let result = process_transaction(&block_env(), &tx_env(), &mut state(&[]), &tx);
+    assert!(matches!(result, Err(EthereumException::InvalidBlock)));
+
+    let mut tx = transaction(&[]);
+    tx.value = U256::from(1_000_000u32);
+    let result = process_transaction(&block_env(), &tx_env(), &mut state(&[]), &tx);
+    assert!(matches!(result, Err(EthereumException::InvalidBlock)));
+}
+
+#[test]
+fn test_check_transaction() {
+    let sender = Account {
+        balance: U256::from(200_000u32),
+        ..Default::default()
+    };
+    assert_eq!(check_transaction(&block_env(), &sender, &transaction(&[])), Ok(()));
+
+    let mut tx = transaction(&[]);
+    tx.gas = U256::from(20999u32);
+    assert_eq!(
+        check_transaction(&block_env(), &sender, &tx),
+        Err(InvalidTransactionReason::IntrinsicGasTooLow {
+            intrinsic_cost: Uint::from(21000u32),
+            gas: U256::from(20999u32),
+        })
+    );
+
+    let mut tx = transaction(&[]);
+    tx.gas = U256::from(1_000_001u32);
+    assert!(matches!(
+        check_transaction(&block_env(), &sender, &tx),
+        Err(InvalidTransactionReason::GasAboveBlockLimit { .. })
+    ));
+
+    let mut tx = transaction(&[]);
+    tx.nonce = U256::from(1u8);
+    assert_eq!(
+        check_transaction(&block_env(), &sender, &tx),
+        Err(InvalidTransactionReason::NonceMismatch {
+            expected: Uint::from(0u8),
+            nonce: U256::from(1u8),
+        })
+    );
+
+    // Gas at 2 wei plus the value.
+    let mut tx = transaction(&[]);
+    tx.value = U256::from(1u8);
+    assert_eq!(
+        check_transaction(&block_env(), &sender, &tx),
+        Err(InvalidTransactionReason::InsufficientBalance {
+            required: U256::from(200_001u32),
+            balance: U256::from(200_000u32),
+        })
+    );
+
+    let contract = Account {
+        code: vec![0].into(),
+        ..sender
+    };
+    assert_eq!(
+        check_transaction(&block_env(), &contract, &transaction(&[])),
+        Err(InvalidTransactionReason::SenderNotEoa)
+    );
+}
+
+#[test]
+fn test_account_emptiness() {
+    let mut state = state(&[0]);
+    set_account(&mut state, COINBASE, Some(EMPTY_ACCOUNT()));
+    let nobody = [0xdd; 20];
+
+    assert!(is_account_empty(&state, &COINBASE));
+    assert!(is_account_empty(&state, &nobody));
+    assert!(!is_account_empty(&state, &SENDER));
+    assert!(account_exists_and_is_empty(&state, &COINBASE));
+    assert!(!account_exists_and_is_empty(&state, &nobody));
+    assert!(is_account_alive(&state, &SENDER) && is_account_alive(&state, &CONTRACT));
+    assert!(!is_account_alive(&state, &COINBASE) && !is_account_alive(&state, &nobody));
+
+    assert!(is_create_collision(&state, &CONTRACT));
+    assert!(!is_create_collision(&state, &COINBASE));
+    set_storage(&mut state, COINBASE, &[0; 32], U256::from(1u8));
+    assert!(account_has_storage(&state, &COINBASE));
+    assert!(is_create_collision(&state, &COINBASE));
+    set_storage(&mut state, COINBASE, &[0; 32], U256::from(0u8));
+    assert!(!account_has_storage(&state, &COINBASE));
+}
+
+#[test]
+fn test_create_collision_with_storage() {
+    // EIP-7610: an address with storage but no code or nonce can't be
+    // created at.
+    let target = compute_contract_address(&SENDER, &Uint::from(0u8));
+    let mut state = state(&[]);
+    set_storage(&mut state, target, &[0; 32], U256::from(1u8));
+    let mut tx = transaction(&hex!("60016000f3"));
+    tx.to = None;
+
+    let (gas_used, _) = process_transaction(&block_env(), &tx_env(), &mut state, &tx).unwrap();
+    assert_eq!(gas_used, Uint::from(100_000u32));
+    assert_eq!(get_account(&state, &target).code.len(), 0);
+    assert_eq!(get_storage(&state, &target, &[0; 32]), U256::from(1u8));
+}
+
+#[test]
+fn test_storage_enumeration() {
+    let mut state = State::default();
+    let slot = |n: u8| {
+        let mut key = [0; 32];
+        key[31] = n;
+        key
+    };
+    for n in 0..4u8 {
+        set_storage(&mut state, CONTRACT, &slot(n), U256::from(n));
+    }
+
+    // In the order of the hashed keys, each with its preimage.
+    let slots: Vec<_> = iter_storage(&state, &CONTRACT).collect();
+    assert_eq!(slots.len(), 3);
+    assert!(slots.windows(2).all(|pair| pair[0].0 < pair[1].0));
+    for (hash, key, value) in &slots {
+        assert_eq!(*hash, keccak256(key));
+        assert_eq!(*value, U256::from(key[31]));
+    }
+    assert_eq!(iter_storage(&state, &SENDER).count(), 0);
+
+    // The original value is the one before the transaction.
+    begin_transaction(&mut state);
+    set_storage(&mut state, CONTRACT, &slot(1), U256::from(7u8));
+    set_storage(&mut state, CONTRACT, &slot(4), U256::from(4u8));
+    assert_eq!(get_storage(&state, &CONTRACT, &slot(1)), U256::from(7u8));
+    assert_eq!(get_storage_original(&state, &CONTRACT, &slot(1)), U256::from(1u8));
+    assert_eq!(get_storage_original(&state, &CONTRACT, &slot(4)), U256::from(0u8));
+    commit_transaction(&mut state);
+    assert_eq!(get_storage_original(&state, &CONTRACT, &slot(1)), U256::from(7u8));
+    assert_eq!(iter_storage(&state, &CONTRACT).count(), 4);
+}
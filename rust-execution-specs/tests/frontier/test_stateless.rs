@@ -0,0 +1,275 @@
+use execution_specs_rs::ethereum::{
+    base_types::{Bytes32, Uint, U256},
+    exceptions::EthereumException,
+    frontier::{
+        fork::{apply_body, recover_sender},
+        fork_types::{Account, Address, Transaction},
+        proof::get_proof,
+        state::{get_accounts, set_account, set_storage, state_diff, state_root, State},
+        stateless::{apply_body_stateless, apply_body_with_witness, witness_state, Witness},
+        utils::address::compute_contract_address,
+        vm::BlockEnvironment,
+    },
+    transaction_signature::sign_transaction,
+};
+use hex_literal::hex;
+use std::{collections::BTreeSet, slice};
+
+const KEY: Bytes32 = [0x45; 32];
+const CONTRACT: [u8; 20] = [0xcc; 20];
+const COINBASE: [u8; 20] = [0xbb; 20];
+
+fn slot(n: u8) -> Bytes32 {
+    let mut key = [0; 32];
+    key[31] = n;
+    key
+}
+
+/// A call to `CONTRACT`, which stores the value it is sent in slot 0.
+fn transaction() -> Transaction {
+    let unsigned = Transaction {
+        nonce: U256::from(0u8),
+        gas_price: U256::from(1u8),
+        gas: U256::from(50000u32),
+        to: Some(CONTRACT),
+        value: U256::from(5u8),
+        data: vec![].into(),
+        v: U256::default(),
+        r: U256::default(),
+        s: U256::default(),
+    };
+    sign_transaction(&unsigned, &KEY, None).unwrap()
+}
+
+fn sender() -> Address {
+    recover_sender(&transaction()).unwrap()
+}
+
+fn block_env() -> BlockEnvironment {
+    BlockEnvironment {
+        coinbase: COINBASE,
+        number: Uint::from(1u8),
+        gas_limit: Uint::from(1_000_000u32),
+        ..Default::default()
+    }
+}
+
+fn state() -> State {
+    let mut state = State::default();
+    let funded = Account {
+        balance: U256::from(1_000_000u32),
+        ..Default::default()
+    };
+    set_account(&mut state, sender(), Some(funded));
+    // SSTORE(0, CALLVALUE)
+    let contract = Account {
+        code: hex!("34 6000 55").into(),
+        ..Default::default()
+    };
+    set_account(&mut state, CONTRACT, Some(contract));
+    set_storage(&mut state, CONTRACT, &slot(1), U256::from(7u8));
+    for i in 1..=10u8 {
+        set_account(&mut state, [i; 20], Some(Account::default()));
+    }
+    state
+}
+
+fn witness(state: &State) -> Witness {
+    Witness {
+        accounts: vec![
+            get_proof(state, &sender(), &[]),
+            get_proof(state, &CONTRACT, &[slot(0)]),
+            get_proof(state, &COINBASE, &[]),
+        ],
+        codes: vec![hex!("34 6000 55").into()],
+        nodes: vec![],
+    }
+}
+
+#[test]
+fn test_apply_body_stateless() {
+    let pre = state();
+    let witness = witness(&pre);
+    let output = apply_body_stateless(&state_root(&pre), &block_env(), &[transaction()], &[], &witness).unwrap();
+
+    let mut post = pre.clone();
+    let (gas_used, transactions_root, _, bloom, fee_summary, logs, _) =
+        apply_body(&mut post, &block_env(), &[transaction()], &[]).unwrap();
+    assert_eq!(output.gas_used, gas_used);
+    assert_eq!(output.transactions_root, transactions_root);
+    assert_eq!(output.block_logs_bloom, bloom);
+    assert_eq!(output.fee_summary, fee_summary);
+    assert_eq!(output.transaction_logs, logs);
+    assert_eq!(output.state_root, state_root(&post));
+    assert_eq!(output.diff, state_diff(&pre, &post));
+    assert_eq!(
+        output.diff[&CONTRACT].storage[&slot(0)],
+        (U256::from(0u8), U256::from(5u8))
+    );
+
+    assert_eq!(Witness::from_json(&witness.to_json()).unwrap(), witness);
+}
+
+#[test]
+fn test_witness_state() {
+    let pre = state();
+    let state = witness_state(&state_root(&pre), &witness(&pre)).unwrap();
+    // Only what is proven, and the coinbase is proven absent.
+    let accounts = get_accounts(&state);
+    assert_eq!(
+        accounts.keys().copied().collect::<BTreeSet<_>>(),
+        BTreeSet::from([sender(), CONTRACT])
+    );
+    assert_eq!(&*accounts[&CONTRACT].code, &hex!("34 6000 55"));
+
+    let mut tampered = witness(&pre);
+    tampered.accounts[0].balance += 1u8;
+    let Err(error) = witness_state(&state_root(&pre), &tampered) else {
+        panic!("a tampered witness is accepted");
+    };
+    assert!(matches!(error.root_cause(), EthereumException::ValueError));
+    assert_eq!(error.address(), Some(&tampered.accounts[0].address));
+    assert!(matches!(
+        witness_state(&[0; 32], &witness(&pre)),
+        Err(e) if matches!(e.root_cause(), EthereumException::ValueError)
+    ));
+}
+
+#[test]
+fn test_missing_witness() {
+    let pre = state();
+    let root = state_root(&pre);
+    let missing = |witness: Witness| match apply_body_stateless(&root, &block_env(), &[transaction()], &[], &witness) {
+        Err(EthereumException::MissingWitness(message)) => message,
+        other => panic!(
+            "expected a missing witness, got {:?}",
+            other.map(|output| output.gas_used)
+        ),
+    };
+
+    let mut witness = witness(&pre);
+    witness.codes.clear();
+    assert!(missing(witness).starts_with("code of 0xcccc"));
+
+    let mut witness = self::witness(&pre);
+    witness.accounts.remove(2);
+    assert_eq!(missing(witness), format!("account 0x{}", "bb".repeat(20)));
+
+    let mut witness = self::witness(&pre);
+    witness.accounts[1] = get_proof(&pre, &CONTRACT, &[slot(1)]);
+    assert!(missing(witness).starts_with("storage slot 0x0000"));
+
+    // Without the sender, the block would be invalid: it is the witness
+    // that is at fault.
+    let mut witness = self::witness(&pre);
+    witness.accounts.remove(0);
+    assert!(missing(witness).starts_with("account 0x"));
+}
+
+#[test]
+fn test_apply_body_with_witness() {
+    let pre = state();
+    let mut post = pre.clone();
+    let (output, witness) = apply_body_with_witness(&mut post, &block_env(), &[transaction()], &[]).unwrap();
+    assert_eq!(
+        output,
+        apply_body(&mut pre.clone(), &block_env(), &[transaction()], &[]).unwrap()
+    );
+
+    let addresses: BTreeSet<_> = witness.accounts.iter().map(|proof| proof.address).collect();
+    assert_eq!(addresses, BTreeSet::from([sender(), CONTRACT, COINBASE]));
+    let contract = witness.accounts.iter().find(|proof| proof.address == CONTRACT).unwrap();
+    assert_eq!(
+        contract.storage_proof.iter().map(|slot| slot.key).collect::<Vec<_>>(),
+        [slot(0)]
+    );
+    assert_eq!(witness.codes, [hex!("34 6000 55").into()]);
+
+    let stateless = apply_body_stateless(&state_root(&pre), &block_env(), &[transaction()], &[], &witness).unwrap();
+    assert_eq!(stateless.diff, state_diff(&pre, &post));
+}
+
+#[test]
+fn test_witness_create_collision() {
+    // A contract can't be created where there is storage, which the
+    // witness has to show with a slot.
+    let mut pre = state();
+    let created = compute_contract_address(&sender(), &Uint::from(0u8));
+    set_account(&mut pre, created, Some(Account::default()));
+    set_storage(&mut pre, created, &slot(9), U256::from(1u8));
+    let mut create = transaction();
+    create.to = None;
+    let create = sign_transaction(&create, &KEY, None).unwrap();
+
+    let (output, witness) =
+        apply_body_with_witness(&mut pre.clone(), &block_env(), slice::from_ref(&create), &[]).unwrap();
+    let proof = witness.accounts.iter().find(|proof| proof.address == created).unwrap();
+    assert_eq!(proof.storage_proof[0].key, slot(9));
+    let stateless =
+        apply_body_stateless(&state_root(&pre), &block_env(), slice::from_ref(&create), &[], &witness).unwrap();
+    assert_eq!(stateless.gas_used, output.0);
+
+    let mut witness = witness;
+    witness
+        .accounts
+        .iter_mut()
+        .find(|proof| proof.address == created)
+        .unwrap()
+        .storage_proof
+        .clear();
+    assert!(matches!(
+        apply_body_stateless(&state_root(&pre), &block_env(), &[create], &[], &witness),
+        Err(EthereumException::MissingWitness(_))
+    ));
+}
+
+/// Runs a call to `to`, whose code is `code` and which has slots 1 and 2,
+/// from a generated witness.
+fn run_with_witness(to: Address, code: &[u8]) -> (State, State, Witness) {
+    let mut pre = state();
+    let contract = Account {
+        code: code.into(),
+        ..Default::default()
+    };
+    set_account(&mut pre, to, Some(contract));
+    set_storage(&mut pre, to, &slot(1), U256::from(7u8));
+    set_storage(&mut pre, to, &slot(2), U256::from(8u8));
+    let mut call = transaction();
+    call.to = Some(to);
+    let call = sign_transaction(&call, &KEY, None).unwrap();
+
+    let mut post = pre.clone();
+    let (_, witness) = apply_body_with_witness(&mut post, &block_env(), slice::from_ref(&call), &[]).unwrap();
+    let stateless = apply_body_stateless(&state_root(&pre), &block_env(), &[call], &[], &witness).unwrap();
+    assert_eq!(stateless.state_root, state_root(&post));
+    (pre, post, witness)
+}
+
+#[test]
+fn test_witness_state_root() {
+    // SSTORE(1, 0) merges the root of the storage with the leaf of slot 2,
+    // which only its node has.
+    let (pre, _, witness) = run_with_witness([0xdd; 20], &hex!("6000 6001 55"));
+    assert_eq!(witness.nodes.len(), 1);
+    assert_eq!(Witness::from_json(&witness.to_json()).unwrap(), witness);
+
+    let mut call = transaction();
+    call.to = Some([0xdd; 20]);
+    let call = sign_transaction(&call, &KEY, None).unwrap();
+    let mut witness = witness;
+    witness.nodes.clear();
+    match apply_body_stateless(&state_root(&pre), &block_env(), &[call], &[], &witness) {
+        Err(EthereumException::MissingWitness(message)) => {
+            assert!(message.starts_with("trie node "), "{message}");
+            assert!(message.ends_with(&format!("of the storage of 0x{}", "dd".repeat(20))));
+        }
+        other => panic!(
+            "expected a missing witness, got {:?}",
+            other.map(|output| output.gas_used)
+        ),
+    }
+
+    // SELFDESTRUCT(CALLER) deletes the account and its storage.
+    let (_, post, _) = run_with_witness([0xde; 20], &hex!("33 ff"));
+    assert!(!get_accounts(&post).contains_key(&[0xde; 20]));
+}
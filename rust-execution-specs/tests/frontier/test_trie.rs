@@ -1,4 +1,15 @@
 
+use execution_specs_rs::ethereum::{
+    base_types::{u256_to_be_bytes32, Uint, U256},
+    crypto::hash::keccak256,
+    frontier::{
+        fork_types::Account,
+        state::{set_account, set_storage, state_root, State},
+        trie::{bytes_to_nibble_list, sorted_root},
+    },
+    rlp,
+};
+use std::collections::BTreeMap;
 use execution_specs_rs::ethereum::{exceptions::EthereumException, frontier::{trie::{Trie, trie_set, root, dummy_root}}, base_types::Bytes, utils::hexadecimal::{has_hex_prefix, hex_to_bytes, hex}};
 
 fn string_to_bytes(data: &str) -> Result<Bytes, EthereumException> {
@@ -101,6 +112,62 @@ pub fn test_trie_any_order() -> Result<(), EthereumException> {
     Ok(())
 }
 
+/// Enough entries for the `parallel` feature to prepare the trie on all
+/// cores.
+const LARGE_TRIE: u32 = 3000;
+
+#[test]
+fn test_large_trie_root_is_deterministic() {
+    let entries: Vec<([u8; 32], U256)> = (1..=LARGE_TRIE)
+        .map(|i| (u256_to_be_bytes32(&U256::from(i)), U256::from(i) * 0x1234_5678u32))
+        .collect();
+
+    // The keys hashed and the values encoded one at a time, in order.
+    let mut obj: Vec<(Bytes, Bytes)> = entries
+        .iter()
+        .map(|(key, value)| (bytes_to_nibble_list(&keccak256(key)), rlp::encode(value)))
+        .collect();
+    obj.sort();
+    let expected = sorted_root(&obj);
+
+    let mut trie = Trie::new(true, U256::default());
+    for (key, value) in entries.iter().rev() {
+        trie_set(&mut trie, *key, value.clone());
+    }
+    let sorted = Trie::<_, _, BTreeMap<_, _>>::from_sorted_iter(true, U256::default(), entries);
+    for _ in 0..4 {
+        assert_eq!(root(&trie, dummy_root), expected);
+        assert_eq!(root(&sorted, dummy_root), expected);
+    }
+}
+
+#[test]
+fn test_large_state_root_is_deterministic() {
+    // Accounts set in opposite orders, so their entries come out of the
+    // backend in different orders.
+    let state = |addresses: &mut dyn Iterator<Item = u32>| {
+        let mut state = State::default();
+        for i in addresses {
+            let mut address = [0; 20];
+            address[16..].copy_from_slice(&i.to_be_bytes());
+            let account = Account {
+                nonce: Uint::from(i),
+                ..Default::default()
+            };
+            set_account(&mut state, address, Some(account));
+            if i % 10 == 0 {
+                set_storage(&mut state, address, &u256_to_be_bytes32(&U256::from(i)), U256::from(i));
+            }
+        }
+        state
+    };
+    let forward = state(&mut (1..=LARGE_TRIE));
+    let backward = state(&mut (1..=LARGE_TRIE).rev());
+    let expected = state_root(&forward);
+    assert_eq!(state_root(&forward.clone()), expected);
+    assert_eq!(state_root(&backward), expected);
+}
+
 
 pub fn load_tests(path: &str) -> Result<serde_json::Value, EthereumException> {
     let ethereum_tests_path = "../tests/";
@@ -0,0 +1,120 @@
+use execution_specs_rs::ethereum::{
+    base_types::{Bytes, Uint, U256},
+    frontier::{
+        fork_types::Account,
+        trie::{
+            debug::{to_dot, to_json, trie_nodes, TrieNode, TrieNodeKind},
+            dummy_root, root, trie_set, Trie, EMPTY_TRIE_ROOT,
+        },
+    },
+};
+use hex_literal::hex;
+
+/// The example of the Ethereum wiki.
+fn dog_trie() -> Trie<Bytes, Bytes> {
+    let mut trie = Trie::new(false, Bytes::default());
+    for (key, value) in [
+        ("do", "verb"),
+        ("dog", "puppy"),
+        ("doge", "coin"),
+        ("horse", "stallion"),
+    ] {
+        trie_set(&mut trie, key.as_bytes().into(), value.as_bytes().into());
+    }
+    trie
+}
+
+#[test]
+fn test_trie_json() {
+    let trie = dog_trie();
+    let tree = to_json(&trie, dummy_root);
+    let root_hash = hex!("5991bb8c6514148a29db676a14ac506cd2cd5775ace63c30a4fe457715e9ac84");
+    assert_eq!(root(&trie, dummy_root), root_hash);
+
+    assert_eq!(tree["type"], "extension");
+    assert_eq!(tree["key"], "0x16");
+    assert_eq!(
+        tree["hash"],
+        "0x5991bb8c6514148a29db676a14ac506cd2cd5775ace63c30a4fe457715e9ac84"
+    );
+    assert_eq!(tree["embedded"], false);
+
+    let branch = &tree["child"];
+    assert_eq!(branch["type"], "branch");
+    assert_eq!(branch["path"], "6");
+    let children = branch["children"].as_object().unwrap();
+    assert_eq!(children.keys().collect::<Vec<_>>(), ["4", "8"]);
+
+    // "horse", after its first two nibbles.
+    let horse = &children["8"];
+    assert_eq!(horse["type"], "leaf");
+    assert_eq!(horse["path"], "68");
+    assert_eq!(horse["key"], "0x206f727365");
+    assert_eq!(horse["value"], "0x7374616c6c696f6e");
+
+    // "do" ends at the branch below the extension of the rest of its key.
+    let do_branch = &children["4"]["child"];
+    assert_eq!(children["4"]["key"], "0x006f");
+    assert_eq!(do_branch["path"], "646f");
+    assert_eq!(do_branch["value"], "0x76657262");
+
+    // The "coin" leaf is small enough to be embedded.
+    let coin = &do_branch["children"]["6"]["child"]["children"]["6"];
+    assert_eq!(coin["key"], "0x35");
+    assert_eq!(coin["embedded"], true);
+    assert_eq!(coin["rlp"], "0xc63584636f696e");
+
+    assert_eq!(
+        to_json(&Trie::<Bytes, Bytes>::new(false, Bytes::default()), dummy_root),
+        serde_json::Value::Null
+    );
+}
+
+#[test]
+fn test_trie_dot() {
+    let dot = to_dot(&dog_trie(), dummy_root);
+    assert!(dot.starts_with("digraph trie {\n"));
+    assert!(dot.ends_with("}\n"));
+    assert!(dot.contains("    n0 [label=\"extension\\nkey 0x16\\nhash 0x5991bb8c...\"];\n"));
+    assert!(dot.contains("    n0 -> n1;\n"));
+    assert!(dot.contains("    n1 -> n2 [label=\"4\"];\n"));
+    // Eight nodes, seven edges.
+    let (edges, nodes): (Vec<_>, Vec<_>) = dot
+        .lines()
+        .filter(|line| line.starts_with("    n") && !line.starts_with("    node"))
+        .partition(|line| line.contains("->"));
+    assert_eq!((nodes.len(), edges.len()), (8, 7));
+
+    let empty = to_dot(&Trie::<Bytes, Bytes>::new(false, Bytes::default()), dummy_root);
+    assert!(empty.contains("n0 [label=\"empty\\nhash 0x56e81f17...\"];"));
+}
+
+#[test]
+fn test_trie_nodes_secured() {
+    let mut trie = Trie::new(true, None);
+    for i in 1..=40u8 {
+        let account = Account {
+            nonce: Uint::from(i),
+            balance: U256::from(i),
+            ..Default::default()
+        };
+        trie_set(&mut trie, [i; 20], Some(account));
+    }
+    let storage_root = |_: &_| EMPTY_TRIE_ROOT();
+    let node = trie_nodes(&trie, storage_root).unwrap();
+    assert_eq!(node.hash, root(&trie, storage_root));
+    assert_eq!(node.kind, TrieNodeKind::Branch);
+
+    // Every referenced node is hashed, every node holds its children.
+    fn check(node: &TrieNode) {
+        for child in &node.children {
+            assert_eq!(child.embedded, child.encoded.len() < 32);
+            if !child.embedded {
+                let hash = &child.hash[..];
+                assert!(node.encoded.windows(32).any(|window| window == hash));
+            }
+            check(child);
+        }
+    }
+    check(&node);
+}
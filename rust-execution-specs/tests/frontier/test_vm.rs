@@ -0,0 +1,271 @@
+//! A subset of the VM tests the Python specification runs, on [TestEnv].
+//! Results are stored in slot 0 of the contract.
+
+use execution_specs_rs::{
+    ethereum::{
+        base_types::U256,
+        exceptions::EthereumException,
+        frontier::{
+            state::{get_storage, state_root},
+            vm::instructions::{arithmetic, InstructionTable, Ops, FRONTIER},
+        },
+    },
+    ethereum_spec_tools::test_env::{slot, TestEnv, TestTx, TxOutcome, SENDER},
+};
+use hex_literal::hex;
+
+const CONTRACT: [u8; 20] = hex!("0f572e5295c57f15886f9b263e2f6d2d6c7b5ec6");
+
+fn run(code: &[u8]) -> TxOutcome {
+    TestEnv::new().with_code(CONTRACT, code).run_tx(TestTx::call(CONTRACT)).unwrap()
+}
+
+#[test]
+fn test_add0() {
+    // PUSH32 2^256 - 1, PUSH32 2^256 - 1, ADD, PUSH1 0, SSTORE
+    let code = hex!(
+        "7fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff"
+        "7fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff"
+        "01600055"
+    );
+    run(&code).assert_snapshot(
+        "
+        gas used: 41012
+        0x000000000000000000000000000073656e646572: nonce 0 -> 1
+        0x0f572e5295c57f15886f9b263e2f6d2d6c7b5ec6[0x0000000000000000000000000000000000000000000000000000000000000000]: 0x0 -> 0xfffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffe
+        ",
+    );
+}
+
+#[test]
+fn test_sub4() {
+    // PUSH1 1, PUSH1 0, SUB, PUSH1 0, SSTORE
+    run(&hex!("600160000360005500")).assert_snapshot(
+        "
+        gas used: 41012
+        0x000000000000000000000000000073656e646572: nonce 0 -> 1
+        0x0f572e5295c57f15886f9b263e2f6d2d6c7b5ec6[0x0000000000000000000000000000000000000000000000000000000000000000]: 0x0 -> 0xffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff
+        ",
+    );
+}
+
+#[test]
+fn test_div_by_zero() {
+    // PUSH1 0, PUSH1 2, DIV, PUSH1 0, SSTORE: nothing is stored.
+    run(&hex!("600060020460005500")).assert_snapshot(
+        "
+        gas used: 26014
+        0x000000000000000000000000000073656e646572: nonce 0 -> 1
+        ",
+    );
+}
+
+#[test]
+fn test_sdiv_overflow() {
+    // PUSH32 -1, PUSH32 -2^255, SDIV, PUSH1 0, SSTORE
+    let code = hex!(
+        "7fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff"
+        "7f8000000000000000000000000000000000000000000000000000000000000000"
+        "05600055"
+    );
+    run(&code).assert_snapshot(
+        "
+        gas used: 41014
+        0x000000000000000000000000000073656e646572: nonce 0 -> 1
+        0x0f572e5295c57f15886f9b263e2f6d2d6c7b5ec6[0x0000000000000000000000000000000000000000000000000000000000000000]: 0x0 -> 0x8000000000000000000000000000000000000000000000000000000000000000
+        ",
+    );
+}
+
+#[test]
+fn test_exp_overflow() {
+    // PUSH1 255, PUSH1 2, EXP, PUSH1 0, SSTORE
+    run(&hex!("60ff60020a600055")).assert_snapshot(
+        "
+        gas used: 41029
+        0x000000000000000000000000000073656e646572: nonce 0 -> 1
+        0x0f572e5295c57f15886f9b263e2f6d2d6c7b5ec6[0x0000000000000000000000000000000000000000000000000000000000000000]: 0x0 -> 0x8000000000000000000000000000000000000000000000000000000000000000
+        ",
+    );
+}
+
+#[test]
+fn test_signextend() {
+    // PUSH1 0xff, PUSH1 0, SIGNEXTEND, PUSH1 0, SSTORE
+    run(&hex!("60ff60000b600055")).assert_snapshot(
+        "
+        gas used: 41014
+        0x000000000000000000000000000073656e646572: nonce 0 -> 1
+        0x0f572e5295c57f15886f9b263e2f6d2d6c7b5ec6[0x0000000000000000000000000000000000000000000000000000000000000000]: 0x0 -> 0xffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff
+        ",
+    );
+}
+
+#[test]
+fn test_byte() {
+    // PUSH32 0x1122..., PUSH1 1, BYTE, PUSH1 0, SSTORE
+    let code = hex!(
+        "7f1122334455667788990011223344556677889900112233445566778899001122"
+        "60011a600055"
+    );
+    run(&code).assert_snapshot(
+        "
+        gas used: 41012
+        0x000000000000000000000000000073656e646572: nonce 0 -> 1
+        0x0f572e5295c57f15886f9b263e2f6d2d6c7b5ec6[0x0000000000000000000000000000000000000000000000000000000000000000]: 0x0 -> 0x22
+        ",
+    );
+}
+
+#[test]
+fn test_sha3_0() {
+    // PUSH1 0, PUSH1 0, SHA3, PUSH1 0, SSTORE
+    run(&hex!("6000600020600055")).assert_snapshot(
+        "
+        gas used: 41039
+        0x000000000000000000000000000073656e646572: nonce 0 -> 1
+        0x0f572e5295c57f15886f9b263e2f6d2d6c7b5ec6[0x0000000000000000000000000000000000000000000000000000000000000000]: 0x0 -> 0xc5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470
+        ",
+    );
+}
+
+#[test]
+fn test_jump_to_push_data() {
+    // PUSH1 3, JUMP, PUSH1 0x5b: the JUMPDEST byte is push data.
+    run(&hex!("600356605b")).assert_snapshot(
+        "
+        gas used: 1000000
+        error: InvalidJumpDest
+        0x000000000000000000000000000073656e646572: nonce 0 -> 1
+        ",
+    );
+}
+
+#[test]
+fn test_log1() {
+    // PUSH1 0xaa, PUSH1 0, MSTORE8, PUSH1 0x42, PUSH1 1, PUSH1 0, LOG1
+    run(&hex!("60aa600053604260016000a1")).assert_snapshot(
+        "
+        gas used: 21779
+        log 0x0f572e5295c57f15886f9b263e2f6d2d6c7b5ec6: [0x0000000000000000000000000000000000000000000000000000000000000042] 0xaa
+        0x000000000000000000000000000073656e646572: nonce 0 -> 1
+        ",
+    );
+}
+
+#[test]
+fn test_return() {
+    // PUSH1 42, PUSH1 0, MSTORE, PUSH1 32, PUSH1 0, RETURN
+    run(&hex!("602a60005260206000f3")).assert_snapshot(
+        "
+        gas used: 21018
+        output: 0x000000000000000000000000000000000000000000000000000000000000002a
+        0x000000000000000000000000000073656e646572: nonce 0 -> 1
+        ",
+    );
+}
+
+#[test]
+fn test_out_of_gas() {
+    // PUSH1 1, PUSH1 0, SSTORE, without the gas for the SSTORE.
+    let mut env = TestEnv::new().with_code(CONTRACT, hex!("6001600055"));
+    let outcome = env.run_tx(TestTx::call(CONTRACT).gas(U256::from(25000u32))).unwrap();
+    outcome.assert_snapshot(
+        "
+        gas used: 25000
+        error: OutOfGas
+        0x000000000000000000000000000073656e646572: nonce 0 -> 1
+        ",
+    );
+}
+
+#[test]
+fn test_suicide() {
+    // PUSH1 0xbe, SUICIDE
+    let mut env = TestEnv::new()
+        .with_code(CONTRACT, hex!("60beff"))
+        .with_balance(CONTRACT, U256::from(100u8));
+    env.run_tx(TestTx::call(CONTRACT)).unwrap().assert_snapshot(
+        "
+        gas used: 10502
+        0x00000000000000000000000000000000000000be: created with nonce 0, balance 100
+        0x000000000000000000000000000073656e646572: nonce 0 -> 1
+        0x0f572e5295c57f15886f9b263e2f6d2d6c7b5ec6: destroyed
+        ",
+    );
+}
+
+#[test]
+fn test_create() {
+    // Init code returning the runtime code `PUSH1 0, SLOAD`:
+    // PUSH3 0x600054, PUSH1 0, MSTORE, PUSH1 3, PUSH1 29, RETURN
+    let mut env = TestEnv::new();
+    env.run_tx(TestTx::create(hex!("626000546000526003601df3"))).unwrap().assert_snapshot(
+        "
+        gas used: 22306
+        output: 0x600054
+        0x000000000000000000000000000073656e646572: nonce 0 -> 1
+        0x1f2a98889594024bffda3311cbe69728d392c06d: created with nonce 0, balance 0, code 0x600054
+        ",
+    );
+}
+
+#[test]
+fn test_call_with_value() {
+    // Send 7 wei to 0xca11 with 0 gas, which still gets the stipend:
+    // PUSH1 0 (4 times), PUSH1 7, PUSH2 0xca11, PUSH1 0, CALL, PUSH1 0, SSTORE
+    let mut env = TestEnv::new()
+        .with_code(CONTRACT, hex!("600060006000600060076200ca116000f1600055"))
+        .with_balance(CONTRACT, U256::from(10u8));
+    env.run_tx(TestTx::call(CONTRACT)).unwrap().assert_snapshot(
+        "
+        gas used: 72764
+        0x000000000000000000000000000000000000ca11: created with nonce 0, balance 7
+        0x000000000000000000000000000073656e646572: nonce 0 -> 1
+        0x0f572e5295c57f15886f9b263e2f6d2d6c7b5ec6: balance 10 -> 3
+        0x0f572e5295c57f15886f9b263e2f6d2d6c7b5ec6[0x0000000000000000000000000000000000000000000000000000000000000000]: 0x0 -> 0x1
+        ",
+    );
+}
+
+#[test]
+fn test_state_carries_over() {
+    // Increment slot 0: PUSH1 0, SLOAD, PUSH1 1, ADD, PUSH1 0, SSTORE
+    let mut env = TestEnv::new().with_code(CONTRACT, hex!("600054600101600055")).with_storage(
+        CONTRACT,
+        slot(0),
+        U256::from(5u8),
+    );
+    env.run_tx(TestTx::call(CONTRACT)).unwrap();
+    env.run_tx(TestTx::call(CONTRACT)).unwrap().assert_snapshot(
+        "
+        gas used: 26062
+        0x000000000000000000000000000073656e646572: nonce 1 -> 2
+        0x0f572e5295c57f15886f9b263e2f6d2d6c7b5ec6[0x0000000000000000000000000000000000000000000000000000000000000000]: 0x6 -> 0x7
+        ",
+    );
+    assert_eq!(get_storage(env.state(), &CONTRACT, &slot(0)), U256::from(7u8));
+}
+
+#[test]
+fn test_insufficient_balance() {
+    let mut env = TestEnv::new().with_balance(SENDER, U256::from(1u8));
+    let result = env.run_tx(TestTx::call(CONTRACT).value(U256::from(2u8)));
+    assert!(matches!(result, Err(EthereumException::InvalidBlock)));
+    let untouched = TestEnv::new().with_balance(SENDER, U256::from(1u8));
+    assert_eq!(state_root(env.state()), state_root(untouched.state()));
+}
+
+#[test]
+fn test_instruction_table() {
+    assert!(FRONTIER.ops().eq(Ops::all()));
+    assert!(InstructionTable::EMPTY.ops().next().is_none());
+
+    // A later fork adds, changes or removes opcodes, and inherits the rest.
+    let table = InstructionTable::EMPTY.with(Ops::ADD, arithmetic::sub);
+    assert!(table.ops().eq([Ops::ADD]));
+    let table = FRONTIER.with(Ops::ADD, arithmetic::sub).without(Ops::SELFDESTRUCT);
+    assert!(table.get(Ops::ADD).is_some());
+    assert!(table.get(Ops::SELFDESTRUCT).is_none());
+    assert!(table.ops().eq(Ops::all().filter(|op| *op != Ops::SELFDESTRUCT)));
+}
@@ -0,0 +1,61 @@
+use execution_specs_rs::ethereum::{
+    access_list::{decode_access_list, AccessList, AccessListEntry},
+    exceptions::EthereumException,
+    rlp,
+};
+use serde_json::json;
+
+fn list() -> AccessList {
+    vec![
+        AccessListEntry {
+            address: [0x11; 20],
+            storage_keys: vec![[0; 32], [0x22; 32]],
+        },
+        AccessListEntry {
+            address: [0x33; 20],
+            storage_keys: vec![],
+        },
+    ]
+}
+
+#[test]
+fn test_rlp() {
+    let entry = AccessListEntry {
+        address: [0x11; 20],
+        storage_keys: vec![[0x22; 32]],
+    };
+    let encoded = rlp::encode(&vec![entry.clone()]);
+    // [[address, [key]]]: 21 bytes of address and 34 of keys.
+    assert_eq!(&encoded[..3], &[0xf8, 0x38, 0xf7]);
+    assert_eq!(decode_access_list(&encoded).unwrap(), vec![entry]);
+
+    let encoded = rlp::encode(&list());
+    assert_eq!(decode_access_list(&encoded).unwrap(), list());
+    assert_eq!(decode_access_list(&rlp::encode(&AccessList::new())).unwrap(), vec![]);
+
+    // An address of 19 bytes.
+    let short = rlp::encode(&vec![(&[0x11u8; 19], &Vec::<[u8; 32]>::new())]);
+    assert!(matches!(
+        decode_access_list(&short),
+        Err(EthereumException::RLPDecodingError)
+    ));
+}
+
+#[test]
+fn test_json() {
+    let value = json!([
+        {
+            "address": format!("0x{}", "11".repeat(20)),
+            "storageKeys": [format!("0x{}", "00".repeat(32)), format!("0x{}", "22".repeat(32))],
+        },
+        { "address": format!("0x{}", "33".repeat(20)), "storageKeys": [] },
+    ]);
+    assert_eq!(serde_json::to_value(list()).unwrap(), value);
+    assert_eq!(serde_json::from_value::<AccessList>(value.clone()).unwrap(), list());
+    assert_eq!(
+        list().iter().map(AccessListEntry::to_json).collect::<Vec<_>>(),
+        value.as_array().unwrap().clone()
+    );
+
+    assert!(serde_json::from_value::<AccessList>(json!([{ "storageKeys": [] }])).is_err());
+}
@@ -0,0 +1,73 @@
+use std::collections::HashMap;
+
+use execution_specs_rs::ethereum::{
+    backend::{CacheStats, CachedBackend, StateBackend},
+    base_types::Bytes,
+    frontier::trie::{dummy_root, root, trie_get, trie_set, Trie},
+};
+
+fn key(n: u8) -> Bytes {
+    vec![n].into()
+}
+
+#[test]
+fn test_cache_hits_and_misses() {
+    let mut backend = HashMap::new();
+    backend.insert(key(1), key(10));
+    let cached = CachedBackend::new(backend, 2);
+
+    assert_eq!(cached.get(&key(1)), Some(key(10)));
+    assert_eq!(cached.get(&key(1)), Some(key(10)));
+    // Absent entries are cached too.
+    assert_eq!(cached.get(&key(2)), None);
+    assert_eq!(cached.get(&key(2)), None);
+    assert_eq!(cached.stats(), CacheStats { hits: 2, misses: 2 });
+}
+
+#[test]
+fn test_cache_evicts_least_recently_used() {
+    let backend: HashMap<_, _> = (1..=3).map(|n| (key(n), key(10 * n))).collect();
+    let cached = CachedBackend::new(backend, 2);
+
+    cached.get(&key(1));
+    cached.get(&key(2));
+    cached.get(&key(1));
+    // Evicts 2, which was used longest ago.
+    cached.get(&key(3));
+    cached.get(&key(1));
+    assert_eq!(cached.stats(), CacheStats { hits: 2, misses: 3 });
+    cached.get(&key(2));
+    assert_eq!(cached.stats(), CacheStats { hits: 2, misses: 4 });
+}
+
+#[test]
+fn test_cache_writes_through() {
+    let mut cached = CachedBackend::new(HashMap::new(), 8);
+    cached.set(key(1), key(10));
+    cached.set(key(2), key(20));
+    cached.delete(&key(2));
+    assert_eq!(cached.get(&key(1)), Some(key(10)));
+    assert_eq!(cached.get(&key(2)), None);
+    assert_eq!(cached.stats(), CacheStats { hits: 2, misses: 0 });
+
+    let backend = cached.into_inner();
+    assert_eq!(backend.len(), 1);
+    assert_eq!(backend[&key(1)], key(10));
+}
+
+#[test]
+fn test_cached_trie() {
+    let mut plain = Trie::<Bytes, Bytes>::new(false, Bytes::default());
+    let mut cached = Trie::with_backend(false, Bytes::default(), CachedBackend::new(HashMap::new(), 4));
+    for n in 0..20 {
+        trie_set(&mut plain, key(n), key(n + 1));
+        trie_set(&mut cached, key(n), key(n + 1));
+    }
+    trie_set(&mut cached, key(3), Bytes::default());
+    trie_set(&mut plain, key(3), Bytes::default());
+
+    assert_eq!(trie_get(&cached, &key(19)), key(20));
+    assert_eq!(trie_get(&cached, &key(3)), Bytes::default());
+    assert_eq!(cached.data.stats().hits, 2);
+    assert_eq!(root(&cached, dummy_root), root(&plain, dummy_root));
+}
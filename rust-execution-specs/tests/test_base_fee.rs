@@ -0,0 +1,126 @@
+use execution_specs_rs::ethereum::{
+    base_fee::{
+        calculate_base_fee_per_gas, calculate_blob_gas_price, calculate_excess_blob_gas, fake_exponential,
+        BaseFeeSchedule, BlobFeeSchedule, CANCUN, GAS_PER_BLOB,
+    },
+    base_types::Uint,
+};
+
+const GWEI: u64 = 1_000_000_000;
+
+/// London, EIP-1559.
+const LONDON: BaseFeeSchedule = BaseFeeSchedule {
+    elasticity_multiplier: 2,
+    base_fee_max_change_denominator: 8,
+};
+
+/// Prague, EIP-7691.
+const PRAGUE: BlobFeeSchedule = BlobFeeSchedule {
+    target_blob_gas_per_block: 6 * GAS_PER_BLOB,
+    base_fee_update_fraction: 5_007_716,
+    ..CANCUN
+};
+
+/// The base fee after a parent with a gas limit of 30 million.
+fn next_base_fee(schedule: &BaseFeeSchedule, gas_used: u64, base_fee: u64) -> Uint {
+    calculate_base_fee_per_gas(
+        schedule,
+        &Uint::from(30_000_000u32),
+        &Uint::from(gas_used),
+        &Uint::from(base_fee),
+    )
+}
+
+#[test]
+fn test_base_fee() {
+    // At the target of 15 million the base fee stays, and moves by at most
+    // an eighth either way.
+    assert_eq!(next_base_fee(&LONDON, 15_000_000, GWEI), Uint::from(GWEI));
+    assert_eq!(next_base_fee(&LONDON, 30_000_000, GWEI), Uint::from(1_125_000_000u32));
+    assert_eq!(next_base_fee(&LONDON, 0, GWEI), Uint::from(875_000_000u32));
+    assert_eq!(next_base_fee(&LONDON, 22_500_000, GWEI), Uint::from(1_062_500_000u32));
+}
+
+#[test]
+fn test_base_fee_rounding() {
+    // The change is rounded down, but a block over its target raises the
+    // base fee by at least one wei.
+    assert_eq!(next_base_fee(&LONDON, 30_000_000, 15), Uint::from(16u8));
+    assert_eq!(next_base_fee(&LONDON, 0, 15), Uint::from(14u8));
+    assert_eq!(next_base_fee(&LONDON, 15_000_001, 100), Uint::from(101u8));
+    assert_eq!(next_base_fee(&LONDON, 14_999_999, 100), Uint::from(100u8));
+    assert_eq!(next_base_fee(&LONDON, 0, 7), Uint::from(7u8));
+
+    // A zero base fee only rises.
+    assert_eq!(next_base_fee(&LONDON, 0, 0), Uint::from(0u8));
+    assert_eq!(next_base_fee(&LONDON, 30_000_000, 0), Uint::from(1u8));
+
+    // The target of an odd gas limit is rounded down.
+    let base_fee = calculate_base_fee_per_gas(
+        &LONDON,
+        &Uint::from(30_000_001u32),
+        &Uint::from(15_000_000u32),
+        &Uint::from(GWEI),
+    );
+    assert_eq!(base_fee, Uint::from(GWEI));
+}
+
+#[test]
+fn test_base_fee_parameters() {
+    // A target of a quarter of the gas limit lets a full block raise the
+    // base fee by three eighths.
+    let elastic = BaseFeeSchedule {
+        elasticity_multiplier: 4,
+        ..LONDON
+    };
+    assert_eq!(next_base_fee(&elastic, 7_500_000, GWEI), Uint::from(GWEI));
+    assert_eq!(next_base_fee(&elastic, 30_000_000, GWEI), Uint::from(1_375_000_000u32));
+    assert_eq!(next_base_fee(&elastic, 0, GWEI), Uint::from(875_000_000u32));
+
+    let slow = BaseFeeSchedule {
+        base_fee_max_change_denominator: 50,
+        ..LONDON
+    };
+    assert_eq!(next_base_fee(&slow, 30_000_000, GWEI), Uint::from(1_020_000_000u32));
+    assert_eq!(next_base_fee(&slow, 30_000_000, 49), Uint::from(50u8));
+}
+
+#[test]
+fn test_excess_blob_gas() {
+    let excess = |schedule, excess_blob_gas: u64, blobs: u64| {
+        calculate_excess_blob_gas(
+            schedule,
+            &Uint::from(excess_blob_gas),
+            &Uint::from(blobs * GAS_PER_BLOB),
+        )
+    };
+    assert_eq!(excess(&CANCUN, 0, 2), Uint::from(0u8));
+    assert_eq!(excess(&CANCUN, 0, 3), Uint::from(0u8));
+    assert_eq!(excess(&CANCUN, 0, 6), Uint::from(3 * GAS_PER_BLOB));
+    assert_eq!(excess(&CANCUN, GAS_PER_BLOB, 2), Uint::from(0u8));
+    assert_eq!(excess(&CANCUN, 2 * GAS_PER_BLOB, 2), Uint::from(GAS_PER_BLOB));
+    // Prague doubled the target.
+    assert_eq!(excess(&PRAGUE, 0, 6), Uint::from(0u8));
+    assert_eq!(excess(&PRAGUE, 0, 9), Uint::from(3 * GAS_PER_BLOB));
+}
+
+#[test]
+fn test_blob_gas_price() {
+    let price = |schedule, excess_blob_gas: u64| calculate_blob_gas_price(schedule, &Uint::from(excess_blob_gas));
+    assert_eq!(price(&CANCUN, 0), Uint::from(1u8));
+    assert_eq!(price(&CANCUN, 3_338_476), Uint::from(2u8));
+    // e and e ** 10, rounded down.
+    assert_eq!(price(&CANCUN, 3_338_477), Uint::from(2u8));
+    assert_eq!(price(&CANCUN, 33_384_770), Uint::from(22026u32));
+    assert_eq!(price(&PRAGUE, 50_077_160), Uint::from(22026u32));
+    // Each term of the series is rounded down, so it falls short of e ** 100
+    // (268811714181613544841...).
+    assert_eq!(
+        fake_exponential(&Uint::from(1u8), &Uint::from(100u8), &Uint::from(1u8)),
+        "26881063886385033622813949849277693891518911".parse::<Uint>().unwrap()
+    );
+    assert_eq!(
+        fake_exponential(&Uint::from(GWEI), &Uint::from(1u8), &Uint::from(8u8)),
+        Uint::from(1_133_148_452u32)
+    );
+}
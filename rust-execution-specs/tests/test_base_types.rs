@@ -0,0 +1,115 @@
+use execution_specs_rs::ethereum::{
+    base_types::{
+        to_fixed_bytes, Bytes0, Bytes256, Bytes4, Bytes48, Bytes8, Bytes96, U256Ext, Uint, U256, U256_MAX_VALUE,
+    },
+    exceptions::EthereumException,
+    rlp::{self, decode_to_bytes},
+    utils::hexadecimal::{hex, hex_to_bytes48, hex_to_bytes96},
+};
+
+fn pow2(exponent: usize) -> U256 {
+    U256::from(1u8) << exponent
+}
+
+#[test]
+fn test_wrapping_arithmetic() {
+    let max = U256_MAX_VALUE();
+    let one = U256::from(1u8);
+    assert_eq!(max.wrapping_add(&one), U256::from(0u8));
+    assert_eq!(max.wrapping_add(&max), &max - 1u8);
+    assert_eq!(U256::from(0u8).wrapping_sub(&one), max);
+    assert_eq!(U256::from(3u8).wrapping_sub(&U256::from(5u8)), &max - 1u8);
+    assert_eq!(U256::from(5u8).wrapping_sub(&U256::from(3u8)), U256::from(2u8));
+    assert_eq!(max.wrapping_mul(&max), one);
+    assert_eq!(pow2(128).wrapping_mul(&pow2(128)), U256::from(0u8));
+    assert_eq!(pow2(128).wrapping_mul(&pow2(127)), pow2(255));
+}
+
+#[test]
+fn test_checked_pow() {
+    let pow = |base: U256, exponent: u32| base.checked_pow(&U256::from(exponent));
+    assert_eq!(pow(U256::from(2u8), 255), Some(pow2(255)));
+    assert_eq!(pow(U256::from(2u8), 256), None);
+    assert_eq!(pow(U256::from(16u8), 63), Some(pow2(252)));
+    assert_eq!(pow(U256::from(16u8), 64), None);
+    assert_eq!(pow(U256_MAX_VALUE(), 1), Some(U256_MAX_VALUE()));
+    assert_eq!(pow(U256_MAX_VALUE(), 2), None);
+    assert_eq!(pow(U256::from(7u8), 0), Some(U256::from(1u8)));
+    // Zero and one to any power fit.
+    assert_eq!(U256::from(0u8).checked_pow(&U256_MAX_VALUE()), Some(U256::from(0u8)));
+    assert_eq!(U256::from(1u8).checked_pow(&U256_MAX_VALUE()), Some(U256::from(1u8)));
+    assert_eq!(pow(U256::from(0u8), 0), Some(U256::from(1u8)));
+}
+
+#[test]
+fn test_bytes() {
+    let mut bytes = [0; 32];
+    bytes[0] = 0x80;
+    bytes[31] = 1;
+    let value = U256::from_be_bytes(&bytes).unwrap();
+    assert_eq!(value, pow2(255) + 1u8);
+    assert_eq!(value.to_be_bytes32(), bytes);
+    assert_eq!(U256::from_be_bytes(&[1, 0]).unwrap(), U256::from(256u16));
+    assert_eq!(U256::from_be_bytes(&[]).unwrap(), U256::from(0u8));
+    assert!(matches!(
+        U256::from_be_bytes(&[0; 33]),
+        Err(EthereumException::ValueError)
+    ));
+}
+
+#[test]
+fn test_conversions() {
+    let big = pow2(300) + pow2(64) + 5u8;
+    // Truncation keeps the low bits.
+    assert_eq!(big.low_u256(), pow2(64) + 5u8);
+    assert_eq!(big.low_u64(), 5);
+    assert_eq!(Uint::from(0u8).low_u64(), 0);
+    assert_eq!(U256_MAX_VALUE().low_u256(), U256_MAX_VALUE());
+    // Saturation keeps the order.
+    assert_eq!(big.saturating_to_u64(), u64::MAX);
+    assert_eq!(big.saturating_to_usize(), usize::MAX);
+    assert_eq!(Uint::from(u64::MAX).saturating_to_u64(), u64::MAX);
+    assert_eq!(Uint::from(42u8).saturating_to_usize(), 42);
+}
+
+#[test]
+fn test_fixed_bytes() {
+    let commitment: Bytes48 = to_fixed_bytes(&[0xc0; 48]).unwrap();
+    let signature: Bytes96 = std::array::from_fn(|i| i as u8);
+    assert!(matches!(
+        to_fixed_bytes::<48>(&[0; 47]),
+        Err(EthereumException::ValueError)
+    ));
+    assert!(matches!(
+        to_fixed_bytes::<4>(&[0; 5]),
+        Err(EthereumException::ValueError)
+    ));
+    let empty: Bytes0 = to_fixed_bytes(&[]).unwrap();
+    assert_eq!(rlp::encode(&empty)[..], [0x80]);
+
+    // Byte arrays encode as byte strings, with a long length past 55 bytes.
+    let encoded = rlp::encode(&commitment);
+    assert_eq!(encoded[..2], [0xb0, 0xc0]);
+    assert_eq!(
+        to_fixed_bytes::<48>(decode_to_bytes(&encoded).unwrap()).unwrap(),
+        commitment
+    );
+    let encoded = rlp::encode(&signature);
+    assert_eq!(encoded[..3], [0xb8, 96, 0]);
+    assert_eq!(
+        to_fixed_bytes::<96>(decode_to_bytes(&encoded).unwrap()).unwrap(),
+        signature
+    );
+    let bloom: Bytes256 = [0; 256];
+    assert_eq!(rlp::encode(&bloom)[..3], [0xb9, 1, 0]);
+    let selector: Bytes4 = [0xff; 4];
+    assert_eq!(&*rlp::encode(&selector), [0x84, 0xff, 0xff, 0xff, 0xff]);
+    let nonce: Bytes8 = [0; 8];
+    assert_eq!(&*rlp::encode(&nonce), [0x88, 0, 0, 0, 0, 0, 0, 0, 0]);
+
+    assert_eq!(hex_to_bytes48(&hex(&commitment)).unwrap(), commitment);
+    assert_eq!(hex_to_bytes96(&hex(&signature)).unwrap(), signature);
+    // Short hex strings are padded on the left, long ones refused.
+    assert_eq!(hex_to_bytes48("0x01").unwrap()[47], 1);
+    assert!(hex_to_bytes48(&hex(&[0; 49])).is_err());
+}
@@ -0,0 +1,118 @@
+use std::collections::BTreeMap;
+
+use execution_specs_rs::ethereum::{
+    base_types::{Uint, U256},
+    experimental::binary_trie::{
+        account_key, root, state_root, state_trie, storage_key, trie_get, trie_proof, trie_set,
+        verify_proof, BinaryTrie, EMPTY_ROOT,
+    },
+    frontier::{
+        fork_types::{keccak256, Account},
+        state::{self, account_proof, set_account, set_storage, State},
+    },
+};
+
+fn slot(n: u8) -> [u8; 32] {
+    let mut key = [0; 32];
+    key[31] = n;
+    key
+}
+
+fn state() -> State {
+    let mut state = State::default();
+    for i in 1..=20u8 {
+        set_account(
+            &mut state,
+            [i; 20],
+            Some(Account {
+                nonce: Uint::from(i),
+                balance: U256::from(i) * 1000u32,
+                code: vec![i; i as usize].into(),
+            }),
+        );
+    }
+    for i in 1..=10u8 {
+        set_storage(&mut state, [1; 20], &slot(i), U256::from(i));
+    }
+    state
+}
+
+#[test]
+fn test_roots() {
+    let mut trie = BinaryTrie::new();
+    assert_eq!(root(&trie), EMPTY_ROOT);
+
+    // A single entry is a leaf.
+    trie_set(&mut trie, [1; 32], vec![0xaa].into());
+    let mut leaf = vec![0x00];
+    leaf.extend([1; 32]);
+    leaf.extend(keccak256(&[0xaa]));
+    assert_eq!(root(&trie), keccak256(&leaf));
+
+    // Keys differing in their first bit are the children of the root.
+    let mut other = [1; 32];
+    other[0] = 0x81;
+    trie_set(&mut trie, other, vec![0xbb].into());
+    let mut other_leaf = vec![0x00];
+    other_leaf.extend(other);
+    other_leaf.extend(keccak256(&[0xbb]));
+    let internal = [&[0x01][..], &keccak256(&leaf), &keccak256(&other_leaf)].concat();
+    assert_eq!(root(&trie), keccak256(&internal));
+
+    // Setting an empty value deletes.
+    trie_set(&mut trie, other, vec![].into());
+    assert_eq!(root(&trie), keccak256(&leaf));
+    assert!(trie_get(&trie, &other).is_empty());
+}
+
+#[test]
+fn test_backends_agree() {
+    let trie = state_trie(&state());
+    let mut ordered = BinaryTrie::with_backend(BTreeMap::new());
+    for (key, value) in trie.data.iter() {
+        trie_set(&mut ordered, *key, value.clone());
+    }
+    assert_eq!(root(&ordered), root(&trie));
+}
+
+#[test]
+fn test_proofs() {
+    let state = state();
+    let trie = state_trie(&state);
+    let root = state_root(&state);
+
+    let key = storage_key(&[1; 20], &slot(3));
+    let value = trie_get(&trie, &key);
+    let proof = trie_proof(&trie, &key);
+    assert!(verify_proof(&root, &key, &value, &proof));
+    assert!(!verify_proof(&root, &key, &[0x04], &proof));
+    assert!(!verify_proof(&root, &key, &[], &proof));
+
+    // Absent keys, ending at another leaf or an empty subtree.
+    for address in 0xe0..=0xffu8 {
+        let key = account_key(&[address; 20]);
+        let proof = trie_proof(&trie, &key);
+        assert!(verify_proof(&root, &key, &[], &proof));
+        assert!(!verify_proof(&root, &key, &[0x01], &proof));
+    }
+
+    let mut bad = proof.clone();
+    bad.siblings.pop();
+    assert!(!verify_proof(&root, &key, &value, &bad));
+}
+
+#[test]
+fn test_proof_sizes() {
+    // Twenty accounts need a handful of 32 byte siblings, where the
+    // hexary proof carries whole branch nodes.
+    let state = state();
+    let binary = trie_proof(&state_trie(&state), &account_key(&[5; 20]));
+    let hexary: usize = account_proof(&state, &[5; 20]).iter().map(|node| node.len()).sum();
+    assert!(binary.size() < hexary);
+
+    // The storage of the account changes the root.
+    let mut changed = state.clone();
+    set_storage(&mut changed, [2; 20], &slot(1), U256::from(1u8));
+    assert_ne!(state_root(&changed), state_root(&state));
+    assert_ne!(state::state_root(&changed), state::state_root(&state));
+}
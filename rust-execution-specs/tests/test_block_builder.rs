@@ -0,0 +1,139 @@
+use execution_specs_rs::{
+    ethereum::{
+        base_types::{Uint, U256},
+        exceptions::{EthereumException, InvalidTransactionReason},
+        frontier::{
+            fork::{state_transition, BlockChain},
+            fork_types::Transaction,
+            state::{get_account, state_root, State},
+        },
+        genesis::{add_genesis_block, GenesisConfiguration},
+    },
+    ethereum_spec_tools::{
+        block_builder::{build_block, BlockAttributes, SkipReason, SkippedTransaction},
+        forks::HardFork,
+        test_fixtures::{initial_balances, TestAccount, ALICE, BOB},
+    },
+};
+
+const RECIPIENT: [u8; 20] = [0xcc; 20];
+const COINBASE: [u8; 20] = [0xbb; 20];
+
+fn transaction(account: &TestAccount, nonce: u8, gas_price: u8, gas: u32) -> Transaction {
+    let unsigned = Transaction {
+        nonce: U256::from(nonce),
+        gas_price: U256::from(gas_price),
+        gas: U256::from(gas),
+        to: Some(RECIPIENT),
+        value: U256::from(1u8),
+        data: vec![].into(),
+        v: U256::default(),
+        r: U256::default(),
+        s: U256::default(),
+    };
+    account.sign(&unsigned, None)
+}
+
+fn genesis_chain() -> BlockChain {
+    let mut chain = BlockChain {
+        blocks: vec![],
+        state: State::default(),
+        chain_id: 1,
+    };
+    let genesis = GenesisConfiguration {
+        chain_id: 1,
+        difficulty: Uint::from(0x20000u32),
+        gas_limit: Uint::from(3_141_592u32),
+        initial_balances: initial_balances(&[ALICE, BOB]),
+        ..Default::default()
+    };
+    add_genesis_block(HardFork::Frontier, &mut chain, genesis).unwrap();
+    chain
+}
+
+fn attributes() -> BlockAttributes {
+    BlockAttributes {
+        coinbase: COINBASE,
+        timestamp: U256::from(15u8),
+        gas_limit: Uint::from(3_141_592u32),
+        extra_data: b"builder".to_vec().into(),
+    }
+}
+
+#[test]
+fn test_build_block_order() {
+    let mut chain = genesis_chain();
+    let mut invalid = transaction(&ALICE, 2, 9, 21000);
+    invalid.s = U256::default();
+    let candidates = [
+        transaction(&ALICE, 1, 5, 21000),
+        transaction(&ALICE, 0, 1, 21000),
+        transaction(&BOB, 0, 3, 21000),
+        // Bob's nonce 1 is missing.
+        transaction(&BOB, 2, 10, 21000),
+        invalid,
+    ];
+    let built = build_block(&chain, &candidates, &attributes()).unwrap();
+
+    // Bob's first transaction pays more than Alice's, whose second waits
+    // for her first despite paying most.
+    assert_eq!(built.included, [2, 1, 0]);
+    assert_eq!(
+        built.skipped,
+        [
+            SkippedTransaction {
+                index: 3,
+                reason: SkipReason::Invalid(InvalidTransactionReason::NonceMismatch {
+                    expected: Uint::from(1u8),
+                    nonce: U256::from(2u8),
+                }),
+            },
+            SkippedTransaction {
+                index: 4,
+                reason: SkipReason::InvalidSignature,
+            },
+        ]
+    );
+    let header = &built.block.header;
+    assert_eq!(header.gas_used, Uint::from(3u32 * 21000));
+    assert_eq!(header.number, Uint::from(1u8));
+    assert_eq!(header.parent_hash, chain.blocks[0].header.hash());
+    assert_eq!(built.fee_summary.transaction_fees, U256::from(21000u32 * (3 + 1 + 5)));
+    assert_eq!(get_account(&built.state, &RECIPIENT).balance, U256::from(3u8));
+    assert_eq!(
+        get_account(&built.state, &COINBASE).balance,
+        built.fee_summary.coinbase_income()
+    );
+
+    // The block is valid on the chain, and leads to the same state.
+    state_transition(&mut chain, built.block.clone()).unwrap();
+    assert_eq!(state_root(&chain.state), header.state_root);
+    assert_eq!(state_root(&built.state), header.state_root);
+}
+
+#[test]
+fn test_build_block_gas() {
+    let chain = genesis_chain();
+    // Bob's transaction runs first, and leaves too little gas for Alice's.
+    let candidates = [transaction(&ALICE, 0, 2, 3_130_000), transaction(&BOB, 0, 3, 100_000)];
+    let built = build_block(&chain, &candidates, &attributes()).unwrap();
+    assert_eq!(built.included, [1]);
+    assert_eq!(
+        built.skipped,
+        [SkippedTransaction {
+            index: 0,
+            reason: SkipReason::GasAllowanceExceeded,
+        }]
+    );
+
+    // Alone, it fits.
+    let built = build_block(&chain, &candidates[..1], &attributes()).unwrap();
+    assert_eq!(built.included, [0]);
+
+    let mut attributes = attributes();
+    attributes.timestamp = U256::default();
+    assert!(matches!(
+        build_block(&chain, &candidates, &attributes),
+        Err(EthereumException::InvalidBlock)
+    ));
+}
@@ -0,0 +1,90 @@
+use execution_specs_rs::{
+    ethereum::{
+        base_types::{Uint, U256},
+        exceptions::EthereumException,
+        frontier::fork_types::{Block, Header, Transaction},
+        rlp::{self, Simple},
+    },
+    ethereum_spec_tools::{
+        block_rlp::{decode_block, decode_header},
+        forks::HardFork,
+        test_fixtures::ALICE,
+    },
+};
+
+fn header(number: u8) -> Header {
+    Header {
+        parent_hash: [number; 32],
+        ommers_hash: [0x1d; 32],
+        coinbase: [0xbb; 20],
+        state_root: [0x5a; 32],
+        transactions_root: [0x7a; 32],
+        receipt_root: [0x8a; 32],
+        bloom: [0; 256],
+        difficulty: Uint::from(0x20000u32),
+        number: Uint::from(number),
+        gas_limit: Uint::from(3_141_592u32),
+        gas_used: Uint::from(21000u32),
+        timestamp: U256::from(15u8 * number),
+        extra_data: b"extra".to_vec().into(),
+        mix_digest: [0x3c; 32],
+        nonce: [0x42; 8],
+    }
+}
+
+fn block() -> Block {
+    let transfer = Transaction {
+        nonce: U256::default(),
+        gas_price: U256::from(1u8),
+        gas: U256::from(21000u32),
+        to: Some([0xcc; 20]),
+        value: U256::from(1u8),
+        data: vec![].into(),
+        v: U256::default(),
+        r: U256::default(),
+        s: U256::default(),
+    };
+    Block {
+        header: header(2),
+        transactions: vec![ALICE.sign(&transfer, None)],
+        ommers: vec![header(1)],
+    }
+}
+
+/// `encoded` with a field appended to the header, as London does with the
+/// base fee.
+fn with_base_fee(encoded: &[u8]) -> Vec<u8> {
+    let Simple::Sequence(mut fields) = rlp::decode(encoded).unwrap() else {
+        panic!("a header is a list");
+    };
+    fields.push(Simple::Bytes(vec![7].into()));
+    rlp::encode(&Simple::Sequence(fields)).to_vec()
+}
+
+#[test]
+fn test_decode_header() {
+    let encoded = rlp::encode(&header(1));
+    let decoded = decode_header(&encoded, HardFork::Frontier).unwrap();
+    assert_eq!(rlp::encode(&decoded), encoded);
+    assert_eq!(decoded.nonce, [0x42; 8]);
+
+    assert!(matches!(
+        decode_header(&with_base_fee(&encoded), HardFork::Frontier),
+        Err(EthereumException::RLPDecodingError)
+    ));
+    assert!(decode_header(&encoded[..encoded.len() - 1], HardFork::Frontier).is_err());
+}
+
+#[test]
+fn test_decode_block() {
+    let encoded = rlp::encode(&block());
+    let decoded = decode_block(&encoded, HardFork::Frontier).unwrap();
+    assert_eq!(rlp::encode(&decoded), encoded);
+    assert_eq!(decoded.transactions.len(), 1);
+    assert_eq!(decoded.ommers[0].number, Uint::from(1u8));
+
+    assert!(matches!(
+        decode_block(&rlp::encode(&(header(2), Vec::<Header>::new())), HardFork::Frontier),
+        Err(EthereumException::RLPDecodingError)
+    ));
+}
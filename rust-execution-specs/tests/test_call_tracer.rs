@@ -0,0 +1,156 @@
+use execution_specs_rs::{
+    ethereum::{
+        base_types::U256,
+        frontier::{
+            fork_types::Account,
+            state::{set_account, State},
+            utils::address::compute_contract_address,
+            vm::Evm,
+        },
+        utils::hexadecimal::hex,
+    },
+    ethereum_spec_tools::evm_tools::call_tracer::{CallFrame, CallKind, CallTracer},
+};
+use serde_json::json;
+
+const CALLER: [u8; 20] = [0x20; 20];
+const RETURNER: [u8; 20] = [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x10];
+const LOOPER: [u8; 20] = [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x30];
+
+/// Calls `RETURNER` with 30000 gas, calls `LOOPER` with 100 gas,
+/// `CALLCODE`s `RETURNER`, creates an empty contract and self-destructs to
+/// `RETURNER`.
+const CALLER_CODE: [u8; 61] = [
+    0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x60, 0x10, 0x61, 0x75, 0x30, 0xf1, 0x50, // CALL
+    0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x60, 0x30, 0x60, 0x64, 0xf1, 0x50, // CALL
+    0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x60, 0x10, 0x61, 0x75, 0x30, 0xf2, 0x50, // CALLCODE
+    0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0xf0, 0x50, // CREATE
+    0x60, 0x10, 0xff, // SELFDESTRUCT
+];
+/// Returns the word 42.
+const RETURNER_CODE: [u8; 10] = [0x60, 0x2a, 0x60, 0x00, 0x52, 0x60, 0x20, 0x60, 0x00, 0xf3];
+/// Loops until it runs out of gas.
+const LOOPER_CODE: [u8; 4] = [0x5b, 0x60, 0x00, 0x56];
+
+fn state() -> State {
+    let mut state = State::default();
+    for (address, code, balance) in [
+        (CALLER, &CALLER_CODE[..], 5u8),
+        (RETURNER, &RETURNER_CODE[..], 0),
+        (LOOPER, &LOOPER_CODE[..], 0),
+    ] {
+        let account = Account {
+            code: code.into(),
+            balance: U256::from(balance),
+            ..Default::default()
+        };
+        set_account(&mut state, address, Some(account));
+    }
+    state
+}
+
+#[test]
+fn test_call_tree() {
+    let mut state = state();
+    let tracer = CallTracer::new();
+    let calls = tracer.calls();
+    Evm::call()
+        .to(CALLER)
+        .gas(100_000u32.into())
+        .tracer(Box::new(tracer))
+        .run(&mut state)
+        .unwrap();
+
+    let calls = calls.borrow();
+    let [call] = calls.as_slice() else {
+        panic!("expected one transaction, got {}", calls.len());
+    };
+    let kinds: Vec<_> = call.calls.iter().map(|frame| frame.kind).collect();
+    assert_eq!(
+        kinds,
+        [CallKind::Call, CallKind::Call, CallKind::CallCode, CallKind::Create, CallKind::SelfDestruct]
+    );
+
+    let trace = call.to_json();
+    assert_eq!(trace["type"], "CALL");
+    assert_eq!(trace["from"], hex(&[0; 20]));
+    assert_eq!(trace["to"], hex(&CALLER));
+    assert_eq!(trace["gas"], "0x186a0");
+    assert!(trace.get("output").is_none());
+    assert_eq!(
+        trace["calls"][0],
+        json!({
+            "type": "CALL",
+            "from": hex(&CALLER),
+            "to": hex(&RETURNER),
+            "value": "0x0",
+            "gas": "0x7530",
+            "gasUsed": "0x12",
+            "input": "0x",
+            "output": format!("0x{:064x}", 42),
+        })
+    );
+    // A failed call uses all its gas, and returns nothing.
+    assert_eq!(
+        trace["calls"][1],
+        json!({
+            "type": "CALL",
+            "from": hex(&CALLER),
+            "to": hex(&LOOPER),
+            "value": "0x0",
+            "gas": "0x64",
+            "gasUsed": "0x64",
+            "input": "0x",
+            "error": "out of gas",
+        })
+    );
+    // The code of `RETURNER` runs for `CALLER`.
+    assert_eq!(trace["calls"][2]["type"], "CALLCODE");
+    assert_eq!(trace["calls"][2]["to"], hex(&RETURNER));
+    assert_eq!(trace["calls"][3]["to"], hex(&compute_contract_address(&CALLER, &0u8.into())));
+    assert_eq!(
+        trace["calls"][4],
+        json!({
+            "type": "SELFDESTRUCT",
+            "from": hex(&CALLER),
+            "to": hex(&RETURNER),
+            "value": "0x5",
+            "gas": "0x0",
+            "gasUsed": "0x0",
+            "input": "0x",
+        })
+    );
+}
+
+/// Create a contract with `init_code` and trace it.
+fn trace_create(init_code: &[u8]) -> CallFrame {
+    let tracer = CallTracer::new();
+    let calls = tracer.calls();
+    Evm::call()
+        .create()
+        .data(init_code.to_vec().into())
+        .gas(100_000u32.into())
+        .tracer(Box::new(tracer))
+        .run(&mut State::default())
+        .unwrap();
+    let mut calls = calls.borrow_mut();
+    assert_eq!(calls.len(), 1);
+    calls.pop().unwrap()
+}
+
+#[test]
+fn test_create() {
+    // PUSH1 1, PUSH1 0, MSTORE8, PUSH1 1, PUSH1 0, RETURN: deploys 0x01.
+    let frame = trace_create(&[0x60, 0x01, 0x60, 0x00, 0x53, 0x60, 0x01, 0x60, 0x00, 0xf3]);
+    assert_eq!(frame.kind, CallKind::Create);
+    assert_eq!(frame.to, Some(compute_contract_address(&[0; 20], &0u8.into())));
+    assert_eq!(frame.to_json()["output"], "0x01");
+    // The code deposit is part of the gas used.
+    assert_eq!(frame.gas_used, U256::from(18u8 + 200));
+
+    let frame = trace_create(&[0xfe]);
+    assert_eq!(frame.error.as_deref(), Some("invalid opcode"));
+    assert_eq!(frame.to, None);
+    assert_eq!(frame.gas_used, U256::from(100_000u32));
+    assert!(frame.to_json().get("to").is_none());
+}
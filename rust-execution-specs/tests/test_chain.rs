@@ -0,0 +1,544 @@
+use execution_specs_rs::{
+    ethereum::{
+        base_types::{Uint, U256},
+        exceptions::EthereumException,
+        frontier::{
+            fork::{apply_body, calculate_block_difficulty, get_last_256_block_hashes, BlockChain},
+            fork_types::{Address, Block, Header, Transaction},
+            state::{get_account, state_root},
+            trie::persistent::NodeStore,
+            utils::address::compute_contract_address,
+            vm::BlockEnvironment,
+        },
+        rlp,
+        transaction_signature::sign_transaction,
+        utils::hexadecimal::hex,
+    },
+    ethereum_spec_tools::{
+        chain::{Chain, RETAINED_BLOCKS},
+        evm_tools::{state_test::SECRET_KEY, t8n::transaction_hash},
+        forks::{ForkCriteria, ForkSchedule, HardFork},
+        header_archive::HeaderArchive,
+        rpc_server::SpecRpc,
+    },
+};
+use hex_literal::hex;
+use serde_json::json;
+
+const RICH: [u8; 20] = [0xaa; 20];
+const COINBASE: [u8; 20] = [0xbb; 20];
+
+fn genesis(config: serde_json::Value) -> String {
+    json!({
+        "config": config,
+        "nonce": "0x0000000000000042",
+        "timestamp": "0x0",
+        "extraData": "0x",
+        "gasLimit": "0x2fefd8",
+        "difficulty": "0x20000",
+        "alloc": { "0xaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa": { "balance": "0x64" } }
+    })
+    .to_string()
+}
+
+/// The empty block after the head of `chain`, paying the reward to
+/// `COINBASE`.
+fn next_block(chain: &BlockChain) -> Block {
+    next_block_to(chain, COINBASE)
+}
+
+/// The empty block after the head of `chain`, paying the reward to
+/// `coinbase`.
+fn next_block_to(chain: &BlockChain, coinbase: Address) -> Block {
+    next_block_with(chain, coinbase, vec![])
+}
+
+/// The block after the head of `chain` including `ommers`, paying the
+/// reward to `coinbase`.
+fn next_block_with(chain: &BlockChain, coinbase: Address, ommers: Vec<Header>) -> Block {
+    next_block_with_transactions(chain, coinbase, ommers, vec![])
+}
+
+/// The block after the head of `chain` including `ommers` and
+/// `transactions`, paying the reward to `coinbase`.
+fn next_block_with_transactions(
+    chain: &BlockChain,
+    coinbase: Address,
+    ommers: Vec<Header>,
+    transactions: Vec<Transaction>,
+) -> Block {
+    let parent = chain.blocks.last().unwrap().header.clone();
+    let number = &parent.number + 1u8;
+    let timestamp = &parent.timestamp + 15u8;
+    let difficulty =
+        calculate_block_difficulty(&number, &timestamp, &parent.timestamp, &parent.difficulty).unwrap();
+    let mut state = chain.state.clone();
+    let block_env = BlockEnvironment {
+        block_hashes: get_last_256_block_hashes(chain).unwrap(),
+        coinbase,
+        number: number.clone(),
+        gas_limit: parent.gas_limit.clone(),
+        time: timestamp.clone(),
+        difficulty: difficulty.clone(),
+    };
+    let (gas_used, transactions_root, receipt_root, bloom, _, _, _) =
+        apply_body(&mut state, &block_env, &transactions, &ommers).unwrap();
+    let header = Header {
+        parent_hash: parent.hash(),
+        ommers_hash: rlp::rlp_hash(&ommers[..]),
+        coinbase,
+        state_root: state_root(&state),
+        transactions_root,
+        receipt_root,
+        bloom,
+        difficulty,
+        number,
+        gas_limit: parent.gas_limit,
+        gas_used,
+        timestamp,
+        extra_data: vec![].into(),
+        mix_digest: [0; 32],
+        nonce: [0; 8],
+    };
+    Block {
+        header,
+        transactions,
+        ommers,
+    }
+}
+
+#[test]
+fn test_schedule_from_config() {
+    let config = json!({
+        "chainId": 1,
+        "homesteadBlock": 5,
+        "daoForkBlock": 10,
+        "daoForkSupport": false,
+        "eip150Block": "0x14",
+        "terminalTotalDifficulty": "58750000000000000000000",
+        "shanghaiTime": 100
+    });
+    let schedule = ForkSchedule::from_config(&config).unwrap();
+    let names: Vec<_> = schedule.forks().iter().map(|fork| fork.name).collect();
+    assert_eq!(names, ["Frontier", "Homestead", "Tangerine Whistle", "Paris", "Shanghai"]);
+    assert_eq!(schedule.forks()[0].fork, Some(HardFork::Frontier));
+    assert_eq!(schedule.forks()[2].criteria, ForkCriteria::ByBlockNumber(20));
+    assert_eq!(schedule.forks()[3].criteria, ForkCriteria::ByTotalDifficulty(Uint::from(5875u32) * Uint::from(10u8).pow(19)));
+
+    let zero = Uint::default();
+    assert_eq!(schedule.active(4, 0, &zero), 0);
+    assert_eq!(schedule.active(5, 0, &zero), 1);
+    assert_eq!(schedule.active(25, 0, &zero), 2);
+    assert_eq!(schedule.active(25, 0, &(Uint::from(6u8) * Uint::from(10u8).pow(22))), 3);
+    assert_eq!(schedule.active(25, 100, &zero), 4);
+
+    assert!(ForkSchedule::from_config(&json!({ "homesteadBlock": "0xzz" })).is_err());
+}
+
+#[test]
+fn test_mainnet_schedule() {
+    let schedule = ForkSchedule::mainnet();
+    let fork = |number, timestamp| schedule.forks()[schedule.active(number, timestamp, &Uint::default())].name;
+    assert_eq!(fork(1_149_999, 0), "Frontier");
+    assert_eq!(fork(1_150_000, 0), "Homestead");
+    assert_eq!(fork(1_920_000, 0), "DAO Fork");
+    assert_eq!(fork(7_280_000, 0), "Petersburg");
+    assert_eq!(fork(15_050_000, 0), "Gray Glacier");
+    assert_eq!(fork(15_050_000, 1_681_338_455), "Shanghai");
+    assert_eq!(fork(15_050_000, 1_746_612_310), "Cancun");
+    assert_eq!(fork(15_050_000, 1_746_612_311), "Prague");
+    assert!(schedule.forks().iter().skip(1).all(|fork| fork.fork.is_none()));
+}
+
+#[test]
+fn test_fork_registry() {
+    assert!(matches!(HardFork::from_name("Frontier"), Ok(HardFork::Frontier)));
+    assert!(matches!(HardFork::from_name("frontier"), Ok(HardFork::Frontier)));
+    assert!(matches!(
+        HardFork::from_name("EIP150"),
+        Err(EthereumException::UnsupportedFork(name)) if name == "Tangerine Whistle"
+    ));
+    assert!(matches!(
+        HardFork::from_name("spuriousdragon"),
+        Err(EthereumException::UnsupportedFork(name)) if name == "Spurious Dragon"
+    ));
+    assert!(matches!(HardFork::from_name("Metropolis"), Err(EthereumException::ValueError)));
+    assert_eq!(HardFork::enabled(), [HardFork::Frontier]);
+}
+
+#[test]
+fn test_chain() {
+    let mut chain = Chain::from_genesis(&genesis(json!({ "chainId": 1, "homesteadBlock": 3 }))).unwrap();
+    assert_eq!(chain.fork().name, "Frontier");
+    assert_eq!(chain.total_difficulty(), &Uint::from(0x20000u32));
+
+    for _ in 0..2 {
+        let block = next_block(chain.blockchain());
+        assert_eq!(chain.fork_of(&block.header).name, "Frontier");
+        let difficulty = block.header.difficulty.clone();
+        let total_difficulty = chain.total_difficulty() + difficulty;
+        chain.apply_block(block).unwrap();
+        assert_eq!(chain.total_difficulty(), &total_difficulty);
+    }
+    assert_eq!(chain.blocks().len(), 3);
+    assert_eq!(get_account(chain.state(), &RICH).balance, U256::from(100u8));
+    assert_eq!(
+        get_account(chain.state(), &COINBASE).balance,
+        U256::from(10u8) * U256::from(10u8).pow(18)
+    );
+
+    // A block that doesn't follow the head is rejected before it can enter
+    // a fork.
+    let mut block = next_block(chain.blockchain());
+    block.header.parent_hash = chain.blocks()[1].hash();
+    assert!(matches!(chain.apply_block(block), Err(e) if matches!(e.root_cause(), EthereumException::InvalidBlock)));
+
+    // Homestead isn't implemented, so the chain stops before it.
+    let block = next_block(chain.blockchain());
+    assert_eq!(chain.fork_of(&block.header).name, "Homestead");
+    let error = chain.apply_block(block).unwrap_err();
+    assert!(matches!(error.root_cause(), EthereumException::UnsupportedFork(fork) if fork == "Homestead"));
+    assert_eq!(error.block_number(), Some(&Uint::from(3u8)));
+    assert_eq!(chain.blocks().len(), 3);
+    assert_eq!(chain.fork().name, "Frontier");
+}
+
+#[test]
+fn test_chain_ommers() {
+    let mut chain = Chain::from_genesis(&genesis(json!({ "chainId": 1 }))).unwrap();
+    // Mined on genesis alongside block 1, by others.
+    let uncle = next_block_to(chain.blockchain(), [0xcc; 20]).header;
+    let late_uncle = next_block_to(chain.blockchain(), [0xdd; 20]).header;
+    chain.apply_block(next_block(chain.blockchain())).unwrap();
+    chain
+        .apply_block(next_block_with(chain.blockchain(), COINBASE, vec![uncle.clone()]))
+        .unwrap();
+    let ether = U256::from(10u8).pow(18);
+    assert_eq!(get_account(chain.state(), &[0xcc; 20]).balance, U256::from(35u8) * &ether / 8u8);
+
+    // An ommer is included once.
+    let block = next_block_with(chain.blockchain(), COINBASE, vec![uncle]);
+    let error = chain.apply_block(block).unwrap_err();
+    assert!(matches!(error.root_cause(), EthereumException::InvalidBlock));
+    assert_eq!(error.block_number(), Some(&Uint::from(3u8)));
+    assert_eq!(error.to_string(), "block 3: invalid block");
+
+    // Block 7 can include an ommer of block 1, block 8 can't.
+    for _ in 3..7 {
+        chain.apply_block(next_block(chain.blockchain())).unwrap();
+    }
+    let mut late = chain.clone();
+    late.apply_block(next_block(late.blockchain())).unwrap();
+    let block = next_block_with(late.blockchain(), COINBASE, vec![late_uncle.clone()]);
+    assert!(matches!(late.apply_block(block), Err(e) if matches!(e.root_cause(), EthereumException::InvalidBlock)));
+    chain
+        .apply_block(next_block_with(chain.blockchain(), COINBASE, vec![late_uncle]))
+        .unwrap();
+    assert_eq!(get_account(chain.state(), &[0xdd; 20]).balance, U256::from(10u8) * &ether / 8u8);
+}
+
+#[test]
+fn test_chain_merge() {
+    // The merge activates after the block that brings the total difficulty
+    // to the terminal total difficulty.
+    let config = json!({ "chainId": 1, "terminalTotalDifficulty": 0x40000 });
+    let mut chain = Chain::from_genesis(&genesis(config)).unwrap();
+    let block = next_block(chain.blockchain());
+    chain.apply_block(block).unwrap();
+    let block = next_block(chain.blockchain());
+    let error = chain.apply_block(block).unwrap_err();
+    assert!(matches!(error.root_cause(), EthereumException::UnsupportedFork(fork) if fork == "Paris"));
+
+    let config = json!({ "chainId": 1, "terminalTotalDifficulty": 0 });
+    assert!(matches!(
+        Chain::from_genesis(&genesis(config)),
+        Err(EthereumException::UnsupportedFork(fork)) if fork == "Paris"
+    ));
+}
+
+#[test]
+fn test_chain_resume() {
+    let mut chain = Chain::from_genesis(&genesis(json!({ "chainId": 1 }))).unwrap();
+    for _ in 0..3 {
+        chain.apply_block(next_block(chain.blockchain())).unwrap();
+    }
+
+    // Only the last two blocks and the state after them, as after loading a
+    // state dump.
+    let resumed = BlockChain {
+        blocks: chain.blocks()[2..].to_vec(),
+        state: chain.state().clone(),
+        chain_id: 1,
+    };
+    let mut resumed = Chain::resume(resumed, chain.schedule().clone(), chain.total_difficulty().clone()).unwrap();
+    let block = next_block(chain.blockchain());
+    chain.apply_block(block.clone()).unwrap();
+    resumed.apply_block(block).unwrap();
+    assert_eq!(resumed.total_difficulty(), chain.total_difficulty());
+    assert_eq!(state_root(resumed.state()), state_root(chain.state()));
+
+    // The total difficulty of the missing blocks decides the merge.
+    let config = json!({ "chainId": 1, "terminalTotalDifficulty": 0x100000 });
+    let schedule = ForkSchedule::from_config(&config).unwrap();
+    let resume = |total_difficulty: u32| {
+        let chain = BlockChain {
+            blocks: chain.blocks()[3..].to_vec(),
+            state: chain.state().clone(),
+            chain_id: 1,
+        };
+        Chain::resume(chain, schedule.clone(), Uint::from(total_difficulty))
+    };
+    assert_eq!(resume(0x80000).unwrap().fork().name, "Frontier");
+    assert!(matches!(resume(0x200000), Err(EthereumException::UnsupportedFork(fork)) if fork == "Paris"));
+}
+
+#[test]
+fn test_chain_rewind() {
+    let mut chain = Chain::from_genesis(&genesis(json!({ "chainId": 1 }))).unwrap().with_reorg_depth(2);
+    for _ in 0..3 {
+        chain.apply_block(next_block(chain.blockchain())).unwrap();
+    }
+    assert_eq!(chain.rewindable_to(), 1);
+    assert!(matches!(chain.rewind(0), Err(EthereumException::ValueError)));
+    assert!(matches!(chain.rewind(4), Err(EthereumException::ValueError)));
+
+    // Switch to a branch leaving block 1, paying another coinbase.
+    let total_difficulty = chain.total_difficulty() - &chain.blocks()[3].header.difficulty;
+    let total_difficulty = total_difficulty - &chain.blocks()[2].header.difficulty;
+    chain.rewind(1).unwrap();
+    assert_eq!(chain.blocks().len(), 2);
+    assert_eq!(chain.total_difficulty(), &total_difficulty);
+    assert_eq!(chain.rewindable_to(), 1);
+    for _ in 0..2 {
+        chain.apply_block(next_block_to(chain.blockchain(), RICH)).unwrap();
+    }
+
+    let mut expected = Chain::from_genesis(&genesis(json!({ "chainId": 1 }))).unwrap();
+    expected.apply_block(next_block(expected.blockchain())).unwrap();
+    for _ in 0..2 {
+        expected.apply_block(next_block_to(expected.blockchain(), RICH)).unwrap();
+    }
+    assert_eq!(state_root(chain.state()), state_root(expected.state()));
+    assert_eq!(chain.total_difficulty(), expected.total_difficulty());
+    assert_eq!(rlp::rlp_hash(&chain.blocks()[3].header), rlp::rlp_hash(&expected.blocks()[3].header));
+
+    // Without a reorg depth, a chain can't rewind.
+    assert!(matches!(expected.rewind(2), Err(EthereumException::ValueError)));
+    assert!(expected.rewind(3).is_ok());
+}
+
+#[test]
+fn test_chain_rejected_block() {
+    for depth in [0, 2] {
+        let mut chain = Chain::from_genesis(&genesis(json!({ "chainId": 1 }))).unwrap().with_reorg_depth(depth);
+        chain.apply_block(next_block(chain.blockchain())).unwrap();
+        let (root, total_difficulty) = (state_root(chain.state()), chain.total_difficulty().clone());
+        let rewindable_to = chain.rewindable_to();
+
+        // Found wrong only once the block ran.
+        let block = next_block(chain.blockchain());
+        let mut invalid = block.clone();
+        invalid.header.state_root = [0; 32];
+        let error = chain.apply_block(invalid).unwrap_err();
+        assert!(matches!(error.root_cause(), EthereumException::InvalidBlock));
+        assert_eq!(state_root(chain.state()), root);
+        assert_eq!(chain.total_difficulty(), &total_difficulty);
+        assert_eq!(chain.blocks().len(), 2);
+        assert_eq!(chain.rewindable_to(), rewindable_to);
+
+        chain.apply_block(block.clone()).unwrap();
+        assert_eq!(state_root(chain.state()), block.header.state_root);
+    }
+}
+
+#[test]
+fn test_chain_pruning() {
+    let path = std::env::temp_dir().join(format!("chain-pruning-{}", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    let mut chain = Chain::from_genesis(&genesis(json!({ "chainId": 1 })))
+        .unwrap()
+        .with_reorg_depth(2)
+        .with_archive(HeaderArchive::open(&path).unwrap())
+        .unwrap();
+    let mut hashes = vec![chain.blocks()[0].hash()];
+    for _ in 0..260 {
+        let block = next_block(chain.blockchain());
+        hashes.push(block.hash());
+        chain.apply_block(block).unwrap();
+    }
+    // Blocks 6 to 260 are held, 4 and 5 kept for a rewind, and 0 to 3
+    // archived.
+    assert_eq!(chain.blocks().len(), RETAINED_BLOCKS);
+    assert_eq!(chain.blocks()[0].header.number, Uint::from(6u8));
+    assert_eq!(chain.archive().unwrap().range(), 0..4);
+    for number in [0, 3, 4, 5, 6, 260] {
+        assert_eq!(chain.header(number).unwrap().unwrap().hash(), hashes[number as usize]);
+    }
+    assert!(chain.header(261).unwrap().is_none());
+
+    // A rewind brings the pruned blocks back.
+    chain.rewind(258).unwrap();
+    assert_eq!(chain.blocks().len(), RETAINED_BLOCKS);
+    assert_eq!(chain.blocks()[0].header.number, Uint::from(4u8));
+    for _ in 0..3 {
+        chain.apply_block(next_block_to(chain.blockchain(), RICH)).unwrap();
+    }
+    assert_eq!(chain.blocks()[0].header.number, Uint::from(7u8));
+    assert_eq!(chain.archive().unwrap().range(), 0..5);
+    assert_eq!(chain.header(4).unwrap().unwrap().hash(), hashes[4]);
+
+    // A chain resumed with the archive can't leave a gap in it.
+    let resumed = BlockChain {
+        blocks: chain.blocks()[200..].to_vec(),
+        state: chain.state().clone(),
+        chain_id: 1,
+    };
+    let resumed = Chain::resume(resumed, chain.schedule().clone(), chain.total_difficulty().clone()).unwrap();
+    let archive = HeaderArchive::open(&path).unwrap();
+    assert!(matches!(resumed.with_archive(archive), Err(EthereumException::ValueError)));
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_transaction_index() {
+    let sender = hex!("a94f5374fce5edbc8e2a8697c15331677e6ebf0b");
+    let mut genesis: serde_json::Value = serde_json::from_str(&genesis(json!({ "chainId": 1 }))).unwrap();
+    genesis["alloc"][hex(&sender)] = json!({ "balance": "0xde0b6b3a7640000" });
+    let mut chain = Chain::from_genesis(&genesis.to_string()).unwrap().with_reorg_depth(2);
+    let tx = |nonce: u8, to: Option<Address>, data: &[u8]| {
+        let unsigned = Transaction {
+            nonce: U256::from(nonce),
+            gas_price: U256::from(1u8),
+            gas: U256::from(100_000u32),
+            to,
+            value: U256::from(5u8),
+            data: data.into(),
+            v: U256::default(),
+            r: U256::default(),
+            s: U256::default(),
+        };
+        sign_transaction(&unsigned, &SECRET_KEY, None).unwrap()
+    };
+    // A transfer, and a creation whose init code logs nothing with LOG0.
+    let transfer = tx(0, Some(RICH), &[]);
+    let creation = tx(1, None, &hex!("6000 6000 a0"));
+    let (transfer_hash, creation_hash) = (transaction_hash(&transfer), transaction_hash(&creation));
+    chain.apply_block(next_block(chain.blockchain())).unwrap();
+    let block = next_block_with_transactions(chain.blockchain(), COINBASE, vec![], vec![transfer, creation]);
+    let block_hash = block.hash();
+    chain.apply_block(block).unwrap();
+    chain.apply_block(next_block(chain.blockchain())).unwrap();
+
+    assert_eq!(chain.transactions().len(), 2);
+    let location = chain.transactions().location(&creation_hash).unwrap();
+    assert_eq!((location.block_number, location.index), (2, 1));
+
+    let transfer = chain.get_transaction_receipt(&transfer_hash).unwrap().unwrap();
+    let creation = chain.get_transaction_receipt(&creation_hash).unwrap().unwrap();
+    assert_eq!(transfer.transaction.from, sender);
+    assert_eq!(transfer.transaction.block_hash, block_hash);
+    assert_eq!(transfer.gas_used, Uint::from(21000u32));
+    assert_eq!(transfer.contract_address, None);
+    assert_eq!(&creation.receipt.cumulative_gas_used - &transfer.gas_used, creation.gas_used);
+    assert_eq!(creation.contract_address, Some(compute_contract_address(&sender, &Uint::from(1u8))));
+    assert_eq!(creation.first_log_index, 0);
+    let json = creation.to_json();
+    assert_eq!(json["transactionIndex"], "0x1");
+    assert_eq!(json["blockNumber"], "0x2");
+    assert_eq!(json["logs"][0]["address"], hex(&creation.contract_address.unwrap()));
+    assert_eq!(json["logs"][0]["logIndex"], "0x0");
+    assert!(chain.get_transaction_receipt(&[0; 32]).unwrap().is_none());
+
+    // The RPC server serves them.
+    let mut server = SpecRpc::from_chain(chain.clone());
+    let request = |method: &str| json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": [hex(&creation_hash)] });
+    assert_eq!(server.handle(&request("eth_getTransactionReceipt"))["result"], json);
+    let response = server.handle(&request("eth_getTransactionByHash"));
+    assert_eq!(response["result"]["from"], hex(&sender));
+    assert_eq!(response["result"]["to"], serde_json::Value::Null);
+    assert_eq!(server.handle(&request("eth_blockNumber"))["result"], "0x4");
+
+    // A resumed chain finds the transactions it holds, but hasn't executed
+    // them.
+    let resumed = Chain::resume(chain.blockchain().clone(), chain.schedule().clone(), chain.total_difficulty().clone())
+        .unwrap();
+    assert_eq!(resumed.get_transaction(&transfer_hash).unwrap().unwrap().location.index, 0);
+    assert!(resumed.get_transaction_receipt(&transfer_hash).unwrap().is_none());
+
+    // Rewinding takes the block's transactions off the chain.
+    chain.rewind(1).unwrap();
+    assert!(chain.transactions().is_empty());
+    assert!(chain.get_transaction(&transfer_hash).unwrap().is_none());
+}
+
+#[test]
+fn test_chain_state_history() {
+    let sender = hex!("a94f5374fce5edbc8e2a8697c15331677e6ebf0b");
+    let mut genesis: serde_json::Value = serde_json::from_str(&genesis(json!({ "chainId": 1 }))).unwrap();
+    genesis["alloc"][hex(&sender)] = json!({ "balance": "0xde0b6b3a7640000" });
+    let mut chain = Chain::from_genesis(&genesis.to_string())
+        .unwrap()
+        .with_reorg_depth(2)
+        .with_state_history(NodeStore::for_state())
+        .unwrap();
+    let tx = |nonce: u8, to: Option<Address>, data: &[u8]| {
+        let unsigned = Transaction {
+            nonce: U256::from(nonce),
+            gas_price: U256::from(1u8),
+            gas: U256::from(100_000u32),
+            to,
+            value: U256::from(5u8),
+            data: data.into(),
+            v: U256::default(),
+            r: U256::default(),
+            s: U256::default(),
+        };
+        sign_transaction(&unsigned, &SECRET_KEY, None).unwrap()
+    };
+    // A contract returning the balance of `RICH`, and a transfer to it in
+    // each block.
+    let creation = tx(
+        0,
+        None,
+        &hex!("601e 600c 6000 39 601e 6000 f3 73aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa 31 6000 52 6020 6000 f3"),
+    );
+    let contract = compute_contract_address(&sender, &Uint::from(0u8));
+    let blocks = [vec![creation, tx(1, Some(RICH), &[])], vec![tx(2, Some(RICH), &[])]];
+    for transactions in blocks {
+        let block = next_block_with_transactions(chain.blockchain(), COINBASE, vec![], transactions);
+        chain.apply_block(block).unwrap();
+    }
+
+    let balance = |chain: &Chain, number: u64| {
+        let state = chain.state_at(number).unwrap().unwrap();
+        state.get_account_optional(&RICH).unwrap().unwrap().balance
+    };
+    assert_eq!(balance(&chain, 0), U256::from(100u8));
+    assert_eq!(balance(&chain, 1), U256::from(105u8));
+    assert_eq!(balance(&chain, 2), U256::from(110u8));
+    assert!(chain.state_at(3).unwrap().is_none());
+
+    // The RPC server runs calls after past blocks, on their state.
+    let mut server = SpecRpc::from_chain(chain.clone());
+    let call = json!({ "to": hex(&contract) });
+    let mut request = |method: &str, params: serde_json::Value| {
+        server.handle(&json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params }))["result"].clone()
+    };
+    let word = |value: u8| format!("0x{value:064x}");
+    assert_eq!(request("eth_call", json!([call, "0x1"])), word(105));
+    assert_eq!(request("eth_call", json!([call, "earliest"])), "0x");
+    assert_eq!(request("eth_call", json!([call, "latest"])), word(110));
+    assert_eq!(request("eth_getBalance", json!([hex(&RICH), "0x1"])), "0x69");
+    assert_eq!(request("eth_getTransactionCount", json!([hex(&sender), "0x1"])), "0x2");
+
+    // Rewinding prunes the states of the blocks it goes back on.
+    chain.rewind(1).unwrap();
+    assert_eq!(balance(&chain, 1), U256::from(105u8));
+    let mut reference = Chain::from_genesis(&genesis.to_string())
+        .unwrap()
+        .with_state_history(NodeStore::for_state())
+        .unwrap();
+    reference.apply_block(chain.blocks()[1].clone()).unwrap();
+    assert_eq!(chain.state_history().unwrap().len(), reference.state_history().unwrap().len());
+    assert!(chain.state_at(2).unwrap().is_none());
+}
@@ -0,0 +1,102 @@
+use execution_specs_rs::ethereum_spec_tools::{
+    chain::Chain,
+    chain_info::{ChainInfo, Consensus, CLIQUE_DEFAULT_EPOCH},
+    chain_spec::ChainSpec,
+    forks::ForkSchedule,
+};
+use serde_json::json;
+
+fn genesis(config: serde_json::Value) -> serde_json::Value {
+    json!({
+        "config": config,
+        "nonce": "0x0000000000000042",
+        "timestamp": "0x0",
+        "extraData": "0x",
+        "gasLimit": "0x2fefd8",
+        "difficulty": "0x20000",
+        "alloc": {}
+    })
+}
+
+#[test]
+fn test_consensus_from_config() {
+    assert_eq!(
+        Consensus::from_config(&json!({ "chainId": 1 })).unwrap(),
+        Consensus::ProofOfWork
+    );
+    assert_eq!(
+        Consensus::from_config(&json!({ "clique": { "period": 15, "epoch": 30000 } })).unwrap(),
+        Consensus::ProofOfAuthority {
+            period: 15,
+            epoch: 30000
+        }
+    );
+    assert_eq!(
+        Consensus::from_config(&json!({ "clique": {} })).unwrap(),
+        Consensus::ProofOfAuthority {
+            period: 0,
+            epoch: CLIQUE_DEFAULT_EPOCH
+        }
+    );
+    assert!(Consensus::from_config(&json!({ "clique": { "period": "soon" } })).is_err());
+}
+
+#[test]
+fn test_chain_info() {
+    let chain = Chain::from_genesis(&genesis(json!({ "chainId": 1337, "homesteadBlock": 10 })).to_string()).unwrap();
+    let info = chain.info();
+    assert_eq!(info.chain_id, 1337);
+    assert_eq!(info.fork.name, "Frontier");
+    assert_eq!(info.consensus, Consensus::ProofOfWork);
+    assert_eq!(info.schedule.forks().len(), 2);
+    assert_eq!(
+        info.to_json(),
+        json!({
+            "chainId": 1337,
+            "fork": "Frontier",
+            "consensus": "PoW",
+            "forks": [{ "name": "Frontier", "block": 0 }, { "name": "Homestead", "block": 10 }],
+        })
+    );
+
+    let clique = json!({ "chainId": 5, "clique": { "period": 15, "epoch": 30000 } });
+    let info = Chain::from_genesis(&genesis(clique).to_string()).unwrap().info();
+    assert_eq!(info.to_json()["consensus"], "PoA");
+    assert_eq!(info.to_json()["clique"], json!({ "period": 15, "epoch": 30000 }));
+}
+
+#[test]
+fn test_chain_spec_info() {
+    let spec = json!({
+        "name": "devnet",
+        "chainId": 17000,
+        "genesis": genesis(json!({})),
+        "forks": {
+            "Paris": { "totalDifficulty": "0x0" },
+            "Shanghai": { "timestamp": 0 },
+        },
+    });
+    let info = ChainSpec::from_json(&spec.to_string()).unwrap().info();
+    // Paris and Shanghai are active at genesis.
+    assert_eq!(info.chain_id, 17000);
+    assert_eq!(info.fork.name, "Shanghai");
+    assert_eq!(info.consensus, Consensus::ProofOfStake);
+    assert_eq!(
+        info.to_json()["forks"][1],
+        json!({ "name": "Paris", "totalDifficulty": "0x0" })
+    );
+}
+
+#[test]
+fn test_merge_ends_other_consensus() {
+    let clique = Consensus::ProofOfAuthority { period: 5, epoch: 100 };
+    let mut schedule = ForkSchedule::holesky();
+    let paris = schedule.forks().iter().position(|fork| fork.name == "Paris").unwrap();
+    assert_eq!(ChainInfo::new(1, schedule.clone(), paris - 1, clique).consensus, clique);
+    assert_eq!(
+        ChainInfo::new(1, schedule.clone(), paris, clique).consensus,
+        Consensus::ProofOfStake
+    );
+    schedule = ForkSchedule::frontier();
+    assert_eq!(ChainInfo::new(1, schedule, 0, clique).consensus, clique);
+}
@@ -0,0 +1,190 @@
+use execution_specs_rs::{
+    ethereum::{
+        base_fee::{BaseFeeSchedule, BlobFeeSchedule, CANCUN, GAS_PER_BLOB},
+        base_types::{Uint, U256},
+        exceptions::EthereumException,
+    },
+    ethereum_spec_tools::{
+        chain::Chain,
+        chain_spec::{BlobSchedule, ChainSpec, FeeParams},
+        forks::{ForkCriteria, ForkSchedule},
+    },
+};
+use serde_json::{json, Value};
+
+fn genesis() -> Value {
+    json!({
+        "config": { "chainId": 1, "homesteadBlock": 1 },
+        "nonce": "0x0000000000000042",
+        "timestamp": "0x0",
+        "extraData": "0x",
+        "gasLimit": "0x2fefd8",
+        "difficulty": "0x20000",
+        "alloc": { "0xaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa": { "balance": "0x64" } }
+    })
+}
+
+fn names(schedule: &ForkSchedule) -> Vec<&str> {
+    schedule.forks().iter().map(|fork| fork.name).collect()
+}
+
+#[test]
+fn test_json_spec() {
+    let spec = json!({
+        "name": "devnet",
+        "chainId": 1337,
+        "genesis": genesis(),
+        "forks": {
+            "Shanghai": { "timestamp": 1_700_000_000 },
+            "TangerineWhistle": { "block": 10 },
+            "paris": { "totalDifficulty": "0x40000" },
+        },
+    });
+    let spec = ChainSpec::from_json(&spec.to_string()).unwrap();
+
+    assert_eq!(spec.name, "devnet");
+    assert_eq!(spec.genesis.chain_id, 1337);
+    // In mainnet's order, whatever the order given, and not the genesis'.
+    assert_eq!(names(&spec.schedule), ["Frontier", "Tangerine Whistle", "Paris", "Shanghai"]);
+    assert_eq!(spec.schedule.forks()[1].criteria, ForkCriteria::ByBlockNumber(10));
+    assert_eq!(spec.schedule.forks()[2].criteria, ForkCriteria::ByTotalDifficulty(Uint::from(0x40000u32)));
+    assert_eq!(spec.params, FeeParams::default());
+
+    let chain = Chain::from_spec(spec).unwrap();
+    assert_eq!(chain.blockchain().chain_id, 1337);
+    assert_eq!(chain.fork().name, "Frontier");
+}
+
+#[test]
+fn test_spec_without_forks() {
+    // The genesis' `config` schedules the forks.
+    let spec = ChainSpec::from_json(&json!({ "genesis": genesis() }).to_string()).unwrap();
+    assert_eq!(spec.name, "");
+    assert_eq!(names(&spec.schedule), ["Frontier", "Homestead"]);
+    assert_eq!(spec.schedule, ForkSchedule::from_config(&genesis()["config"]).unwrap());
+}
+
+#[test]
+fn test_toml_spec() {
+    let toml = format!(
+        r#"
+        name = "devnet"
+        genesis = {genesis}
+
+        [forks]
+        Homestead = {{ block = 0 }}
+        Cancun = {{ timestamp = 100 }}
+
+        [params]
+        elasticityMultiplier = 4
+        blobSchedule = {{ Cancun = {{ target = 4, max = 8, baseFeeUpdateFraction = 4000000 }} }}
+        "#,
+        // JSON objects are TOML inline tables once the keys are bare.
+        genesis = genesis().to_string().replace("\":", "\"=")
+    );
+    let spec = ChainSpec::from_toml(&toml).unwrap();
+
+    assert_eq!(spec.genesis.initial_balances[&[0xaa; 20]], U256::from(100u8));
+    assert_eq!(names(&spec.schedule), ["Frontier", "Homestead", "Cancun"]);
+    assert_eq!(spec.params.elasticity_multiplier, 4);
+    assert_eq!(spec.params.base_fee_max_change_denominator, 8);
+    let cancun = BlobSchedule {
+        target: 4,
+        max: 8,
+        base_fee_update_fraction: 4_000_000,
+    };
+    assert_eq!(spec.params.blob_schedule["Cancun"], cancun);
+    assert_eq!(spec.params.blob_schedule["Prague"], FeeParams::default().blob_schedule["Prague"]);
+}
+
+#[test]
+fn test_fee_schedules() {
+    // Mainnet's parameters are the forks' own.
+    let params = FeeParams::default();
+    assert_eq!(
+        params.base_fee_schedule(),
+        BaseFeeSchedule {
+            elasticity_multiplier: 2,
+            base_fee_max_change_denominator: 8,
+        }
+    );
+    assert_eq!(params.blob_schedule["Cancun"].fee_schedule(), CANCUN);
+    assert_eq!(
+        params.blob_schedule["Prague"].fee_schedule(),
+        BlobFeeSchedule {
+            target_blob_gas_per_block: 6 * GAS_PER_BLOB,
+            base_fee_update_fraction: 5_007_716,
+            ..CANCUN
+        }
+    );
+
+    let params = FeeParams {
+        elasticity_multiplier: 4,
+        ..FeeParams::default()
+    };
+    assert_eq!(params.base_fee_schedule().elasticity_multiplier, 4);
+}
+
+#[test]
+fn test_load_spec() {
+    let directory = std::env::temp_dir().join(format!("chain-spec-{}", std::process::id()));
+    std::fs::create_dir_all(&directory).unwrap();
+    std::fs::write(directory.join("genesis.json"), genesis().to_string()).unwrap();
+    let path = directory.join("devnet.toml");
+    std::fs::write(&path, "chainId = 5\ngenesis = \"genesis.json\"\n").unwrap();
+
+    let spec = ChainSpec::load(&path);
+    std::fs::remove_dir_all(&directory).unwrap();
+    let spec = spec.unwrap();
+    assert_eq!(spec.genesis.chain_id, 5);
+    assert_eq!(names(&spec.schedule), ["Frontier", "Homestead"]);
+
+    assert!(matches!(
+        ChainSpec::load(&directory.join("missing.json")),
+        Err(EthereumException::FileNotFound(_))
+    ));
+}
+
+#[test]
+fn test_invalid_specs() {
+    let spec_with_forks = |forks: Value| json!({ "genesis": genesis(), "forks": forks }).to_string();
+    assert!(matches!(
+        ChainSpec::from_json(&spec_with_forks(json!({ "Atlantis": { "block": 1 } }))),
+        Err(EthereumException::ValueError)
+    ));
+    for activation in [json!({ "height": 1 }), json!({ "block": 1, "timestamp": 1 }), json!(1)] {
+        assert!(matches!(
+            ChainSpec::from_json(&spec_with_forks(json!({ "Homestead": activation }))),
+            Err(EthereumException::JsonDecodeError(_))
+        ));
+    }
+
+    // A genesis file can only be named when loading from a file.
+    assert!(matches!(
+        ChainSpec::from_json(&json!({ "genesis": "genesis.json" }).to_string()),
+        Err(EthereumException::JsonDecodeError(_))
+    ));
+    let mut without_chain_id = genesis();
+    without_chain_id["config"] = json!({});
+    assert!(matches!(
+        ChainSpec::from_json(&json!({ "genesis": without_chain_id }).to_string()),
+        Err(EthereumException::JsonDecodeError(_))
+    ));
+}
+
+#[test]
+fn test_testnet_schedules() {
+    let fork = |schedule: &ForkSchedule, number, timestamp, total_difficulty: u64| {
+        schedule.forks()[schedule.active(number, timestamp, &Uint::from(total_difficulty))].name
+    };
+
+    let sepolia = ForkSchedule::sepolia();
+    assert_eq!(fork(&sepolia, 0, 0, 0), "London");
+    assert!(!names(&sepolia).contains(&"DAO Fork"));
+    assert_eq!(fork(&sepolia, 1_735_371, 0, 17_000_000_000_000_000), "Paris");
+    assert_eq!(fork(&sepolia, 3_000_000, 1_706_655_072, 17_000_000_000_000_000), "Cancun");
+
+    let holesky = ForkSchedule::holesky();
+    assert_eq!(fork(&holesky, 0, 1_695_902_100, 0), "Paris");
+    assert_eq!(fork(&holesky, 1, 1_740_434_112, 0), "Prague");
+}
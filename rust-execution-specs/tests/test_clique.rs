@@ -0,0 +1,233 @@
+use execution_specs_rs::ethereum::{
+    base_types::{Bytes32, Bytes8, Uint, U256},
+    clique::{
+        checkpoint_signers, seal_header, signer, Snapshot, DIFF_IN_TURN, DIFF_NO_TURN, EXTRA_SEAL, EXTRA_VANITY,
+        NONCE_AUTH, NONCE_DROP,
+    },
+    exceptions::EthereumException,
+    frontier::fork_types::{Address, Header},
+    rlp,
+};
+
+const PERIOD: u64 = 15;
+const KEYS: [Bytes32; 4] = [[1; 32], [2; 32], [3; 32], [4; 32]];
+
+fn header(number: u64, extra_data: Vec<u8>) -> Header {
+    Header {
+        parent_hash: [0; 32],
+        ommers_hash: rlp::rlp_hash::<[Header]>(&[]),
+        coinbase: [0; 20],
+        state_root: [0; 32],
+        transactions_root: [0; 32],
+        receipt_root: [0; 32],
+        bloom: [0; 256],
+        difficulty: Uint::from(1u8),
+        number: Uint::from(number),
+        gas_limit: Uint::from(8_000_000u64),
+        gas_used: Uint::from(0u8),
+        timestamp: U256::from(number * PERIOD),
+        extra_data: extra_data.into(),
+        mix_digest: [0; 32],
+        nonce: NONCE_DROP,
+    }
+}
+
+fn address(key: &Bytes32) -> Address {
+    let mut header = header(1, vec![0; EXTRA_VANITY + EXTRA_SEAL]);
+    seal_header(&mut header, key).unwrap();
+    signer(&header).unwrap()
+}
+
+fn extra_data(signers: &[Address]) -> Vec<u8> {
+    let mut extra_data = vec![0; EXTRA_VANITY];
+    for signer in signers {
+        extra_data.extend_from_slice(signer);
+    }
+    extra_data.extend_from_slice(&[0; EXTRA_SEAL]);
+    extra_data
+}
+
+/// A chain of the first three keys, with a checkpoint every `epoch` blocks.
+fn genesis(epoch: u64) -> (Header, Snapshot) {
+    let mut signers: Vec<_> = KEYS[..3].iter().map(address).collect();
+    signers.sort();
+    let genesis = header(0, extra_data(&signers));
+    let snapshot = Snapshot::from_genesis(&genesis, PERIOD, epoch).unwrap();
+    (genesis, snapshot)
+}
+
+/// The next block, signed by `key` with the difficulty of its turn.
+fn sign_next(snapshot: &Snapshot, parent: &Header, key: &Bytes32, vote: Option<(Address, Bytes8)>) -> Header {
+    let number = snapshot.number + 1;
+    let extra = match number % snapshot.epoch {
+        0 => extra_data(&snapshot.signers.iter().copied().collect::<Vec<_>>()),
+        _ => extra_data(&[]),
+    };
+    let mut next = header(number, extra);
+    next.parent_hash = parent.hash();
+    if let Some((coinbase, nonce)) = vote {
+        next.coinbase = coinbase;
+        next.nonce = nonce;
+    }
+    let difficulty = match snapshot.in_turn(number, &address(key)) {
+        true => DIFF_IN_TURN,
+        false => DIFF_NO_TURN,
+    };
+    next.difficulty = Uint::from(difficulty);
+    seal_header(&mut next, key).unwrap();
+    next
+}
+
+fn invalid<T>(result: Result<T, EthereumException>) -> bool {
+    matches!(result, Err(EthereumException::InvalidBlock))
+}
+
+/// The key of the signer in turn for the block after the snapshot.
+fn in_turn_key(snapshot: &Snapshot) -> &'static Bytes32 {
+    KEYS.iter()
+        .find(|key| snapshot.in_turn(snapshot.number + 1, &address(key)))
+        .unwrap()
+}
+
+#[test]
+fn test_genesis_signers() {
+    let (genesis, snapshot) = genesis(30_000);
+    assert_eq!(checkpoint_signers(&genesis).unwrap().len(), 3);
+    assert_eq!(snapshot.signers.len(), 3);
+    assert!(!snapshot.signers.contains(&address(&KEYS[3])));
+
+    // The signers must be whole addresses.
+    let mut broken = genesis.clone();
+    broken.extra_data = vec![0; EXTRA_VANITY + 19 + EXTRA_SEAL].into();
+    assert!(invalid(checkpoint_signers(&broken)));
+}
+
+#[test]
+fn test_seal() {
+    let (genesis, snapshot) = genesis(30_000);
+    let key = in_turn_key(&snapshot);
+    let block = sign_next(&snapshot, &genesis, key, None);
+    assert_eq!(signer(&block).unwrap(), address(key));
+    assert_eq!(snapshot.validate(&block, &genesis).unwrap(), address(key));
+    assert_eq!(block.difficulty, Uint::from(DIFF_IN_TURN));
+
+    // Anything signed changes the signer.
+    let mut tampered = block.clone();
+    tampered.gas_limit += 1u8;
+    assert_ne!(signer(&tampered).unwrap(), address(key));
+    assert!(snapshot.validate(&tampered, &genesis).is_err());
+}
+
+#[test]
+fn test_difficulty() {
+    let (genesis, snapshot) = genesis(30_000);
+    let in_turn = in_turn_key(&snapshot);
+    let out_of_turn = KEYS[..3].iter().find(|key| *key != in_turn).unwrap();
+    let block = sign_next(&snapshot, &genesis, out_of_turn, None);
+    assert_eq!(block.difficulty, Uint::from(DIFF_NO_TURN));
+    assert!(snapshot.validate(&block, &genesis).is_ok());
+
+    let mut claimed = block.clone();
+    claimed.difficulty = Uint::from(DIFF_IN_TURN);
+    seal_header(&mut claimed, out_of_turn).unwrap();
+    assert!(invalid(snapshot.validate(&claimed, &genesis)));
+}
+
+#[test]
+fn test_unauthorized_and_recent_signers() {
+    let (genesis, mut snapshot) = genesis(30_000);
+    let outsider = sign_next(&snapshot, &genesis, &KEYS[3], None);
+    assert!(invalid(snapshot.validate(&outsider, &genesis)));
+
+    let key = &KEYS[0];
+    let block = sign_next(&snapshot, &genesis, key, None);
+    snapshot.apply(&block, &genesis).unwrap();
+    // Of three signers, one may sign only one of two consecutive blocks.
+    let again = sign_next(&snapshot, &block, key, None);
+    assert!(invalid(snapshot.validate(&again, &block)));
+    let other = sign_next(&snapshot, &block, &KEYS[1], None);
+    snapshot.apply(&other, &block).unwrap();
+    let later = sign_next(&snapshot, &other, key, None);
+    assert!(snapshot.validate(&later, &other).is_ok());
+}
+
+#[test]
+fn test_timestamp() {
+    let (genesis, snapshot) = genesis(30_000);
+    let key = in_turn_key(&snapshot);
+    let mut early = sign_next(&snapshot, &genesis, key, None);
+    early.timestamp = U256::from(PERIOD - 1);
+    seal_header(&mut early, key).unwrap();
+    assert!(invalid(snapshot.validate(&early, &genesis)));
+}
+
+#[test]
+fn test_votes() {
+    let (genesis, mut snapshot) = genesis(30_000);
+    let candidate = address(&KEYS[3]);
+    let mut parent = genesis;
+
+    // One vote of three isn't enough.
+    let block = sign_next(&snapshot, &parent, &KEYS[0], Some((candidate, NONCE_AUTH)));
+    snapshot.apply(&block, &parent).unwrap();
+    parent = block;
+    assert!(!snapshot.signers.contains(&candidate));
+    assert_eq!(snapshot.votes.len(), 1);
+
+    // A vote must be all ones or all zeros.
+    let bad_nonce = sign_next(&snapshot, &parent, &KEYS[1], Some((candidate, [1; 8])));
+    assert!(invalid(snapshot.validate(&bad_nonce, &parent)));
+
+    let block = sign_next(&snapshot, &parent, &KEYS[1], Some((candidate, NONCE_AUTH)));
+    snapshot.apply(&block, &parent).unwrap();
+    parent = block;
+    assert!(snapshot.signers.contains(&candidate));
+    assert!(snapshot.votes.is_empty());
+
+    // Three of four drop it again. Of four signers, one may sign only one
+    // of three consecutive blocks.
+    for key in [&KEYS[2], &KEYS[0], &KEYS[1]] {
+        assert!(snapshot.signers.contains(&candidate));
+        let block = sign_next(&snapshot, &parent, key, Some((candidate, NONCE_DROP)));
+        snapshot.apply(&block, &parent).unwrap();
+        parent = block;
+    }
+    assert!(!snapshot.signers.contains(&candidate));
+    assert_eq!(snapshot.signers.len(), 3);
+}
+
+#[test]
+fn test_checkpoint() {
+    let (genesis, mut snapshot) = genesis(2);
+    let candidate = address(&KEYS[3]);
+    let block = sign_next(&snapshot, &genesis, &KEYS[0], Some((candidate, NONCE_AUTH)));
+    snapshot.apply(&block, &genesis).unwrap();
+    assert_eq!(snapshot.votes.len(), 1);
+
+    // A checkpoint lists the signers, and can't vote.
+    let key = &KEYS[1];
+    let checkpoint = sign_next(&snapshot, &block, key, None);
+    assert_eq!(checkpoint_signers(&checkpoint).unwrap().len(), 3);
+    let mut voting = checkpoint.clone();
+    voting.coinbase = candidate;
+    voting.nonce = NONCE_AUTH;
+    seal_header(&mut voting, key).unwrap();
+    assert!(invalid(snapshot.validate(&voting, &block)));
+    let mut unlisted = checkpoint.clone();
+    unlisted.extra_data = extra_data(&[]).into();
+    seal_header(&mut unlisted, key).unwrap();
+    assert!(invalid(snapshot.validate(&unlisted, &block)));
+
+    // It drops the votes pending.
+    snapshot.apply(&checkpoint, &block).unwrap();
+    assert!(snapshot.votes.is_empty());
+    assert_eq!(snapshot.hash, checkpoint.hash());
+
+    // Other blocks can't list signers.
+    let listing = sign_next(&snapshot, &checkpoint, &KEYS[2], None);
+    let mut listing_signers = listing.clone();
+    listing_signers.extra_data = checkpoint.extra_data.clone();
+    seal_header(&mut listing_signers, &KEYS[2]).unwrap();
+    assert!(snapshot.validate(&listing, &checkpoint).is_ok());
+    assert!(invalid(snapshot.validate(&listing_signers, &checkpoint)));
+}
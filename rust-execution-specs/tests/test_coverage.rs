@@ -0,0 +1,61 @@
+use execution_specs_rs::ethereum_spec_tools::{
+    evm_tools::{
+        coverage::{Coverage, CoverageTracer},
+        disasm::opcode_table,
+        run::{run, RunConfig},
+    },
+    forks::HardFork,
+};
+use hex_literal::hex;
+use serde_json::json;
+
+fn cover(code: &[u8]) -> Coverage {
+    let tracer = CoverageTracer::new();
+    let coverage = tracer.coverage();
+    run(
+        RunConfig {
+            code: code.into(),
+            ..Default::default()
+        },
+        Some(Box::new(tracer)),
+    );
+    let coverage = coverage.borrow().clone();
+    coverage
+}
+
+#[test]
+fn test_counts() {
+    // PUSH1 1 PUSH1 2 ADD PUSH1 0 JUMP
+    let coverage = cover(&hex!("6001 6002 01 6000 56"));
+    assert_eq!(
+        coverage.opcodes.into_iter().collect::<Vec<_>>(),
+        [(0x01, 1), (0x56, 1), (0x60, 3)]
+    );
+    assert!(coverage.precompiles.is_empty());
+    assert_eq!(
+        coverage.exceptions.into_iter().collect::<Vec<_>>(),
+        [("InvalidJumpDest".to_owned(), 1)]
+    );
+}
+
+#[test]
+fn test_merge_and_report() {
+    let mut coverage = cover(&hex!("6001 6002 01"));
+    coverage.merge(&cover(&hex!("6001 50 50")));
+    assert_eq!(coverage.opcodes[&0x60], 3);
+    assert_eq!(coverage.exceptions["StackUnderflow"], 1);
+
+    let report = coverage.report(HardFork::Frontier);
+    assert_eq!(report["opcodes"]["covered"], 3);
+    assert_eq!(report["opcodes"]["total"], opcode_table(&HardFork::Frontier).len());
+    assert_eq!(report["opcodes"]["counts"], json!({ "ADD": 1, "POP": 2, "PUSH1": 3 }));
+    assert!(report["opcodes"]["missing"]
+        .as_array()
+        .unwrap()
+        .contains(&json!("SSTORE")));
+    assert_eq!(report["exceptions"]["counts"], json!({ "StackUnderflow": 1 }));
+    assert!(report["exceptions"]["missing"]
+        .as_array()
+        .unwrap()
+        .contains(&json!("OutOfGas")));
+}
@@ -0,0 +1,173 @@
+use execution_specs_rs::{
+    ethereum::{
+        base_types::{Uint, U256},
+        create_policy::{
+            check_code_prefix, check_code_size, check_initcode_size, code_deposit, create_policy, CodeDeposit,
+            CreateFailure, CreatePolicy, FRONTIER,
+        },
+        frontier::{
+            fork_types::Account,
+            state::{get_account, set_account, State},
+            vm::{call::CallResult, Evm},
+        },
+    },
+    ethereum_spec_tools::forks::HardFork,
+};
+use hex_literal::hex;
+
+const ORIGIN: [u8; 20] = [0xaa; 20];
+
+/// Homestead, EIP-2.
+const HOMESTEAD: CreatePolicy = CreatePolicy {
+    deposit_out_of_gas_fails: true,
+    ..FRONTIER
+};
+
+/// Spurious Dragon through Berlin, EIP-170.
+const SPURIOUS_DRAGON: CreatePolicy = CreatePolicy {
+    max_code_size: Some(0x6000),
+    ..HOMESTEAD
+};
+
+/// London and Paris, EIP-3541.
+const LONDON: CreatePolicy = CreatePolicy {
+    reject_ef_prefix: true,
+    ..SPURIOUS_DRAGON
+};
+
+/// Shanghai onwards, EIP-3860.
+const SHANGHAI: CreatePolicy = CreatePolicy {
+    max_initcode_size: Some(2 * 0x6000),
+    ..LONDON
+};
+
+/// The experimental EOF rules of `ethereum::eof`, which no fork has yet.
+const EOF: CreatePolicy = CreatePolicy {
+    accept_eof: true,
+    ..SHANGHAI
+};
+
+#[test]
+fn test_code_size_limits() {
+    assert_eq!(create_policy(HardFork::Frontier), &FRONTIER);
+
+    for size in [0, 0x6000, 0x6001, 0x10000] {
+        assert!(check_code_size(&FRONTIER, size));
+        assert!(check_code_size(&HOMESTEAD, size));
+        assert!(check_initcode_size(&SPURIOUS_DRAGON, size));
+    }
+    assert!(check_code_size(&SPURIOUS_DRAGON, 0x6000));
+    assert!(!check_code_size(&SPURIOUS_DRAGON, 0x6001));
+    assert!(check_initcode_size(&SHANGHAI, 0xc000));
+    assert!(!check_initcode_size(&SHANGHAI, 0xc001));
+}
+
+#[test]
+fn test_code_deposit() {
+    let gas = |gas: u32| Uint::from(gas);
+    let code = |size: usize| vec![0; size];
+    assert_eq!(code_deposit(&FRONTIER, &[], &gas(0)), CodeDeposit::Store(gas(0)));
+    assert_eq!(
+        code_deposit(&FRONTIER, &code(10), &gas(2000)),
+        CodeDeposit::Store(gas(2000))
+    );
+    // Frontier creates the contract without code, Homestead fails.
+    assert_eq!(code_deposit(&FRONTIER, &code(10), &gas(1999)), CodeDeposit::Empty);
+    assert_eq!(
+        code_deposit(&HOMESTEAD, &code(10), &gas(1999)),
+        CodeDeposit::Fail(CreateFailure::OutOfGas)
+    );
+    // An oversized code fails whatever the gas.
+    assert_eq!(
+        code_deposit(&SPURIOUS_DRAGON, &code(0x6000), &gas(u32::MAX)),
+        CodeDeposit::Store(gas(0x6000 * 200))
+    );
+    assert_eq!(
+        code_deposit(&SPURIOUS_DRAGON, &code(0x6001), &gas(u32::MAX)),
+        CodeDeposit::Fail(CreateFailure::OutOfGas)
+    );
+    // The prefix is checked before the gas.
+    assert_eq!(
+        code_deposit(&LONDON, &[0xef], &gas(0)),
+        CodeDeposit::Fail(CreateFailure::InvalidContractPrefix)
+    );
+}
+
+#[test]
+fn test_code_prefix() {
+    let container = hex!("ef0001 010004 0200010001 ff0001 00 00800000 00 aa");
+    for code in [&[0xef][..], &[0xef, 0x00], &container] {
+        assert!(check_code_prefix(&FRONTIER, code));
+        assert!(check_code_prefix(&SPURIOUS_DRAGON, code));
+        assert!(!check_code_prefix(&LONDON, code));
+        assert!(!check_code_prefix(&SHANGHAI, code));
+    }
+    for code in [&[][..], &[0x00, 0xef], &[0xfe]] {
+        assert!(check_code_prefix(&LONDON, code));
+    }
+    // EOF deploys valid containers only.
+    assert!(check_code_prefix(&EOF, &container));
+    assert!(!check_code_prefix(&EOF, &container[..container.len() - 1]));
+    assert!(!check_code_prefix(&EOF, &[0xef]));
+    assert_eq!(
+        code_deposit(&EOF, &container, &Uint::from(u32::MAX)),
+        CodeDeposit::Store(Uint::from(container.len() * 200))
+    );
+}
+
+/// Create a contract with initcode returning 100 zero bytes, which uses 18
+/// gas before the deposit of 20000.
+fn create(state: &mut State, gas: u32) -> CallResult {
+    set_account(
+        state,
+        ORIGIN,
+        Some(Account {
+            balance: U256::from(1000u32),
+            ..Default::default()
+        }),
+    );
+    Evm::call()
+        .origin(ORIGIN)
+        .create()
+        .data(hex!("6064 6000 f3").into())
+        .gas(Uint::from(gas))
+        .run(state)
+        .unwrap()
+}
+
+#[test]
+fn test_frontier_code_deposit() {
+    let mut state = State::default();
+    let result = create(&mut state, 18 + 20000);
+    assert_eq!(result.error, None);
+    assert_eq!(result.gas_left, Uint::from(0u8));
+    let address = result.created_address.unwrap();
+    assert_eq!(&*get_account(&state, &address).code, &[0; 100]);
+
+    // One gas short, the contract is created all the same, without code,
+    // and the deposit isn't charged.
+    let mut state = State::default();
+    let result = create(&mut state, 18 + 19999);
+    assert_eq!(result.error, None);
+    assert_eq!(result.gas_used, Uint::from(18u8));
+    assert_eq!(result.gas_left, Uint::from(19999u32));
+    assert!(result.output.is_empty());
+    let address = result.created_address.unwrap();
+    assert!(get_account(&state, &address).code.is_empty());
+    assert_eq!(get_account(&state, &ORIGIN).nonce, Uint::from(1u8));
+}
+
+#[test]
+fn test_frontier_deploys_ef_prefix() {
+    let mut state = State::default();
+    set_account(&mut state, ORIGIN, Some(Account::default()));
+    // Deploys `0xEF`.
+    let result = Evm::call()
+        .origin(ORIGIN)
+        .create()
+        .data(hex!("60ef 6000 53 6001 6000 f3").into())
+        .run(&mut state)
+        .unwrap();
+    assert_eq!(result.error, None);
+    assert_eq!(&*get_account(&state, &result.created_address.unwrap()).code, &[0xef]);
+}
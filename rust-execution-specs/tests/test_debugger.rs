@@ -0,0 +1,140 @@
+use std::{
+    cell::RefCell,
+    io::{self, Cursor, Write},
+    rc::Rc,
+};
+
+use execution_specs_rs::{
+    ethereum::{
+        base_types::Uint,
+        frontier::{state::set_code, vm::exceptions::EvmError},
+    },
+    ethereum_spec_tools::evm_tools::{
+        debugger::{Breakpoint, Command, Debugger},
+        run::{run, RunConfig, RunOutput},
+    },
+};
+use hex_literal::hex;
+
+/// A writer whose contents can still be read after it was handed to the
+/// debugger.
+#[derive(Clone, Default)]
+struct SharedBuffer(Rc<RefCell<Vec<u8>>>);
+
+impl Write for SharedBuffer {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.borrow_mut().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Debug `config` typing `commands`, returning the output of the run and of
+/// the debugger.
+fn debug(config: RunConfig, commands: &str, breakpoints: &[Breakpoint]) -> (RunOutput, String) {
+    let buffer = SharedBuffer::default();
+    let mut debugger = Debugger::new(Box::new(Cursor::new(commands.to_owned())), Box::new(buffer.clone()));
+    for breakpoint in breakpoints {
+        debugger.add_breakpoint(*breakpoint);
+    }
+    if !breakpoints.is_empty() {
+        debugger = debugger.run_to_breakpoint();
+    }
+    let output = run(config, Some(Box::new(debugger)));
+    let text = String::from_utf8(buffer.0.borrow().clone()).unwrap();
+    (output, text)
+}
+
+fn code(code: &[u8]) -> RunConfig {
+    RunConfig {
+        code: code.into(),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_parse_commands() {
+    assert_eq!(Command::parse("s"), Ok(Command::Step));
+    assert_eq!(Command::parse(""), Ok(Command::Step));
+    assert_eq!(Command::parse("next"), Ok(Command::Next));
+    assert_eq!(Command::parse("b 0x10"), Ok(Command::Break(Some(Breakpoint::Pc(16)))));
+    assert_eq!(
+        Command::parse("break sstore"),
+        Ok(Command::Break(Some(Breakpoint::Op(0x55))))
+    );
+    assert_eq!(
+        Command::parse("memory 32 64"),
+        Ok(Command::Memory {
+            offset: 32,
+            length: Some(64)
+        })
+    );
+    assert_eq!(Command::parse("gas 0x64"), Ok(Command::Gas(Some(Uint::from(100u8)))));
+    assert!(Command::parse("break nowhere").is_err());
+    assert!(Command::parse("stack 1").is_err());
+    assert!(Command::parse(&format!("storage 0x1{}", "0".repeat(64))).is_err());
+}
+
+#[test]
+fn test_step_and_inspect() {
+    // PUSH1 1 PUSH1 2 ADD PUSH1 0 MSTORE STOP
+    let (output, text) = debug(
+        code(&hex!("6001 6002 01 6000 52 00")),
+        "s\ns\nstack\ns\ns\ns\nmemory\nq\n",
+        &[],
+    );
+    assert_eq!(output.error, None);
+    assert!(text.contains("[depth 0] pc 0x0000 PUSH1 0x01"));
+    assert!(text.contains("[depth 0] pc 0x0004 ADD"));
+    assert!(text.contains("0: 0x2\n1: 0x1"));
+    assert!(text.contains(&format!("0x0000: 0x{}03", "0".repeat(62))));
+}
+
+#[test]
+fn test_breakpoint_and_storage() {
+    // SSTORE(2, 1) SSTORE(4, 3)
+    let (output, text) = debug(
+        code(&hex!("6001 6002 55 6003 6004 55")),
+        "storage\nc\nstorage\nstorage 4\nc\n",
+        &[Breakpoint::Op(0x55)],
+    );
+    assert_eq!(output.error, None);
+    // Only the breakpoints stop the run.
+    assert!(!text.contains("PUSH1"));
+    assert!(text.contains("pc 0x0004 SSTORE"));
+    assert!(text.contains("(empty)"));
+    assert!(text.contains("pc 0x0009 SSTORE"));
+    assert!(text.contains(&format!("0x{}02: 0x1", "0".repeat(62))));
+    assert!(text.contains(&format!("0x{}04: 0x0", "0".repeat(62))));
+}
+
+#[test]
+fn test_set_gas() {
+    // Three `PUSH1`s and an `ADD` need 12 gas.
+    let (output, text) = debug(code(&hex!("6001 6002 01 6003")), "gas 5\nc\n", &[]);
+    assert_eq!(output.error, Some(EvmError::OutOfGas));
+    assert!(text.contains("halted: OutOfGas"));
+}
+
+#[test]
+fn test_next_steps_over_calls() {
+    // CALL(0xffff, 0xff, 0, 0, 0, 0, 0) STOP
+    let caller = hex!("6000 6000 6000 6000 6000 60ff 61ffff f1 00");
+    let mut callee = [0; 20];
+    callee[19] = 0xff;
+    let config = || {
+        let mut config = code(&caller);
+        set_code(&mut config.state, callee, hex!("6001 6002 01").to_vec().into());
+        config
+    };
+
+    let (_, text) = debug(config(), "n\nq\n", &[Breakpoint::Op(0xf1)]);
+    assert!(text.contains("[depth 0] pc 0x000f CALL"));
+    assert!(text.contains("[depth 0] pc 0x0010 STOP"));
+    assert!(!text.contains("[depth 1]"));
+
+    let (_, text) = debug(config(), "s\nq\n", &[Breakpoint::Op(0xf1)]);
+    assert!(text.contains("[depth 1] pc 0x0000 PUSH1 0x01"));
+}
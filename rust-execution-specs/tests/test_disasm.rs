@@ -0,0 +1,117 @@
+use execution_specs_rs::ethereum_spec_tools::{
+    evm_tools::disasm::{
+        assemble, disassemble, disassemble_to_string, opcode, opcode_by_name, opcode_table, Instruction,
+    },
+    forks::HardFork,
+};
+use hex_literal::hex;
+use proptest::{collection, prelude::*};
+
+const FORK: HardFork = HardFork::Frontier;
+
+#[test]
+fn test_disassemble_listing() {
+    let code = hex!("6080604052fe5b6000");
+    let expected = "\
+00000: PUSH1 0x80
+00002: PUSH1 0x40
+00004: MSTORE
+00005: INVALID 0xfe
+00006: JUMPDEST
+00007: PUSH1 0x00
+";
+    assert_eq!(disassemble_to_string(&FORK, &code), expected);
+}
+
+#[test]
+fn test_disassemble_truncated_push() {
+    let code = hex!("00636001");
+    let instructions = disassemble(&FORK, &code);
+    assert_eq!(instructions.len(), 2);
+    let (pc, instruction) = &instructions[1];
+    assert_eq!(*pc, 1);
+    assert!(matches!(instruction, Instruction::Truncated { .. }));
+    assert_eq!(instruction.to_string(), "DATA 0x636001 ; truncated PUSH4");
+}
+
+#[test]
+fn test_disassemble_opcodes_not_in_fork() {
+    // SHL (0x1b), DELEGATECALL (0xf4) and REVERT (0xfd) came after Frontier.
+    for byte in [0x1b, 0xf4, 0xfd] {
+        let instructions = disassemble(&FORK, &[byte]);
+        assert_eq!(instructions, vec![(0, Instruction::Invalid(byte))]);
+    }
+}
+
+#[test]
+fn test_assemble() {
+    let source = "
+        ; store 0x2a at memory offset 0 and return it
+        PUSH1 42
+        PUSH1 0x0
+        MSTORE
+        push2 0x20      ; lower case mnemonics are accepted
+        PUSH1 0
+        RETURN
+        DATA 0xdeadbeef
+    ";
+    let code = assemble(&FORK, source).unwrap();
+    assert_eq!(&*code, hex!("602a6000526100206000f3deadbeef"));
+}
+
+#[test]
+fn test_assemble_errors() {
+    let cases = [
+        ("PUSH1", 1),
+        ("STOP\nPUSH1 0x100", 2),
+        ("ADD 0x01", 1),
+        ("\n\nDELEGATECALL", 3),
+        ("INVALID 0xfefe", 1),
+        ("DATA 1234", 1),
+    ];
+    for (source, line) in cases {
+        let error = assemble(&FORK, source).unwrap_err();
+        assert_eq!(error.line, line, "{}: {}", source, error);
+    }
+}
+
+#[test]
+fn test_opcode_table() {
+    let table = opcode_table(&FORK);
+    assert_eq!(table.len(), 129);
+    assert!(table.windows(2).all(|pair| pair[0].value < pair[1].value));
+    for entry in &table {
+        assert_eq!(opcode(&FORK, entry.value).as_ref(), Some(entry));
+        assert_eq!(opcode_by_name(&FORK, entry.mnemonic).as_ref(), Some(entry));
+    }
+
+    let cases = [
+        ("STOP", 0, 0, 0, 0),
+        ("ADDMOD", 0, 8, 3, 1),
+        ("KECCAK", 0, 30, 2, 1),
+        ("SLOAD", 0, 50, 1, 1),
+        ("JUMPDEST", 0, 1, 0, 0),
+        ("PUSH32", 32, 3, 0, 1),
+        ("DUP16", 0, 3, 16, 17),
+        ("SWAP16", 0, 3, 17, 17),
+        ("LOG4", 0, 375 * 5, 6, 0),
+        ("CALL", 0, 40, 7, 1),
+    ];
+    for (mnemonic, push_size, base_gas, stack_inputs, stack_outputs) in cases {
+        let entry = opcode_by_name(&FORK, mnemonic).unwrap();
+        assert_eq!(
+            (entry.push_size, entry.base_gas, entry.stack_inputs, entry.stack_outputs),
+            (push_size, base_gas, stack_inputs, stack_outputs),
+            "{}",
+            mnemonic
+        );
+    }
+}
+
+proptest! {
+    #[test]
+    fn disassembly_round_trip(code in collection::vec(any::<u8>(), 0..256)) {
+        let listing = disassemble_to_string(&FORK, &code);
+        prop_assert_eq!(&*assemble(&FORK, &listing).unwrap(), &code[..]);
+    }
+}
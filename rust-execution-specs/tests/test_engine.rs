@@ -0,0 +1,303 @@
+use execution_specs_rs::{
+    ethereum::{
+        base_types::{Uint, U256},
+        frontier::{
+            fork::{make_receipt, signing_hash, BlockChain},
+            fork_types::{keccak256, Account, Address, Transaction},
+            state::{get_account, set_account, state_root, State},
+            trie::{dummy_root, root, trie_set, Trie},
+        },
+        genesis::{add_genesis_block, GenesisConfiguration},
+        rlp,
+        transaction_signature::signing_hash_155,
+        utils::hexadecimal::hex,
+    },
+    ethereum_spec_tools::{
+        engine::{EngineApi, ExecutionPayload, HeaderExtensions},
+        forks::HardFork,
+    },
+};
+use k256::ecdsa::SigningKey;
+use serde_json::{json, Value};
+
+const RECIPIENT: [u8; 20] = [0xcc; 20];
+const FEE_RECIPIENT: [u8; 20] = [0xbb; 20];
+const VALIDATOR: [u8; 20] = [0xdd; 20];
+const ZERO_HASH: &str = "0x0000000000000000000000000000000000000000000000000000000000000000";
+
+fn signing_key() -> SigningKey {
+    SigningKey::from_slice(&[0x45; 32]).unwrap()
+}
+
+fn sender() -> Address {
+    let point = signing_key().verifying_key().to_encoded_point(false);
+    keccak256(&point.as_bytes()[1..])[12..].try_into().unwrap()
+}
+
+/// A transfer of 1000 wei to `RECIPIENT`, paying 2 wei per gas, signed for
+/// `chain_id` or for every chain.
+fn transfer_on(chain_id: Option<u64>) -> Transaction {
+    let mut tx = Transaction {
+        nonce: U256::from(0u8),
+        gas_price: U256::from(2u8),
+        gas: U256::from(21000u32),
+        to: Some(RECIPIENT),
+        value: U256::from(1000u32),
+        data: vec![].into(),
+        v: U256::from(0u8),
+        r: U256::from(0u8),
+        s: U256::from(0u8),
+    };
+    let hash = match chain_id {
+        Some(chain_id) => signing_hash_155(&tx, chain_id),
+        None => signing_hash(&tx),
+    };
+    let (signature, recovery_id) = signing_key().sign_prehash_recoverable(&hash).unwrap();
+    let (r, s) = signature.split_bytes();
+    let offset = chain_id.map_or(27, |chain_id| 35 + 2 * chain_id);
+    tx.v = U256::from(offset + u64::from(recovery_id.to_byte()));
+    tx.r = U256::from_bytes_be(&r);
+    tx.s = U256::from_bytes_be(&s);
+    tx
+}
+
+fn transfer() -> Transaction {
+    transfer_on(None)
+}
+
+fn engine() -> EngineApi {
+    let mut chain = BlockChain {
+        blocks: vec![],
+        state: State::default(),
+        chain_id: 0,
+    };
+    let genesis = GenesisConfiguration {
+        chain_id: 1,
+        difficulty: Uint::from(0x20000u32),
+        gas_limit: Uint::from(3_141_592u32),
+        initial_balances: [(sender(), U256::from(1_000_000u32))].into(),
+        ..Default::default()
+    };
+    add_genesis_block(HardFork::Frontier, &mut chain, genesis).unwrap();
+    EngineApi::new(chain, HeaderExtensions::default()).unwrap()
+}
+
+fn balance(balance: u64) -> Option<Account> {
+    Some(Account {
+        balance: U256::from(balance),
+        ..Default::default()
+    })
+}
+
+/// The state after `transfer()`, and the withdrawal of 5 gwei to
+/// `VALIDATOR` if `withdrawn`, worked out by hand.
+fn expected_state(withdrawn: bool) -> State {
+    let mut state = State::default();
+    set_account(
+        &mut state,
+        sender(),
+        Some(Account {
+            nonce: Uint::from(1u8),
+            balance: U256::from(1_000_000u32 - 42_000 - 1000),
+            ..Default::default()
+        }),
+    );
+    set_account(&mut state, RECIPIENT, balance(1000));
+    set_account(&mut state, FEE_RECIPIENT, balance(42_000));
+    if withdrawn {
+        set_account(&mut state, VALIDATOR, balance(5_000_000_000));
+    }
+    state
+}
+
+/// The `V2` payload of the block after genesis holding `transfer()` and a
+/// withdrawal, with a correct block hash.
+fn payload(parent_hash: &str) -> Value {
+    payload_with(parent_hash, transfer())
+}
+
+/// `payload()` with `transfer` signed another way.
+fn payload_with(parent_hash: &str, transfer: Transaction) -> Value {
+    let mut receipts = Trie::new(false, None);
+    let receipt = make_receipt(state_root(&expected_state(false)), Uint::from(21000u32), &[]);
+    trie_set(&mut receipts, rlp::encode(&Uint::from(0u8)), Some(receipt));
+
+    let mut payload = json!({
+        "parentHash": parent_hash,
+        "feeRecipient": hex(&FEE_RECIPIENT),
+        "stateRoot": hex(&state_root(&expected_state(true))),
+        "receiptsRoot": hex(&root(&receipts, dummy_root)),
+        "logsBloom": format!("0x{}", "00".repeat(256)),
+        "prevRandao": format!("0x{}", "11".repeat(32)),
+        "blockNumber": "0x1",
+        "gasLimit": "0x2fefd8",
+        "gasUsed": "0x5208",
+        "timestamp": "0xc",
+        "extraData": "0x",
+        "baseFeePerGas": "0x7",
+        "blockHash": ZERO_HASH,
+        "transactions": [hex(&rlp::encode(&transfer))],
+        "withdrawals": [{
+            "index": "0x0",
+            "validatorIndex": "0x1",
+            "address": hex(&VALIDATOR),
+            "amount": "0x5"
+        }]
+    });
+    rehash(&mut payload);
+    payload
+}
+
+/// Fix up the block hash of a changed payload.
+fn rehash(payload: &mut Value) {
+    let hash = ExecutionPayload::from_json(payload).unwrap().compute_block_hash(None);
+    payload["blockHash"] = json!(hex(&hash));
+}
+
+fn request(engine: &mut EngineApi, method: &str, params: Value) -> Value {
+    engine.handle(&json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params }))
+}
+
+fn forkchoice(head: &str, finalized: &str) -> Value {
+    json!([{ "headBlockHash": head, "safeBlockHash": ZERO_HASH, "finalizedBlockHash": finalized }, null])
+}
+
+#[test]
+fn test_new_payload() {
+    let mut engine = engine();
+    let genesis = hex(&engine.head());
+    let payload = payload(&genesis);
+
+    let response = request(&mut engine, "engine_newPayloadV2", json!([payload]));
+    assert_eq!(
+        response["result"],
+        json!({ "status": "VALID", "latestValidHash": payload["blockHash"], "validationError": null })
+    );
+    assert_eq!(hex(&engine.head()), payload["blockHash"]);
+    assert_eq!(state_root(engine.state()), state_root(&expected_state(true)));
+    assert_eq!(get_account(engine.state(), &VALIDATOR).balance, U256::from(5_000_000_000u64));
+
+    // Sending it again changes nothing.
+    let response = request(&mut engine, "engine_newPayloadV2", json!([payload]));
+    assert_eq!(response["result"]["status"], "VALID");
+    assert_eq!(state_root(engine.state()), state_root(&expected_state(true)));
+
+    let head = payload["blockHash"].as_str().unwrap();
+    let response = request(&mut engine, "engine_forkchoiceUpdatedV2", forkchoice(head, &genesis));
+    assert_eq!(response["result"]["payloadStatus"]["status"], "VALID");
+    assert_eq!(response["result"]["payloadId"], Value::Null);
+    assert_eq!(hex(&engine.forkchoice().finalized_block_hash), genesis);
+}
+
+#[test]
+fn test_invalid_payload() {
+    let mut engine = engine();
+    let genesis = hex(&engine.head());
+    let genesis_root = state_root(engine.state());
+
+    let mut wrong_hash = payload(&genesis);
+    wrong_hash["blockHash"] = json!(ZERO_HASH);
+    let response = request(&mut engine, "engine_newPayloadV2", json!([wrong_hash]));
+    assert_eq!(response["result"]["status"], "INVALID");
+    assert_eq!(response["result"]["latestValidHash"], Value::Null);
+
+    let mut v1 = payload(&genesis);
+    v1.as_object_mut().unwrap().remove("withdrawals");
+    v1["blockHash"] = json!(ZERO_HASH);
+    let response = request(&mut engine, "engine_newPayloadV1", json!([v1]));
+    assert_eq!(response["result"]["status"], "INVALID_BLOCK_HASH");
+
+    let mut wrong_root = payload(&genesis);
+    wrong_root["stateRoot"] = json!(hex(&genesis_root));
+    rehash(&mut wrong_root);
+    let response = request(&mut engine, "engine_newPayloadV2", json!([wrong_root]));
+    assert_eq!(response["result"]["status"], "INVALID");
+    assert_eq!(response["result"]["latestValidHash"], genesis);
+    assert_eq!(
+        response["result"]["validationError"],
+        format!("the state root is {}", hex(&state_root(&expected_state(true))))
+    );
+    // The changes of the invalid payload are undone.
+    assert_eq!(state_root(engine.state()), genesis_root);
+    assert_eq!(hex(&engine.head()), genesis);
+
+    let mut too_early = payload(&genesis);
+    too_early["timestamp"] = json!("0x0");
+    rehash(&mut too_early);
+    let response = request(&mut engine, "engine_newPayloadV2", json!([too_early]));
+    assert_eq!(response["result"]["validationError"], "invalid timestamp");
+}
+
+#[test]
+fn test_unknown_blocks() {
+    let mut engine = engine();
+    let genesis = hex(&engine.head());
+
+    let orphan = payload(&format!("0x{}", "ab".repeat(32)));
+    let response = request(&mut engine, "engine_newPayloadV2", json!([orphan]));
+    assert_eq!(response["result"]["status"], "SYNCING");
+
+    let first = payload(&genesis);
+    request(&mut engine, "engine_newPayloadV2", json!([first]));
+    // A sibling of the tip is only accepted.
+    let mut sibling = payload(&genesis);
+    sibling["extraData"] = json!("0x01");
+    rehash(&mut sibling);
+    let response = request(&mut engine, "engine_newPayloadV2", json!([sibling]));
+    assert_eq!(response["result"]["status"], "ACCEPTED");
+
+    let sibling_hash = sibling["blockHash"].as_str().unwrap();
+    let response = request(&mut engine, "engine_forkchoiceUpdatedV1", forkchoice(sibling_hash, ZERO_HASH));
+    assert_eq!(response["result"]["payloadStatus"]["status"], "SYNCING");
+
+    // The consensus layer may go back to an ancestor of the tip.
+    let response = request(&mut engine, "engine_forkchoiceUpdatedV1", forkchoice(&genesis, ZERO_HASH));
+    assert_eq!(response["result"]["payloadStatus"]["status"], "VALID");
+
+    let head = first["blockHash"].as_str().unwrap();
+    let response = request(&mut engine, "engine_forkchoiceUpdatedV1", forkchoice(head, sibling_hash));
+    assert_eq!(response["error"]["code"], -38002);
+}
+
+#[test]
+fn test_payload_versions() {
+    let mut engine = engine();
+    let genesis = hex(&engine.head());
+
+    let response = request(&mut engine, "engine_newPayloadV1", json!([payload(&genesis)]));
+    assert_eq!(response["error"]["code"], -32602);
+
+    let mut v3 = payload(&genesis);
+    v3["blobGasUsed"] = json!("0x0");
+    v3["excessBlobGas"] = json!("0x0");
+    let response = request(&mut engine, "engine_newPayloadV3", json!([v3, []]));
+    assert_eq!(response["error"]["code"], -32602);
+
+    // The parent beacon block root is part of the hash from V3.
+    let response = request(&mut engine, "engine_newPayloadV3", json!([v3, [], ZERO_HASH]));
+    assert_eq!(response["result"]["status"], "INVALID");
+    let hash = ExecutionPayload::from_json(&v3).unwrap().compute_block_hash(Some(&[0; 32]));
+    v3["blockHash"] = json!(hex(&hash));
+    let response = request(&mut engine, "engine_newPayloadV3", json!([v3, [], ZERO_HASH]));
+    assert_eq!(response["result"]["status"], "VALID");
+
+    let response = request(&mut engine, "engine_exchangeCapabilities", json!([[]]));
+    assert_eq!(response["result"].as_array().unwrap().len(), 6);
+}
+
+#[test]
+fn test_replay_protected_payload() {
+    let mut engine = engine();
+    let genesis = hex(&engine.head());
+    let genesis_root = state_root(engine.state());
+
+    let other_chain = payload_with(&genesis, transfer_on(Some(5)));
+    let response = request(&mut engine, "engine_newPayloadV2", json!([other_chain]));
+    assert_eq!(response["result"]["status"], "INVALID");
+
+    // Payloads run with the rules of Frontier, which predate EIP-155.
+    let protected = payload_with(&genesis, transfer_on(Some(1)));
+    let response = request(&mut engine, "engine_newPayloadV2", json!([protected]));
+    assert_eq!(response["result"]["status"], "INVALID", "{response}");
+    assert_eq!(state_root(engine.state()), genesis_root);
+}
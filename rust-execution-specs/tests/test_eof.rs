@@ -0,0 +1,201 @@
+use execution_specs_rs::ethereum::{
+    base_types::{Uint, U256},
+    eof::{
+        container::{parse, Container, TypeSection, NON_RETURNING},
+        exceptions::EofError,
+        interpreter::execute_code,
+        is_eof,
+        validation::{validate, validate_container},
+    },
+    frontier::vm::{exceptions::EvmError, Environment, Evm, Message},
+};
+use hex_literal::hex;
+
+fn function(inputs: u8, outputs: u8, max_stack_height: u16) -> TypeSection {
+    TypeSection {
+        inputs,
+        outputs,
+        max_stack_height,
+    }
+}
+
+fn main_section(max_stack_height: u16) -> TypeSection {
+    function(0, NON_RETURNING, max_stack_height)
+}
+
+fn container(sections: &[(TypeSection, &[u8])], data: &[u8]) -> Container {
+    Container {
+        types: sections.iter().map(|(ty, _)| *ty).collect(),
+        code: sections.iter().map(|(_, code)| code.to_vec().into()).collect(),
+        containers: vec![],
+        data: data.into(),
+    }
+}
+
+fn run(container: &Container) -> Evm {
+    validate_container(container).unwrap();
+    let message = Message {
+        caller: [0; 20],
+        target: Some([0xee; 20]),
+        current_target: [0xee; 20],
+        gas: U256::from(1_000_000u32),
+        value: U256::default(),
+        data: vec![].into(),
+        code_address: Some([0xee; 20]),
+        code: container.encode().into(),
+        depth: Uint::default(),
+    };
+    execute_code(container, message, Environment::default())
+}
+
+/// `value` as the 32 bytes `RETURN` outputs from `MSTORE`.
+fn word(value: u8) -> Box<[u8]> {
+    let mut word = [0; 32];
+    word[31] = value;
+    word.into()
+}
+
+#[test]
+fn test_parse() {
+    // STOP, with one byte of data.
+    let code = hex!("ef0001 010004 0200010001 ff0001 00 00800000 00 aa");
+    assert!(is_eof(&code));
+    let parsed = parse(&code).unwrap();
+    assert_eq!(parsed, container(&[(main_section(0), &[0x00])], &[0xaa]));
+    assert_eq!(parsed.encode(), code);
+
+    let with_subcontainer = Container {
+        containers: vec![code.to_vec().into()],
+        ..parsed.clone()
+    };
+    assert_eq!(parse(&with_subcontainer.encode()).unwrap(), with_subcontainer);
+
+    assert_eq!(parse(&hex!("ef01")), Err(EofError::InvalidMagic));
+    assert_eq!(parse(&hex!("ef0002")), Err(EofError::UnsupportedVersion(2)));
+    // No code sections.
+    assert_eq!(parse(&hex!("ef0001 010004 020000 ff0000 00")), Err(EofError::InvalidHeader));
+    // The data section before the code sections.
+    assert_eq!(parse(&hex!("ef0001 010004 ff0000 0200010001 00")), Err(EofError::InvalidHeader));
+    // Types for two sections.
+    assert_eq!(
+        parse(&hex!("ef0001 010008 0200010001 ff0000 00 00800000 00800000 00")),
+        Err(EofError::InvalidTypes)
+    );
+    // The first section returns.
+    assert_eq!(
+        parse(&hex!("ef0001 010004 0200010001 ff0000 00 00000000 00")),
+        Err(EofError::InvalidTypes)
+    );
+    // Missing data, and trailing bytes.
+    assert_eq!(parse(&code[..code.len() - 1]), Err(EofError::InvalidSectionBodies));
+    assert_eq!(parse(&[&code[..], &[0xaa]].concat()), Err(EofError::InvalidSectionBodies));
+}
+
+#[test]
+fn test_validate_code() {
+    let invalid = |sections: &[(TypeSection, &[u8])]| validate_container(&container(sections, &[])).unwrap_err();
+
+    // JUMP was removed.
+    assert_eq!(
+        invalid(&[(main_section(1), &hex!("6000 56"))]),
+        EofError::UndefinedInstruction { section: 0, pos: 2, opcode: 0x56 }
+    );
+    assert_eq!(
+        invalid(&[(main_section(1), &hex!("61 00"))]),
+        EofError::TruncatedInstruction { section: 0, pos: 0 }
+    );
+    // Into the data of the PUSH1.
+    assert_eq!(
+        invalid(&[(main_section(1), &hex!("e0 0001 6000 00"))]),
+        EofError::InvalidJumpDestination { section: 0, pos: 0 }
+    );
+    assert_eq!(
+        invalid(&[(main_section(0), &hex!("e0 0001 00 00"))]),
+        EofError::UnreachableCode { section: 0, pos: 3 }
+    );
+    assert_eq!(invalid(&[(main_section(0), &hex!("50 00"))]), EofError::StackUnderflow { section: 0, pos: 0 });
+    // A loop pushing an item on each iteration.
+    assert_eq!(
+        invalid(&[(main_section(1), &hex!("6000 e0 fffb"))]),
+        EofError::StackHeightMismatch { section: 0, pos: 2 }
+    );
+    assert_eq!(invalid(&[(main_section(2), &hex!("6000 00"))]), EofError::InvalidMaxStackHeight { section: 0 });
+    assert_eq!(invalid(&[(main_section(1), &hex!("6000"))]), EofError::NoTerminatingInstruction { section: 0 });
+    assert_eq!(
+        invalid(&[(main_section(0), &hex!("e30001 00")), (main_section(0), &hex!("00"))]),
+        EofError::CallfToNonReturning { section: 0, pos: 0 }
+    );
+    assert_eq!(
+        invalid(&[(main_section(0), &hex!("e30002 00")), (function(0, 0, 0), &hex!("e4"))]),
+        EofError::InvalidCodeSectionIndex { section: 0, pos: 0 }
+    );
+    // Declared returning, but never returns.
+    assert_eq!(
+        invalid(&[(main_section(0), &hex!("e30001 00")), (function(0, 0, 0), &hex!("00"))]),
+        EofError::InvalidNonReturning { section: 1 }
+    );
+    assert_eq!(
+        invalid(&[(main_section(0), &hex!("00")), (function(0, 0, 0), &hex!("e4"))]),
+        EofError::UnreachableCodeSection { section: 1 }
+    );
+    // One item left where none is returned.
+    assert_eq!(
+        invalid(&[(main_section(0), &hex!("e30001 00")), (function(0, 0, 1), &hex!("6000 e4"))]),
+        EofError::StackHeightMismatch { section: 1, pos: 2 }
+    );
+    assert_eq!(
+        validate_container(&container(&[(main_section(1), &hex!("d1 0001 00"))], &[0; 32])),
+        Err(EofError::InvalidDataloadnIndex { section: 0, pos: 0 })
+    );
+
+    let mut with_subcontainer = container(&[(main_section(0), &hex!("00"))], &[]);
+    with_subcontainer.containers.push(with_subcontainer.encode().into());
+    assert_eq!(
+        validate(&with_subcontainer.encode()),
+        Err(EofError::UnreferencedSubcontainer { index: 0 })
+    );
+}
+
+#[test]
+fn test_execute_functions_and_data() {
+    let program = container(
+        &[
+            // CALLF 1 with 2 and 3, add the data word, and return the sum.
+            (main_section(2), &hex!("6002 6003 e30001 d10000 01 6000 52 6020 6000 f3")),
+            // ADD, RETF
+            (function(2, 1, 2), &hex!("01 e4")),
+        ],
+        &word(10),
+    );
+    assert_eq!(validate(&program.encode()), Ok(program.clone()));
+
+    let evm = run(&program);
+    assert_eq!(evm.error, None);
+    assert_eq!(evm.output, word(15));
+    assert_eq!(evm.gas_left, U256::from(1_000_000u32 - 38));
+}
+
+#[test]
+fn test_execute_loop() {
+    // Sum 5 + 4 + ... + 1 with RJUMPI and RJUMP, and EXCHANGE to reach the
+    // accumulator.
+    let program = container(
+        &[(
+            main_section(3),
+            &hex!("6000 6005 80 15 e1000c 80 e800 01 90 6001 90 03 e0ffef 50 6000 52 6020 6000 f3"),
+        )],
+        &[],
+    );
+    let evm = run(&program);
+    assert_eq!(evm.error, None);
+    assert_eq!(evm.output, word(15));
+}
+
+#[test]
+fn test_execute_call_depth() {
+    // A function calling itself forever.
+    let program = container(&[(main_section(0), &hex!("e30001 00")), (function(0, 0, 0), &hex!("e30001 e4"))], &[]);
+    let evm = run(&program);
+    assert_eq!(evm.error, Some(EvmError::StackOverflow));
+    assert_eq!(evm.gas_left, U256::default());
+}
@@ -0,0 +1,49 @@
+use execution_specs_rs::{
+    ethereum::{
+        ethash::{cache_size, dataset_size, generate_cache, HASH_BYTES},
+        exceptions::EthereumException,
+        frontier::fork::validate_proof_of_work,
+    },
+    ethereum_spec_tools::rpc::parse_header,
+};
+use serde_json::json;
+
+#[test]
+fn test_epoch_sizes() {
+    assert_eq!(cache_size(0), 16_776_896);
+    assert_eq!(dataset_size(0), 1_073_739_904);
+    assert_eq!(cache_size(30_000), 16_907_456);
+    assert_eq!(dataset_size(29_999), dataset_size(0));
+}
+
+#[test]
+fn test_proof_of_work() {
+    // Mainnet block 1.
+    let (_, mut header) = parse_header(&json!({
+        "difficulty": "0x3ff800000",
+        "extraData": "0x476574682f76312e302e302f6c696e75782f676f312e342e32",
+        "gasLimit": "0x1388",
+        "gasUsed": "0x0",
+        "hash": "0x88e96d4537bea4d9c05d12549907b32561d3bf31f45aae734cdc119f13406cb6",
+        "logsBloom": format!("0x{}", "00".repeat(256)),
+        "miner": "0x05a56e2d52c817161883f50c441c3228cfe54d9f",
+        "mixHash": "0x969b900de27b6ac6a67742365dd65f55a0526c41fd18e1b16f1a1215c2e66f59",
+        "nonce": "0x539bd4979fef1ec4",
+        "number": "0x1",
+        "parentHash": "0xd4e56740f876aef8c010b86a40d5f56745a118d0906a34e69aec8c0db1cb8fa3",
+        "receiptsRoot": "0x56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421",
+        "sha3Uncles": "0x1dcc4de8dec75d7aab85b567b6ccd41ad312451b948a7413f0a142fd40d49347",
+        "stateRoot": "0xd67e4d450343046425ae4271474353857ab860dbc0a1dde64b41b5cd3a532bf3",
+        "timestamp": "0x55ba4224",
+        "transactionsRoot": "0x56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421",
+    }))
+    .unwrap();
+    let cache = generate_cache(1);
+    assert_eq!(cache.len() as u64, cache_size(1) / HASH_BYTES as u64);
+
+    validate_proof_of_work(&header, &cache).unwrap();
+
+    // The mix digest is for another nonce.
+    header.nonce[7] ^= 1;
+    assert!(matches!(validate_proof_of_work(&header, &cache), Err(EthereumException::InvalidBlock)));
+}
@@ -0,0 +1,244 @@
+use std::{
+    cell::RefCell,
+    io::{self, Write},
+    rc::Rc,
+};
+
+use execution_specs_rs::{
+    ethereum::{
+        base_types::{Uint, U256},
+        frontier::{
+            state::{get_account, get_storage},
+            vm::exceptions::EvmError,
+        },
+    },
+    ethereum_spec_tools::evm_tools::{
+        alloc::load_alloc,
+        eip3155::Eip3155Tracer,
+        run::{run, RunConfig, RunOutput, DEFAULT_RECEIVER},
+    },
+};
+use hex_literal::hex;
+
+/// A writer whose contents can still be read after it was handed to a tracer.
+#[derive(Clone, Default)]
+struct SharedBuffer(Rc<RefCell<Vec<u8>>>);
+
+impl Write for SharedBuffer {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.borrow_mut().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+fn run_code(code: &[u8]) -> RunOutput {
+    run(
+        RunConfig {
+            code: code.into(),
+            ..Default::default()
+        },
+        None,
+    )
+}
+
+#[test]
+fn test_run_return() {
+    // PUSH1 2 PUSH1 1 ADD PUSH1 0 MSTORE PUSH1 32 PUSH1 0 RETURN
+    let output = run_code(&hex!("600260010160005260206000f3"));
+    assert_eq!(output.error, None);
+    assert_eq!(output.gas_used, Uint::from(24u8));
+    let mut three = [0; 32];
+    three[31] = 3;
+    assert_eq!(*output.output, three);
+}
+
+#[test]
+fn test_run_exceptional_halt_consumes_gas() {
+    let output = run_code(&hex!("6001 56"));
+    assert_eq!(output.error, Some(EvmError::InvalidJumpDest));
+    assert_eq!(output.gas_used, RunConfig::default().gas);
+    assert!(output.output.is_empty());
+}
+
+#[test]
+fn test_run_storage_and_prestate() {
+    let state = load_alloc(
+        r#"{
+            "0x0000000000000000000000007265636569766572": {
+                "balance": "0x10",
+                "storage": { "0x01": "0x2a" }
+            }
+        }"#,
+    )
+    .unwrap();
+    // SSTORE(2, SLOAD(1) + 1)
+    let output = run(
+        RunConfig {
+            code: hex!("6001 54 6001 01 6002 55").into(),
+            state,
+            ..Default::default()
+        },
+        None,
+    );
+    assert_eq!(output.error, None);
+    let key = |n: u8| {
+        let mut key = [0; 32];
+        key[31] = n;
+        key
+    };
+    assert_eq!(get_storage(&output.state, &DEFAULT_RECEIVER, &key(2)), U256::from(43u8));
+    assert_eq!(get_account(&output.state, &DEFAULT_RECEIVER).balance, U256::from(16u8));
+}
+
+#[test]
+fn test_run_create() {
+    // Deploys the single byte `0x01`.
+    let output = run(
+        RunConfig {
+            code: hex!("6001 6000 53 6001 6000 f3").into(),
+            create: true,
+            ..Default::default()
+        },
+        None,
+    );
+    assert_eq!(output.error, None);
+    assert_eq!(&*output.output, &[1u8]);
+    // Execution plus the code deposit.
+    assert_eq!(output.gas_used, Uint::from(18u8 + 200));
+}
+
+#[test]
+fn test_run_eip3155_trace() {
+    let buffer = SharedBuffer::default();
+    let tracer = Eip3155Tracer::new(Box::new(buffer.clone()));
+    run(
+        RunConfig {
+            code: hex!("6001 6002 01").into(),
+            ..Default::default()
+        },
+        Some(Box::new(tracer)),
+    );
+
+    let trace = String::from_utf8(buffer.0.borrow().clone()).unwrap();
+    let lines: Vec<serde_json::Value> = trace
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    let op_names: Vec<_> = lines.iter().map(|line| line["opName"].clone()).collect();
+    // Running off the end of the code is traced as a `STOP`.
+    assert_eq!(op_names[..4], ["PUSH1", "PUSH1", "ADD", "STOP"]);
+    assert_eq!(lines[2]["stack"], serde_json::json!(["0x1", "0x2"]));
+    assert_eq!(lines[2]["gasCost"], "0x3");
+    assert_eq!(lines[4]["gasUsed"], "0x9");
+    assert_eq!(lines[4]["output"], "0x");
+}
+
+/// Run `code` and return the word it leaves on top of the stack.
+fn run_word(code: &[u8]) -> U256 {
+    // PUSH1 0 MSTORE PUSH1 32 PUSH1 0 RETURN
+    let output = run_code(&[code, &hex!("6000 52 6020 6000 f3")].concat());
+    assert_eq!(output.error, None);
+    U256::from_bytes_be(&output.output)
+}
+
+const MINUS_ONE: [u8; 33] = hex!("7f ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff");
+
+#[test]
+fn test_run_wrapping_arithmetic() {
+    let max = U256::from_bytes_be(&[0xff; 32]);
+    // (2 ** 256 - 1) + 2, 0 - 1 and 2 ** 255 * 2.
+    assert_eq!(run_word(&[&hex!("6002")[..], &MINUS_ONE, &hex!("01")].concat()), U256::from(1u8));
+    assert_eq!(run_word(&hex!("6001 6000 03")), max);
+    assert_eq!(
+        run_word(&hex!("6002 7f 8000000000000000000000000000000000000000000000000000000000000000 02")),
+        U256::from(0u8)
+    );
+}
+
+#[test]
+fn test_run_signed_division() {
+    let minus = |n: u8| U256::from_bytes_be(&[0xff; 32]) - (n - 1);
+    // -7 / 2, 7 / -2 and -2 ** 255 / -1, which overflows back to itself.
+    assert_eq!(run_word(&hex!("6002 6007 6000 03 05")), minus(3));
+    assert_eq!(run_word(&hex!("6002 6000 03 6007 05")), minus(3));
+    let min = hex!("7f 8000000000000000000000000000000000000000000000000000000000000000");
+    assert_eq!(
+        run_word(&[&MINUS_ONE[..], &min, &hex!("05")].concat()),
+        U256::from_bytes_be(&min[1..])
+    );
+    // The remainder takes the sign of the dividend: -7 % 3 and 7 % -3.
+    assert_eq!(run_word(&hex!("6003 6007 6000 03 07")), minus(1));
+    assert_eq!(run_word(&hex!("6003 6000 03 6007 07")), U256::from(1u8));
+    // Division by zero is zero.
+    assert_eq!(run_word(&[&hex!("6000")[..], &MINUS_ONE, &hex!("05")].concat()), U256::from(0u8));
+}
+
+#[test]
+fn test_run_signed_comparison() {
+    // -1 < 0 and 0 > -1, but not the other way around.
+    assert_eq!(run_word(&[&hex!("6000")[..], &MINUS_ONE, &hex!("12")].concat()), U256::from(1u8));
+    assert_eq!(run_word(&[&MINUS_ONE[..], &hex!("6000 13")].concat()), U256::from(1u8));
+    assert_eq!(run_word(&[&MINUS_ONE[..], &hex!("6000 12")].concat()), U256::from(0u8));
+    assert_eq!(run_word(&[&hex!("6000")[..], &MINUS_ONE, &hex!("13")].concat()), U256::from(0u8));
+    // Unsigned, -1 is the largest word.
+    assert_eq!(run_word(&[&hex!("6000")[..], &MINUS_ONE, &hex!("11")].concat()), U256::from(1u8));
+}
+
+#[test]
+fn test_run_not() {
+    let max = U256::from_bytes_be(&[0xff; 32]);
+    assert_eq!(run_word(&hex!("6000 19")), max);
+    assert_eq!(run_word(&hex!("6001 19")), &max - 1u8);
+    assert_eq!(run_word(&[&MINUS_ONE[..], &hex!("19")].concat()), U256::from(0u8));
+}
+
+#[test]
+fn test_run_exp() {
+    // 3 ** 5, 2 ** 256 and 1 ** (2 ** 255), whose exponent is too large
+    // to raise to directly.
+    assert_eq!(run_word(&hex!("6005 6003 0a")), U256::from(243u8));
+    assert_eq!(run_word(&hex!("610100 6002 0a")), U256::from(0u8));
+    assert_eq!(
+        run_word(&hex!("7f 8000000000000000000000000000000000000000000000000000000000000000 6001 0a")),
+        U256::from(1u8)
+    );
+    // 3 ** (2 ** 256 - 1) is the inverse of 3 modulo 2 ** 256.
+    assert_eq!(
+        run_word(&[&MINUS_ONE[..], &hex!("6003 0a")].concat()),
+        U256::from_bytes_be(&hex!("aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaab"))
+    );
+}
+
+#[test]
+fn test_run_dup_swap_underflow() {
+    // DUP1 on an empty stack, DUP2 and SWAP1 with one item.
+    for code in [&hex!("80")[..], &hex!("6001 81"), &hex!("6001 90")] {
+        assert_eq!(run_code(code).error, Some(EvmError::StackUnderflow));
+    }
+    assert_eq!(run_word(&hex!("6001 6002 90")), U256::from(1u8));
+}
+
+#[test]
+fn test_run_empty_memory_and_buffer_ranges() {
+    let far = hex!("7f ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff");
+    // Copying or returning nothing at any offset touches no memory.
+    let output = run_code(&[&hex!("6000 6000")[..], &far, &hex!("37 6000"), &far, &hex!("f3")].concat());
+    assert_eq!(output.error, None);
+    assert!(output.output.is_empty());
+    // Reading calldata past its end gives zeros.
+    let output = run(
+        RunConfig {
+            code: hex!("6001 35 6000 52 6020 6000 f3").into(),
+            input: hex!("aabb").into(),
+            ..Default::default()
+        },
+        None,
+    );
+    let mut word = [0; 32];
+    word[0] = 0xbb;
+    assert_eq!(*output.output, word);
+    assert_eq!(run_word(&hex!("6040 35")), U256::from(0u8));
+}
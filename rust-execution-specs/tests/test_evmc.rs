@@ -0,0 +1,272 @@
+use std::{collections::HashMap, ffi::CStr, slice};
+
+use execution_specs_rs::ethereum_spec_tools::evm_tools::evmc::{
+    evmc_create_execution_specs_rs, EvmcAddress, EvmcBytes32, EvmcHostContext,
+    EvmcHostInterface, EvmcMessage, EvmcResult, EvmcTxContext, EVMC_ABI_VERSION,
+    EVMC_BAD_JUMP_DESTINATION, EVMC_CALL, EVMC_FRONTIER, EVMC_REJECTED, EVMC_SUCCESS,
+};
+
+const RECIPIENT: [u8; 20] = [0xcc; 20];
+const OTHER: [u8; 20] = [0xdd; 20];
+const GAS: i64 = 100_000;
+
+/// A host keeping its state in maps, and recording the calls made to it.
+#[derive(Default)]
+struct MockHost {
+    storage: HashMap<([u8; 20], [u8; 32]), [u8; 32]>,
+    balances: HashMap<[u8; 20], [u8; 32]>,
+    logs: Vec<([u8; 20], Vec<u8>, usize)>,
+    calls: Vec<EvmcMessage>,
+    call_output: Vec<u8>,
+    number: i64,
+}
+
+unsafe fn host<'a>(context: *mut EvmcHostContext) -> &'a mut MockHost {
+    &mut *(context as *mut MockHost)
+}
+
+unsafe extern "C" fn account_exists(context: *mut EvmcHostContext, address: *const EvmcAddress) -> bool {
+    host(context).balances.contains_key(&(*address).bytes)
+}
+
+unsafe extern "C" fn get_storage(
+    context: *mut EvmcHostContext,
+    address: *const EvmcAddress,
+    key: *const EvmcBytes32,
+) -> EvmcBytes32 {
+    let value = host(context).storage.get(&((*address).bytes, (*key).bytes));
+    EvmcBytes32 {
+        bytes: value.copied().unwrap_or_default(),
+    }
+}
+
+unsafe extern "C" fn set_storage(
+    context: *mut EvmcHostContext,
+    address: *const EvmcAddress,
+    key: *const EvmcBytes32,
+    value: *const EvmcBytes32,
+) -> i32 {
+    host(context)
+        .storage
+        .insert(((*address).bytes, (*key).bytes), (*value).bytes);
+    0
+}
+
+unsafe extern "C" fn get_balance(context: *mut EvmcHostContext, address: *const EvmcAddress) -> EvmcBytes32 {
+    EvmcBytes32 {
+        bytes: host(context).balances.get(&(*address).bytes).copied().unwrap_or_default(),
+    }
+}
+
+unsafe extern "C" fn get_code_size(_: *mut EvmcHostContext, _: *const EvmcAddress) -> usize {
+    0
+}
+
+unsafe extern "C" fn get_code_hash(_: *mut EvmcHostContext, _: *const EvmcAddress) -> EvmcBytes32 {
+    EvmcBytes32::default()
+}
+
+unsafe extern "C" fn copy_code(
+    _: *mut EvmcHostContext,
+    _: *const EvmcAddress,
+    _: usize,
+    _: *mut u8,
+    _: usize,
+) -> usize {
+    0
+}
+
+unsafe extern "C" fn selfdestruct(
+    _: *mut EvmcHostContext,
+    _: *const EvmcAddress,
+    _: *const EvmcAddress,
+) -> bool {
+    true
+}
+
+unsafe extern "C" fn call(context: *mut EvmcHostContext, message: *const EvmcMessage) -> EvmcResult {
+    let host = host(context);
+    host.calls.push(*message);
+    EvmcResult {
+        status_code: EVMC_SUCCESS,
+        gas_left: (*message).gas,
+        gas_refund: 0,
+        output_data: host.call_output.as_ptr(),
+        output_size: host.call_output.len(),
+        release: None,
+        create_address: EvmcAddress::default(),
+        padding: [0; 4],
+    }
+}
+
+unsafe extern "C" fn get_tx_context(context: *mut EvmcHostContext) -> EvmcTxContext {
+    EvmcTxContext {
+        block_number: host(context).number,
+        ..Default::default()
+    }
+}
+
+unsafe extern "C" fn get_block_hash(_: *mut EvmcHostContext, number: i64) -> EvmcBytes32 {
+    EvmcBytes32 {
+        bytes: [number as u8; 32],
+    }
+}
+
+unsafe extern "C" fn emit_log(
+    context: *mut EvmcHostContext,
+    address: *const EvmcAddress,
+    data: *const u8,
+    data_size: usize,
+    _: *const EvmcBytes32,
+    topics_count: usize,
+) {
+    let data = slice::from_raw_parts(data, data_size).to_vec();
+    host(context).logs.push(((*address).bytes, data, topics_count));
+}
+
+unsafe extern "C" fn access_account(_: *mut EvmcHostContext, _: *const EvmcAddress) -> i32 {
+    0
+}
+
+unsafe extern "C" fn access_storage(
+    _: *mut EvmcHostContext,
+    _: *const EvmcAddress,
+    _: *const EvmcBytes32,
+) -> i32 {
+    0
+}
+
+const INTERFACE: EvmcHostInterface = EvmcHostInterface {
+    account_exists,
+    get_storage,
+    set_storage,
+    get_balance,
+    get_code_size,
+    get_code_hash,
+    copy_code,
+    selfdestruct,
+    call,
+    get_tx_context,
+    get_block_hash,
+    emit_log,
+    access_account,
+    access_storage,
+};
+
+/// Run `code` as a call to `RECIPIENT`, returning the status, gas left and
+/// output.
+fn execute(host: &mut MockHost, rev: i32, code: &[u8]) -> (i32, i64, Vec<u8>) {
+    let message = EvmcMessage {
+        kind: EVMC_CALL,
+        flags: 0,
+        depth: 0,
+        gas: GAS,
+        recipient: EvmcAddress { bytes: RECIPIENT },
+        sender: EvmcAddress::default(),
+        input_data: std::ptr::null(),
+        input_size: 0,
+        value: EvmcBytes32::default(),
+        create2_salt: EvmcBytes32::default(),
+        code_address: EvmcAddress { bytes: RECIPIENT },
+    };
+    unsafe {
+        let vm = evmc_create_execution_specs_rs();
+        let context = host as *mut MockHost as *mut EvmcHostContext;
+        let result =
+            ((*vm).execute.unwrap())(vm, &INTERFACE, context, rev, &message, code.as_ptr(), code.len());
+        let output = if result.output_size == 0 {
+            vec![]
+        } else {
+            slice::from_raw_parts(result.output_data, result.output_size).to_vec()
+        };
+        (result.release.unwrap())(&result);
+        ((*vm).destroy.unwrap())(vm);
+        (result.status_code, result.gas_left, output)
+    }
+}
+
+#[test]
+fn test_vm_metadata() {
+    unsafe {
+        let vm = evmc_create_execution_specs_rs();
+        assert_eq!((*vm).abi_version, EVMC_ABI_VERSION);
+        assert_eq!(CStr::from_ptr((*vm).name).to_str().unwrap(), "execution-specs-rs");
+        assert_eq!(((*vm).get_capabilities.unwrap())(vm), 1);
+        ((*vm).destroy.unwrap())(vm);
+    }
+}
+
+#[test]
+fn test_storage_goes_through_the_host() {
+    let mut host = MockHost::default();
+    // SSTORE(1, 42), then return SLOAD(1).
+    let code = [
+        0x60, 0x2a, 0x60, 0x01, 0x55, 0x60, 0x01, 0x54, 0x60, 0x00, 0x52, 0x60, 0x20, 0x60, 0x00, 0xf3,
+    ];
+    let (status, gas_left, output) = execute(&mut host, EVMC_FRONTIER, &code);
+
+    assert_eq!(status, EVMC_SUCCESS);
+    assert_eq!(GAS - gas_left, 3 + 3 + 20000 + 3 + 50 + 3 + 3 + 3 + 3 + 3);
+    assert_eq!(output[31], 42);
+    let mut key = [0; 32];
+    key[31] = 1;
+    assert_eq!(host.storage[&(RECIPIENT, key)][31], 42);
+}
+
+#[test]
+fn test_calls_go_through_the_host() {
+    let mut host = MockHost {
+        call_output: vec![1, 2],
+        ..Default::default()
+    };
+    host.balances.insert(OTHER, [0; 32]);
+    // CALL(0x1000, OTHER, 0, 0, 0, 0, 2), then return the first word of
+    // memory.
+    let mut code = vec![0x60, 0x02, 0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x73];
+    code.extend(OTHER);
+    code.extend([0x61, 0x10, 0x00, 0xf1, 0x60, 0x20, 0x60, 0x00, 0xf3]);
+    let (status, gas_left, output) = execute(&mut host, EVMC_FRONTIER, &code);
+
+    assert_eq!(status, EVMC_SUCCESS);
+    // The call is charged, less the gas the host returned, and one word of
+    // memory is used.
+    assert_eq!(GAS - gas_left, 7 * 3 + 40 + 3 + 2 * 3);
+    assert_eq!(output[..3], [1, 2, 0]);
+    let [message] = host.calls.as_slice() else {
+        panic!("expected one call, got {}", host.calls.len());
+    };
+    assert_eq!(message.kind, EVMC_CALL);
+    assert_eq!(message.depth, 1);
+    assert_eq!(message.gas, 0x1000);
+    assert_eq!(message.sender.bytes, RECIPIENT);
+    assert_eq!(message.recipient.bytes, OTHER);
+}
+
+#[test]
+fn test_block_and_logs_go_through_the_host() {
+    let mut host = MockHost {
+        number: 10,
+        ..Default::default()
+    };
+    // LOG1(0, 32) of BLOCKHASH(NUMBER - 1), with one topic.
+    let code = [
+        0x60, 0x01, 0x43, 0x03, 0x40, 0x60, 0x00, 0x52, 0x60, 0x07, 0x60, 0x20, 0x60, 0x00, 0xa1,
+    ];
+    let (status, _, _) = execute(&mut host, EVMC_FRONTIER, &code);
+
+    assert_eq!(status, EVMC_SUCCESS);
+    assert_eq!(host.logs, [(RECIPIENT, vec![9; 32], 1)]);
+}
+
+#[test]
+fn test_failures() {
+    let mut host = MockHost::default();
+
+    // JUMP(0), which is not a JUMPDEST.
+    let (status, gas_left, _) = execute(&mut host, EVMC_FRONTIER, &[0x60, 0x00, 0x56]);
+    assert_eq!((status, gas_left), (EVMC_BAD_JUMP_DESTINATION, 0));
+
+    // Only Frontier is implemented.
+    let (status, _, _) = execute(&mut host, EVMC_FRONTIER + 1, &[0x00]);
+    assert_eq!(status, EVMC_REJECTED);
+}
@@ -0,0 +1,45 @@
+use std::error::Error;
+
+use execution_specs_rs::ethereum::{
+    base_types::Uint,
+    exceptions::{ErrorContext, EthereumException},
+};
+
+fn failing() -> Result<(), EthereumException> {
+    Err(EthereumException::InvalidBlock)
+}
+
+#[test]
+fn test_context() {
+    let error = failing()
+        .in_account(&[0xaa; 20])
+        .in_transaction(2)
+        .in_block(&Uint::from(46147u32))
+        .unwrap_err();
+    assert!(matches!(error.root_cause(), EthereumException::InvalidBlock));
+    assert_eq!(error.block_number(), Some(&Uint::from(46147u32)));
+    assert_eq!(error.transaction_index(), Some(2));
+    assert_eq!(error.address(), Some(&[0xaa; 20]));
+    assert_eq!(
+        error.to_string(),
+        format!(
+            "block 46147: transaction 2: account 0x{}: invalid block",
+            "aa".repeat(20)
+        )
+    );
+
+    let source = error.source().unwrap().to_string();
+    assert!(source.starts_with("transaction 2: "));
+    assert!(EthereumException::InvalidBlock.source().is_none());
+}
+
+#[test]
+fn test_no_context() {
+    let error = failing().unwrap_err();
+    assert!(matches!(error.root_cause(), EthereumException::InvalidBlock));
+    assert_eq!(error.block_number(), None);
+    assert_eq!(error.transaction_index(), None);
+    assert_eq!(error.address(), None);
+    assert_eq!(error.to_string(), "invalid block");
+    assert!(Ok::<_, EthereumException>(()).in_block(&Uint::from(1u8)).is_ok());
+}
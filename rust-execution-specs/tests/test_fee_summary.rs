@@ -0,0 +1,22 @@
+use execution_specs_rs::ethereum::{
+    base_types::{Uint, U256},
+    fee_summary::BlockFeeSummary,
+};
+
+#[test]
+fn test_fee_summary() {
+    let mut fees = BlockFeeSummary::default();
+    // Before London nothing is burned.
+    fees.add_transaction(&Uint::from(21000u32), &U256::from(10u8), &U256::from(0u8));
+    // A London transaction paying 7 wei per gas over a base fee of 3.
+    fees.add_transaction(&Uint::from(50000u32), &U256::from(10u8), &U256::from(3u8));
+    fees.burned_blob_fee = U256::from(131072u32);
+    fees.miner_reward = U256::from(2u8);
+
+    assert_eq!(fees.transaction_fees, U256::from(710_000u32));
+    assert_eq!(fees.priority_fees, U256::from(210_000u32 + 350_000));
+    assert_eq!(fees.burned_base_fee, U256::from(150_000u32));
+    assert_eq!(fees.burned(), U256::from(150_000u32 + 131072));
+    assert_eq!(fees.issuance(), U256::from(2u8));
+    assert_eq!(fees.coinbase_income(), U256::from(560_002u32));
+}
@@ -0,0 +1,78 @@
+use std::ffi::{CStr, CString};
+
+use execution_specs_rs::ethereum_spec_tools::evm_tools::ffi::{
+    es_vm_execute, es_vm_free, es_vm_load_prestate, es_vm_new, es_vm_result, EsVm,
+    ES_INVALID_INPUT, ES_INVALID_TRANSACTION, ES_OK,
+};
+use serde_json::Value;
+
+const PRESTATE: &str = r#"{
+    "0xaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa": { "balance": "1000000" },
+    "0xcccccccccccccccccccccccccccccccccccccccc": { "code": "0x60016000a0" }
+}"#;
+
+fn load(vm: *mut EsVm, alloc: &str) -> i32 {
+    let alloc = CString::new(alloc).unwrap();
+    unsafe { es_vm_load_prestate(vm, alloc.as_ptr()) }
+}
+
+fn execute(vm: *mut EsVm, tx: &str) -> i32 {
+    let tx = CString::new(tx).unwrap();
+    unsafe { es_vm_execute(vm, tx.as_ptr()) }
+}
+
+fn result(vm: *mut EsVm) -> Value {
+    let result = unsafe { CStr::from_ptr(es_vm_result(vm)) };
+    serde_json::from_str(result.to_str().unwrap()).unwrap()
+}
+
+#[test]
+fn test_execute_transactions() {
+    let vm = es_vm_new();
+    assert_eq!(load(vm, PRESTATE), ES_OK);
+
+    // LOG0 of one byte of memory.
+    let tx = r#"{
+        "from": "0xaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa",
+        "to": "0xcccccccccccccccccccccccccccccccccccccccc",
+        "gas": "0x10000",
+        "gasPrice": "0x1",
+        "value": "0x5"
+    }"#;
+    assert_eq!(execute(vm, tx), ES_OK);
+    let result = result(vm);
+    let gas_used = 21000 + 3 + 3 + 375 + 8 + 3;
+    assert_eq!(result["gasUsed"], format!("{gas_used:#x}"));
+    assert_eq!(result["logs"][0]["address"], "0xcccccccccccccccccccccccccccccccccccccccc");
+    assert_eq!(result["logs"][0]["data"], "0x00");
+    let accounts = &result["postState"]["accounts"];
+    assert_eq!(
+        accounts["0xaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa"]["balance"],
+        (1_000_000 - 5 - gas_used).to_string()
+    );
+    assert_eq!(accounts["0xaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa"]["nonce"], 1);
+    assert_eq!(accounts["0xcccccccccccccccccccccccccccccccccccccccc"]["balance"], "5");
+    assert_eq!(result["stateRoot"], result["postState"]["root"]);
+
+    // The nonce has moved on.
+    assert_eq!(execute(vm, tx), ES_INVALID_TRANSACTION);
+    assert!(self::result(vm)["error"].as_str().unwrap().starts_with("NonceMismatch"));
+
+    unsafe { es_vm_free(vm) };
+}
+
+#[test]
+fn test_invalid_input() {
+    let vm = es_vm_new();
+    assert_eq!(load(vm, "[]"), ES_INVALID_INPUT);
+    assert!(result(vm)["error"].is_string());
+    assert_eq!(execute(vm, "{"), ES_INVALID_INPUT);
+    assert_eq!(unsafe { es_vm_execute(vm, std::ptr::null()) }, ES_INVALID_INPUT);
+
+    // Unsigned, and without a sender.
+    assert_eq!(execute(vm, r#"{"gas": "0x5208"}"#), ES_INVALID_TRANSACTION);
+    assert_eq!(result(vm)["error"], "invalid signature");
+
+    unsafe { es_vm_free(vm) };
+    unsafe { es_vm_free(std::ptr::null_mut()) };
+}
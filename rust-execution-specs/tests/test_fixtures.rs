@@ -0,0 +1,282 @@
+use std::{fs, path::PathBuf, slice};
+
+use execution_specs_rs::{
+    ethereum::{
+        base_types::U256,
+        frontier::{
+            fork::{apply_body, calculate_block_difficulty, get_last_256_block_hashes},
+            fork_types::{Block, Header, Transaction},
+            state::state_root,
+            vm::BlockEnvironment,
+        },
+        rlp,
+        transaction_signature::sign_transaction,
+        utils::hexadecimal::{hex, hex_to_bytes},
+    },
+    ethereum_spec_tools::{
+        block_rlp::decode_block,
+        chain::Chain,
+        evm_tools::{
+            alloc::dump_alloc,
+            fixtures::{
+                run_fixtures, run_fixtures_parallel, run_fixtures_with_coverage, run_test, RunMode, RunSummary,
+                Status,
+            },
+            results::ResultsDb,
+            state_test::{StateTestFiller, StateTestTx, SECRET_KEY},
+        },
+        forks::HardFork,
+    },
+};
+use serde_json::{json, Value};
+
+const CONTRACT: [u8; 20] = [0xcc; 20];
+
+fn temp_dir(name: &str) -> PathBuf {
+    let directory = std::env::temp_dir().join(format!("{name}-{}", std::process::id()));
+    fs::create_dir_all(&directory).unwrap();
+    directory
+}
+
+/// A state test storing 1 at slot 0, filled for Frontier and claiming
+/// results for Berlin too.
+fn state_test() -> Value {
+    // PUSH1 1, PUSH1 0, SSTORE
+    let mut fixture = StateTestFiller::new("sstore")
+        .with_code(CONTRACT, [0x60, 0x01, 0x60, 0x00, 0x55])
+        .with_transaction(StateTestTx::call(CONTRACT).gas_limit([U256::from(100_000u32), U256::from(20_000u32)]))
+        .fill(&[HardFork::Frontier])
+        .unwrap();
+    fixture["sstore"]["post"]["Berlin"] = fixture["sstore"]["post"]["Frontier"].clone();
+    fixture
+}
+
+#[test]
+fn test_state_test() {
+    let fixture = state_test();
+    let test = &fixture["sstore"];
+    assert_eq!(run_test(test, "Frontier"), Status::Passed);
+    assert_eq!(run_test(test, "Berlin"), Status::Unsupported);
+
+    let mut wrong = test.clone();
+    wrong["post"]["Frontier"][0]["hash"] = json!(hex(&[0; 32]));
+    match run_test(&wrong, "Frontier") {
+        Status::Failed(message) => assert!(message.starts_with("data 0 gas 0 value 0: state root"), "{message}"),
+        status => panic!("unexpected {status:?}"),
+    }
+
+    // The second gas limit is too low for the intrinsic cost.
+    let mut wrong = test.clone();
+    wrong["post"]["Frontier"][1].as_object_mut().unwrap().remove("expectException");
+    match run_test(&wrong, "Frontier") {
+        Status::Failed(message) => assert!(message.contains("unexpected TransactionException"), "{message}"),
+        status => panic!("unexpected {status:?}"),
+    }
+}
+
+/// A blockchain test of one valid block with a transaction, followed by
+/// an invalid one.
+fn blockchain_test() -> Value {
+    let genesis = json!({
+        "config": { "chainId": 1 },
+        "nonce": "0x0000000000000042",
+        "timestamp": "0x0",
+        "extraData": "0x",
+        "gasLimit": "0x2fefd8",
+        "difficulty": "0x20000",
+        "alloc": { "0xa94f5374fce5edbc8e2a8697c15331677e6ebf0b": { "balance": "0xde0b6b3a7640000" } }
+    });
+    let chain = Chain::from_genesis(&genesis.to_string()).unwrap();
+    let genesis_block = chain.blocks()[0].clone();
+    let parent = genesis_block.header.clone();
+
+    let tx = Transaction {
+        nonce: U256::from(0u8),
+        gas_price: U256::from(10u8),
+        gas: U256::from(21_000u32),
+        to: Some(CONTRACT),
+        value: U256::from(5u8),
+        data: Default::default(),
+        v: U256::default(),
+        r: U256::default(),
+        s: U256::default(),
+    };
+    let tx = sign_transaction(&tx, &SECRET_KEY, None).unwrap();
+    let number = &parent.number + 1u8;
+    let timestamp = &parent.timestamp + 15u8;
+    let difficulty = calculate_block_difficulty(&number, &timestamp, &parent.timestamp, &parent.difficulty).unwrap();
+    let block_env = BlockEnvironment {
+        block_hashes: get_last_256_block_hashes(chain.blockchain()).unwrap(),
+        coinbase: [0xbb; 20],
+        number: number.clone(),
+        gas_limit: parent.gas_limit.clone(),
+        time: timestamp.clone(),
+        difficulty: difficulty.clone(),
+    };
+    let mut state = chain.state().clone();
+    let (gas_used, transactions_root, receipt_root, bloom, _, _, _) =
+        apply_body(&mut state, &block_env, slice::from_ref(&tx), &[]).unwrap();
+    let header = Header {
+        parent_hash: rlp::rlp_hash(&parent),
+        ommers_hash: rlp::rlp_hash(&()),
+        coinbase: [0xbb; 20],
+        state_root: state_root(&state),
+        transactions_root,
+        receipt_root,
+        bloom,
+        difficulty,
+        number,
+        gas_limit: parent.gas_limit.clone(),
+        gas_used,
+        timestamp,
+        extra_data: vec![].into(),
+        mix_digest: [0; 32],
+        nonce: [0; 8],
+    };
+    let block = Block {
+        header: header.clone(),
+        transactions: vec![tx],
+        ommers: vec![],
+    };
+    // The same block again, on top of itself: not later than its parent.
+    let mut invalid = block.clone();
+    invalid.header.parent_hash = rlp::rlp_hash(&header);
+    invalid.header.number += 1u8;
+
+    json!({
+        "chain": {
+            "network": "Frontier",
+            "genesisRLP": hex(&rlp::encode(&genesis_block)),
+            "pre": dump_alloc(chain.state()),
+            "blocks": [
+                { "rlp": hex(&rlp::encode(&block)) },
+                { "rlp": hex(&rlp::encode(&invalid)), "expectException": "BlockException.INVALID_BLOCK_TIMESTAMP_OLDER_THAN_PARENT" },
+            ],
+            "lastblockhash": hex(&rlp::rlp_hash(&header)),
+            "postState": dump_alloc(&state),
+        }
+    })
+}
+
+#[test]
+fn test_blockchain_test() {
+    let fixture = blockchain_test();
+    let test = &fixture["chain"];
+    assert_eq!(run_test(test, "Frontier"), Status::Passed);
+
+    let encoded = hex_to_bytes(test["blocks"][0]["rlp"].as_str().unwrap()).unwrap();
+    let block = decode_block(&encoded, HardFork::Frontier).unwrap();
+    assert_eq!(rlp::encode(&block)[..], encoded[..]);
+    assert_eq!(block.transactions.len(), 1);
+
+    let mut wrong = test.clone();
+    wrong["blocks"][1].as_object_mut().unwrap().remove("expectException");
+    match run_test(&wrong, "Frontier") {
+        Status::Failed(message) => assert!(message.starts_with("block 1 is invalid"), "{message}"),
+        status => panic!("unexpected {status:?}"),
+    }
+
+    let mut wrong = test.clone();
+    wrong["blocks"][0]["expectException"] = json!("BlockException.INVALID_STATE_ROOT");
+    assert!(matches!(run_test(&wrong, "Frontier"), Status::Failed(message) if message.starts_with("block 0 is valid")));
+}
+
+#[test]
+fn test_resumable_runs() {
+    let directory = temp_dir("fixture-runner");
+    let fixtures = directory.join("fixtures");
+    fs::create_dir_all(fixtures.join("state")).unwrap();
+    fs::write(fixtures.join("state/sstore.json"), state_test().to_string()).unwrap();
+    fs::write(fixtures.join("chain.json"), blockchain_test().to_string()).unwrap();
+    let results = directory.join("results.json");
+    let paths = [fixtures.clone()];
+
+    let mut db = ResultsDb::open(&results).unwrap();
+    let summary = run_fixtures(&paths, &mut db, RunMode::Changed).unwrap();
+    assert_eq!((summary.passed(), summary.failed(), summary.unsupported()), (2, 0, 1));
+    // The unsupported fork isn't recorded.
+    assert_eq!(ResultsDb::open(&results).unwrap().len(), 2);
+
+    // Nothing changed, so nothing runs again.
+    let mut db = ResultsDb::open(&results).unwrap();
+    let summary = run_fixtures(&paths, &mut db, RunMode::Changed).unwrap();
+    assert_eq!((summary.passed(), summary.skipped()), (0, 2));
+
+    // Breaking the state test runs it again.
+    let mut broken = state_test();
+    broken["sstore"]["post"]["Frontier"][0]["logs"] = json!(hex(&[0; 32]));
+    fs::write(fixtures.join("state/sstore.json"), broken.to_string()).unwrap();
+    let summary = run_fixtures(&paths, &mut db, RunMode::Changed).unwrap();
+    assert_eq!((summary.passed(), summary.failed(), summary.skipped()), (0, 1, 1));
+    let (id, message) = summary.failures().next().unwrap();
+    assert_eq!(id.to_string(), format!("{}::sstore::Frontier", fixtures.join("state/sstore.json").display()));
+    assert!(message.contains("logs hash"), "{message}");
+
+    // Only the failure is retried, and once fixed it passes.
+    fs::write(fixtures.join("state/sstore.json"), state_test().to_string()).unwrap();
+    let mut db = ResultsDb::open(&results).unwrap();
+    let summary = run_fixtures(&paths, &mut db, RunMode::OnlyFailed).unwrap();
+    assert_eq!((summary.passed(), summary.skipped(), summary.unsupported()), (1, 2, 0));
+    let summary = run_fixtures(&paths, &mut db, RunMode::OnlyFailed).unwrap();
+    assert_eq!(summary.passed(), 0);
+
+    let summary = run_fixtures(&paths, &mut db, RunMode::All).unwrap();
+    assert_eq!(summary.passed(), 2);
+    fs::remove_dir_all(directory).unwrap();
+}
+
+#[test]
+fn test_parallel_runs() {
+    let directory = temp_dir("parallel-fixture-runner");
+    for index in 0..6 {
+        fs::write(directory.join(format!("sstore{index}.json")), state_test().to_string()).unwrap();
+    }
+    fs::write(directory.join("chain.json"), blockchain_test().to_string()).unwrap();
+    fs::write(directory.join("broken.json"), "[\"<not a test>\"]").unwrap();
+    let paths = [directory.clone()];
+
+    let sequential = run_fixtures(&paths, &mut ResultsDb::new(), RunMode::All).unwrap();
+    let parallel = run_fixtures_parallel(&paths, &mut ResultsDb::new(), RunMode::All, 4).unwrap();
+    // The same outcomes, in the order of the files.
+    let statuses = |summary: &RunSummary| {
+        summary.outcomes.iter().map(|outcome| (outcome.id.clone(), outcome.status.clone())).collect::<Vec<_>>()
+    };
+    assert_eq!(statuses(&parallel), statuses(&sequential));
+    assert_eq!((parallel.passed(), parallel.failed(), parallel.unsupported()), (7, 1, 6));
+    // The broken file fails alone.
+    assert_eq!(parallel.outcomes[0].id.file, directory.join("broken.json").display().to_string());
+
+    let xml = parallel.junit_xml();
+    assert!(xml.starts_with("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<testsuites name=\"execution-specs-rs\" tests=\"14\" failures=\"1\" skipped=\"6\""));
+    assert_eq!(xml.matches("<testsuite ").count(), 8);
+    assert!(xml.contains("name=\"sstore[Frontier]\""));
+    assert!(xml.contains("<skipped message=\"unsupported fork\"/>"));
+    assert!(!xml.contains("<not a test>"));
+    assert!(xml.contains("<failure message=\""));
+    fs::remove_dir_all(directory).unwrap();
+}
+
+#[test]
+fn test_coverage() {
+    let directory = temp_dir("fixture-coverage");
+    fs::write(directory.join("sstore.json"), state_test().to_string()).unwrap();
+    fs::write(directory.join("chain.json"), blockchain_test().to_string()).unwrap();
+    let paths = [directory.clone()];
+
+    let summary = run_fixtures_parallel(&paths, &mut ResultsDb::new(), RunMode::All, 2).unwrap();
+    assert!(summary.coverage.is_empty());
+
+    let summary = run_fixtures_with_coverage(&paths, &mut ResultsDb::new(), RunMode::All, 2).unwrap();
+    assert_eq!(summary.passed(), 2);
+    // Nothing ran in the unsupported fork.
+    assert_eq!(summary.coverage.keys().collect::<Vec<_>>(), ["Frontier"]);
+    let frontier = &summary.coverage["Frontier"];
+    assert!(frontier.opcodes[&0x55] >= 1);
+    assert!(frontier.exceptions.is_empty());
+
+    let report = &summary.coverage_report()["Frontier"];
+    assert_eq!(report["opcodes"]["counts"]["SSTORE"], json!(frontier.opcodes[&0x55]));
+    assert!(report["opcodes"]["missing"].as_array().unwrap().contains(&json!("MUL")));
+    assert_eq!(report["exceptions"]["covered"], 0);
+    fs::remove_dir_all(directory).unwrap();
+}
@@ -0,0 +1,41 @@
+use std::collections::HashSet;
+
+use execution_specs_rs::{
+    ethereum::{
+        base_types::{Uint, U256},
+        fork_constants::{compare_constants, fork_constant, fork_constants},
+        frontier::{
+            constants::{BLOCK_REWARD, GAS_CALL_STIPEND, GAS_SLOAD, MAX_OMMER_DEPTH, TX_BASE_COST},
+            fork,
+        },
+    },
+    ethereum_spec_tools::forks::HardFork,
+};
+
+#[test]
+fn test_fork_constants() {
+    let constants = fork_constants(HardFork::Frontier);
+    let names: HashSet<&str> = constants.iter().map(|constant| constant.name).collect();
+    assert_eq!(names.len(), constants.len(), "duplicate names");
+    assert!(constants.iter().all(|constant| !constant.source.is_empty()));
+
+    // The table holds the values the fork uses.
+    let value = |name| fork_constant(HardFork::Frontier, name).unwrap().value;
+    assert_eq!(U256::from(value("BLOCK_REWARD")), BLOCK_REWARD());
+    assert_eq!(Uint::from(value("GAS_SLOAD")), GAS_SLOAD());
+    assert_eq!(Uint::from(value("GAS_CALL_STIPEND")), GAS_CALL_STIPEND());
+    assert_eq!(value("TX_BASE_COST"), TX_BASE_COST);
+    assert_eq!(value("MAX_OMMER_DEPTH"), u64::from(MAX_OMMER_DEPTH));
+    assert_eq!(
+        fork_constant(HardFork::Frontier, "GAS_SLOAD").unwrap().source,
+        "Yellow Paper, Appendix G (G_sload)"
+    );
+
+    // Frontier predates EIP-170.
+    assert_eq!(fork_constant(HardFork::Frontier, "MAX_CODE_SIZE"), None);
+
+    // The modules using the constants re-export them.
+    assert_eq!(fork::GAS_LIMIT_MINIMUM, value("GAS_LIMIT_MINIMUM"));
+
+    assert_eq!(compare_constants(HardFork::Frontier, HardFork::Frontier), vec![]);
+}
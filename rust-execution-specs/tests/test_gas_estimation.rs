@@ -0,0 +1,170 @@
+use execution_specs_rs::{
+    ethereum::{
+        base_types::{Uint, U256},
+        frontier::{
+            fork_types::{Account, Address},
+            state::{set_account, state_root, State},
+            vm::{exceptions::EvmError, BlockEnvironment},
+        },
+    },
+    ethereum_spec_tools::gas_estimation::{estimate_gas, run_call, CallRequest, EstimateError},
+};
+use hex_literal::hex;
+
+const SENDER: Address = [0x10; 20];
+/// Does nothing.
+const CALLEE: Address = [0xcc; 20];
+/// CALL(0x1000, CALLEE, 0, 0, 0, 0, 0), which has to have the gas it asks
+/// for, and gives back what it doesn't use.
+const PROXY: Address = [0xdd; 20];
+/// Fails unless the gas left is over 0x10000.
+const GREEDY: Address = [0xee; 20];
+/// JUMP(0), which is not a JUMPDEST.
+const BAD_JUMP: Address = [0xbb; 20];
+/// Loops forever.
+const LOOP: Address = [0xaa; 20];
+
+fn state() -> State {
+    let mut state = State::default();
+    let mut account = |address: Address, balance: u32, code: &[u8]| {
+        let account = Account {
+            balance: U256::from(balance),
+            code: code.into(),
+            ..Default::default()
+        };
+        set_account(&mut state, address, Some(account));
+    };
+    account(SENDER, 1_000_000, &[]);
+    account(CALLEE, 0, &hex!("00"));
+    account(
+        PROXY,
+        0,
+        &hex!("6000 6000 6000 6000 6000 73cccccccccccccccccccccccccccccccccccccccc 611000 f1 00"),
+    );
+    account(GREEDY, 0, &hex!("5a 62010000 10 600a 57 fe 5b 00"));
+    account(BAD_JUMP, 0, &hex!("6000 56"));
+    account(LOOP, 0, &hex!("5b 6000 56"));
+    state
+}
+
+fn block_env() -> BlockEnvironment {
+    BlockEnvironment {
+        gas_limit: Uint::from(1_000_000u32),
+        ..Default::default()
+    }
+}
+
+fn call(to: Address) -> CallRequest {
+    CallRequest {
+        from: SENDER,
+        to: Some(to),
+        ..Default::default()
+    }
+}
+
+fn succeeds(state: &mut State, request: &CallRequest, gas: &Uint) -> bool {
+    let (result, _) = run_call(state, &block_env(), request, gas, None).unwrap();
+    result.error.is_none()
+}
+
+/// The estimate is the least gas the call succeeds with.
+fn assert_least(state: &mut State, request: &CallRequest, estimate: &Uint) {
+    assert!(succeeds(state, request, estimate));
+    assert!(!succeeds(state, request, &(estimate - 1u8)));
+}
+
+#[test]
+fn test_estimate_gas_used() {
+    let mut state = state();
+    let cap = Uint::from(1_000_000u32);
+    let estimate = estimate_gas(&mut state, &block_env(), &call(CALLEE), &cap).unwrap();
+    assert_eq!(estimate, Uint::from(21000u32));
+
+    let mut transfer = call(SENDER);
+    transfer.data = vec![1].into();
+    let estimate = estimate_gas(&mut state, &block_env(), &transfer, &cap).unwrap();
+    assert_eq!(estimate, Uint::from(21000u32 + 68));
+}
+
+#[test]
+fn test_estimate_gas_call() {
+    let mut state = state();
+    let root = state_root(&state);
+    let request = call(PROXY);
+    let estimate = estimate_gas(&mut state, &block_env(), &request, &Uint::from(1_000_000u32)).unwrap();
+    assert_least(&mut state, &request, &estimate);
+
+    // The callee used none of the gas the call asked for, but it had to be
+    // there.
+    let (_, gas_used) = run_call(&mut state, &block_env(), &request, &estimate, None).unwrap();
+    assert_eq!(gas_used, Uint::from(21000u32 + 5 * 3 + 3 + 3 + 40));
+    assert_eq!(estimate, &gas_used + 0x1000u32);
+    assert_eq!(state_root(&state), root);
+}
+
+#[test]
+fn test_estimate_gas_greedy() {
+    let mut state = state();
+    let request = call(GREEDY);
+    let estimate = estimate_gas(&mut state, &block_env(), &request, &Uint::from(1_000_000u32)).unwrap();
+    assert_least(&mut state, &request, &estimate);
+    // The gas left after GAS has to be over 0x10000.
+    assert_eq!(estimate, Uint::from(21000u32 + 2 + 0x10001));
+}
+
+#[test]
+fn test_estimate_gas_create() {
+    let mut state = state();
+    // RETURN(0, 10): deploys ten zero bytes, for 2000 gas.
+    let request = CallRequest {
+        from: SENDER,
+        to: None,
+        data: hex!("600a 6000 f3").to_vec().into(),
+        ..Default::default()
+    };
+    let estimate = estimate_gas(&mut state, &block_env(), &request, &Uint::from(1_000_000u32)).unwrap();
+    let (result, _) = run_call(&mut state, &block_env(), &request, &estimate, None).unwrap();
+    assert_eq!(result.output.len(), 10);
+
+    // With less, the contract is created without code, which in Frontier
+    // is a success.
+    let (result, _) = run_call(&mut state, &block_env(), &request, &(estimate - 1u8), None).unwrap();
+    assert!(result.error.is_none());
+    assert!(result.output.is_empty());
+}
+
+#[test]
+fn test_estimate_gas_errors() {
+    let mut state = state();
+    let cap = Uint::from(100_000u32);
+    assert_eq!(
+        estimate_gas(&mut state, &block_env(), &call(LOOP), &cap),
+        Err(EstimateError::GasRequiredExceedsAllowance(cap.clone()))
+    );
+    assert_eq!(
+        estimate_gas(&mut state, &block_env(), &call(BAD_JUMP), &cap),
+        Err(EstimateError::Failed(EvmError::InvalidJumpDest))
+    );
+    assert_eq!(
+        estimate_gas(&mut state, &block_env(), &call(CALLEE), &Uint::from(1u8)),
+        Err(EstimateError::IntrinsicGasTooLow {
+            gas: Uint::from(1u8),
+            intrinsic_gas: Uint::from(21000u32),
+        })
+    );
+
+    // The sender can only pay for 1_000_000 / 20 gas.
+    let mut request = call(LOOP);
+    request.gas_price = U256::from(20u8);
+    assert_eq!(
+        estimate_gas(&mut state, &block_env(), &request, &cap),
+        Err(EstimateError::GasRequiredExceedsAllowance(Uint::from(50000u32)))
+    );
+
+    let mut request = call(CALLEE);
+    request.value = U256::from(2_000_000u32);
+    assert_eq!(
+        estimate_gas(&mut state, &block_env(), &request, &cap),
+        Err(EstimateError::InsufficientFunds)
+    );
+}
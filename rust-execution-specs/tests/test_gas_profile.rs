@@ -0,0 +1,106 @@
+use execution_specs_rs::{
+    ethereum::{
+        base_types::U256,
+        frontier::{
+            fork_types::Account,
+            state::{set_account, State},
+            vm::Evm,
+        },
+    },
+    ethereum_spec_tools::evm_tools::gas_profile::{CalleeProfile, GasProfile, GasProfiler, OpcodeProfile},
+};
+use serde_json::json;
+
+const CALLER: [u8; 20] = [0x20; 20];
+const CALLEE: [u8; 20] = [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x10];
+
+/// Profile a call to `CALLER`, whose code calls `CALLEE` with 30000 gas.
+fn profile(callee_code: &[u8]) -> GasProfile {
+    let mut state = State::default();
+    // PUSH1 0 (five times), PUSH1 0x10, PUSH2 30000, CALL, STOP
+    let caller_code = [
+        0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x60, 0x10, 0x61, 0x75, 0x30, 0xf1, 0x00,
+    ];
+    for (address, code) in [(CALLER, &caller_code[..]), (CALLEE, callee_code)] {
+        let account = Account {
+            code: code.into(),
+            ..Default::default()
+        };
+        set_account(&mut state, address, Some(account));
+    }
+
+    let profiler = GasProfiler::new();
+    let profile = profiler.profile();
+    Evm::call()
+        .to(CALLER)
+        .gas(100_000u32.into())
+        .tracer(Box::new(profiler))
+        .run(&mut state)
+        .unwrap();
+    let profile = profile.borrow().clone();
+    profile
+}
+
+fn opcode(count: u64, gas: u32) -> OpcodeProfile {
+    OpcodeProfile {
+        count,
+        gas: U256::from(gas),
+    }
+}
+
+#[test]
+fn test_nested_call() {
+    // PUSH1 1, PUSH1 0, SSTORE
+    let profile = profile(&[0x60, 0x01, 0x60, 0x00, 0x55]);
+
+    // The cost of the call excludes the gas passed on.
+    assert_eq!(profile.opcodes[&0xf1], opcode(1, 40));
+    assert_eq!(profile.opcodes[&0x60], opcode(8, 24));
+    assert_eq!(profile.opcodes[&0x55], opcode(1, 20000));
+    assert_eq!(profile.opcodes[&0x00], opcode(1, 0));
+
+    assert_eq!(
+        profile.callees[&CALLER],
+        CalleeProfile {
+            calls: 1,
+            instructions: 9,
+            gas: U256::from(61u8),
+            inclusive_gas: U256::from(20067u32),
+        }
+    );
+    assert_eq!(
+        profile.callees[&CALLEE],
+        CalleeProfile {
+            calls: 1,
+            instructions: 3,
+            gas: U256::from(20006u32),
+            inclusive_gas: U256::from(20006u32),
+        }
+    );
+
+    assert_eq!(
+        profile.to_json()["callees"][0],
+        json!({
+            "address": "0x0000000000000000000000000000000000000010",
+            "calls": 1,
+            "instructions": 3,
+            "gas": "0x4e26",
+            "inclusiveGas": "0x4e26",
+        })
+    );
+    assert_eq!(
+        profile.opcodes_csv().lines().take(3).collect::<Vec<_>>(),
+        ["op,op_name,count,gas", "0,STOP,1,0", "85,SSTORE,1,20000"]
+    );
+}
+
+#[test]
+fn test_exceptional_halt() {
+    // An undefined opcode takes all the gas of the frame.
+    let profile = profile(&[0xfe]);
+    assert_eq!(profile.opcodes[&0xfe], opcode(1, 30000));
+    assert_eq!(profile.opcodes[&0xf1], opcode(1, 40));
+    assert_eq!(profile.callees[&CALLEE].instructions, 1);
+    assert_eq!(profile.callees[&CALLEE].inclusive_gas, U256::from(30000u32));
+    assert_eq!(profile.callees[&CALLER].inclusive_gas, U256::from(30061u32));
+}
@@ -5,7 +5,12 @@ use num_bigint::BigUint;
 use num_traits::cast::{FromPrimitive};
 use execution_specs_rs::ethereum::base_types::{U256, Bytes};
 use execution_specs_rs::ethereum::exceptions::EthereumException;
-use execution_specs_rs::ethereum::genesis::{get_genesis_configuration, GenesisConfiguration};
+use execution_specs_rs::ethereum::frontier::fork_types::Header;
+use execution_specs_rs::ethereum::frontier::state::{create_ether, state_root, State};
+use execution_specs_rs::ethereum::frontier::trie::EMPTY_TRIE_ROOT;
+use execution_specs_rs::ethereum::frontier::fork::BlockChain;
+use execution_specs_rs::ethereum::genesis::{add_genesis_block, get_genesis_configuration, parse_genesis_configuration, GenesisConfiguration};
+use execution_specs_rs::ethereum_spec_tools::forks::HardFork;
 use execution_specs_rs::ethereum::rlp;
 use execution_specs_rs::ethereum::utils::hexadecimal::hex_to_bytes;
 
@@ -67,3 +72,78 @@ pub fn test_mainnet_genesis_config() {
         BigUint::from_u64(0).unwrap()
     );
 }
+
+#[test]
+pub fn test_parse_genesis_configuration() {
+    // As the library is used without a file system, such as in WebAssembly.
+    let parsed = parse_genesis_configuration(include_str!("../assets/mainnet.json")).unwrap();
+    let read = mainnet_genesis_configuration();
+    assert_eq!(parsed.initial_balances, read.initial_balances);
+    assert_eq!(parsed.chain_id, read.chain_id);
+    assert_eq!(parsed.extra_data, read.extra_data);
+
+    assert!(matches!(
+        parse_genesis_configuration("{"),
+        Err(EthereumException::JsonDecodeError(_))
+    ));
+}
+
+#[test]
+pub fn test_mainnet_genesis_state_root() {
+    let mainnet_genesis_configuration: GenesisConfiguration = mainnet_genesis_configuration();
+
+    let mut state = State::default();
+    for (address, balance) in mainnet_genesis_configuration.initial_balances {
+        create_ether(&mut state, address, balance);
+    }
+
+    assert_eq!(
+        state_root(&state),
+        hex!("d7f8974fb5ac78d9ac099b9ad5018bedc2ce0a72dad1827a1709da30580f0544")
+    );
+}
+
+#[test]
+pub fn test_mainnet_genesis_header_hash() {
+    let mainnet_genesis_configuration: GenesisConfiguration = mainnet_genesis_configuration();
+
+    let header = Header {
+        parent_hash: [0; 32],
+        ommers_hash: rlp::rlp_hash(&()),
+        coinbase: [0; 20],
+        state_root: hex!("d7f8974fb5ac78d9ac099b9ad5018bedc2ce0a72dad1827a1709da30580f0544"),
+        transactions_root: EMPTY_TRIE_ROOT(),
+        receipt_root: EMPTY_TRIE_ROOT(),
+        bloom: [0; 256],
+        difficulty: mainnet_genesis_configuration.difficulty,
+        number: BigUint::default(),
+        gas_limit: mainnet_genesis_configuration.gas_limit,
+        gas_used: BigUint::default(),
+        timestamp: mainnet_genesis_configuration.timestamp,
+        extra_data: mainnet_genesis_configuration.extra_data,
+        mix_digest: [0; 32],
+        nonce: mainnet_genesis_configuration.nonce,
+    };
+
+    assert_eq!(
+        rlp::rlp_hash(&header),
+        hex!("d4e56740f876aef8c010b86a40d5f56745a118d0906a34e69aec8c0db1cb8fa3")
+    );
+}
+
+#[test]
+pub fn test_add_mainnet_genesis_block() {
+    let mut chain = BlockChain {
+        blocks: vec![],
+        state: State::default(),
+        chain_id: 0,
+    };
+    add_genesis_block(HardFork::Frontier, &mut chain, mainnet_genesis_configuration()).unwrap();
+
+    assert_eq!(chain.chain_id, 1);
+    assert_eq!(chain.blocks.len(), 1);
+    assert_eq!(
+        rlp::rlp_hash(&chain.blocks[0].header),
+        hex!("d4e56740f876aef8c010b86a40d5f56745a118d0906a34e69aec8c0db1cb8fa3")
+    );
+}
@@ -0,0 +1,74 @@
+//! The golden-trace corpus in `tests/golden`. After a change that is meant
+//! to alter executions, record the new traces with `UPDATE_GOLDEN=1`.
+//!
+//! The corpus holds Frontier transactions filled with `StateTestFiller`.
+//! Mainnet transactions, with the accounts they touch as the pre-state, and
+//! transactions of later forks are added as fixtures of the same format.
+
+use std::{fs, path::Path};
+
+use execution_specs_rs::ethereum_spec_tools::evm_tools::{fixtures::Status, golden::check_corpus};
+
+#[test]
+fn test_golden_corpus() {
+    let corpus = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/golden");
+    let outcomes = check_corpus(&corpus, std::env::var_os("UPDATE_GOLDEN").is_some()).unwrap();
+    assert!(!outcomes.is_empty());
+    let failures: Vec<_> = outcomes
+        .iter()
+        .filter(|outcome| matches!(outcome.status, Status::Failed(_)))
+        .collect();
+    assert!(failures.is_empty(), "{failures:#?}");
+}
+
+#[test]
+fn test_golden_mismatch() {
+    let corpus = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/golden");
+    let directory = std::env::temp_dir().join(format!("golden-{}", std::process::id()));
+    fs::create_dir_all(&directory).unwrap();
+    fs::copy(
+        corpus.join("storage_and_logs.json"),
+        directory.join("storage_and_logs.json"),
+    )
+    .unwrap();
+    let trace_path = directory.join("storage_and_logs.Frontier.jsonl");
+
+    // Without a golden trace, the test fails unless it is recorded.
+    let outcomes = check_corpus(&directory, false).unwrap();
+    assert!(matches!(&outcomes[0].status, Status::Failed(message) if message.contains("no golden trace")));
+    let outcomes = check_corpus(&directory, true).unwrap();
+    assert_eq!(outcomes[0].status, Status::Passed);
+    let golden = fs::read_to_string(&trace_path).unwrap();
+    assert_eq!(
+        golden,
+        fs::read_to_string(corpus.join("storage_and_logs.Frontier.jsonl")).unwrap()
+    );
+
+    // A changed gas cost is caught at its line.
+    fs::write(
+        &trace_path,
+        golden.replacen("\"gasCost\":\"0x3\"", "\"gasCost\":\"0x4\"", 1),
+    )
+    .unwrap();
+    let outcomes = check_corpus(&directory, false).unwrap();
+    assert!(matches!(&outcomes[0].status, Status::Failed(message) if message.contains("line 1:")));
+
+    // A wrong post-state root fails, and its trace isn't recorded.
+    let fixture = fs::read_to_string(directory.join("storage_and_logs.json")).unwrap();
+    let root = serde_json::from_str::<serde_json::Value>(&fixture).unwrap()["storage_and_logs"]["post"]["Frontier"][0]
+        ["hash"]
+        .as_str()
+        .unwrap()
+        .to_owned();
+    fs::write(
+        directory.join("storage_and_logs.json"),
+        fixture.replace(&root, &format!("0x{}", "0".repeat(64))),
+    )
+    .unwrap();
+    fs::remove_file(&trace_path).unwrap();
+    let outcomes = check_corpus(&directory, true).unwrap();
+    assert!(matches!(&outcomes[0].status, Status::Failed(message) if message.contains("state root")));
+    assert!(!trace_path.exists());
+
+    fs::remove_dir_all(&directory).unwrap();
+}
@@ -0,0 +1,83 @@
+use execution_specs_rs::ethereum::crypto::hash::{
+    keccak256, keccak256_batch, keccak512, ripemd160, sha256, sha256_batch, Sha256,
+};
+use hex_literal::hex;
+
+#[test]
+fn test_keccak_vectors() {
+    assert_eq!(
+        keccak256(b""),
+        hex!("c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470")
+    );
+    assert_eq!(
+        keccak256(b"abc"),
+        hex!("4e03657aea45a94fc7d47ba826c8d667c0d1e6e33a64a036ec44f58fa12d6c45")
+    );
+    assert_eq!(
+        keccak512(b""),
+        hex!(
+            "0eab42de4c3ceb9235fc91acffe746b29c29a8c366b7c60e4e67c466f36a4304"
+            "c00fa9caf9d87976ba469bcbe06713b435f091ef2769fb160cdab33d3670680e"
+        )
+    );
+    // Longer than the 136 byte rate of keccak256.
+    assert_eq!(keccak256(&[0xff; 200]), keccak256_batch(&[[0xff; 200]])[0]);
+}
+
+#[test]
+fn test_keccak256_batch() {
+    assert!(keccak256_batch::<&[u8]>(&[]).is_empty());
+
+    // Enough keys for the `parallel` feature to split the batch.
+    let keys: Vec<Vec<u8>> = (0..3000u32).map(|i| i.to_be_bytes().repeat(i as usize % 50)).collect();
+    let hashes = keccak256_batch(&keys);
+    assert_eq!(hashes.len(), keys.len());
+    for (key, hash) in keys.iter().zip(hashes) {
+        assert_eq!(keccak256(key), hash);
+    }
+}
+
+#[test]
+fn test_ripemd160_vectors() {
+    // From the RIPEMD-160 specification.
+    assert_eq!(ripemd160(b""), hex!("9c1185a5c5e9fc54612808977ee8f548b2258d31"));
+    assert_eq!(ripemd160(b"abc"), hex!("8eb208f7e05d987a9b044a8e98c6b087f15a0bfc"));
+    assert_eq!(
+        ripemd160(b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq"),
+        hex!("12a053384a9c0c88e405a06c27dcf49ada62eb2b")
+    );
+    assert_eq!(
+        ripemd160(&b"1234567890".repeat(8)),
+        hex!("9b752e45573d4b39f4dbd3323cab82bf63326bfb")
+    );
+    assert_eq!(
+        ripemd160(&b"a".repeat(1_000_000)),
+        hex!("52783243c1697bdbe16d37f97f68f08325dc1528")
+    );
+}
+
+#[test]
+fn test_sha256() {
+    assert_eq!(
+        sha256(b""),
+        hex!("e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855")
+    );
+    assert_eq!(
+        sha256(b"abc"),
+        hex!("ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad")
+    );
+
+    // Given in parts, across the 64 byte blocks.
+    let input = [0x5a; 200];
+    let mut hasher = Sha256::new();
+    hasher.update(&input[..1]).update(&input[1..100]).update(&[]);
+    hasher.update(&input[100..]);
+    assert_eq!(hasher.finalize(), sha256(&input));
+
+    let inputs: Vec<Vec<u8>> = (0..3000u32).map(|i| i.to_le_bytes().repeat(i as usize % 20)).collect();
+    let hashes = sha256_batch(&inputs);
+    assert_eq!(hashes.len(), inputs.len());
+    for (input, hash) in inputs.iter().zip(hashes) {
+        assert_eq!(sha256(input), hash);
+    }
+}
@@ -0,0 +1,101 @@
+use std::{fs, path::PathBuf};
+
+use execution_specs_rs::{
+    ethereum::{
+        base_types::{Uint, U256},
+        exceptions::EthereumException,
+        frontier::fork_types::Header,
+    },
+    ethereum_spec_tools::header_archive::HeaderArchive,
+};
+
+fn temp_file(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("{name}-{}", std::process::id()));
+    let _ = fs::remove_file(&path);
+    path
+}
+
+/// Headers `first..end`, each the child of the one before.
+fn headers(first: u64, end: u64) -> Vec<Header> {
+    let mut headers: Vec<Header> = vec![];
+    for number in first..end {
+        headers.push(Header {
+            parent_hash: headers.last().map_or([0; 32], Header::hash),
+            ommers_hash: [0; 32],
+            coinbase: [0xbb; 20],
+            state_root: [number as u8; 32],
+            transactions_root: [0; 32],
+            receipt_root: [0; 32],
+            bloom: [0; 256],
+            difficulty: Uint::from(0x20000u32),
+            number: Uint::from(number),
+            gas_limit: Uint::from(5000u32),
+            gas_used: Uint::from(0u8),
+            timestamp: U256::from(number * 15),
+            extra_data: vec![0xab; number as usize % 40].into(),
+            mix_digest: [0; 32],
+            nonce: [0; 8],
+        });
+    }
+    headers
+}
+
+#[test]
+fn test_header_archive() {
+    let path = temp_file("header-archive");
+    let headers = headers(100, 110);
+    let mut archive = HeaderArchive::open(&path).unwrap();
+    assert!(archive.is_empty());
+    assert_eq!(archive.get(100).unwrap().map(|header| header.hash()), None);
+    for header in &headers {
+        archive.append(header).unwrap();
+    }
+    assert_eq!(archive.range(), 100..110);
+    assert_eq!(archive.get(105).unwrap().unwrap().hash(), headers[5].hash());
+    assert!(archive.get(110).unwrap().is_none());
+
+    // Only the child of the last header.
+    assert!(matches!(
+        archive.append(&headers[3]),
+        Err(EthereumException::ValueError)
+    ));
+    let mut orphan = self::headers(110, 111).remove(0);
+    assert!(matches!(archive.append(&orphan), Err(EthereumException::ValueError)));
+    orphan.parent_hash = headers[9].hash();
+    archive.append(&orphan).unwrap();
+
+    // Opened again, it carries on.
+    let reopened = HeaderArchive::open(&path).unwrap();
+    assert_eq!(reopened.range(), 100..111);
+    assert_eq!(reopened.get(100).unwrap().unwrap().hash(), headers[0].hash());
+    assert_eq!(reopened.get(110).unwrap().unwrap().hash(), orphan.hash());
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_header_archive_torn_write() {
+    let path = temp_file("header-archive-torn");
+    let headers = headers(0, 3);
+    let mut archive = HeaderArchive::open(&path).unwrap();
+    for header in &headers {
+        archive.append(header).unwrap();
+    }
+    drop(archive);
+
+    // A crash in the middle of the last header.
+    let length = fs::metadata(&path).unwrap().len();
+    fs::OpenOptions::new()
+        .write(true)
+        .open(&path)
+        .unwrap()
+        .set_len(length - 10)
+        .unwrap();
+    let mut archive = HeaderArchive::open(&path).unwrap();
+    assert_eq!(archive.range(), 0..2);
+    archive.append(&headers[2]).unwrap();
+    assert_eq!(
+        HeaderArchive::open(&path).unwrap().get(2).unwrap().unwrap().hash(),
+        headers[2].hash()
+    );
+    fs::remove_file(&path).unwrap();
+}
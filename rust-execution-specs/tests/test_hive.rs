@@ -0,0 +1,146 @@
+use std::{net::TcpListener, thread};
+
+use execution_specs_rs::{
+    ethereum::{
+        frontier::{state::state_root, trie::EMPTY_TRIE_ROOT},
+        utils::hexadecimal::hex,
+    },
+    ethereum_spec_tools::{
+        engine::ExecutionPayload,
+        evm_tools::alloc::load_alloc,
+        hive::{load_genesis, serve_node, HiveNode, CLIENT_VERSION},
+        rpc::{Request, RpcClient},
+    },
+};
+use serde_json::{json, Value};
+
+const RICH: &str = "0x1000000000000000000000000000000000000001";
+/// Returns the value of its storage slot 0.
+const GETTER: &str = "0xcccccccccccccccccccccccccccccccccccccccc";
+const VALIDATOR: &str = "0xdddddddddddddddddddddddddddddddddddddddd";
+
+fn alloc() -> Value {
+    json!({
+        RICH: { "balance": "0xde0b6b3a7640000" },
+        GETTER: {
+            "code": "0x60005460005260206000f3",
+            "storage": { "0x00": "0x2a" },
+            "balance": "0x0"
+        }
+    })
+}
+
+/// A genesis in the shape hive writes them, with Shanghai active from the
+/// start.
+fn genesis() -> String {
+    json!({
+        "config": {
+            "chainId": 7,
+            "londonBlock": 0,
+            "mergeNetsplitBlock": 0,
+            "terminalTotalDifficulty": 0,
+            "shanghaiTime": 0
+        },
+        "gasLimit": "0x1c9c380",
+        "baseFeePerGas": "0x3b9aca00",
+        "alloc": alloc()
+    })
+    .to_string()
+}
+
+fn request(node: &mut HiveNode, method: &str, params: Value) -> Value {
+    node.handle(&json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params }))["result"].clone()
+}
+
+#[test]
+fn test_load_genesis() {
+    let (chain, extensions) = load_genesis(&genesis()).unwrap();
+
+    assert_eq!(chain.chain_id, 7);
+    let header = &chain.blocks[0].header;
+    assert_eq!(header.state_root, state_root(&load_alloc(&alloc().to_string()).unwrap()));
+    assert_eq!(extensions.base_fee_per_gas, Some(1_000_000_000u64.into()));
+    assert_eq!(extensions.withdrawals_root, Some(EMPTY_TRIE_ROOT()));
+    assert_eq!(extensions.blob_gas_used, None);
+}
+
+#[test]
+fn test_node() {
+    let mut node = HiveNode::from_genesis(&genesis()).unwrap();
+
+    assert_eq!(request(&mut node, "web3_clientVersion", json!([])), CLIENT_VERSION);
+    assert_eq!(request(&mut node, "net_version", json!([])), "7");
+    assert_eq!(request(&mut node, "eth_chainId", json!([])), "0x7");
+    assert_eq!(request(&mut node, "eth_syncing", json!([])), false);
+    assert_eq!(request(&mut node, "eth_getBalance", json!([RICH, "latest"])), "0xde0b6b3a7640000");
+    assert_eq!(request(&mut node, "eth_getCode", json!([GETTER, "latest"])), "0x60005460005260206000f3");
+    assert_eq!(
+        request(&mut node, "eth_getStorageAt", json!([GETTER, "0x0", "latest"])),
+        format!("0x{:064x}", 42)
+    );
+    assert_eq!(
+        request(&mut node, "eth_call", json!([{ "to": GETTER }, "latest"])),
+        format!("0x{:064x}", 42)
+    );
+
+    let genesis = request(&mut node, "eth_getBlockByNumber", json!(["latest", false]));
+    assert_eq!(genesis["number"], "0x0");
+    assert_eq!(genesis["baseFeePerGas"], "0x3b9aca00");
+    assert_eq!(genesis["withdrawals"], json!([]));
+    assert_eq!(genesis["hash"], hex(&node.engine().head()));
+    assert_eq!(request(&mut node, "eth_getBlockByNumber", json!(["0x1", false])), Value::Null);
+}
+
+#[test]
+fn test_node_follows_the_head() {
+    let mut node = HiveNode::from_genesis(&genesis()).unwrap();
+    let genesis_hash = hex(&node.engine().head());
+
+    let mut post_alloc = alloc();
+    post_alloc[VALIDATOR] = json!({ "balance": "0x3b9aca00" });
+    let mut payload = json!({
+        "parentHash": genesis_hash,
+        "feeRecipient": RICH,
+        "stateRoot": hex(&state_root(&load_alloc(&post_alloc.to_string()).unwrap())),
+        "receiptsRoot": hex(&EMPTY_TRIE_ROOT()),
+        "logsBloom": format!("0x{}", "00".repeat(256)),
+        "prevRandao": format!("0x{}", "00".repeat(32)),
+        "blockNumber": "0x1",
+        "gasLimit": "0x1c9c380",
+        "gasUsed": "0x0",
+        "timestamp": "0xc",
+        "extraData": "0x",
+        "baseFeePerGas": "0x342770c0",
+        "blockHash": genesis_hash,
+        "transactions": [],
+        "withdrawals": [{ "index": "0x0", "validatorIndex": "0x0", "address": VALIDATOR, "amount": "0x1" }]
+    });
+    let hash = ExecutionPayload::from_json(&payload).unwrap().compute_block_hash(None);
+    payload["blockHash"] = json!(hex(&hash));
+
+    assert_eq!(request(&mut node, "eth_getBalance", json!([VALIDATOR, "latest"])), "0x0");
+    let status = request(&mut node, "engine_newPayloadV2", json!([payload]));
+    assert_eq!(status["status"], "VALID", "{status}");
+
+    assert_eq!(request(&mut node, "eth_blockNumber", json!([])), "0x1");
+    assert_eq!(request(&mut node, "eth_getBalance", json!([VALIDATOR, "latest"])), "0x3b9aca00");
+    let block = request(&mut node, "eth_getBlockByHash", json!([hex(&hash), false]));
+    assert_eq!(block["parentHash"], genesis_hash);
+    assert_eq!(block["withdrawals"][0]["address"], VALIDATOR);
+    assert_eq!(request(&mut node, "eth_getBlockByNumber", json!(["earliest", false]))["hash"], genesis_hash);
+}
+
+#[test]
+fn test_serve_node() {
+    let listeners: Vec<_> = (0..2).map(|_| TcpListener::bind("127.0.0.1:0").unwrap()).collect();
+    let ports: Vec<_> = listeners.iter().map(|listener| listener.local_addr().unwrap().port()).collect();
+    let node = HiveNode::from_genesis(&genesis()).unwrap();
+    // The servers run until the process exits.
+    thread::spawn(move || serve_node(node, listeners));
+
+    for port in ports {
+        let client = RpcClient::new(&format!("http://127.0.0.1:{port}")).unwrap();
+        let version = client.call(&Request::new("web3_clientVersion", json!([]))).unwrap();
+        assert_eq!(version, CLIENT_VERSION);
+    }
+}
@@ -0,0 +1,125 @@
+use execution_specs_rs::{
+    ethereum::{
+        base_types::{Uint, U256},
+        frontier::{fork, fork_types::Transaction},
+        intrinsic_gas::{calculate_intrinsic_cost, intrinsic_cost, IntrinsicGasInputs, IntrinsicGasSchedule, FRONTIER},
+    },
+    ethereum_spec_tools::forks::HardFork,
+};
+
+/// Homestead, EIP-2.
+const HOMESTEAD: IntrinsicGasSchedule = IntrinsicGasSchedule {
+    create: 32000,
+    ..FRONTIER
+};
+
+/// Istanbul, EIP-2028.
+const ISTANBUL: IntrinsicGasSchedule = IntrinsicGasSchedule {
+    data_non_zero: 16,
+    ..HOMESTEAD
+};
+
+/// Berlin, EIP-2930.
+const BERLIN: IntrinsicGasSchedule = IntrinsicGasSchedule {
+    access_list_address: 2400,
+    access_list_storage_key: 1900,
+    ..ISTANBUL
+};
+
+/// Shanghai, EIP-3860.
+const SHANGHAI: IntrinsicGasSchedule = IntrinsicGasSchedule {
+    initcode_word: 2,
+    ..BERLIN
+};
+
+/// Prague, EIP-7702.
+const PRAGUE: IntrinsicGasSchedule = IntrinsicGasSchedule {
+    authorization: 25000,
+    ..SHANGHAI
+};
+
+fn transaction(to: Option<[u8; 20]>, data: &[u8]) -> Transaction {
+    Transaction {
+        nonce: U256::from(0u8),
+        gas_price: U256::from(1u8),
+        gas: U256::from(100_000u32),
+        to,
+        value: U256::from(0u8),
+        data: data.into(),
+        v: U256::from(27u8),
+        r: U256::from(1u8),
+        s: U256::from(1u8),
+    }
+}
+
+#[test]
+fn test_frontier_matches_the_fork() {
+    for tx in [
+        transaction(Some([1; 20]), &[]),
+        transaction(Some([1; 20]), &[0, 1, 0, 2, 0xff]),
+        transaction(None, &[0x60, 0x00, 0x00]),
+    ] {
+        assert_eq!(
+            calculate_intrinsic_cost(&tx, HardFork::Frontier),
+            fork::calculate_intrinsic_cost(&tx)
+        );
+    }
+    // Frontier doesn't charge extra for creating a contract.
+    let tx = transaction(None, &[0; 40]);
+    assert_eq!(
+        calculate_intrinsic_cost(&tx, HardFork::Frontier),
+        Uint::from(21000u32 + 40 * 4)
+    );
+}
+
+#[test]
+fn test_later_schedules() {
+    let data = [0, 1, 0, 2];
+    let call = IntrinsicGasInputs {
+        data: &data,
+        ..Default::default()
+    };
+    let create = IntrinsicGasInputs {
+        create: true,
+        ..call
+    };
+    assert_eq!(intrinsic_cost(&HOMESTEAD, &call), Uint::from(21000u32 + 8 + 136));
+    assert_eq!(intrinsic_cost(&HOMESTEAD, &create), Uint::from(53000u32 + 8 + 136));
+    assert_eq!(intrinsic_cost(&ISTANBUL, &call), Uint::from(21000u32 + 8 + 32));
+
+    let access_list = IntrinsicGasInputs {
+        access_list_addresses: 2,
+        access_list_storage_keys: 3,
+        ..call
+    };
+    assert_eq!(
+        intrinsic_cost(&BERLIN, &access_list),
+        Uint::from(21000u32 + 8 + 32 + 2 * 2400 + 3 * 1900)
+    );
+    assert_eq!(intrinsic_cost(&FRONTIER, &access_list), intrinsic_cost(&FRONTIER, &call));
+
+    // 33 bytes of initcode are two words.
+    let initcode = [1; 33];
+    let create = IntrinsicGasInputs {
+        data: &initcode,
+        create: true,
+        ..Default::default()
+    };
+    assert_eq!(
+        intrinsic_cost(&SHANGHAI, &create),
+        Uint::from(53000u32 + 33 * 16 + 2 * 2)
+    );
+    assert_eq!(
+        intrinsic_cost(&BERLIN, &create),
+        Uint::from(53000u32 + 33 * 16)
+    );
+
+    let authorizations = IntrinsicGasInputs {
+        authorizations: 2,
+        ..call
+    };
+    assert_eq!(
+        intrinsic_cost(&PRAGUE, &authorizations),
+        Uint::from(21000u32 + 8 + 32 + 2 * 25000)
+    );
+}
@@ -0,0 +1,153 @@
+use std::{
+    io::{self, Write},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use execution_specs_rs::ethereum_spec_tools::{
+    evm_tools::t8n::{transition, T8nInput},
+    logging::{subscriber, LogFormat, Progress},
+};
+use serde_json::{json, Value};
+use tracing::Level;
+
+/// Keeps what is written to it, for the test to read.
+#[derive(Clone, Default)]
+struct Captured(Arc<Mutex<Vec<u8>>>);
+
+impl Captured {
+    fn lines(&self) -> Vec<String> {
+        String::from_utf8(self.0.lock().unwrap().clone())
+            .unwrap()
+            .lines()
+            .map(str::to_owned)
+            .collect()
+    }
+}
+
+impl Write for Captured {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn test_text() {
+    let output = Captured::default();
+    let writer = output.clone();
+    tracing::subscriber::with_default(subscriber(Level::INFO, LogFormat::Text, move || writer.clone()), || {
+        tracing::info!("started");
+        {
+            let _block = tracing::info_span!("block", number = 7).entered();
+            let _tx = tracing::info_span!("tx", index = 0, hash = %"0xab").entered();
+            tracing::warn!(reason = "nonce too low", gas = 21000, "rejected");
+            tracing::debug!("hidden");
+        }
+        tracing::error!("stopped");
+    });
+
+    let lines = output.lines();
+    assert_eq!(lines.len(), 3);
+    assert!(lines[0].ends_with(" INFO started"), "{}", lines[0]);
+    assert!(
+        lines[1].ends_with(" WARN block{number=7}:tx{index=0 hash=0xab}: rejected reason=\"nonce too low\" gas=21000"),
+        "{}",
+        lines[1]
+    );
+    // The spans were left.
+    assert!(lines[2].ends_with("ERROR stopped"), "{}", lines[2]);
+}
+
+#[test]
+fn test_json() {
+    let output = Captured::default();
+    let writer = output.clone();
+    tracing::subscriber::with_default(subscriber(Level::DEBUG, LogFormat::Json, move || writer.clone()), || {
+        let _block = tracing::info_span!("block", number = 7).entered();
+        tracing::debug!(transactions = 2, "applied");
+    });
+
+    let lines = output.lines();
+    assert_eq!(lines.len(), 1);
+    let mut line: Value = serde_json::from_str(&lines[0]).unwrap();
+    assert!(line["timestamp"].is_string());
+    line.as_object_mut().unwrap().remove("timestamp");
+    assert_eq!(
+        line,
+        json!({
+            "level": "DEBUG",
+            "fields": { "message": "applied", "transactions": 2 },
+            "spans": [{ "name": "block", "number": 7 }],
+        })
+    );
+
+    assert_eq!("json".parse(), Ok(LogFormat::Json));
+    assert!("yaml".parse::<LogFormat>().is_err());
+    assert_eq!("trace".parse::<Level>().unwrap(), Level::TRACE);
+    assert!("loud".parse::<Level>().is_err());
+}
+
+#[test]
+fn test_progress() {
+    let start = Instant::now();
+    let mut progress = Progress::starting_at(Duration::from_secs(10), start);
+
+    assert_eq!(progress.record_block_at(start + Duration::from_secs(4), 1, 2, 1_000_000), None);
+    let report = progress.record_block_at(start + Duration::from_secs(10), 2, 1, 3_000_000).unwrap();
+    assert_eq!(report.blocks, 2);
+    assert_eq!(report.transactions, 3);
+    assert_eq!(report.blocks_per_second(), 0.2);
+    assert_eq!(report.gas_per_second(), 400_000.0);
+
+    // The next window starts at the report.
+    assert_eq!(progress.record_block_at(start + Duration::from_secs(15), 3, 0, 0), None);
+    let report = progress.record_block_at(start + Duration::from_secs(20), 4, 0, 5_000_000).unwrap();
+    assert_eq!(report.blocks, 2);
+    assert_eq!(report.gas, 5_000_000);
+
+    let total = progress.finish();
+    assert_eq!(total.blocks, 4);
+    assert_eq!(total.gas, 9_000_000);
+}
+
+#[test]
+fn test_transition_logs() {
+    // A transaction with a nonce from the future is rejected.
+    let alloc = json!({ "0xa94f5374fce5edbc8e2a8697c15331677e6ebf0b": { "balance": "0xde0b6b3a7640000" } });
+    let env = json!({
+        "currentCoinbase": "0x2adc25665018aa1fe0e6bc666dac8fc2697ff9ba",
+        "currentDifficulty": "0x20000",
+        "currentGasLimit": "0x5f5e100",
+        "currentNumber": "0x1",
+        "currentTimestamp": "0x3e8",
+    });
+    let txs = json!([{
+        "nonce": "0x1",
+        "gasPrice": "0xa",
+        "gas": "0x5208",
+        "to": "0xcccccccccccccccccccccccccccccccccccccccc",
+        "value": "0x0",
+        "input": "0x",
+        "secretKey": "0x45a915e4d060149eb4365960e6a7a45f334393093061116b197e3240065ff2d8",
+    }]);
+    let input = T8nInput::from_json(&alloc.to_string(), &env.to_string(), &txs.to_string()).unwrap();
+    let output = Captured::default();
+    let writer = output.clone();
+    let result = tracing::subscriber::with_default(
+        subscriber(Level::DEBUG, LogFormat::Text, move || writer.clone()),
+        || transition(&input),
+    );
+
+    let lines = output.lines();
+    assert_eq!(lines.len(), 1);
+    assert!(
+        lines[0].ends_with(&format!("DEBUG tx{{index=0}}: rejected reason=\"{}\"", result.rejected[0].1)),
+        "{}",
+        lines[0]
+    );
+}
@@ -0,0 +1,99 @@
+use std::{
+    io::{Read, Write},
+    net::TcpStream,
+    time::{Duration, Instant},
+};
+
+use execution_specs_rs::{
+    ethereum::metrics::{render, Histogram, Observations, EVM_INSTRUCTIONS, EVM_MESSAGES},
+    ethereum_spec_tools::{
+        evm_tools::run::{run, RunConfig},
+        logging::Progress,
+        metrics::{self, BLOCKS, GAS, HEAD, TRANSACTIONS},
+    },
+};
+use hex_literal::hex;
+
+/// The body of the answer to `GET path` from the endpoint at `port`, with
+/// its status line.
+fn get(port: u16, path: &str) -> (String, String) {
+    let mut stream = TcpStream::connect(("127.0.0.1", port)).unwrap();
+    write!(stream, "GET {path} HTTP/1.1\r\nHost: 127.0.0.1\r\n\r\n").unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    let (head, body) = response.split_once("\r\n\r\n").unwrap();
+    (head.lines().next().unwrap().to_owned(), body.to_owned())
+}
+
+#[test]
+fn test_histogram() {
+    static LATENCY: Histogram = Histogram::new("latency_seconds", "Latency.", &[0.1, 1.0]);
+    for value in [0.05, 0.5, 0.7, 2.0] {
+        LATENCY.observe(value);
+    }
+    assert_eq!(
+        LATENCY.get(),
+        Observations {
+            buckets: vec![1, 2],
+            sum: 3.25,
+            count: 4,
+        }
+    );
+    assert_eq!(
+        render(&[&LATENCY]),
+        "# HELP latency_seconds Latency.\n\
+         # TYPE latency_seconds histogram\n\
+         latency_seconds_bucket{le=\"0.1\"} 1\n\
+         latency_seconds_bucket{le=\"1\"} 3\n\
+         latency_seconds_bucket{le=\"+Inf\"} 4\n\
+         latency_seconds_sum 3.25\n\
+         latency_seconds_count 4\n"
+    );
+}
+
+#[test]
+fn test_interpreter_counts() {
+    let (instructions, messages) = (EVM_INSTRUCTIONS.get(), EVM_MESSAGES.get());
+    // PUSH1 2 PUSH1 1 ADD PUSH1 0 MSTORE PUSH1 32 PUSH1 0 RETURN
+    let output = run(
+        RunConfig {
+            code: hex!("600260010160005260206000f3").as_slice().into(),
+            ..Default::default()
+        },
+        None,
+    );
+    assert_eq!(output.error, None);
+    // Other tests may run code at the same time.
+    assert!(EVM_INSTRUCTIONS.get() >= instructions + 8);
+    assert!(EVM_MESSAGES.get() > messages);
+}
+
+#[test]
+fn test_progress_counts() {
+    let (blocks, transactions, gas) = (BLOCKS.get(), TRANSACTIONS.get(), GAS.get());
+    let start = Instant::now();
+    let mut progress = Progress::starting_at(Duration::from_secs(10), start);
+    progress.record_block_at(start, 7, 2, 42000);
+    progress.record_block_at(start + Duration::from_secs(10), 8, 1, 21000);
+    assert!(BLOCKS.get() >= blocks + 2);
+    assert!(TRANSACTIONS.get() >= transactions + 3);
+    assert!(GAS.get() >= gas + 63000);
+    assert!(HEAD.get() >= 8.0);
+}
+
+#[test]
+fn test_serve() {
+    let port = metrics::spawn("127.0.0.1:0".parse().unwrap()).unwrap().port();
+    let (status, body) = get(port, "/metrics");
+    assert_eq!(status, "HTTP/1.1 200 OK");
+    for name in [
+        "evm_instructions_total",
+        "backend_cache_hits_total",
+        "sync_blocks_total",
+        "sync_block_seconds_count",
+    ] {
+        assert!(body.contains(&format!("\n{name} ")), "{name}");
+    }
+    let (status, _) = get(port, "/");
+    assert_eq!(status, "HTTP/1.1 404 Not Found");
+}
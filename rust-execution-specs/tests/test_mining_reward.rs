@@ -0,0 +1,36 @@
+use execution_specs_rs::{
+    ethereum::{
+        base_types::{Uint, U256},
+        mining_reward::{miner_reward, ommer_reward, reward_schedule, RewardSchedule, FRONTIER},
+    },
+    ethereum_spec_tools::forks::HardFork,
+};
+
+fn ether(amount: u64) -> U256 {
+    U256::from(amount) * U256::from(10u8).pow(18)
+}
+
+/// A block reward of 2 ether, as Constantinople paid.
+const TWO_ETHER: RewardSchedule = RewardSchedule {
+    block_reward: 2_000_000_000_000_000_000,
+};
+
+#[test]
+fn test_block_rewards() {
+    assert_eq!(reward_schedule(HardFork::Frontier), &FRONTIER);
+    assert_eq!(miner_reward(&FRONTIER, 0), ether(5));
+    assert_eq!(miner_reward(&TWO_ETHER, 0), ether(2));
+    assert_eq!(miner_reward(&RewardSchedule { block_reward: 0 }, 2), U256::from(0u8));
+
+    // A thirty-second of the block reward per ommer.
+    assert_eq!(miner_reward(&FRONTIER, 2), ether(5) + ether(5) / 16u8);
+    assert_eq!(miner_reward(&TWO_ETHER, 1), ether(2) + ether(2) / 32u8);
+}
+
+#[test]
+fn test_ommer_rewards() {
+    let reward = |schedule, age: u8| ommer_reward(schedule, &Uint::from(100u8), &Uint::from(100 - age));
+    assert_eq!(reward(&FRONTIER, 1), ether(5) * 7u8 / 8u8);
+    assert_eq!(reward(&FRONTIER, 6), ether(5) * 2u8 / 8u8);
+    assert_eq!(reward(&TWO_ETHER, 2), ether(2) * 6u8 / 8u8);
+}
@@ -0,0 +1,130 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    slice,
+};
+
+use execution_specs_rs::{
+    ethereum::utils::hexadecimal::hex_to_bytes,
+    ethereum_spec_tools::evm_tools::{
+        fixtures::Status,
+        precompiles::{load_vectors, precompile_address, run_precompile_vectors, run_vector, PrecompileVector},
+    },
+};
+use serde_json::json;
+
+/// The vector files of `tests/precompiles`, see its README for their source.
+fn vectors_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/precompiles")
+}
+
+fn vectors(file: &str) -> Vec<PrecompileVector> {
+    load_vectors(&fs::read_to_string(vectors_dir().join(file)).unwrap()).unwrap()
+}
+
+fn address(index: u8) -> [u8; 20] {
+    let mut address = [0; 20];
+    address[19] = index;
+    address
+}
+
+#[test]
+fn test_precompile_address() {
+    assert_eq!(precompile_address("ecRecover"), Some(address(1)));
+    assert_eq!(precompile_address("modexp_eip2565"), Some(address(5)));
+    assert_eq!(precompile_address("bn256Pairing"), Some(address(8)));
+    assert_eq!(precompile_address("fail-blake2f"), Some(address(9)));
+    assert_eq!(precompile_address("pointEvaluation"), Some(address(0x0a)));
+    assert_eq!(precompile_address("blsMapG2"), Some(address(0x11)));
+    assert_eq!(precompile_address("sstore"), None);
+}
+
+#[test]
+fn test_load_vectors() {
+    let identity = vectors("identity.json");
+    assert_eq!(
+        identity[1],
+        PrecompileVector {
+            name: "identity-5".to_owned(),
+            input: hex_to_bytes("0102030405").unwrap(),
+            expected: Ok(hex_to_bytes("0102030405").unwrap()),
+            gas: Some(18),
+        }
+    );
+    let blake2f = vectors("fail-blake2f.json");
+    assert_eq!(blake2f[0].expected, Err("invalid input length".to_owned()));
+    assert_eq!(blake2f[0].gas, None);
+
+    assert!(load_vectors(&json!([{ "Input": "", "Name": "no expectation" }]).to_string()).is_err());
+    assert!(load_vectors("{}").is_err());
+}
+
+#[test]
+fn test_run_vectors() {
+    // Blake2f comes after Frontier.
+    let blake2f = vectors("fail-blake2f.json");
+    assert_eq!(run_vector(&address(9), &blake2f[0]), Status::Unsupported);
+
+    let summary = run_precompile_vectors(&[vectors_dir()]).unwrap();
+    let not_passed: Vec<_> = summary
+        .outcomes
+        .iter()
+        .filter(|outcome| outcome.status != Status::Passed)
+        .map(|outcome| (outcome.id.name.as_str(), outcome.status.clone()))
+        .collect();
+    assert_eq!(not_passed, [("vector 0: empty input", Status::Unsupported)]);
+    assert_eq!(summary.outcomes.len(), 10);
+
+    let directory = std::env::temp_dir().join(format!("precompile-vectors-{}", std::process::id()));
+    fs::create_dir_all(&directory).unwrap();
+    fs::copy(vectors_dir().join("identity.json"), directory.join("identity.json")).unwrap();
+    fs::write(directory.join("sstore.json"), "[]").unwrap();
+    fs::write(directory.join("sha256.json"), "[{}]").unwrap();
+
+    let summary = run_precompile_vectors(slice::from_ref(&directory)).unwrap();
+    let outcomes: Vec<_> = summary
+        .outcomes
+        .iter()
+        .map(|outcome| (outcome.id.name.as_str(), outcome.status.clone()))
+        .collect();
+    assert_eq!(
+        outcomes[..3],
+        [
+            ("identity-empty", Status::Passed),
+            ("identity-5", Status::Passed),
+            ("identity-33", Status::Passed)
+        ]
+    );
+    // Files that aren't vectors of a precompile fail as a whole.
+    assert!(matches!(&outcomes[3], ("", Status::Failed(message)) if message.contains("JsonDecodeError")));
+    assert!(
+        matches!(&outcomes[4], ("", Status::Failed(message)) if message.contains("no precompile is named `sstore`"))
+    );
+    assert_eq!(summary.outcomes[0].id.fork, "Frontier");
+    fs::remove_dir_all(directory).unwrap();
+}
+
+#[test]
+fn test_ripemd160_vectors() {
+    // The hash is left padded to a word, and each word of input costs 120.
+    let vectors = vectors("ripemd160.json");
+    let address = precompile_address("ripemd160").unwrap();
+    for vector in &vectors {
+        assert_eq!(run_vector(&address, vector), Status::Passed, "{}", vector.name);
+    }
+
+    // Without the gas for the last word, it runs out.
+    let mut short = vectors[2].clone();
+    short.gas = Some(959);
+    assert!(matches!(run_vector(&address, &short), Status::Failed(message) if message.contains("OutOfGas")));
+}
+
+#[test]
+fn test_sha256_vectors() {
+    // 60 gas, and 12 for each word of input.
+    let vectors = vectors("sha256.json");
+    let address = precompile_address("sha256").unwrap();
+    for vector in &vectors {
+        assert_eq!(run_vector(&address, vector), Status::Passed, "{}", vector.name);
+    }
+}
@@ -0,0 +1,111 @@
+use execution_specs_rs::{
+    ethereum::{
+        base_types::{Uint, U256},
+        frontier::{
+            fork_types::Account,
+            state::{
+                begin_transaction, destroy_account, set_account, set_storage, state_diff, transaction_diff, State,
+            },
+        },
+    },
+    ethereum_spec_tools::evm_tools::prestate::diff_mode_json,
+};
+use serde_json::json;
+
+const CONTRACT: [u8; 20] = [1; 20];
+const DESTROYED: [u8; 20] = [2; 20];
+const CREATED: [u8; 20] = [3; 20];
+const UNTOUCHED: [u8; 20] = [4; 20];
+
+fn slot(n: u8) -> [u8; 32] {
+    let mut key = [0; 32];
+    key[31] = n;
+    key
+}
+
+fn account(nonce: u8, balance: u32, code: &[u8]) -> Option<Account> {
+    Some(Account {
+        nonce: Uint::from(nonce),
+        balance: U256::from(balance),
+        code: code.to_vec().into(),
+    })
+}
+
+fn pre_state() -> State {
+    let mut state = State::default();
+    set_account(&mut state, CONTRACT, account(1, 0x100, &[0x60, 0x00]));
+    set_storage(&mut state, CONTRACT, &slot(1), U256::from(2u8));
+    set_storage(&mut state, CONTRACT, &slot(2), U256::from(3u8));
+    set_storage(&mut state, CONTRACT, &slot(4), U256::from(4u8));
+    set_account(&mut state, DESTROYED, account(0, 7, &[]));
+    set_account(&mut state, UNTOUCHED, account(5, 5, &[]));
+    state
+}
+
+/// Change the balance and three slots of `CONTRACT`, destroy `DESTROYED`
+/// and create `CREATED`.
+fn apply_changes(state: &mut State) {
+    set_account(state, CONTRACT, account(1, 0xff, &[0x60, 0x00]));
+    set_storage(state, CONTRACT, &slot(1), U256::from(5u8));
+    set_storage(state, CONTRACT, &slot(2), U256::from(0u8));
+    set_storage(state, CONTRACT, &slot(3), U256::from(7u8));
+    destroy_account(state, &DESTROYED);
+    set_account(state, CREATED, account(1, 1, &[0xfe]));
+}
+
+#[test]
+fn test_state_diff() {
+    let pre = pre_state();
+    let mut post = pre.clone();
+    apply_changes(&mut post);
+
+    let diff = state_diff(&pre, &post);
+    assert_eq!(diff.keys().collect::<Vec<_>>(), [&CONTRACT, &DESTROYED, &CREATED]);
+    assert_eq!(diff[&CONTRACT].pre, account(1, 0x100, &[0x60, 0x00]));
+    assert_eq!(diff[&CONTRACT].post, account(1, 0xff, &[0x60, 0x00]));
+    let changed_slots: Vec<_> = diff[&CONTRACT].storage.keys().collect();
+    assert_eq!(changed_slots, [&slot(1), &slot(2), &slot(3)]);
+    assert_eq!(diff[&CONTRACT].storage[&slot(3)], (U256::from(0u8), U256::from(7u8)));
+    assert_eq!(diff[&DESTROYED].post, None);
+    assert_eq!(diff[&CREATED].pre, None);
+
+    // The same changes, made in a transaction.
+    let mut state = pre_state();
+    begin_transaction(&mut state);
+    apply_changes(&mut state);
+    assert_eq!(transaction_diff(&state), diff);
+
+    assert!(state_diff(&post, &post).is_empty());
+}
+
+#[test]
+fn test_diff_mode_json() {
+    let pre = pre_state();
+    let mut post = pre.clone();
+    apply_changes(&mut post);
+
+    let slot = |n| format!("0x{:064x}", n);
+    assert_eq!(
+        diff_mode_json(&state_diff(&pre, &post)),
+        json!({
+            "pre": {
+                "0x0101010101010101010101010101010101010101": {
+                    "balance": "0x100",
+                    "nonce": 1,
+                    "code": "0x6000",
+                    // The slot that was empty is left out.
+                    "storage": { slot(1): slot(2), slot(2): slot(3) },
+                },
+                "0x0202020202020202020202020202020202020202": { "balance": "0x7" },
+            },
+            "post": {
+                // So is the slot that was cleared.
+                "0x0101010101010101010101010101010101010101": {
+                    "balance": "0xff",
+                    "storage": { slot(1): slot(5), slot(3): slot(7) },
+                },
+                "0x0303030303030303030303030303030303030303": { "balance": "0x1", "nonce": 1, "code": "0xfe" },
+            },
+        })
+    );
+}
@@ -0,0 +1,260 @@
+use std::collections::{BTreeMap, HashMap};
+
+use execution_specs_rs::ethereum::{
+    arbitrary,
+    base_types::{strip_leading_zeros, Bytes, Bytes32, U256Ext, U256},
+    frontier::{
+        fork_types::{Account, Address, Transaction},
+        state::{
+            begin_transaction, commit_transaction, destroy_storage, get_accounts, get_storage_slots,
+            rollback_transaction, set_account, set_storage, state_root, State,
+        },
+        trie::{
+            bytes_to_nibble_list, dummy_root, encode_internal_node, nibble_list_to_compact, patricialize,
+            patricialize_sorted, root, trie_get, trie_set, Trie,
+        },
+    },
+    rlp::{self, decode, Simple},
+};
+use proptest::{collection, prelude::*};
+
+/// Inverse of `nibble_list_to_compact`, returns the nibbles and the leaf flag.
+fn compact_to_nibble_list(compact: &[u8]) -> (Vec<u8>, bool) {
+    let flag = compact[0] >> 4;
+    let mut nibbles = vec![];
+    if flag & 1 == 1 {
+        nibbles.push(compact[0] & 15);
+    }
+    nibbles.extend(bytes_to_nibble_list(&compact[1..]).iter());
+    (nibbles, flag & 2 == 2)
+}
+
+fn unsecured_root(items: &[(Bytes, Bytes)]) -> [u8; 32] {
+    let mut trie = Trie::<Bytes, Bytes>::new(false, Bytes::default());
+    for (key, value) in items {
+        trie_set(&mut trie, key.clone(), value.clone());
+    }
+    root(&trie, dummy_root)
+}
+
+fn as_bytes(item: &Simple) -> &[u8] {
+    match item {
+        Simple::Bytes(bytes) => bytes,
+        Simple::Sequence(_) => panic!("expected bytes, got {:?}", item),
+    }
+}
+
+/// A change to a state, for checking the cached storage roots.
+#[derive(Clone, Debug)]
+enum StateOp {
+    SetStorage(usize, u8, u8),
+    DestroyStorage(usize),
+    Begin,
+    Commit,
+    Rollback,
+    Root,
+}
+
+fn state_op() -> impl Strategy<Value = StateOp> {
+    prop_oneof![
+        4 => (0..3usize, 0..4u8, 0..3u8).prop_map(|(account, key, value)| StateOp::SetStorage(account, key, value)),
+        1 => (0..3usize).prop_map(StateOp::DestroyStorage),
+        1 => Just(StateOp::Begin),
+        1 => Just(StateOp::Commit),
+        1 => Just(StateOp::Rollback),
+        2 => Just(StateOp::Root),
+    ]
+}
+
+/// The state root of a copy of `state` built from scratch, so without any
+/// cached storage roots.
+fn uncached_state_root(state: &State) -> [u8; 32] {
+    let mut copy = State::default();
+    for (address, account) in get_accounts(state) {
+        set_account(&mut copy, address, Some(account));
+        for (key, value) in get_storage_slots(state, &address) {
+            set_storage(&mut copy, address, &key, value);
+        }
+    }
+    state_root(&copy)
+}
+
+proptest! {
+    #[test]
+    fn rlp_round_trip(item in arbitrary::rlp_item()) {
+        let encoded = rlp::encode(&item);
+        prop_assert_eq!(decode(&encoded).unwrap(), item);
+    }
+
+    #[test]
+    fn rlp_uint_round_trip(value in arbitrary::uint()) {
+        let encoded = rlp::encode(&value);
+        let decoded = decode(&encoded).unwrap();
+        // Integers are encoded without leading zeros, so zero is `b""`.
+        let be_bytes = value.to_bytes_be();
+        prop_assert_eq!(as_bytes(&decoded), strip_leading_zeros(&be_bytes));
+        prop_assert_eq!(U256::from_bytes_be(as_bytes(&decoded)), value);
+    }
+
+    #[test]
+    fn u256_wrapping_arithmetic(x in arbitrary::u256(), y in arbitrary::u256()) {
+        prop_assert_eq!(x.wrapping_add(&y).wrapping_sub(&y), x.clone());
+        prop_assert_eq!(x.wrapping_add(&y), y.wrapping_add(&x));
+        prop_assert_eq!(x.wrapping_mul(&y), (&x * &y).low_u256());
+        prop_assert_eq!(U256::from_be_bytes(&x.to_be_bytes32()).unwrap(), x);
+    }
+
+    #[test]
+    fn rlp_transaction_round_trip(tx in any::<Transaction>()) {
+        let Simple::Sequence(fields) = decode(&rlp::encode(&tx)).unwrap() else {
+            panic!("transactions encode as a sequence");
+        };
+        prop_assert_eq!(fields.len(), 9);
+        let uint = |i: usize| U256::from_bytes_be(as_bytes(&fields[i]));
+        prop_assert_eq!(uint(0), tx.nonce);
+        prop_assert_eq!(uint(1), tx.gas_price);
+        prop_assert_eq!(uint(2), tx.gas);
+        prop_assert_eq!(as_bytes(&fields[3]), tx.to.as_ref().map_or(&[][..], |to| &to[..]));
+        prop_assert_eq!(uint(4), tx.value);
+        prop_assert_eq!(as_bytes(&fields[5]), &*tx.data);
+        prop_assert_eq!(uint(6), tx.v);
+        prop_assert_eq!(uint(7), tx.r);
+        prop_assert_eq!(uint(8), tx.s);
+    }
+
+    #[test]
+    fn rlp_decode_never_panics(data in collection::vec(any::<u8>(), 0..96)) {
+        if let Ok(item) = decode(&data) {
+            // Whatever decodes must encode back to a prefix of the input.
+            let encoded = rlp::encode(&item);
+            prop_assert_eq!(&data[..encoded.len()], &*encoded);
+        }
+    }
+
+    #[test]
+    fn nibble_compaction_round_trip(nibbles in collection::vec(0u8..16, 0..64), is_leaf in any::<bool>()) {
+        let compact = nibble_list_to_compact(&nibbles, is_leaf);
+        prop_assert_eq!(compact.len(), nibbles.len() / 2 + 1);
+        prop_assert_eq!(compact_to_nibble_list(&compact), (nibbles, is_leaf));
+    }
+
+    #[test]
+    fn trie_root_ignores_insertion_order(items in arbitrary::trie_items()) {
+        let mut items: Vec<_> = items.into_iter().collect();
+        let expected = unsecured_root(&items);
+        items.reverse();
+        prop_assert_eq!(unsecured_root(&items), expected);
+        items.sort();
+        prop_assert_eq!(unsecured_root(&items), expected);
+    }
+
+    #[test]
+    fn trie_root_ignores_deleted_keys(items in arbitrary::trie_items(), extra in arbitrary::trie_items()) {
+        let mut trie = Trie::<Bytes, Bytes>::new(false, Bytes::default());
+        for (key, value) in extra.iter().filter(|(key, _)| !items.contains_key(*key)) {
+            trie_set(&mut trie, key.clone(), value.clone());
+        }
+        for (key, value) in &items {
+            trie_set(&mut trie, key.clone(), value.clone());
+        }
+        for key in extra.keys().filter(|key| !items.contains_key(*key)) {
+            trie_set(&mut trie, key.clone(), Bytes::default());
+        }
+        let items: Vec<_> = items.into_iter().collect();
+        prop_assert_eq!(root(&trie, dummy_root), unsecured_root(&items));
+    }
+
+    #[test]
+    fn trie_root_ignores_backend(items in arbitrary::trie_items()) {
+        let mut trie = Trie::with_backend(false, Bytes::default(), BTreeMap::new());
+        for (key, value) in &items {
+            trie_set(&mut trie, key.clone(), value.clone());
+        }
+        prop_assert_eq!(trie_get(&trie, &Bytes::default()), items.get(&Bytes::default()).cloned().unwrap_or_default());
+        let items: Vec<_> = items.into_iter().collect();
+        prop_assert_eq!(root(&trie, dummy_root), unsecured_root(&items));
+    }
+
+    #[test]
+    fn sorted_patricialize_matches_unsorted(items in arbitrary::trie_items()) {
+        let mut obj: Vec<_> = items.iter().map(|(key, value)| (bytes_to_nibble_list(key), value.clone())).collect();
+        let expected = rlp::encode(&encode_internal_node(patricialize(obj.clone(), 0)));
+        obj.sort();
+        prop_assert_eq!(rlp::encode(&encode_internal_node(patricialize_sorted(&obj, 0))), expected);
+    }
+
+    #[test]
+    fn bulk_loaded_trie_matches_trie_set(items in arbitrary::trie_items(), deleted in arbitrary::trie_items()) {
+        // Entries holding the default value are left out.
+        let mut entries: BTreeMap<_, _> = deleted.into_keys().map(|key| (key, Bytes::default())).collect();
+        entries.extend(items.clone());
+        let sorted = Trie::<Bytes, Bytes>::from_sorted_iter(false, Bytes::default(), entries.clone());
+        let collected: Trie<Bytes, Bytes> = entries.into_iter().collect();
+        let items: Vec<_> = items.into_iter().collect();
+        prop_assert_eq!(sorted.data.len(), items.len());
+        prop_assert_eq!(root(&sorted, dummy_root), unsecured_root(&items));
+        prop_assert_eq!(&collected.data, &sorted.data);
+    }
+
+    #[test]
+    fn state_root_ignores_insertion_order(
+        accounts in collection::vec((any::<Address>(), any::<Account>(), arbitrary::storage()), 0..8),
+    ) {
+        let build = |accounts: &mut dyn Iterator<Item = &(Address, Account, HashMap<Bytes32, U256>)>| {
+            let mut state = State::default();
+            for (address, account, storage) in accounts {
+                set_account(&mut state, *address, Some(account.clone()));
+                for (key, value) in storage {
+                    set_storage(&mut state, *address, key, value.clone());
+                }
+            }
+            state_root(&state)
+        };
+        // Later duplicates of an address overwrite its account, so only
+        // compare distinct addresses.
+        let mut seen = std::collections::HashSet::new();
+        let accounts: Vec<_> = accounts.into_iter().filter(|(address, ..)| seen.insert(*address)).collect();
+        prop_assert_eq!(build(&mut accounts.iter()), build(&mut accounts.iter().rev()));
+    }
+
+    #[test]
+    fn cached_storage_roots_match_uncached(ops in collection::vec(state_op(), 0..40)) {
+        let addresses = [[1; 20], [2; 20], [3; 20]];
+        let mut state = State::default();
+        for address in addresses {
+            set_account(&mut state, address, Some(Account::default()));
+        }
+        let mut depth = 0;
+        for op in ops {
+            match op {
+                StateOp::SetStorage(account, key, value) => {
+                    set_storage(&mut state, addresses[account], &[key; 32], U256::from(value));
+                }
+                StateOp::DestroyStorage(account) => destroy_storage(&mut state, &addresses[account]),
+                StateOp::Begin => {
+                    begin_transaction(&mut state);
+                    depth += 1;
+                }
+                StateOp::Commit | StateOp::Rollback if depth > 0 => {
+                    match op {
+                        StateOp::Commit => commit_transaction(&mut state),
+                        _ => rollback_transaction(&mut state),
+                    }
+                    depth -= 1;
+                }
+                StateOp::Root if depth == 0 => {
+                    prop_assert_eq!(state_root(&state), uncached_state_root(&state));
+                    // A copy keeps the cache and stays right after changes.
+                    let mut copy = state.clone();
+                    set_storage(&mut copy, addresses[0], &[9; 32], U256::from(1u8));
+                    prop_assert_eq!(state_root(&copy), uncached_state_root(&copy));
+                }
+                _ => {}
+            }
+        }
+        for _ in 0..depth {
+            rollback_transaction(&mut state);
+        }
+        prop_assert_eq!(state_root(&state), uncached_state_root(&state));
+    }
+}
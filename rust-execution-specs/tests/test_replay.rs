@@ -0,0 +1,283 @@
+use execution_specs_rs::{
+    ethereum::{
+        base_types::{Uint, U256},
+        frontier::{
+            fork_types::{Address, Hash32, Transaction},
+            proof::get_proof,
+            state::{get_account, State},
+            utils::address::compute_contract_address,
+        },
+        transaction_signature::sign_transaction,
+        utils::hexadecimal::{hex, hex_to_bytes20, hex_to_hash},
+    },
+    ethereum_spec_tools::{
+        block_builder::{build_block, BlockAttributes},
+        chain::Chain,
+        evm_tools::{alloc::dump_alloc, prestate::diff_mode_json, state_test::SECRET_KEY, t8n::transaction_hash},
+        replay::{compare, replay_transaction, Prestate, Replay},
+        rpc::{RpcClient, RpcError, Transport},
+    },
+};
+use hex_literal::hex;
+use serde_json::{json, Value};
+
+const SENDER: [u8; 20] = hex!("a94f5374fce5edbc8e2a8697c15331677e6ebf0b");
+const COINBASE: [u8; 20] = [0xbb; 20];
+
+/// A node serving a chain built in the test, as an archive node would.
+#[derive(Clone)]
+struct Node {
+    chain: Chain,
+    /// The state after each block.
+    states: Vec<State>,
+    /// Whether it answers `debug_traceTransaction`.
+    traces: bool,
+    /// What `debug_traceTransaction` answers in `diffMode`.
+    diff_mode: Value,
+    /// Added to the gas used of every receipt.
+    extra_gas: u64,
+}
+
+impl Node {
+    fn block_json(&self, number: u64, full: bool) -> Value {
+        let Some(block) = self.chain.blocks().get(number as usize) else {
+            return Value::Null;
+        };
+        let header = &block.header;
+        let total_difficulty = self.chain.blocks()[..=number as usize]
+            .iter()
+            .fold(Uint::default(), |total, block| total + &block.header.difficulty);
+        let transactions: Vec<Value> = block
+            .transactions
+            .iter()
+            .map(|tx| match full {
+                true => self
+                    .chain
+                    .get_transaction(&transaction_hash(tx))
+                    .unwrap()
+                    .unwrap()
+                    .to_json(),
+                false => json!(hex(&transaction_hash(tx))),
+            })
+            .collect();
+        json!({
+            "hash": hex(&header.hash()),
+            "parentHash": hex(&header.parent_hash),
+            "sha3Uncles": hex(&header.ommers_hash),
+            "miner": hex(&header.coinbase),
+            "stateRoot": hex(&header.state_root),
+            "transactionsRoot": hex(&header.transactions_root),
+            "receiptsRoot": hex(&header.receipt_root),
+            "logsBloom": hex(&header.bloom),
+            "difficulty": format!("{:#x}", header.difficulty),
+            "number": format!("{:#x}", header.number),
+            "gasLimit": format!("{:#x}", header.gas_limit),
+            "gasUsed": format!("{:#x}", header.gas_used),
+            "timestamp": format!("{:#x}", header.timestamp),
+            "extraData": hex(&header.extra_data),
+            "mixHash": hex(&header.mix_digest),
+            "nonce": hex(&header.nonce),
+            "totalDifficulty": format!("{total_difficulty:#x}"),
+            "transactions": transactions,
+            "uncles": [],
+        })
+    }
+
+    fn result(&self, method: &str, params: &Value) -> Value {
+        let number = |value: &Value| u64::from_str_radix(value.as_str().unwrap().trim_start_matches("0x"), 16).unwrap();
+        let hash = |value: &Value| hex_to_hash(value.as_str().unwrap()).unwrap();
+        let address = |value: &Value| hex_to_bytes20(value.as_str().unwrap()).unwrap();
+        match method {
+            "eth_getBlockByNumber" => self.block_json(number(&params[0]), params[1].as_bool().unwrap()),
+            "eth_getTransactionByHash" => self
+                .chain
+                .get_transaction(&hash(&params[0]))
+                .unwrap()
+                .unwrap()
+                .to_json(),
+            "eth_getTransactionReceipt" => {
+                let mut receipt = self.chain.get_transaction_receipt(&hash(&params[0])).unwrap().unwrap();
+                receipt.gas_used += Uint::from(self.extra_gas);
+                receipt.to_json()
+            }
+            "eth_getProof" => {
+                let keys: Vec<[u8; 32]> = params[1].as_array().unwrap().iter().map(hash).collect();
+                get_proof(&self.states[number(&params[2]) as usize], &address(&params[0]), &keys).to_json()
+            }
+            "eth_getCode" => json!(hex(&get_account(
+                &self.states[number(&params[1]) as usize],
+                &address(&params[0])
+            )
+            .code)),
+            // Only the transactions of block 2 are traced, on the state
+            // after block 1.
+            "debug_traceTransaction" => match params[1]["tracerConfig"]["diffMode"].as_bool() {
+                Some(true) => self.diff_mode.clone(),
+                _ => dump_alloc(&self.states[1]),
+            },
+            _ => Value::Null,
+        }
+    }
+
+    fn respond(&self, request: &Value) -> Value {
+        let method = request["method"].as_str().unwrap();
+        if method.starts_with("debug_") && !self.traces {
+            return json!({
+                "jsonrpc": "2.0",
+                "id": request["id"],
+                "error": { "code": -32601, "message": "the method does not exist" },
+            });
+        }
+        json!({ "jsonrpc": "2.0", "id": request["id"], "result": self.result(method, &request["params"]) })
+    }
+}
+
+impl Transport for Node {
+    fn post(&self, body: &str) -> Result<String, RpcError> {
+        let request: Value = serde_json::from_str(body).unwrap();
+        let response = match &request {
+            Value::Array(requests) => requests.iter().map(|request| self.respond(request)).collect(),
+            request => self.respond(request),
+        };
+        Ok(response.to_string())
+    }
+}
+
+fn tx(nonce: u8, to: Option<Address>, value: u8, data: &[u8]) -> Transaction {
+    let unsigned = Transaction {
+        nonce: U256::from(nonce),
+        gas_price: U256::from(1u8),
+        gas: U256::from(100_000u32),
+        to,
+        value: U256::from(value),
+        data: data.into(),
+        v: U256::default(),
+        r: U256::default(),
+        s: U256::default(),
+    };
+    sign_transaction(&unsigned, &SECRET_KEY, None).unwrap()
+}
+
+///
+///     A chain creating a contract which stores the value it is sent in
+///     slot 0 and logs topic 42, then calling it twice in block 2.
+///
+fn node() -> (Node, Address, [Hash32; 2]) {
+    let genesis = json!({
+        "config": { "chainId": 1 },
+        "nonce": "0x0000000000000042",
+        "timestamp": "0x0",
+        "extraData": "0x",
+        "gasLimit": "0x2fefd8",
+        "difficulty": "0x20000",
+        "alloc": { hex(&SENDER): { "balance": "0xde0b6b3a7640000" } }
+    });
+    let mut chain = Chain::from_genesis(&genesis.to_string()).unwrap();
+    let mut states = vec![chain.state().clone()];
+    // CALLVALUE PUSH1 0 SSTORE PUSH1 42 PUSH1 0 PUSH1 0 LOG1 STOP
+    let creation = tx(
+        0,
+        None,
+        0,
+        &hex!("600c 600c 6000 39 600c 6000 f3 34600055 602a60006000a1 00"),
+    );
+    let contract = compute_contract_address(&SENDER, &Uint::from(0u8));
+    let calls = [tx(1, Some(contract), 7, &[]), tx(2, Some(contract), 9, &[])];
+    let hashes = calls.clone().map(|call| transaction_hash(&call));
+    for (timestamp, transactions) in [(15u8, vec![creation]), (30, calls.to_vec())] {
+        let attributes = BlockAttributes {
+            coinbase: COINBASE,
+            timestamp: U256::from(timestamp),
+            gas_limit: Uint::from(0x2fefd8u32),
+            extra_data: Default::default(),
+        };
+        let built = build_block(chain.blockchain(), &transactions, &attributes).unwrap();
+        assert_eq!(built.included.len(), transactions.len());
+        chain.apply_block(built.block).unwrap();
+        states.push(chain.state().clone());
+    }
+    let node = Node {
+        chain,
+        states,
+        traces: false,
+        diff_mode: Value::Null,
+        extra_gas: 0,
+    };
+    (node, contract, hashes)
+}
+
+fn slot_0(replay: &Replay, contract: &Address) -> (U256, U256) {
+    replay.diff[contract].storage[&[0; 32]].clone()
+}
+
+#[test]
+fn test_replay_matches_chain() {
+    let (node, contract, hashes) = node();
+    let schedule = node.chain.schedule().clone();
+    let receipts = hashes.map(|hash| node.chain.get_transaction_receipt(&hash).unwrap().unwrap());
+    let client = RpcClient::with_transport(Node {
+        traces: true,
+        ..node.clone()
+    });
+
+    let traced = replay_transaction(&client, &schedule, &hashes[0], Prestate::Tracer).unwrap();
+    assert_eq!((traced.block_number, traced.index), (2, 0));
+    assert_eq!(traced.gas_used, receipts[0].gas_used);
+    assert_eq!(traced.logs, receipts[0].receipt.logs);
+    assert_eq!(slot_0(&traced, &contract), (U256::default(), U256::from(7u8)));
+
+    // Proofs need no tracing.
+    let client = RpcClient::with_transport(node);
+    let proven = replay_transaction(&client, &schedule, &hashes[0], Prestate::Proofs).unwrap();
+    assert_eq!(proven.to_json(), traced.to_json());
+
+    // The call before it in the block runs first.
+    let second = replay_transaction(&client, &schedule, &hashes[1], Prestate::Proofs).unwrap();
+    assert_eq!(second.gas_used, receipts[1].gas_used);
+    assert_eq!(second.logs, receipts[1].receipt.logs);
+    assert_eq!(slot_0(&second, &contract), (U256::from(7u8), U256::from(9u8)));
+
+    // Without tracing, only the receipt is compared.
+    let comparison = compare(&client, &second).unwrap();
+    assert!(comparison.differences.is_empty());
+    assert!(!comparison.state_compared);
+}
+
+#[test]
+fn test_compare() {
+    let (node, contract, hashes) = node();
+    let schedule = node.chain.schedule().clone();
+    let mut client = RpcClient::with_transport(node);
+    let replay = replay_transaction(&client, &schedule, &hashes[0], Prestate::Proofs).unwrap();
+    let mut trace = diff_mode_json(&replay.diff);
+
+    client = RpcClient::with_transport(Node {
+        traces: true,
+        diff_mode: trace.clone(),
+        ..client.transport().clone()
+    });
+    let comparison = compare(&client, &replay).unwrap();
+    assert!(comparison.state_compared);
+    assert!(comparison.differences.is_empty());
+
+    trace["post"][hex(&contract)]["storage"][hex(&[0u8; 32])] = json!(format!("0x{:064x}", 8));
+    client = RpcClient::with_transport(Node {
+        diff_mode: trace,
+        extra_gas: 1,
+        ..client.transport().clone()
+    });
+    let comparison = compare(&client, &replay).unwrap();
+    let fields: Vec<String> = comparison
+        .differences
+        .iter()
+        .map(|difference| difference.field.clone())
+        .collect();
+    assert_eq!(
+        fields,
+        [
+            "gasUsed".to_owned(),
+            format!("stateDiff.post.{}.storage.{}", hex(&contract), hex(&[0u8; 32])),
+        ]
+    );
+    assert_eq!(comparison.differences[1].spec, json!(format!("0x{:064x}", 7)));
+}
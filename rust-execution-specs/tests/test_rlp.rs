@@ -1,6 +1,6 @@
 use execution_specs_rs::ethereum::{
     base_types::{Uint, U256},
-    rlp::{encode, encode_bytes, encode_iter, encode_sequence},
+    rlp::{decode, decode_to_bytes, encode, encode_bytes, encode_iter, encode_sequence, Simple},
 };
 
 macro_rules! bytes {
@@ -193,3 +193,56 @@ fn test_rlp_encode_successfully() {
         // ),
     }
 }
+
+#[test]
+fn test_rlp_decode_to_bytes() {
+    assert_eq!(decode_to_bytes(b"\x80").unwrap(), b"");
+    assert_eq!(decode_to_bytes(b"\x7f").unwrap(), b"\x7f");
+    assert_eq!(decode_to_bytes(b"\x85hello").unwrap(), b"hello");
+    assert_eq!(decode_to_bytes(&bytes![0xb7, b'\x83' * 55]).unwrap(), [b'\x83'; 55]);
+}
+
+#[test]
+fn test_rlp_decode_nested_sequence() {
+    let encoded = b"\xdd\x85hello\x81\xff\xd4\x83how\xcf\x83are\x83you\xc6\x85doing";
+    let bytes = |b: &[u8]| Simple::Bytes(b.into());
+    let expected = Simple::Sequence(vec![
+        bytes(b"hello"),
+        bytes(b"\xff"),
+        Simple::Sequence(vec![
+            bytes(b"how"),
+            Simple::Sequence(vec![
+                bytes(b"are"),
+                bytes(b"you"),
+                Simple::Sequence(vec![bytes(b"doing")]),
+            ]),
+        ]),
+    ]);
+    assert_eq!(decode(encoded).unwrap(), expected);
+    assert_eq!(*encode(&expected), *encoded);
+}
+
+#[test]
+fn test_rlp_decode_failure() {
+    macro_rules! test {
+        ($($encoded:expr),* $(,)?) => {
+            $(assert!(decode(&$encoded[..]).is_err(), "{:?}", &$encoded[..]);)*
+        };
+    }
+
+    test! {
+        b"",
+        // Single bytes below 0x80 must not be prefixed.
+        b"\x81\x05",
+        // Lengths below 56 must use the short form.
+        b"\xb8\x05hello",
+        b"\xf8\x05hello",
+        // Lengths must not have leading zeros.
+        bytes![0xb9, 0x00, 0x38, 0x00 * 56],
+        // Truncated data.
+        b"\x85hell",
+        b"\xc6\x85hell",
+        b"\xb8",
+        b"\xc2\x85h",
+    }
+}
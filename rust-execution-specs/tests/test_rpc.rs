@@ -0,0 +1,231 @@
+use std::{
+    cell::{Cell, RefCell},
+    io::{Read, Write},
+    net::TcpListener,
+    thread,
+    time::Duration,
+};
+
+use execution_specs_rs::{
+    ethereum::{base_types::Uint, rlp, utils::hexadecimal::hex},
+    ethereum_spec_tools::rpc::{
+        HttpTransport, Request, RetryPolicy, RpcBlock, RpcClient, RpcError, RpcTransactions,
+        Transport,
+    },
+};
+use serde_json::{json, Value};
+
+const BLOCK_1_HASH: &str = "0x88e96d4537bea4d9c05d12549907b32561d3bf31f45aae734cdc119f13406cb6";
+
+/// Mainnet block 1, as geth returns it from `eth_getBlockByNumber`.
+fn block_1(uncles: Vec<&str>) -> Value {
+    json!({
+        "difficulty": "0x3ff800000",
+        "extraData": "0x476574682f76312e302e302f6c696e75782f676f312e342e32",
+        "gasLimit": "0x1388",
+        "gasUsed": "0x0",
+        "hash": BLOCK_1_HASH,
+        "logsBloom": format!("0x{}", "00".repeat(256)),
+        "miner": "0x05a56e2d52c817161883f50c441c3228cfe54d9f",
+        "mixHash": "0x969b900de27b6ac6a67742365dd65f55a0526c41fd18e1b16f1a1215c2e66f59",
+        "nonce": "0x539bd4979fef1ec4",
+        "number": "0x1",
+        "parentHash": "0xd4e56740f876aef8c010b86a40d5f56745a118d0906a34e69aec8c0db1cb8fa3",
+        "receiptsRoot": "0x56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421",
+        "sha3Uncles": "0x1dcc4de8dec75d7aab85b567b6ccd41ad312451b948a7413f0a142fd40d49347",
+        "size": "0x219",
+        "stateRoot": "0xd67e4d450343046425ae4271474353857ab860dbc0a1dde64b41b5cd3a532bf3",
+        "timestamp": "0x55ba4224",
+        "totalDifficulty": "0x7ff800000",
+        "transactions": [],
+        "transactionsRoot": "0x56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421",
+        "uncles": uncles
+    })
+}
+
+/// A transport answering each request with `handler`, and recording them.
+struct MockTransport<F> {
+    handler: F,
+    requests: RefCell<Vec<Value>>,
+}
+
+impl<F: Fn(&Value) -> Result<Value, RpcError>> MockTransport<F> {
+    fn new(handler: F) -> Self {
+        Self {
+            handler,
+            requests: RefCell::default(),
+        }
+    }
+}
+
+impl<F: Fn(&Value) -> Result<Value, RpcError>> Transport for MockTransport<F> {
+    fn post(&self, body: &str) -> Result<String, RpcError> {
+        let request: Value = serde_json::from_str(body).unwrap();
+        self.requests.borrow_mut().push(request.clone());
+        (self.handler)(&request).map(|response| response.to_string())
+    }
+}
+
+/// Answer a single request or a batch, computing each result with
+/// `result`.
+fn respond(request: &Value, result: impl Fn(&str, &Value) -> Value) -> Value {
+    let respond_one = |request: &Value| {
+        let method = request["method"].as_str().unwrap();
+        json!({ "jsonrpc": "2.0", "id": request["id"], "result": result(method, &request["params"]) })
+    };
+    match request {
+        Value::Array(requests) => requests.iter().rev().map(respond_one).collect(),
+        request => respond_one(request),
+    }
+}
+
+#[test]
+fn test_parse_block() {
+    let block = RpcBlock::from_json(&block_1(vec![])).unwrap();
+
+    assert_eq!(hex(&block.hash), BLOCK_1_HASH);
+    assert_eq!(rlp::rlp_hash(&block.header), block.hash);
+    assert_eq!(block.header.number, Uint::from(1u8));
+    assert_eq!(block.header.timestamp, Uint::from(1438269988u32));
+    assert!(matches!(block.transactions, RpcTransactions::Hashes(hashes) if hashes.is_empty()));
+    assert_eq!(block.total_difficulty, Some(Uint::from(0x7ff800000u64)));
+
+    let mut post_merge = block_1(vec![]);
+    post_merge.as_object_mut().unwrap().remove("totalDifficulty");
+    assert_eq!(RpcBlock::from_json(&post_merge).unwrap().total_difficulty, None);
+}
+
+#[test]
+fn test_typed_requests() {
+    let transport = MockTransport::new(|request: &Value| {
+        Ok(respond(request, |method, _| match method {
+            "eth_blockNumber" => json!("0x10"),
+            "debug_getRawHeader" => json!("0xc0"),
+            "debug_getRawReceipts" => json!(["0x01", "0x0203"]),
+            _ => Value::Null,
+        }))
+    });
+    let client = RpcClient::with_transport(transport);
+
+    assert_eq!(client.block_number().unwrap(), Uint::from(16u8));
+    assert_eq!(client.get_raw_header(7).unwrap().as_ref(), [0xc0]);
+    assert_eq!(client.get_raw_receipts(7).unwrap(), vec![vec![1].into(), vec![2, 3].into()]);
+    assert!(client.get_block_by_number(1 << 40, false).unwrap().is_none());
+
+    let requests = client.transport().requests.borrow().clone();
+    assert_eq!(requests[1]["method"], "debug_getRawHeader");
+    assert_eq!(requests[1]["params"], json!(["0x7"]));
+    assert_eq!(requests[3]["params"], json!(["0x10000000000", false]));
+    assert_ne!(requests[0]["id"], requests[1]["id"]);
+}
+
+#[test]
+fn test_batch() {
+    let transport = MockTransport::new(|request: &Value| {
+        Ok(respond(request, |_, params| match params[0].as_str() {
+            Some("0x1") => block_1(vec![]),
+            _ => Value::Null,
+        }))
+    });
+    let client = RpcClient::with_transport(transport);
+
+    // The mock answers in reverse order.
+    let blocks = client.get_blocks_by_number([2, 1], false).unwrap();
+    assert!(blocks[0].is_none());
+    assert_eq!(hex(&blocks[1].as_ref().unwrap().hash), BLOCK_1_HASH);
+
+    let results = client
+        .batch(&[Request::block_number(), Request::get_raw_block(1)])
+        .unwrap();
+    assert_eq!(results.len(), 2);
+    assert_eq!(client.transport().requests.borrow().len(), 2);
+}
+
+#[test]
+fn test_get_blocks_with_ommers() {
+    let uncle_hash = "0x".to_owned() + &"ab".repeat(32);
+    let transport = MockTransport::new(move |request: &Value| {
+        Ok(respond(request, |method, params| match (method, params[0].as_str()) {
+            ("eth_getBlockByNumber", Some("0x1")) => block_1(vec![&uncle_hash]),
+            ("eth_getUncleByBlockHashAndIndex", Some(BLOCK_1_HASH)) => block_1(vec![]),
+            _ => Value::Null,
+        }))
+    });
+    let client = RpcClient::with_transport(transport);
+
+    let blocks = client.get_blocks(1..3).unwrap();
+    let [(hash, block)] = blocks.as_slice() else {
+        panic!("expected one block, got {}", blocks.len());
+    };
+    assert_eq!(hex(hash), BLOCK_1_HASH);
+    assert_eq!(block.ommers.len(), 1);
+    assert_eq!(block.ommers[0].number, Uint::from(1u8));
+    assert!(block.transactions.is_empty());
+}
+
+#[test]
+fn test_retry() {
+    let attempts = Cell::new(0);
+    let transport = MockTransport::new(|request: &Value| {
+        attempts.set(attempts.get() + 1);
+        match attempts.get() {
+            1 => Err(RpcError::Http(503)),
+            2 => Err(RpcError::Transport("connection reset".to_owned())),
+            _ => Ok(respond(request, |_, _| json!("0x1"))),
+        }
+    });
+    let retry = RetryPolicy {
+        attempts: 3,
+        initial_backoff: Duration::ZERO,
+        max_backoff: Duration::ZERO,
+    };
+    let client = RpcClient::with_transport(transport).with_retry(retry.clone());
+    assert_eq!(client.block_number().unwrap(), Uint::from(1u8));
+    assert_eq!(attempts.get(), 3);
+
+    // RPC errors are answers, and aren't retried.
+    let transport = MockTransport::new(|request: &Value| {
+        Ok(json!({ "jsonrpc": "2.0", "id": request["id"], "error": { "code": -32601, "message": "no such method" } }))
+    });
+    let client = RpcClient::with_transport(transport).with_retry(retry);
+    match client.get_raw_block(1) {
+        Err(RpcError::Rpc { code, message }) => assert_eq!((code, message.as_str()), (-32601, "no such method")),
+        other => panic!("expected an RPC error, got {other:?}"),
+    }
+    assert_eq!(client.transport().requests.borrow().len(), 1);
+}
+
+#[test]
+fn test_http_transport() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let server = thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        let mut request = vec![];
+        let mut buffer = [0; 1024];
+        // Read until the whole JSON body has arrived.
+        while !request.ends_with(b"}") {
+            let read = stream.read(&mut buffer).unwrap();
+            request.extend_from_slice(&buffer[..read]);
+        }
+        let body = r#"{"jsonrpc":"2.0","id":1,"result":"0x2a"}"#;
+        let (first, second) = body.split_at(10);
+        write!(
+            stream,
+            "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nTransfer-Encoding: chunked\r\n\r\n\
+             {:x}\r\n{first}\r\n{:x}\r\n{second}\r\n0\r\n\r\n",
+            first.len(),
+            second.len()
+        )
+        .unwrap();
+        String::from_utf8(request).unwrap()
+    });
+
+    let client = RpcClient::new(&format!("http://127.0.0.1:{port}/rpc")).unwrap();
+    assert_eq!(client.block_number().unwrap(), Uint::from(42u8));
+    let request = server.join().unwrap();
+    assert!(request.starts_with("POST /rpc HTTP/1.1\r\n"));
+    assert!(request.contains(r#""method":"eth_blockNumber""#));
+
+    assert!(matches!(HttpTransport::new("https://example.com"), Err(RpcError::Transport(_))));
+}
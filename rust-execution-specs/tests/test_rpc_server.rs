@@ -0,0 +1,261 @@
+use std::{net::TcpListener, thread};
+
+use execution_specs_rs::{
+    ethereum::{
+        base_types::Uint,
+        crypto::hash::keccak256,
+        frontier::{
+            proof::{verify_proof, AccountProof},
+            state::{get_account, state_root},
+            vm::BlockEnvironment,
+        },
+        utils::hexadecimal::{hex, hex_to_bytes, hex_to_bytes20, hex_to_hash},
+    },
+    ethereum_spec_tools::{
+        evm_tools::run::RunConfig,
+        rpc::{Request, RpcClient, RpcError},
+        rpc_server::{load_state, SpecRpc},
+    },
+};
+use serde_json::{json, Value};
+
+const SENDER: &str = "0x1000000000000000000000000000000000000001";
+/// Returns the value of its storage slot 0.
+const GETTER: &str = "0xcccccccccccccccccccccccccccccccccccccccc";
+/// JUMP(0), which is not a JUMPDEST.
+const BAD_JUMP: &str = "0xdddddddddddddddddddddddddddddddddddddddd";
+/// Calls `GETTER`, then reads its own storage slot 1.
+const PROXY: &str = "0xeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeee";
+
+fn alloc() -> Value {
+    json!({
+        SENDER: { "balance": "0xde0b6b3a7640000" },
+        GETTER: {
+            "balance": "0x0",
+            "code": "0x60005460005260206000f3",
+            "storage": { "0x00": "0x2a" }
+        },
+        BAD_JUMP: { "balance": "0x0", "code": "0x600056" },
+        PROXY: {
+            "balance": "0x0",
+            "code": "0x6000600060006000600073cccccccccccccccccccccccccccccccccccccccc611000f160015400"
+        }
+    })
+}
+
+fn server() -> SpecRpc {
+    let (state, block) = load_state(&alloc().to_string()).unwrap();
+    assert!(block.is_none());
+    SpecRpc::new(state, RunConfig::default().block, 1337)
+}
+
+fn request(method: &str, params: Value) -> Value {
+    json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params })
+}
+
+#[test]
+fn test_call() {
+    let mut server = server();
+    let root = state_root(server.state());
+
+    let response = server.handle(&request("eth_call", json!([{ "from": SENDER, "to": GETTER }, "latest"])));
+    assert_eq!(response["result"], format!("0x{:064x}", 42));
+
+    // A transfer and its nonce increment are undone.
+    let response = server.handle(&request(
+        "eth_call",
+        json!([{ "from": SENDER, "to": GETTER, "value": "0x1" }]),
+    ));
+    assert!(response.get("error").is_none());
+    assert_eq!(state_root(server.state()), root);
+
+    let response = server.handle(&request("eth_call", json!([{ "to": BAD_JUMP }])));
+    assert_eq!(response["error"]["code"], -32000);
+}
+
+#[test]
+fn test_estimate_gas() {
+    let mut server = server();
+
+    let response = server.handle(&request("eth_estimateGas", json!([{ "from": SENDER, "to": GETTER }])));
+    assert_eq!(response["result"], format!("{:#x}", 21000 + 3 + 50 + 3 + 3 + 3 + 3 + 3));
+
+    // Each non-zero byte of data costs 68.
+    let response = server.handle(&request(
+        "eth_estimateGas",
+        json!([{ "from": SENDER, "to": SENDER, "data": "0x01" }]),
+    ));
+    assert_eq!(response["result"], format!("{:#x}", 21000 + 68));
+
+    let response = server.handle(&request("eth_estimateGas", json!([{ "to": BAD_JUMP, "gas": "0x5208" }])));
+    assert_eq!(response["error"]["message"], "gas required exceeds allowance (21000)");
+
+    let response = server.handle(&request("eth_estimateGas", json!([{ "to": GETTER, "gas": "0x1" }])));
+    assert!(response["error"]["message"].as_str().unwrap().starts_with("intrinsic gas too low"));
+}
+
+#[test]
+fn test_create_access_list() {
+    let mut server = server();
+    let root = state_root(server.state());
+
+    let response = server.handle(&request("eth_createAccessList", json!([{ "from": SENDER, "to": PROXY }])));
+    let result = &response["result"];
+    // The proxy is the recipient, so neither it nor its slot is listed.
+    assert_eq!(
+        result["accessList"],
+        json!([{ "address": GETTER, "storageKeys": [format!("0x{:064x}", 0)] }])
+    );
+    let trace = server.handle(&request("debug_traceCall", json!([{ "from": SENDER, "to": PROXY }])));
+    assert_eq!(result["gasUsed"], format!("{:#x}", trace["result"]["gas"].as_u64().unwrap()));
+    assert!(result.get("error").is_none());
+    assert_eq!(state_root(server.state()), root);
+
+    let response = server.handle(&request("eth_createAccessList", json!([{ "from": SENDER, "to": GETTER }])));
+    assert_eq!(response["result"]["accessList"], json!([]));
+
+    let response = server.handle(&request("eth_createAccessList", json!([{ "to": BAD_JUMP }])));
+    assert_eq!(response["result"]["error"], "InvalidJumpDest");
+}
+
+#[test]
+fn test_trace_call() {
+    let mut server = server();
+
+    let response = server.handle(&request("debug_traceCall", json!([{ "to": GETTER }, "latest", {}])));
+    let trace = &response["result"];
+    assert_eq!(trace["failed"], false);
+    assert_eq!(trace["gas"], 21000 + 68);
+    assert_eq!(trace["returnValue"], format!("0x{:064x}", 42));
+    let ops: Vec<_> = trace["structLogs"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|log| log["op"].as_str().unwrap())
+        .collect();
+    assert_eq!(ops, ["PUSH1", "SLOAD", "PUSH1", "MSTORE", "PUSH1", "PUSH1", "RETURN"]);
+    assert_eq!(trace["structLogs"][1]["gasCost"], 50);
+    assert_eq!(trace["structLogs"][1]["stack"], json!(["0x0"]));
+
+    let response = server.handle(&request("debug_traceCall", json!([{ "to": BAD_JUMP }])));
+    assert_eq!(response["result"]["failed"], true);
+}
+
+#[test]
+fn test_get_proof() {
+    let mut server = server();
+    let response = server.handle(&request("eth_getProof", json!([GETTER, ["0x0"], "latest"])));
+    let proof = &response["result"];
+    assert_eq!(proof["storageProof"][0]["value"], "0x2a");
+
+    let address = hex_to_bytes20(GETTER).unwrap();
+    let account = get_account(server.state(), &address);
+    let proof = AccountProof {
+        address,
+        account_proof: proof["accountProof"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|node| hex_to_bytes(node.as_str().unwrap()).unwrap())
+            .collect(),
+        balance: account.balance,
+        code_hash: keccak256(&account.code),
+        nonce: account.nonce,
+        storage_hash: hex_to_hash(proof["storageHash"].as_str().unwrap()).unwrap(),
+        storage_proof: vec![],
+    };
+    verify_proof(&state_root(server.state()), &proof).unwrap();
+}
+
+#[test]
+fn test_storage() {
+    let mut server = server();
+    let response = server.handle(&request("eth_getStorageAt", json!([GETTER, "0x0", "latest"])));
+    assert_eq!(response["result"], format!("0x{:064x}", 42));
+    let response = server.handle(&request("eth_getStorageAt", json!([SENDER, "0x1", "latest"])));
+    assert_eq!(response["result"], format!("0x{:064x}", 0));
+
+    let zero = format!("0x{:064x}", 0);
+    let hash = hex(&keccak256(&[0; 32]));
+    let response = server.handle(&request("debug_storageRangeAt", json!([zero, 0, GETTER, "0x0", 10])));
+    assert_eq!(
+        response["result"],
+        json!({ "storage": { hash.clone(): { "key": zero, "value": format!("0x{:064x}", 42) } }, "nextKey": null })
+    );
+    // Past the only slot.
+    let response = server.handle(&request("debug_storageRangeAt", json!([zero, 0, GETTER, format!("0x{}", "f".repeat(64)), 10])));
+    assert_eq!(response["result"], json!({ "storage": {}, "nextKey": null }));
+    let response = server.handle(&request("debug_storageRangeAt", json!([zero, 0, GETTER, "0x0", 0])));
+    assert_eq!(response["result"], json!({ "storage": {}, "nextKey": hash }));
+}
+
+#[test]
+fn test_requests() {
+    let mut server = server();
+
+    let responses = server.handle(&json!([
+        request("eth_chainId", json!([])),
+        request("eth_blockNumber", json!([])),
+        request("eth_sendTransaction", json!([])),
+    ]));
+    assert_eq!(responses[0]["result"], "0x539");
+    assert_eq!(responses[1]["result"], "0x0");
+    assert_eq!(responses[2]["error"]["code"], -32601);
+
+    let response = server.handle(&request("eth_call", json!([{ "to": "0xzz" }])));
+    assert_eq!(response["error"]["code"], -32602);
+}
+
+#[test]
+fn test_fixture_state() {
+    let fixture = json!({
+        "callGetter": {
+            "env": {
+                "currentCoinbase": "0x2adc25665018aa1fe0e6bc666dac8fc2697ff9ba",
+                "currentDifficulty": "0x020000",
+                "currentGasLimit": "0x7fffffffffffffff",
+                "currentNumber": "0x01",
+                "currentTimestamp": "0x03e8"
+            },
+            "pre": alloc(),
+            "transaction": {},
+            "post": {}
+        }
+    });
+    let (state, block) = load_state(&fixture.to_string()).unwrap();
+    let BlockEnvironment { number, time, .. } = block.unwrap();
+    assert_eq!((number, time), (Uint::from(1u8), Uint::from(1000u16)));
+
+    let (alloc_state, _) = load_state(&alloc().to_string()).unwrap();
+    assert_eq!(state_root(&state), state_root(&alloc_state));
+}
+
+#[test]
+fn test_serve() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let mut server = server();
+    // The server runs until the process exits.
+    thread::spawn(move || server.serve(listener));
+
+    let client = RpcClient::new(&format!("http://127.0.0.1:{port}")).unwrap();
+    assert_eq!(client.block_number().unwrap(), Uint::default());
+    let output = client
+        .call(&Request::new("eth_call", json!([{ "to": GETTER }, "latest"])))
+        .unwrap();
+    assert_eq!(output, format!("0x{:064x}", 42));
+    match client.call(&Request::new("eth_call", json!([{ "to": BAD_JUMP }]))) {
+        Err(RpcError::Rpc { code, .. }) => assert_eq!(code, -32000),
+        other => panic!("expected an RPC error, got {other:?}"),
+    }
+}
+
+#[test]
+fn test_transactions_without_chain() {
+    let mut server = server();
+    let hash = format!("0x{}", "ab".repeat(32));
+    let response = server.handle(&request("eth_getTransactionReceipt", json!([hash])));
+    assert_eq!(response["result"], Value::Null);
+    let response = server.handle(&request("eth_getTransactionByHash", json!(["0xzz"])));
+    assert_eq!(response["error"]["code"], -32602);
+}
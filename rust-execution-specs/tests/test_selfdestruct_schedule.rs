@@ -0,0 +1,80 @@
+use execution_specs_rs::ethereum::{
+    base_types::{Uint, U256},
+    frontier::fork_types::Account,
+    selfdestruct::{
+        selfdestruct_destroys, selfdestruct_gas, selfdestruct_refund, NewAccount, SelfdestructSchedule, FRONTIER,
+    },
+};
+
+/// Tangerine Whistle, EIP-150.
+const TANGERINE_WHISTLE: SelfdestructSchedule = SelfdestructSchedule {
+    cost: 5000,
+    new_account_cost: 25000,
+    ..FRONTIER
+};
+
+/// Spurious Dragon, EIP-161.
+const SPURIOUS_DRAGON: SelfdestructSchedule = SelfdestructSchedule {
+    new_account: NewAccount::DeadWithValue,
+    clear_touched: true,
+    ..TANGERINE_WHISTLE
+};
+
+/// London, EIP-3529.
+const LONDON: SelfdestructSchedule = SelfdestructSchedule {
+    refund: 0,
+    ..SPURIOUS_DRAGON
+};
+
+/// Cancun, EIP-6780.
+const CANCUN: SelfdestructSchedule = SelfdestructSchedule {
+    only_in_creating_transaction: true,
+    ..LONDON
+};
+
+fn alive() -> Option<Account> {
+    Some(Account {
+        nonce: Uint::from(1u8),
+        ..Default::default()
+    })
+}
+
+fn empty() -> Option<Account> {
+    Some(Account::default())
+}
+
+#[test]
+fn test_selfdestruct_gas() {
+    let zero = U256::from(0u8);
+    let value = U256::from(7u8);
+
+    // Frontier has no surcharge, so never looks up the beneficiary.
+    assert_eq!(selfdestruct_gas(&FRONTIER, &value, || unreachable!()), 0);
+
+    assert_eq!(selfdestruct_gas(&TANGERINE_WHISTLE, &zero, || None), 30000);
+    assert_eq!(selfdestruct_gas(&TANGERINE_WHISTLE, &zero, empty), 5000);
+    assert_eq!(selfdestruct_gas(&TANGERINE_WHISTLE, &value, alive), 5000);
+
+    for schedule in [SPURIOUS_DRAGON, LONDON, CANCUN] {
+        assert_eq!(selfdestruct_gas(&schedule, &zero, || None), 5000);
+        assert_eq!(selfdestruct_gas(&schedule, &value, || None), 30000);
+        assert_eq!(selfdestruct_gas(&schedule, &value, empty), 30000);
+        assert_eq!(selfdestruct_gas(&schedule, &value, alive), 5000);
+    }
+}
+
+#[test]
+fn test_selfdestruct_refund() {
+    assert_eq!(selfdestruct_refund(&FRONTIER, 2), Uint::from(48000u32));
+    assert_eq!(selfdestruct_refund(&SPURIOUS_DRAGON, 1), Uint::from(24000u32));
+    assert_eq!(selfdestruct_refund(&LONDON, 2), Uint::from(0u8));
+}
+
+#[test]
+fn test_selfdestruct_destroys() {
+    for schedule in [FRONTIER, TANGERINE_WHISTLE, SPURIOUS_DRAGON, LONDON] {
+        assert!(selfdestruct_destroys(&schedule, || unreachable!()));
+    }
+    assert!(selfdestruct_destroys(&CANCUN, || true));
+    assert!(!selfdestruct_destroys(&CANCUN, || false));
+}
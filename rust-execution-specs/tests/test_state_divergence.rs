@@ -0,0 +1,193 @@
+use execution_specs_rs::{
+    ethereum::{
+        base_types::{Uint, U256},
+        exceptions::EthereumException,
+        frontier::{
+            fork::recover_sender,
+            fork_types::{keccak256, Account, Address, Block, Header, Transaction},
+            proof::get_proof,
+            state::{set_account, set_storage, state_root, State},
+            utils::address::compute_contract_address,
+        },
+        transaction_signature::sign_transaction,
+    },
+    ethereum_spec_tools::{
+        evm_tools::dump::{dump_state, load_state_dump},
+        state_divergence::{block_addresses, find_divergence, find_divergence_in_proofs, AccountFields, Divergence},
+    },
+};
+
+const ALICE: Address = [0xaa; 20];
+const BOB: Address = [0xbb; 20];
+
+fn account(nonce: u8, balance: u32) -> Option<Account> {
+    Some(Account {
+        nonce: Uint::from(nonce),
+        balance: U256::from(balance),
+        code: vec![0x60, 0x00].into(),
+    })
+}
+
+fn key(byte: u8) -> [u8; 32] {
+    [byte; 32]
+}
+
+/// Two accounts, with two slots each.
+fn reference() -> State {
+    let mut state = State::default();
+    for address in [ALICE, BOB] {
+        set_account(&mut state, address, account(1, 100));
+        set_storage(&mut state, address, &key(1), U256::from(1u8));
+        set_storage(&mut state, address, &key(2), U256::from(2u8));
+    }
+    state
+}
+
+#[test]
+fn test_find_divergence() {
+    let theirs = reference();
+    assert_eq!(find_divergence(&theirs, &theirs.clone()), None);
+
+    // A round trip through a dump loses nothing.
+    let dumped = load_state_dump(&dump_state(&theirs).to_string()).unwrap();
+    assert_eq!(find_divergence(&dumped, &theirs), None);
+
+    let mut ours = theirs.clone();
+    set_storage(&mut ours, BOB, &key(2), U256::from(3u8));
+    assert_eq!(
+        find_divergence(&ours, &theirs),
+        Some(Divergence::Storage {
+            address: BOB,
+            key: key(2),
+            ours: U256::from(3u8),
+            theirs: U256::from(2u8),
+        })
+    );
+
+    // The account's own fields are compared before its storage.
+    set_account(&mut ours, BOB, account(2, 100));
+    assert!(matches!(
+        find_divergence(&ours, &theirs),
+        Some(Divergence::Account { address: BOB, ours: Some(_), theirs: Some(_) })
+    ));
+
+    // Accounts are compared in the order of their hashed addresses.
+    set_account(&mut ours, ALICE, None);
+    let first = if keccak256(&ALICE) < keccak256(&BOB) { ALICE } else { BOB };
+    let divergence = find_divergence(&ours, &theirs).unwrap();
+    assert!(matches!(divergence, Divergence::Account { address, .. } if address == first));
+}
+
+#[test]
+fn test_find_divergence_in_proofs() {
+    let theirs = reference();
+    let root = state_root(&theirs);
+    let proofs = |keys: &[[u8; 32]]| -> Vec<_> {
+        [ALICE, BOB, [0xcc; 20]].iter().map(|address| get_proof(&theirs, address, keys)).collect()
+    };
+    assert_eq!(find_divergence_in_proofs(&theirs, &root, &proofs(&[key(1), key(2)])).unwrap(), None);
+
+    let mut ours = theirs.clone();
+    set_storage(&mut ours, ALICE, &key(2), U256::from(0u8));
+    let divergence = find_divergence_in_proofs(&ours, &root, &proofs(&[key(1), key(2)])).unwrap();
+    assert_eq!(
+        divergence,
+        Some(Divergence::Storage {
+            address: ALICE,
+            key: key(2),
+            ours: U256::from(0u8),
+            theirs: U256::from(2u8),
+        })
+    );
+    assert_eq!(
+        divergence.unwrap().to_string(),
+        format!(
+            "storage slot 0x{} of account 0x{} is 0x0, expected 0x2",
+            "02".repeat(32),
+            "aa".repeat(20)
+        )
+    );
+
+    // Without a proof of the slot, only the storage root tells.
+    let divergence = find_divergence_in_proofs(&ours, &root, &proofs(&[key(1)])).unwrap();
+    let Some(Divergence::Account { address: ALICE, ours: Some(a), theirs: Some(b) }) = divergence else {
+        panic!("expected an account divergence, got {divergence:?}");
+    };
+    assert_eq!((&a.nonce, &a.balance, &a.code_hash), (&b.nonce, &b.balance, &b.code_hash));
+    assert_ne!(a.storage_root, b.storage_root);
+
+    // An account the proofs show absent.
+    let mut ours = theirs.clone();
+    set_account(&mut ours, [0xcc; 20], account(0, 1));
+    let divergence = find_divergence_in_proofs(&ours, &root, &proofs(&[])).unwrap();
+    assert_eq!(
+        divergence,
+        Some(Divergence::Account {
+            address: [0xcc; 20],
+            ours: AccountFields::of(&ours, &[0xcc; 20]),
+            theirs: None,
+        })
+    );
+
+    // Proofs must be from the expected root.
+    let mut tampered = proofs(&[key(1)]);
+    tampered[0].balance += 1u8;
+    assert!(matches!(
+        find_divergence_in_proofs(&theirs, &root, &tampered),
+        Err(EthereumException::ValueError)
+    ));
+}
+
+#[test]
+fn test_block_addresses() {
+    let private_key = [0x45; 32];
+    let create = Transaction {
+        nonce: U256::from(0u8),
+        gas_price: U256::from(1u8),
+        gas: U256::from(100_000u32),
+        to: None,
+        value: U256::from(0u8),
+        data: vec![].into(),
+        v: U256::from(0u8),
+        r: U256::from(0u8),
+        s: U256::from(0u8),
+    };
+    let create = sign_transaction(&create, &private_key, None).unwrap();
+    let call = sign_transaction(&Transaction { to: Some(BOB), ..create.clone() }, &private_key, None).unwrap();
+    let header = Header {
+        parent_hash: [0; 32],
+        ommers_hash: [0; 32],
+        coinbase: ALICE,
+        state_root: [0; 32],
+        transactions_root: [0; 32],
+        receipt_root: [0; 32],
+        bloom: [0; 256],
+        difficulty: Uint::default(),
+        number: Uint::default(),
+        gas_limit: Uint::default(),
+        gas_used: Uint::default(),
+        timestamp: U256::default(),
+        extra_data: vec![].into(),
+        mix_digest: [0; 32],
+        nonce: [0; 8],
+    };
+    let block = Block {
+        header: header.clone(),
+        transactions: vec![create.clone(), call, create],
+        ommers: vec![Header { coinbase: [0xcc; 20], ..header }],
+    };
+
+    let mut state = State::default();
+    let sender = recover_sender(&block.transactions[0]).unwrap();
+    set_account(&mut state, sender, account(5, 0));
+    let mut expected = vec![
+        ALICE,
+        BOB,
+        [0xcc; 20],
+        sender,
+        compute_contract_address(&sender, &Uint::from(5u8)),
+        compute_contract_address(&sender, &Uint::from(7u8)),
+    ];
+    expected.sort();
+    assert_eq!(block_addresses(&state, &block).unwrap(), expected);
+}
@@ -0,0 +1,92 @@
+use execution_specs_rs::{
+    ethereum::{
+        base_types::{Uint, U256},
+        frontier::{
+            fork_types::Account,
+            state::{get_account, get_storage, set_account, set_storage, state_root, State},
+        },
+    },
+    ethereum_spec_tools::evm_tools::dump::{dump_state, load_state_dump},
+};
+
+fn slot(n: u8) -> [u8; 32] {
+    let mut key = [0; 32];
+    key[31] = n;
+    key
+}
+
+fn state() -> State {
+    let mut state = State::default();
+    set_account(
+        &mut state,
+        [1; 20],
+        Some(Account {
+            nonce: Uint::from(3u8),
+            balance: U256::from(10u8).pow(18),
+            code: vec![0x60, 0x00].into(),
+        }),
+    );
+    set_storage(&mut state, [1; 20], &slot(1), U256::from(0x1234u32));
+    set_account(&mut state, [2; 20], Some(Account::default()));
+    state
+}
+
+#[test]
+fn test_dump_format() {
+    let dump = dump_state(&state());
+    let account = &dump["accounts"]["0x0101010101010101010101010101010101010101"];
+    assert_eq!(account["balance"], "1000000000000000000");
+    assert_eq!(account["nonce"], 3);
+    assert_eq!(account["code"], "0x6000");
+    assert_eq!(
+        account["storage"]["0x0000000000000000000000000000000000000000000000000000000000000001"],
+        "1234"
+    );
+
+    let empty = &dump["accounts"]["0x0202020202020202020202020202020202020202"];
+    assert!(empty.get("code").is_none());
+    assert!(empty.get("storage").is_none());
+    assert_eq!(
+        empty["codeHash"],
+        "0xc5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470"
+    );
+}
+
+#[test]
+fn test_round_trip() {
+    let state = state();
+    let loaded = load_state_dump(&dump_state(&state).to_string()).unwrap();
+    assert_eq!(state_root(&loaded), state_root(&state));
+    assert_eq!(get_account(&loaded, &[1; 20]), get_account(&state, &[1; 20]));
+    assert_eq!(get_storage(&loaded, &[1; 20], &slot(1)), U256::from(0x1234u32));
+}
+
+#[test]
+fn test_load_checks_root_and_code_hash() {
+    let mut dump = dump_state(&state());
+    dump["root"] = "0x0000000000000000000000000000000000000000000000000000000000000000".into();
+    assert!(load_state_dump(&dump.to_string()).is_err());
+
+    let mut dump = dump_state(&state());
+    dump["accounts"]["0x0101010101010101010101010101010101010101"]["code"] = "0x6001".into();
+    assert!(load_state_dump(&dump.to_string()).is_err());
+}
+
+#[test]
+fn test_load_hand_written_dump() {
+    // No root, hashes or addresses, and prefixed storage values.
+    let dump = r#"{
+        "accounts": {
+            "0x0303030303030303030303030303030303030303": {
+                "balance": "0x10",
+                "nonce": "5",
+                "storage": { "0x02": "0x07" }
+            }
+        }
+    }"#;
+    let state = load_state_dump(dump).unwrap();
+    let account = get_account(&state, &[3; 20]);
+    assert_eq!(account.balance, U256::from(16u8));
+    assert_eq!(account.nonce, Uint::from(5u8));
+    assert_eq!(get_storage(&state, &[3; 20], &slot(2)), U256::from(7u8));
+}
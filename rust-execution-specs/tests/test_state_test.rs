@@ -0,0 +1,122 @@
+use execution_specs_rs::{
+    ethereum::{
+        base_types::{Uint, U256},
+        frontier::state::state_root,
+        utils::hexadecimal::hex,
+    },
+    ethereum_spec_tools::{
+        evm_tools::{
+            alloc::{dump_alloc, load_alloc},
+            state_test::{fill_to_directory, Expect, FillError, Indexes, StateTestFiller, StateTestTx},
+        },
+        forks::HardFork,
+        test_env::slot,
+    },
+};
+use hex_literal::hex;
+
+const CONTRACT: [u8; 20] = [0xcc; 20];
+const SENDER: [u8; 20] = hex!("a94f5374fce5edbc8e2a8697c15331677e6ebf0b");
+
+/// Stores the call data's first word at slot 0.
+fn filler(name: &str) -> StateTestFiller {
+    // PUSH1 0, CALLDATALOAD, PUSH1 0, SSTORE
+    StateTestFiller::new(name).with_code(CONTRACT, [0x60, 0x00, 0x35, 0x60, 0x00, 0x55])
+}
+
+fn word(n: u8) -> [u8; 32] {
+    slot(n.into())
+}
+
+#[test]
+fn test_fill() {
+    let fixture = filler("store")
+        .with_transaction(StateTestTx::call(CONTRACT).data([word(1), word(2)]))
+        .expect(Expect::new().data_index(0).storage(CONTRACT, slot(0), U256::from(1u8)))
+        .expect(Expect::new().data_index(1).storage(CONTRACT, slot(0), U256::from(2u8)))
+        .expect(Expect::new().nonce(SENDER, Uint::from(1u8)))
+        .fill(&[HardFork::Frontier])
+        .unwrap();
+    let test = &fixture["store"];
+
+    assert_eq!(test["_info"]["filling-tool"], "execution-specs-rs");
+    assert_eq!(test["env"]["currentNumber"], "0x1");
+    assert_eq!(test["transaction"]["sender"], hex(&SENDER));
+    assert_eq!(test["transaction"]["to"], hex(&CONTRACT));
+    assert_eq!(test["transaction"]["data"][1], hex(&word(2)));
+    // The sender was funded.
+    assert_eq!(test["pre"][hex(&SENDER)]["balance"], "0xde0b6b3a7640000");
+
+    let results = test["post"]["Frontier"].as_array().unwrap();
+    assert_eq!(results.len(), 2);
+    for (index, result) in results.iter().enumerate() {
+        assert_eq!(result["indexes"]["data"], index);
+        assert!(result.get("expectException").is_none());
+        // The root is that of the post-state written along.
+        let post = load_alloc(&result["state"].to_string()).unwrap();
+        assert_eq!(result["hash"], hex(&state_root(&post)));
+        assert_eq!(result["state"], dump_alloc(&post));
+    }
+    assert_ne!(results[0]["hash"], results[1]["hash"]);
+    // No logs: the hash of an empty list.
+    assert_eq!(
+        results[0]["logs"],
+        "0x1dcc4de8dec75d7aab85b567b6ccd41ad312451b948a7413f0a142fd40d49347"
+    );
+}
+
+#[test]
+fn test_invalid_transaction() {
+    let filler = filler("intrinsic")
+        .with_transaction(StateTestTx::call(CONTRACT).gas_limit([U256::from(100_000u32), U256::from(20_000u32)]))
+        .expect(Expect::new().gas_index(1).exception().storage(CONTRACT, slot(0), U256::from(0u8)));
+    let fixture = filler.fill(&[HardFork::Frontier]).unwrap();
+    let results = fixture["intrinsic"]["post"]["Frontier"].as_array().unwrap();
+
+    assert!(results[0].get("expectException").is_none());
+    assert_eq!(results[1]["expectException"], "TransactionException.INTRINSIC_GAS_TOO_LOW");
+    let pre = load_alloc(&fixture["intrinsic"]["pre"].to_string()).unwrap();
+    assert_eq!(results[1]["hash"], hex(&state_root(&pre)));
+}
+
+#[test]
+fn test_unmet_expectations() {
+    let error = filler("wrong")
+        .with_transaction(StateTestTx::call(CONTRACT).data([word(1)]).value([U256::from(0u8), U256::from(5u8)]))
+        .expect(Expect::new().value_index(1).balance(CONTRACT, U256::from(6u8)))
+        .fill(&[HardFork::Frontier])
+        .unwrap_err();
+    match error {
+        FillError::Expectation { fork, indexes, message } => {
+            assert_eq!(fork, HardFork::Frontier);
+            assert_eq!(indexes, Indexes { data: 0, gas: 0, value: 1 });
+            assert!(message.contains("expected 6, got 5"), "{message}");
+        }
+        error => panic!("unexpected {error}"),
+    }
+
+    let error = filler("valid")
+        .with_transaction(StateTestTx::call(CONTRACT))
+        .expect(Expect::new().exception())
+        .fill(&[HardFork::Frontier])
+        .unwrap_err();
+    assert!(error.to_string().contains("expected an invalid transaction"));
+
+    assert!(filler("no transaction").fill(&[HardFork::Frontier]).is_err());
+}
+
+#[test]
+fn test_fill_to_directory() {
+    let directory = std::env::temp_dir().join(format!("state-test-filler-{}", std::process::id()));
+    let fillers = [
+        filler("first").with_transaction(StateTestTx::call(CONTRACT)),
+        filler("second").with_transaction(StateTestTx::create([0x00])),
+    ];
+    fill_to_directory(&fillers, &[HardFork::Frontier], &directory).unwrap();
+
+    let second: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(directory.join("second.json")).unwrap()).unwrap();
+    assert_eq!(second["second"]["transaction"]["to"], "");
+    assert!(directory.join("first.json").exists());
+    std::fs::remove_dir_all(directory).unwrap();
+}
@@ -0,0 +1,153 @@
+use execution_specs_rs::ethereum::{
+    base_types::{Uint, U256},
+    frontier::{fork_types::Account, state::set_account, state::State, vm::Evm},
+    storage_gas::{sstore_allowed, sstore_gas, StorageGasSchedule, FRONTIER},
+};
+use hex_literal::hex;
+
+/// Constantinople, EIP-1283.
+const EIP_1283: StorageGasSchedule = StorageGasSchedule {
+    net_metering: true,
+    no_op: 200,
+    set: 20000,
+    reset: 5000,
+    clear_refund: 15000,
+    cold_access: 0,
+    sentry: None,
+};
+
+/// Istanbul, EIP-2200.
+const EIP_2200: StorageGasSchedule = StorageGasSchedule {
+    net_metering: true,
+    no_op: 800,
+    set: 20000,
+    reset: 5000,
+    clear_refund: 15000,
+    cold_access: 0,
+    sentry: Some(2300),
+};
+
+/// Berlin, EIP-2929.
+const EIP_2929: StorageGasSchedule = StorageGasSchedule {
+    net_metering: true,
+    no_op: 100,
+    set: 20000,
+    reset: 5000 - 2100,
+    clear_refund: 15000,
+    cold_access: 2100,
+    sentry: Some(2300),
+};
+
+/// London onwards, EIP-3529.
+const EIP_3529: StorageGasSchedule = StorageGasSchedule {
+    clear_refund: 5000 - 2100 + 1900,
+    ..EIP_2929
+};
+
+/// Run consecutive stores of `values` to one slot whose original value is
+/// `original`, as `PUSH1 value PUSH1 0 SSTORE` each, returning the gas used
+/// (pushes included) and the final refund.
+fn run(schedule: &StorageGasSchedule, original: u8, values: &[u8]) -> (u64, i64) {
+    let original = U256::from(original);
+    let mut current = original.clone();
+    let (mut used, mut refund) = (0, 0);
+    for (i, value) in values.iter().enumerate() {
+        let new = U256::from(*value);
+        let gas = sstore_gas(schedule, &original, &current, &new, i == 0);
+        used += 2 * 3 + gas.cost;
+        refund += gas.refund;
+        current = new;
+    }
+    (used, refund)
+}
+
+#[test]
+fn test_eip_1283_examples() {
+    // From the table in EIP-1283.
+    let cases: &[(u8, &[u8], u64, i64)] = &[
+        (0, &[0, 0], 412, 0),
+        (0, &[0, 1], 20212, 0),
+        (0, &[1, 0], 20212, 19800),
+        (0, &[1, 2], 20212, 0),
+        (0, &[1, 1], 20212, 0),
+        (1, &[0, 0], 5212, 15000),
+        (1, &[0, 1], 5212, 4800),
+        (1, &[0, 2], 5212, 0),
+        (1, &[2, 0], 5212, 15000),
+        (1, &[2, 3], 5212, 0),
+        (1, &[2, 1], 5212, 4800),
+        (1, &[2, 2], 5212, 0),
+        (1, &[1, 0], 5212, 15000),
+        (1, &[1, 2], 5212, 0),
+        (1, &[1, 1], 412, 0),
+        (0, &[1, 0, 1], 40218, 19800),
+        (1, &[0, 1, 0], 10218, 19800),
+    ];
+    for (original, values, used, refund) in cases {
+        assert_eq!(run(&EIP_1283, *original, values), (*used, *refund), "{original} {values:?}");
+    }
+}
+
+#[test]
+fn test_eip_2200_examples() {
+    // From the table in EIP-2200.
+    let cases: &[(u8, &[u8], u64, i64)] = &[
+        (0, &[0, 0], 1612, 0),
+        (0, &[0, 1], 20812, 0),
+        (0, &[1, 0], 20812, 19200),
+        (0, &[1, 2], 20812, 0),
+        (0, &[1, 1], 20812, 0),
+        (1, &[0, 0], 5812, 15000),
+        (1, &[0, 1], 5812, 4200),
+        (1, &[0, 2], 5812, 0),
+        (1, &[2, 0], 5812, 15000),
+        (1, &[2, 3], 5812, 0),
+        (1, &[2, 1], 5812, 4200),
+        (1, &[2, 2], 5812, 0),
+        (1, &[1, 0], 5812, 15000),
+        (1, &[1, 2], 5812, 0),
+        (1, &[1, 1], 1612, 0),
+        (0, &[1, 0, 1], 40818, 19200),
+        (1, &[0, 1, 0], 10818, 19200),
+    ];
+    for (original, values, used, refund) in cases {
+        assert_eq!(run(&EIP_2200, *original, values), (*used, *refund), "{original} {values:?}");
+    }
+}
+
+#[test]
+fn test_eip_2929_and_3529_prices() {
+    // A cold clean reset costs the same 5000 as before EIP-2929.
+    assert_eq!(run(&EIP_2929, 1, &[2]), (6 + 5000, 0));
+    assert_eq!(run(&EIP_2929, 0, &[1, 0]), (12 + 2100 + 20000 + 100, 19900));
+    assert_eq!(run(&EIP_2929, 1, &[0]).1, 15000);
+    assert_eq!(run(&EIP_3529, 1, &[0]).1, 4800);
+    assert_eq!(run(&EIP_3529, 1, &[0, 1]), (12 + 5000 + 100, 2800));
+}
+
+#[test]
+fn test_sentry() {
+    assert!(sstore_allowed(&FRONTIER, &U256::from(0u8)));
+    assert!(!sstore_allowed(&EIP_2200, &U256::from(2300u32)));
+    assert!(sstore_allowed(&EIP_2200, &U256::from(2301u32)));
+}
+
+#[test]
+fn test_frontier_is_not_net_metered() {
+    assert_eq!(run(&FRONTIER, 0, &[1, 0, 1]), (18 + 20000 + 5000 + 20000, 15000));
+
+    // The interpreter charges the same.
+    let contract = [0xcc; 20];
+    let mut state = State::default();
+    set_account(
+        &mut state,
+        contract,
+        Some(Account {
+            code: hex!("6001 6000 55 6000 6000 55 6001 6000 55").into(),
+            ..Default::default()
+        }),
+    );
+    let result = Evm::call().to(contract).run(&mut state).unwrap();
+    assert_eq!(result.gas_used, Uint::from(18u32 + 20000 + 5000 + 20000));
+    assert_eq!(result.refund_counter, U256::from(15000u32));
+}
@@ -0,0 +1,162 @@
+use execution_specs_rs::{
+    ethereum::{base_types::Uint, frontier::state::set_storage, utils::hexadecimal::hex},
+    ethereum_spec_tools::{
+        evm_tools::{
+            alloc::dump_alloc,
+            t8n::{compare, parse_env, transition, T8nInput, T8nResult},
+        },
+        state_divergence::Divergence,
+    },
+};
+use serde_json::json;
+
+const SENDER: &str = "0xa94f5374fce5edbc8e2a8697c15331677e6ebf0b";
+const SECRET_KEY: &str = "0x45a915e4d060149eb4365960e6a7a45f334393093061116b197e3240065ff2d8";
+const CONTRACT: &str = "0xcccccccccccccccccccccccccccccccccccccccc";
+
+fn input() -> T8nInput {
+    let alloc = json!({
+        SENDER: { "balance": "0xde0b6b3a7640000" },
+        // PUSH1 1, PUSH1 0, SSTORE, PUSH1 0, PUSH1 0, LOG0
+        CONTRACT: { "code": "0x600160005560006000a0" },
+    });
+    let env = json!({
+        "currentCoinbase": "0x2adc25665018aa1fe0e6bc666dac8fc2697ff9ba",
+        "currentDifficulty": "0x20000",
+        "currentGasLimit": "0x5f5e100",
+        "currentNumber": "0x1",
+        "currentTimestamp": "0x3e8",
+    });
+    let tx = |nonce: u8| {
+        json!({
+            "nonce": format!("{nonce:#x}"),
+            "gasPrice": "0xa",
+            "gas": "0x186a0",
+            "to": CONTRACT,
+            "value": "0x0",
+            "input": "0x",
+            "secretKey": SECRET_KEY,
+        })
+    };
+    // The third reuses the first nonce.
+    let txs = json!([tx(0), tx(1), tx(0)]);
+    T8nInput::from_json(&alloc.to_string(), &env.to_string(), &txs.to_string()).unwrap()
+}
+
+fn round_trip(result: &T8nResult, input: &T8nInput) -> T8nResult {
+    let result_json = result.result_json().to_string();
+    T8nResult::from_json(&result_json, &dump_alloc(&result.alloc).to_string(), &input.txs).unwrap()
+}
+
+#[test]
+fn test_transition() {
+    let input = input();
+    let result = transition(&input);
+
+    assert_eq!(result.receipts.len(), 2);
+    assert_eq!(result.receipts[1].index, 1);
+    assert_eq!(result.receipts[0].logs.len(), 1);
+    assert_eq!(result.rejected.len(), 1);
+    assert_eq!(result.rejected[0].0, 2);
+    assert_eq!(result.rejected[0].1, "TransactionException.NONCE_MISMATCH_TOO_LOW");
+    assert_eq!(
+        result.gas_used,
+        &result.receipts[0].gas_used + &result.receipts[1].gas_used
+    );
+
+    // What another tool would write reads back the same.
+    let theirs = round_trip(&result, &input);
+    let diff = compare(&result, &theirs);
+    assert!(diff.is_empty(), "{diff}");
+    assert_eq!(diff.to_string(), "no mismatches\n");
+}
+
+#[test]
+fn test_mismatches() {
+    let input = input();
+    let ours = transition(&input);
+
+    let mut theirs = round_trip(&ours, &input);
+    theirs.receipts[1].gas_used += 1u8;
+    theirs.rejected.clear();
+    theirs.state_root = [0; 32];
+    set_storage(&mut theirs.alloc, [0xcc; 20], &[0; 32], Uint::from(2u8));
+
+    let diff = compare(&ours, &theirs);
+    assert_eq!(diff.first_tx(), Some(1));
+    let fields: Vec<_> = diff.mismatches.iter().map(|m| (m.tx_index, m.field.as_str())).collect();
+    assert_eq!(fields, [(Some(1), "gasUsed"), (Some(2), "status"), (None, "stateRoot")]);
+    let gas_used = &ours.receipts[1].gas_used;
+    assert_eq!(
+        diff.mismatches[0].to_string(),
+        format!("tx 1: gasUsed: ours {gas_used}, theirs {}", gas_used + 1u8)
+    );
+    match diff.divergence {
+        Some(Divergence::Storage { address, .. }) => assert_eq!(address, [0xcc; 20]),
+        divergence => panic!("unexpected {divergence:?}"),
+    }
+}
+
+#[test]
+fn test_env() {
+    let env = json!({
+        "currentCoinbase": "0x2adc25665018aa1fe0e6bc666dac8fc2697ff9ba",
+        "currentDifficulty": "0x20000",
+        "currentGasLimit": "0x5f5e100",
+        "currentNumber": "0x3",
+        "currentTimestamp": "0x3e8",
+        "blockHashes": {
+            "0": format!("0x{}", "11".repeat(32)),
+            "2": format!("0x{}", "22".repeat(32)),
+        },
+    });
+    let block = parse_env(&env).unwrap();
+    assert_eq!(block.block_hashes, [[0x11; 32], [0; 32], [0x22; 32]]);
+
+    let mut input = input();
+    input.env = block.clone();
+    let written = input.env_json();
+    assert_eq!(written["blockHashes"]["1"], hex(&[0; 32]));
+    assert_eq!(parse_env(&written).unwrap().block_hashes, block.block_hashes);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_external_tool() {
+    use std::{fs, os::unix::fs::PermissionsExt};
+
+    use execution_specs_rs::ethereum_spec_tools::evm_tools::t8n::{differential, ExternalT8n, T8nError};
+
+    let input = input();
+    let directory = std::env::temp_dir().join(format!("t8n-diff-test-{}", std::process::id()));
+    let work_dir = directory.join("work");
+    fs::create_dir_all(&work_dir).unwrap();
+
+    // A tool agreeing with this crate, except for the gas of the first
+    // transaction, which checks it was given the inputs.
+    let ours = transition(&input);
+    let mut result = ours.result_json();
+    result["receipts"][0]["gasUsed"] = json!("0x1");
+    fs::write(directory.join("result.json"), result.to_string()).unwrap();
+    fs::write(directory.join("post.json"), dump_alloc(&ours.alloc).to_string()).unwrap();
+    let script = format!(
+        "#!/bin/sh\n[ \"$1\" = t8n ] && [ -f {work}/txs.json ] || exit 1\ncp {dir}/result.json {dir}/post.json {work}/\n",
+        dir = directory.display(),
+        work = work_dir.display(),
+    );
+    let program = directory.join("evm");
+    fs::write(&program, script).unwrap();
+    fs::set_permissions(&program, fs::Permissions::from_mode(0o755)).unwrap();
+
+    let diff = differential(&input, &ExternalT8n::new(&program), &work_dir).unwrap();
+    assert_eq!(diff.first_tx(), Some(0));
+    assert_eq!(diff.mismatches.len(), 1);
+    assert!(diff.divergence.is_none());
+
+    let failing = ExternalT8n {
+        program: program.clone(),
+        args: vec!["other".to_owned()],
+    };
+    assert!(matches!(differential(&input, &failing, &work_dir), Err(T8nError::External(_))));
+    fs::remove_dir_all(directory).unwrap();
+}
@@ -0,0 +1,57 @@
+use execution_specs_rs::{
+    ethereum::{
+        base_types::U256,
+        frontier::{
+            fork::recover_sender,
+            fork_types::Transaction,
+            state::{get_account, get_accounts},
+        },
+    },
+    ethereum_spec_tools::test_fixtures::{
+        contract_account, funded_state, initial_balances, pre_state, test_balance, TestAccount, ALICE, BOB, CAROL,
+        TEST_ACCOUNTS,
+    },
+};
+
+#[test]
+fn test_accounts() {
+    for (n, account) in TEST_ACCOUNTS.iter().enumerate() {
+        assert_eq!(TestAccount::from_secret_key(account.secret_key).unwrap(), *account);
+        assert_eq!(TestAccount::nth(n as u32), *account);
+    }
+    assert_ne!(TestAccount::nth(300).address, TestAccount::nth(301).address);
+    assert!(TestAccount::from_secret_key([0; 32]).is_err());
+    assert!(TestAccount::from_secret_key([0xff; 32]).is_err());
+}
+
+#[test]
+fn test_sign() {
+    let tx = Transaction {
+        nonce: U256::default(),
+        gas_price: U256::from(1u8),
+        gas: U256::from(21000u32),
+        to: Some(BOB.address),
+        value: U256::from(1u8),
+        data: vec![].into(),
+        v: U256::default(),
+        r: U256::default(),
+        s: U256::default(),
+    };
+    assert_eq!(recover_sender(&CAROL.sign(&tx, None)).unwrap(), CAROL.address);
+}
+
+#[test]
+fn test_pre_states() {
+    let state = funded_state(&[ALICE, BOB]);
+    assert_eq!(get_accounts(&state).len(), 2);
+    assert_eq!(get_account(&state, &ALICE.address).balance, test_balance());
+    assert_eq!(test_balance(), U256::from(10u8).pow(21));
+
+    let state = pre_state([(CAROL.address, contract_account([0x00]))]);
+    assert_eq!(&*get_account(&state, &CAROL.address).code, &[0x00]);
+    assert_eq!(get_account(&state, &CAROL.address).balance, U256::default());
+
+    let balances = initial_balances(&TEST_ACCOUNTS);
+    assert_eq!(balances.len(), 4);
+    assert_eq!(balances[&BOB.address], test_balance());
+}
@@ -0,0 +1,113 @@
+use execution_specs_rs::{
+    ethereum::{
+        base_types::U256,
+        exceptions::EthereumException,
+        frontier::{
+            fork::{recover_sender as frontier_recover_sender, signing_hash},
+            fork_types::{keccak256, Address, Transaction},
+        },
+        transaction_signature::{
+            recover_sender, sign_transaction, signature_chain_id, signature_schedule, signing_hash_155,
+            signing_hash_pre155, SignatureSchedule, FRONTIER,
+        },
+    },
+    ethereum_spec_tools::forks::HardFork,
+};
+use hex_literal::hex;
+use k256::ecdsa::SigningKey;
+
+/// The signatures Spurious Dragon accepts, EIP-155.
+const SPURIOUS_DRAGON: SignatureSchedule = SignatureSchedule { eip155: true };
+
+fn signing_key() -> SigningKey {
+    SigningKey::from_slice(&[0x45; 32]).unwrap()
+}
+
+fn sender() -> Address {
+    let point = signing_key().verifying_key().to_encoded_point(false);
+    keccak256(&point.as_bytes()[1..])[12..].try_into().unwrap()
+}
+
+fn transaction() -> Transaction {
+    Transaction {
+        nonce: U256::from(9u8),
+        gas_price: U256::from(20_000_000_000u64),
+        gas: U256::from(21000u32),
+        to: Some(hex!("3535353535353535353535353535353535353535")),
+        value: U256::from(10u8).pow(18),
+        data: vec![].into(),
+        v: U256::from(0u8),
+        r: U256::from(0u8),
+        s: U256::from(0u8),
+    }
+}
+
+/// Sign `tx` for `chain_id`, or for every chain.
+fn sign(mut tx: Transaction, chain_id: Option<u64>) -> Transaction {
+    let hash = match chain_id {
+        Some(chain_id) => signing_hash_155(&tx, chain_id),
+        None => signing_hash_pre155(&tx),
+    };
+    let (signature, recovery_id) = signing_key().sign_prehash_recoverable(&hash).unwrap();
+    let (r, s) = signature.split_bytes();
+    let offset = chain_id.map_or(U256::from(27u8), |chain_id| U256::from(35 + 2 * chain_id));
+    tx.v = offset + recovery_id.to_byte();
+    tx.r = U256::from_bytes_be(&r);
+    tx.s = U256::from_bytes_be(&s);
+    tx
+}
+
+#[test]
+fn test_eip155_example() {
+    // The example of EIP-155.
+    let hash = signing_hash_155(&transaction(), 1);
+    assert_eq!(hash, hex!("daf5a779ae972f972197303d7b574746c7ef83eadac0f2791ad23db92e4c8e53"));
+    assert_eq!(signing_hash_pre155(&transaction()), signing_hash(&transaction()));
+}
+
+#[test]
+fn test_replay_protection() {
+    let protected = sign(transaction(), Some(7));
+    assert_eq!(signature_chain_id(&protected), Some(7));
+    assert_eq!(recover_sender(&SPURIOUS_DRAGON, 7, &protected).unwrap(), sender());
+    // Not valid on any other chain, nor before EIP-155.
+    assert!(matches!(recover_sender(&SPURIOUS_DRAGON, 1, &protected), Err(EthereumException::InvalidBlock)));
+    assert!(matches!(recover_sender(&FRONTIER, 7, &protected), Err(EthereumException::InvalidBlock)));
+    assert!(frontier_recover_sender(&protected).is_err());
+
+    // Unprotected signatures are valid everywhere.
+    let unprotected = sign(transaction(), None);
+    assert_eq!(signature_chain_id(&unprotected), None);
+    for (schedule, chain_id) in [(&FRONTIER, 1), (&SPURIOUS_DRAGON, 1), (&SPURIOUS_DRAGON, 7)] {
+        assert_eq!(recover_sender(schedule, chain_id, &unprotected).unwrap(), sender());
+    }
+    assert_eq!(frontier_recover_sender(&unprotected).unwrap(), sender());
+    assert_eq!(signature_schedule(HardFork::Frontier), &FRONTIER);
+
+    let mut malformed = unprotected;
+    malformed.v = U256::from(29u8);
+    assert!(recover_sender(&SPURIOUS_DRAGON, 1, &malformed).is_err());
+}
+
+#[test]
+fn test_sign_transaction() {
+    // The signed transaction of the EIP-155 example.
+    let signed = sign_transaction(&transaction(), &[0x46; 32], Some(1)).unwrap();
+    assert_eq!(signed.v, U256::from(37u8));
+    assert_eq!(
+        signed.r,
+        "18515461264373351373200002665853028612451056578545711640558177340181847433846".parse().unwrap()
+    );
+    assert_eq!(
+        signed.s,
+        "46948507304638947509940763649030358759909902576025900602547168820602576006531".parse().unwrap()
+    );
+    assert_eq!(signed.nonce, transaction().nonce);
+
+    for chain_id in [None, Some(1), Some(1337)] {
+        let signed = sign_transaction(&transaction(), &[0x45; 32], chain_id).unwrap();
+        assert_eq!(signed, sign(transaction(), chain_id));
+        assert_eq!(recover_sender(&SPURIOUS_DRAGON, chain_id.unwrap_or(1), &signed).unwrap(), sender());
+    }
+    assert!(sign_transaction(&transaction(), &[0; 32], None).is_err());
+}
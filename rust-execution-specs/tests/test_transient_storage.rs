@@ -0,0 +1,90 @@
+use execution_specs_rs::ethereum::{
+    base_types::{Bytes32, U256},
+    transient_storage::{
+        begin_transaction, clear_transient_storage, commit_transaction, get_transient_storage,
+        rollback_transaction, set_transient_storage, TransientStorage,
+    },
+};
+
+const CALLER: [u8; 20] = [0xaa; 20];
+const CALLEE: [u8; 20] = [0xcc; 20];
+
+fn slot(n: u8) -> Bytes32 {
+    let mut key = [0; 32];
+    key[31] = n;
+    key
+}
+
+fn get(storage: &TransientStorage, address: &[u8; 20], n: u8) -> U256 {
+    get_transient_storage(storage, address, &slot(n))
+}
+
+fn set(storage: &mut TransientStorage, address: [u8; 20], n: u8, value: u8) {
+    set_transient_storage(storage, address, &slot(n), U256::from(value));
+}
+
+#[test]
+fn test_nested_calls() {
+    let mut storage = TransientStorage::default();
+    assert_eq!(get(&storage, &CALLER, 0), U256::from(0u8));
+
+    // The transaction's frame.
+    begin_transaction(&mut storage);
+    set(&mut storage, CALLER, 0, 1);
+
+    // A call that succeeds, with a nested call that reverts.
+    begin_transaction(&mut storage);
+    set(&mut storage, CALLEE, 0, 2);
+    set(&mut storage, CALLER, 0, 3);
+    begin_transaction(&mut storage);
+    set(&mut storage, CALLEE, 0, 4);
+    set(&mut storage, CALLEE, 1, 5);
+    assert_eq!(get(&storage, &CALLEE, 1), U256::from(5u8));
+    rollback_transaction(&mut storage);
+    assert_eq!(get(&storage, &CALLEE, 0), U256::from(2u8));
+    assert_eq!(get(&storage, &CALLEE, 1), U256::from(0u8));
+    commit_transaction(&mut storage);
+    assert_eq!(get(&storage, &CALLER, 0), U256::from(3u8));
+
+    // A call that reverts after a nested call succeeded: both are undone.
+    begin_transaction(&mut storage);
+    set(&mut storage, CALLEE, 0, 6);
+    begin_transaction(&mut storage);
+    set(&mut storage, CALLEE, 0, 7);
+    set(&mut storage, CALLER, 0, 0);
+    commit_transaction(&mut storage);
+    assert_eq!(get(&storage, &CALLER, 0), U256::from(0u8));
+    rollback_transaction(&mut storage);
+    assert_eq!(get(&storage, &CALLEE, 0), U256::from(2u8));
+    assert_eq!(get(&storage, &CALLER, 0), U256::from(3u8));
+
+    commit_transaction(&mut storage);
+    assert_eq!(get(&storage, &CALLEE, 0), U256::from(2u8));
+
+    // Nothing is left for the next transaction.
+    clear_transient_storage(&mut storage);
+    assert_eq!(get(&storage, &CALLER, 0), U256::from(0u8));
+    assert_eq!(get(&storage, &CALLEE, 0), U256::from(0u8));
+}
+
+#[test]
+fn test_reverted_transaction() {
+    let mut storage = TransientStorage::default();
+    begin_transaction(&mut storage);
+    set(&mut storage, CALLER, 0, 1);
+    // Written twice in one frame, then cleared.
+    set(&mut storage, CALLER, 1, 2);
+    set(&mut storage, CALLER, 1, 3);
+    set(&mut storage, CALLER, 0, 0);
+    rollback_transaction(&mut storage);
+    assert_eq!(get(&storage, &CALLER, 0), U256::from(0u8));
+    assert_eq!(get(&storage, &CALLER, 1), U256::from(0u8));
+}
+
+#[test]
+#[should_panic(expected = "a frame is still open")]
+fn test_clear_inside_a_frame() {
+    let mut storage = TransientStorage::default();
+    begin_transaction(&mut storage);
+    clear_transient_storage(&mut storage);
+}
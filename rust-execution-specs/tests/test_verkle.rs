@@ -0,0 +1,128 @@
+use execution_specs_rs::ethereum::{
+    base_types::{Uint, U256},
+    experimental::verkle::{
+        banderwagon::Point,
+        embedding::{
+            chunkify_code, get_tree_key, get_tree_key_for_basic_data, get_tree_key_for_code_chunk,
+            get_tree_key_for_storage_slot,
+        },
+        ipa::{prove, verify, Transcript},
+        pedersen::{commit, crs},
+        tree::VerkleTree,
+    },
+};
+use hex_literal::hex;
+use sha2::{Digest, Sha256};
+
+const ADDRESS: [u8; 20] = hex!("00000000000000000000000000000000000000ff");
+
+#[test]
+fn test_crs_matches_go_ipa() {
+    let crs = crs();
+    assert_eq!(crs.len(), 256);
+    assert_eq!(
+        crs[0].serialize(),
+        hex!("01587ad1336675eb912550ec2a28eb8923b824b490dd2ba82e48f14590a298a0")
+    );
+    assert_eq!(
+        crs[255].serialize(),
+        hex!("3de2be346b539395b0c0de56a5ccca54a317f1b5c80107b0802af9a62276a4d8")
+    );
+    let hash = crs.iter().fold(Sha256::new(), |hash, point| hash.chain_update(point.serialize()));
+    assert_eq!(
+        hash.finalize()[..],
+        hex!("1fcaea10bf24f750200e06fa473c76ff0468007291fa548e2d99f09ba9256fdb")
+    );
+}
+
+#[test]
+fn test_serialization_round_trip() {
+    let point = Point::generator().mul(&Uint::from(12345u32));
+    assert_eq!(Point::deserialize(&point.serialize()), Some(point.clone()));
+    assert_eq!(point.add(&point.neg()), Point::identity());
+    assert_eq!(Point::identity().serialize(), [0; 32]);
+    // Not the x of a point of the group.
+    assert_eq!(Point::deserialize(&[0xff; 32]), None);
+}
+
+#[test]
+fn test_ipa_proof() {
+    let a: Vec<Uint> = (1u32..=8).map(|i| Uint::from(i * i)).collect();
+    let commitment = commit(&a);
+    let z = Uint::from(1000u32);
+
+    let (y, proof) = prove(&mut Transcript::new("test"), &commitment, &a, &z);
+    assert!(verify(&mut Transcript::new("test"), &commitment, 8, &z, &y, &proof));
+    assert!(!verify(&mut Transcript::new("test"), &commitment, 8, &z, &(&y + 1u8), &proof));
+    assert!(!verify(&mut Transcript::new("other"), &commitment, 8, &z, &y, &proof));
+
+    // Inside the domain, the value is the committed one.
+    let (y, proof) = prove(&mut Transcript::new("test"), &commitment, &a, &Uint::from(3u8));
+    assert_eq!(y, Uint::from(16u8));
+    assert!(verify(&mut Transcript::new("test"), &commitment, 8, &Uint::from(3u8), &y, &proof));
+}
+
+#[test]
+fn test_tree_keys() {
+    // Computed independently from the EIP-6800 definitions.
+    let header = hex!("2dd8a329b5969554bb398961ae1df4024d20882eeb20d7e03eba6fbcecbe2100");
+    assert_eq!(get_tree_key_for_basic_data(&ADDRESS), header);
+    assert_eq!(get_tree_key(&ADDRESS, &Uint::from(0u8), 0), header);
+
+    // The first storage slots share the account's stem.
+    let mut slot = header;
+    slot[31] = 64 + 5;
+    assert_eq!(get_tree_key_for_storage_slot(&ADDRESS, &U256::from(5u8)), slot);
+    let main = get_tree_key_for_storage_slot(&ADDRESS, &U256::from(64u8));
+    assert_ne!(main[..31], header[..31]);
+    assert_eq!(main[31], 64);
+
+    // Chunk 133 is at position 261, suffix 5 of tree index 1.
+    assert_eq!(
+        get_tree_key_for_code_chunk(&ADDRESS, 133),
+        hex!("58bd76e16463c609149185ce2d7be51ad84733f0b6ff5edbdae44ac1e233e805")
+    );
+}
+
+#[test]
+fn test_chunkify_code() {
+    // PUSH1 0x01, then a PUSH32 whose data runs into the second chunk.
+    let mut code = vec![0x60, 0x01];
+    code.extend([0x7f; 1]);
+    code.extend([0xaa; 32]);
+    let chunks = chunkify_code(&code);
+    assert_eq!(chunks.len(), 2);
+    assert_eq!(chunks[0][0], 0);
+    assert_eq!(chunks[0][1..4], [0x60, 0x01, 0x7f]);
+    // 28 bytes of the push data are in the first chunk, 4 in the second.
+    assert_eq!(chunks[1][0], 4);
+    assert_eq!(chunks[1][1..5], [0xaa; 4]);
+    assert_eq!(chunks[1][5..], [0; 27]);
+
+    assert!(chunkify_code(&[]).is_empty());
+}
+
+#[test]
+fn test_tree_root() {
+    assert_eq!(VerkleTree::new().root(), [0; 32]);
+
+    let basic = get_tree_key_for_basic_data(&ADDRESS);
+    let chunk = get_tree_key_for_code_chunk(&ADDRESS, 133);
+    let mut value = [0; 32];
+    value[31] = 7;
+
+    let mut tree = VerkleTree::new();
+    tree.insert(basic, value);
+    tree.insert(chunk, [0x11; 32]);
+    assert_eq!(tree.get(&basic), Some(&value));
+    assert_eq!(tree.len(), 2);
+    assert_eq!(
+        tree.root(),
+        hex!("6cfb81c652c3a17f61d9c2e88d1c6a6aa83354274e6fce2c0b95ed0f764f5124")
+    );
+
+    let mut reversed = VerkleTree::new();
+    reversed.insert(chunk, [0x11; 32]);
+    reversed.insert(basic, value);
+    assert_eq!(reversed.root(), tree.root());
+}
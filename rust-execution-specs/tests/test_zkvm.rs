@@ -0,0 +1,107 @@
+use execution_specs_rs::{
+    ethereum::{
+        base_types::{Uint, U256},
+        exceptions::EthereumException,
+        frontier::{
+            fork::{state_transition, BlockChain},
+            fork_types::{Block, Transaction},
+            state::State,
+        },
+        genesis::{add_genesis_block, GenesisConfiguration},
+        zkvm::{execute_block, prepare_block},
+    },
+    ethereum_spec_tools::{
+        block_builder::{build_block, BlockAttributes},
+        forks::HardFork,
+        test_fixtures::{initial_balances, TestAccount, ALICE, BOB},
+    },
+};
+
+fn transfer(account: &TestAccount, nonce: u8) -> Transaction {
+    let unsigned = Transaction {
+        nonce: U256::from(nonce),
+        gas_price: U256::from(1u8),
+        gas: U256::from(21000u32),
+        to: Some([0xcc; 20]),
+        value: U256::from(1u8),
+        data: vec![].into(),
+        v: U256::default(),
+        r: U256::default(),
+        s: U256::default(),
+    };
+    account.sign(&unsigned, None)
+}
+
+/// Block `n` of the chain, in which Alice and Bob each send a transfer.
+fn next_block(chain: &BlockChain, n: u8) -> Block {
+    let attributes = BlockAttributes {
+        coinbase: [0xbb; 20],
+        timestamp: U256::from(15 * n),
+        gas_limit: Uint::from(3_141_592u32),
+        extra_data: vec![].into(),
+    };
+    build_block(chain, &[transfer(&ALICE, n - 1), transfer(&BOB, n - 1)], &attributes)
+        .unwrap()
+        .block
+}
+
+/// A chain of two blocks, and a third block on it.
+fn chain() -> (BlockChain, Block) {
+    let mut chain = BlockChain {
+        blocks: vec![],
+        state: State::default(),
+        chain_id: 1,
+    };
+    let genesis = GenesisConfiguration {
+        chain_id: 1,
+        difficulty: Uint::from(0x20000u32),
+        gas_limit: Uint::from(3_141_592u32),
+        initial_balances: initial_balances(&[ALICE, BOB]),
+        ..Default::default()
+    };
+    add_genesis_block(HardFork::Frontier, &mut chain, genesis).unwrap();
+
+    let first = next_block(&chain, 1);
+    state_transition(&mut chain, first).unwrap();
+    let second = next_block(&chain, 2);
+    (chain, second)
+}
+
+#[test]
+fn test_execute_block() {
+    let (chain, block) = chain();
+    let witness = prepare_block(&chain, &block).unwrap();
+    assert_eq!(witness.ancestors.len(), 2);
+    assert!(witness
+        .ancestors
+        .iter()
+        .all(|ancestor| ancestor.transactions.is_empty()));
+    assert_eq!(execute_block(&witness, &block).unwrap(), block.header.state_root);
+}
+
+#[test]
+fn test_execute_invalid_block() {
+    let (chain, block) = chain();
+    let witness = prepare_block(&chain, &block).unwrap();
+
+    let mut tampered = witness.clone();
+    tampered.ancestors[0].header.extra_data = b"tampered".to_vec().into();
+    assert!(matches!(
+        execute_block(&tampered, &block),
+        Err(EthereumException::ValueError)
+    ));
+
+    let mut invalid = block.clone();
+    invalid.header.state_root = [0; 32];
+    assert!(matches!(
+        execute_block(&witness, &invalid),
+        Err(EthereumException::InvalidBlock)
+    ));
+
+    let mut missing = witness.clone();
+    missing.state.accounts.pop();
+    assert!(matches!(
+        execute_block(&missing, &block),
+        Err(EthereumException::MissingWitness(_))
+    ));
+}